@@ -1,7 +1,7 @@
 use anyhow::Result;
 use chrono;
 use lst_cli::config::{get_config, UiConfig};
-use lst_cli::models::{fuzzy_find, is_valid_anchor, ItemStatus, List, ListItem};
+use lst_cli::models::{fuzzy_find, is_valid_anchor, split_due_date, ItemStatus, List, ListItem};
 use lst_cli::storage::{
     list_lists, list_notes,
     markdown::{self, load_list},
@@ -63,27 +63,25 @@ fn add_item(list: String, text: String, category: Option<String>) -> Result<List
     for item in text.split(',').map(|s| s.trim()) {
         if !item.is_empty() {
             // Check for ##category inline syntax
-            let (parsed_category, parsed_text) = parse_item_input(item);
+            let (parsed_category, parsed_text, due) = parse_item_input(item);
             let final_category = parsed_category.or(category.as_deref());
 
-            markdown::add_item_to_category(&list, parsed_text, final_category)
+            markdown::add_item_to_category(&list, parsed_text, final_category, due)
                 .map_err(|e| e.to_string())?;
         }
     }
     load_list(&list).map_err(|e| e.to_string())
 }
 
-fn parse_item_input(input: &str) -> (Option<&str>, &str) {
-    if input.starts_with("##") {
-        if let Some(space_index) = input.find(' ') {
-            if space_index > 2 {
-                let category = &input[2..space_index];
-                let text = &input[space_index + 1..];
-                return (Some(category), text);
-            }
+fn parse_item_input(input: &str) -> (Option<&str>, &str, Option<chrono::NaiveDate>) {
+    let (category, text) = match input.find(' ') {
+        Some(space_index) if input.starts_with("##") && space_index > 2 => {
+            (Some(&input[2..space_index]), &input[space_index + 1..])
         }
-    }
-    (None, input)
+        _ => (None, input),
+    };
+    let (text, due) = split_due_date(text);
+    (category, text, due)
 }
 
 fn find_item_index(list: &List, target: &str) -> Option<usize> {
@@ -171,7 +169,7 @@ fn get_note(name: String) -> Result<Note, String> {
 #[tauri::command]
 #[specta::specta]
 fn create_note_cmd(title: String) -> Result<Note, String> {
-    let path = create_note(&title).map_err(|e| e.to_string())?;
+    let path = create_note(&title, false).map_err(|e| e.to_string())?;
     let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
 
     let (parsed_title, created, content_without_frontmatter) =
@@ -374,21 +372,10 @@ fn get_categories(list_name: String) -> Result<Vec<String>, String> {
 #[specta::specta]
 fn rename_category(list_name: String, old_name: String, new_name: String) -> Result<List, String> {
     let mut list = load_list(&list_name).map_err(|e| e.to_string())?;
-
-    // Check if new name already exists
-    if list.categories.iter().any(|c| c.name == new_name) {
-        return Err(format!("Category '{}' already exists", new_name));
-    }
-
-    // Find and rename category
-    if let Some(category) = list.categories.iter_mut().find(|c| c.name == old_name) {
-        category.name = new_name;
-        list.metadata.updated = chrono::Utc::now();
-        markdown::save_list_with_path(&list, &list_name).map_err(|e| e.to_string())?;
-        Ok(list)
-    } else {
-        Err(format!("Category '{}' not found", old_name))
-    }
+    markdown::rename_category_in_list(&mut list, &old_name, &new_name, false, false)
+        .map_err(|e| e.to_string())?;
+    markdown::save_list_with_path(&list, &list_name).map_err(|e| e.to_string())?;
+    Ok(list)
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -451,6 +438,12 @@ pub fn run() {
 
             Ok(())
         })
+        .on_window_event(|window, event| {
+            // Follow the OS switching between light and dark appearance
+            if let tauri::WindowEvent::ThemeChanged(_) = event {
+                theme::broadcast_theme(window.app_handle()).ok();
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             get_lists,
             get_notes,
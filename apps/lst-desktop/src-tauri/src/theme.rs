@@ -1,6 +1,7 @@
+use lst_core::theme::{Theme, ThemeVariant};
 use serde::{Deserialize, Serialize};
 use specta::Type;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager, WebviewWindow};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct ThemeData {
@@ -10,20 +11,43 @@ pub struct ThemeData {
     pub variant: Option<String>,
 }
 
-/// Get the current theme and generate CSS variables
-#[tauri::command]
-#[specta::specta]
-pub fn get_current_theme() -> Result<ThemeData, String> {
+impl From<&Theme> for ThemeData {
+    fn from(theme: &Theme) -> Self {
+        Self {
+            css_variables: theme.generate_css_variables(),
+            scheme: theme.scheme.clone(),
+            name: theme.name.clone(),
+            variant: theme.variant.as_ref().map(|v| format!("{:?}", v)),
+        }
+    }
+}
+
+/// The OS light/dark appearance as reported for `window` (macOS and
+/// Windows; other platforms report none)
+fn system_appearance(window: &WebviewWindow) -> Option<ThemeVariant> {
+    match window.theme().ok()? {
+        tauri::Theme::Light => Some(ThemeVariant::Light),
+        tauri::Theme::Dark => Some(ThemeVariant::Dark),
+        _ => None,
+    }
+}
+
+/// The current theme, switched to its light or dark variant to match
+/// `appearance` when it has one
+fn current_theme(appearance: Option<ThemeVariant>) -> Result<ThemeData, String> {
     // Reload config from disk to get latest theme changes from CLI
     let config = lst_cli::config::Config::load().map_err(|e| e.to_string())?;
-    let theme = config.get_theme().map_err(|e| e.to_string())?;
+    let theme = config
+        .get_theme_for(appearance)
+        .map_err(|e| e.to_string())?;
+    Ok(ThemeData::from(&theme))
+}
 
-    Ok(ThemeData {
-        css_variables: theme.generate_css_variables(),
-        scheme: theme.scheme.clone(),
-        name: theme.name.clone(),
-        variant: theme.variant.as_ref().map(|v| format!("{:?}", v)),
-    })
+/// Get the current theme and generate CSS variables
+#[tauri::command]
+#[specta::specta]
+pub fn get_current_theme(window: WebviewWindow) -> Result<ThemeData, String> {
+    current_theme(system_appearance(&window))
 }
 
 /// Apply a theme by name
@@ -38,12 +62,7 @@ pub fn apply_theme(theme_name: String) -> Result<ThemeData, String> {
     config.set_theme(theme.clone());
     config.save().map_err(|e| e.to_string())?;
 
-    Ok(ThemeData {
-        css_variables: theme.generate_css_variables(),
-        scheme: theme.scheme.clone(),
-        name: theme.name.clone(),
-        variant: theme.variant.as_ref().map(|v| format!("{:?}", v)),
-    })
+    Ok(ThemeData::from(&theme))
 }
 
 /// List all available themes
@@ -56,9 +75,13 @@ pub fn list_themes() -> Result<Vec<String>, String> {
     Ok(loader.list_themes())
 }
 
-/// Broadcast theme update to frontend
+/// Broadcast theme update to frontend, resolving light/dark variants against
+/// the main window's appearance. Called again whenever that appearance changes
 pub fn broadcast_theme(app: &AppHandle) -> tauri::Result<()> {
-    match get_current_theme() {
+    let appearance = app
+        .get_webview_window("main")
+        .and_then(|window| system_appearance(&window));
+    match current_theme(appearance) {
         Ok(theme_data) => {
             app.emit("theme-update", theme_data)?;
         }
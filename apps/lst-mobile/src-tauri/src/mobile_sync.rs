@@ -493,7 +493,11 @@ impl MobileSyncManager {
 
         // Request document list to discover new documents
         println!("📱 Requesting document list from server...");
-        let request_msg = lst_proto::ClientMessage::RequestDocumentList;
+        let request_msg = lst_proto::ClientMessage::RequestDocumentList {
+            after: None,
+            after_updated_at: None,
+            limit: None,
+        };
         if let Err(e) = write
             .send(Message::Text(serde_json::to_string(&request_msg)?))
             .await
@@ -591,19 +595,17 @@ impl MobileSyncManager {
                                                 let relative_path = canonical.relative_path.clone();
 
                                                 // Encrypt relative path before sending
-                                                let encrypted_filename = crypto::encrypt(
-                                                    relative_path.as_bytes(),
+                                                let encoded_filename = crypto::encrypt_filename(
+                                                    &relative_path,
                                                     &self.encryption_key,
                                                 )?;
-                                                let encoded_filename =
-                                                    base64::engine::general_purpose::STANDARD
-                                                        .encode(&encrypted_filename);
 
                                                 println!("📱 Mobile sync: Encrypting relative path: {} for doc {}", relative_path, doc_id);
 
                                                 let msg = lst_proto::ClientMessage::PushSnapshot {
                                                     doc_id: uuid,
                                                     filename: encoded_filename,
+                                                    hash: Some(lst_proto::snapshot_hash(&state)),
                                                     snapshot: state,
                                                 };
                                                 if let Err(e) = write
@@ -628,7 +630,18 @@ impl MobileSyncManager {
                                 doc_id,
                                 filename,
                                 snapshot,
+                                hash,
                             } => {
+                                // Never apply a snapshot damaged in transfer; the
+                                // next sync requests it again
+                                if !lst_proto::verify_snapshot(&snapshot, hash.as_deref()) {
+                                    println!(
+                                        "📱 Mobile sync: Snapshot for {} failed its checksum, skipping",
+                                        doc_id
+                                    );
+                                    continue;
+                                }
+
                                 // Decrypt filename
                                 let decrypted_filename = if let Ok(encrypted_bytes) =
                                     base64::engine::general_purpose::STANDARD.decode(&filename)
@@ -75,7 +75,11 @@ async fn listen_once(
         .context("Failed to connect to sync server for mobile trigger")?;
     let (mut write, mut read) = ws.split();
 
-    let request_list = lst_proto::ClientMessage::RequestDocumentList;
+    let request_list = lst_proto::ClientMessage::RequestDocumentList {
+        after: None,
+        after_updated_at: None,
+        limit: None,
+    };
     write
         .send(Message::Text(
             serde_json::to_string(&request_list)
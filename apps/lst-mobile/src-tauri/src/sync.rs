@@ -405,7 +405,11 @@ impl SyncManager {
 
         // Now request document list to discover new documents
         println!("📊 Requesting document list from server...");
-        let request_msg = lst_proto::ClientMessage::RequestDocumentList;
+        let request_msg = lst_proto::ClientMessage::RequestDocumentList {
+            after: None,
+            after_updated_at: None,
+            limit: None,
+        };
         if let Err(e) = write.send(Message::Text(serde_json::to_string(&request_msg)?)).await {
             return Err(anyhow::anyhow!("Failed to request document list: {}", e));
         }
@@ -1,8 +1,7 @@
 use anyhow::{Context, Result};
 use automerge::{Automerge, ObjType, ReadDoc, Value};
 use lst_core::sync::{
-    extract_automerge_content, path_from_relative, path_from_server_filename, write_document,
-    CanonicalDocPath, DocumentKind,
+    extract_automerge_content, path_from_relative, write_document, CanonicalDocPath, DocumentKind,
 };
 use rusqlite::{params, Connection};
 use std::path::Path;
@@ -197,7 +196,7 @@ impl LocalDb {
         let doc = Automerge::load(snapshot)?;
         let doc_kind = Self::detect_kind_from_doc(&doc);
         let content = extract_automerge_content(&doc, doc_kind)?;
-        let canonical = path_from_server_filename(relative_path)?;
+        let canonical = path_from_relative(relative_path)?;
 
         write_document(&canonical, &content)?;
         println!(
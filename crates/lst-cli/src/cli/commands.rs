@@ -1,47 +1,93 @@
 use anyhow::{bail, Context, Result};
 use colored::{ColoredString, Colorize};
 use fuzzy_matcher::FuzzyMatcher;
+use rayon::prelude::*;
 use serde_json;
 use serde_yaml;
 use std::io::{self, BufRead, IsTerminal};
 
-use crate::cli::{DlCmd, SyncCommands};
-use crate::config::{get_config, Config};
+use crate::cli::{DlCmd, DlTemplateCmd, SyncCommands};
+use crate::config::{get_config, ColorMode, Config, UiConfig};
 use crate::storage;
 use crate::{models::ItemStatus, storage::notes::delete_note};
-use chrono::{Local, Utc};
+use chrono::{DateTime, Local, NaiveDate, Utc};
 use lst_core::config::State;
-use lst_core::models::Category;
+use lst_core::models::{split_due_date, Category, List, ListItem, NoteFrontmatter};
+use lst_core::theme::{
+    hex_to_rgb, is_valid_color, Theme, ThemeLoader, ThemeOverrides, ThemeVariant,
+};
 use std::path::Path;
 use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
 
 /// Create a new list: initializes file and opens in editor
 pub fn new_list(title: &str) -> Result<()> {
     let key = title.trim_end_matches(".md");
     let path = storage::markdown::create_list(key).context("Failed to create note")?;
-    open_editor(&path)
+    open_editor(&path, None)
 }
 
 /// Handle the 'ls' command to list all lists
-pub fn list_lists(json: bool) -> Result<()> {
-    let lists = storage::list_lists()?;
+pub fn list_lists(tag: Option<&str>, sort: Option<&str>, tree: bool, json: bool) -> Result<()> {
+    let mut lists = match tag {
+        Some(tag) => storage::list_lists_with_tag(tag)?,
+        None => storage::list_lists()?,
+    };
+    lists.sort();
+    match sort {
+        None => {}
+        Some("updated") => storage::sort_lists_by_updated(&mut lists)?,
+        Some(sort) => bail!("--sort {} orders items; pass a list name", sort),
+    }
+    let pinned = storage::pinned_first(&mut lists)?;
 
     if json {
+        let lists: Vec<serde_json::Value> = lists
+            .iter()
+            .map(|name| serde_json::json!({ "name": name, "pinned": pinned.contains(name) }))
+            .collect();
         println!("{}", serde_json::to_string(&lists)?);
         return Ok(());
     }
 
     if lists.is_empty() {
-        println!("No lists found. Create one with 'lst new <list>'");
+        match tag {
+            Some(tag) => println!("No lists tagged '{}'", tag),
+            None => println!("No lists found. Create one with 'lst new <list>'"),
+        }
+        return Ok(());
+    }
+
+    let terminal = std::io::stdout().is_terminal();
+    if tree {
+        if terminal {
+            println!("Available lists:");
+        }
+        let label = |name: &str, path: &str| {
+            if terminal && pinned.contains(path) {
+                format!("{} {}", name, "(pinned)".dimmed())
+            } else {
+                name.to_string()
+            }
+        };
+        let indent = if terminal { "  " } else { "" };
+        for line in render_tree(&lists, &label) {
+            println!("{}{}", indent, line);
+        }
         return Ok(());
     }
 
     // Check if output is going to a terminal or is being piped
-    if std::io::stdout().is_terminal() {
+    if terminal {
         // Human-readable format with header and indentation
         println!("Available lists:");
         for list in lists {
-            println!("  {}", list);
+            if pinned.contains(&list) {
+                println!("  {} {}", list, "(pinned)".dimmed());
+            } else {
+                println!("  {}", list);
+            }
         }
     } else {
         // Machine-readable format for pipes (no header, no indentation)
@@ -53,36 +99,126 @@ pub fn list_lists(json: bool) -> Result<()> {
     Ok(())
 }
 /// Handle daily list commands: create/display/add/done/undone for YYYYMMDD_daily_list
+///
+/// Item commands given a `--date` work on that day's list, which has to exist
 pub async fn daily_list(cmd: Option<&DlCmd>, json: bool) -> Result<()> {
-    let date = Local::now().format("%Y%m%d").to_string();
-    let list_name = format!("daily_lists/{}_daily_list", date);
-    // No subcommand: ensure exists then display
+    let date = match cmd {
+        Some(
+            DlCmd::Add { date, .. }
+            | DlCmd::Done { date, .. }
+            | DlCmd::Undone { date, .. }
+            | DlCmd::Remove { date, .. },
+        ) => date.as_deref(),
+        _ => None,
+    };
+    let list_name = daily_list_name(&storage::get_lists_dir()?, date)?;
+    // Create today's list from the template if it doesn't exist yet
+    if date.is_none()
+        && !matches!(cmd, Some(DlCmd::Template(_)))
+        && storage::markdown::load_list(&list_name).is_err()
+    {
+        storage::markdown::create_daily_list(&list_name)?;
+    }
     match cmd {
-        Some(DlCmd::Add { item }) => {
+        Some(DlCmd::Add { item, .. }) => {
             add_item(&list_name, item, None, json).await?;
         }
-        Some(DlCmd::Done { item }) => {
+        Some(DlCmd::Done { item, .. }) => {
             mark_done(&list_name, item, json).await?;
         }
-        Some(DlCmd::Undone { item }) => {
+        Some(DlCmd::Undone { item, .. }) => {
             mark_undone(&list_name, item, json).await?;
         }
         Some(DlCmd::List) => {
             display_daily_list(json)?;
         }
-        Some(DlCmd::Remove { item }) => {
+        Some(DlCmd::Remove { item, .. }) => {
             remove_item(&list_name, item, json).await?;
         }
+        Some(DlCmd::Rollover { clear_source }) => {
+            daily_list_rollover(&list_name, *clear_source, json).await?;
+        }
+        Some(DlCmd::Template(cmd)) => {
+            daily_list_template(cmd, json).await?;
+        }
         None => {
-            // create if missing
-            if storage::markdown::load_list(&list_name).is_err() {
-                storage::markdown::create_list(&list_name)?;
+            display_list(&list_name, json, false, None, None, false)?;
+        }
+    }
+    Ok(())
+}
+
+/// Name of the daily list for `date` (YYYYMMDD), which must exist in
+/// `lists_dir`, or of today's daily list without a date
+fn daily_list_name(lists_dir: &Path, date: Option<&str>) -> Result<String> {
+    let Some(date) = date else {
+        let today = Local::now().format("%Y%m%d");
+        return Ok(format!("daily_lists/{}_daily_list", today));
+    };
+    let valid = date.len() == 8
+        && date.bytes().all(|b| b.is_ascii_digit())
+        && NaiveDate::parse_from_str(date, "%Y%m%d").is_ok();
+    if !valid {
+        bail!("Invalid date '{}': expected YYYYMMDD, e.g. 20240101", date);
+    }
+    let name = format!("daily_lists/{}_daily_list", date);
+    if !lists_dir.join(format!("{}.md", name)).exists() {
+        bail!("No daily list for {}", date);
+    }
+    Ok(name)
+}
+
+/// Handle `dl rollover`: carry the previous daily list's open items to today
+async fn daily_list_rollover(list_name: &str, clear_source: bool, json: bool) -> Result<()> {
+    let rollover = storage::markdown::rollover_daily_list(list_name, clear_source)?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({ "source": rollover.source, "items": rollover.items })
+        );
+    } else {
+        match &rollover.source {
+            None => println!("No earlier daily list to roll over from"),
+            Some(_) if rollover.items.is_empty() => println!("Nothing to roll over"),
+            Some(source) => {
+                println!(
+                    "Rolled over {} item(s) from {}:",
+                    rollover.items.len(),
+                    source.cyan()
+                );
+                for item in &rollover.items {
+                    println!("  {}", item.text);
+                }
             }
-            display_list(&list_name, json, false)?;
         }
     }
+
+    // Notify desktop app that the list was updated
+    #[cfg(feature = "gui")]
+    {
+        let _ = notify_list_updated(list_name).await;
+    }
+
     Ok(())
 }
+
+/// Handle `dl template` commands on the daily list template
+async fn daily_list_template(cmd: &DlTemplateCmd, json: bool) -> Result<()> {
+    let name = storage::markdown::DAILY_TEMPLATE_LIST;
+    if storage::markdown::load_list(name).is_err() {
+        storage::markdown::create_list(name)?;
+    }
+    match cmd {
+        DlTemplateCmd::Show => display_list(name, json, false, None, None, false),
+        DlTemplateCmd::Edit => {
+            let path = storage::get_lists_dir()?.join(format!("{}.md", name));
+            open_editor(&path, None)
+        }
+        DlTemplateCmd::Add { item } => add_item(name, item, None, json).await,
+        DlTemplateCmd::Remove { item } => remove_item(name, item, json).await,
+    }
+}
 /// Handle daily note: create or open YYYYMMDD_daily_note.md
 pub fn daily_note(_json: bool) -> Result<()> {
     let date = Local::now().format("%Y%m%d").to_string();
@@ -103,15 +239,28 @@ pub fn daily_note(_json: bool) -> Result<()> {
         let now = Utc::now().to_rfc3339();
         let title = format!("{}_daily_note", date);
         let content = format!("---\ntitle: \"{}\"\ncreated: {}\n---\n\n", title, now);
-        std::fs::write(&path, content)
+        storage::atomic_write(&path, content)
             .context(format!("Failed to create daily note: {}", path.display()))?;
     }
     // open in editor
-    open_editor(&path)
+    open_editor(&path, None)
 }
 
-/// Handle the 'ls' command to list all lists
-pub fn list_notes(json: bool) -> Result<()> {
+/// Open the scratch note in the editor after appending a fresh timestamped section
+pub fn scratch_note(json: bool) -> Result<()> {
+    let path = storage::notes::get_scratch_note_path()?;
+    storage::notes::append_scratch_section(&path)?;
+
+    if json {
+        println!("{}", serde_json::json!({ "path": path }));
+        return Ok(());
+    }
+
+    open_editor(&path, None)
+}
+
+/// Handle the 'note ls' command to list all notes
+pub fn list_notes(tree: bool, json: bool) -> Result<()> {
     let notes = storage::list_notes()?;
 
     if json {
@@ -124,8 +273,20 @@ pub fn list_notes(json: bool) -> Result<()> {
         return Ok(());
     }
 
+    let terminal = std::io::stdout().is_terminal();
+    if tree {
+        if terminal {
+            println!("Available notes:");
+        }
+        let indent = if terminal { "  " } else { "" };
+        for line in render_tree(&notes, &|name, _| name.to_string()) {
+            println!("{}{}", indent, line);
+        }
+        return Ok(());
+    }
+
     // Check if output is going to a terminal or is being piped
-    if std::io::stdout().is_terminal() {
+    if terminal {
         // Human-readable format with header and indentation
         println!("Available notes:");
         for note in notes {
@@ -141,12 +302,82 @@ pub fn list_notes(json: bool) -> Result<()> {
     Ok(())
 }
 
+/// One level of a [`render_tree`]: documents and subdirectories in the
+/// order they were first seen
+#[derive(Default)]
+struct PathTree {
+    entries: Vec<PathTreeEntry>,
+}
+
+enum PathTreeEntry {
+    /// Document name and its full relative path
+    Leaf(String, String),
+    Dir(String, PathTree),
+}
+
+impl PathTree {
+    fn insert(&mut self, segments: &[&str], path: &str) {
+        match segments {
+            [] => {}
+            [name] => self
+                .entries
+                .push(PathTreeEntry::Leaf(name.to_string(), path.to_string())),
+            [dir, rest @ ..] => {
+                let existing = self.entries.iter_mut().find_map(|entry| match entry {
+                    PathTreeEntry::Dir(name, tree) if name == dir => Some(tree),
+                    _ => None,
+                });
+                match existing {
+                    Some(tree) => tree.insert(rest, path),
+                    None => {
+                        let mut tree = PathTree::default();
+                        tree.insert(rest, path);
+                        self.entries.push(PathTreeEntry::Dir(dir.to_string(), tree));
+                    }
+                }
+            }
+        }
+    }
+
+    fn render(&self, depth: usize, label: &dyn Fn(&str, &str) -> String, out: &mut Vec<String>) {
+        let indent = "  ".repeat(depth);
+        for entry in &self.entries {
+            match entry {
+                PathTreeEntry::Leaf(name, path) => {
+                    out.push(format!("{}{}", indent, label(name, path)))
+                }
+                PathTreeEntry::Dir(name, tree) => {
+                    out.push(format!("{}{}/", indent, name));
+                    tree.render(depth + 1, label, out);
+                }
+            }
+        }
+    }
+}
+
+/// Lines showing relative document `paths` (e.g. `groceries/pharmacy`) as an
+/// indented tree grouped by directory, keeping the order of `paths` within
+/// each directory. `label` turns a document's name and path into its line text
+fn render_tree(paths: &[String], label: &dyn Fn(&str, &str) -> String) -> Vec<String> {
+    let mut tree = PathTree::default();
+    for path in paths {
+        let segments: Vec<&str> = path.split(['/', '\\']).collect();
+        tree.insert(&segments, path);
+    }
+    let mut lines = Vec::new();
+    tree.render(0, label, &mut lines);
+    lines
+}
+
 /// Create a new note: initializes file and opens in editor
-pub async fn note_new(title: &str) -> Result<()> {
+pub async fn note_new(title: &str, overwrite: bool, template: Option<&str>) -> Result<()> {
     // Resolve note name (handle special cases like 'dn')
     let key = resolve_note(title).unwrap_or_else(|_| title.trim_end_matches(".md").to_string());
-    // Create the note file (with frontmatter)
-    let path = storage::notes::create_note(&key).context("Failed to create note")?;
+    // Create the note file (with frontmatter); an existing title gets a numeric suffix
+    let path = match template {
+        Some(template) => storage::notes::create_note_from_template(&key, template, overwrite)?,
+        None => storage::notes::create_note(&key, overwrite).context("Failed to create note")?,
+    };
 
     // Notify desktop app that a note was updated
     #[cfg(feature = "gui")]
@@ -155,16 +386,45 @@ pub async fn note_new(title: &str) -> Result<()> {
     }
 
     // Open in editor
-    open_editor(&path)
+    open_editor(&path, None)
+}
+
+/// List the templates available to `lst note new --template`
+pub fn note_template_list(json: bool) -> Result<()> {
+    let templates = storage::notes::list_templates()?;
+
+    if json {
+        println!("{}", serde_json::to_string(&templates)?);
+        return Ok(());
+    }
+
+    if templates.is_empty() {
+        println!(
+            "No templates found. Add markdown files to {}",
+            storage::get_note_templates_dir()?.display()
+        );
+        return Ok(());
+    }
+    for template in templates {
+        println!("{}", template);
+    }
+    Ok(())
 }
 
-/// Open an existing note in the editor
-pub fn note_open(title: &str) -> Result<()> {
+/// Open an existing note in the editor (or `editor`), or just print its path
+pub fn note_open(title: &str, print_path: bool, editor: Option<&str>) -> Result<()> {
     // Resolve note (allow fuzzy and omit .md)
     let key = title.trim_end_matches(".md");
     let note = resolve_note(key)?;
     let path = storage::notes::load_note(&note).context("Failed to load note")?;
-    open_editor(&path)
+    let env_editor = std::env::var("EDITOR").ok();
+    open_or_print_path(
+        &path,
+        print_path,
+        editor,
+        env_editor.as_deref(),
+        &mut io::stdout(),
+    )
 }
 /// Append text to an existing note (or create one), then open in editor
 pub async fn note_add(title: &str, text: &str) -> Result<()> {
@@ -172,7 +432,8 @@ pub async fn note_add(title: &str, text: &str) -> Result<()> {
     let key = title.trim_end_matches(".md");
     let note = resolve_note(key).unwrap_or_else(|_| key.to_string());
     // Append to note, creating if missing
-    let path = storage::notes::append_to_note(&note, text).context("Failed to append to note")?;
+    let path =
+        storage::notes::append_to_note(&note, text, None).context("Failed to append to note")?;
 
     // Notify desktop app that a note was updated
     #[cfg(feature = "gui")]
@@ -180,9 +441,40 @@ pub async fn note_add(title: &str, text: &str) -> Result<()> {
         let _ = notify_note_updated(&note).await;
     }
 
-    open_editor(&path)
+    open_editor(&path, None)
 }
 
+/// Handle `note pipe`: append stdin to a note (created if missing), under
+/// `heading` if given
+pub async fn note_pipe(title: &str, heading: Option<&str>, json: bool) -> Result<()> {
+    let mut text = String::new();
+    io::Read::read_to_string(&mut io::stdin(), &mut text).context("Failed to read stdin")?;
+    if text.trim().is_empty() {
+        bail!("Nothing to append: stdin was empty");
+    }
+
+    let key = title.trim_end_matches(".md");
+    let note = resolve_note(key).unwrap_or_else(|_| key.to_string());
+    let path = storage::notes::append_to_note(&note, &text, heading)
+        .context("Failed to append to note")?;
+
+    #[cfg(feature = "gui")]
+    {
+        let _ = notify_note_updated(&note).await;
+    }
+
+    let lines = text.trim_end_matches('\n').lines().count();
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({ "note": note, "path": path, "lines": lines })
+        );
+    } else {
+        println!("Appended {} lines to {}", lines, note.cyan());
+    }
+    Ok(())
+}
+    
 /// Delete a note
 pub async fn note_delete(title: &str, force: bool) -> Result<()> {
     // Determine the note file path
@@ -206,8 +498,17 @@ pub async fn note_delete(title: &str, force: bool) -> Result<()> {
             return Ok(());
         }
     }
-    
+
+    let path = storage::notes::get_note_path(&note)?;
     let result = delete_note(&note);
+    if result.is_ok() {
+        forget_synced_document(&path)?;
+        if config.ui.use_trash {
+            println!("Moved note {} to the trash", note.cyan());
+        } else {
+            println!("Deleted note: {}", note.cyan());
+        }
+    }
 
     // Notify desktop app that a note was updated (deleted)
     #[cfg(feature = "gui")]
@@ -493,24 +794,8 @@ pub fn note_metadata(title: &str, json: bool) -> Result<()> {
     let content = std::fs::read_to_string(&path)
         .context(format!("Failed to read note: {}", path.display()))?;
 
-    let mut frontmatter = NoteFrontmatter::default();
-    let body: String;
-
-    if content.starts_with("---") {
-        let parts: Vec<&str> = content.splitn(3, "---").collect();
-        if parts.len() >= 3 {
-            if let Ok(fm) = serde_yaml::from_str::<NoteFrontmatter>(parts[1]) {
-                frontmatter = fm;
-            }
-            body = parts[2].trim_start_matches('\n').to_string();
-        } else {
-            body = content.clone();
-        }
-    } else {
-        body = content.clone();
-    }
-
-    let word_count = body.split_whitespace().count();
+    let (frontmatter, body) = split_note_frontmatter(&content);
+    let word_count = count_words(body);
     let line_count = body.lines().count();
 
     if json {
@@ -570,9 +855,171 @@ pub fn note_metadata(title: &str, json: bool) -> Result<()> {
     Ok(())
 }
 
-/// Spawn the user's editor (from $EDITOR or default 'vi') on the given path
-fn open_editor(path: &Path) -> Result<()> {
-    let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
+/// Split a note into its parsed frontmatter (default when missing or invalid)
+/// and its body
+fn split_note_frontmatter(content: &str) -> (NoteFrontmatter, &str) {
+    if content.starts_with("---") {
+        let parts: Vec<&str> = content.splitn(3, "---").collect();
+        if parts.len() >= 3 {
+            let frontmatter = serde_yaml::from_str(parts[1]).unwrap_or_default();
+            return (frontmatter, parts[2].trim_start_matches('\n'));
+        }
+    }
+    (NoteFrontmatter::default(), content)
+}
+
+/// Number of whitespace separated words in a note body
+fn count_words(body: &str) -> usize {
+    body.split_whitespace().count()
+}
+
+/// Item counts for one list in `lst stats`
+#[derive(Debug, serde::Serialize)]
+struct ListStats {
+    name: String,
+    done: usize,
+    total: usize,
+}
+
+/// Aggregate numbers reported by `lst stats`
+#[derive(Debug, serde::Serialize)]
+struct ContentStats {
+    lists: usize,
+    notes: usize,
+    items: usize,
+    done_items: usize,
+    note_words: usize,
+    per_list: Vec<ListStats>,
+    /// Lists and notes that could not be read, left out of the counts
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<String>,
+}
+
+/// Load every list and note in `storage` (concurrently) and count items and
+/// words. Files that fail to load are reported in `errors` instead of
+/// aborting the whole run
+fn collect_stats(storage: &dyn storage::backend::Storage) -> Result<ContentStats> {
+    let mut names = storage.list_names()?;
+    names.sort();
+    let lists: Vec<Result<ListStats, String>> = names
+        .into_par_iter()
+        .map(|name| {
+            let list = storage
+                .read_list(&name)
+                .map_err(|e| format!("Error reading list '{}': {}", name, e))?;
+            Ok(ListStats {
+                done: list
+                    .all_items()
+                    .filter(|item| item.status == ItemStatus::Done)
+                    .count(),
+                total: list.all_items().count(),
+                name,
+            })
+        })
+        .collect();
+
+    let mut note_names = storage.note_names()?;
+    note_names.sort();
+    let notes: Vec<Result<usize, String>> = note_names
+        .par_iter()
+        .map(|name| {
+            let content = storage
+                .read_note(name)
+                .map_err(|e| format!("Error reading note '{}': {}", name, e))?;
+            Ok(count_words(split_note_frontmatter(&content).1))
+        })
+        .collect();
+
+    let mut errors = Vec::new();
+    let mut per_list = Vec::new();
+    for list in lists {
+        match list {
+            Ok(list) => per_list.push(list),
+            Err(e) => errors.push(e),
+        }
+    }
+    let mut note_count = 0;
+    let mut note_words = 0;
+    for words in notes {
+        match words {
+            Ok(words) => {
+                note_count += 1;
+                note_words += words;
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+
+    Ok(ContentStats {
+        lists: per_list.len(),
+        notes: note_count,
+        items: per_list.iter().map(|l| l.total).sum(),
+        done_items: per_list.iter().map(|l| l.done).sum(),
+        note_words,
+        per_list,
+        errors,
+    })
+}
+
+/// Handle `lst stats`: summarize lists, items and notes
+pub fn stats(json: bool) -> Result<()> {
+    let stats = collect_stats(&storage::backend::FsStorage::from_config()?)?;
+
+    if json {
+        println!("{}", serde_json::to_string(&stats)?);
+        return Ok(());
+    }
+
+    let percent = |done: usize, total: usize| {
+        (done * 100)
+            .checked_div(total)
+            .map_or_else(|| "-".to_string(), |p| format!("{}%", p))
+    };
+    println!("Lists: {}", stats.lists.to_string().cyan());
+    println!(
+        "Items: {} ({} done, {})",
+        stats.items.to_string().cyan(),
+        stats.done_items,
+        percent(stats.done_items, stats.items)
+    );
+    println!("Notes: {}", stats.notes.to_string().cyan());
+    println!("Words in notes: {}", stats.note_words.to_string().cyan());
+
+    if !stats.per_list.is_empty() {
+        let width = stats
+            .per_list
+            .iter()
+            .map(|l| l.name.len())
+            .max()
+            .unwrap_or(0);
+        println!();
+        for list in &stats.per_list {
+            println!(
+                "  {:<width$}  {:>4}/{:<4} {}",
+                list.name,
+                list.done,
+                list.total,
+                percent(list.done, list.total).dimmed(),
+                width = width
+            );
+        }
+    }
+
+    if !stats.errors.is_empty() {
+        println!("\nErrors:");
+        for error in &stats.errors {
+            println!("  {}", error.red());
+        }
+    }
+    Ok(())
+}
+
+/// Spawn `editor` on the given path, defaulting to $EDITOR or 'vi'
+fn open_editor(path: &Path, editor: Option<&str>) -> Result<()> {
+    let editor = match editor {
+        Some(editor) => editor.to_string(),
+        None => std::env::var("EDITOR").unwrap_or_else(|_| "vi".to_string()),
+    };
     let status = Command::new(editor)
         .arg(path)
         .status()
@@ -582,6 +1029,24 @@ fn open_editor(path: &Path) -> Result<()> {
     }
     Ok(())
 }
+
+/// Write `path` to `out` when `print_path` is set (for scripts), otherwise
+/// open it in `editor`, falling back to `env_editor` (the value of $EDITOR)
+/// and then 'vi'
+fn open_or_print_path(
+    path: &Path,
+    print_path: bool,
+    editor: Option<&str>,
+    env_editor: Option<&str>,
+    out: &mut dyn io::Write,
+) -> Result<()> {
+    if print_path {
+        writeln!(out, "{}", path.display())?;
+        return Ok(());
+    }
+    open_editor(path, Some(editor.or(env_editor).unwrap_or("vi")))
+}
+
 /// Normalize a list identifier: strip .md and fuzzy-match existing, or allow new
 fn normalize_list(input: &str) -> Result<String> {
     let key = input.trim_end_matches(".md");
@@ -597,47 +1062,9 @@ fn normalize_list(input: &str) -> Result<String> {
         return Ok(key.to_string());
     }
 
-    // Otherwise try fuzzy matching
+    // Otherwise try fuzzy matching, allowing new list creation
     let entries = storage::list_lists_with_info()?;
-
-    // First try exact filename match
-    for entry in &entries {
-        if entry.name == key {
-            return Ok(entry.relative_path.clone());
-        }
-    }
-
-    // Then try fuzzy match by filename
-    let config = crate::config::Config::load()?;
-    let matcher = fuzzy_matcher::skim::SkimMatcherV2::default();
-
-    let mut fuzzy_matches: Vec<(&storage::FileEntry, i64)> = entries
-        .iter()
-        .filter_map(|entry| {
-            matcher
-                .fuzzy_match(&entry.name, key)
-                .filter(|&score| score >= config.fuzzy.threshold)
-                .map(|score| (entry, score))
-        })
-        .collect();
-
-    // Sort by score (highest first)
-    fuzzy_matches.sort_by(|a, b| b.1.cmp(&a.1));
-
-    match fuzzy_matches.len() {
-        0 => Ok(key.to_string()), // Allow new list creation
-        1 => Ok(fuzzy_matches[0].0.relative_path.clone()),
-        _ => {
-            // Show top matches with scores
-            let max_suggestions = config.fuzzy.max_suggestions as usize;
-            let match_names: Vec<String> = fuzzy_matches
-                .iter()
-                .take(max_suggestions)
-                .map(|(entry, score)| format!("{} (score: {})", entry.relative_path, score))
-                .collect();
-            bail!("Multiple lists match '{}': {}", key, match_names.join(", "));
-        }
-    }
+    Ok(resolve_entry("list", key, &entries)?.unwrap_or_else(|| key.to_string()))
 }
 
 /// Resolve a note identifier: strip .md and fuzzy-match to exactly one or error
@@ -657,49 +1084,8 @@ fn resolve_note(input: &str) -> Result<String> {
 
     // Otherwise try fuzzy matching
     let entries = storage::list_notes_with_info()?;
-
-    // First try exact filename match
-    for entry in &entries {
-        if entry.name == key {
-            return Ok(entry.relative_path.clone());
-        }
-    }
-
-    // Then try fuzzy match by filename
-    let config = crate::config::Config::load()?;
-    let matcher = fuzzy_matcher::skim::SkimMatcherV2::default();
-
-    let mut fuzzy_matches: Vec<(&storage::FileEntry, i64)> = entries
-        .iter()
-        .filter_map(|entry| {
-            matcher
-                .fuzzy_match(&entry.name, key)
-                .filter(|&score| score >= config.fuzzy.threshold)
-                .map(|score| (entry, score))
-        })
-        .collect();
-
-    // Sort by score (highest first)
-    fuzzy_matches.sort_by(|a, b| b.1.cmp(&a.1));
-
-    match fuzzy_matches.len() {
-        0 => bail!("No note matching '{}' found", input),
-        1 => Ok(fuzzy_matches[0].0.relative_path.clone()),
-        _ => {
-            // Show top matches with scores
-            let max_suggestions = config.fuzzy.max_suggestions as usize;
-            let match_names: Vec<String> = fuzzy_matches
-                .iter()
-                .take(max_suggestions)
-                .map(|(entry, score)| format!("{} (score: {})", entry.relative_path, score))
-                .collect();
-            bail!(
-                "Multiple notes match '{}': {}",
-                input,
-                match_names.join(", ")
-            );
-        }
-    }
+    resolve_entry("note", key, &entries)?
+        .with_context(|| format!("No note matching '{}' found", input))
 }
 
 /// Resolve a note identifier: strip .md and fuzzy-match to exactly one or error
@@ -719,25 +1105,30 @@ fn resolve_list(input: &str) -> Result<String> {
 
     // Otherwise try fuzzy matching
     let entries = storage::list_lists_with_info()?;
+    resolve_entry("list", key, &entries)?
+        .with_context(|| format!("No list matching '{}' found", input))
+}
 
+/// Match `key` against the file names in `entries`: an exact name wins, then
+/// a single fuzzy match; several fuzzy matches go through [`choose_match`].
+/// `None` when nothing matches
+fn resolve_entry(kind: &str, key: &str, entries: &[storage::FileEntry]) -> Result<Option<String>> {
     // First try exact filename match
-    for entry in &entries {
-        if entry.name == key {
-            return Ok(entry.relative_path.clone());
-        }
+    if let Some(entry) = entries.iter().find(|entry| entry.name == key) {
+        return Ok(Some(entry.relative_path.clone()));
     }
 
     // Then try fuzzy match by filename
     let config = crate::config::Config::load()?;
     let matcher = fuzzy_matcher::skim::SkimMatcherV2::default();
 
-    let mut fuzzy_matches: Vec<(&storage::FileEntry, i64)> = entries
+    let mut fuzzy_matches: Vec<(String, i64)> = entries
         .iter()
         .filter_map(|entry| {
             matcher
                 .fuzzy_match(&entry.name, key)
                 .filter(|&score| score >= config.fuzzy.threshold)
-                .map(|score| (entry, score))
+                .map(|score| (entry.relative_path.clone(), score))
         })
         .collect();
 
@@ -745,49 +1136,160 @@ fn resolve_list(input: &str) -> Result<String> {
     fuzzy_matches.sort_by(|a, b| b.1.cmp(&a.1));
 
     match fuzzy_matches.len() {
-        0 => bail!("No list matching '{}' found", input),
-        1 => Ok(fuzzy_matches[0].0.relative_path.clone()),
+        0 => Ok(None),
+        1 => Ok(Some(fuzzy_matches.remove(0).0)),
         _ => {
-            // Show top matches with scores
-            let max_suggestions = config.fuzzy.max_suggestions as usize;
-            let match_names: Vec<String> = fuzzy_matches
-                .iter()
-                .take(max_suggestions)
-                .map(|(entry, score)| format!("{} (score: {})", entry.relative_path, score))
-                .collect();
-            bail!(
-                "Multiple lists match '{}': {}",
-                input,
-                match_names.join(", ")
-            );
+            let select: Option<Selector> = PROMPT_ON_AMBIGUITY
+                .load(Ordering::Relaxed)
+                .then_some(&select_prompt);
+            let max_suggestions = config.fuzzy.max_suggestions;
+            choose_match(kind, key, &fuzzy_matches, max_suggestions, select).map(Some)
         }
     }
 }
-/// Handle the 'open' command to open a list
-pub fn open_list(list: &str) -> Result<()> {
-    // Resolve list name (omit .md, fuzzy match)
-    let key = list.trim_end_matches(".md");
-    let name = resolve_list(key)?;
-    let list = storage::markdown::load_list(&name).context("Failed to load list")?;
-    let path = list.file_path();
-    open_editor(&path)
+
+/// Whether ambiguous fuzzy matches may be settled with an interactive prompt
+static PROMPT_ON_AMBIGUITY: AtomicBool = AtomicBool::new(false);
+
+/// Let ambiguous fuzzy matches prompt for a choice when stdin and stdout are
+/// terminals and the output isn't JSON; otherwise they stay an error
+pub fn init_prompts(json: bool) {
+    let interactive = !json && io::stdin().is_terminal() && io::stdout().is_terminal();
+    PROMPT_ON_AMBIGUITY.store(interactive, Ordering::Relaxed);
 }
-/// Parse item text with category prefix (##category item)
-fn parse_item_with_category(input: &str) -> (Option<String>, String) {
-    if let Some(stripped) = input.strip_prefix("##") {
-        // Format: "##category item text"
+
+/// Asks the user to pick one of the candidates (`None` if they cancel)
+type Selector<'a> = &'a dyn Fn(&str, &[String]) -> Result<Option<usize>>;
+
+/// [`Selector`] showing a `dialoguer` menu
+fn select_prompt(prompt: &str, candidates: &[String]) -> Result<Option<usize>> {
+    dialoguer::Select::new()
+        .with_prompt(prompt)
+        .items(candidates)
+        .default(0)
+        .interact_opt()
+        .context("Failed to read selection")
+}
+
+/// Settle several fuzzy `matches` (name and score, best first): `select` picks
+/// among the top `max_suggestions`; without a selector, or if the user
+/// cancels, this fails listing them
+fn choose_match(
+    kind: &str,
+    key: &str,
+    matches: &[(String, i64)],
+    max_suggestions: usize,
+    select: Option<Selector>,
+) -> Result<String> {
+    let top = &matches[..matches.len().min(max_suggestions.max(1))];
+    if let Some(select) = select {
+        let names: Vec<String> = top.iter().map(|(name, _)| name.clone()).collect();
+        let prompt = format!("Multiple {}s match '{}'", kind, key);
+        if let Some(name) = select(&prompt, &names)?.and_then(|i| names.get(i)) {
+            return Ok(name.clone());
+        }
+    }
+
+    // Show top matches with scores
+    let match_names: Vec<String> = top
+        .iter()
+        .map(|(name, score)| format!("{} (score: {})", name, score))
+        .collect();
+    bail!(
+        "Multiple {}s match '{}': {}",
+        kind,
+        key,
+        match_names.join(", ")
+    )
+}
+
+/// Settle an item `target` of `list_name` that fuzzily matches several items
+/// through [`choose_match`], returning the chosen item's anchor. Any other
+/// target is returned unchanged for the storage layer to resolve
+fn resolve_item_target(list_name: &str, target: &str, config: &Config) -> Result<String> {
+    let list = storage::markdown::load_list(list_name)?;
+    let select: Option<Selector> = PROMPT_ON_AMBIGUITY
+        .load(Ordering::Relaxed)
+        .then_some(&select_prompt);
+    settle_item_target(
+        &list,
+        target,
+        config.fuzzy.threshold,
+        config.fuzzy.max_suggestions,
+        select,
+    )
+}
+
+/// [`resolve_item_target`] within a loaded `list`
+fn settle_item_target(
+    list: &List,
+    target: &str,
+    threshold: i64,
+    max_suggestions: usize,
+    select: Option<Selector>,
+) -> Result<String> {
+    // Several targets are resolved one by one, skipping ambiguous ones
+    if target.contains(',') {
+        return Ok(target.to_string());
+    }
+    if !matches!(
+        storage::markdown::find_item_for_removal(list, target, threshold),
+        Err(storage::StorageError::Ambiguous { .. })
+    ) {
+        return Ok(target.to_string());
+    }
+
+    let items: Vec<ListItem> = list.all_items().cloned().collect();
+    let scored = lst_core::models::fuzzy_find_scored(&items, target, threshold);
+    let matches: Vec<(String, i64)> = scored
+        .iter()
+        .map(|&(index, score)| (items[index].text.clone(), score))
+        .collect();
+    let chosen = choose_match("item", target, &matches, max_suggestions, select)?;
+    let (index, _) = scored
+        .iter()
+        .find(|&&(index, _)| items[index].text == chosen)
+        .context("Chosen item vanished")?;
+    Ok(items[*index].anchor.clone())
+}
+
+/// Handle the 'open' command to open a list in the editor (or `editor`), or
+/// just print its path
+pub fn open_list(list: &str, print_path: bool, editor: Option<&str>) -> Result<()> {
+    // Resolve list name (omit .md, fuzzy match)
+    let key = list.trim_end_matches(".md");
+    let name = resolve_list(key)?;
+    let list = storage::markdown::load_list(&name).context("Failed to load list")?;
+    let path = list.file_path();
+    let env_editor = std::env::var("EDITOR").ok();
+    open_or_print_path(
+        &path,
+        print_path,
+        editor,
+        env_editor.as_deref(),
+        &mut io::stdout(),
+    )
+}
+/// Parse item text with category prefix (##category item) and a trailing
+/// `@due YYYY-MM-DD` / `@due:YYYY-MM-DD` token
+fn parse_item_with_category(input: &str) -> (Option<String>, String, Option<NaiveDate>) {
+    let (category, text) = if let Some(stripped) = input.strip_prefix("##") {
+        // Format: "##category item text"
         if let Some(space_pos) = stripped.find(' ') {
             let category = stripped[..space_pos].to_string();
-            let item_text = stripped[space_pos + 1..].to_string();
+            let item_text = &stripped[space_pos + 1..];
             (Some(category), item_text)
         } else {
             // Just "##category" - treat as uncategorized with ## prefix
-            (None, input.to_string())
+            (None, input)
         }
     } else {
         // No category prefix
-        (None, input.to_string())
-    }
+        (None, input)
+    };
+
+    let (text, due) = split_due_date(text);
+    (category, text.to_string(), due)
 }
 
 /// Handle the 'add' command to add an item to a list
@@ -801,18 +1303,25 @@ pub async fn add_item(list: &str, text: &str, category: Option<&str>, json: bool
     }
 
     // Split by commas and trim whitespace
-    let items: Vec<&str> = text.split(',').map(|s| s.trim()).collect();
-    let mut added_items = Vec::new();
-
-    for item_text in items {
-        if !item_text.is_empty() {
-            let (inline_category, text) = parse_item_with_category(item_text);
-            // Inline category (##category) takes precedence over flag category
-            let final_category = inline_category.as_deref().or(category);
-            let item = storage::markdown::add_item_to_category(&list_name, &text, final_category)?;
-            added_items.push(item);
-        }
-    }
+    let parsed: Vec<_> = text
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(parse_item_with_category)
+        .collect();
+    // Inline category (##category) takes precedence over flag category
+    let items: Vec<storage::markdown::NewItem> = parsed
+        .iter()
+        .map(|(inline_category, text, due)| {
+            (text.as_str(), inline_category.as_deref().or(category), *due)
+        })
+        .collect();
+    // All items go in with one write, so a single undo removes them again
+    let added_items = if items.is_empty() {
+        Vec::new()
+    } else {
+        storage::markdown::add_items(&list_name, &items)?
+    };
 
     if json {
         println!("{}", serde_json::to_string(&added_items)?);
@@ -845,7 +1354,8 @@ pub async fn add_item(list: &str, text: &str, category: Option<&str>, json: bool
 pub async fn mark_done(list: &str, target: &str, json: bool) -> Result<()> {
     let list_name = normalize_list(list)?;
     let config = crate::config::Config::load()?;
-    let items = storage::markdown::mark_done(&list_name, target, config.fuzzy.threshold)?;
+    let target = resolve_item_target(&list_name, target, &config)?;
+    let items = storage::markdown::mark_done(&list_name, &target, config.fuzzy.threshold)?;
 
     if json {
         println!("{}", serde_json::to_string(&items)?);
@@ -878,7 +1388,8 @@ pub async fn mark_done(list: &str, target: &str, json: bool) -> Result<()> {
 pub async fn mark_undone(list: &str, target: &str, json: bool) -> Result<()> {
     let list_name = normalize_list(list)?;
     let config = crate::config::Config::load()?;
-    let items = storage::markdown::mark_undone(&list_name, target, config.fuzzy.threshold)?;
+    let target = resolve_item_target(&list_name, target, &config)?;
+    let items = storage::markdown::mark_undone(&list_name, &target, config.fuzzy.threshold)?;
 
     if json {
         println!("{}", serde_json::to_string(&items)?);
@@ -907,6 +1418,78 @@ pub async fn mark_undone(list: &str, target: &str, json: bool) -> Result<()> {
     Ok(())
 }
 
+/// Handle `done --all` / `undone --all` to set every item in a list at once
+pub async fn mark_all(list: &str, status: ItemStatus, json: bool) -> Result<()> {
+    let list_name = normalize_list(list)?;
+    let verb = match status {
+        ItemStatus::Done => "done",
+        ItemStatus::Todo => "undone",
+    };
+    let items = storage::markdown::set_all_status(&list_name, status)?;
+
+    if json {
+        println!("{}", serde_json::to_string(&items)?);
+        return Ok(());
+    }
+
+    println!(
+        "Marked {} items as {} in {}:",
+        items.len(),
+        verb,
+        list_name.cyan()
+    );
+    for item in &items {
+        println!("  {}", item.text);
+    }
+
+    // Notify desktop app that the list was updated
+    #[cfg(feature = "gui")]
+    {
+        let _ = notify_list_updated(&list_name).await;
+    }
+
+    Ok(())
+}
+
+/// Handle `done`/`undone --category`: set every item in one category to `status`
+pub async fn mark_category(
+    list: &str,
+    category: &str,
+    status: ItemStatus,
+    json: bool,
+) -> Result<()> {
+    let list_name = normalize_list(list)?;
+    let verb = match status {
+        ItemStatus::Done => "done",
+        ItemStatus::Todo => "undone",
+    };
+    let items = storage::markdown::set_category_status(&list_name, category, status)?;
+
+    if json {
+        println!("{}", serde_json::to_string(&items)?);
+        return Ok(());
+    }
+
+    println!(
+        "Marked {} items as {} in {} ({}):",
+        items.len(),
+        verb,
+        list_name.cyan(),
+        category
+    );
+    for item in &items {
+        println!("  {}", item.text);
+    }
+
+    // Notify desktop app that the list was updated
+    #[cfg(feature = "gui")]
+    {
+        let _ = notify_list_updated(&list_name).await;
+    }
+
+    Ok(())
+}
+
 /// Handle the 'reset' command to mark all items in a list as undone
 pub async fn reset_list(list: &str, json: bool) -> Result<()> {
     let list_name = normalize_list(list)?;
@@ -943,7 +1526,10 @@ pub async fn remove_item(list: &str, target: &str, json: bool) -> Result<()> {
     let config = crate::config::Config::load()?;
 
     // Use the storage layer implementation
-    let removed = storage::markdown::delete_item(&list_name, target, config.fuzzy.threshold)
+    let removed = resolve_item_target(&list_name, target, &config)
+        .and_then(|resolved| {
+            storage::markdown::delete_item(&list_name, &resolved, config.fuzzy.threshold)
+        })
         .with_context(|| format!("Failed to delete '{}' from {}", target, list_name))?;
 
     if json {
@@ -962,6 +1548,34 @@ pub async fn remove_item(list: &str, target: &str, json: bool) -> Result<()> {
     Ok(())
 }
 
+/// Handle the 'note-item' command to set or clear an item's note
+pub async fn note_item(list: &str, target: &str, text: &str, json: bool) -> Result<()> {
+    let list_name = normalize_list(list)?;
+    let config = crate::config::Config::load()?;
+
+    let item = resolve_item_target(&list_name, target, &config)
+        .and_then(|resolved| {
+            storage::markdown::set_item_note(&list_name, &resolved, text, config.fuzzy.threshold)
+        })
+        .with_context(|| format!("Failed to set note on '{}' in {}", target, list_name))?;
+
+    if json {
+        println!("{}", serde_json::to_string(&item)?);
+    } else if item.note.is_some() {
+        println!("Updated note on '{}' in {}", item.text, list_name.cyan());
+    } else {
+        println!("Removed note from '{}' in {}", item.text, list_name.cyan());
+    }
+
+    // Notify desktop app that the list was updated
+    #[cfg(feature = "gui")]
+    {
+        let _ = notify_list_updated(&list_name).await;
+    }
+
+    Ok(())
+}
+
 /// Handle the 'wipe' command to delete all entries from a list
 pub fn wipe_list(list: &str, force: bool, json: bool) -> Result<()> {
     let list_name = normalize_list(list)?;
@@ -989,14 +1603,157 @@ pub fn wipe_list(list: &str, force: bool, json: bool) -> Result<()> {
     Ok(())
 }
 
+/// Handle the 'search' command: search list item text and note contents
+pub fn search_all(query: &str, regex: bool, json: bool) -> Result<()> {
+    let notes = storage::search::search_notes_in(&storage::get_notes_dir()?, query, regex)?;
+    let lists = storage::search::search_lists_in(&storage::get_lists_dir()?, query, regex)?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "notes": notes,
+                "lists": lists,
+            }))?
+        );
+        return Ok(());
+    }
+
+    if notes.is_empty() && lists.is_empty() {
+        println!("No matches found for: {}", query);
+        return Ok(());
+    }
+
+    if !lists.is_empty() {
+        println!("{}", "Lists:".bold());
+        for hit in &lists {
+            let text = match hit.status {
+                ItemStatus::Todo => hit.text.normal(),
+                ItemStatus::Done => hit.text.strikethrough(),
+            };
+            println!("  {}:{} {}", hit.list.cyan(), hit.anchor.dimmed(), text);
+        }
+    }
+
+    if !notes.is_empty() {
+        if !lists.is_empty() {
+            println!();
+        }
+        println!("{}", "Notes:".bold());
+        for hit in &notes {
+            println!(
+                "  {}:{} {}",
+                hit.note.cyan(),
+                hit.line.to_string().dimmed(),
+                hit.content
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle the 'apply-template' command to overlay a template onto a list
+pub async fn apply_template(template: &str, list: &str, json: bool) -> Result<()> {
+    let list_name = normalize_list(list)?;
+    let template_name = template.trim_end_matches(".md");
+    let added = storage::markdown::apply_template(&list_name, template_name)?;
+    
+    if json {
+        println!("{}", serde_json::json!({ "added": added }));
+    } else if added == 0 {
+        println!(
+            "{} already contains everything from template {}",
+            list_name.cyan(),
+            template_name.cyan()
+        );
+    } else {
+        println!(
+            "Added {} item(s) from template {} to {}",
+            added,
+            template_name.cyan(),
+            list_name.cyan()
+        );
+    }
+    
+    // Notify desktop app that the list was updated
+    #[cfg(feature = "gui")]
+    {
+        let _ = notify_list_updated(&list_name).await;
+    }
+
+    Ok(())
+}
+
+/// Handle the 'archive' command to move done items into the archive category
+pub async fn archive_list(list: &str, restore: bool, json: bool) -> Result<()> {
+    let list_name = normalize_list(list)?;
+    let count = if restore {
+        storage::markdown::restore_archived_items(&list_name)?
+    } else {
+        storage::markdown::archive_done_items(&list_name)?
+    };
+
+    if json {
+        let key = if restore { "restored" } else { "archived" };
+        println!("{}", serde_json::json!({ key: count }));
+    } else if count == 0 {
+        if restore {
+            println!("No archived items in {}", list_name.cyan());
+        } else {
+            println!("No done items to archive in {}", list_name.cyan());
+        }
+    } else if restore {
+        println!("Restored {} item(s) in {}", count, list_name.cyan());
+    } else {
+        println!("Archived {} item(s) in {}", count, list_name.cyan());
+    }
+
+    // Notify desktop app that the list was updated
+    #[cfg(feature = "gui")]
+    {
+        let _ = notify_list_updated(&list_name).await;
+    }
+
+    Ok(())
+}
+
+/// Handle the 'pin' and 'unpin' commands
+pub async fn pin_list(list: &str, pinned: bool, json: bool) -> Result<()> {
+    let list_name = resolve_list(list)?;
+    let changed = storage::markdown::set_pinned(&list_name, pinned)?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({ "list": list_name, "pinned": pinned, "changed": changed })
+        );
+    } else if !changed {
+        let state = if pinned { "pinned" } else { "not pinned" };
+        println!("{} is already {}", list_name.cyan(), state);
+    } else if pinned {
+        println!("Pinned {}", list_name.cyan());
+    } else {
+        println!("Unpinned {}", list_name.cyan());
+    }
+
+    // Notify desktop app that the list was updated
+    #[cfg(feature = "gui")]
+    {
+        let _ = notify_list_updated(&list_name).await;
+    }
+
+    Ok(())
+}
+
 /// Handle the 'delete' command to delete a list file
 pub fn delete_list(list: &str, force: bool, json: bool) -> Result<()> {
     let list_name = normalize_list(list)?;
-    
+
     // Check if confirmation is needed
     let config = get_config();
     let need_confirm = config.ui.confirm_delete && !force;
-    
+
     if need_confirm {
         use dialoguer::Confirm;
         let prompt = format!("Delete list file '{}.md'?", list_name);
@@ -1014,10 +1771,17 @@ pub fn delete_list(list: &str, force: bool, json: bool) -> Result<()> {
         }
     }
 
+    let path = storage::get_lists_dir()?.join(format!("{}.md", list_name));
     storage::markdown::delete_list(&list_name)?;
+    forget_synced_document(&path)?;
 
     if json {
-        println!("{{\"deleted\": true, \"list\": \"{}\"}}", list_name);
+        println!(
+            "{{\"deleted\": true, \"list\": \"{}\", \"trashed\": {}}}",
+            list_name, config.ui.use_trash
+        );
+    } else if config.ui.use_trash {
+        println!("Moved list {} to the trash", list_name.cyan());
     } else {
         println!("Deleted list: {}", list_name.cyan());
     }
@@ -1025,6 +1789,70 @@ pub fn delete_list(list: &str, force: bool, json: bool) -> Result<()> {
     Ok(())
 }
 
+/// Handle 'trash ls': show deleted lists and notes, newest first
+pub fn trash_ls(json: bool) -> Result<()> {
+    let entries = storage::trash::entries()?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+    if entries.is_empty() {
+        println!("The trash is empty");
+        return Ok(());
+    }
+    for entry in &entries {
+        let deleted_at = entry.deleted_at.with_timezone(&Local);
+        println!(
+            "{}  {}",
+            deleted_at.format("%Y-%m-%d %H:%M").to_string().dimmed(),
+            entry.name.cyan()
+        );
+    }
+    Ok(())
+}
+
+/// Handle 'restore': move a deleted list or note back out of the trash
+pub fn restore(name: &str, json: bool) -> Result<()> {
+    let entry = storage::trash::restore(name)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entry)?);
+    } else {
+        println!("Restored {}", entry.name.cyan());
+    }
+    Ok(())
+}
+
+/// Handle 'trash empty': permanently delete everything in the trash
+pub fn trash_empty(force: bool, json: bool) -> Result<()> {
+    let count = storage::trash::entries()?.len();
+    if count > 0 && !force {
+        use dialoguer::Confirm;
+        let prompt = format!("Permanently delete {} file(s) in the trash?", count);
+        let proceed = Confirm::new()
+            .with_prompt(prompt)
+            .default(false)
+            .interact()?;
+        if !proceed {
+            if json {
+                println!("{{\"removed\": 0, \"message\": \"Aborted\"}}");
+            } else {
+                println!("Aborted");
+            }
+            return Ok(());
+        }
+    }
+
+    let removed = storage::trash::empty()?;
+    if json {
+        println!("{{\"removed\": {}}}", removed);
+    } else {
+        println!("Removed {} file(s) from the trash", removed);
+    }
+    Ok(())
+}
+
 /// Handle the 'pipe' command to read items from stdin
 pub fn pipe(list: &str, json: bool) -> Result<()> {
     // Try to load the list, create it if it doesn't exist
@@ -1055,98 +1883,720 @@ pub fn pipe(list: &str, json: bool) -> Result<()> {
     Ok(())
 }
 
+/// Item text for display: done items are struck through, overdue items are red
+fn styled_item_text(item: &ListItem, today: NaiveDate, color_mode: ColorMode) -> ColoredString {
+    let mut text = match item.priority {
+        Some(priority) => format!("{} {}", priority.marker(), item.text),
+        None => item.text.clone(),
+    };
+    let overdue = item.status == ItemStatus::Todo && item.is_overdue(today);
+    if let Some(due) = item.due {
+        text = if overdue && color_mode == ColorMode::Colorblind {
+            format!("{} (overdue, due {})", text, due)
+        } else {
+            format!("{} (due {})", text, due)
+        };
+    }
+    match item.status {
+        ItemStatus::Done => text.strikethrough(),
+        ItemStatus::Todo if overdue => text.red(),
+        ItemStatus::Todo => text.normal(),
+    }
+}
+
+/// Compact age of an item created at `created` as of `now` ("5m", "3h", "12d")
+fn format_age(created: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let age = now.signed_duration_since(created);
+    if age.num_days() > 0 {
+        format!("{}d", age.num_days())
+    } else if age.num_hours() > 0 {
+        format!("{}h", age.num_hours())
+    } else {
+        format!("{}m", age.num_minutes().max(0))
+    }
+}
+
+/// Checkbox for `status`; colorblind mode uses glyphs that differ in shape
+fn status_symbol(ui: &UiConfig, status: &ItemStatus) -> &'static str {
+    match (ui.color_mode, status) {
+        (ColorMode::Colorblind, ItemStatus::Todo) => "•",
+        (ColorMode::Colorblind, ItemStatus::Done) => "✓",
+        _ => ui.checkbox_style.symbol(status),
+    }
+}
+
+/// Apply `ui.color_mode` to everything the CLI prints through `colored`,
+/// honouring `NO_COLOR` and piped output
+pub fn init_color_mode() {
+    let color_mode = get_config().ui.color_mode;
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    colored::control::set_override(color_mode.use_color(no_color, io::stdout().is_terminal()));
+}
+
 /// Handle displaying a list
-pub fn display_list(list: &str, json: bool, clean: bool) -> Result<()> {
+///
+/// With `sort` set to "priority", items are ordered by priority within each
+/// category; with `status` set, only items in that state are shown. Item
+/// numbers always refer to their position in the file. With `age`, each item
+/// shows how long ago it was added
+pub fn display_list(
+    list: &str,
+    json: bool,
+    clean: bool,
+    sort: Option<&str>,
+    status: Option<ItemStatus>,
+    age: bool,
+) -> Result<()> {
+    if sort == Some("updated") {
+        bail!("--sort updated orders lists; leave out the list name");
+    }
     let list_name = normalize_list(list)?;
-    let list = storage::markdown::load_list(&list_name)?;
+    let mut list = storage::markdown::load_list(&list_name)?;
 
     if json {
+        if let Some(status) = &status {
+            list.uncategorized_items
+                .retain(|item| &item.status == status);
+            for category in &mut list.categories {
+                category.items.retain(|item| &item.status == status);
+            }
+        }
         println!("{}", serde_json::to_string(&list)?);
         return Ok(());
     }
 
-    println!("{}:", list.metadata.title.cyan().bold());
+    let ui = &get_config().ui;
+    let today = Local::now().date_naive();
+    let age_at = age.then(Utc::now);
+    let out = render_list(&list, ui, clean, sort, status, today, age_at);
+    print!("{}", out);
+    Ok(())
+}
+
+/// Render `list` the way `lst ls <list>` prints it; item ages are shown
+/// relative to `age_at` when set
+fn render_list(
+    list: &List,
+    ui: &UiConfig,
+    clean: bool,
+    sort: Option<&str>,
+    status: Option<ItemStatus>,
+    today: NaiveDate,
+    age_at: Option<DateTime<Utc>>,
+) -> String {
+    let mut out = format!("{}:\n", list.metadata.title.cyan().bold());
 
     // Check if list has any items at all
     let total_items = list.uncategorized_items.len()
         + list.categories.iter().map(|c| c.items.len()).sum::<usize>();
     if total_items == 0 {
-        println!("  No items in list");
-        return Ok(());
+        out.push_str("  No items in list\n");
+        return out;
     }
 
-    let mut item_counter = 1;
-
-    // Display uncategorized items first
-    for item in &list.uncategorized_items {
+    let theme = if list.metadata.category_colors.is_empty() {
+        None
+    } else {
+        get_config().get_theme().ok()
+    };
+    let sort = sort
+        .and_then(lst_core::models::ListSort::from_name)
+        .or(list.metadata.list.default_sort);
+    let order = |items: &[ListItem]| -> Vec<usize> {
+        let order = match sort {
+            Some(sort) => sort.order(items),
+            None => (0..items.len()).collect(),
+        };
+        order
+            .into_iter()
+            .filter(|&i| status.as_ref().is_none_or(|s| &items[i].status == s))
+            .collect()
+    };
+    let render_item = |out: &mut String, number: usize, item: &ListItem| {
+        let symbol = status_symbol(ui, &item.status);
         let checkbox: ColoredString = match item.status {
-            ItemStatus::Todo => "[ ]".into(),
-            ItemStatus::Done => "[x]".green(),
+            ItemStatus::Todo => symbol.into(),
+            ItemStatus::Done => symbol.green(),
         };
 
-        let text = match item.status {
-            ItemStatus::Todo => item.text.normal(),
-            ItemStatus::Done => item.text.strikethrough(),
-        };
+        let mut text = styled_item_text(item, today, ui.color_mode).to_string();
+        if let Some(now) = age_at {
+            text = format!("{} {}", text, format_age(item.created, now).dimmed());
+        }
 
         if clean {
-            println!("#{} {} {}", item_counter, checkbox, text);
+            out.push_str(&format!("#{} {} {}\n", number, checkbox, text));
         } else {
-            println!(
-                "#{} {} {} {}",
-                item_counter,
+            out.push_str(&format!(
+                "#{} {} {} {}\n",
+                number,
                 checkbox,
                 text,
                 item.anchor.dimmed()
-            );
+            ));
+            for line in item.note.iter().flat_map(|note| note.lines()) {
+                out.push_str(&format!("    {}\n", line.dimmed()));
+            }
         }
-        item_counter += 1;
+    };
+
+    // Display uncategorized items first
+    for i in order(&list.uncategorized_items) {
+        render_item(&mut out, i + 1, &list.uncategorized_items[i]);
     }
+    let mut offset = list.uncategorized_items.len();
 
     // Display categorized items
     for category in &list.categories {
-        if !category.items.is_empty() {
-            println!("\n{}:", category.name.cyan().bold());
-
-            for item in &category.items {
-                let checkbox: ColoredString = match item.status {
-                    ItemStatus::Todo => "[ ]".into(),
-                    ItemStatus::Done => "[x]".green(),
-                };
-
-                let text = match item.status {
-                    ItemStatus::Todo => item.text.normal(),
-                    ItemStatus::Done => item.text.strikethrough(),
-                };
-
-                if clean {
-                    println!("#{} {} {}", item_counter, checkbox, text);
-                } else {
-                    println!(
-                        "#{} {} {} {}",
-                        item_counter,
-                        checkbox,
-                        text,
-                        item.anchor.dimmed()
-                    );
-                }
-                item_counter += 1;
+        let visible = order(&category.items);
+        if !visible.is_empty() {
+            let heading = match theme
+                .as_ref()
+                .and_then(|theme| list.category_color(&category.name, theme))
+            {
+                Some((r, g, b)) => category.name.truecolor(r, g, b).bold(),
+                None => category.name.cyan().bold(),
+            };
+            out.push_str(&format!("\n{}:\n", heading));
+
+            for i in visible {
+                render_item(&mut out, offset + i + 1, &category.items[i]);
             }
         }
+        offset += category.items.len();
     }
 
-    Ok(())
+    out
 }
 
 /// Handle sync daemon commands
-pub fn handle_sync_command(cmd: SyncCommands, json: bool) -> Result<()> {
+pub async fn handle_sync_command(cmd: SyncCommands, json: bool) -> Result<()> {
     match cmd {
         SyncCommands::Setup { server } => sync_setup(server, json),
         SyncCommands::Start { foreground } => sync_start(foreground, json),
         SyncCommands::Stop => sync_stop(json),
-        SyncCommands::Status => sync_status(json),
+        SyncCommands::Now => sync_now(json).await,
+        SyncCommands::Status => sync_status(json).await,
         SyncCommands::Logs { follow, lines } => sync_logs(follow, lines, json),
+        SyncCommands::Ping => sync_ping(json).await,
+        SyncCommands::WhoamiDevices => sync_whoami_devices(json),
+        SyncCommands::Verify => sync_verify(json).await,
+    }
+}
+
+/// List synced documents with the device that last pushed a change to each,
+/// as recorded by lst-syncd in the local sync database
+fn sync_whoami_devices(json: bool) -> Result<()> {
+    use rusqlite::{Connection, OpenFlags};
+
+    let state = State::load().unwrap_or_default();
+    let this_device = state.device.device_id.clone();
+    let db_path = state
+        .get_sync_database_path()
+        .filter(|path| path.exists())
+        .context("No sync database found. Run 'lst sync start' to start syncing first")?;
+
+    let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    // Databases written by an older daemon have no author table yet
+    let tracked: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'doc_authors')",
+        [],
+        |row| row.get(0),
+    )?;
+    let query = if tracked {
+        "SELECT d.file_path, a.device_id, a.updated_at
+         FROM documents d LEFT JOIN doc_authors a ON a.doc_id = d.doc_id
+         ORDER BY d.file_path"
+    } else {
+        "SELECT file_path, NULL, NULL FROM documents ORDER BY file_path"
+    };
+    let mut stmt = conn.prepare(query)?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    if json {
+        let docs: Vec<_> = rows
+            .iter()
+            .map(|(path, device, at)| {
+                serde_json::json!({
+                    "path": path,
+                    "device_id": device,
+                    "updated_at": at,
+                    "this_device": device.is_some() && *device == this_device,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&docs)?);
+        return Ok(());
+    }
+
+    if rows.is_empty() {
+        println!("No synced documents");
+        return Ok(());
+    }
+    for (path, device, at) in rows {
+        let author = match &device {
+            Some(id) if Some(id) == this_device.as_ref() => format!("{} (this device)", id).green(),
+            Some(id) => id.yellow(),
+            None => "unknown".dimmed(),
+        };
+        let when = at
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(&t).ok())
+            .map(|t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        println!("{}  {}  {}", path.cyan(), author, when.dimmed());
+    }
+    Ok(())
+}
+
+/// WebSocket connection to the sync server
+type SyncSocket =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// Sync server WebSocket URL and a fresh JWT, refreshing the token if needed
+async fn sync_server_credentials() -> Result<(String, String)> {
+    let config = get_config();
+    let server_url = config
+        .sync
+        .as_ref()
+        .and_then(|s| s.server_url.as_ref())
+        .context("No server URL configured. Run 'lst sync setup' first")?;
+    let (host, port) = parse_server_config(server_url)?;
+    let ws_url = build_websocket_url(&host, port);
+
+    let mut state = State::load()?;
+    if !state.is_jwt_valid() || state.needs_jwt_refresh() {
+        if state.get_auth_token().is_none() {
+            bail!("No valid JWT token. Run 'lst auth request <email>' to authenticate");
+        }
+        refresh_jwt_token(config, &mut state)
+            .await
+            .context("JWT token expired and refresh failed")?;
+    }
+    let jwt = state.get_jwt().context("No valid JWT token")?.to_string();
+    Ok((ws_url, jwt))
+}
+
+/// Send one JSON-encoded message over the sync WebSocket
+async fn send_sync_message<W>(write: &mut W, msg: &lst_proto::ClientMessage) -> Result<()>
+where
+    W: futures_util::Sink<tokio_tungstenite::tungstenite::Message> + Unpin,
+    W::Error: std::error::Error + Send + Sync + 'static,
+{
+    use futures_util::SinkExt;
+
+    let text = serde_json::to_string(msg)?;
+    write
+        .send(tokio_tungstenite::tungstenite::Message::Text(text))
+        .await
+        .context("Failed to send to sync server")
+}
+
+/// Open an authenticated WebSocket connection to the sync server
+async fn connect_sync_socket(
+    ws_url: &str,
+    jwt: &str,
+    timeout: std::time::Duration,
+) -> Result<SyncSocket> {
+    use tokio_tungstenite::tungstenite::{
+        self, client::IntoClientRequest, http::header::AUTHORIZATION,
+    };
+
+    let mut request = ws_url.into_client_request()?;
+    request
+        .headers_mut()
+        .insert(AUTHORIZATION, format!("Bearer {}", jwt).parse()?);
+
+    let connect = tokio_tungstenite::connect_async(request);
+    match tokio::time::timeout(timeout, connect).await {
+        Err(_) => bail!("Timed out connecting to {}", ws_url),
+        Ok(Err(tungstenite::Error::Http(response)))
+            if response.status() == tungstenite::http::StatusCode::UNAUTHORIZED =>
+        {
+            bail!("Authentication failed. Run 'lst auth request <email>' to re-authenticate")
+        }
+        Ok(Err(e)) => bail!("Could not reach sync server at {}: {}", ws_url, e),
+        Ok(Ok((ws, _))) => Ok(ws),
+    }
+}
+
+/// Time a Ping/Pong round trip over the sync WebSocket and count server documents
+async fn sync_ping(json: bool) -> Result<()> {
+    use futures_util::{SinkExt, StreamExt};
+    use lst_proto::{ClientMessage, ServerMessage};
+    use std::time::{Duration, Instant};
+    use tokio_tungstenite::tungstenite::Message;
+
+    let (ws_url, jwt) = sync_server_credentials().await?;
+    let timeout = Duration::from_secs(10);
+    let ws = connect_sync_socket(&ws_url, &jwt, timeout).await?;
+    let (mut write, mut read) = ws.split();
+
+    let ping_id = Utc::now().timestamp_millis() as u64;
+    let started = Instant::now();
+    for msg in [
+        ClientMessage::Ping { id: ping_id },
+        ClientMessage::RequestDocumentList {
+            after: None,
+            after_updated_at: None,
+            limit: None,
+        },
+    ] {
+        write
+            .send(Message::Text(serde_json::to_string(&msg)?))
+            .await
+            .context("Failed to send to sync server")?;
+    }
+
+    // Messages are handled in order, so the Pong arrives before the document
+    // list; servers without Ping support only answer the latter
+    let mut latency = None;
+    let documents = loop {
+        let msg = match tokio::time::timeout(timeout, read.next()).await {
+            Err(_) => bail!("Timed out waiting for the sync server to respond"),
+            Ok(None) | Ok(Some(Ok(Message::Close(_)))) => {
+                bail!("Sync server closed the connection")
+            }
+            Ok(Some(Err(e))) => bail!("Sync connection error: {}", e),
+            Ok(Some(Ok(msg))) => msg,
+        };
+        let Message::Text(text) = msg else { continue };
+        match serde_json::from_str::<ServerMessage>(&text) {
+            Ok(ServerMessage::Authenticated { success: false }) => {
+                bail!("Sync server rejected authentication")
+            }
+            Ok(ServerMessage::Pong { id }) if id == ping_id => {
+                latency.get_or_insert(started.elapsed());
+            }
+            Ok(ServerMessage::DocumentList { documents }) => break documents.len(),
+            _ => {}
+        }
+    };
+    let latency = latency.unwrap_or_else(|| started.elapsed());
+    let latency_ms = latency.as_secs_f64() * 1000.0;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({ "latency_ms": latency_ms, "documents": documents })
+        );
+    } else {
+        println!(
+            "Pong from {}: {:.1} ms, {} document(s) on server",
+            ws_url.cyan(),
+            latency_ms,
+            documents
+        );
+    }
+    Ok(())
+}
+
+/// How a synced document compares to its server snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyStatus {
+    Match,
+    Mismatch,
+    MissingLocal,
+    MissingOnServer,
+}
+
+/// A document tracked in the local sync database
+#[derive(Debug, Clone)]
+pub struct SyncedDoc {
+    pub doc_id: uuid::Uuid,
+    /// Path relative to the content directory, for display
+    pub relative_path: String,
+    pub full_path: std::path::PathBuf,
+    pub kind: lst_core::sync::DocumentKind,
+}
+
+/// Request the server snapshot of every doc in `docs` and compare its content
+/// hash with the local file. Nothing is written on either side
+pub async fn verify_against_server(
+    ws_url: &str,
+    jwt: &str,
+    docs: &[SyncedDoc],
+) -> Result<Vec<(String, VerifyStatus)>> {
+    use lst_core::sync::{snapshot_content_hash, synced_content_hash};
+
+    let snapshots = fetch_server_snapshots(ws_url, jwt, docs).await?;
+    let mut results = Vec::new();
+    for doc in docs {
+        let local = std::fs::read_to_string(&doc.full_path);
+        let status = match (local, snapshots.get(&doc.doc_id)) {
+            (_, None) => VerifyStatus::MissingOnServer,
+            (Err(_), Some(_)) => VerifyStatus::MissingLocal,
+            (Ok(local), Some(snapshot)) => {
+                if synced_content_hash(&local, doc.kind)?
+                    == snapshot_content_hash(snapshot, doc.kind)?
+                {
+                    VerifyStatus::Match
+                } else {
+                    VerifyStatus::Mismatch
+                }
+            }
+        };
+        results.push((doc.relative_path.clone(), status));
+    }
+    Ok(results)
+}
+
+/// Server snapshots of those `docs` the server has, by doc id
+async fn fetch_server_snapshots(
+    ws_url: &str,
+    jwt: &str,
+    docs: &[SyncedDoc],
+) -> Result<std::collections::HashMap<uuid::Uuid, Vec<u8>>> {
+    use futures_util::StreamExt;
+    use lst_proto::{ClientMessage, ServerMessage};
+    use std::collections::{HashMap, HashSet};
+    use std::time::Duration;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let timeout = Duration::from_secs(10);
+    let ws = connect_sync_socket(ws_url, jwt, timeout).await?;
+    let (mut write, mut read) = ws.split();
+
+    let request_list = ClientMessage::RequestDocumentList {
+        after: None,
+        after_updated_at: None,
+        limit: None,
+    };
+    send_sync_message(&mut write, &request_list).await?;
+
+    // The server skips snapshot requests it can't answer, so a Ping sent after
+    // them marks the end of the replies
+    let barrier = Utc::now().timestamp_millis() as u64;
+    let mut on_server = None;
+    let mut snapshots: HashMap<uuid::Uuid, Vec<u8>> = HashMap::new();
+    loop {
+        let msg = match tokio::time::timeout(timeout, read.next()).await {
+            Err(_) if on_server.is_some() => break,
+            Err(_) => bail!("Timed out waiting for the sync server to respond"),
+            Ok(None) | Ok(Some(Ok(Message::Close(_)))) => {
+                bail!("Sync server closed the connection")
+            }
+            Ok(Some(Err(e))) => bail!("Sync connection error: {}", e),
+            Ok(Some(Ok(msg))) => msg,
+        };
+        let Message::Text(text) = msg else { continue };
+        match serde_json::from_str::<ServerMessage>(&text) {
+            Ok(ServerMessage::Authenticated { success: false }) => {
+                bail!("Sync server rejected authentication")
+            }
+            Ok(ServerMessage::DocumentList { documents }) if on_server.is_none() => {
+                let ids: HashSet<uuid::Uuid> = documents.iter().map(|d| d.doc_id).collect();
+                for doc in docs.iter().filter(|d| ids.contains(&d.doc_id)) {
+                    let request = ClientMessage::RequestSnapshot { doc_id: doc.doc_id };
+                    send_sync_message(&mut write, &request).await?;
+                }
+                send_sync_message(&mut write, &ClientMessage::Ping { id: barrier }).await?;
+                on_server = Some(ids);
+            }
+            Ok(ServerMessage::Snapshot {
+                doc_id, snapshot, ..
+            }) => {
+                snapshots.insert(doc_id, snapshot);
+            }
+            Ok(ServerMessage::Pong { id }) if id == barrier => break,
+            _ => {}
+        }
+    }
+    Ok(snapshots)
+}
+
+/// Unified diff from the server's copy of `doc` to the local file, both as
+/// the sync layer sees them (so formatting it normalizes away doesn't show).
+/// `None` when they are in sync
+pub async fn diff_against_server(
+    ws_url: &str,
+    jwt: &str,
+    doc: &SyncedDoc,
+) -> Result<Option<String>> {
+    use lst_core::sync::{snapshot_content, snapshot_from_content};
+
+    let snapshots = fetch_server_snapshots(ws_url, jwt, std::slice::from_ref(doc)).await?;
+    let snapshot = snapshots
+        .get(&doc.doc_id)
+        .with_context(|| format!("'{}' is not on the server yet", doc.relative_path))?;
+    let local = std::fs::read_to_string(&doc.full_path)
+        .with_context(|| format!("Failed to read {}", doc.full_path.display()))?;
+
+    let server = snapshot_content(snapshot, doc.kind)?;
+    let local = snapshot_content(&snapshot_from_content(&local, doc.kind)?, doc.kind)?;
+    if server == local {
+        return Ok(None);
+    }
+    let diff = similar::TextDiff::from_lines(&server, &local)
+        .unified_diff()
+        .header(
+            &format!("server/{}", doc.relative_path),
+            &format!("local/{}", doc.relative_path),
+        )
+        .to_string();
+    Ok(Some(diff))
+}
+
+/// The synced document `name` refers to: its path relative to the content
+/// directory (with or without `.md`), or else its file name
+fn find_synced_document(docs: Vec<SyncedDoc>, name: &str) -> Result<SyncedDoc> {
+    let name = name.trim_end_matches(".md");
+    let stem = |doc: &SyncedDoc| doc.relative_path.trim_end_matches(".md").to_string();
+    if let Some(doc) = docs.iter().find(|doc| stem(doc) == name) {
+        return Ok(doc.clone());
+    }
+    let mut matches: Vec<SyncedDoc> = docs
+        .into_iter()
+        .filter(|doc| {
+            Path::new(&stem(doc))
+                .file_name()
+                .is_some_and(|file| file == name)
+        })
+        .collect();
+    match matches.len() {
+        0 => bail!("No synced list or note named '{}'", name),
+        1 => Ok(matches.remove(0)),
+        _ => {
+            let paths: Vec<&str> = matches.iter().map(|d| d.relative_path.as_str()).collect();
+            bail!(
+                "Multiple synced documents match '{}': {}",
+                name,
+                paths.join(", ")
+            )
+        }
+    }
+}
+
+/// Show how a list or note differs from its copy on the sync server
+pub async fn diff(name: &str, json: bool) -> Result<()> {
+    let doc = find_synced_document(synced_documents()?, name)?;
+    let (ws_url, jwt) = sync_server_credentials().await?;
+    let diff = diff_against_server(&ws_url, &jwt, &doc).await?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "path": doc.relative_path,
+                "kind": doc.kind.as_str(),
+                "in_sync": diff.is_none(),
+                "diff": diff,
+            })
+        );
+        return Ok(());
+    }
+
+    match diff {
+        None => println!("{} is in sync with the server", doc.relative_path.cyan()),
+        Some(diff) => {
+            for line in diff.lines() {
+                if line.starts_with("+++") || line.starts_with("---") {
+                    println!("{}", line.bold());
+                } else if line.starts_with('+') {
+                    println!("{}", line.green());
+                } else if line.starts_with('-') {
+                    println!("{}", line.red());
+                } else if line.starts_with("@@") {
+                    println!("{}", line.cyan());
+                } else {
+                    println!("{}", line);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Documents tracked in the local sync database
+fn synced_documents() -> Result<Vec<SyncedDoc>> {
+    use rusqlite::{Connection, OpenFlags};
+
+    let state = State::load().unwrap_or_default();
+    let db_path = state
+        .get_sync_database_path()
+        .filter(|path| path.exists())
+        .context("No sync database found. Run 'lst sync start' to start syncing first")?;
+    let content_dir = storage::get_content_dir()?;
+
+    let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let mut stmt =
+        conn.prepare("SELECT doc_id, file_path, doc_type FROM documents ORDER BY file_path")?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut docs = Vec::new();
+    for (doc_id, file_path, doc_type) in rows {
+        let Ok(doc_id) = uuid::Uuid::parse_str(&doc_id) else {
+            continue;
+        };
+        let path = Path::new(&file_path);
+        let (relative_path, full_path) = match path.strip_prefix(&content_dir) {
+            Ok(relative) => (relative.to_string_lossy().to_string(), path.to_path_buf()),
+            Err(_) => (file_path.clone(), content_dir.join(path)),
+        };
+        docs.push(SyncedDoc {
+            doc_id,
+            relative_path,
+            full_path,
+            kind: lst_core::sync::DocumentKind::from_str(&doc_type),
+        });
+    }
+    Ok(docs)
+}
+
+/// Compare every synced document with the server's snapshot and report any
+/// divergence, without changing anything
+async fn sync_verify(json: bool) -> Result<()> {
+    let docs = synced_documents()?;
+    let (ws_url, jwt) = sync_server_credentials().await?;
+    let results = verify_against_server(&ws_url, &jwt, &docs).await?;
+    let diverged = results
+        .iter()
+        .filter(|(_, status)| *status != VerifyStatus::Match)
+        .count();
+
+    if json {
+        let docs: Vec<_> = results
+            .iter()
+            .map(|(path, status)| serde_json::json!({ "path": path, "status": status }))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&docs)?);
+    } else {
+        for (path, status) in &results {
+            let label = match status {
+                VerifyStatus::Match => "ok".green(),
+                VerifyStatus::Mismatch => "differs".red(),
+                VerifyStatus::MissingLocal => "missing locally".yellow(),
+                VerifyStatus::MissingOnServer => "missing on server".yellow(),
+            };
+            println!("{} {}", label, path);
+        }
+        if diverged == 0 {
+            println!("All {} document(s) match the server", results.len());
+        } else {
+            println!(
+                "{} of {} document(s) differ from the server",
+                diverged,
+                results.len()
+            );
+        }
     }
+    Ok(())
 }
 
 /// Setup sync configuration (first login flow)
@@ -1229,13 +2679,7 @@ pub fn sync_start(foreground: bool, json: bool) -> Result<()> {
             bail!("lst-syncd exited with status: {}", status);
         }
     } else {
-        // Start daemon in background
-        cmd.stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .stdin(Stdio::null());
-
-        let child = cmd.spawn()?;
-        let pid = child.id();
+        let pid = launch_syncd_daemon(cmd)?;
 
         if json {
             println!("{{\"status\": \"started\", \"pid\": {}}}", pid);
@@ -1247,26 +2691,151 @@ pub fn sync_start(foreground: bool, json: bool) -> Result<()> {
     Ok(())
 }
 
+/// Start lst-syncd in the background and return the daemon's PID. On Unix it
+/// detaches itself and records its PID before the launching process exits, so
+/// startup errors still reach our stderr
+#[cfg(unix)]
+fn launch_syncd_daemon(mut cmd: Command) -> Result<u32> {
+    cmd.stdout(Stdio::null()).stdin(Stdio::null());
+
+    let status = cmd.status()?;
+    if !status.success() {
+        bail!("lst-syncd failed to start ({})", status);
+    }
+    lst_core::sync::read_pid_file(&lst_core::sync::syncd_pid_path()?)?
+        .context("lst-syncd did not record its PID")
+}
+
+/// Elsewhere lst-syncd cannot detach and keeps running in the foreground, so
+/// it is spawned as a background child instead
+#[cfg(not(unix))]
+fn launch_syncd_daemon(mut cmd: Command) -> Result<u32> {
+    cmd.stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .stdin(Stdio::null());
+
+    let child = cmd.spawn()?;
+    Ok(child.id())
+}
+
 /// Stop sync daemon
 pub fn sync_stop(json: bool) -> Result<()> {
-    // Find running lst-syncd process and stop it
-    let output = Command::new("pkill").args(&["-f", "lst-syncd"]).output()?;
+    let pid_path = lst_core::sync::syncd_pid_path()?;
+    let Some(pid) = lst_core::sync::running_daemon_pid(&pid_path)? else {
+        if json {
+            println!("{}", serde_json::json!({ "status": "not_running" }));
+        } else {
+            println!("No sync daemon found running");
+        }
+        return Ok(());
+    };
+
+    lst_core::sync::terminate_process(pid)?;
+    // Give the daemon a moment to shut down and remove its PID file
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while lst_core::sync::process_alive(pid) && std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    let stopped = !lst_core::sync::process_alive(pid);
 
     if json {
-        println!("{{\"status\": \"stopped\"}}");
+        let status = if stopped { "stopped" } else { "stopping" };
+        println!("{}", serde_json::json!({ "status": status, "pid": pid }));
+    } else if stopped {
+        println!("Sync daemon stopped (PID: {})", pid);
     } else {
-        if output.status.success() {
-            println!("Sync daemon stopped");
+        println!("Sent stop signal to sync daemon (PID: {})", pid);
+    }
+
+    Ok(())
+}
+
+/// Client for the sync daemon's local control endpoint
+fn control_client(timeout: std::time::Duration) -> Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .no_proxy()
+        .build()
+        .context("Failed to build HTTP client")
+}
+
+/// Live state reported by the daemon's control endpoint, if it answers
+async fn fetch_daemon_status(config: &Config) -> Option<lst_core::sync::DaemonStatus> {
+    let client = control_client(std::time::Duration::from_secs(2)).ok()?;
+    let url = format!("http://{}/status", config.syncd_control_addr());
+    let response = client.get(url).send().await.ok()?;
+    response.error_for_status().ok()?.json().await.ok()
+}
+
+/// Run a sync cycle through the daemon's control endpoint, waiting for its
+/// outcome. `None` when the endpoint cannot be reached.
+async fn request_control_sync(config: &Config) -> Option<lst_core::sync::ManualSyncResult> {
+    let client = control_client(std::time::Duration::from_secs(300)).ok()?;
+    let url = format!("http://{}/sync", config.syncd_control_addr());
+    let response = client.post(url).send().await.ok()?;
+    response.json().await.ok()
+}
+
+/// Ask the running sync daemon for an immediate sync cycle
+pub async fn sync_now(json: bool) -> Result<()> {
+    let pid_path = lst_core::sync::syncd_pid_path()?;
+    let Some(pid) = lst_core::sync::running_daemon_pid(&pid_path)? else {
+        if json {
+            println!("{}", serde_json::json!({ "status": "not_running" }));
         } else {
-            println!("No sync daemon found running");
+            println!("No sync daemon found running. Start it with 'lst sync start'");
+        }
+        return Ok(());
+    };
+
+    use lst_core::sync::ManualSyncResult;
+    match request_control_sync(get_config()).await {
+        Some(ManualSyncResult::Synced) => {
+            if json {
+                println!("{}", serde_json::json!({ "status": "synced", "pid": pid }));
+            } else {
+                println!("Sync finished (PID: {})", pid);
+            }
+            return Ok(());
+        }
+        Some(ManualSyncResult::Skipped) => {
+            if json {
+                println!("{}", serde_json::json!({ "status": "skipped", "pid": pid }));
+            } else {
+                println!("Nothing to sync: no server is configured");
+            }
+            return Ok(());
+        }
+        Some(ManualSyncResult::Failed { error }) => {
+            if json {
+                println!(
+                    "{}",
+                    serde_json::json!({ "status": "failed", "pid": pid, "error": error })
+                );
+            }
+            bail!("Sync failed: {}", error);
         }
+        // Daemons without the control endpoint still take the signal
+        None => {}
     }
 
+    lst_core::sync::request_daemon_sync(pid)?;
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({ "status": "requested", "pid": pid })
+        );
+    } else {
+        println!(
+            "Requested a sync from the sync daemon (PID: {}); see 'lst sync logs' for the result",
+            pid
+        );
+    }
     Ok(())
 }
 
 /// Show sync daemon status
-pub fn sync_status(json: bool) -> Result<()> {
+pub async fn sync_status(json: bool) -> Result<()> {
     let config = get_config();
 
     // Check if syncd is configured
@@ -1275,16 +2844,35 @@ pub fn sync_status(json: bool) -> Result<()> {
     // Auth token no longer used - authentication is JWT-only
 
     // Check if daemon is running
-    let running = Command::new("pgrep")
-        .args(&["-f", "lst-syncd"])
-        .output()
-        .map(|output| output.status.success())
-        .unwrap_or(false);
+    let pid = lst_core::sync::running_daemon_pid(&lst_core::sync::syncd_pid_path()?)?;
+    let running = pid.is_some();
+
+    // Prefer the daemon's live view; older daemons only leave the connection
+    // state reported by their server listener
+    let live = if running {
+        fetch_daemon_status(config).await
+    } else {
+        None
+    };
+    let connection = match &live {
+        Some(live) => live.connection.clone(),
+        None if running => lst_core::sync::ConnectionStatus::load().ok().flatten(),
+        None => None,
+    };
 
     if json {
         println!(
-            "{{\"configured\": {}, \"running\": {}, \"server\": {:?}}}",
-            configured, running, server_url
+            "{}",
+            serde_json::json!({
+                "configured": configured,
+                "running": running,
+                "pid": pid,
+                "server": server_url,
+                "connection": connection,
+                "queued_changes": live.as_ref().map(|l| l.queued_changes),
+                "last_sync": live.as_ref().and_then(|l| l.last_sync),
+                "last_error": live.as_ref().and_then(|l| l.last_error.as_ref()),
+            })
         );
     } else {
         println!("Sync Configuration:");
@@ -1304,14 +2892,35 @@ pub fn sync_status(json: bool) -> Result<()> {
         }
 
         // Auth token removed - authentication is via JWT only
-        println!(
-            "  Daemon: {}",
-            if running {
-                "Running".green()
-            } else {
-                "Stopped".red()
+        match pid {
+            Some(pid) => println!("  Daemon: {} (PID: {})", "Running".green(), pid),
+            None => println!("  Daemon: {}", "Stopped".red()),
+        }
+
+        if let Some(connection) = &connection {
+            let description = connection.describe(Utc::now());
+            let description = match connection {
+                lst_core::sync::ConnectionStatus::Connected { .. } => description.green(),
+                _ => description.yellow(),
+            };
+            println!("  Connection: {}", description);
+        }
+
+        if let Some(live) = &live {
+            println!("  Queued changes: {}", live.queued_changes);
+            let last_sync = live
+                .last_sync
+                .map(|t| {
+                    t.with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string()
+                })
+                .unwrap_or_else(|| "never".to_string());
+            println!("  Last sync: {}", last_sync);
+            if let Some(error) = &live.last_error {
+                println!("  Last error: {}", error.red());
             }
-        );
+        }
 
         if !configured {
             println!("\nRun 'lst sync setup' to configure sync settings");
@@ -1324,19 +2933,92 @@ pub fn sync_status(json: bool) -> Result<()> {
 }
 
 /// Show sync daemon logs
-pub fn sync_logs(follow: bool, lines: usize, _json: bool) -> Result<()> {
-    println!("Sync daemon logs (last {} lines):", lines);
+pub fn sync_logs(follow: bool, lines: usize, json: bool) -> Result<()> {
+    let path = lst_core::sync::syncd_log_path()?;
+    let tail = if path.exists() {
+        tail_lines(&path, lines)?
+    } else {
+        Vec::new()
+    };
 
-    // For now, just indicate that logging isn't implemented yet
-    println!("Log viewing not implemented yet - check system logs for lst-syncd");
+    if json {
+        let output = serde_json::json!({
+            "path": path.display().to_string(),
+            "lines": tail,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else if !path.exists() {
+        println!("No sync daemon log yet at {}", path.display());
+    } else {
+        for line in &tail {
+            println!("{}", line);
+        }
+    }
 
     if follow {
-        println!("Use 'lst sync start --foreground' to see live output");
+        let offset = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        follow_log(&path, offset)?;
     }
-
     Ok(())
 }
 
+/// Read the last `count` lines of the file at `path`, oldest first, reading
+/// backwards in blocks so large logs are not loaded whole
+fn tail_lines(path: &Path, count: usize) -> Result<Vec<String>> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = std::fs::File::open(path)
+        .with_context(|| format!("Failed to open log file: {}", path.display()))?;
+    let mut start = file.metadata()?.len();
+    let mut buf = Vec::new();
+
+    // One line break more than requested guarantees the oldest line is whole
+    while start > 0 && buf.iter().filter(|&&b| b == b'\n').count() <= count {
+        let block = start.min(8192);
+        start -= block;
+        let mut chunk = vec![0; block as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&buf);
+        buf = chunk;
+    }
+
+    let text = String::from_utf8_lossy(&buf);
+    let lines: Vec<&str> = text.lines().collect();
+    let first = lines.len().saturating_sub(count);
+    Ok(lines[first..].iter().map(|line| line.to_string()).collect())
+}
+
+/// Print whatever gets appended to the log at `path` after `offset` until
+/// interrupted; starts over if the log is rolled over or truncated
+fn follow_log(path: &Path, mut offset: u64) -> Result<()> {
+    use std::io::{Read, Seek, SeekFrom, Write};
+
+    loop {
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        let Ok(len) = std::fs::metadata(path).map(|m| m.len()) else {
+            continue;
+        };
+        if len < offset {
+            offset = 0;
+        }
+        if len == offset {
+            continue;
+        }
+
+        let mut file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open log file: {}", path.display()))?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut appended = Vec::new();
+        file.read_to_end(&mut appended)?;
+        offset += appended.len() as u64;
+
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(&appended)?;
+        stdout.flush()?;
+    }
+}
+
 /// Find the lst-syncd binary
 fn find_syncd_binary() -> Result<String> {
     // Try common locations for lst-syncd
@@ -1368,7 +3050,7 @@ fn find_syncd_binary() -> Result<String> {
 pub fn display_daily_list(json: bool) -> Result<()> {
     let date = Local::now().format("%Y%m%d").to_string();
     let list_name = format!("daily_lists/{}_daily_list", date);
-    display_list(&list_name, json, false)
+    display_list(&list_name, json, false, None, None, false)
 }
 
 /// Share a document by updating writers and readers in the local sync database
@@ -1414,6 +3096,393 @@ pub fn share_document(doc: &str, writers: Option<&str>, readers: Option<&str>) -
     Ok(())
 }
 
+/// Split a list into one list per category
+pub async fn split_by_category(list: &str, move_items: bool, json: bool) -> Result<()> {
+    let list_name = normalize_list(list)?;
+    let (created, deleted) = storage::markdown::split_by_category(&list_name, move_items)?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "list": list_name,
+                "created": created,
+                "original_deleted": deleted,
+            })
+        );
+    } else {
+        for name in &created {
+            println!("Created {}", name.cyan());
+        }
+        if deleted {
+            println!("Deleted {} (no items left)", list_name.cyan());
+        } else if move_items {
+            println!("Kept uncategorized items in {}", list_name.cyan());
+        }
+    }
+
+    #[cfg(feature = "gui")]
+    {
+        if !deleted {
+            let _ = notify_list_updated(&list_name).await;
+        }
+        for name in &created {
+            let _ = notify_list_updated(name).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Move or rename a list or note, keeping the sync database in step
+pub fn move_document(from: &str, to: &str, force: bool, json: bool) -> Result<()> {
+    let key = from.trim_end_matches(".md");
+    let lists_dir = storage::get_lists_dir()?;
+    let (source_name, base_dir, kind) = match resolve_list(key) {
+        Ok(name) if lists_dir.join(format!("{}.md", name)).exists() => (name, lists_dir, "list"),
+        _ => (resolve_note(key)?, storage::get_notes_dir()?, "note"),
+    };
+
+    let target_name = storage::move_target_name(&source_name, to);
+
+    let from_path = base_dir.join(format!("{}.md", source_name));
+    let to_path = base_dir.join(format!("{}.md", target_name));
+    if from_path == to_path {
+        bail!(
+            "Source and destination are the same: {}",
+            from_path.display()
+        );
+    }
+    storage::move_document_file(&from_path, &to_path, force)?;
+    let resynced = rename_synced_document(&from_path, &to_path)?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "status": "success",
+                "kind": kind,
+                "from": source_name,
+                "to": target_name,
+                "sync_updated": resynced,
+            })
+        );
+    } else {
+        println!(
+            "Moved {} {} to {}",
+            kind,
+            source_name.cyan(),
+            target_name.cyan()
+        );
+    }
+
+    Ok(())
+}
+
+/// Copy a list (with fresh item anchors) or note (retitled) under a new name
+pub fn copy_document(from: &str, to: &str, force: bool, json: bool) -> Result<()> {
+    let key = from.trim_end_matches(".md");
+    let lists_dir = storage::get_lists_dir()?;
+    let (source_name, kind) = match resolve_list(key) {
+        Ok(name) if lists_dir.join(format!("{}.md", name)).exists() => (name, "list"),
+        _ => (resolve_note(key)?, "note"),
+    };
+
+    let target_name = storage::move_target_name(&source_name, to);
+    if kind == "list" {
+        storage::markdown::copy_list_in(&lists_dir, &source_name, &target_name, force)?;
+    } else {
+        let notes_dir = storage::get_notes_dir()?;
+        storage::notes::copy_note_in(&notes_dir, &source_name, &target_name, force)?;
+    }
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "status": "success",
+                "kind": kind,
+                "from": source_name,
+                "to": target_name,
+            })
+        );
+    } else {
+        println!(
+            "Copied {} {} to {}",
+            kind,
+            source_name.cyan(),
+            target_name.cyan()
+        );
+    }
+
+    Ok(())
+}
+
+/// Handle the 'cat' command: print a list (rendered like `ls --clean`) or a
+/// note body, or with `raw` the file itself. With `--json` a list is printed
+/// as `ls --json` does and anything else as `{kind, name, content}`
+pub fn cat_document(name: &str, raw: bool, kind: Option<&str>, json: bool) -> Result<()> {
+    let (name, kind) = resolve_document(name.trim_end_matches(".md"), kind)?;
+    let base_dir = match kind {
+        "list" => storage::get_lists_dir()?,
+        _ => storage::get_notes_dir()?,
+    };
+    let path = base_dir.join(format!("{}.md", name));
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}: {}", kind, path.display()))?;
+
+    if json && kind == "list" && !raw {
+        let list = storage::markdown::parse_list_from_string(&content, &path)?;
+        println!("{}", serde_json::to_string(&list)?);
+        return Ok(());
+    }
+
+    let today = Local::now().date_naive();
+    let text = cat_text(kind, &content, &path, raw, &get_config().ui, today)?;
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({ "kind": kind, "name": name, "content": text })
+        );
+    } else {
+        print!("{}", text);
+    }
+    Ok(())
+}
+
+/// Resolve `key` to a list or note name for `lst cat`. Without `kind`, a name
+/// matching both is an error unless only one of them matches exactly
+fn resolve_document(key: &str, kind: Option<&str>) -> Result<(String, &'static str)> {
+    match kind {
+        Some("list") => return Ok((resolve_list(key)?, "list")),
+        Some("note") => return Ok((resolve_note(key)?, "note")),
+        _ => {}
+    }
+
+    let lists_dir = storage::get_lists_dir()?;
+    let notes_dir = storage::get_notes_dir()?;
+    let list = resolve_list(key)
+        .ok()
+        .filter(|name| lists_dir.join(format!("{}.md", name)).exists());
+    let note = resolve_note(key)
+        .ok()
+        .filter(|name| notes_dir.join(format!("{}.md", name)).exists());
+    let exact = |name: &String| name == key || name.rsplit('/').next() == Some(key);
+
+    match (list, note) {
+        (Some(list), Some(note)) => match (exact(&list), exact(&note)) {
+            (true, false) => Ok((list, "list")),
+            (false, true) => Ok((note, "note")),
+            _ => bail!(
+                "'{}' matches list '{}' and note '{}'; pick one with --kind list or --kind note",
+                key,
+                list,
+                note
+            ),
+        },
+        (Some(list), None) => Ok((list, "list")),
+        (None, Some(note)) => Ok((note, "note")),
+        (None, None) => bail!("No list or note matching '{}' found", key),
+    }
+}
+
+/// What `lst cat` prints for a `kind` document read from `path`
+fn cat_text(
+    kind: &str,
+    content: &str,
+    path: &Path,
+    raw: bool,
+    ui: &UiConfig,
+    today: NaiveDate,
+) -> Result<String> {
+    if raw {
+        return Ok(content.to_string());
+    }
+    match kind {
+        "list" => {
+            let list = storage::markdown::parse_list_from_string(content, path)?;
+            Ok(render_list(&list, ui, true, None, None, today, None))
+        }
+        _ => Ok(split_note_frontmatter(content).1.to_string()),
+    }
+}
+
+/// Move or rename a note, keeping the sync database in step
+pub fn note_move(from: &str, to: &str, force: bool, json: bool) -> Result<()> {
+    let source_name = resolve_note(from.trim_end_matches(".md"))?;
+    let (from_path, to_path) = storage::notes::move_note(&source_name, to, force)?;
+    let target_name = storage::move_target_name(&source_name, to);
+    let resynced = rename_synced_document(&from_path, &to_path)?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "status": "success",
+                "from": source_name,
+                "to": target_name,
+                "sync_updated": resynced,
+            })
+        );
+    } else {
+        println!(
+            "Moved note {} to {}",
+            source_name.cyan(),
+            target_name.cyan()
+        );
+    }
+
+    Ok(())
+}
+
+/// Handle `note rename-heading`: rename a note's file after its title
+pub fn note_rename_heading(title: &str, dry_run: bool, json: bool) -> Result<()> {
+    let name = resolve_note(title.trim_end_matches(".md"))?;
+    let rename = storage::notes::rename_to_title(&name, dry_run)?;
+    let renamed = rename.from != rename.to;
+    let resynced = if renamed && !dry_run {
+        let notes_dir = storage::get_notes_dir()?;
+        rename_synced_document(
+            &notes_dir.join(format!("{}.md", rename.from)),
+            &notes_dir.join(format!("{}.md", rename.to)),
+        )?
+    } else {
+        false
+    };
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "from": rename.from,
+                "to": rename.to,
+                "relinked": rename.relinked,
+                "dry_run": dry_run,
+                "sync_updated": resynced,
+            })
+        );
+        return Ok(());
+    }
+
+    if !renamed {
+        println!("Note {} already matches its title", rename.from.cyan());
+        return Ok(());
+    }
+    let verb = if dry_run { "Would rename" } else { "Renamed" };
+    println!(
+        "{} note {} to {}",
+        verb,
+        rename.from.cyan(),
+        rename.to.cyan()
+    );
+    for note in &rename.relinked {
+        let verb = if dry_run { "would update" } else { "updated" };
+        println!("  {} links in {}", verb, note);
+    }
+    Ok(())
+}
+
+/// Handle `note export`: render a note to HTML (printed, or written to `out`)
+/// or, through the configured renderer, to PDF
+pub fn note_export(
+    title: &str,
+    format: &str,
+    out: Option<&str>,
+    no_theme: bool,
+    json: bool,
+) -> Result<()> {
+    let name = resolve_note(title.trim_end_matches(".md"))?;
+    let path = storage::notes::load_note(&name)?;
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read note: {}", path.display()))?;
+    let config = get_config();
+    let theme = if no_theme {
+        None
+    } else {
+        Some(config.get_theme()?)
+    };
+    let note_dir = path.parent().unwrap_or(Path::new("."));
+    let media_dir = storage::get_media_dir().ok();
+    let html = storage::html::note_to_html(
+        &content,
+        &name,
+        note_dir,
+        media_dir.as_deref(),
+        theme.as_ref(),
+    );
+
+    let Some(out) = out else {
+        // clap requires --out for PDF
+        print!("{}", html);
+        return Ok(());
+    };
+    let out = std::path::PathBuf::from(out);
+    if format == "pdf" {
+        let renderer = config
+            .ui
+            .pdf_renderer
+            .as_deref()
+            .unwrap_or(storage::html::DEFAULT_PDF_RENDERER);
+        storage::html::html_to_pdf(&html, renderer, &out)?;
+    } else {
+        std::fs::write(&out, html).with_context(|| format!("Failed to write {}", out.display()))?;
+    }
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "note": name,
+                "format": format,
+                "path": out,
+            })
+        );
+    } else {
+        println!(
+            "Exported note {} to {}",
+            name.cyan(),
+            out.display().to_string().cyan()
+        );
+    }
+    Ok(())
+}
+
+/// Re-register a moved document under its new path-derived doc id if it is
+/// tracked in the local sync database
+fn rename_synced_document(from_path: &Path, to_path: &Path) -> Result<bool> {
+    use rusqlite::Connection;
+
+    let state = State::load()?;
+    let Some(db_path) = state.get_sync_database_path() else {
+        return Ok(false);
+    };
+    if !db_path.exists() {
+        return Ok(false);
+    }
+    let conn = Connection::open(db_path)?;
+    let old_doc = lst_core::sync::canonicalize_doc_path(from_path)?;
+    let new_doc = lst_core::sync::canonicalize_doc_path(to_path)?;
+    let content = std::fs::read_to_string(to_path)?;
+    lst_core::sync::rename_tracked_document(&conn, &old_doc, &new_doc, &content)
+}
+
+/// Drop a deleted document from the local sync database, so lst-syncd
+/// registers it again if it is restored from the trash
+fn forget_synced_document(path: &Path) -> Result<bool> {
+    use rusqlite::Connection;
+
+    let state = State::load()?;
+    let Some(db_path) = state.get_sync_database_path() else {
+        return Ok(false);
+    };
+    if !db_path.exists() {
+        return Ok(false);
+    }
+    let conn = Connection::open(db_path)?;
+    let doc = lst_core::sync::canonicalize_doc_path(path)?;
+    lst_core::sync::forget_tracked_document(&conn, &doc)
+}
+
 /// Remove sharing information from a document in the local sync database
 pub fn unshare_document(doc: &str) -> Result<()> {
     share_document(doc, None, None)
@@ -1421,7 +3490,7 @@ pub fn unshare_document(doc: &str) -> Result<()> {
 
 pub async fn remote_switch_list(list_name: &str) -> Result<()> {
     let resolved_name = resolve_list(list_name)?;
-    let client = reqwest::Client::new();
+    let client = http_client();
     let res = client
         .post(format!("http://localhost:33333/command/switch-list"))
         .body(resolved_name.clone())
@@ -1438,7 +3507,7 @@ pub async fn remote_switch_list(list_name: &str) -> Result<()> {
 }
 
 pub async fn remote_show_message(message: &str) -> Result<()> {
-    let client = reqwest::Client::new();
+    let client = http_client();
     let res = client
         .post(format!("http://localhost:33333/command/show-message"))
         .body(message.to_string())
@@ -1457,7 +3526,7 @@ pub async fn remote_show_message(message: &str) -> Result<()> {
 /// Send notification to desktop app that a list was updated
 #[cfg(feature = "gui")]
 async fn notify_list_updated(list_name: &str) -> Result<()> {
-    let client = reqwest::Client::new();
+    let client = http_client();
     let res = client
         .post("http://localhost:33333/command/list-updated")
         .body(list_name.to_string())
@@ -1479,7 +3548,7 @@ async fn notify_list_updated(list_name: &str) -> Result<()> {
 /// Send notification to desktop app that a note was updated
 #[cfg(feature = "gui")]
 async fn notify_note_updated(note_name: &str) -> Result<()> {
-    let client = reqwest::Client::new();
+    let client = http_client();
     let res = client
         .post("http://localhost:33333/command/note-updated")
         .body(note_name.to_string())
@@ -1501,7 +3570,7 @@ async fn notify_note_updated(note_name: &str) -> Result<()> {
 /// Send notification to desktop app that a file was changed
 #[cfg(feature = "gui")]
 async fn notify_file_changed(file_path: &str) -> Result<()> {
-    let client = reqwest::Client::new();
+    let client = http_client();
     let res = client
         .post("http://localhost:33333/command/file-changed")
         .body(file_path.to_string())
@@ -1523,7 +3592,7 @@ async fn notify_file_changed(file_path: &str) -> Result<()> {
 /// Send notification to desktop app that theme was changed
 #[cfg(feature = "gui")]
 async fn notify_theme_changed(theme_name: &str) -> Result<()> {
-    let client = reqwest::Client::new();
+    let client = http_client();
     let res = client
         .post("http://localhost:33333/command/theme-changed")
         .body(theme_name.to_string())
@@ -1542,150 +3611,537 @@ async fn notify_theme_changed(theme_name: &str) -> Result<()> {
     Ok(())
 }
 
-/// Tidy all lists: ensure they have proper YAML frontmatter and formatting
-pub fn tidy_lists(json: bool) -> Result<()> {
+/// Tidy all lists: ensure they have proper YAML frontmatter and formatting.
+/// With `dry_run`, print a diff of each list that would change instead
+pub fn tidy_lists(dry_run: bool, json: bool) -> Result<()> {
     let entries = storage::list_lists_with_info()?;
+    let outcomes = tidy_entries(&entries, dry_run, |entry, content| {
+        storage::markdown::tidy_list_markdown(content, &entry.full_path)
+    });
+    report_tidy("list", &outcomes, dry_run, json);
+    Ok(())
+}
+
+/// Run [`tidy_file`] with `tidy` over all `entries` concurrently. Outcomes
+/// are returned in the order of `entries`, paired with their relative paths
+fn tidy_entries<F>(
+    entries: &[storage::FileEntry],
+    dry_run: bool,
+    tidy: F,
+) -> Vec<(&str, Result<Option<String>>)>
+where
+    F: Fn(&storage::FileEntry, &str) -> Result<String> + Sync,
+{
+    entries
+        .par_iter()
+        .map(|entry| {
+            let outcome = tidy_file(&entry.full_path, dry_run, |content| tidy(entry, content));
+            (entry.relative_path.as_str(), outcome)
+        })
+        .collect()
+}
+
+/// Print what [`tidy_entries`] did to each file of `kind`, followed by the
+/// summary
+fn report_tidy(kind: &str, outcomes: &[(&str, Result<Option<String>>)], dry_run: bool, json: bool) {
     let mut tidied_count = 0;
     let mut errors = Vec::new();
-
-    for entry in entries {
-        match tidy_single_list(&entry.relative_path) {
-            Ok(was_modified) => {
-                if was_modified {
-                    tidied_count += 1;
-                    if !json {
-                        println!("Tidied: {}", entry.relative_path.cyan());
-                    }
+    for (relative_path, outcome) in outcomes {
+        match outcome {
+            Ok(Some(diff)) => {
+                tidied_count += 1;
+                if !json && dry_run {
+                    print!("{}", diff);
+                } else if !json {
+                    println!("Tidied: {}", relative_path.cyan());
                 }
             }
+            Ok(None) => {}
             Err(e) => {
-                errors.push(format!("Error tidying '{}': {}", entry.relative_path, e));
+                errors.push(format!("Error tidying '{}': {}", relative_path, e));
+            }
+        }
+    }
+    print_tidy_summary(kind, tidied_count, &errors, dry_run, json);
+}
+
+/// Run `tidy` on the contents of the file at `path` and write the result back,
+/// or only compare it with `dry_run`. Returns a unified diff when the content
+/// changed (or would change)
+fn tidy_file(
+    path: &Path,
+    dry_run: bool,
+    tidy: impl FnOnce(&str) -> Result<String>,
+) -> Result<Option<String>> {
+    let original = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let tidied = tidy(&original)?;
+    if tidied == original {
+        return Ok(None);
+    }
+
+    let label = path.display().to_string();
+    let diff = similar::TextDiff::from_lines(&original, &tidied)
+        .unified_diff()
+        .header(&label, &label)
+        .to_string();
+    if !dry_run {
+        storage::atomic_write(path, tidied)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(Some(diff))
+}
+
+/// Print the outcome of `tidy` or `note tidy` over files of `kind`
+fn print_tidy_summary(kind: &str, count: usize, errors: &[String], dry_run: bool, json: bool) {
+    if json {
+        let key = if dry_run { "would_modify" } else { "tidied" };
+        println!(
+            "{}",
+            serde_json::json!({ key: count, "errors": errors.len() })
+        );
+        return;
+    }
+
+    if count == 0 {
+        println!("All {}s are already properly formatted", kind);
+    } else if dry_run {
+        println!("Would tidy {} {}(s)", count, kind);
+    } else {
+        println!("Tidied {} {}(s)", count, kind);
+    }
+
+    if !errors.is_empty() {
+        println!("\nErrors:");
+        for error in errors {
+            println!("  {}", error.red());
+        }
+    }
+}
+
+/// Print the JSON Schema of the configuration file, a list, or note
+/// frontmatter
+pub fn schema(kind: Option<&str>) -> Result<()> {
+    let schema = match kind.unwrap_or("config") {
+        "list" => List::generate_schema()?,
+        "note" => NoteFrontmatter::generate_schema()?,
+        _ => Config::generate_schema()?,
+    };
+    println!("{}", schema);
+    Ok(())
+}
+
+/// Rename a tag in all notes, or only report the affected notes with `dry_run`
+pub fn note_tags_rename(old: &str, new: &str, dry_run: bool, json: bool) -> Result<()> {
+    if old == new {
+        bail!("Old and new tag are the same: {}", old);
+    }
+    let changed = storage::notes::rename_tag(old, new, dry_run)?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "old": old,
+                "new": new,
+                "dry_run": dry_run,
+                "changed": changed.len(),
+                "notes": changed,
+            })
+        );
+        return Ok(());
+    }
+
+    if changed.is_empty() {
+        println!("No notes are tagged '{}'", old);
+        return Ok(());
+    }
+    for note in &changed {
+        println!("  {}", note.cyan());
+    }
+    let verb = if dry_run { "Would rename" } else { "Renamed" };
+    println!(
+        "{} '{}' to '{}' in {} note(s)",
+        verb,
+        old,
+        new,
+        changed.len()
+    );
+    Ok(())
+}
+
+/// Resolve a note title to the path of an existing note file
+fn existing_note_path(title: &str) -> Result<std::path::PathBuf> {
+    let note = resolve_note(title.trim_end_matches(".md"))?;
+    let path = storage::notes::load_note(&note).context("Failed to load note")?;
+    if !path.exists() {
+        bail!("Note '{}' does not exist", title);
+    }
+    Ok(path)
+}
+
+/// Handle `note links`: outgoing [[wikilinks]] of a note and its backlinks
+pub fn note_links(title: &str, json: bool) -> Result<()> {
+    let path = existing_note_path(title)?;
+    let links = storage::notes::note_links(&path)?;
+
+    if json {
+        println!("{}", serde_json::to_string(&links)?);
+        return Ok(());
+    }
+
+    println!("{}", "Links:".bold());
+    if links.outgoing.is_empty() {
+        println!("  {}", "none".dimmed());
+    }
+    for link in &links.outgoing {
+        match &link.note {
+            Some(note) if note == &link.target => println!("  {}", note.cyan()),
+            Some(note) => println!("  {} -> {}", link.target, note.cyan()),
+            None => println!("  {} {}", link.target, "(broken)".red()),
+        }
+    }
+
+    println!("{}", "Backlinks:".bold());
+    if links.backlinks.is_empty() {
+        println!("  {}", "none".dimmed());
+    }
+    for note in &links.backlinks {
+        println!("  {}", note.cyan());
+    }
+    Ok(())
+}
+
+/// Handle `note embed`: copy a file into the media dir and link it from a note
+pub async fn note_embed(file: &str, to: &str, name: Option<&str>, json: bool) -> Result<()> {
+    let file = Path::new(file);
+    if !file.is_file() {
+        bail!("File not found: {}", file.display());
+    }
+    let path = existing_note_path(to)?;
+    let label = match name {
+        Some(name) => name.to_string(),
+        None => file
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    };
+    let attachment = storage::media::attach(&path, file, &label, false)?;
+
+    if json {
+        println!("{}", serde_json::to_string(&attachment)?);
+    } else {
+        let reused = if attachment.reused {
+            " (reused existing file)".dimmed().to_string()
+        } else {
+            String::new()
+        };
+        println!(
+            "Attached to {}: {}{}",
+            to.cyan(),
+            attachment.markdown,
+            reused
+        );
+    }
+
+    #[cfg(feature = "gui")]
+    {
+        let _ = notify_note_updated(to).await;
+    }
+
+    Ok(())
+}
+
+/// Handle `note attachments`: list the non-image media links in a note
+pub fn note_attachments(title: &str, json: bool) -> Result<()> {
+    let path = existing_note_path(title)?;
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read note: {}", path.display()))?;
+    let media_dir = storage::get_media_dir()?;
+    let attachments: Vec<_> = storage::media::references(&content)
+        .into_iter()
+        .filter(|r| !r.image)
+        .collect();
+
+    if json {
+        println!("{}", serde_json::to_string(&attachments)?);
+        return Ok(());
+    }
+
+    if attachments.is_empty() {
+        println!("No attachments in {}", title.cyan());
+        return Ok(());
+    }
+    for attachment in &attachments {
+        let missing = match storage::media::stored_file_in(&media_dir, attachment) {
+            Some(_) => String::new(),
+            None => format!(" {}", "(missing)".red()),
+        };
+        println!(
+            "{} {}{}",
+            attachment.hash[..12].dimmed(),
+            attachment.label,
+            missing
+        );
+    }
+    Ok(())
+}
+
+/// Handle `note detach`: remove an attachment link, deleting the file once unused
+pub async fn note_detach(title: &str, hash: &str, json: bool) -> Result<()> {
+    let path = existing_note_path(title)?;
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read note: {}", path.display()))?;
+    // Accept an unambiguous prefix of the hash, as printed by `note attachments`
+    let hash = match storage::media::matching_hashes(&content, hash).as_slice() {
+        [] => bail!("No attachment matching '{}' in note '{}'", hash, title),
+        [hash] => hash.clone(),
+        _ => bail!("Multiple attachments match '{}'; use more characters", hash),
+    };
+    let detached = storage::media::detach(&path, &hash)?;
+
+    if json {
+        println!("{}", serde_json::to_string(&detached)?);
+    } else {
+        println!(
+            "Removed {} reference(s) from {}{}",
+            detached.removed,
+            title.cyan(),
+            if detached.media_deleted {
+                "; deleted unused media file"
+            } else {
+                ""
             }
+        );
+    }
+
+    #[cfg(feature = "gui")]
+    {
+        let _ = notify_note_updated(title).await;
+    }
+
+    Ok(())
+}
+
+/// A note or list that images can be added to
+struct MediaDocument {
+    name: String,
+    path: std::path::PathBuf,
+    is_list: bool,
+}
+
+/// Resolve a document identifier to an existing note, falling back to a list
+fn resolve_media_document(input: &str) -> Result<MediaDocument> {
+    if let Ok(name) = resolve_note(input) {
+        let path = storage::get_notes_dir()?.join(format!("{}.md", name));
+        if path.exists() {
+            return Ok(MediaDocument {
+                name,
+                path,
+                is_list: false,
+            });
+        }
+    }
+    if let Ok(name) = resolve_list(input) {
+        let path = storage::get_lists_dir()?.join(format!("{}.md", name));
+        if path.exists() {
+            return Ok(MediaDocument {
+                name,
+                path,
+                is_list: true,
+            });
         }
     }
+    bail!("No note or list matching '{}' found", input)
+}
+
+/// Handle `img add`: copy an image into the media dir and reference it from a
+/// note or list
+pub async fn image_add(file: &str, to: &str, caption: Option<&str>, json: bool) -> Result<()> {
+    let file = Path::new(file);
+    if !file.is_file() {
+        bail!("File not found: {}", file.display());
+    }
+    let caption = match caption {
+        Some(caption) => caption.to_string(),
+        None => file
+            .file_stem()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    };
+    let document = resolve_media_document(to)?;
+    let attachment = if document.is_list {
+        storage::media::attach_to_list(&document.name, file, &caption, true)?
+    } else {
+        storage::media::attach(&document.path, file, &caption, true)?
+    };
 
     if json {
-        println!(
-            "{{\"tidied\": {}, \"errors\": {}}}",
-            tidied_count,
-            errors.len()
-        );
+        println!("{}", serde_json::to_string(&attachment)?);
     } else {
-        if tidied_count > 0 {
-            println!("Tidied {} list(s)", tidied_count);
+        let reused = if attachment.reused {
+            " (reused existing file)".dimmed().to_string()
         } else {
-            println!("All lists are already properly formatted");
-        }
+            String::new()
+        };
+        println!(
+            "Added image to {}: {}{}",
+            document.name.cyan(),
+            attachment.markdown,
+            reused
+        );
+    }
 
-        if !errors.is_empty() {
-            println!("\nErrors:");
-            for error in errors {
-                println!("  {}", error.red());
-            }
-        }
+    #[cfg(feature = "gui")]
+    {
+        let _ = if document.is_list {
+            notify_list_updated(&document.name).await
+        } else {
+            notify_note_updated(&document.name).await
+        };
     }
 
     Ok(())
 }
 
-/// Tidy a single list file, returning whether it was modified
-fn tidy_single_list(list_name: &str) -> Result<bool> {
-    // Load the list (this will parse and normalize it)
-    let mut list = storage::markdown::load_list(list_name)?;
+/// Handle `img list`: show the image references in a note or list
+pub fn image_list(document: &str, json: bool) -> Result<()> {
+    let document = resolve_media_document(document)?;
+    let content = std::fs::read_to_string(&document.path)
+        .with_context(|| format!("Failed to read document: {}", document.path.display()))?;
+    let media_dir = storage::get_media_dir()?;
+    let images: Vec<_> = storage::media::references(&content)
+        .into_iter()
+        .filter(|r| r.image)
+        .collect();
 
-    // Check if any items are missing proper anchors
-    let mut was_modified = false;
+    if json {
+        let images: Vec<_> = images
+            .iter()
+            .map(|image| {
+                serde_json::json!({
+                    "hash": image.hash,
+                    "caption": image.label,
+                    "target": image.target,
+                    "line": image.line,
+                    "missing": storage::media::stored_file_in(&media_dir, image).is_none(),
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string(&images)?);
+        return Ok(());
+    }
 
-    for item in &mut list.items {
-        // Check if anchor is missing or invalid
-        if item.anchor.is_empty() || !crate::models::is_valid_anchor(&item.anchor) {
-            item.anchor = crate::models::generate_anchor();
-            was_modified = true;
-        }
+    if images.is_empty() {
+        println!("No images in {}", document.name.cyan());
+        return Ok(());
+    }
+    for image in &images {
+        let missing = match storage::media::stored_file_in(&media_dir, image) {
+            Some(_) => String::new(),
+            None => format!(" {}", "(missing)".red()),
+        };
+        println!(
+            "{} {} {}{}",
+            image.hash[..12].dimmed(),
+            image.label,
+            format!("(line {})", image.line).dimmed(),
+            missing
+        );
     }
+    Ok(())
+}
 
-    // Always save to ensure proper formatting (frontmatter + item formatting)
-    // The save operation will format everything properly
-    let original_content = std::fs::read_to_string(get_list_file_path(list_name)?)?;
-    storage::markdown::save_list_with_path(&list, list_name)?;
-    let new_content = std::fs::read_to_string(get_list_file_path(list_name)?)?;
+/// Handle `img rm`: remove an image's references from a note or list,
+/// deleting the media file once nothing references it
+pub async fn image_remove(document: &str, hash: &str, json: bool) -> Result<()> {
+    let document = resolve_media_document(document)?;
+    let content = std::fs::read_to_string(&document.path)
+        .with_context(|| format!("Failed to read document: {}", document.path.display()))?;
+    // Accept an unambiguous prefix of the hash, as printed by `img list`
+    let hash = match storage::media::matching_hashes(&content, hash).as_slice() {
+        [] => bail!("No image matching '{}' in '{}'", hash, document.name),
+        [hash] => hash.clone(),
+        _ => bail!("Multiple images match '{}'; use more characters", hash),
+    };
+    let detached = if document.is_list {
+        storage::media::detach_from_list(&document.name, &hash)?
+    } else {
+        storage::media::detach(&document.path, &hash)?
+    };
 
-    // Check if the content actually changed
-    if original_content != new_content {
-        was_modified = true;
+    if json {
+        println!("{}", serde_json::to_string(&detached)?);
+    } else {
+        println!(
+            "Removed {} reference(s) from {}{}",
+            detached.removed,
+            document.name.cyan(),
+            if detached.media_deleted {
+                "; deleted unused media file"
+            } else {
+                ""
+            }
+        );
     }
 
-    Ok(was_modified)
-}
-
-/// Helper to get the full file path for a list
-fn get_list_file_path(list_name: &str) -> Result<std::path::PathBuf> {
-    let lists_dir = storage::get_lists_dir()?;
-    let filename = format!("{}.md", list_name);
-    Ok(lists_dir.join(filename))
-}
+    #[cfg(feature = "gui")]
+    {
+        let _ = if document.is_list {
+            notify_list_updated(&document.name).await
+        } else {
+            notify_note_updated(&document.name).await
+        };
+    }
 
-/// Structure of note frontmatter used for tidying
-#[derive(serde::Serialize, serde::Deserialize, Default)]
-struct NoteFrontmatter {
-    title: Option<String>,
-    created: Option<chrono::DateTime<chrono::Utc>>,
-    updated: Option<chrono::DateTime<chrono::Utc>>,
-    tags: Option<Vec<String>>,
+    Ok(())
 }
 
-/// Tidy all notes: ensure they have proper YAML frontmatter
-pub fn tidy_notes(json: bool) -> Result<()> {
+/// Tidy all notes: ensure they have proper YAML frontmatter. With `dry_run`,
+/// print a diff of each note that would change instead
+pub fn tidy_notes(dry_run: bool, json: bool) -> Result<()> {
     let entries = storage::list_notes_with_info()?;
-    let mut tidied_count = 0;
-    let mut errors = Vec::new();
+    let outcomes = tidy_entries(&entries, dry_run, |entry, content| {
+        tidy_note_content(&entry.relative_path, content)
+    });
+    report_tidy("note", &outcomes, dry_run, json);
+    Ok(())
+}
 
-    for entry in entries {
-        match tidy_single_note(&entry.relative_path) {
-            Ok(was_modified) => {
-                if was_modified {
-                    tidied_count += 1;
-                    if !json {
-                        println!("Tidied: {}", entry.relative_path.cyan());
-                    }
-                }
-            }
-            Err(e) => {
-                errors.push(format!("Error tidying '{}': {}", entry.relative_path, e));
-            }
-        }
-    }
+/// Handle `note fmt`: normalize a note's markdown, or with `check` only show
+/// what would change and fail if anything would
+pub fn note_fmt(title: &str, width: usize, check: bool, json: bool) -> Result<()> {
+    let name = resolve_note(title.trim_end_matches(".md"))?;
+    let path = storage::notes::load_note(&name)?;
+    let diff = tidy_file(&path, check, |content| {
+        storage::format::format_note(content, width)
+    })?;
 
     if json {
         println!(
-            "{{\"tidied\": {}, \"errors\": {}}}",
-            tidied_count,
-            errors.len()
+            "{}",
+            serde_json::json!({
+                "note": name,
+                "changed": diff.is_some(),
+                "check": check,
+            })
         );
     } else {
-        if tidied_count > 0 {
-            println!("Tidied {} note(s)", tidied_count);
-        } else {
-            println!("All notes are already properly formatted");
-        }
-
-        if !errors.is_empty() {
-            println!("\nErrors:");
-            for error in errors {
-                println!("  {}", error.red());
-            }
+        match &diff {
+            None => println!("Note {} is already formatted", name.cyan()),
+            Some(diff) if check => print!("{}", diff),
+            Some(_) => println!("Formatted note {}", name.cyan()),
         }
     }
-
+    if check && diff.is_some() {
+        bail!("Note '{}' needs formatting", name);
+    }
     Ok(())
 }
 
-/// Tidy a single note file, returning whether it was modified
-fn tidy_single_note(note_name: &str) -> Result<bool> {
-    let path = get_note_file_path(note_name)?;
-    let original_content = std::fs::read_to_string(&path)?;
-
-    let mut was_modified = false;
+/// Tidied version of a note's content: frontmatter with at least a title and
+/// creation time, followed by the body
+fn tidy_note_content(note_name: &str, original_content: &str) -> Result<String> {
     let mut frontmatter: NoteFrontmatter = NoteFrontmatter::default();
     let body: String;
 
@@ -1694,17 +4150,13 @@ fn tidy_single_note(note_name: &str) -> Result<bool> {
         if parts.len() >= 3 {
             if let Ok(fm) = serde_yaml::from_str::<NoteFrontmatter>(parts[1]) {
                 frontmatter = fm;
-            } else {
-                was_modified = true;
             }
             body = parts[2].to_string();
         } else {
             body = parts.last().unwrap_or(&"").to_string();
-            was_modified = true;
         }
     } else {
-        body = original_content.clone();
-        was_modified = true;
+        body = original_content.to_string();
     }
 
     if frontmatter.title.is_none() {
@@ -1714,29 +4166,17 @@ fn tidy_single_note(note_name: &str) -> Result<bool> {
             .unwrap_or(note_name)
             .to_string();
         frontmatter.title = Some(title);
-        was_modified = true;
     }
     if frontmatter.created.is_none() {
         frontmatter.created = Some(chrono::Utc::now());
-        was_modified = true;
     }
 
     let fm_string = serde_yaml::to_string(&frontmatter)?;
-    let new_content = format!("---\n{}---\n\n{}", fm_string, body.trim_start_matches('\n'));
-
-    if new_content != original_content {
-        std::fs::write(&path, new_content)?;
-        was_modified = true;
-    }
-
-    Ok(was_modified)
-}
-
-/// Helper to get the full file path for a note
-fn get_note_file_path(note_name: &str) -> Result<std::path::PathBuf> {
-    let notes_dir = storage::get_notes_dir()?;
-    let filename = format!("{}.md", note_name);
-    Ok(notes_dir.join(filename))
+    Ok(format!(
+        "---\n{}---\n\n{}",
+        fm_string,
+        body.trim_start_matches('\n')
+    ))
 }
 
 // Authentication command implementations
@@ -1832,7 +4272,7 @@ pub async fn auth_register(email: &str, host: Option<&str>, json: bool) -> Resul
         .expect("hashing failed")
         .to_string();
 
-    let client = reqwest::Client::new();
+    let client = http_client();
     let payload = serde_json::json!({
         "email": email,
         "host": host,
@@ -1906,7 +4346,7 @@ pub async fn auth_login(email: &str, auth_token: &str, json: bool) -> Result<()>
             state.store_auth_credentials(email.to_string(), auth_token.to_string());
 
             // Get JWT token for server authentication
-            let client = reqwest::Client::new();
+            let client = http_client();
             let payload = serde_json::json!({
                 "email": email,
                 "token": auth_token
@@ -1922,8 +4362,7 @@ pub async fn auth_login(email: &str, auth_token: &str, json: bool) -> Result<()>
                 let verify_response: serde_json::Value = response.json().await?;
 
                 if let Some(jwt) = verify_response.get("jwt").and_then(|j| j.as_str()) {
-                    // Parse JWT to get expiration (basic extraction without validation)
-                    let expires_at = chrono::Utc::now() + chrono::Duration::hours(1); // Default 1 hour
+                    let expires_at = jwt_expires_at(&verify_response);
 
                     state.store_jwt(jwt.to_string(), expires_at);
                     state.save()?;
@@ -1995,7 +4434,7 @@ pub async fn auth_request(email: &str, host: Option<&str>, json: bool) -> Result
         .expect("hashing failed")
         .to_string();
 
-    let client = reqwest::Client::new();
+    let client = http_client();
     let payload = serde_json::json!({
         "email": email,
         "host": host,
@@ -2091,6 +4530,117 @@ pub fn auth_status(json: bool) -> Result<()> {
     Ok(())
 }
 
+/// Claims read from a JWT without verifying its signature
+#[derive(Debug, serde::Serialize)]
+struct TokenInfo {
+    /// Subject the token was issued to (the account email)
+    sub: Option<String>,
+    expires_at: Option<chrono::DateTime<Utc>>,
+    /// Seconds until expiry, negative once expired
+    remaining_seconds: Option<i64>,
+    /// Every claim in the token payload
+    claims: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Read the claims of `token` locally. The signature is not checked since the
+/// signing secret only lives on the server
+fn inspect_jwt(token: &str, now: chrono::DateTime<Utc>) -> Result<TokenInfo> {
+    let mut validation = jsonwebtoken::Validation::default();
+    validation.insecure_disable_signature_validation();
+    validation.validate_exp = false;
+    validation.required_spec_claims.clear();
+    let claims = jsonwebtoken::decode::<serde_json::Map<String, serde_json::Value>>(
+        token,
+        &jsonwebtoken::DecodingKey::from_secret(&[]),
+        &validation,
+    )
+    .context("Stored JWT could not be decoded")?
+    .claims;
+
+    let expires_at = claims
+        .get("exp")
+        .and_then(|exp| exp.as_i64())
+        .and_then(|exp| chrono::DateTime::from_timestamp(exp, 0));
+    Ok(TokenInfo {
+        sub: claims
+            .get("sub")
+            .and_then(|sub| sub.as_str())
+            .map(str::to_string),
+        remaining_seconds: expires_at.map(|exp| (exp - now).num_seconds()),
+        expires_at,
+        claims,
+    })
+}
+
+/// Handle `lst whoami`: decode the stored JWT and show the account, expiry,
+/// server and whether an encryption key is present
+pub fn whoami(json: bool) -> Result<()> {
+    let config = get_config();
+    let state = State::load()?;
+    let server_url = config.sync.as_ref().and_then(|s| s.server_url.clone());
+    let key_path = lst_core::crypto::get_master_key_path()?;
+    let has_key = key_path.exists();
+    let token = match state.auth.jwt_token.as_deref() {
+        Some(jwt) if !jwt.is_empty() => Some(inspect_jwt(jwt, Utc::now())?),
+        _ => None,
+    };
+    let needs_refresh = token.is_some() && state.needs_jwt_refresh();
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "server_url": server_url,
+                "master_key_path": key_path,
+                "master_key_present": has_key,
+                "needs_refresh": needs_refresh,
+                "token": token,
+            })
+        );
+        return Ok(());
+    }
+
+    match &token {
+        None => {
+            println!("Not logged in");
+            println!("  Run 'lst auth request <email>' to authenticate");
+        }
+        Some(token) => {
+            println!(
+                "Logged in as {}",
+                token.sub.as_deref().unwrap_or("(unknown)").cyan()
+            );
+            match (token.expires_at, token.remaining_seconds) {
+                (Some(expires_at), Some(remaining)) if remaining > 0 => println!(
+                    "  Expires: {} (in {}m)",
+                    expires_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                    remaining / 60
+                ),
+                (Some(expires_at), _) => println!(
+                    "  Expires: {} ({})",
+                    expires_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                    "expired".red()
+                ),
+                _ => println!("  Expires: {}", "unknown".yellow()),
+            }
+            if needs_refresh {
+                println!("  {}", "Refresh due".yellow());
+            }
+        }
+    }
+    match &server_url {
+        Some(url) => println!("  Server: {}", url.cyan()),
+        None => println!("  Server: {}", "Not configured".red()),
+    }
+    if has_key {
+        println!("  Encryption key: {}", key_path.display());
+    } else {
+        println!("  Encryption key: {}", "missing".red());
+    }
+
+    Ok(())
+}
+
 /// Remove stored authentication token
 pub fn auth_logout(json: bool) -> Result<()> {
     let mut state = State::load()?;
@@ -2118,11 +4668,16 @@ pub async fn refresh_jwt_token(config: &Config, state: &mut State) -> Result<()>
         .get_auth_token()
         .context("No auth token stored. Run 'lst auth request <email>' to authenticate")?;
 
+    let email = state
+        .get_email()
+        .context("No stored email. Run 'lst auth request <email>' to authenticate")?;
+
     let (host, port) = parse_server_config(server_url)?;
     let http_base_url = build_http_url(&host, port);
 
-    let client = reqwest::Client::new();
+    let client = http_client();
     let payload = serde_json::json!({
+        "email": email,
         "password_hash": auth_token
     });
 
@@ -2136,8 +4691,7 @@ pub async fn refresh_jwt_token(config: &Config, state: &mut State) -> Result<()>
         let refresh_response: serde_json::Value = response.json().await?;
 
         if let Some(jwt) = refresh_response.get("jwt").and_then(|j| j.as_str()) {
-            // Parse JWT to get expiration (basic extraction without validation)
-            let expires_at = chrono::Utc::now() + chrono::Duration::hours(1); // Default 1 hour
+            let expires_at = jwt_expires_at(&refresh_response);
 
             state.store_jwt(jwt.to_string(), expires_at);
             state.save()?;
@@ -2147,13 +4701,532 @@ pub async fn refresh_jwt_token(config: &Config, state: &mut State) -> Result<()>
         } else {
             bail!("Invalid refresh response: missing JWT token");
         }
-    } else {
-        let error_text = response.text().await?;
-        bail!("Failed to refresh JWT token: {}. You may need to re-authenticate with 'lst auth request <email>'", error_text);
+    } else {
+        let error_text = response.text().await?;
+        bail!("Failed to refresh JWT token: {}. You may need to re-authenticate with 'lst auth request <email>'", error_text);
+    }
+}
+
+/// Expiry of a JWT from a verify/refresh response's `expires_in` (seconds),
+/// assuming one hour for servers that don't send it
+fn jwt_expires_at(response: &serde_json::Value) -> chrono::DateTime<Utc> {
+    let seconds = response
+        .get("expires_in")
+        .and_then(|s| s.as_i64())
+        .unwrap_or(60 * 60);
+    Utc::now() + chrono::Duration::seconds(seconds)
+}
+
+/// Outcome of [`check_server`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerCheck {
+    /// Reachable and the JWT is accepted
+    Ok,
+    /// `/api/health` could not be reached or did not answer OK
+    Unreachable(String),
+    /// The server is up but rejected (or we have no) credentials
+    AuthFailed(String),
+}
+
+impl ServerCheck {
+    /// One-line explanation suitable for an error message
+    pub fn message(&self) -> String {
+        match self {
+            ServerCheck::Ok => "ok".to_string(),
+            ServerCheck::Unreachable(reason) => format!("Server unreachable: {}", reason),
+            ServerCheck::AuthFailed(reason) => format!(
+                "Authentication failed: {}. Run 'lst auth request <email>' to re-authenticate",
+                reason
+            ),
+        }
+    }
+}
+
+/// Probe the server: `/api/health` without credentials first, then a cheap
+/// authenticated endpoint with `jwt`, so network and auth problems can be told apart
+pub async fn check_server(
+    client: &reqwest::Client,
+    http_base_url: &str,
+    jwt: Option<&str>,
+) -> ServerCheck {
+    match client
+        .get(format!("{}/api/health", http_base_url))
+        .send()
+        .await
+    {
+        Ok(response) if response.status().is_success() => {}
+        Ok(response) => {
+            return ServerCheck::Unreachable(format!("health check returned {}", response.status()))
+        }
+        Err(e) => return ServerCheck::Unreachable(format!("{} ({})", http_base_url, e)),
+    }
+
+    let Some(jwt) = jwt else {
+        return ServerCheck::AuthFailed("no valid JWT stored".to_string());
+    };
+    match client
+        .head(format!("{}/api/content/lists", http_base_url))
+        .bearer_auth(jwt)
+        .send()
+        .await
+    {
+        Ok(response) if response.status().is_success() => ServerCheck::Ok,
+        Ok(response)
+            if matches!(
+                response.status(),
+                reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN
+            ) =>
+        {
+            ServerCheck::AuthFailed(format!("server rejected the JWT ({})", response.status()))
+        }
+        Ok(response) => ServerCheck::Unreachable(format!(
+            "authenticated request returned {}",
+            response.status()
+        )),
+        Err(e) => ServerCheck::Unreachable(format!("{} ({})", http_base_url, e)),
+    }
+}
+
+/// HTTP base URL of the configured sync server
+fn server_http_base_url(config: &Config) -> Result<String> {
+    let server_url = config
+        .sync
+        .as_ref()
+        .and_then(|s| s.server_url.as_ref())
+        .context("No server URL configured")?;
+
+    let (host, port) = parse_server_config(server_url)?;
+    Ok(build_http_url(&host, port))
+}
+
+/// Refresh the JWT if it is missing or about to expire; `Err` explains why not
+async fn ensure_fresh_jwt(config: &Config, state: &mut State) -> Result<()> {
+    if state.is_jwt_valid() && !state.needs_jwt_refresh() {
+        return Ok(());
+    }
+    if state.get_auth_token().is_none() {
+        bail!("No valid JWT token and no auth token for refresh. Run 'lst auth request <email>' to authenticate");
+    }
+    println!("JWT token expired or about to expire, refreshing...");
+    refresh_jwt_token(config, state).await
+}
+
+/// Check the server and auth state without changing anything
+pub async fn server_ping(json: bool) -> Result<()> {
+    let config = get_config();
+    let mut state = State::load().unwrap_or_default();
+    let http_base_url = server_http_base_url(config)?;
+
+    // A failed refresh is reported by the check below
+    let _ = ensure_fresh_jwt(config, &mut state).await;
+    let check = check_server(http_client(), &http_base_url, state.get_jwt()).await;
+
+    if json {
+        let (status, detail) = match &check {
+            ServerCheck::Ok => ("ok", None),
+            ServerCheck::Unreachable(reason) => ("unreachable", Some(reason)),
+            ServerCheck::AuthFailed(reason) => ("auth_failed", Some(reason)),
+        };
+        println!(
+            "{}",
+            serde_json::json!({ "server": http_base_url, "status": status, "detail": detail })
+        );
+    } else if check == ServerCheck::Ok {
+        println!("{} {}", http_base_url.cyan(), "ok".green());
+    }
+
+    if check != ServerCheck::Ok {
+        bail!(check.message());
+    }
+    Ok(())
+}
+
+/// A document the sync server tracks, with its filename decrypted locally
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ServerDocument {
+    pub doc_id: uuid::Uuid,
+    /// Path relative to the content directory, or `None` when the filename
+    /// cannot be decrypted with the local master key
+    pub path: Option<String>,
+    pub updated_at: chrono::DateTime<Utc>,
+}
+
+/// Fetch every page of the server's document list over the sync WebSocket
+pub async fn fetch_server_documents(
+    ws_url: &str,
+    jwt: &str,
+) -> Result<Vec<lst_proto::DocumentInfo>> {
+    use futures_util::StreamExt;
+    use lst_proto::{ClientMessage, ServerMessage, MAX_DOCUMENT_PAGE};
+    use std::time::Duration;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let timeout = Duration::from_secs(10);
+    let ws = connect_sync_socket(ws_url, jwt, timeout).await?;
+    let (mut write, mut read) = ws.split();
+
+    let mut all: Vec<lst_proto::DocumentInfo> = Vec::new();
+    loop {
+        let request = ClientMessage::document_page_after(all.last(), Some(MAX_DOCUMENT_PAGE));
+        send_sync_message(&mut write, &request).await?;
+        let page = loop {
+            let msg = match tokio::time::timeout(timeout, read.next()).await {
+                Err(_) => bail!("Timed out waiting for the sync server to respond"),
+                Ok(None) | Ok(Some(Ok(Message::Close(_)))) => {
+                    bail!("Sync server closed the connection")
+                }
+                Ok(Some(Err(e))) => bail!("Sync connection error: {}", e),
+                Ok(Some(Ok(msg))) => msg,
+            };
+            let Message::Text(text) = msg else { continue };
+            match serde_json::from_str::<ServerMessage>(&text) {
+                Ok(ServerMessage::Authenticated { success: false }) => {
+                    bail!("Sync server rejected authentication")
+                }
+                Ok(ServerMessage::DocumentList { documents }) => break documents,
+                _ => {}
+            }
+        };
+        let full = page.len() >= MAX_DOCUMENT_PAGE as usize;
+        all.extend(page);
+        if !full {
+            return Ok(all);
+        }
+    }
+}
+
+/// Decrypt the filenames of `documents` with `key`
+pub fn decode_server_documents(
+    documents: Vec<lst_proto::DocumentInfo>,
+    key: &[u8; 32],
+) -> Vec<ServerDocument> {
+    documents
+        .into_iter()
+        .map(|doc| ServerDocument {
+            doc_id: doc.doc_id,
+            path: lst_core::crypto::decrypt_filename(&doc.filename, key).ok(),
+            updated_at: doc.updated_at,
+        })
+        .collect()
+}
+
+/// The master key server filenames and content are encrypted with
+fn sync_master_key() -> Result<[u8; 32]> {
+    let key_ref = get_config()
+        .sync
+        .as_ref()
+        .and_then(|s| s.encryption_key_ref.as_deref())
+        .unwrap_or("lst-master-key");
+    let key_path = lst_core::crypto::resolve_key_path(key_ref)?;
+    lst_core::crypto::load_key(&key_path).with_context(|| {
+        format!(
+            "Failed to load the master key from {}. Run 'lst auth login' first",
+            key_path.display()
+        )
+    })
+}
+
+/// List the documents the sync server holds for the current user
+pub async fn server_list_docs(json: bool) -> Result<()> {
+    let key = sync_master_key()?;
+    let (ws_url, jwt) = sync_server_credentials().await?;
+    let mut documents = decode_server_documents(fetch_server_documents(&ws_url, &jwt).await?, &key);
+    documents.sort_by(|a, b| a.path.cmp(&b.path));
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&documents)?);
+        return Ok(());
+    }
+    if documents.is_empty() {
+        println!("No documents on the server");
+        return Ok(());
+    }
+    for doc in &documents {
+        let path = match &doc.path {
+            Some(path) => path.cyan(),
+            None => "<undecryptable filename>".red(),
+        };
+        let updated = doc.updated_at.with_timezone(&Local);
+        let updated = updated.format("%Y-%m-%d %H:%M");
+        println!("{}  {}  {}", path, doc.doc_id.to_string().dimmed(), updated);
+    }
+    Ok(())
+}
+
+/// Outcome of `lst server sync-pull`, as paths relative to the content
+/// directory
+#[derive(Debug, Default, PartialEq, serde::Serialize)]
+pub struct SyncPullReport {
+    /// Documents written from their server snapshot
+    pub pulled: Vec<String>,
+    /// Documents whose local copy already matched the server
+    pub skipped: Vec<String>,
+    /// Documents whose local copy differs from the server and was kept, as it
+    /// may hold edits that were never synced
+    pub conflicts: Vec<String>,
+    /// Documents that could not be pulled, with the reason
+    pub failed: Vec<String>,
+}
+
+/// Outcome of [`write_snapshot_if_changed`]
+#[derive(Debug, PartialEq)]
+enum SnapshotWrite {
+    Written,
+    /// The local file already holds the snapshot's content
+    Unchanged,
+    /// The local file differs from the snapshot and was left alone
+    Conflict,
+}
+
+/// Write every document the sync server holds into `content_dir`, at the path
+/// its encrypted filename decrypts to with `key`. Local files whose content
+/// hash (as the sync layer sees it) matches the server's are left alone, as
+/// are files that differ from it unless `force` is given. Snapshots that don't
+/// match the hash the server sent along are not written
+pub async fn pull_server_documents(
+    ws_url: &str,
+    jwt: &str,
+    key: &[u8; 32],
+    content_dir: &Path,
+    force: bool,
+) -> Result<SyncPullReport> {
+    use lst_core::sync::path_from_server_filename_in;
+
+    let documents = fetch_server_documents(ws_url, jwt).await?;
+    let doc_ids: Vec<uuid::Uuid> = documents.iter().map(|doc| doc.doc_id).collect();
+    let snapshots = fetch_snapshots_by_id(ws_url, jwt, &doc_ids).await?;
+
+    let mut report = SyncPullReport::default();
+    for doc in &documents {
+        let path = match path_from_server_filename_in(content_dir, &doc.filename, key) {
+            Ok(path) => path,
+            Err(e) => {
+                report.failed.push(format!("{}: {:#}", doc.doc_id, e));
+                continue;
+            }
+        };
+        let Some((snapshot, hash)) = snapshots.get(&doc.doc_id) else {
+            let reason = "no snapshot from the server";
+            report
+                .failed
+                .push(format!("{}: {}", path.relative_path, reason));
+            continue;
+        };
+        if !lst_proto::verify_snapshot(snapshot, hash.as_deref()) {
+            let reason = "snapshot does not match its hash";
+            report
+                .failed
+                .push(format!("{}: {}", path.relative_path, reason));
+            continue;
+        }
+        match write_snapshot_if_changed(&path, snapshot, force) {
+            Ok(SnapshotWrite::Written) => report.pulled.push(path.relative_path),
+            Ok(SnapshotWrite::Unchanged) => report.skipped.push(path.relative_path),
+            Ok(SnapshotWrite::Conflict) => report.conflicts.push(path.relative_path),
+            Err(e) => report
+                .failed
+                .push(format!("{}: {:#}", path.relative_path, e)),
+        }
+    }
+    Ok(report)
+}
+
+/// Write the content of `snapshot` to `path` unless the local file already
+/// holds the same content, or holds different content and `force` is not set
+fn write_snapshot_if_changed(
+    path: &lst_core::sync::CanonicalDocPath,
+    snapshot: &[u8],
+    force: bool,
+) -> Result<SnapshotWrite> {
+    use lst_core::sync::{
+        snapshot_content, snapshot_content_hash, synced_content_hash, write_document,
+    };
+
+    if let Ok(local) = std::fs::read_to_string(&path.full_path) {
+        if synced_content_hash(&local, path.kind)? == snapshot_content_hash(snapshot, path.kind)? {
+            return Ok(SnapshotWrite::Unchanged);
+        }
+        if !force {
+            return Ok(SnapshotWrite::Conflict);
+        }
+    }
+    write_document(path, &snapshot_content(snapshot, path.kind)?)?;
+    Ok(SnapshotWrite::Written)
+}
+
+/// Server snapshots of the documents `doc_ids` with the hash sent along
+/// (see [`lst_proto::verify_snapshot`]), by doc id. Documents the server
+/// doesn't have are left out
+async fn fetch_snapshots_by_id(
+    ws_url: &str,
+    jwt: &str,
+    doc_ids: &[uuid::Uuid],
+) -> Result<std::collections::HashMap<uuid::Uuid, (Vec<u8>, Option<String>)>> {
+    use futures_util::StreamExt;
+    use lst_proto::{ClientMessage, ServerMessage};
+    use std::collections::HashMap;
+    use std::time::Duration;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let timeout = Duration::from_secs(10);
+    let ws = connect_sync_socket(ws_url, jwt, timeout).await?;
+    let (mut write, mut read) = ws.split();
+
+    for doc_id in doc_ids {
+        let request = ClientMessage::RequestSnapshot { doc_id: *doc_id };
+        send_sync_message(&mut write, &request).await?;
+    }
+    // The server skips snapshot requests it can't answer, so a Ping sent after
+    // them marks the end of the replies
+    let barrier = Utc::now().timestamp_millis() as u64;
+    send_sync_message(&mut write, &ClientMessage::Ping { id: barrier }).await?;
+
+    let mut snapshots = HashMap::new();
+    loop {
+        let msg = match tokio::time::timeout(timeout, read.next()).await {
+            // Servers without Ping support never send the Pong
+            Err(_) => break,
+            Ok(None) | Ok(Some(Ok(Message::Close(_)))) => {
+                bail!("Sync server closed the connection")
+            }
+            Ok(Some(Err(e))) => bail!("Sync connection error: {}", e),
+            Ok(Some(Ok(msg))) => msg,
+        };
+        let Message::Text(text) = msg else { continue };
+        match serde_json::from_str::<ServerMessage>(&text) {
+            Ok(ServerMessage::Authenticated { success: false }) => {
+                bail!("Sync server rejected authentication")
+            }
+            Ok(ServerMessage::Snapshot {
+                doc_id,
+                snapshot,
+                hash,
+                ..
+            }) => {
+                snapshots.insert(doc_id, (snapshot, hash));
+            }
+            Ok(ServerMessage::Pong { id }) if id == barrier => break,
+            _ => {}
+        }
+    }
+    Ok(snapshots)
+}
+
+/// Handle `lst server sync-pull`: write every server document to its place
+/// in the local content directory
+pub async fn server_sync_pull(force: bool, json: bool) -> Result<()> {
+    let key = sync_master_key()?;
+    let (ws_url, jwt) = sync_server_credentials().await?;
+    let content_dir = storage::get_content_dir()?;
+    let report = pull_server_documents(&ws_url, &jwt, &key, &content_dir, force).await?;
+
+    if json {
+        println!("{}", serde_json::to_string(&report)?);
+        return Ok(());
+    }
+    for path in &report.pulled {
+        println!("Pulled {}", path.cyan());
+    }
+    println!(
+        "Pulled {} document(s), skipped {} already up to date",
+        report.pulled.len(),
+        report.skipped.len()
+    );
+    if !report.conflicts.is_empty() {
+        println!("\nKept local changes (use --force to overwrite them):");
+        for path in &report.conflicts {
+            println!("  {}", path.yellow());
+        }
+    }
+    if !report.failed.is_empty() {
+        println!("\nErrors:");
+        for error in &report.failed {
+            println!("  {}", error.red());
+        }
+    }
+    Ok(())
+}
+
+/// Client shared by every request the CLI makes, so connections to the server
+/// are pooled and reused; built on first use with the `sync` timeouts
+fn http_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        let config = get_config();
+        build_http_client(config.request_timeout(), config.connect_timeout())
+    })
+}
+
+/// HTTP client giving up on requests that take longer than `timeout`, or on
+/// connections not established within `connect_timeout`
+fn build_http_client(
+    timeout: std::time::Duration,
+    connect_timeout: std::time::Duration,
+) -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .connect_timeout(connect_timeout)
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new())
+}
+
+/// Error for a request that got no answer within `timeout`
+fn timeout_error(timeout: std::time::Duration) -> anyhow::Error {
+    anyhow::anyhow!(
+        "The server did not respond within {:?}; raise sync.request_timeout_seconds \
+         if it is just slow",
+        timeout
+    )
+}
+
+/// Error for an idempotent request that still failed after `attempts` tries
+fn retries_exhausted_error(attempts: u32, last: &reqwest::Error) -> anyhow::Error {
+    let reason = if last.is_timeout() {
+        "the last attempt timed out".to_string()
+    } else {
+        format!("last error: {}", last)
+    };
+    anyhow::anyhow!(
+        "Gave up after {} attempts ({}); check that the server is up, or raise \
+         sync.request_retries",
+        attempts,
+        reason
+    )
+}
+
+/// Delay before retry number `retry` (1-based): 250ms, doubling each time
+fn retry_backoff(retry: u32) -> std::time::Duration {
+    std::time::Duration::from_millis(250 << (retry - 1).min(5))
+}
+
+/// Send the request produced by `build`. Idempotent methods (GET, DELETE)
+/// are retried up to `retries` times with backoff when the request fails or
+/// the server answers with a 5xx status; anything else is sent once. On
+/// failure, returns the last error and how many attempts were made
+async fn send_with_retry(
+    method: &reqwest::Method,
+    build: impl Fn() -> reqwest::RequestBuilder,
+    retries: u32,
+) -> std::result::Result<reqwest::Response, (reqwest::Error, u32)> {
+    let idempotent = matches!(*method, reqwest::Method::GET | reqwest::Method::DELETE);
+    let max_attempts = if idempotent { retries + 1 } else { 1 };
+    let mut attempt = 1;
+    loop {
+        let result = build().send().await;
+        let retry = match &result {
+            Ok(response) => response.status().is_server_error(),
+            Err(_) => true,
+        };
+        if !retry || attempt >= max_attempts {
+            return result.map_err(|e| (e, attempt));
+        }
+        tokio::time::sleep(retry_backoff(attempt)).await;
+        attempt += 1;
     }
 }
 
-/// Helper function to make authenticated requests to the server
+/// Helper function to make authenticated requests to the server. Failures are
+/// explained with [`check_server`] as either a network or an auth problem
 pub async fn make_authenticated_request(
     method: reqwest::Method,
     endpoint: &str,
@@ -2161,26 +5234,15 @@ pub async fn make_authenticated_request(
 ) -> Result<reqwest::Response> {
     let config = get_config();
     let mut state = State::load()?;
-
-    let server_url = config
-        .sync
-        .as_ref()
-        .and_then(|s| s.server_url.as_ref())
-        .context("No server URL configured")?;
-
-    let (host, port) = parse_server_config(server_url)?;
-    let http_base_url = build_http_url(&host, port);
+    let http_base_url = server_http_base_url(config)?;
+    let client = http_client();
 
     // Check if JWT needs refresh before making the request
-    if !state.is_jwt_valid() || state.needs_jwt_refresh() {
-        if state.get_auth_token().is_some() {
-            println!("JWT token expired or about to expire, refreshing...");
-            if let Err(e) = refresh_jwt_token(&config, &mut state).await {
-                eprintln!("Failed to refresh JWT token: {}", e);
-                bail!("JWT token expired and refresh failed. Run 'lst auth request <email>' to re-authenticate");
-            }
-        } else {
-            bail!("No valid JWT token and no auth token for refresh. Run 'lst auth request <email>' to authenticate");
+    if let Err(e) = ensure_fresh_jwt(config, &mut state).await {
+        eprintln!("Failed to refresh JWT token: {}", e);
+        match check_server(client, &http_base_url, None).await {
+            check @ ServerCheck::Unreachable(_) => bail!(check.message()),
+            _ => bail!("JWT token expired and refresh failed. Run 'lst auth request <email>' to re-authenticate"),
         }
     }
 
@@ -2188,22 +5250,32 @@ pub async fn make_authenticated_request(
         .get_jwt()
         .context("No valid JWT token after refresh attempt")?;
 
-    let client = reqwest::Client::new();
-    let mut request = client
-        .request(
-            method,
-            format!("{}/{}", http_base_url, endpoint.trim_start_matches('/')),
-        )
-        .header("Authorization", format!("Bearer {}", jwt));
-
-    if let Some(body) = body {
-        request = request.json(&body);
-    }
+    let url = format!("{}/{}", http_base_url, endpoint.trim_start_matches('/'));
+    let build = || {
+        let request = client
+            .request(method.clone(), &url)
+            .header("Authorization", format!("Bearer {}", jwt));
+        match &body {
+            Some(body) => request.json(body),
+            None => request,
+        }
+    };
 
-    let response = request.send().await?;
+    let response = match send_with_retry(&method, build, config.request_retries()).await {
+        Ok(response) => response,
+        Err((e, attempts)) if attempts > 1 => return Err(retries_exhausted_error(attempts, &e)),
+        Err((e, _)) if e.is_timeout() => return Err(timeout_error(config.request_timeout())),
+        Err((e, _)) => match check_server(client, &http_base_url, Some(jwt)).await {
+            ServerCheck::Ok => return Err(e.into()),
+            check => bail!(check.message()),
+        },
+    };
 
     if response.status() == reqwest::StatusCode::UNAUTHORIZED {
-        bail!("Authentication failed. JWT token may be expired. Run 'lst auth request <email>' to re-authenticate");
+        match check_server(client, &http_base_url, Some(jwt)).await {
+            check @ ServerCheck::Unreachable(_) => bail!(check.message()),
+            _ => bail!(ServerCheck::AuthFailed("JWT token may be expired".to_string()).message()),
+        }
     }
 
     Ok(response)
@@ -2334,6 +5406,145 @@ pub async fn server_delete(kind: &str, path: &str, json: bool) -> Result<()> {
     Ok(())
 }
 
+/// Delete all content of `kind` under a path prefix on the server
+pub async fn server_delete_recursive(
+    kind: &str,
+    prefix: Option<&str>,
+    force: bool,
+    json: bool,
+) -> Result<()> {
+    // Treat the prefix as a directory so "daily" does not also match "daily_old/"
+    let prefix = match prefix.map(|p| p.trim_matches('/')) {
+        Some(p) if !p.is_empty() => format!("{}/", p),
+        _ => String::new(),
+    };
+    let target = format!("{}/{}*", kind, prefix);
+
+    if !force {
+        use dialoguer::Confirm;
+        let proceed = Confirm::new()
+            .with_prompt(format!("Delete all server content under '{}'?", target))
+            .default(false)
+            .interact()?;
+        if !proceed {
+            println!("Aborted");
+            return Ok(());
+        }
+    }
+
+    let endpoint = format!(
+        "/api/content/{}?prefix={}",
+        kind,
+        urlencoding::encode(&prefix)
+    );
+    let response = make_authenticated_request(reqwest::Method::DELETE, &endpoint, None).await?;
+
+    if response.status().is_success() {
+        let result: serde_json::Value = response.json().await?;
+        if json {
+            println!("{}", serde_json::to_string_pretty(&result)?);
+        } else {
+            let deleted = result.get("deleted").and_then(|d| d.as_u64()).unwrap_or(0);
+            println!("Deleted {} item(s) under {}", deleted, target.cyan());
+        }
+    } else {
+        let error_text = response.text().await?;
+        bail!("Failed to delete content: {}", error_text);
+    }
+
+    Ok(())
+}
+
+/// Download every item of `kind` from the server into the local content directory.
+/// Progress is recorded in a manifest so `--resume` only fetches items that are
+/// missing locally or have been updated on the server since.
+pub async fn server_pull(kind: &str, resume: bool, json: bool) -> Result<()> {
+    use storage::pull::{PullManifest, RemoteItem};
+
+    let target_dir = match kind {
+        "lists" => storage::get_lists_dir()?,
+        "notes" => storage::get_notes_dir()?,
+        other => storage::get_content_dir()?.join(other),
+    };
+    let manifest_path = storage::pull::get_manifest_path(kind)?;
+    let mut manifest = if resume {
+        PullManifest::load(&manifest_path)?
+    } else {
+        PullManifest::default()
+    };
+
+    let endpoint = format!("/api/content/{}", kind);
+    let response = make_authenticated_request(reqwest::Method::GET, &endpoint, None).await?;
+    if !response.status().is_success() {
+        let error_text = response.text().await?;
+        bail!("Failed to list content: {}", error_text);
+    }
+    let remote: Vec<RemoteItem> = response
+        .json()
+        .await
+        .context("Failed to parse content listing")?;
+
+    let pending = manifest.pending(&remote);
+    let skipped = remote.len() - pending.len();
+    let mut fetched = Vec::new();
+
+    for item in pending {
+        let relative = Path::new(&item.path);
+        if relative
+            .components()
+            .any(|c| !matches!(c, std::path::Component::Normal(_)))
+        {
+            eprintln!("Skipping unsafe path from server: {}", item.path);
+            continue;
+        }
+
+        let endpoint = format!("/api/content/{}/{}", kind, item.path);
+        let response = make_authenticated_request(reqwest::Method::GET, &endpoint, None).await?;
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            bail!("Failed to get {}/{}: {}", kind, item.path, error_text);
+        }
+        let content = response.text().await?;
+
+        let dest = target_dir.join(relative);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+        storage::atomic_write(&dest, content)
+            .with_context(|| format!("Failed to write file: {}", dest.display()))?;
+
+        // Persist progress after every item so an interrupted pull can be resumed
+        manifest.mark_done(item);
+        manifest.save(&manifest_path)?;
+        fetched.push(item.path.clone());
+
+        if !json {
+            println!("Pulled {}/{}", kind.cyan(), item.path);
+        }
+    }
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "kind": kind,
+                "fetched": fetched,
+                "skipped": skipped,
+            })
+        );
+    } else {
+        println!(
+            "Pulled {} item(s) into {} ({} already up to date)",
+            fetched.len(),
+            target_dir.display().to_string().cyan(),
+            skipped
+        );
+    }
+
+    Ok(())
+}
+
 // Category management commands
 
 /// Create a new category in a list
@@ -2353,7 +5564,11 @@ pub async fn category_add(list: &str, name: &str, json: bool) -> Result<()> {
     });
 
     list_obj.metadata.updated = chrono::Utc::now();
-    storage::markdown::save_list_with_path(&list_obj, &list_name)?;
+    storage::markdown::save_list_with_undo(
+        &list_obj,
+        &list_name,
+        &format!("add category '{}'", name),
+    )?;
 
     if json {
         println!(
@@ -2374,8 +5589,9 @@ pub async fn category_move(list: &str, item: &str, category: &str, json: bool) -
     let config = crate::config::Config::load()?;
 
     // Find and remove the item from its current location
+    let item = resolve_item_target(&list_name, item, &config)?;
     let location =
-        storage::markdown::find_item_for_removal(&list_obj, item, config.fuzzy.threshold)?;
+        storage::markdown::find_item_for_removal(&list_obj, &item, config.fuzzy.threshold)?;
     let moved_item = storage::markdown::remove_item_at_location(&mut list_obj, location);
 
     // Add to target category (create if doesn't exist)
@@ -2390,7 +5606,8 @@ pub async fn category_move(list: &str, item: &str, category: &str, json: bool) -
     }
 
     list_obj.metadata.updated = chrono::Utc::now();
-    storage::markdown::save_list_with_path(&list_obj, &list_name)?;
+    let summary = format!("move '{}' to category '{}'", moved_item.text, category);
+    storage::markdown::save_list_with_undo(&list_obj, &list_name, &summary)?;
 
     if json {
         println!(
@@ -2455,7 +5672,11 @@ pub async fn category_remove(list: &str, name: &str, json: bool) -> Result<()> {
         list_obj.uncategorized_items.extend(removed_category.items);
 
         list_obj.metadata.updated = chrono::Utc::now();
-        storage::markdown::save_list_with_path(&list_obj, &list_name)?;
+        storage::markdown::save_list_with_undo(
+            &list_obj,
+            &list_name,
+            &format!("remove category '{}'", name),
+        )?;
 
         if json {
             println!(
@@ -2477,6 +5698,183 @@ pub async fn category_remove(list: &str, name: &str, json: bool) -> Result<()> {
     Ok(())
 }
 
+/// Rename a category, merging into an existing category when requested
+pub async fn category_rename(
+    list: &str,
+    old_name: &str,
+    new_name: &str,
+    merge: bool,
+    dedupe: bool,
+    json: bool,
+) -> Result<()> {
+    let list_name = normalize_list(list)?;
+    let merging = storage::markdown::load_list(&list_name)?
+        .categories
+        .iter()
+        .any(|c| c.name == new_name);
+    let moved = storage::markdown::rename_category(&list_name, old_name, new_name, merge, dedupe)?;
+
+    #[cfg(feature = "gui")]
+    {
+        let _ = notify_list_updated(&list_name).await;
+    }
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({"status": "success", "merged": merging, "moved_items": moved})
+        );
+    } else if merging {
+        println!(
+            "Merged category '{}' into '{}' in {} ({} items moved)",
+            old_name,
+            new_name.cyan(),
+            list_name.cyan(),
+            moved
+        );
+    } else {
+        println!(
+            "Renamed category '{}' to '{}' in {}",
+            old_name,
+            new_name.cyan(),
+            list_name.cyan()
+        );
+    }
+
+    Ok(())
+}
+
+// List tag commands
+
+/// Add tags to a list
+pub async fn tag_add(list: &str, tags: &[String], json: bool) -> Result<()> {
+    let list_name = normalize_list(list)?;
+    let tags = storage::markdown::add_tags(&list_name, tags)?;
+    print_list_tags(&list_name, &tags, json)?;
+
+    #[cfg(feature = "gui")]
+    {
+        let _ = notify_list_updated(&list_name).await;
+    }
+
+    Ok(())
+}
+
+/// Remove tags from a list
+pub async fn tag_remove(list: &str, tags: &[String], json: bool) -> Result<()> {
+    let list_name = normalize_list(list)?;
+    let tags = storage::markdown::remove_tags(&list_name, tags)?;
+    print_list_tags(&list_name, &tags, json)?;
+
+    #[cfg(feature = "gui")]
+    {
+        let _ = notify_list_updated(&list_name).await;
+    }
+
+    Ok(())
+}
+
+/// Show the tags of a list
+pub fn tag_list(list: &str, json: bool) -> Result<()> {
+    let list_name = normalize_list(list)?;
+    let list = storage::markdown::load_list(&list_name)?;
+    print_list_tags(&list_name, &list.metadata.tags, json)
+}
+
+fn print_list_tags(list_name: &str, tags: &[String], json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::json!({ "list": list_name, "tags": tags }));
+    } else if tags.is_empty() {
+        println!("{} has no tags", list_name.cyan());
+    } else {
+        let tags: Vec<String> = tags.iter().map(|t| format!("#{}", t)).collect();
+        println!("{}: {}", list_name.cyan(), tags.join(" "));
+    }
+    Ok(())
+}
+
+/// Handle the 'undo' command: restore the pre-image of the last operation on
+/// `list`, or on whichever list changed most recently when none is given
+pub fn undo(list: Option<&str>, json: bool) -> Result<()> {
+    let list_name = match list {
+        Some(list) => normalize_list(list)?,
+        None => storage::undo::all_entries()?
+            .into_iter()
+            .filter_map(|(name, entries)| Some((entries.first()?.timestamp, name)))
+            .max()
+            .map(|(_, name)| name)
+            .context("Undo journal is empty")?,
+    };
+
+    let Some(entry) = storage::undo::restore(&list_name)? else {
+        bail!("Nothing to undo for {}", list_name);
+    };
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "list": list_name,
+                "undone": entry.summary,
+                "timestamp": entry.timestamp.to_rfc3339(),
+            })
+        );
+    } else {
+        println!("Undid {} on {}", entry.summary, list_name.cyan());
+    }
+    Ok(())
+}
+
+/// Show the undo journal, optionally restricted to a single list
+pub fn undo_journal(list: Option<&str>, json: bool) -> Result<()> {
+    let journals = match list {
+        Some(list) => {
+            let list_name = normalize_list(list)?;
+            let entries = storage::undo::entries(&list_name)?;
+            vec![(list_name, entries)]
+        }
+        None => storage::undo::all_entries()?,
+    };
+
+    if json {
+        let entries: Vec<_> = journals
+            .iter()
+            .flat_map(|(list_name, entries)| {
+                entries.iter().enumerate().map(move |(idx, entry)| {
+                    serde_json::json!({
+                        "list": list_name,
+                        "index": idx + 1,
+                        "timestamp": entry.timestamp.to_rfc3339(),
+                        "summary": entry.summary,
+                    })
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string(&entries)?);
+        return Ok(());
+    }
+
+    if journals.iter().all(|(_, entries)| entries.is_empty()) {
+        println!("Undo journal is empty");
+        return Ok(());
+    }
+
+    for (list_name, entries) in journals.iter().filter(|(_, e)| !e.is_empty()) {
+        println!("{}:", list_name.cyan());
+        for (idx, entry) in entries.iter().enumerate() {
+            let when = entry.timestamp.with_timezone(&Local);
+            println!(
+                "  {:>2}. {}  {}",
+                idx + 1,
+                when.format("%Y-%m-%d %H:%M:%S").to_string().dimmed(),
+                entry.summary
+            );
+        }
+    }
+
+    Ok(())
+}
+
 // ============================================================================
 // Theme Management Commands
 // ============================================================================
@@ -2580,12 +5978,19 @@ pub fn theme_current(json: bool) -> Result<()> {
     Ok(())
 }
 
-/// Apply a theme
-pub async fn theme_apply(theme_name: &str, json: bool) -> Result<()> {
+/// Apply a theme. With `variant` "light" or "dark" the theme's variant for
+/// that appearance is applied; "auto" applies the theme as is, leaving the
+/// choice between its variants to the desktop app
+pub async fn theme_apply(theme_name: &str, variant: &str, json: bool) -> Result<()> {
     let mut config = Config::load()?;
     let theme = config
         .load_theme_by_name(theme_name)
         .with_context(|| format!("Failed to load theme '{}'", theme_name))?;
+    let theme = match variant {
+        "light" => theme.resolve_for_variant(ThemeVariant::Light, &config.get_theme_loader())?,
+        "dark" => theme.resolve_for_variant(ThemeVariant::Dark, &config.get_theme_loader())?,
+        _ => theme,
+    };
 
     config.set_theme(theme.clone());
     config.save()?;
@@ -2602,11 +6007,15 @@ pub async fn theme_apply(theme_name: &str, json: bool) -> Result<()> {
             serde_json::json!({
                 "status": "success",
                 "theme": theme_name,
+                "scheme": theme.scheme,
                 "message": format!("Applied theme '{}'", theme_name)
             })
         );
     } else {
         println!("Applied theme: {}", theme_name.cyan());
+        if theme.scheme != theme_name {
+            println!("  {} variant: {}", variant, theme.scheme);
+        }
         if let Some(name) = &theme.name {
             println!("  {}", name.dimmed());
         }
@@ -2648,26 +6057,7 @@ pub fn theme_info(theme_name: &str, json: bool) -> Result<()> {
     }
 
     println!("\nColor palette:");
-    let palette_colors = [
-        ("base00", &theme.palette.base00, "Default Background"),
-        ("base01", &theme.palette.base01, "Lighter Background"),
-        ("base02", &theme.palette.base02, "Selection Background"),
-        ("base03", &theme.palette.base03, "Comments"),
-        ("base04", &theme.palette.base04, "Dark Foreground"),
-        ("base05", &theme.palette.base05, "Default Foreground"),
-        ("base06", &theme.palette.base06, "Light Foreground"),
-        ("base07", &theme.palette.base07, "Light Background"),
-        ("base08", &theme.palette.base08, "Red"),
-        ("base09", &theme.palette.base09, "Orange"),
-        ("base0A", &theme.palette.base0a, "Yellow"),
-        ("base0B", &theme.palette.base0b, "Green"),
-        ("base0C", &theme.palette.base0c, "Cyan"),
-        ("base0D", &theme.palette.base0d, "Blue"),
-        ("base0E", &theme.palette.base0e, "Purple"),
-        ("base0F", &theme.palette.base0f, "Brown"),
-    ];
-
-    for (name, color, description) in palette_colors {
+    for (name, color, description) in palette_colors(&theme) {
         if let Some(color_value) = color {
             println!(
                 "  {}: {} ({})",
@@ -2690,6 +6080,157 @@ pub fn theme_info(theme_name: &str, json: bool) -> Result<()> {
     Ok(())
 }
 
+/// The base16 palette slots of `theme` with their conventional roles
+fn palette_colors(theme: &Theme) -> [(&'static str, Option<&str>, &'static str); 16] {
+    let palette = &theme.palette;
+    [
+        ("base00", palette.base00.as_deref(), "Default Background"),
+        ("base01", palette.base01.as_deref(), "Lighter Background"),
+        ("base02", palette.base02.as_deref(), "Selection Background"),
+        ("base03", palette.base03.as_deref(), "Comments"),
+        ("base04", palette.base04.as_deref(), "Dark Foreground"),
+        ("base05", palette.base05.as_deref(), "Default Foreground"),
+        ("base06", palette.base06.as_deref(), "Light Foreground"),
+        ("base07", palette.base07.as_deref(), "Light Background"),
+        ("base08", palette.base08.as_deref(), "Red"),
+        ("base09", palette.base09.as_deref(), "Orange"),
+        ("base0A", palette.base0a.as_deref(), "Yellow"),
+        ("base0B", palette.base0b.as_deref(), "Green"),
+        ("base0C", palette.base0c.as_deref(), "Cyan"),
+        ("base0D", palette.base0d.as_deref(), "Blue"),
+        ("base0E", palette.base0e.as_deref(), "Purple"),
+        ("base0F", palette.base0f.as_deref(), "Brown"),
+    ]
+}
+
+/// Semantic roles of a theme, in the order `lst themes preview` shows them
+const SEMANTIC_ROLES: [&str; 11] = [
+    "background",
+    "foreground",
+    "primary",
+    "secondary",
+    "accent",
+    "muted",
+    "border",
+    "success",
+    "warning",
+    "error",
+    "info",
+];
+
+/// How `lst themes preview` draws color swatches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Swatches {
+    /// 24-bit colors straight from the hex values
+    TrueColor,
+    /// The nearest colors of the 256-color palette
+    Ansi256,
+    /// No swatches, just the hex values
+    Off,
+}
+
+impl Swatches {
+    /// Pick the swatch style: none when color is disabled (`NO_COLOR`,
+    /// `ui.color_mode`, piped output), truecolor when `COLORTERM` says the
+    /// terminal supports it, 256 colors otherwise
+    fn detect(colorize: bool, colorterm: Option<&str>) -> Self {
+        match colorterm {
+            _ if !colorize => Swatches::Off,
+            Some("truecolor" | "24bit") => Swatches::TrueColor,
+            _ => Swatches::Ansi256,
+        }
+    }
+
+    /// A colored block for `hex`, followed by a space
+    fn paint(self, hex: &str) -> String {
+        let Some((r, g, b)) = hex_to_rgb(hex).filter(|_| self != Swatches::Off) else {
+            return String::new();
+        };
+        // Swatches are only drawn when color is on, so the escape codes are
+        // written directly rather than through `colored`
+        if self == Swatches::TrueColor {
+            return format!("\x1b[48;2;{};{};{}m    \x1b[0m ", r, g, b);
+        }
+        // Map each channel onto the 6x6x6 color cube (codes 16-231)
+        let level = |v: u8| match v {
+            0..=47 => 0,
+            48..=114 => 1,
+            _ => (u16::from(v) - 35) / 40,
+        };
+        let code = 16 + 36 * level(r) + 6 * level(g) + level(b);
+        format!("\x1b[48;5;{}m    \x1b[0m ", code)
+    }
+}
+
+/// Render the palette and the resolved semantic colors of `theme`
+fn render_theme_preview(theme: &Theme, swatches: Swatches) -> String {
+    let mut out = format!("Theme: {}\n\nPalette:\n", theme.scheme);
+    for (name, color, description) in palette_colors(theme) {
+        if let Some(color) = color {
+            out.push_str(&format!(
+                "  {}{}  {}  {}\n",
+                swatches.paint(color),
+                name,
+                color,
+                description
+            ));
+        }
+    }
+
+    out.push_str("\nSemantic colors:\n");
+    for role in SEMANTIC_ROLES {
+        let base = theme.semantic_color_ref(role).unwrap_or_default();
+        match theme.resolve_semantic_color(role) {
+            Some(color) => out.push_str(&format!(
+                "  {}{:<10}  {} -> {}\n",
+                swatches.paint(&color),
+                role,
+                base,
+                color
+            )),
+            None => out.push_str(&format!("  {:<10}  {} (unset)\n", role, base)),
+        }
+    }
+    out
+}
+
+/// Show a theme's colors as swatches
+pub fn theme_preview(theme_name: &str, json: bool) -> Result<()> {
+    let config = Config::load()?;
+    let theme = config
+        .get_theme_loader()
+        .load_theme(theme_name)
+        .with_context(|| format!("Failed to load theme '{}'", theme_name))?;
+
+    if json {
+        let palette: serde_json::Map<String, serde_json::Value> = palette_colors(&theme)
+            .into_iter()
+            .filter_map(|(name, color, _)| Some((name.to_string(), color?.into())))
+            .collect();
+        let semantic: serde_json::Map<String, serde_json::Value> = SEMANTIC_ROLES
+            .into_iter()
+            .map(|role| (role.to_string(), theme.resolve_semantic_color(role).into()))
+            .collect();
+        println!(
+            "{}",
+            serde_json::json!({
+                "theme": theme.scheme,
+                "palette": palette,
+                "semantic": semantic,
+            })
+        );
+        return Ok(());
+    }
+
+    let colorize = colored::control::SHOULD_COLORIZE.should_colorize();
+    let colorterm = std::env::var("COLORTERM").ok();
+    print!(
+        "{}",
+        render_theme_preview(&theme, Swatches::detect(colorize, colorterm.as_deref()))
+    );
+    Ok(())
+}
+
 /// Validate a theme file
 pub fn theme_validate(file_path: &str, json: bool) -> Result<()> {
     let config = Config::load()?;
@@ -2738,6 +6279,172 @@ pub fn theme_validate(file_path: &str, json: bool) -> Result<()> {
     Ok(())
 }
 
+/// Start of a new theme called `name`: a copy of the theme `from` or, without
+/// one, an empty base16 palette, with `overrides` applied
+fn build_theme(
+    loader: &ThemeLoader,
+    name: &str,
+    from: Option<&str>,
+    overrides: &ThemeOverrides,
+) -> Result<Theme> {
+    let mut theme = match from {
+        Some(from) => {
+            let mut theme = loader
+                .load_theme(from)
+                .with_context(|| format!("Failed to load theme '{}'", from))?;
+            theme.description = Some(format!("Based on {}", from));
+            theme
+        }
+        None => Theme {
+            palette: Default::default(),
+            description: None,
+            variant: None,
+            ..Theme::default()
+        },
+    };
+    theme.scheme = name.to_string();
+    theme.name = Some(name.to_string());
+    // The file stands on its own rather than tracking its origin
+    theme.inherits = None;
+    theme.variants = None;
+    theme.overrides = None;
+    loader.apply_overrides(theme, overrides)
+}
+
+/// Parse `{"base00": "#1e1e2e", ..., "semantic": {"primary": "base0E"}}` into
+/// theme overrides, rejecting unknown keys and malformed colors
+fn theme_overrides_from_json(input: &str) -> Result<ThemeOverrides> {
+    let fields: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(input).context("Expected the palette as a JSON object")?;
+    let slots = palette_colors(&Theme::default()).map(|(name, _, _)| name);
+    let mut overrides = ThemeOverrides::default();
+    for (key, value) in fields {
+        if key == "semantic" {
+            let roles: std::collections::BTreeMap<String, String> =
+                serde_json::from_value(value)
+                    .context("\"semantic\" must map color roles to palette slots")?;
+            for (role, slot) in &roles {
+                if !SEMANTIC_ROLES.contains(&role.as_str()) {
+                    bail!("Unknown semantic color '{}'", role);
+                }
+                if !slots.contains(&slot.as_str()) {
+                    bail!(
+                        "'{}' must map to a palette slot (base00-base0F), not '{}'",
+                        role,
+                        slot
+                    );
+                }
+            }
+            overrides.semantic = Some(roles);
+            continue;
+        }
+        if !slots.contains(&key.as_str()) {
+            bail!("Unknown palette color '{}' (expected base00-base0F)", key);
+        }
+        let color = value
+            .as_str()
+            .filter(|color| is_valid_color(color))
+            .with_context(|| format!("Invalid color for {}: {}", key, value))?;
+        overrides.palette.insert(key, color.to_string());
+    }
+    Ok(overrides)
+}
+
+/// Ask for each palette color, offering the starting theme's value, and
+/// optionally for the semantic mappings
+fn prompt_theme_overrides(theme: &Theme) -> Result<ThemeOverrides> {
+    use dialoguer::{Confirm, Input};
+
+    let mut overrides = ThemeOverrides::default();
+    for (name, color, description) in palette_colors(theme) {
+        let mut input = Input::<String>::new()
+            .with_prompt(format!("{} ({})", name, description))
+            .validate_with(|value: &String| {
+                if is_valid_color(value) {
+                    Ok(())
+                } else {
+                    Err("Enter a hex color like #1e1e2e")
+                }
+            });
+        if let Some(color) = color {
+            input = input.default(color.to_string());
+        }
+        overrides
+            .palette
+            .insert(name.to_string(), input.interact_text()?);
+    }
+
+    let customize = Confirm::new()
+        .with_prompt("Customize the semantic color mappings?")
+        .default(false)
+        .interact()?;
+    if customize {
+        let slots = palette_colors(theme).map(|(name, _, _)| name);
+        let mut roles = std::collections::BTreeMap::new();
+        for role in SEMANTIC_ROLES {
+            let current = theme.semantic_color_ref(role).unwrap_or_default();
+            let slot = Input::<String>::new()
+                .with_prompt(role)
+                .default(current.to_string())
+                .validate_with(|value: &String| {
+                    if slots.contains(&value.as_str()) {
+                        Ok(())
+                    } else {
+                        Err("Enter a palette slot (base00-base0F)")
+                    }
+                })
+                .interact_text()?;
+            roles.insert(role.to_string(), slot);
+        }
+        overrides.semantic = Some(roles);
+    }
+    Ok(overrides)
+}
+
+/// Create a theme file in the user themes directory, asking for its colors
+/// or, with `--json`, reading them from stdin as a JSON object
+pub fn theme_new(name: &str, from: Option<&str>, force: bool, json: bool) -> Result<()> {
+    let config = Config::load()?;
+    let loader = config.get_theme_loader();
+    let overrides = if json {
+        let mut input = String::new();
+        io::Read::read_to_string(&mut io::stdin(), &mut input)
+            .context("Failed to read the palette from stdin")?;
+        theme_overrides_from_json(&input)?
+    } else if io::stdin().is_terminal() {
+        prompt_theme_overrides(&build_theme(
+            &loader,
+            name,
+            from,
+            &ThemeOverrides::default(),
+        )?)?
+    } else {
+        bail!(
+            "Run `lst themes new` in a terminal, or pass the palette as JSON on stdin with --json"
+        );
+    };
+    let theme = build_theme(&loader, name, from, &overrides)?;
+    let dir = loader
+        .user_themes_dir()
+        .context("Could not determine the themes directory (set paths.themes_dir)")?;
+    let path = loader.save_theme_in(dir, &theme, force)?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "status": "success",
+                "theme": name,
+                "path": path,
+            })
+        );
+    } else {
+        println!("Created theme {} at {}", name.cyan(), path.display());
+        println!("  Apply it with: lst themes apply {}", name);
+    }
+    Ok(())
+}
+
 /// Generate CSS from current theme (debug command)
 pub fn theme_generate_css(json: bool) -> Result<()> {
     let config = get_config();
@@ -2909,3 +6616,1310 @@ pub async fn user_info(email: &str, json: bool) -> Result<()> {
 
     Ok(())
 }
+
+// Configuration commands
+
+/// Print where config, state, content and the sync database live
+pub fn config_path(json: bool) -> Result<()> {
+    let state = State::load().unwrap_or_default();
+    let paths = lst_core::config::ResolvedPaths::resolve(get_config(), &state)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&paths)?);
+        return Ok(());
+    }
+
+    for (label, path) in [
+        ("config: ", &paths.config),
+        ("state:  ", &paths.state),
+        ("content:", &paths.content),
+        ("sync db:", &paths.sync_db),
+    ] {
+        let missing = if path.exists() {
+            String::new()
+        } else {
+            format!(" {}", "(missing)".dimmed())
+        };
+        println!("{} {}{}", label, path.display().to_string().cyan(), missing);
+    }
+
+    Ok(())
+}
+
+/// Export lists and notes (and optionally media) to a gzip tarball at `out`
+pub fn export_content(out: &Path, include_media: bool, json: bool) -> Result<()> {
+    let content_dir = storage::get_content_dir()?;
+    let media_dir = if include_media {
+        Some(storage::get_media_dir()?)
+    } else {
+        None
+    };
+    let manifest = storage::export::export_archive(&content_dir, media_dir.as_deref(), out)?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "archive": out,
+                "files": manifest.entries.len(),
+                "bytes": manifest.entries.iter().map(|e| e.size).sum::<u64>(),
+                "entries": manifest.entries,
+            })
+        );
+    } else {
+        let documents = manifest
+            .entries
+            .iter()
+            .filter(|e| e.doc_id.is_some())
+            .count();
+        let media = manifest.entries.len() - documents;
+        print!("Exported {} documents", documents);
+        if include_media {
+            print!(" and {} media files", media);
+        }
+        println!(" to {}", out.display().to_string().cyan());
+    }
+    Ok(())
+}
+
+/// Import an archive written by `export_content` into the content directory
+pub fn import_content(
+    archive: &Path,
+    overwrite: bool,
+    kind: Option<&str>,
+    json: bool,
+) -> Result<()> {
+    let content_dir = storage::get_content_dir()?;
+    let media_dir = if kind.is_none() {
+        Some(storage::get_media_dir()?)
+    } else {
+        None
+    };
+    let report = storage::export::import_archive(
+        archive,
+        &content_dir,
+        media_dir.as_deref(),
+        kind,
+        overwrite,
+    )?;
+    let refused = !report.conflicts.is_empty() && !overwrite;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else if refused {
+        eprintln!("{}", "These files already exist:".red());
+        for path in &report.conflicts {
+            eprintln!("  {}", path);
+        }
+    } else {
+        for (path, error) in &report.invalid {
+            eprintln!("{} {}: {}", "warning:".yellow(), path, error);
+        }
+        let replaced = if overwrite { report.conflicts.len() } else { 0 };
+        println!(
+            "Imported {} files ({} replaced) from {}",
+            report.imported.len(),
+            replaced,
+            archive.display().to_string().cyan()
+        );
+    }
+
+    if refused {
+        bail!(
+            "Import refused: {} file(s) already exist. Use --overwrite to replace them",
+            report.conflicts.len()
+        );
+    }
+    Ok(())
+}
+
+/// Check the installation for common problems, repairing them with `fix`
+pub async fn doctor(fix: bool, json: bool) -> Result<()> {
+    use lst_core::config::ResolvedPaths;
+    use lst_core::doctor::{self, CheckResult, CheckStatus};
+
+    let mut state = State::load().unwrap_or_default();
+    // Resolve against the defaults first: the config file itself may be broken
+    let config_path = ResolvedPaths::resolve(&Config::default(), &state)?.config;
+    let mut results = Vec::new();
+
+    let mut replace_invalid = false;
+    if fix && !json && io::stdin().is_terminal() && doctor::config_is_invalid(&config_path) {
+        use dialoguer::Confirm;
+        let prompt = format!(
+            "Config at {} is invalid. Replace it with defaults? (a backup is kept)",
+            config_path.display()
+        );
+        replace_invalid = Confirm::new()
+            .with_prompt(prompt)
+            .default(false)
+            .interact()?;
+    }
+    let config_check = doctor::check_config_file(&config_path, fix, replace_invalid);
+    let config_ok = matches!(config_check.status, CheckStatus::Ok | CheckStatus::Fixed);
+    results.push(config_check);
+
+    let config = Config::load_from(&config_path).unwrap_or_default();
+    let paths = ResolvedPaths::resolve(&config, &state)?;
+    results.extend(doctor::check_content_dirs(&paths.content, fix));
+
+    // Tidying goes through the regular storage layer, which needs a loadable config
+    if fix && config_ok && paths.content.join("lists").is_dir() {
+        let mut tidied = 0;
+        let mut errors = Vec::new();
+        for entry in storage::list_lists_with_info()? {
+            let name = entry.relative_path;
+            let path = &entry.full_path;
+            let result = tidy_file(path, false, |original| {
+                let tidied = storage::markdown::tidy_list_markdown(original, path)?;
+                if tidied != original {
+                    // Keep the pre-image so the tidy can be undone per list
+                    storage::undo::record(&name, "doctor tidy", original)?;
+                }
+                Ok(tidied)
+            });
+            match result {
+                Ok(Some(_)) => tidied += 1,
+                Ok(None) => {}
+                Err(e) => errors.push(format!("{}: {}", name, e)),
+            }
+        }
+        results.push(if !errors.is_empty() {
+            CheckResult::new("tidy", CheckStatus::FixFailed, errors.join("; "))
+        } else if tidied > 0 {
+            CheckResult::new(
+                "tidy",
+                CheckStatus::Fixed,
+                format!("tidied {} list(s); see 'lst undo --list' to revert", tidied),
+            )
+        } else {
+            CheckResult::new("tidy", CheckStatus::Ok, "all lists already tidy")
+        });
+    }
+
+    if state.get_auth_token().is_some() {
+        results.push(if state.is_jwt_valid() && !state.needs_jwt_refresh() {
+            CheckResult::new("auth", CheckStatus::Ok, "JWT is valid")
+        } else if !fix {
+            CheckResult::new("auth", CheckStatus::Problem, "JWT expired or expiring soon")
+        } else {
+            match refresh_jwt_token(&config, &mut state).await {
+                Ok(()) => CheckResult::new("auth", CheckStatus::Fixed, "refreshed JWT"),
+                Err(e) => CheckResult::new("auth", CheckStatus::FixFailed, e.to_string()),
+            }
+        });
+    }
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "fix": fix,
+                "checks": results,
+            }))?
+        );
+        return Ok(());
+    }
+
+    for result in &results {
+        let mark = match result.status {
+            CheckStatus::Ok => format!("{:<8}", "ok").green(),
+            CheckStatus::Problem => format!("{:<8}", "problem").yellow(),
+            CheckStatus::Fixed => format!("{:<8}", "fixed").cyan(),
+            CheckStatus::FixFailed => format!("{:<8}", "failed").red(),
+        };
+        println!("{} {:<12} {}", mark, result.check, result.detail);
+    }
+    if results.iter().any(|r| r.status == CheckStatus::Problem) && !fix {
+        println!(
+            "\n{}",
+            "Run 'lst doctor --fix' to repair what can be fixed automatically".dimmed()
+        );
+    }
+
+    Ok(())
+}
+
+/// Print the effective configuration (environment overrides applied, secrets
+/// redacted) as TOML or JSON
+pub fn config_show(json: bool) -> Result<()> {
+    let config = get_config().for_display(|name| std::env::var(name).ok());
+    if json {
+        println!("{}", serde_json::to_string_pretty(&config)?);
+    } else {
+        print!(
+            "{}",
+            toml::to_string_pretty(&config).context("Failed to serialize config")?
+        );
+    }
+    Ok(())
+}
+
+/// Handle `lst config set`: update one key in the config file in place
+pub fn config_set(key: &str, value: &str, json: bool) -> Result<()> {
+    // Loading first writes the default config when none exists yet
+    let config = Config::load()?;
+    let state = State::load().unwrap_or_default();
+    let path = lst_core::config::ResolvedPaths::resolve(&config, &state)?.config;
+    Config::set_value_at(&path, key, value)?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({ "key": key, "value": value, "path": path })
+        );
+    } else {
+        println!(
+            "Set {} = {} in {}",
+            key.cyan(),
+            value.green(),
+            path.display().to_string().dimmed()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Minimal HTTP stub: `/api/health` answers with `health`, every other path
+    /// requires `Bearer good-jwt`. Returns the base URL
+    async fn stub_server(health: u16) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 4096];
+                let n = socket.read(&mut buf).await.unwrap();
+                let request = String::from_utf8_lossy(&buf[..n]).to_lowercase();
+                let status = if request.contains(" /api/health ") {
+                    health
+                } else if request.contains("authorization: bearer good-jwt") {
+                    200
+                } else {
+                    401
+                };
+                let response = format!(
+                    "HTTP/1.1 {} Stub\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    status
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        base
+    }
+
+    #[tokio::test]
+    async fn test_check_server_ok_and_auth_failures() {
+        let base = stub_server(200).await;
+        let client = reqwest::Client::new();
+
+        assert_eq!(
+            check_server(&client, &base, Some("good-jwt")).await,
+            ServerCheck::Ok
+        );
+        let rejected = check_server(&client, &base, Some("expired-jwt")).await;
+        assert!(matches!(rejected, ServerCheck::AuthFailed(_)));
+        assert!(rejected.message().starts_with("Authentication failed"));
+        assert!(matches!(
+            check_server(&client, &base, None).await,
+            ServerCheck::AuthFailed(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_http_client_uses_configured_timeouts_and_reports_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "[sync]\nrequest_timeout_seconds = 5\n").unwrap();
+        let config = Config::load_from(&path).unwrap();
+        assert_eq!(config.request_timeout(), std::time::Duration::from_secs(5));
+
+        let (url, _) = silent_server().await;
+        let timeout = std::time::Duration::from_millis(200);
+        let started = std::time::Instant::now();
+        let err = build_http_client(timeout, timeout)
+            .get(&url)
+            .send()
+            .await
+            .unwrap_err();
+        assert!(err.is_timeout());
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        let message = timeout_error(timeout).to_string();
+        assert!(message.starts_with("The server did not respond within 200ms"));
+    }
+
+    /// Server that accepts connections but never answers; returns its URL and
+    /// the number of connections made so far
+    async fn silent_server() -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/api/content/x", listener.local_addr().unwrap());
+        let hits = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = hits.clone();
+        tokio::spawn(async move {
+            let mut open = Vec::new();
+            loop {
+                open.push(listener.accept().await.unwrap().0);
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        (url, hits)
+    }
+
+    #[tokio::test]
+    async fn test_get_is_retried_but_post_is_not() {
+        let timeout = std::time::Duration::from_millis(150);
+        let client = build_http_client(timeout, timeout);
+        let settle = || tokio::time::sleep(std::time::Duration::from_millis(100));
+
+        let (url, hits) = silent_server().await;
+        let (err, attempts) = send_with_retry(&reqwest::Method::GET, || client.get(&url), 2)
+            .await
+            .unwrap_err();
+        settle().await;
+        assert!(err.is_timeout());
+        assert_eq!(attempts, 3);
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+        let message = retries_exhausted_error(attempts, &err).to_string();
+        assert!(message.starts_with("Gave up after 3 attempts (the last attempt timed out)"));
+
+        let (url, hits) = silent_server().await;
+        let (err, attempts) = send_with_retry(&reqwest::Method::POST, || client.post(&url), 2)
+            .await
+            .unwrap_err();
+        settle().await;
+        assert!(err.is_timeout());
+        assert_eq!(attempts, 1);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_check_server_unreachable() {
+        let client = reqwest::Client::new();
+
+        // Nothing listening on the port
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let down = check_server(&client, &base, Some("good-jwt")).await;
+        assert!(matches!(down, ServerCheck::Unreachable(_)));
+        assert!(down.message().starts_with("Server unreachable"));
+
+        // Listening, but unhealthy: reported as a server problem, not auth
+        let base = stub_server(503).await;
+        assert!(matches!(
+            check_server(&client, &base, Some("expired-jwt")).await,
+            ServerCheck::Unreachable(_)
+        ));
+    }
+
+    #[test]
+    fn test_tidy_dry_run_reports_diffs_without_writing() {
+        let dir = tempfile::tempdir().unwrap();
+        let tidy = dir.path().join("tidy.md");
+        let untidy = dir.path().join("untidy.md");
+        let tidy_content = tidy_note_content("tidy", "body\n").unwrap();
+        std::fs::write(&tidy, &tidy_content).unwrap();
+        std::fs::write(&untidy, "just a body\n").unwrap();
+
+        let mut diffs = Vec::new();
+        for (name, path) in [("tidy", &tidy), ("untidy", &untidy)] {
+            if let Some(diff) =
+                tidy_file(path, true, |content| tidy_note_content(name, content)).unwrap()
+            {
+                diffs.push(diff);
+            }
+        }
+        assert_eq!(diffs.len(), 1);
+        assert!(diffs[0].contains("+title: untidy"));
+        assert!(diffs[0].contains(" just a body"));
+        assert_eq!(std::fs::read_to_string(&untidy).unwrap(), "just a body\n");
+        assert_eq!(std::fs::read_to_string(&tidy).unwrap(), tidy_content);
+
+        let diff = tidy_file(&untidy, false, |c| tidy_note_content("untidy", c)).unwrap();
+        assert!(diff.is_some());
+        assert!(std::fs::read_to_string(&untidy)
+            .unwrap()
+            .starts_with("---\ntitle: untidy\n"));
+    }
+
+    #[test]
+    fn test_inspect_jwt_reads_claims_without_secret() {
+        let exp = chrono::DateTime::from_timestamp(1_900_000_000, 0).unwrap();
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &serde_json::json!({ "sub": "ada@example.com", "exp": exp.timestamp() }),
+            &jsonwebtoken::EncodingKey::from_secret(b"server-only-secret"),
+        )
+        .unwrap();
+
+        let now = exp - chrono::Duration::minutes(90);
+        let info = inspect_jwt(&token, now).unwrap();
+        assert_eq!(info.sub.as_deref(), Some("ada@example.com"));
+        assert_eq!(info.expires_at, Some(exp));
+        assert_eq!(info.remaining_seconds, Some(90 * 60));
+        assert_eq!(info.claims["sub"], "ada@example.com");
+
+        let later = inspect_jwt(&token, exp + chrono::Duration::seconds(5)).unwrap();
+        assert_eq!(later.remaining_seconds, Some(-5));
+        assert!(inspect_jwt("not-a-jwt", now).is_err());
+    }
+
+    #[test]
+    fn test_stats_counts_items_across_categories_and_note_words() {
+        use storage::backend::{MemoryStorage, Storage};
+
+        let storage = MemoryStorage::new();
+        let mut groceries = lst_core::models::List::new("groceries".to_string());
+        groceries.add_item("milk".to_string());
+        groceries.add_item_to_category("apples".to_string(), Some("fruit"));
+        groceries.add_item_to_category("pears".to_string(), Some("fruit"));
+        groceries.all_items_mut().next().unwrap().status = ItemStatus::Done;
+        groceries.categories[0].items[1].status = ItemStatus::Done;
+        storage.write_list("groceries", &groceries, "seed").unwrap();
+        let empty = lst_core::models::List::new("empty".to_string());
+        storage.write_list("empty", &empty, "seed").unwrap();
+        let ideas = "---\ntitle: ideas\ntags: [a]\n---\n\nthree small words\n";
+        storage.write_note("ideas", ideas).unwrap();
+        storage.write_note("plain", "no frontmatter here").unwrap();
+
+        let stats = collect_stats(&storage).unwrap();
+        assert_eq!(stats.lists, 2);
+        assert_eq!(stats.notes, 2);
+        assert_eq!(stats.items, 3);
+        assert_eq!(stats.done_items, 2);
+        assert_eq!(stats.note_words, 6);
+        let groceries = stats
+            .per_list
+            .iter()
+            .find(|l| l.name == "groceries")
+            .unwrap();
+        assert_eq!((groceries.done, groceries.total), (2, 3));
+        let empty = stats.per_list.iter().find(|l| l.name == "empty").unwrap();
+        assert_eq!((empty.done, empty.total), (0, 0));
+    }
+
+    #[test]
+    fn test_concurrent_stats_and_tidy_over_many_files_are_complete_and_ordered() {
+        use storage::backend::{FsStorage, Storage};
+
+        let dir = tempfile::tempdir().unwrap();
+        let storage = FsStorage::new(dir.path());
+        for i in 0..300 {
+            let name = format!("list-{:03}", i);
+            let mut list = lst_core::models::List::new(name.clone());
+            for j in 0..i % 5 {
+                list.add_item(format!("item {}", j));
+            }
+            if let Some(item) = list.all_items_mut().next() {
+                item.status = ItemStatus::Done;
+            }
+            storage.write_list(&name, &list, "seed").unwrap();
+            storage
+                .write_note(&format!("note-{:03}", i), &"word ".repeat(i % 7))
+                .unwrap();
+        }
+        std::fs::write(dir.path().join("lists/broken.md"), [0xff, 0xfe]).unwrap();
+        std::fs::write(dir.path().join("notes/broken.md"), [0xff, 0xfe]).unwrap();
+
+        let stats = collect_stats(&storage).unwrap();
+        assert_eq!((stats.lists, stats.notes), (300, 300));
+        assert_eq!(stats.items, (0..300).map(|i| i % 5).sum::<usize>());
+        assert_eq!(stats.done_items, (0..300).filter(|i| i % 5 > 0).count());
+        assert_eq!(stats.note_words, (0..300).map(|i| i % 7).sum::<usize>());
+        let names: Vec<&str> = stats.per_list.iter().map(|l| l.name.as_str()).collect();
+        let expected: Vec<String> = (0..300).map(|i| format!("list-{:03}", i)).collect();
+        assert_eq!(names, expected);
+        assert_eq!(stats.errors.len(), 2, "{:?}", stats.errors);
+        assert!(
+            stats.errors[0].contains("list 'broken'"),
+            "{:?}",
+            stats.errors
+        );
+        assert!(
+            stats.errors[1].contains("note 'broken'"),
+            "{:?}",
+            stats.errors
+        );
+
+        let entries: Vec<storage::FileEntry> = (0..300)
+            .map(|i| format!("note-{:03}", i))
+            .chain(["broken".to_string()])
+            .map(|name| storage::FileEntry {
+                full_path: dir.path().join("notes").join(format!("{}.md", name)),
+                relative_path: name.clone(),
+                name,
+            })
+            .collect();
+        let tidy = |_: &storage::FileEntry, content: &str| Ok(format!("{}\n", content.trim_end()));
+        let dry_run = tidy_entries(&entries, true, tidy);
+        let paths: Vec<&str> = dry_run.iter().map(|(path, _)| *path).collect();
+        let expected: Vec<&str> = entries.iter().map(|e| e.relative_path.as_str()).collect();
+        assert_eq!(paths, expected);
+        assert!(dry_run[..300]
+            .iter()
+            .all(|(_, outcome)| matches!(outcome, Ok(Some(_)))));
+        assert!(dry_run[300].1.is_err());
+
+        let tidied = tidy_entries(&entries, false, tidy);
+        assert_eq!(tidied.iter().filter(|(_, o)| o.is_err()).count(), 1);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("notes/note-002.md")).unwrap(),
+            "word word\n"
+        );
+        let again = tidy_entries(&entries, true, tidy);
+        assert!(again[..300].iter().all(|(_, o)| matches!(o, Ok(None))));
+    }
+
+    /// Minimal sync server stub holding `snapshots`; answers the document
+    /// list, snapshot requests and pings. Returns the WebSocket URL
+    async fn stub_sync_server(snapshots: Vec<(uuid::Uuid, Vec<u8>)>) -> String {
+        let documents = snapshots
+            .into_iter()
+            .map(|(doc_id, snapshot)| (doc_id, String::new(), snapshot))
+            .collect();
+        stub_sync_server_with_filenames(documents).await
+    }
+
+    /// Sync server stub like [`stub_sync_server`] listing each document under
+    /// its (encrypted) filename. Accepts any number of connections
+    async fn stub_sync_server_with_filenames(
+        documents: Vec<(uuid::Uuid, String, Vec<u8>)>,
+    ) -> String {
+        let documents = documents
+            .into_iter()
+            .map(|(doc_id, filename, snapshot)| {
+                let hash = lst_proto::snapshot_hash(&snapshot);
+                (doc_id, filename, snapshot, hash)
+            })
+            .collect();
+        stub_sync_server_with_hashes(documents).await
+    }
+
+    /// Sync server stub like [`stub_sync_server_with_filenames`] sending each
+    /// snapshot with the given hash
+    async fn stub_sync_server_with_hashes(
+        documents: Vec<(uuid::Uuid, String, Vec<u8>, String)>,
+    ) -> String {
+        use futures_util::{SinkExt, StreamExt};
+        use lst_proto::{ClientMessage, DocumentInfo, ServerMessage};
+        use tokio_tungstenite::tungstenite::Message;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/api/sync", listener.local_addr().unwrap());
+        let documents = std::sync::Arc::new(documents);
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let documents = documents.clone();
+                tokio::spawn(async move {
+                    let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+                    while let Some(Ok(Message::Text(text))) = ws.next().await {
+                        let reply = match serde_json::from_str(&text).unwrap() {
+                            ClientMessage::RequestDocumentList { .. } => {
+                                ServerMessage::DocumentList {
+                                    documents: documents
+                                        .iter()
+                                        .map(|(doc_id, filename, ..)| DocumentInfo {
+                                            doc_id: *doc_id,
+                                            filename: filename.clone(),
+                                            updated_at: Utc::now(),
+                                        })
+                                        .collect(),
+                                }
+                            }
+                            ClientMessage::RequestSnapshot { doc_id } => {
+                                let (_, filename, snapshot, hash) =
+                                    documents.iter().find(|(id, ..)| *id == doc_id).unwrap();
+                                ServerMessage::Snapshot {
+                                    doc_id,
+                                    filename: filename.clone(),
+                                    snapshot: snapshot.clone(),
+                                    hash: Some(hash.clone()),
+                                }
+                            }
+                            ClientMessage::Ping { id } => ServerMessage::Pong { id },
+                            _ => continue,
+                        };
+                        let text = serde_json::to_string(&reply).unwrap();
+                        ws.send(Message::Text(text)).await.unwrap();
+                    }
+                });
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_verify_reports_mismatched_documents() {
+        use lst_core::sync::{snapshot_from_content, DocumentKind};
+
+        let dir = tempfile::tempdir().unwrap();
+        let doc = |name: &str, content: &str| {
+            let full_path = dir.path().join(name);
+            std::fs::write(&full_path, content).unwrap();
+            SyncedDoc {
+                doc_id: uuid::Uuid::new_v4(),
+                relative_path: name.to_string(),
+                full_path,
+                kind: DocumentKind::List,
+            }
+        };
+        let same = doc("same.md", "- [ ] milk ^abc12\n");
+        let changed = doc("changed.md", "- [x] eggs ^def34\n");
+        let local_only = doc("local.md", "- [ ] bread ^ghi56\n");
+        let snapshot = |content| snapshot_from_content(content, DocumentKind::List).unwrap();
+
+        let url = stub_sync_server(vec![
+            (same.doc_id, snapshot("- [ ] milk ^abc12\n")),
+            (changed.doc_id, snapshot("- [ ] eggs ^def34\n")),
+        ])
+        .await;
+        let results = verify_against_server(&url, "jwt", &[same, changed, local_only])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            results,
+            [
+                ("same.md".to_string(), VerifyStatus::Match),
+                ("changed.md".to_string(), VerifyStatus::Mismatch),
+                ("local.md".to_string(), VerifyStatus::MissingOnServer),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_diff_shows_local_changes_against_server_copy() {
+        use lst_core::sync::{snapshot_from_content, DocumentKind};
+
+        let dir = tempfile::tempdir().unwrap();
+        let doc = |name: &str, kind, content: &str| {
+            let full_path = dir.path().join(name);
+            std::fs::write(&full_path, content).unwrap();
+            SyncedDoc {
+                doc_id: uuid::Uuid::new_v4(),
+                relative_path: name.to_string(),
+                full_path,
+                kind,
+            }
+        };
+        let list = doc(
+            "groceries.md",
+            DocumentKind::List,
+            "- [x] milk ^abc12\n- [ ] eggs ^def34\n",
+        );
+        let note = doc("ideas.md", DocumentKind::Note, "# Ideas\n\nfirst\n");
+        let server_list = "- [ ] milk ^abc12\n- [ ] eggs ^def34\n";
+        let server_list = snapshot_from_content(server_list, DocumentKind::List).unwrap();
+        let server_note = snapshot_from_content("# Ideas\n\nfirst\n", DocumentKind::Note).unwrap();
+
+        let url = stub_sync_server(vec![(list.doc_id, server_list)]).await;
+        let diff = diff_against_server(&url, "jwt", &list)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(diff.starts_with("--- server/groceries.md\n+++ local/groceries.md\n"));
+        assert!(
+            diff.contains("\n-- [ ] milk ^abc12\n+- [x] milk ^abc12\n"),
+            "{}",
+            diff
+        );
+        assert!(diff.contains("\n - [ ] eggs ^def34"), "{}", diff);
+
+        let url = stub_sync_server(vec![(note.doc_id, server_note)]).await;
+        assert_eq!(diff_against_server(&url, "jwt", &note).await.unwrap(), None);
+
+        let found = find_synced_document(vec![list.clone(), note.clone()], "ideas").unwrap();
+        assert_eq!(found.doc_id, note.doc_id);
+        assert!(find_synced_document(vec![list, note], "nothing").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sync_pull_writes_server_documents_and_skips_matching_ones() {
+        use lst_core::crypto::encrypt_filename;
+        use lst_core::sync::{snapshot_from_content, DocumentKind};
+
+        let key = [5u8; 32];
+        let list = "- [ ] milk ^abc12\n- [x] eggs ^def34\n";
+        let note = "---\ntitle: ideas\n---\n\n# Ideas\n\nfirst\n";
+        let url = stub_sync_server_with_filenames(vec![
+            (
+                uuid::Uuid::new_v4(),
+                encrypt_filename("lists/groceries.md", &key).unwrap(),
+                snapshot_from_content(list, DocumentKind::List).unwrap(),
+            ),
+            (
+                uuid::Uuid::new_v4(),
+                encrypt_filename("notes/work/ideas.md", &key).unwrap(),
+                snapshot_from_content(note, DocumentKind::Note).unwrap(),
+            ),
+        ])
+        .await;
+
+        // The local list already matches the server copy
+        let dir = tempfile::tempdir().unwrap();
+        let groceries = dir.path().join("lists/groceries.md");
+        std::fs::create_dir_all(groceries.parent().unwrap()).unwrap();
+        let local_list = list;
+        std::fs::write(&groceries, local_list).unwrap();
+
+        let report = pull_server_documents(&url, "jwt", &key, dir.path(), false)
+            .await
+            .unwrap();
+        assert_eq!(
+            report,
+            SyncPullReport {
+                pulled: vec!["notes/work/ideas.md".to_string()],
+                skipped: vec!["lists/groceries.md".to_string()],
+                conflicts: vec![],
+                failed: vec![],
+            }
+        );
+        assert_eq!(std::fs::read_to_string(&groceries).unwrap(), local_list);
+        let ideas = std::fs::read_to_string(dir.path().join("notes/work/ideas.md")).unwrap();
+        assert_eq!(ideas, note);
+
+        let again = pull_server_documents(&url, "jwt", &key, dir.path(), false)
+            .await
+            .unwrap();
+        assert_eq!(again.pulled.len(), 0);
+        assert_eq!(again.skipped.len(), 2);
+
+        // Filenames that don't decrypt with the key are reported, not written
+        let other = pull_server_documents(&url, "jwt", &[6u8; 32], dir.path(), false)
+            .await
+            .unwrap();
+        assert_eq!(other.failed.len(), 2, "{:?}", other.failed);
+
+        // Unsynced local edits are kept unless forced
+        let edited = "- [ ] milk ^abc12\n- [ ] bread ^ghi56\n";
+        std::fs::write(&groceries, edited).unwrap();
+        let report = pull_server_documents(&url, "jwt", &key, dir.path(), false)
+            .await
+            .unwrap();
+        assert_eq!(report.conflicts, ["lists/groceries.md"]);
+        assert!(report.pulled.is_empty());
+        assert_eq!(std::fs::read_to_string(&groceries).unwrap(), edited);
+
+        let report = pull_server_documents(&url, "jwt", &key, dir.path(), true)
+            .await
+            .unwrap();
+        assert_eq!(report.pulled, ["lists/groceries.md"]);
+        assert!(report.conflicts.is_empty());
+        assert!(std::fs::read_to_string(&groceries)
+            .unwrap()
+            .contains("[x] eggs"));
+    }
+
+    #[tokio::test]
+    async fn test_sync_pull_rejects_snapshots_not_matching_their_hash() {
+        use lst_core::crypto::encrypt_filename;
+        use lst_core::sync::{snapshot_from_content, DocumentKind};
+
+        let key = [5u8; 32];
+        let snapshot = snapshot_from_content("- [ ] milk ^abc12\n", DocumentKind::List).unwrap();
+        let url = stub_sync_server_with_hashes(vec![(
+            uuid::Uuid::new_v4(),
+            encrypt_filename("lists/groceries.md", &key).unwrap(),
+            snapshot,
+            lst_proto::snapshot_hash(b"something else"),
+        )])
+        .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let report = pull_server_documents(&url, "jwt", &key, dir.path(), true)
+            .await
+            .unwrap();
+        assert_eq!(
+            report.failed,
+            ["lists/groceries.md: snapshot does not match its hash"]
+        );
+        assert!(!dir.path().join("lists/groceries.md").exists());
+    }
+
+    #[tokio::test]
+    async fn test_list_docs_pages_through_and_decrypts_filenames() {
+        use futures_util::{SinkExt, StreamExt};
+        use lst_core::crypto::encrypt_filename;
+        use lst_proto::{ClientMessage, DocumentInfo, ServerMessage};
+        use tokio_tungstenite::tungstenite::Message;
+
+        let key = [7u8; 32];
+        let updated_at = chrono::DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let documents = vec![
+            DocumentInfo {
+                doc_id: uuid::Uuid::new_v4(),
+                filename: encrypt_filename("lists/groceries.md", &key).unwrap(),
+                updated_at,
+            },
+            DocumentInfo {
+                doc_id: uuid::Uuid::new_v4(),
+                filename: encrypt_filename("notes/ideas.md", &[8u8; 32]).unwrap(),
+                updated_at,
+            },
+        ];
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/api/sync", listener.local_addr().unwrap());
+        let served = documents.clone();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                let ClientMessage::RequestDocumentList { after, .. } =
+                    serde_json::from_str(&text).unwrap()
+                else {
+                    continue;
+                };
+                assert_eq!(after, None);
+                let reply = ServerMessage::DocumentList {
+                    documents: served.clone(),
+                };
+                let text = serde_json::to_string(&reply).unwrap();
+                ws.send(Message::Text(text)).await.unwrap();
+            }
+        });
+
+        let fetched = fetch_server_documents(&url, "jwt").await.unwrap();
+        let decoded = decode_server_documents(fetched, &key);
+        assert_eq!(
+            decoded,
+            [
+                ServerDocument {
+                    doc_id: documents[0].doc_id,
+                    path: Some("lists/groceries.md".to_string()),
+                    updated_at,
+                },
+                ServerDocument {
+                    doc_id: documents[1].doc_id,
+                    path: None,
+                    updated_at,
+                },
+            ]
+        );
+        let json = serde_json::to_value(&decoded[0]).unwrap();
+        assert_eq!(json["path"], "lists/groceries.md");
+        assert_eq!(json["updated_at"], "2024-05-01T12:00:00Z");
+    }
+
+    #[test]
+    fn test_render_tree_groups_paths_under_their_directories() {
+        let paths: Vec<String> = [
+            "todo",
+            "groceries/pharmacy",
+            "work/projects/alpha",
+            "groceries/weekly",
+            "work/inbox",
+            "work/projects/beta",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+        let lines = render_tree(&paths, &|name, _| name.to_string());
+        assert_eq!(
+            lines,
+            [
+                "todo",
+                "groceries/",
+                "  pharmacy",
+                "  weekly",
+                "work/",
+                "  projects/",
+                "    alpha",
+                "    beta",
+                "  inbox",
+            ]
+        );
+
+        let label = |name: &str, path: &str| format!("{} <{}>", name, path);
+        let lines = render_tree(&paths[1..2], &label);
+        assert_eq!(lines, ["groceries/", "  pharmacy <groceries/pharmacy>"]);
+    }
+
+    /// Forces colored output on or off until dropped. The override is process
+    /// wide, so tests holding one run one at a time
+    struct ColorOverride {
+        _lock: std::sync::MutexGuard<'static, ()>,
+    }
+
+    impl ColorOverride {
+        fn set(color: bool) -> Self {
+            static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+            let lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            colored::control::set_override(color);
+            ColorOverride { _lock: lock }
+        }
+    }
+
+    impl Drop for ColorOverride {
+        fn drop(&mut self) {
+            colored::control::unset_override();
+        }
+    }
+
+    fn render_fixture() -> List {
+        let mut list = List::new("groceries".to_string());
+        list.add_item("milk".to_string());
+        list.add_item("bread".to_string());
+        let due = NaiveDate::from_ymd_opt(2024, 1, 1);
+        list.add_item_with_due("cheese".to_string(), Some("dairy"), due);
+        list.uncategorized_items[1].status = ItemStatus::Done;
+        list
+    }
+
+    #[test]
+    fn test_render_list_never_mode_has_no_ansi_codes() {
+        let ui = UiConfig {
+            color_mode: ColorMode::Never,
+            ..UiConfig::default()
+        };
+        let color = ColorOverride::set(ui.color_mode.use_color(false, true));
+        let today = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        let out = render_list(&render_fixture(), &ui, true, None, None, today, None);
+        drop(color);
+
+        assert!(!out.contains('\x1b'), "unexpected ANSI codes in {:?}", out);
+        assert!(out.contains("#1 [ ] milk\n"));
+        assert!(out.contains("#2 [x] bread\n"));
+        assert!(out.contains("#3 [ ] cheese (due 2024-01-01)\n"));
+    }
+
+    #[test]
+    fn test_render_list_colorblind_mode_uses_alternate_glyphs() {
+        let ui = UiConfig {
+            color_mode: ColorMode::Colorblind,
+            ..UiConfig::default()
+        };
+        let today = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        let out = render_list(&render_fixture(), &ui, true, None, None, today, None);
+
+        assert!(out.contains("•"));
+        assert!(out.contains("✓"));
+        assert!(!out.contains("[x]") && !out.contains("[ ]"));
+        assert!(out.contains("cheese (overdue, due 2024-01-01)"));
+        assert!(ColorMode::Colorblind.use_color(false, true));
+        assert!(!ColorMode::Colorblind.use_color(true, true));
+        assert!(!ColorMode::Auto.use_color(false, false));
+    }
+
+    #[test]
+    fn test_render_list_status_filter_keeps_item_numbers() {
+        let ui = UiConfig {
+            color_mode: ColorMode::Never,
+            ..UiConfig::default()
+        };
+        let _color = ColorOverride::set(false);
+        let today = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let list = render_fixture();
+
+        let todo = render_list(&list, &ui, true, None, Some(ItemStatus::Todo), today, None);
+        assert!(todo.contains("#1 [ ] milk"));
+        assert!(todo.contains("#3 [ ] cheese"));
+        assert!(!todo.contains("bread"));
+
+        let done = render_list(&list, &ui, true, None, Some(ItemStatus::Done), today, None);
+        assert!(done.contains("#2 [x] bread"));
+        assert!(!done.contains("milk") && !done.contains("cheese"));
+        assert!(!done.contains("dairy"), "empty categories are hidden");
+    }
+
+    #[test]
+    fn test_render_list_shows_item_notes_unless_clean() {
+        let ui = UiConfig {
+            color_mode: ColorMode::Never,
+            ..UiConfig::default()
+        };
+        let _color = ColorOverride::set(false);
+        let today = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let mut list = render_fixture();
+        list.uncategorized_items[0].note = Some("oat, not dairy\n2 litres".to_string());
+
+        let out = render_list(&list, &ui, false, None, None, today, None);
+        assert!(out.contains("milk ^"), "{}", out);
+        assert!(out.contains("\n    oat, not dairy\n    2 litres\n#2 [x] bread"));
+
+        let clean = render_list(&list, &ui, true, None, None, today, None);
+        assert!(!clean.contains("oat, not dairy"));
+    }
+
+    #[test]
+    fn test_render_list_shows_item_ages_when_asked() {
+        let ui = UiConfig {
+            color_mode: ColorMode::Never,
+            ..UiConfig::default()
+        };
+        let _color = ColorOverride::set(false);
+        let today = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        let now = today.and_hms_opt(12, 0, 0).unwrap().and_utc();
+        let mut list = render_fixture();
+        list.uncategorized_items[0].created = now - chrono::Duration::days(3);
+        list.uncategorized_items[1].created = now - chrono::Duration::hours(5);
+        list.categories[0].items[0].created = now - chrono::Duration::seconds(30);
+
+        let out = render_list(&list, &ui, true, None, None, today, Some(now));
+        assert!(out.contains("#1 [ ] milk 3d\n"), "{}", out);
+        assert!(out.contains("#2 [x] bread 5h\n"));
+        assert!(out.contains("#3 [ ] cheese (due 2024-01-01) 0m\n"));
+
+        let out = render_list(&list, &ui, true, None, None, today, None);
+        assert!(out.contains("#1 [ ] milk\n"));
+    }
+
+    #[test]
+    fn test_cat_text_renders_lists_and_strips_note_frontmatter() {
+        let ui = UiConfig {
+            color_mode: ColorMode::Never,
+            ..UiConfig::default()
+        };
+        let _color = ColorOverride::set(false);
+        let today = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+
+        let list = "---\ntitle: groceries\n---\n\n- [ ] milk  ^abcde\n- [x] bread  ^fghij\n";
+        let path = Path::new("groceries.md");
+        let out = cat_text("list", list, path, false, &ui, today).unwrap();
+        assert_eq!(out, "groceries:\n#1 [ ] milk\n#2 [x] bread\n");
+        let raw = cat_text("list", list, path, true, &ui, today).unwrap();
+        assert_eq!(raw, list);
+
+        let note = "---\ntitle: Meeting\ntags: [work]\n---\n\n# Agenda\n- budget\n";
+        let path = Path::new("meeting.md");
+        let out = cat_text("note", note, path, false, &ui, today).unwrap();
+        assert_eq!(out, "# Agenda\n- budget\n");
+        let raw = cat_text("note", note, path, true, &ui, today).unwrap();
+        assert_eq!(raw, note);
+        let plain = "no frontmatter\n";
+        let out = cat_text("note", plain, path, false, &ui, today).unwrap();
+        assert_eq!(out, plain);
+    }
+
+    #[test]
+    fn test_daily_list_name_targets_existing_dated_lists_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let lists_dir = dir.path();
+        std::fs::create_dir_all(lists_dir.join("daily_lists")).unwrap();
+        std::fs::write(lists_dir.join("daily_lists/20240101_daily_list.md"), "").unwrap();
+
+        assert_eq!(
+            daily_list_name(lists_dir, Some("20240101")).unwrap(),
+            "daily_lists/20240101_daily_list"
+        );
+        let today = Local::now().format("%Y%m%d").to_string();
+        assert_eq!(
+            daily_list_name(lists_dir, None).unwrap(),
+            format!("daily_lists/{}_daily_list", today)
+        );
+
+        let err = daily_list_name(lists_dir, Some("20240102")).unwrap_err();
+        assert_eq!(err.to_string(), "No daily list for 20240102");
+        for invalid in ["2024-01-01", "20241301", "240101", "2024010a", "+2024010"] {
+            let err = daily_list_name(lists_dir, Some(invalid)).unwrap_err();
+            assert!(err.to_string().starts_with("Invalid date"), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_print_path_skips_the_editor() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lists/groceries.md");
+
+        // An editor that can't be spawned proves none is started
+        let mut out = Vec::new();
+        open_or_print_path(&path, true, Some("/nonexistent/editor"), None, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("{}\n", path.display())
+        );
+
+        let mut out = Vec::new();
+        let err = open_or_print_path(&path, false, Some("/nonexistent/editor"), None, &mut out)
+            .unwrap_err();
+        assert!(err.to_string().contains("Failed to launch editor"));
+        assert!(out.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_editor_flag_overrides_environment() {
+        let path = Path::new("groceries.md");
+        let mut out = Vec::new();
+        open_or_print_path(path, false, Some("true"), Some("false"), &mut out).unwrap();
+        let err = open_or_print_path(path, false, None, Some("false"), &mut out).unwrap_err();
+        assert_eq!(err.to_string(), "Editor exited with non-zero status");
+    }
+
+    #[test]
+    fn test_tail_lines_returns_last_lines_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("syncd.log");
+        // Enough lines to span several read blocks
+        let content: String = (1..=2000).map(|i| format!("log line {}\n", i)).collect();
+        std::fs::write(&path, content).unwrap();
+
+        let tail = tail_lines(&path, 3).unwrap();
+        assert_eq!(tail, ["log line 1998", "log line 1999", "log line 2000"]);
+
+        let tail = tail_lines(&path, 1500).unwrap();
+        assert_eq!(tail.len(), 1500);
+        assert_eq!(tail[0], "log line 501");
+        assert_eq!(tail[1499], "log line 2000");
+
+        assert_eq!(tail_lines(&path, 5000).unwrap().len(), 2000);
+        assert!(tail_lines(&path, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_choose_match_uses_selector_and_errors_without_one() {
+        let matches = [
+            ("groceries".to_string(), 90),
+            ("stores/grocer".to_string(), 70),
+            ("gross".to_string(), 40),
+        ];
+
+        let offered = std::cell::RefCell::new(Vec::new());
+        let pick_second = |_: &str, names: &[String]| -> Result<Option<usize>> {
+            *offered.borrow_mut() = names.to_vec();
+            Ok(Some(1))
+        };
+        let chosen = choose_match("list", "gro", &matches, 2, Some(&pick_second)).unwrap();
+        assert_eq!(chosen, "stores/grocer");
+        assert_eq!(*offered.borrow(), ["groceries", "stores/grocer"]);
+
+        let cancel = |_: &str, _: &[String]| -> Result<Option<usize>> { Ok(None) };
+        let err = choose_match("list", "gro", &matches, 2, Some(&cancel)).unwrap_err();
+        assert!(err.to_string().starts_with("Multiple lists match 'gro'"));
+
+        let err = choose_match("note", "gro", &matches, 5, None)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            "Multiple notes match 'gro': groceries (score: 90), stores/grocer (score: 70), \
+             gross (score: 40)"
+        );
+    }
+
+    #[test]
+    fn test_ambiguous_item_targets_go_through_choose_match() {
+        let mut list = List::new("groceries".to_string());
+        for text in ["milk", "oat milk", "bread"] {
+            list.add_item(text.to_string());
+        }
+        let anchor = |text: &str| {
+            list.all_items()
+                .find(|item| item.text == text)
+                .unwrap()
+                .anchor
+                .clone()
+        };
+
+        let offered = std::cell::RefCell::new(Vec::new());
+        let pick_second = |_: &str, names: &[String]| -> Result<Option<usize>> {
+            *offered.borrow_mut() = names.to_vec();
+            Ok(Some(1))
+        };
+        let chosen = settle_item_target(&list, "mlk", 0, 5, Some(&pick_second)).unwrap();
+        assert_eq!(offered.borrow().len(), 2);
+        assert_eq!(chosen, anchor(&offered.borrow()[1]));
+
+        let err = settle_item_target(&list, "mlk", 0, 5, None).unwrap_err();
+        assert!(err.to_string().starts_with("Multiple items match 'mlk'"));
+
+        // Unambiguous targets are left to the storage layer
+        let never = |_: &str, _: &[String]| -> Result<Option<usize>> { unreachable!() };
+        for target in ["milk", "brd", "#3", "nothing", "mlk,brd"] {
+            assert_eq!(
+                settle_item_target(&list, target, 0, 5, Some(&never)).unwrap(),
+                target
+            );
+        }
+    }
+
+    #[test]
+    fn test_theme_preview_shows_resolved_hex_colors_and_respects_no_color() {
+        let theme = lst_core::ThemeLoader::default()
+            .load_theme("base16-default-dark")
+            .unwrap();
+
+        let plain = render_theme_preview(&theme, Swatches::Off);
+        assert!(
+            !plain.contains('\x1b'),
+            "unexpected ANSI codes in {:?}",
+            plain
+        );
+        assert!(plain.contains("base00  #181818  Default Background\n"));
+        assert!(plain.contains("base0D  #7cafc2  Blue\n"));
+        assert!(plain.contains("primary     base0D -> #7cafc2\n"));
+        assert!(plain.contains("error       base08 -> #ab4642\n"));
+
+        let truecolor = render_theme_preview(&theme, Swatches::TrueColor);
+        assert!(truecolor.contains("\x1b[48;2;124;175;194m"));
+        assert!(truecolor.contains("#7cafc2"));
+        let ansi256 = render_theme_preview(&theme, Swatches::Ansi256);
+        assert!(ansi256.contains("\x1b[48;5;109m"));
+        assert!(!ansi256.contains("48;2;"));
+
+        // NO_COLOR turns color off, which suppresses the swatches
+        let colorize = ColorMode::Auto.use_color(true, true);
+        assert_eq!(Swatches::detect(colorize, Some("truecolor")), Swatches::Off);
+        assert_eq!(
+            Swatches::detect(true, Some("truecolor")),
+            Swatches::TrueColor
+        );
+        assert_eq!(Swatches::detect(true, None), Swatches::Ansi256);
+    }
+
+    #[test]
+    fn test_new_theme_from_json_palette_loads_and_validates() {
+        let dir = tempfile::tempdir().unwrap();
+        let loader = ThemeLoader::with_config(Some(dir.path().to_path_buf()));
+        let palette: serde_json::Map<String, serde_json::Value> = (0..16)
+            .map(|i| {
+                let color = format!("#{:02x}{:02x}{:02x}", i * 16, i * 8, 255 - i * 16);
+                (format!("base0{:X}", i), color.into())
+            })
+            .collect();
+        let mut input = serde_json::Value::Object(palette);
+        input["semantic"] = serde_json::json!({ "primary": "base0E" });
+        let overrides = theme_overrides_from_json(&input.to_string()).unwrap();
+
+        let theme = build_theme(&loader, "my-theme", None, &overrides).unwrap();
+        let path = loader.save_theme_in(dir.path(), &theme, false).unwrap();
+        assert_eq!(path, dir.path().join("my-theme.toml"));
+        let loaded = loader.load_theme("my-theme").unwrap();
+        loader.validate_theme(&loaded).unwrap();
+        assert_eq!(loaded.scheme, "my-theme");
+        assert_eq!(loaded.palette.base0f.as_deref(), Some("#f0780f"));
+        assert_eq!(
+            loaded.resolve_semantic_color("primary").as_deref(),
+            Some("#e0701f")
+        );
+
+        // --from keeps the rest of an existing palette
+        let overrides = theme_overrides_from_json(r##"{"base08": "#ff0000"}"##).unwrap();
+        let theme = build_theme(&loader, "red-nord", Some("base16-nord"), &overrides).unwrap();
+        let nord = loader.load_theme("base16-nord").unwrap();
+        assert_eq!(theme.palette.base08.as_deref(), Some("#ff0000"));
+        assert_eq!(theme.palette.base00, nord.palette.base00);
+        loader.save_theme_in(dir.path(), &theme, false).unwrap();
+
+        // A partial palette without a starting theme doesn't validate
+        let theme = build_theme(&loader, "partial", None, &overrides).unwrap();
+        assert!(loader.save_theme_in(dir.path(), &theme, false).is_err());
+
+        for bad in [
+            r##"["#ffffff"]"##,
+            r##"{"base00": "white"}"##,
+            r##"{"base20": "#ffffff"}"##,
+            r##"{"semantic": {"primary": "#ffffff"}}"##,
+            r##"{"semantic": {"sparkle": "base00"}}"##,
+        ] {
+            assert!(theme_overrides_from_json(bad).is_err(), "{}", bad);
+        }
+    }
+}
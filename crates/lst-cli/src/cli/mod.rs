@@ -24,6 +24,25 @@ pub enum Commands {
         /// Hide anchors in list item display
         #[clap(short = 'c', long = "clean")]
         clean: bool,
+        /// Only show lists carrying this tag
+        #[clap(long, conflicts_with = "list")]
+        tag: Option<String>,
+        /// Order items within each category (defaults to the list's default_sort);
+        /// without a list, `updated` shows the most recently changed lists first
+        #[clap(long, value_parser = ["alpha", "created", "priority", "updated"])]
+        sort: Option<String>,
+        /// Only show items that are still to do
+        #[clap(long, requires = "list", conflicts_with = "done_only")]
+        todo_only: bool,
+        /// Only show completed items
+        #[clap(long, requires = "list")]
+        done_only: bool,
+        /// Show how long ago each item was added (e.g. "3d")
+        #[clap(long, requires = "list")]
+        age: bool,
+        /// Show lists as a tree grouped by directory
+        #[clap(long, conflicts_with = "list")]
+        tree: bool,
     },
 
     /// Create and open a new list
@@ -50,14 +69,27 @@ pub enum Commands {
     Open {
         /// Name of the list
         list: String,
+        /// Print the resolved file path instead of opening an editor
+        #[clap(long)]
+        print_path: bool,
+        /// Editor to use instead of $EDITOR for this invocation
+        #[clap(long, conflicts_with = "print_path")]
+        editor: Option<String>,
     },
     /// Mark an item as done
     #[clap(name = "done")]
     Done {
         /// Name of the list
         list: String,
-        /// Target item to mark as done (anchor, text, or index; comma-separated for multiple items)
-        target: String,
+        /// Target item to mark as done (anchor, text, index or range like #2-5; comma-separated for multiple items)
+        #[clap(required_unless_present_any = ["all", "category"])]
+        target: Option<String>,
+        /// Mark every item in the list as done
+        #[clap(long, conflicts_with_all = ["target", "category"])]
+        all: bool,
+        /// Mark every item in this category as done
+        #[clap(long, conflicts_with = "target")]
+        category: Option<String>,
     },
 
     /// Mark a completed item as not done
@@ -65,8 +97,15 @@ pub enum Commands {
     Undone {
         /// Name of the list
         list: String,
-        /// Target item to mark as not done (anchor, text, or index; comma-separated for multiple items)
-        target: String,
+        /// Target item to mark as not done (anchor, text, index or range like #2-5; comma-separated for multiple items)
+        #[clap(required_unless_present_any = ["all", "category"])]
+        target: Option<String>,
+        /// Mark every item in the list as not done
+        #[clap(long, conflicts_with_all = ["target", "category"])]
+        all: bool,
+        /// Mark every item in this category as not done
+        #[clap(long, conflicts_with = "target")]
+        category: Option<String>,
     },
 
     /// Mark all items in a list as undone (reset completion status)
@@ -81,8 +120,19 @@ pub enum Commands {
     Rm {
         /// Name of the list
         list: String,
-        /// Target item to delete (anchor, text, or index; comma-separated for multiple items)
+        /// Target item to delete (anchor, text, index or range like #2-5; comma-separated for multiple items)
+        target: String,
+    },
+
+    /// Set the note shown below an item (an empty text removes it)
+    #[clap(name = "note-item")]
+    NoteItem {
+        /// Name of the list
+        list: String,
+        /// Target item (anchor, text, or index)
         target: String,
+        /// Note text; may span several lines
+        text: String,
     },
 
     /// Delete a list file
@@ -95,6 +145,17 @@ pub enum Commands {
         force: bool,
     },
 
+    /// Inspect or empty the trash of deleted lists and notes
+    #[clap(subcommand, name = "trash")]
+    Trash(TrashCommands),
+
+    /// Restore a deleted list or note from the trash
+    #[clap(name = "restore")]
+    Restore {
+        /// Original name of the file, e.g. `groceries` or `lists/groceries`
+        name: String,
+    },
+
     /// Delete all entries from a list
     #[clap(name = "wipe")]
     Wipe {
@@ -105,6 +166,93 @@ pub enum Commands {
         force: bool,
     },
 
+    /// Search list items and notes for text
+    #[clap(name = "search")]
+    Search {
+        /// Text to search for (literal unless --regex is given)
+        query: String,
+        /// Treat the query as a regular expression
+        #[clap(long)]
+        regex: bool,
+    },
+
+    /// Add a template's missing categories and items to an existing list
+    #[clap(name = "apply-template")]
+    ApplyTemplate {
+        /// Name of the template (in the templates directory)
+        template: String,
+        /// Name of the list
+        list: String,
+    },
+
+    /// Move done items into an "Archived" category at the bottom of a list
+    #[clap(name = "archive")]
+    Archive {
+        /// Name of the list
+        list: String,
+        /// Move archived items back into the uncategorized section
+        #[clap(long)]
+        restore: bool,
+    },
+
+    /// Pin a list so `lst ls` shows it before all others
+    #[clap(name = "pin")]
+    Pin {
+        /// Name of the list
+        list: String,
+    },
+
+    /// Unpin a list
+    #[clap(name = "unpin")]
+    Unpin {
+        /// Name of the list
+        list: String,
+    },
+
+    /// Split a list into one list per category, named <list>-<category>
+    #[clap(name = "split-by-category")]
+    SplitByCategory {
+        /// Name of the list
+        list: String,
+        /// Remove the categories from the original list (deleted if left empty)
+        #[clap(long = "move")]
+        move_items: bool,
+    },
+
+    /// Move or rename a list or note file
+    #[clap(name = "mv")]
+    Mv {
+        /// List or note to move
+        from: String,
+        /// New name, optionally with directories (e.g. groceries/pharmacy)
+        to: String,
+        /// Overwrite the destination if it already exists
+        #[clap(short, long)]
+        force: bool,
+    },
+
+    /// Copy a list or note under a new name
+    #[clap(name = "cp")]
+    Cp {
+        /// List or note to copy
+        from: String,
+        /// Name of the copy, optionally with directories (e.g. trips/rome)
+        to: String,
+        /// Overwrite the destination if it already exists
+        #[clap(short, long)]
+        force: bool,
+    },
+
+    /// Undo the last change to a list
+    #[clap(name = "undo")]
+    Undo {
+        /// Name of the list (defaults to the most recently changed one)
+        list: Option<String>,
+        /// Show the undo journal instead of restoring
+        #[clap(short = 'l', long = "list")]
+        show_journal: bool,
+    },
+
     /// Read items from stdin and add them to a list
     #[clap(name = "pipe")]
     Pipe {
@@ -131,10 +279,21 @@ pub enum Commands {
     #[clap(name = "dn")]
     Dn,
 
+    /// Open the rolling scratch note, starting a new timestamped section
+    #[clap(name = "scratch")]
+    Scratch,
+
     /// Sync daemon commands
     #[clap(subcommand, name = "sync")]
     Sync(SyncCommands),
 
+    /// Show how a synced list or note differs from the server's copy
+    #[clap(name = "diff")]
+    Diff {
+        /// Name or path of the list or note
+        name: String,
+    },
+
     /// Share a document with other devices
     #[clap(name = "share")]
     Share {
@@ -161,16 +320,48 @@ pub enum Commands {
 
     /// Tidy all lists: ensure proper YAML frontmatter and formatting
     #[clap(name = "tidy")]
-    Tidy,
+    Tidy {
+        /// Show a diff of what would change without writing anything
+        #[clap(long)]
+        dry_run: bool,
+    },
+
+    /// Print a list or note to stdout, or manage categories with a subcommand
+    ///
+    /// Names equal to a category subcommand (add, mv, ls, rm, rename) run
+    /// that subcommand; to print a document with such a name, put `--` (or
+    /// `--kind`/`--raw`) before it, e.g. `lst cat -- add`.
+    #[clap(
+        name = "cat",
+        args_conflicts_with_subcommands = true,
+        subcommand_negates_reqs = true
+    )]
+    Cat {
+        #[clap(subcommand)]
+        category: Option<CategoryCommands>,
+        /// List or note to print
+        #[clap(required = true)]
+        name: Option<String>,
+        /// Print the markdown file verbatim, including frontmatter
+        #[clap(long)]
+        raw: bool,
+        /// Only look for a list or a note (when both match the name)
+        #[clap(long, value_parser = ["list", "note"])]
+        kind: Option<String>,
+    },
 
-    /// Category management commands
-    #[clap(subcommand, name = "cat")]
-    Category(CategoryCommands),
+    /// List tag management commands
+    #[clap(subcommand, name = "tag")]
+    Tag(TagCommands),
 
     /// Authentication commands for server access
     #[clap(subcommand, name = "auth")]
     Auth(AuthCommands),
 
+    /// Show who the stored JWT belongs to and when it expires
+    #[clap(name = "whoami")]
+    Whoami,
+
     /// Server content management commands
     #[clap(subcommand, name = "server")]
     Server(ServerCommands),
@@ -183,9 +374,91 @@ pub enum Commands {
     #[clap(subcommand, name = "user")]
     User(UserCommands),
 
-    /// Generate JSON schema for configuration validation
+    /// Generate JSON schema for the configuration, lists, or note frontmatter
     #[clap(name = "schema")]
-    Schema,
+    Schema {
+        /// What to describe (defaults to the configuration file)
+        #[clap(value_parser = ["config", "list", "note"])]
+        kind: Option<String>,
+    },
+
+    /// Inspect the configuration
+    #[clap(subcommand, name = "config")]
+    Config(ConfigCommands),
+
+    /// Check config, content directories and auth for common problems
+    #[clap(name = "doctor")]
+    Doctor {
+        /// Repair what can be fixed safely; replacing an invalid config asks first
+        #[clap(long)]
+        fix: bool,
+    },
+
+    /// Summarize lists, item completion and note word counts
+    #[clap(name = "stats")]
+    Stats,
+
+    /// Bundle all lists and notes into a gzip tarball with a manifest
+    #[clap(name = "export")]
+    Export {
+        /// Archive to write, e.g. backup.tar.gz
+        #[clap(long, short)]
+        out: std::path::PathBuf,
+        /// Also include files from the media directory
+        #[clap(long)]
+        include_media: bool,
+    },
+
+    /// Restore lists, notes and media from an archive written by `lst export`
+    #[clap(name = "import")]
+    Import {
+        /// Archive to import, e.g. backup.tar.gz
+        archive: std::path::PathBuf,
+        /// Replace existing files instead of refusing to import
+        #[clap(long)]
+        overwrite: bool,
+        /// Only import one kind of document
+        #[clap(long, value_parser = ["lists", "notes"])]
+        kind: Option<String>,
+    },
+}
+
+/// Trash subcommands
+#[derive(Subcommand)]
+pub enum TrashCommands {
+    /// List deleted lists and notes, most recently deleted first
+    #[clap(name = "ls")]
+    Ls,
+
+    /// Permanently delete everything in the trash
+    #[clap(name = "empty")]
+    Empty {
+        /// Do not ask for confirmation
+        #[clap(short, long)]
+        force: bool,
+    },
+}
+
+/// Configuration subcommands
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Print the resolved config, state, content and sync database paths
+    #[clap(name = "path")]
+    Path,
+
+    /// Print the effective configuration (environment overrides applied,
+    /// secrets redacted) as TOML (or JSON with --json)
+    #[clap(name = "show")]
+    Show,
+
+    /// Set a config value, e.g. `lst config set paths.content_dir ~/notes`
+    #[clap(name = "set")]
+    Set {
+        /// Dotted config key, e.g. ui.vim_mode
+        key: String,
+        /// New value (parsed as TOML for non-string settings)
+        value: String,
+    },
 }
 
 /// User management subcommands (requires lst-server binary)
@@ -259,6 +532,12 @@ pub enum NoteCommands {
     New {
         /// Title of the note
         title: String,
+        /// Replace an existing note instead of creating `title-2`, `title-3`, ...
+        #[clap(long)]
+        overwrite: bool,
+        /// Start from `note_templates/<name>.md`, filling in {{title}}, {{date}} and {{time}}
+        #[clap(long)]
+        template: Option<String>,
     },
 
     /// Append text to a note (create if it doesn't exist)
@@ -270,11 +549,27 @@ pub enum NoteCommands {
         text: String,
     },
 
+    /// Append stdin to a note (create if it doesn't exist)
+    #[clap(name = "pipe", alias = "append-stdin")]
+    Pipe {
+        /// Title of the note
+        title: String,
+        /// Put the appended text under a `## <heading>`
+        #[clap(long)]
+        heading: Option<String>,
+    },
+
     /// Open a note in the default editor
     #[clap(name = "open")]
     Open {
         /// Title of the note
         title: String,
+        /// Print the resolved file path instead of opening an editor
+        #[clap(long)]
+        print_path: bool,
+        /// Editor to use instead of $EDITOR for this invocation
+        #[clap(long, conflicts_with = "print_path")]
+        editor: Option<String>,
     },
 
     /// Delete a note
@@ -289,11 +584,72 @@ pub enum NoteCommands {
 
     /// List all notes
     #[clap(name = "ls")]
-    ListNotes {},
+    ListNotes {
+        /// Show notes as a tree grouped by directory
+        #[clap(long)]
+        tree: bool,
+    },
 
     /// Tidy all notes: ensure proper YAML frontmatter
     #[clap(name = "tidy")]
-    Tidy,
+    Tidy {
+        /// Show a diff of what would change without writing anything
+        #[clap(long)]
+        dry_run: bool,
+    },
+
+    /// Normalize a note's markdown: collapse blank lines, use `-` bullets and
+    /// wrap prose, leaving code blocks and frontmatter alone
+    #[clap(name = "fmt")]
+    Fmt {
+        /// Title of the note
+        title: String,
+        /// Column to wrap prose at (0 disables wrapping)
+        #[clap(long, default_value_t = lst_core::storage::format::DEFAULT_WIDTH)]
+        width: usize,
+        /// Show what would change and exit with an error instead of writing
+        #[clap(long)]
+        check: bool,
+    },
+
+    /// Move or rename a note (e.g. `lst note mv entry journal/2024/entry`). A
+    /// synced note is removed from the server under its old path on the next sync
+    #[clap(name = "mv")]
+    Move {
+        /// Note to move
+        from: String,
+        /// New name, optionally with directories
+        to: String,
+        /// Overwrite the destination if it already exists
+        #[clap(short, long)]
+        force: bool,
+    },
+
+    /// Rename a note's file after its title, updating [[wikilinks]] to it
+    #[clap(name = "rename-heading")]
+    RenameHeading {
+        /// Title of the note
+        title: String,
+        /// Show what would be renamed and relinked without changing anything
+        #[clap(long)]
+        dry_run: bool,
+    },
+
+    /// Render a note to a standalone HTML page or a PDF
+    #[clap(name = "export")]
+    Export {
+        /// Title of the note
+        title: String,
+        /// Output format; PDF is produced by `ui.pdf_renderer` (default wkhtmltopdf)
+        #[clap(long, value_parser = ["html", "pdf"], default_value = "html")]
+        format: String,
+        /// File to write (HTML is printed when omitted)
+        #[clap(long, required_if_eq("format", "pdf"))]
+        out: Option<String>,
+        /// Leave out the current theme's colors
+        #[clap(long)]
+        no_theme: bool,
+    },
 
     /// Display note content with metadata
     #[clap(name = "show")]
@@ -322,6 +678,74 @@ pub enum NoteCommands {
         /// Title of the note
         title: String,
     },
+
+    /// Show a note's [[wikilinks]] and the notes linking back to it
+    #[clap(name = "links")]
+    Links {
+        /// Title of the note
+        title: String,
+    },
+
+    /// Manage tags across all notes
+    #[clap(subcommand, name = "tags")]
+    Tags(NoteTagCommands),
+
+    /// Manage templates for new notes
+    #[clap(subcommand, name = "template")]
+    Template(NoteTemplateCommands),
+
+    /// Attach a file (PDF, audio, ...) to a note as a markdown link
+    #[clap(name = "embed")]
+    Embed {
+        /// Path to the file to attach
+        file: String,
+        /// Note to attach the file to
+        #[clap(long)]
+        to: String,
+        /// Link text (defaults to the file name)
+        #[clap(long)]
+        name: Option<String>,
+    },
+
+    /// List the files attached to a note
+    #[clap(name = "attachments")]
+    Attachments {
+        /// Title of the note
+        title: String,
+    },
+
+    /// Remove an attachment from a note, deleting the file once unused
+    #[clap(name = "detach")]
+    Detach {
+        /// Title of the note
+        title: String,
+        /// Hash of the attachment to remove
+        hash: String,
+    },
+}
+
+/// Note tag subcommands
+#[derive(Subcommand)]
+pub enum NoteTagCommands {
+    /// Rename a tag in every note's frontmatter
+    #[clap(name = "rename")]
+    Rename {
+        /// Tag to rename
+        old: String,
+        /// New tag name (e.g. work/project)
+        new: String,
+        /// Show which notes would change without writing them
+        #[clap(long)]
+        dry_run: bool,
+    },
+}
+
+/// Note template subcommands
+#[derive(Subcommand)]
+pub enum NoteTemplateCommands {
+    /// List the available templates
+    #[clap(name = "ls")]
+    List,
 }
 
 #[derive(Subcommand)]
@@ -378,20 +802,29 @@ pub enum DlCmd {
     Add {
         /// Text of the item to add
         item: String,
+        /// Target the daily list of this day (YYYYMMDD) instead of today's
+        #[clap(long)]
+        date: Option<String>,
     },
 
     /// Mark an item as done in today's daily list
     #[clap(name = "done")]
     Done {
-        /// Target item to mark as done (anchor, text, or index; comma-separated for multiple items)
+        /// Target item to mark as done (anchor, text, index or range like #2-5; comma-separated for multiple items)
         item: String,
+        /// Target the daily list of this day (YYYYMMDD) instead of today's
+        #[clap(long)]
+        date: Option<String>,
     },
 
     /// Mark an item as not done in today's daily list
     #[clap(name = "undone")]
     Undone {
-        /// Target item to mark as not done (anchor, text, or index; comma-separated for multiple items)
+        /// Target item to mark as not done (anchor, text, index or range like #2-5; comma-separated for multiple items)
         item: String,
+        /// Target the daily list of this day (YYYYMMDD) instead of today's
+        #[clap(long)]
+        date: Option<String>,
     },
 
     /// List all daily lists
@@ -401,7 +834,48 @@ pub enum DlCmd {
     /// Remove an item from today's daily list
     #[clap(name = "rm")]
     Remove {
-        /// Target item to remove (anchor, text, or index; comma-separated for multiple items)
+        /// Target item to remove (anchor, text, index or range like #2-5; comma-separated for multiple items)
+        item: String,
+        /// Target the daily list of this day (YYYYMMDD) instead of today's
+        #[clap(long)]
+        date: Option<String>,
+    },
+
+    /// Carry unfinished items of the previous daily list over to today's
+    #[clap(name = "rollover")]
+    Rollover {
+        /// Remove the carried items from the previous daily list
+        #[clap(long)]
+        clear_source: bool,
+    },
+
+    /// Manage the template carried over into each new daily list
+    #[clap(subcommand, name = "template")]
+    Template(DlTemplateCmd),
+}
+
+/// Subcommands for the daily list template
+#[derive(Subcommand)]
+pub enum DlTemplateCmd {
+    /// Show the template items
+    #[clap(name = "show")]
+    Show,
+
+    /// Open the template in the editor
+    #[clap(name = "edit")]
+    Edit,
+
+    /// Add an item to the template
+    #[clap(name = "add")]
+    Add {
+        /// Text of the item to add
+        item: String,
+    },
+
+    /// Remove an item from the template
+    #[clap(name = "rm")]
+    Remove {
+        /// Target item to remove (anchor, text, index or range like #2-5; comma-separated for multiple items)
         item: String,
     },
 }
@@ -421,6 +895,10 @@ pub enum SyncCommands {
     #[clap(name = "stop")]
     Stop,
 
+    /// Ask the running sync daemon to sync right away
+    #[clap(name = "now")]
+    Now,
+
     /// Show sync daemon status
     #[clap(name = "status")]
     Status,
@@ -443,6 +921,18 @@ pub enum SyncCommands {
         #[clap(short, long, default_value = "50")]
         lines: usize,
     },
+
+    /// Measure the round-trip latency to the sync server
+    #[clap(name = "ping")]
+    Ping,
+
+    /// Show which device last changed each synced document
+    #[clap(name = "whoami-devices")]
+    WhoamiDevices,
+
+    /// Compare local files against the server's snapshots without changing anything
+    #[clap(name = "verify")]
+    Verify,
 }
 
 /// Category management subcommands
@@ -483,6 +973,54 @@ pub enum CategoryCommands {
         /// Name of the category to remove
         name: String,
     },
+
+    /// Rename a category, optionally merging it into an existing one
+    #[clap(name = "rename")]
+    Rename {
+        /// Name of the list
+        list: String,
+        /// Current category name
+        old_name: String,
+        /// New category name
+        new_name: String,
+        /// Merge into the target category if it already exists
+        #[clap(long)]
+        merge: bool,
+        /// When merging, skip items whose text already exists in the target
+        #[clap(long, requires = "merge")]
+        dedupe: bool,
+    },
+}
+
+/// List tag subcommands
+#[derive(Subcommand)]
+pub enum TagCommands {
+    /// Add tags to a list
+    #[clap(name = "add")]
+    Add {
+        /// Name of the list
+        list: String,
+        /// Tags to add
+        #[clap(required = true)]
+        tags: Vec<String>,
+    },
+
+    /// Remove tags from a list
+    #[clap(name = "rm")]
+    Remove {
+        /// Name of the list
+        list: String,
+        /// Tags to remove
+        #[clap(required = true)]
+        tags: Vec<String>,
+    },
+
+    /// Show the tags of a list
+    #[clap(name = "ls")]
+    List {
+        /// Name of the list
+        list: String,
+    },
 }
 
 /// Authentication subcommands
@@ -565,8 +1103,42 @@ pub enum ServerCommands {
     Delete {
         /// Kind of content (e.g., "notes", "lists")
         kind: String,
-        /// Path of the content (e.g., "example.md")
-        path: String,
+        /// Path of the content (e.g., "example.md"), or a directory prefix with --recursive
+        #[clap(required_unless_present = "recursive")]
+        path: Option<String>,
+        /// Delete everything of this kind under the given path prefix
+        #[clap(short, long)]
+        recursive: bool,
+        /// Do not ask for confirmation
+        #[clap(short, long)]
+        force: bool,
+    },
+
+    /// Check that the server is reachable and accepts the stored credentials
+    #[clap(name = "ping")]
+    Ping,
+
+    /// List the documents the sync server tracks, with decrypted filenames
+    #[clap(name = "list-docs")]
+    ListDocs,
+
+    /// Download all content of a kind from the server
+    #[clap(name = "pull")]
+    Pull {
+        /// Kind of content (e.g., "notes", "lists")
+        kind: String,
+        /// Skip items already downloaded by a previous, interrupted pull
+        #[clap(long)]
+        resume: bool,
+    },
+
+    /// Write every document on the sync server into the local content
+    /// directory (e.g. to set up a new device)
+    #[clap(name = "sync-pull")]
+    SyncPull {
+        /// Overwrite local files that differ from the server copy
+        #[clap(short, long)]
+        force: bool,
     },
 }
 
@@ -590,6 +1162,10 @@ pub enum ThemeCommands {
     Apply {
         /// Name of the theme to apply
         theme: String,
+        /// Use the theme's light or dark variant; `auto` keeps both so the
+        /// desktop app can follow the system appearance
+        #[clap(long, value_parser = ["auto", "light", "dark"], default_value = "auto")]
+        variant: String,
     },
 
     /// Show detailed information about a theme
@@ -599,6 +1175,27 @@ pub enum ThemeCommands {
         theme: String,
     },
 
+    /// Show a theme's palette and semantic colors as color swatches
+    #[clap(name = "preview")]
+    Preview {
+        /// Name of the theme to preview
+        theme: String,
+    },
+
+    /// Create a base16 theme, prompting for its colors (with --json, read
+    /// them from stdin as an object like {"base00": "#1e1e2e", ...})
+    #[clap(name = "new")]
+    New {
+        /// Name of the new theme
+        name: String,
+        /// Start from an existing theme's palette
+        #[clap(long)]
+        from: Option<String>,
+        /// Replace an existing theme file of the same name
+        #[clap(short, long)]
+        force: bool,
+    },
+
     /// Validate a theme file
     #[clap(name = "validate")]
     Validate {
@@ -606,3 +1203,41 @@ pub enum ThemeCommands {
         file: String,
     },
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Name `lst cat` prints for `args`, or `None` when a category subcommand runs
+    fn cat_target(args: &[&str]) -> Option<String> {
+        match Cli::try_parse_from(args).unwrap().command {
+            Commands::Cat {
+                category: None,
+                name,
+                ..
+            } => name,
+            Commands::Cat { .. } => None,
+            _ => panic!("expected the cat command"),
+        }
+    }
+
+    #[test]
+    fn test_cat_prints_documents_named_like_category_subcommands() {
+        assert_eq!(
+            cat_target(&["lst", "cat", "groceries"]).as_deref(),
+            Some("groceries")
+        );
+        assert_eq!(
+            cat_target(&["lst", "cat", "add", "groceries", "Dairy"]),
+            None
+        );
+
+        for args in [
+            ["lst", "cat", "--", "add"].as_slice(),
+            &["lst", "cat", "--kind", "list", "add"],
+            &["lst", "cat", "--raw", "add"],
+        ] {
+            assert_eq!(cat_target(args).as_deref(), Some("add"), "{:?}", args);
+        }
+    }
+}
@@ -6,8 +6,9 @@ use lst_cli::{config, models, storage};
 use anyhow::Result;
 use clap::Parser;
 use cli::{
-    AuthCommands, CategoryCommands, Cli, Commands, GuiCommands, ImageCommands, NoteCommands,
-    ServerCommands, ThemeCommands, UserCommands,
+    AuthCommands, CategoryCommands, Cli, Commands, ConfigCommands, GuiCommands, ImageCommands,
+    NoteCommands, NoteTagCommands, NoteTemplateCommands, ServerCommands, TagCommands,
+    ThemeCommands, TrashCommands, UserCommands,
 };
 
 #[tokio::main]
@@ -16,14 +17,37 @@ async fn main() -> Result<()> {
     let cli = Cli::parse();
 
     // Configuration is now loaded on first use via a global cache
+    cli::commands::init_color_mode();
+    cli::commands::init_prompts(cli.json);
 
     // Process commands
     match &cli.command {
-        Commands::ListLists { list, clean } => {
+        Commands::ListLists {
+            list,
+            clean,
+            tag,
+            sort,
+            todo_only,
+            done_only,
+            age,
+            tree,
+        } => {
             if let Some(list_name) = list {
-                cli::commands::display_list(list_name, cli.json, *clean)?;
+                let status = match (todo_only, done_only) {
+                    (true, _) => Some(models::ItemStatus::Todo),
+                    (_, true) => Some(models::ItemStatus::Done),
+                    _ => None,
+                };
+                cli::commands::display_list(
+                    list_name,
+                    cli.json,
+                    *clean,
+                    sort.as_deref(),
+                    status,
+                    *age,
+                )?;
             } else {
-                cli::commands::list_lists(cli.json)?;
+                cli::commands::list_lists(tag.as_deref(), sort.as_deref(), *tree, cli.json)?;
             }
         }
         Commands::New { list } => {
@@ -36,14 +60,42 @@ async fn main() -> Result<()> {
         } => {
             cli::commands::add_item(list, text, category.as_deref(), cli.json).await?;
         }
-        Commands::Open { list } => {
-            cli::commands::open_list(list)?;
+        Commands::Open {
+            list,
+            print_path,
+            editor,
+        } => {
+            cli::commands::open_list(list, *print_path, editor.as_deref())?;
         }
-        Commands::Done { list, target } => {
-            cli::commands::mark_done(list, target, cli.json).await?;
+        Commands::Done {
+            list,
+            target,
+            all,
+            category,
+        } => {
+            if *all {
+                cli::commands::mark_all(list, models::ItemStatus::Done, cli.json).await?;
+            } else if let Some(category) = category {
+                cli::commands::mark_category(list, category, models::ItemStatus::Done, cli.json)
+                    .await?;
+            } else if let Some(target) = target {
+                cli::commands::mark_done(list, target, cli.json).await?;
+            }
         }
-        Commands::Undone { list, target } => {
-            cli::commands::mark_undone(list, target, cli.json).await?;
+        Commands::Undone {
+            list,
+            target,
+            all,
+            category,
+        } => {
+            if *all {
+                cli::commands::mark_all(list, models::ItemStatus::Todo, cli.json).await?;
+            } else if let Some(category) = category {
+                cli::commands::mark_category(list, category, models::ItemStatus::Todo, cli.json)
+                    .await?;
+            } else if let Some(target) = target {
+                cli::commands::mark_undone(list, target, cli.json).await?;
+            }
         }
         Commands::Reset { list } => {
             cli::commands::reset_list(list, cli.json).await?;
@@ -51,29 +103,96 @@ async fn main() -> Result<()> {
         Commands::Rm { list, target } => {
             cli::commands::remove_item(list, target, cli.json).await?;
         }
+        Commands::NoteItem { list, target, text } => {
+            cli::commands::note_item(list, target, text, cli.json).await?;
+        }
         Commands::Delete { list, force } => {
             cli::commands::delete_list(list, *force, cli.json)?;
         }
+        Commands::Trash(trash_cmd) => match trash_cmd {
+            TrashCommands::Ls => cli::commands::trash_ls(cli.json)?,
+            TrashCommands::Empty { force } => cli::commands::trash_empty(*force, cli.json)?,
+        },
+        Commands::Restore { name } => {
+            cli::commands::restore(name, cli.json)?;
+        }
         Commands::Wipe { list, force } => {
             cli::commands::wipe_list(list, *force, cli.json)?;
         }
+        Commands::Search { query, regex } => {
+            cli::commands::search_all(query, *regex, cli.json)?;
+        }
+        Commands::ApplyTemplate { template, list } => {
+            cli::commands::apply_template(template, list, cli.json).await?;
+        }
+        Commands::Archive { list, restore } => {
+            cli::commands::archive_list(list, *restore, cli.json).await?;
+        }
+        Commands::Pin { list } => {
+            cli::commands::pin_list(list, true, cli.json).await?;
+        }
+        Commands::Unpin { list } => {
+            cli::commands::pin_list(list, false, cli.json).await?;
+        }
+        Commands::SplitByCategory { list, move_items } => {
+            cli::commands::split_by_category(list, *move_items, cli.json).await?;
+        }
+        Commands::Mv { from, to, force } => {
+            cli::commands::move_document(from, to, *force, cli.json)?;
+        }
+        Commands::Cp { from, to, force } => {
+            cli::commands::copy_document(from, to, *force, cli.json)?;
+        }
+        Commands::Undo { list, show_journal } => {
+            if *show_journal {
+                cli::commands::undo_journal(list.as_deref(), cli.json)?;
+            } else {
+                cli::commands::undo(list.as_deref(), cli.json)?;
+            }
+        }
         Commands::Pipe { list } => {
             cli::commands::pipe(list, cli.json)?;
         }
         Commands::Note(note_cmd) => match note_cmd {
-            NoteCommands::New { title } => cli::commands::note_new(title).await?,
+            NoteCommands::New {
+                title,
+                overwrite,
+                template,
+            } => cli::commands::note_new(title, *overwrite, template.as_deref()).await?,
             NoteCommands::Add { title, text } => {
                 cli::commands::note_add(title, text).await?;
             }
-            NoteCommands::Open { title } => cli::commands::note_open(title)?,
+            NoteCommands::Pipe { title, heading } => {
+                cli::commands::note_pipe(title, heading.as_deref(), cli.json).await?;
+            }
+            NoteCommands::Open {
+                title,
+                print_path,
+                editor,
+            } => cli::commands::note_open(title, *print_path, editor.as_deref())?,
             NoteCommands::Remove { title, force } => {
                 cli::commands::note_delete(title, *force).await?
             }
-            NoteCommands::ListNotes {} => {
-                cli::commands::list_notes(cli.json)?;
+            NoteCommands::ListNotes { tree } => {
+                cli::commands::list_notes(*tree, cli.json)?;
             }
-            NoteCommands::Tidy => {
-                cli::commands::tidy_notes(cli.json)?;
+            NoteCommands::Tidy { dry_run } => {
+                cli::commands::tidy_notes(*dry_run, cli.json)?;
+            }
+            NoteCommands::Fmt {
+                title,
+                width,
+                check,
+            } => {
+                cli::commands::note_fmt(title, *width, *check, cli.json)?;
+            }
+            NoteCommands::Export {
+                title,
+                format,
+                out,
+                no_theme,
+            } => {
+                cli::commands::note_export(title, format, out.as_deref(), *no_theme, cli.json)?;
             }
             NoteCommands::Show { title } => {
                 cli::commands::note_show(title, cli.json)?;
@@ -87,6 +206,30 @@ async fn main() -> Result<()> {
             NoteCommands::Metadata { title } => {
                 cli::commands::note_metadata(title, cli.json)?;
             }
+            NoteCommands::Links { title } => {
+                cli::commands::note_links(title, cli.json)?;
+            }
+            NoteCommands::Template(NoteTemplateCommands::List) => {
+                cli::commands::note_template_list(cli.json)?;
+            }
+            NoteCommands::Tags(NoteTagCommands::Rename { old, new, dry_run }) => {
+                cli::commands::note_tags_rename(old, new, *dry_run, cli.json)?;
+            }
+            NoteCommands::Embed { file, to, name } => {
+                cli::commands::note_embed(file, to, name.as_deref(), cli.json).await?;
+            }
+            NoteCommands::Attachments { title } => {
+                cli::commands::note_attachments(title, cli.json)?;
+            }
+            NoteCommands::Detach { title, hash } => {
+                cli::commands::note_detach(title, hash, cli.json).await?;
+            }
+            NoteCommands::Move { from, to, force } => {
+                cli::commands::note_move(from, to, *force, cli.json)?;
+            }
+            NoteCommands::RenameHeading { title, dry_run } => {
+                cli::commands::note_rename_heading(title, *dry_run, cli.json)?;
+            }
         },
         // Commands::Post(post_cmd) => {
         //     match post_cmd {
@@ -107,16 +250,18 @@ async fn main() -> Result<()> {
         Commands::Dn => {
             cli::commands::daily_note(cli.json)?;
         }
+        Commands::Scratch => {
+            cli::commands::scratch_note(cli.json)?;
+        }
         Commands::Sync(sync_cmd) => {
-            cli::commands::handle_sync_command(sync_cmd.clone(), cli.json)?;
+            cli::commands::handle_sync_command(sync_cmd.clone(), cli.json).await?;
+        }
+        Commands::Diff { name } => {
+            cli::commands::diff(name, cli.json).await?;
         }
         Commands::Image(img_cmd) => match img_cmd {
-            ImageCommands::Add {
-                file: _,
-                to: _,
-                caption: _,
-            } => {
-                eprintln!("Image commands not implemented yet");
+            ImageCommands::Add { file, to, caption } => {
+                cli::commands::image_add(file, to, caption.as_deref(), cli.json).await?;
             }
             ImageCommands::Paste {
                 to: _,
@@ -125,14 +270,11 @@ async fn main() -> Result<()> {
             } => {
                 eprintln!("Image commands not implemented yet");
             }
-            ImageCommands::List { document: _ } => {
-                eprintln!("Image commands not implemented yet");
+            ImageCommands::List { document } => {
+                cli::commands::image_list(document, cli.json)?;
             }
-            ImageCommands::Remove {
-                document: _,
-                hash: _,
-            } => {
-                eprintln!("Image commands not implemented yet");
+            ImageCommands::Remove { document, hash } => {
+                cli::commands::image_remove(document, hash, cli.json).await?;
             }
         },
         Commands::Share {
@@ -153,10 +295,22 @@ async fn main() -> Result<()> {
                 cli::commands::remote_show_message(text).await?;
             }
         },
-        Commands::Tidy => {
-            cli::commands::tidy_lists(cli.json)?;
+        Commands::Tidy { dry_run } => {
+            cli::commands::tidy_lists(*dry_run, cli.json)?;
+        }
+        Commands::Cat {
+            category: None,
+            name,
+            raw,
+            kind,
+        } => {
+            let name = name.as_deref().unwrap_or_default();
+            cli::commands::cat_document(name, *raw, kind.as_deref(), cli.json)?;
         }
-        Commands::Category(cat_cmd) => match cat_cmd {
+        Commands::Cat {
+            category: Some(cat_cmd),
+            ..
+        } => match cat_cmd {
             CategoryCommands::Add { list, name } => {
                 cli::commands::category_add(list, name, cli.json).await?;
             }
@@ -173,6 +327,27 @@ async fn main() -> Result<()> {
             CategoryCommands::Remove { list, name } => {
                 cli::commands::category_remove(list, name, cli.json).await?;
             }
+            CategoryCommands::Rename {
+                list,
+                old_name,
+                new_name,
+                merge,
+                dedupe,
+            } => {
+                cli::commands::category_rename(list, old_name, new_name, *merge, *dedupe, cli.json)
+                    .await?;
+            }
+        },
+        Commands::Tag(tag_cmd) => match tag_cmd {
+            TagCommands::Add { list, tags } => {
+                cli::commands::tag_add(list, tags, cli.json).await?;
+            }
+            TagCommands::Remove { list, tags } => {
+                cli::commands::tag_remove(list, tags, cli.json).await?;
+            }
+            TagCommands::List { list } => {
+                cli::commands::tag_list(list, cli.json)?;
+            }
         },
         Commands::Auth(auth_cmd) => match auth_cmd {
             AuthCommands::Register { email, host } => {
@@ -192,6 +367,9 @@ async fn main() -> Result<()> {
                 cli::commands::auth_logout(cli.json)?;
             }
         },
+        Commands::Whoami => {
+            cli::commands::whoami(cli.json)?;
+        }
         Commands::Server(server_cmd) => match server_cmd {
             ServerCommands::Create {
                 kind,
@@ -210,8 +388,30 @@ async fn main() -> Result<()> {
             } => {
                 cli::commands::server_update(kind, path, content, cli.json).await?;
             }
-            ServerCommands::Delete { kind, path } => {
-                cli::commands::server_delete(kind, path, cli.json).await?;
+            ServerCommands::Delete {
+                kind,
+                path,
+                recursive,
+                force,
+            } => {
+                if *recursive {
+                    cli::commands::server_delete_recursive(kind, path.as_deref(), *force, cli.json)
+                        .await?;
+                } else if let Some(path) = path {
+                    cli::commands::server_delete(kind, path, cli.json).await?;
+                }
+            }
+            ServerCommands::Pull { kind, resume } => {
+                cli::commands::server_pull(kind, *resume, cli.json).await?;
+            }
+            ServerCommands::Ping => {
+                cli::commands::server_ping(cli.json).await?;
+            }
+            ServerCommands::ListDocs => {
+                cli::commands::server_list_docs(cli.json).await?;
+            }
+            ServerCommands::SyncPull { force } => {
+                cli::commands::server_sync_pull(*force, cli.json).await?;
             }
         },
         Commands::Themes(theme_cmd) => match theme_cmd {
@@ -221,12 +421,18 @@ async fn main() -> Result<()> {
             ThemeCommands::Current => {
                 cli::commands::theme_current(cli.json)?;
             }
-            ThemeCommands::Apply { theme } => {
-                cli::commands::theme_apply(theme, cli.json).await?;
+            ThemeCommands::Apply { theme, variant } => {
+                cli::commands::theme_apply(theme, variant, cli.json).await?;
             }
             ThemeCommands::Info { theme } => {
                 cli::commands::theme_info(theme, cli.json)?;
             }
+            ThemeCommands::Preview { theme } => {
+                cli::commands::theme_preview(theme, cli.json)?;
+            }
+            ThemeCommands::New { name, from, force } => {
+                cli::commands::theme_new(name, from.as_deref(), *force, cli.json)?;
+            }
             ThemeCommands::Validate { file } => {
                 cli::commands::theme_validate(file, cli.json)?;
             }
@@ -252,10 +458,27 @@ async fn main() -> Result<()> {
                 cli::commands::user_info(email, cli.json).await?;
             }
         },
-        Commands::Schema => {
-            use lst_core::config::Config;
-            let schema = Config::generate_schema()?;
-            println!("{}", schema);
+        Commands::Schema { kind } => cli::commands::schema(kind.as_deref())?,
+        Commands::Config(config_cmd) => match config_cmd {
+            ConfigCommands::Path => cli::commands::config_path(cli.json)?,
+            ConfigCommands::Show => cli::commands::config_show(cli.json)?,
+            ConfigCommands::Set { key, value } => cli::commands::config_set(key, value, cli.json)?,
+        },
+        Commands::Doctor { fix } => {
+            cli::commands::doctor(*fix, cli.json).await?;
+        }
+        Commands::Stats => {
+            cli::commands::stats(cli.json)?;
+        }
+        Commands::Export { out, include_media } => {
+            cli::commands::export_content(out, *include_media, cli.json)?;
+        }
+        Commands::Import {
+            archive,
+            overwrite,
+            kind,
+        } => {
+            cli::commands::import_content(archive, *overwrite, kind.as_deref(), cli.json)?;
         }
     }
 
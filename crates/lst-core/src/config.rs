@@ -5,7 +5,8 @@ use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::theme::{Theme, ThemeLoader};
+use crate::models::ItemStatus;
+use crate::theme::{Theme, ThemeLoader, ThemeVariant};
 
 /// Configuration for the lst application
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -58,11 +59,85 @@ pub struct UiConfig {
     #[serde(default = "default_confirm_delete")]
     pub confirm_delete: bool,
 
+    /// Move deleted lists and notes to `.trash/` instead of removing them
+    #[serde(default = "default_use_trash")]
+    pub use_trash: bool,
+
+    /// Checkbox symbols used when displaying lists (`ascii`, `unicode` or `emoji`)
+    #[serde(default)]
+    pub checkbox_style: CheckboxStyle,
+
+    /// When the CLI uses color (`auto`, `always`, `never` or `colorblind`)
+    #[serde(default)]
+    pub color_mode: ColorMode,
+
+    /// Command turning HTML into PDF for `lst note export --format pdf`,
+    /// called as `<command> <input.html> <output.pdf>` (defaults to `wkhtmltopdf`)
+    #[serde(default)]
+    pub pdf_renderer: Option<String>,
+
     // Legacy theme config for backwards compatibility
     #[serde(default)]
     pub theme: LegacyThemeConfig,
 }
 
+/// How checkboxes are rendered in the terminal; the stored markdown is unaffected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[cfg_attr(feature = "tauri", derive(Type))]
+#[serde(rename_all = "lowercase")]
+pub enum CheckboxStyle {
+    /// `[ ]` / `[x]`
+    #[default]
+    Ascii,
+    /// `☐` / `☑`
+    Unicode,
+    /// `⬜` / `✅`
+    Emoji,
+}
+
+impl CheckboxStyle {
+    /// Symbol to display for an item with the given status
+    pub fn symbol(self, status: &ItemStatus) -> &'static str {
+        match (self, status) {
+            (CheckboxStyle::Ascii, ItemStatus::Todo) => "[ ]",
+            (CheckboxStyle::Ascii, ItemStatus::Done) => "[x]",
+            (CheckboxStyle::Unicode, ItemStatus::Todo) => "☐",
+            (CheckboxStyle::Unicode, ItemStatus::Done) => "☑",
+            (CheckboxStyle::Emoji, ItemStatus::Todo) => "⬜",
+            (CheckboxStyle::Emoji, ItemStatus::Done) => "✅",
+        }
+    }
+}
+
+/// How the CLI uses color; `NO_COLOR` and piped output turn it off unless `always`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[cfg_attr(feature = "tauri", derive(Type))]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    /// Color on terminals
+    #[default]
+    Auto,
+    /// Color even when piped or with `NO_COLOR` set
+    Always,
+    /// No color at all
+    Never,
+    /// Like `auto`, but item status is shown with distinct glyphs and markers
+    /// instead of relying on color
+    Colorblind,
+}
+
+impl ColorMode {
+    /// Whether to emit ANSI colors, given whether `NO_COLOR` is set and
+    /// whether output goes to a terminal
+    pub fn use_color(self, no_color: bool, is_terminal: bool) -> bool {
+        match self {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto | ColorMode::Colorblind => !no_color && is_terminal,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[cfg_attr(feature = "tauri", derive(Type))]
 pub struct FuzzyConfig {
@@ -80,8 +155,19 @@ pub struct PathsConfig {
     pub kinds: Option<Vec<String>>,
     /// Directory containing theme files (defaults to ~/.config/themes)
     pub themes_dir: Option<PathBuf>,
+    /// Rolling scratch note used by `lst scratch` (defaults to notes/scratch.md;
+    /// relative paths are resolved against the notes directory)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scratch_note: Option<PathBuf>,
+    /// Leave out files matched by `.gitignore` or `.lstignore` in the content
+    /// directory from listings, searches and syncing
+    #[serde(default)]
+    pub respect_gitignore: bool,
 }
 
+/// Environment variable overriding `server.jwt_secret`
+pub const JWT_SECRET_ENV: &str = "LST_JWT_SECRET";
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[cfg_attr(feature = "tauri", derive(Type))]
 pub struct ServerConfig {
@@ -97,6 +183,17 @@ pub struct ServerConfig {
     pub content_db: Option<String>,
     /// Sync database filename (only used when running lst-server)
     pub sync_db: Option<String>,
+    /// Secret used to sign JWTs (only used when running lst-server;
+    /// `LST_JWT_SECRET` takes precedence)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwt_secret: Option<String>,
+    /// Lifetime of issued JWTs in minutes (only used when running lst-server)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwt_expiry_minutes: Option<u32>,
+    /// Stored changes per document after which the server asks clients to
+    /// compact it (only used when running lst-server)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compaction_threshold: Option<u32>,
 }
 
 // SyncdConfig removed - consolidated into SyncSettings
@@ -129,9 +226,33 @@ pub struct SyncSettings {
     #[serde(default = "default_max_file_size")]
     pub max_file_size: u64,
 
-    /// File patterns to exclude from sync
-    #[serde(default)]
-    pub exclude_patterns: Vec<String>,
+    /// Glob patterns for files lst-syncd ignores, matched against the path
+    /// relative to the content directory and each of its components
+    #[serde(default = "default_ignore", alias = "exclude_patterns")]
+    pub ignore: Vec<String>,
+
+    /// Milliseconds lst-syncd waits for further changes to a file before
+    /// syncing it, so an editor's burst of writes on save syncs once
+    #[serde(default = "default_watch_debounce")]
+    pub watch_debounce_ms: u64,
+
+    /// Seconds to wait for the server to answer a request before giving up
+    #[serde(default = "default_request_timeout")]
+    pub request_timeout_seconds: u64,
+
+    /// Seconds to wait for a connection to the server to be established
+    #[serde(default = "default_connect_timeout")]
+    pub connect_timeout_seconds: u64,
+
+    /// How often a failed read or delete request is retried (with backoff);
+    /// creating and updating content is never retried
+    #[serde(default = "default_request_retries")]
+    pub request_retries: u32,
+
+    /// Port of lst-syncd's control endpoint on 127.0.0.1, used by
+    /// `lst sync status` and `lst sync now`
+    #[serde(default = "default_control_port")]
+    pub control_port: u16,
 }
 
 /// Machine-specific state that should not be synced across devices
@@ -190,6 +311,43 @@ fn default_max_file_size() -> u64 {
     10 * 1024 * 1024 // 10MB
 }
 
+fn default_ignore() -> Vec<String> {
+    // Dotfiles, sync conflict copies and editor swap/backup files ("4913" is
+    // the probe file Vim writes before saving)
+    [
+        ".*",
+        "*.conflict.md",
+        "*.swp",
+        "*.swo",
+        "*~",
+        "*.tmp",
+        "4913",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+fn default_watch_debounce() -> u64 {
+    300
+}
+
+fn default_request_timeout() -> u64 {
+    30
+}
+
+fn default_connect_timeout() -> u64 {
+    10
+}
+
+fn default_request_retries() -> u32 {
+    2
+}
+
+fn default_control_port() -> u16 {
+    33334
+}
+
 fn default_max_snapshots() -> usize {
     100
 }
@@ -202,6 +360,10 @@ impl Default for Config {
                 vim_mode: false,
                 leader_key: default_leader_key(),
                 confirm_delete: default_confirm_delete(),
+                use_trash: default_use_trash(),
+                checkbox_style: CheckboxStyle::default(),
+                color_mode: ColorMode::default(),
+                pdf_renderer: None,
                 theme: LegacyThemeConfig::default(),
             },
             fuzzy: FuzzyConfig {
@@ -213,6 +375,8 @@ impl Default for Config {
                 media_dir: None,
                 kinds: None,
                 themes_dir: None,
+                scratch_note: None,
+                respect_gitignore: false,
             },
             server: ServerConfig::default(),
             theme: None,
@@ -229,6 +393,10 @@ impl Default for UiConfig {
             vim_mode: false,
             leader_key: default_leader_key(),
             confirm_delete: default_confirm_delete(),
+            use_trash: default_use_trash(),
+            checkbox_style: CheckboxStyle::default(),
+            color_mode: ColorMode::default(),
+            pdf_renderer: None,
             theme: LegacyThemeConfig::default(),
         }
     }
@@ -250,6 +418,8 @@ impl Default for PathsConfig {
             media_dir: None,
             kinds: None,
             themes_dir: None,
+            scratch_note: None,
+            respect_gitignore: false,
         }
     }
 }
@@ -263,6 +433,9 @@ impl Default for ServerConfig {
             tokens_db: None,
             content_db: None,
             sync_db: None,
+            jwt_secret: None,
+            jwt_expiry_minutes: None,
+            compaction_threshold: None,
         }
     }
 }
@@ -328,6 +501,10 @@ fn default_confirm_delete() -> bool {
     true
 }
 
+fn default_use_trash() -> bool {
+    true
+}
+
 impl Config {
     /// Load configuration from the default location
     pub fn load() -> Result<Self> {
@@ -341,24 +518,31 @@ impl Config {
         let config_path = config_dir.join("config.toml");
         if !config_path.exists() {
             // Create default config if it doesn't exist
-            fs::create_dir_all(&config_dir).context("Failed to create config directory")?;
-            let default_config = Self::default();
-            let mut toml_str = toml::to_string_pretty(&default_config)
-                .context("Failed to serialize default config")?;
+            return Self::write_default_to(&config_path);
+        }
+        Self::load_from(&config_path)
+    }
 
-            // Add schema reference header
-            let header = r#"# LST Configuration File
+    /// Write the default configuration (with schema header) to `path`
+    pub fn write_default_to(path: &Path) -> Result<Self> {
+        if let Some(config_dir) = path.parent() {
+            fs::create_dir_all(config_dir).context("Failed to create config directory")?;
+        }
+        let default_config = Self::default();
+        let mut toml_str = toml::to_string_pretty(&default_config)
+            .context("Failed to serialize default config")?;
+
+        // Add schema reference header
+        let header = r#"# LST Configuration File
 # Schema: https://json-schema.org/draft-07/schema#
 # LST Configuration Schema: ./lst-config-schema.json
 # For LSP/editor validation, configure your editor to use the schema above
 
 "#;
-            toml_str = format!("{}{}", header, toml_str);
+        toml_str = format!("{}{}", header, toml_str);
 
-            fs::write(&config_path, toml_str).context("Failed to write default config file")?;
-            return Ok(default_config);
-        }
-        Self::load_from(&config_path)
+        fs::write(path, toml_str).context("Failed to write default config file")?;
+        Ok(default_config)
     }
 
     /// Load configuration from a specific path
@@ -384,6 +568,25 @@ impl Config {
         Ok(())
     }
 
+    /// Dotted keys accepted by [`Config::set_value_at`], e.g. `paths.content_dir`
+    pub fn settable_keys() -> Vec<String> {
+        schema_keys().into_keys().collect()
+    }
+
+    /// Set `key` to `value` in the config file at `path`, keeping its comments
+    /// and layout. String settings take `value` verbatim, anything else is
+    /// parsed as a TOML value (e.g. `true`, `42`, `["a", "b"]`)
+    pub fn set_value_at(path: &Path, key: &str, value: &str) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        let updated = set_toml_value(&content, key, value)?;
+        let config: Self =
+            toml::from_str(&updated).with_context(|| format!("Invalid value for {}", key))?;
+        fs::write(path, updated)
+            .with_context(|| format!("Failed to write config file: {}", path.display()))?;
+        Ok(config)
+    }
+
     /// Get the content directory, using default if not configured
     pub fn get_content_dir(&self) -> PathBuf {
         if let Some(ref content_dir) = self.paths.content_dir {
@@ -395,6 +598,78 @@ impl Config {
         }
     }
 
+    /// The config as `lst config show` prints it: environment overrides (looked
+    /// up through `env`) applied, and secrets replaced by `<redacted>`
+    pub fn for_display(&self, env: impl Fn(&str) -> Option<String>) -> Config {
+        let mut config = self.clone();
+        if let Some(secret) = env(JWT_SECRET_ENV) {
+            config.server.jwt_secret = Some(secret);
+        }
+        if config.server.jwt_secret.is_some() {
+            config.server.jwt_secret = Some("<redacted>".to_string());
+        }
+        config
+    }
+
+    /// Content directory lst reads and writes: `paths.content_dir` when set
+    /// (with `~` and environment variables expanded, relative to the working
+    /// directory), otherwise `content/` in the working directory. The
+    /// directory is not created
+    pub fn content_dir(&self) -> Result<PathBuf> {
+        let working_dir = std::env::current_dir().context("Failed to get current directory")?;
+        Ok(content_dir_with(self, &working_dir, expand_path))
+    }
+
+    /// How long to wait for the sync server to answer a request
+    pub fn request_timeout(&self) -> std::time::Duration {
+        let seconds = self
+            .sync
+            .as_ref()
+            .map_or_else(default_request_timeout, |sync| sync.request_timeout_seconds);
+        std::time::Duration::from_secs(seconds.max(1))
+    }
+
+    /// How long to wait for a connection to the sync server
+    pub fn connect_timeout(&self) -> std::time::Duration {
+        let seconds = self
+            .sync
+            .as_ref()
+            .map_or_else(default_connect_timeout, |sync| sync.connect_timeout_seconds);
+        std::time::Duration::from_secs(seconds.max(1))
+    }
+
+    /// How often idempotent requests to the sync server are retried
+    pub fn request_retries(&self) -> u32 {
+        self.sync
+            .as_ref()
+            .map_or_else(default_request_retries, |sync| sync.request_retries)
+    }
+
+    /// Glob patterns for files lst-syncd does not watch
+    pub fn sync_ignore(&self) -> Vec<String> {
+        self.sync
+            .as_ref()
+            .map_or_else(default_ignore, |sync| sync.ignore.clone())
+    }
+
+    /// How long file events are collected before a changed file is synced
+    pub fn watch_debounce(&self) -> std::time::Duration {
+        let millis = self
+            .sync
+            .as_ref()
+            .map_or_else(default_watch_debounce, |sync| sync.watch_debounce_ms);
+        std::time::Duration::from_millis(millis)
+    }
+
+    /// Local address of lst-syncd's control endpoint
+    pub fn syncd_control_addr(&self) -> std::net::SocketAddr {
+        let port = self
+            .sync
+            .as_ref()
+            .map_or_else(default_control_port, |sync| sync.control_port);
+        std::net::SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
     /// Initialize sync configuration with defaults
     pub fn init_sync(&mut self) -> Result<()> {
         if self.sync.is_none() {
@@ -408,7 +683,12 @@ impl Config {
                 encryption_key_ref: Some("lst-master-key".to_string()),
                 interval_seconds: default_sync_interval(),
                 max_file_size: default_max_file_size(),
-                exclude_patterns: vec![".*".to_string(), "*.tmp".to_string(), "*.swp".to_string()],
+                ignore: default_ignore(),
+                watch_debounce_ms: default_watch_debounce(),
+                request_timeout_seconds: default_request_timeout(),
+                connect_timeout_seconds: default_connect_timeout(),
+                request_retries: default_request_retries(),
+                control_port: default_control_port(),
             });
 
             self.storage = Some(StorageConfig {
@@ -430,6 +710,17 @@ impl Config {
         }
     }
 
+    /// Get the current theme for the given system appearance, switching to
+    /// its light or dark variant when it has one (see
+    /// [`Theme::resolve_for_variant`])
+    pub fn get_theme_for(&self, appearance: Option<ThemeVariant>) -> Result<Theme> {
+        let theme = self.get_theme()?;
+        match appearance {
+            Some(variant) => theme.resolve_for_variant(variant, &self.get_theme_loader()),
+            None => Ok(theme),
+        }
+    }
+
     /// Set the current theme
     pub fn set_theme(&mut self, theme: Theme) {
         self.theme = Some(theme);
@@ -471,6 +762,111 @@ impl Config {
     }
 }
 
+/// Leaf settings of the config schema, mapped to whether they hold a string
+fn schema_keys() -> BTreeMap<String, bool> {
+    let schema = serde_json::to_value(schemars::schema_for!(Config)).unwrap_or_default();
+    let mut keys = BTreeMap::new();
+    collect_schema_keys(&schema, &schema["definitions"], "", &mut keys);
+    keys
+}
+
+fn collect_schema_keys(
+    schema: &serde_json::Value,
+    definitions: &serde_json::Value,
+    prefix: &str,
+    keys: &mut BTreeMap<String, bool>,
+) {
+    let schema = resolve_schema(schema, definitions);
+    match schema["properties"].as_object() {
+        Some(properties) => {
+            for (name, property) in properties {
+                let key = if prefix.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{}.{}", prefix, name)
+                };
+                collect_schema_keys(property, definitions, &key, keys);
+            }
+        }
+        None if !prefix.is_empty() => {
+            keys.insert(prefix.to_string(), schema_is_string(schema));
+        }
+        None => {}
+    }
+}
+
+/// Follow `$ref`s and `allOf`/`anyOf` wrappers (as used for defaults and
+/// `Option`s) to the schema describing the actual value
+fn resolve_schema<'a>(
+    schema: &'a serde_json::Value,
+    definitions: &'a serde_json::Value,
+) -> &'a serde_json::Value {
+    if let Some(name) = schema["$ref"]
+        .as_str()
+        .and_then(|r| r.strip_prefix("#/definitions/"))
+    {
+        return resolve_schema(&definitions[name], definitions);
+    }
+    let wrapped = ["allOf", "anyOf"]
+        .iter()
+        .filter_map(|combinator| schema[*combinator].as_array())
+        .flatten()
+        .find(|inner| inner["type"] != "null");
+    match wrapped {
+        Some(inner) => resolve_schema(inner, definitions),
+        None => schema,
+    }
+}
+
+fn schema_is_string(schema: &serde_json::Value) -> bool {
+    let is_string = |schema: &serde_json::Value| match &schema["type"] {
+        serde_json::Value::String(ty) => ty == "string",
+        serde_json::Value::Array(types) => types.iter().any(|ty| ty == "string"),
+        _ => false,
+    };
+    match schema["oneOf"].as_array() {
+        Some(variants) => variants.iter().all(is_string),
+        None => is_string(schema),
+    }
+}
+
+/// Set the dotted `key` in the TOML document `content`, see [`Config::set_value_at`]
+fn set_toml_value(content: &str, key: &str, value: &str) -> Result<String> {
+    let keys = schema_keys();
+    let Some(&is_string) = keys.get(key) else {
+        let known: Vec<&str> = keys.keys().map(String::as_str).collect();
+        anyhow::bail!(
+            "Unknown config key '{}'. Known keys:\n  {}",
+            key,
+            known.join("\n  ")
+        );
+    };
+
+    let mut doc: toml_edit::DocumentMut = content.parse().context("Failed to parse config file")?;
+    let mut new_value = if is_string {
+        toml_edit::Value::from(value)
+    } else {
+        value
+            .parse::<toml_edit::Value>()
+            .with_context(|| format!("Invalid value for {}: {}", key, value))?
+    };
+
+    let (sections, field) = key.rsplit_once('.').unwrap_or(("", key));
+    let mut table = doc.as_table_mut();
+    for section in sections.split('.').filter(|s| !s.is_empty()) {
+        table = table
+            .entry(section)
+            .or_insert_with(toml_edit::table)
+            .as_table_mut()
+            .with_context(|| format!("'{}' is not a table in the config file", section))?;
+    }
+    if let Some(existing) = table.get(field).and_then(|item| item.as_value()) {
+        *new_value.decor_mut() = existing.decor().clone();
+    }
+    table.insert(field, toml_edit::Item::Value(new_value));
+    Ok(doc.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -484,6 +880,167 @@ mod tests {
         assert!(config_with_header.contains("[fuzzy]"));
         assert!(config_with_header.contains("threshold = 50.0"));
     }
+
+    #[test]
+    fn test_resolved_paths_for_temp_home() {
+        let home = tempfile::tempdir().unwrap();
+        let config = Config::default();
+        let state = State::default();
+
+        let work = home.path().join("work");
+
+        let paths = ResolvedPaths::for_home(home.path(), &work, &config, &state);
+        assert_eq!(paths.config, home.path().join(".config/lst/config.toml"));
+        assert_eq!(paths.state, home.path().join(".local/share/lst/state.toml"));
+        // Like the storage layer, the content dir defaults to the working directory
+        assert_eq!(paths.content, work.join("content"));
+        assert_eq!(paths.sync_db, home.path().join(".local/share/lst/sync.db"));
+
+        let mut config = Config::default();
+        config.paths.content_dir = Some(home.path().join("notes"));
+        let paths = ResolvedPaths::for_home(home.path(), &work, &config, &state);
+        assert_eq!(paths.content, home.path().join("notes"));
+        config.paths.content_dir = Some(PathBuf::from("~/lst"));
+        let paths = ResolvedPaths::for_home(home.path(), &work, &config, &state);
+        assert_eq!(paths.content, home.path().join("lst"));
+        config.paths.content_dir = Some(PathBuf::from("data"));
+        let paths = ResolvedPaths::for_home(home.path(), &work, &config, &state);
+        assert_eq!(paths.content, work.join("data"));
+    }
+
+    #[test]
+    fn test_checkbox_styles_render_symbols() {
+        let mut list = crate::models::List::new("fixture".to_string());
+        list.add_item("milk".to_string());
+        list.add_item("bread".to_string());
+        list.uncategorized_items[1].status = ItemStatus::Done;
+
+        let render = |style: CheckboxStyle| -> Vec<String> {
+            list.all_items()
+                .map(|item| format!("{} {}", style.symbol(&item.status), item.text))
+                .collect()
+        };
+
+        assert_eq!(render(CheckboxStyle::Ascii), vec!["[ ] milk", "[x] bread"]);
+        assert_eq!(render(CheckboxStyle::Unicode), vec!["☐ milk", "☑ bread"]);
+        assert_eq!(render(CheckboxStyle::Emoji), vec!["⬜ milk", "✅ bread"]);
+    }
+
+    #[test]
+    fn test_checkbox_style_defaults_to_ascii() {
+        let config: Config = toml::from_str("[ui]\n").unwrap();
+        assert_eq!(config.ui.checkbox_style, CheckboxStyle::Ascii);
+        let config: Config = toml::from_str("[ui]\ncheckbox_style = \"emoji\"\n").unwrap();
+        assert_eq!(config.ui.checkbox_style, CheckboxStyle::Emoji);
+    }
+
+    fn expand(path: &str) -> PathBuf {
+        expand_path_with(
+            Path::new(path),
+            |name| match name {
+                "HOME" => Some("/home/me".to_string()),
+                "XDG_DATA_HOME" => Some("/data".to_string()),
+                _ => None,
+            },
+            |user| match user {
+                "" => Some(PathBuf::from("/home/me")),
+                "alice" => Some(PathBuf::from("/home/alice")),
+                _ => None,
+            },
+        )
+    }
+
+    #[test]
+    fn test_expand_path_passes_plain_paths_through() {
+        assert_eq!(expand("/srv/lst"), PathBuf::from("/srv/lst"));
+        assert_eq!(expand("notes/lst"), PathBuf::from("notes/lst"));
+        assert_eq!(expand("a~b"), PathBuf::from("a~b"));
+    }
+
+    #[test]
+    fn test_expand_path_expands_tilde_forms() {
+        assert_eq!(expand("~"), PathBuf::from("/home/me"));
+        assert_eq!(expand("~/notes"), PathBuf::from("/home/me/notes"));
+        assert_eq!(expand("~alice/notes"), PathBuf::from("/home/alice/notes"));
+        assert_eq!(expand("~nobody/notes"), PathBuf::from("~nobody/notes"));
+    }
+
+    #[test]
+    fn test_expand_path_expands_environment_variables() {
+        assert_eq!(expand("$HOME/lst"), PathBuf::from("/home/me/lst"));
+        assert_eq!(
+            expand("${XDG_DATA_HOME}/lst/sync.db"),
+            PathBuf::from("/data/lst/sync.db")
+        );
+        assert_eq!(expand("$XDG_DATA_HOME-x"), PathBuf::from("/data-x"));
+        assert_eq!(expand("~/$HOME"), PathBuf::from("/home/me/home/me"));
+    }
+
+    #[test]
+    fn test_expand_path_leaves_undefined_variables_literal() {
+        assert_eq!(expand("$NOPE/lst"), PathBuf::from("$NOPE/lst"));
+        assert_eq!(expand("${NOPE}/lst"), PathBuf::from("${NOPE}/lst"));
+        assert_eq!(expand("/cost/$5"), PathBuf::from("/cost/$5"));
+        assert_eq!(expand("/a/$/b"), PathBuf::from("/a/$/b"));
+        assert_eq!(expand("${HOME"), PathBuf::from("${HOME"));
+    }
+
+    #[test]
+    fn test_config_for_display_redacts_secrets_and_applies_env() {
+        let mut config = Config::default();
+        let shown = config.for_display(|_| None);
+        assert_eq!(shown.server.jwt_secret, None);
+        let shown =
+            config.for_display(|name| (name == JWT_SECRET_ENV).then(|| "from-env".to_string()));
+        assert_eq!(shown.server.jwt_secret.as_deref(), Some("<redacted>"));
+
+        config.server.jwt_secret = Some("hunter2".to_string());
+        let toml = toml::to_string_pretty(&config.for_display(|_| None)).unwrap();
+        assert!(!toml.contains("hunter2"));
+        assert!(toml.contains("jwt_secret = \"<redacted>\""));
+        assert_eq!(config.server.jwt_secret.as_deref(), Some("hunter2"));
+    }
+
+    #[test]
+    fn test_set_value_round_trips_and_keeps_comments() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        Config::write_default_to(&path).unwrap();
+
+        let config = Config::set_value_at(&path, "paths.content_dir", "/tmp/notes").unwrap();
+        assert_eq!(config.paths.content_dir, Some(PathBuf::from("/tmp/notes")));
+        let config = Config::set_value_at(&path, "ui.vim_mode", "true").unwrap();
+        assert!(config.ui.vim_mode);
+
+        let reloaded = Config::load_from(&path).unwrap();
+        assert_eq!(
+            reloaded.paths.content_dir,
+            Some(PathBuf::from("/tmp/notes"))
+        );
+        assert!(reloaded.ui.vim_mode);
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("# LST Configuration File"));
+
+        assert!(Config::set_value_at(&path, "fuzzy.threshold", "lots").is_err());
+        assert!(Config::set_value_at(&path, "ui.checkbox_style", "fancy").is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+    }
+
+    #[test]
+    fn test_set_value_rejects_unknown_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        Config::write_default_to(&path).unwrap();
+        let before = fs::read_to_string(&path).unwrap();
+
+        let err = Config::set_value_at(&path, "paths.contnet_dir", "/tmp")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Unknown config key 'paths.contnet_dir'"));
+        assert!(err.contains("paths.content_dir"));
+        assert!(Config::settable_keys().contains(&"sync.server_url".to_string()));
+        assert_eq!(fs::read_to_string(&path).unwrap(), before);
+    }
 }
 
 impl State {
@@ -623,9 +1180,9 @@ impl State {
         }
     }
 
-    /// Get sync database path
-    pub fn get_sync_database_path(&self) -> Option<&PathBuf> {
-        self.sync.database_path.as_ref()
+    /// Get sync database path, with `~` and environment variables expanded
+    pub fn get_sync_database_path(&self) -> Option<PathBuf> {
+        self.sync.database_path.as_deref().map(expand_path)
     }
 
     /// Set sync database path
@@ -645,6 +1202,155 @@ impl State {
     }
 }
 
+/// Expand a configured path: a leading `~` or `~user` becomes that user's home
+/// directory and `$VAR` / `${VAR}` are replaced by environment variables.
+/// Undefined variables are left as written (with a warning); relative paths
+/// stay relative to the current directory
+pub fn expand_path(path: &Path) -> PathBuf {
+    expand_path_with(path, |name| std::env::var(name).ok(), user_home_dir)
+}
+
+fn expand_path_for_home(path: &Path, home_dir: &Path) -> PathBuf {
+    expand_path_with(
+        path,
+        |name| std::env::var(name).ok(),
+        |user| match user {
+            "" => Some(home_dir.to_path_buf()),
+            user => user_home_dir(user),
+        },
+    )
+}
+
+/// [`expand_path`] with explicit lookups for environment variables and for
+/// home directories (`""` meaning the current user)
+fn expand_path_with(
+    path: &Path,
+    var: impl Fn(&str) -> Option<String>,
+    home: impl Fn(&str) -> Option<PathBuf>,
+) -> PathBuf {
+    let raw = path.to_string_lossy();
+    if !raw.starts_with('~') && !raw.contains('$') {
+        return path.to_path_buf();
+    }
+
+    let mut expanded = String::with_capacity(raw.len());
+    let mut rest: &str = &raw;
+    if let Some(after_tilde) = rest.strip_prefix('~') {
+        let end = after_tilde
+            .find(['/', std::path::MAIN_SEPARATOR])
+            .unwrap_or(after_tilde.len());
+        let user = &after_tilde[..end];
+        match home(user) {
+            Some(dir) => {
+                expanded.push_str(&dir.to_string_lossy());
+                rest = &after_tilde[end..];
+            }
+            None => eprintln!("Warning: cannot resolve home directory in path {}", raw),
+        }
+    }
+
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, literal_len) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], end + 3),
+                None => ("", 1),
+            },
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], end + 1)
+            }
+        };
+        let literal = &rest[start..start + literal_len];
+        match var(name) {
+            Some(value) if !name.is_empty() => expanded.push_str(&value),
+            _ => {
+                if !name.is_empty() {
+                    eprintln!(
+                        "Warning: environment variable {} is not set (in path {})",
+                        name, raw
+                    );
+                }
+                expanded.push_str(literal);
+            }
+        }
+        rest = &rest[start + literal_len..];
+    }
+    expanded.push_str(rest);
+    PathBuf::from(expanded)
+}
+
+/// Home directory of `user`, or of the current user when `user` is empty
+fn user_home_dir(user: &str) -> Option<PathBuf> {
+    if user.is_empty() {
+        return dirs::home_dir();
+    }
+    // Look the user up in the passwd database (name:pw:uid:gid:gecos:home:shell)
+    let passwd = fs::read_to_string("/etc/passwd").ok()?;
+    passwd.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split(':').collect();
+        (fields.len() > 5 && fields[0] == user).then(|| PathBuf::from(fields[5]))
+    })
+}
+
+/// See [`Config::content_dir`]; `expand` resolves a configured directory
+fn content_dir_with(
+    config: &Config,
+    working_dir: &Path,
+    expand: impl Fn(&Path) -> PathBuf,
+) -> PathBuf {
+    match config.paths.content_dir.as_deref() {
+        Some(dir) => working_dir.join(expand(dir)),
+        None => working_dir.join("content"),
+    }
+}
+
+/// Resolved on-disk locations of lst's configuration, state and data
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResolvedPaths {
+    pub config: PathBuf,
+    pub state: PathBuf,
+    pub content: PathBuf,
+    pub sync_db: PathBuf,
+}
+
+impl ResolvedPaths {
+    /// Resolve paths for the current user, honouring `LST_CONFIG` and `LST_STATE`
+    pub fn resolve(config: &Config, state: &State) -> Result<Self> {
+        let home_dir = dirs::home_dir().context("Could not determine home directory")?;
+        let working_dir = std::env::current_dir().context("Failed to get current directory")?;
+        let mut paths = Self::for_home(&home_dir, &working_dir, config, state);
+        if let Ok(custom_path) = std::env::var("LST_CONFIG") {
+            paths.config = PathBuf::from(custom_path);
+        }
+        if let Ok(custom_path) = std::env::var("LST_STATE") {
+            paths.state = PathBuf::from(custom_path);
+        }
+        Ok(paths)
+    }
+
+    /// Default locations below `home_dir`; the content dir is resolved like
+    /// [`Config::content_dir`] from `working_dir`, and it and the sync database
+    /// come from `config` and `state` when they are set there
+    pub fn for_home(home_dir: &Path, working_dir: &Path, config: &Config, state: &State) -> Self {
+        let data_dir = home_dir.join(".local").join("share").join("lst");
+        Self {
+            config: home_dir.join(".config").join("lst").join("config.toml"),
+            state: data_dir.join("state.toml"),
+            content: content_dir_with(config, working_dir, |dir| {
+                expand_path_for_home(dir, home_dir)
+            }),
+            sync_db: match &state.sync.database_path {
+                Some(path) => expand_path_for_home(path, home_dir),
+                None => data_dir.join("sync.db"),
+            },
+        }
+    }
+}
+
 // Global cached configuration: loaded once on first access
 lazy_static::lazy_static! {
     static ref GLOBAL_CONFIG: Config = Config::load().expect("Failed to load config");
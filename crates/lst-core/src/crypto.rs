@@ -66,15 +66,7 @@ pub fn derive_key_from_credentials(
 
 /// Load a previously saved encryption key from disk
 pub fn load_key(path: &Path) -> Result<[u8; 32]> {
-    let expanded = if path.starts_with("~/") {
-        if let Some(home) = dirs::home_dir() {
-            home.join(path.strip_prefix("~/").unwrap())
-        } else {
-            return Err(anyhow!("Cannot determine home directory"));
-        }
-    } else {
-        path.to_path_buf()
-    };
+    let expanded = crate::config::expand_path(path);
 
     if expanded.exists() {
         let data = fs::read(&expanded)
@@ -163,8 +155,8 @@ pub fn resolve_key_path(encryption_key_ref: &str) -> Result<std::path::PathBuf>
         // Use platform-appropriate path
         get_master_key_path()
     } else {
-        // Treat as literal path (supports existing behavior for custom paths)
-        Ok(std::path::PathBuf::from(encryption_key_ref))
+        // Treat as a custom path (with `~` and environment variables expanded)
+        Ok(crate::config::expand_path(Path::new(encryption_key_ref)))
     }
 }
 
@@ -174,22 +166,14 @@ pub fn resolve_mobile_key_path(encryption_key_ref: &str) -> Result<std::path::Pa
         // Use mobile-specific path
         get_mobile_master_key_path()
     } else {
-        // Treat as literal path (supports existing behavior for custom paths)
-        Ok(std::path::PathBuf::from(encryption_key_ref))
+        // Treat as a custom path (with `~` and environment variables expanded)
+        Ok(crate::config::expand_path(Path::new(encryption_key_ref)))
     }
 }
 
 /// Save a derived key to the key file for consistency
 pub fn save_derived_key(path: &Path, key: &[u8; 32]) -> Result<()> {
-    let expanded = if path.starts_with("~/") {
-        if let Some(home) = dirs::home_dir() {
-            home.join(path.strip_prefix("~/").unwrap())
-        } else {
-            return Err(anyhow!("Cannot determine home directory"));
-        }
-    } else {
-        path.to_path_buf()
-    };
+    let expanded = crate::config::expand_path(path);
 
     if let Some(parent) = expanded.parent() {
         fs::create_dir_all(parent)
@@ -239,3 +223,70 @@ pub fn decrypt(data: &[u8], key: &[u8; 32]) -> Result<Vec<u8>> {
         .map_err(|e| anyhow!("Decryption failed: {e}"))?;
     Ok(plaintext)
 }
+
+/// Encrypt a document path (relative to the content directory) for use as its
+/// filename on the server. The nonce is derived from the key and the path, so
+/// the same path always encrypts to the same filename and the server can match
+/// documents by filename without learning the path. Returns base64 of
+/// nonce || ciphertext, the same layout as [`encrypt`]
+pub fn encrypt_filename(path: &str, key: &[u8; 32]) -> Result<String> {
+    let cipher =
+        XChaCha20Poly1305::new_from_slice(key).map_err(|e| anyhow!("Invalid key length: {e}"))?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"lst-filename-nonce-v1:");
+    hasher.update(key);
+    hasher.update(path.as_bytes());
+    let digest = hasher.finalize();
+    let nonce_bytes: [u8; 24] = digest[..24]
+        .try_into()
+        .map_err(|_| anyhow!("Invalid nonce length"))?;
+    let nonce = XNonce::from(nonce_bytes);
+
+    let ciphertext = cipher
+        .encrypt(&nonce, path.as_bytes())
+        .map_err(|e| anyhow!("Encryption failed: {e}"))?;
+
+    let mut out = Vec::with_capacity(24 + ciphertext.len());
+    out.extend_from_slice(&nonce_bytes);
+    out.extend_from_slice(&ciphertext);
+    Ok(general_purpose::STANDARD.encode(out))
+}
+
+/// Decrypt a server filename produced by [`encrypt_filename`] (or by
+/// [`encrypt`] and base64, as older clients did) back into the relative path
+pub fn decrypt_filename(filename: &str, key: &[u8; 32]) -> Result<String> {
+    let raw = general_purpose::STANDARD
+        .decode(filename)
+        .context("Filename is not valid base64")?;
+    let plaintext = decrypt(&raw, key)?;
+    String::from_utf8(plaintext).context("Decrypted filename is not valid UTF-8")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filename_round_trip() {
+        let key = [7u8; 32];
+        let path = "lists/groceries/weekly.md";
+        let encrypted = encrypt_filename(path, &key).unwrap();
+        assert!(!encrypted.contains("groceries"));
+        assert_eq!(decrypt_filename(&encrypted, &key).unwrap(), path);
+        assert!(decrypt_filename(&encrypted, &[8u8; 32]).is_err());
+
+        // Filenames from older clients used a random nonce
+        let legacy = general_purpose::STANDARD.encode(encrypt(path.as_bytes(), &key).unwrap());
+        assert_eq!(decrypt_filename(&legacy, &key).unwrap(), path);
+    }
+
+    #[test]
+    fn test_identical_paths_encrypt_identically() {
+        let key = [7u8; 32];
+        let a = encrypt_filename("notes/ideas.md", &key).unwrap();
+        assert_eq!(a, encrypt_filename("notes/ideas.md", &key).unwrap());
+        assert_ne!(a, encrypt_filename("notes/ideas2.md", &key).unwrap());
+        assert_ne!(a, encrypt_filename("notes/ideas.md", &[9u8; 32]).unwrap());
+    }
+}
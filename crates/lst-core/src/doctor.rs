@@ -0,0 +1,188 @@
+use crate::config::Config;
+use anyhow::Result;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Outcome of a single doctor check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    /// Nothing to do
+    Ok,
+    /// Something is wrong and was left as is
+    Problem,
+    /// Something was wrong and has been repaired
+    Fixed,
+    /// A repair was attempted but failed
+    FixFailed,
+}
+
+/// A check performed by `lst doctor`, with a human-readable detail line
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub check: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl CheckResult {
+    pub fn new(check: &str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            check: check.to_string(),
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Check that the content directory and its `lists/` and `notes/` subdirectories
+/// exist, creating missing ones when `fix` is set
+pub fn check_content_dirs(content_dir: &Path, fix: bool) -> Vec<CheckResult> {
+    let dirs = [
+        content_dir.to_path_buf(),
+        content_dir.join("lists"),
+        content_dir.join("notes"),
+    ];
+
+    dirs.iter()
+        .map(|dir| {
+            let check = "content dir";
+            let shown = dir.display().to_string();
+            if dir.is_dir() {
+                CheckResult::new(check, CheckStatus::Ok, shown)
+            } else if !fix {
+                CheckResult::new(check, CheckStatus::Problem, format!("{} is missing", shown))
+            } else {
+                match fs::create_dir_all(dir) {
+                    Ok(()) => {
+                        CheckResult::new(check, CheckStatus::Fixed, format!("created {}", shown))
+                    }
+                    Err(e) => CheckResult::new(
+                        check,
+                        CheckStatus::FixFailed,
+                        format!("could not create {}: {}", shown, e),
+                    ),
+                }
+            }
+        })
+        .collect()
+}
+
+/// Whether `path` holds a config file that fails to parse
+pub fn config_is_invalid(path: &Path) -> bool {
+    path.exists() && Config::load_from(path).is_err()
+}
+
+/// Backup location used before an invalid config is replaced
+pub fn config_backup_path(path: &Path) -> PathBuf {
+    path.with_extension("toml.bak")
+}
+
+/// Check the config file. With `fix`, a missing config is written with the
+/// defaults; an invalid one is only replaced when `replace_invalid` is also set
+/// (the caller confirms first), after copying it to [`config_backup_path`]
+pub fn check_config_file(path: &Path, fix: bool, replace_invalid: bool) -> CheckResult {
+    let check = "config";
+    let shown = path.display().to_string();
+
+    if !path.exists() {
+        if !fix {
+            return CheckResult::new(check, CheckStatus::Problem, format!("{} is missing", shown));
+        }
+        return match Config::write_default_to(path) {
+            Ok(_) => CheckResult::new(
+                check,
+                CheckStatus::Fixed,
+                format!("wrote default config to {}", shown),
+            ),
+            Err(e) => CheckResult::new(check, CheckStatus::FixFailed, format!("{:#}", e)),
+        };
+    }
+
+    let err = match Config::load_from(path) {
+        Ok(_) => return CheckResult::new(check, CheckStatus::Ok, shown),
+        Err(e) => e,
+    };
+    if !(fix && replace_invalid) {
+        return CheckResult::new(check, CheckStatus::Problem, format!("{:#}", err));
+    }
+
+    let backup = config_backup_path(path);
+    let replaced: Result<()> = fs::copy(path, &backup)
+        .map_err(anyhow::Error::from)
+        .and_then(|_| Config::write_default_to(path).map(|_| ()));
+    match replaced {
+        Ok(()) => CheckResult::new(
+            check,
+            CheckStatus::Fixed,
+            format!(
+                "replaced invalid config with defaults (previous file kept at {})",
+                backup.display()
+            ),
+        ),
+        Err(e) => CheckResult::new(check, CheckStatus::FixFailed, format!("{:#}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fix_creates_missing_content_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let content = dir.path().join("content");
+
+        let report = check_content_dirs(&content, false);
+        assert!(report.iter().all(|r| r.status == CheckStatus::Problem));
+        assert!(!content.exists());
+
+        let report = check_content_dirs(&content, true);
+        assert_eq!(report.len(), 3);
+        assert!(report.iter().all(|r| r.status == CheckStatus::Fixed));
+        assert!(report[0].detail.contains("created"));
+        assert!(content.join("lists").is_dir());
+        assert!(content.join("notes").is_dir());
+
+        let report = check_content_dirs(&content, true);
+        assert!(report.iter().all(|r| r.status == CheckStatus::Ok));
+    }
+
+    #[test]
+    fn test_fix_creates_the_content_dir_lists_are_read_from() {
+        use crate::config::{ResolvedPaths, State};
+
+        // `lst doctor --fix` checks the resolved content dir, which must be
+        // the working directory's content/ that storage falls back to
+        let home = tempfile::tempdir().unwrap();
+        let work = home.path().join("work");
+        let paths =
+            ResolvedPaths::for_home(home.path(), &work, &Config::default(), &State::default());
+
+        check_content_dirs(&paths.content, true);
+        assert!(work.join("content").join("lists").is_dir());
+        assert!(work.join("content").join("notes").is_dir());
+        assert!(!home.path().join("lst").exists());
+    }
+
+    #[test]
+    fn test_invalid_config_is_only_replaced_when_allowed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "not = [valid").unwrap();
+
+        assert!(config_is_invalid(&path));
+        let result = check_config_file(&path, true, false);
+        assert_eq!(result.status, CheckStatus::Problem);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "not = [valid");
+
+        let result = check_config_file(&path, true, true);
+        assert_eq!(result.status, CheckStatus::Fixed);
+        assert!(!config_is_invalid(&path));
+        assert_eq!(
+            fs::read_to_string(config_backup_path(&path)).unwrap(),
+            "not = [valid"
+        );
+    }
+}
@@ -1,6 +1,7 @@
 pub mod commands;
 pub mod config;
 pub mod crypto;
+pub mod doctor;
 pub mod models;
 pub mod storage;
 pub mod sync;
@@ -1,13 +1,16 @@
 use crate::storage::get_lists_dir;
-use chrono::{DateTime, Utc};
+use crate::theme::Theme;
+use chrono::{DateTime, NaiveDate, Utc};
 use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
 use rand::distributions::{Alphanumeric, DistString};
 use regex::Regex;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "tauri")]
 use specta::Type;
 
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use uuid::Uuid;
 
@@ -21,7 +24,7 @@ pub fn generate_anchor() -> String {
 }
 
 /// Represents the metadata for a list
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[cfg_attr(feature = "tauri", derive(Type))]
 pub struct ListMetadata {
     /// Unique identifier for the list
@@ -35,21 +38,131 @@ pub struct ListMetadata {
     #[serde(default)]
     pub sharing: Vec<String>,
 
+    /// Free-form tags used to group and filter lists
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Per-category heading colors: a palette key (e.g. `base0B`) or `#rrggbb`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub category_colors: BTreeMap<String, String>,
+
+    /// Pinned lists are shown before all others by `lst ls`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+
+    /// Per-list display and insertion defaults (the `list:` table)
+    #[serde(default, skip_serializing_if = "ListSettings::is_empty")]
+    pub list: ListSettings,
+
+    /// When the list was created (absent for lists written before it was recorded)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<DateTime<Utc>>,
+
     /// When the list was last updated
     #[serde(default = "Utc::now")]
     pub updated: DateTime<Utc>,
 }
 
+/// Per-list defaults stored under `list:` in the frontmatter
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[cfg_attr(feature = "tauri", derive(Type))]
+pub struct ListSettings {
+    /// Item order used by `ls` when no `--sort` is given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_sort: Option<ListSort>,
+
+    /// Category that items added without one are placed in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_category: Option<String>,
+}
+
+impl ListSettings {
+    fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// Order of items within each category when displaying a list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "tauri", derive(Type))]
+pub enum ListSort {
+    /// Case-insensitive alphabetical order of the item text
+    Alpha,
+    /// Order in which items were added, i.e. document order
+    Created,
+    /// High priority first, see [`priority_order`]
+    Priority,
+}
+
+impl ListSort {
+    /// Parse a sort name as used on the command line
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "alpha" => Some(ListSort::Alpha),
+            "created" => Some(ListSort::Created),
+            "priority" => Some(ListSort::Priority),
+            _ => None,
+        }
+    }
+
+    /// Indices of `items` in this order, stable among equal items
+    pub fn order(self, items: &[ListItem]) -> Vec<usize> {
+        match self {
+            ListSort::Alpha => {
+                let mut order: Vec<usize> = (0..items.len()).collect();
+                order.sort_by_cached_key(|&i| items[i].text.to_lowercase());
+                order
+            }
+            ListSort::Created => (0..items.len()).collect(),
+            ListSort::Priority => priority_order(items),
+        }
+    }
+}
+
 /// Represents the status of a list item (done or not)
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 #[cfg_attr(feature = "tauri", derive(Type))]
 pub enum ItemStatus {
     Todo,
     Done,
 }
 
+/// Priority of a list item, written as a leading `!!!` (high), `!!` (medium)
+/// or `!` (low) marker in the item text
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "tauri", derive(Type))]
+pub enum Priority {
+    High,
+    Medium,
+    Low,
+}
+
+impl Priority {
+    /// Marker written in front of the item text
+    pub fn marker(self) -> &'static str {
+        match self {
+            Priority::High => "!!!",
+            Priority::Medium => "!!",
+            Priority::Low => "!",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "high" => Some(Priority::High),
+            "medium" | "med" => Some(Priority::Medium),
+            "low" => Some(Priority::Low),
+            _ => None,
+        }
+    }
+}
+
 /// Represents a single item in a list
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 #[cfg_attr(feature = "tauri", derive(Type))]
 pub struct ListItem {
     /// The text content of the item
@@ -60,10 +173,34 @@ pub struct ListItem {
 
     /// Unique anchor identifier for the item
     pub anchor: String,
+
+    /// Optional due date, stored as a trailing `@due:YYYY-MM-DD` token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>,
+
+    /// Optional priority, stored as a leading `!`/`!!`/`!!!` marker
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<Priority>,
+
+    /// Optional free-form detail, stored as indented lines below the item
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+
+    /// When the item was added, stored as a trailing `@since:<rfc3339>` token.
+    /// Items written before that token existed get the file's modification time
+    #[serde(default = "Utc::now")]
+    pub created: DateTime<Utc>,
+}
+
+impl ListItem {
+    /// Whether the item is still open and its due date lies before `today`
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        self.status == ItemStatus::Todo && self.due.is_some_and(|due| due < today)
+    }
 }
 
 /// Represents a category containing list items
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 #[cfg_attr(feature = "tauri", derive(Type))]
 pub struct Category {
     /// The name of the category
@@ -74,7 +211,7 @@ pub struct Category {
 }
 
 /// Represents a complete list with metadata and items
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[cfg_attr(feature = "tauri", derive(Type))]
 pub struct List {
     /// Metadata for the list
@@ -91,18 +228,51 @@ pub struct List {
 
     /// Legacy field for backward compatibility - will be migrated to uncategorized_items
     #[serde(default, skip_serializing)]
+    #[schemars(skip)]
     pub items: Vec<ListItem>,
 }
 
 impl List {
+    /// JSON Schema of a list as serialized to JSON (frontmatter fields plus
+    /// `uncategorized_items` and `categories`)
+    pub fn generate_schema() -> anyhow::Result<String> {
+        use schemars::schema::{ObjectValidation, Schema};
+
+        fn clear_defaults(object: Option<&mut Box<ObjectValidation>>, fields: &[&str]) {
+            let Some(object) = object else { return };
+            for field in fields {
+                if let Some(Schema::Object(property)) = object.properties.get_mut(*field) {
+                    if let Some(metadata) = property.metadata.as_mut() {
+                        metadata.default = None;
+                    }
+                }
+            }
+        }
+
+        let mut schema = schemars::schema_for!(List);
+        // `id`, `updated` and an item's `created` default to a fresh value when
+        // missing; the one sampled while generating the schema would only mislead
+        clear_defaults(schema.schema.object.as_mut(), &["id", "updated"]);
+        if let Some(Schema::Object(item)) = schema.definitions.get_mut("ListItem") {
+            clear_defaults(item.object.as_mut(), &["created"]);
+        }
+        Ok(serde_json::to_string_pretty(&schema)?)
+    }
+
     /// Create a new list with the given title
     pub fn new(title: String) -> Self {
+        let now = Utc::now();
         Self {
             metadata: ListMetadata {
                 id: Uuid::new_v4(),
                 title,
                 sharing: vec![],
-                updated: Utc::now(),
+                tags: vec![],
+                category_colors: BTreeMap::new(),
+                pinned: false,
+                list: ListSettings::default(),
+                created: Some(now),
+                updated: now,
             },
             uncategorized_items: vec![],
             categories: vec![],
@@ -110,13 +280,27 @@ impl List {
         }
     }
 
+    /// RGB color configured for a category heading, resolved against `theme`.
+    /// `None` means the category should use the default heading style
+    pub fn category_color(&self, category: &str, theme: &Theme) -> Option<(u8, u8, u8)> {
+        self.metadata
+            .category_colors
+            .get(category)
+            .and_then(|color| theme.resolve_rgb(color))
+    }
+
     /// Add a new item to the list (uncategorized)
     pub fn add_item(&mut self, text: String) -> &ListItem {
         let anchor = generate_anchor();
+        let (text, priority) = split_priority(&text);
         let item = ListItem {
             text,
             status: ItemStatus::Todo,
             anchor,
+            due: None,
+            priority,
+            note: None,
+            created: Utc::now(),
         };
         self.uncategorized_items.push(item);
         self.metadata.updated = Utc::now();
@@ -125,11 +309,26 @@ impl List {
 
     /// Add a new item to a specific category
     pub fn add_item_to_category(&mut self, text: String, category: Option<&str>) -> ListItem {
+        self.add_item_with_due(text, category, None)
+    }
+
+    /// Add a new item with an optional due date to a specific category
+    pub fn add_item_with_due(
+        &mut self,
+        text: String,
+        category: Option<&str>,
+        due: Option<NaiveDate>,
+    ) -> ListItem {
         let anchor = generate_anchor();
+        let (text, priority) = split_priority(&text);
         let item = ListItem {
             text,
             status: ItemStatus::Todo,
             anchor,
+            due,
+            priority,
+            note: None,
+            created: Utc::now(),
         };
 
         self.metadata.updated = Utc::now();
@@ -229,9 +428,80 @@ pub fn is_valid_anchor(anchor: &str) -> bool {
     ANCHOR_RE.is_match(anchor)
 }
 
+/// Split a trailing due-date token (`@due:YYYY-MM-DD` or `@due YYYY-MM-DD`) off
+/// item text. Text without a token, or with a date that does not parse, is
+/// returned unchanged.
+pub fn split_due_date(text: &str) -> (&str, Option<NaiveDate>) {
+    lazy_static::lazy_static! {
+        static ref DUE_RE: Regex = Regex::new(r"\s*@due[: ](\S+)$").unwrap();
+    }
+    if let Some(captures) = DUE_RE.captures(text) {
+        if let Ok(date) = NaiveDate::parse_from_str(&captures[1], "%Y-%m-%d") {
+            let start = captures.get(0).unwrap().start();
+            return (&text[..start], Some(date));
+        }
+    }
+    (text, None)
+}
+
+/// Split a trailing `@since:<rfc3339>` creation timestamp off item text. Text
+/// without a valid timestamp is returned unchanged.
+pub fn split_created(text: &str) -> (&str, Option<DateTime<Utc>>) {
+    lazy_static::lazy_static! {
+        static ref SINCE_RE: Regex = Regex::new(r"\s*@since:(\S+)$").unwrap();
+    }
+    if let Some(captures) = SINCE_RE.captures(text) {
+        if let Ok(created) = DateTime::parse_from_rfc3339(&captures[1]) {
+            let start = captures.get(0).unwrap().start();
+            return (&text[..start], Some(created.with_timezone(&Utc)));
+        }
+    }
+    (text, None)
+}
+
+/// Split a priority off item text: a leading `!!!`, `!!` or `!` followed by
+/// whitespace, or an `@prio:high|medium|low` token anywhere in the text. Text
+/// without either is returned unchanged.
+pub fn split_priority(text: &str) -> (String, Option<Priority>) {
+    lazy_static::lazy_static! {
+        static ref MARKER_RE: Regex = Regex::new(r"^(!{1,3})\s+").unwrap();
+        static ref TOKEN_RE: Regex = Regex::new(r"(?i)(?:^|\s+)@prio:(high|medium|med|low)\b").unwrap();
+    }
+    if let Some(captures) = MARKER_RE.captures(text) {
+        let priority = match captures[1].len() {
+            3 => Priority::High,
+            2 => Priority::Medium,
+            _ => Priority::Low,
+        };
+        return (text[captures[0].len()..].to_string(), Some(priority));
+    }
+    if let Some(captures) = TOKEN_RE.captures(text) {
+        let range = captures.get(0).unwrap().range();
+        let rest = format!("{}{}", &text[..range.start], &text[range.end..]);
+        return (rest.trim().to_string(), Priority::from_name(&captures[1]));
+    }
+    (text.to_string(), None)
+}
+
+/// Indices of `items` ordered by priority (high first, unprioritized last),
+/// keeping the original order among items of equal priority
+pub fn priority_order(items: &[ListItem]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by_key(|&i| items[i].priority.map_or(u8::MAX, |p| p as u8));
+    order
+}
+
 /// Find items by fuzzy matching text with scoring and ranking
 /// Returns a vector of matching indices sorted by relevance score
 pub fn fuzzy_find(items: &[ListItem], query: &str, threshold: i64) -> Vec<usize> {
+    fuzzy_find_scored(items, query, threshold)
+        .into_iter()
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+/// [`fuzzy_find`] returning each match's score along with its index
+pub fn fuzzy_find_scored(items: &[ListItem], query: &str, threshold: i64) -> Vec<(usize, i64)> {
     if query.is_empty() {
         return Vec::new();
     }
@@ -257,10 +527,65 @@ pub fn fuzzy_find(items: &[ListItem], query: &str, threshold: i64) -> Vec<usize>
         }
     }
 
-    // Sort by score (highest first) and return indices
+    // Sort by score (highest first)
     matches_with_scores.sort_by(|a, b| b.1.cmp(&a.1));
     matches_with_scores
-        .into_iter()
-        .map(|(idx, _)| idx)
-        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schemas_describe_model_properties_and_enum_strings() {
+        let schema: serde_json::Value =
+            serde_json::from_str(&List::generate_schema().unwrap()).unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+        for field in [
+            "id",
+            "title",
+            "tags",
+            "pinned",
+            "uncategorized_items",
+            "categories",
+        ] {
+            assert!(properties.contains_key(field), "missing {}", field);
+        }
+        assert!(!properties.contains_key("items"), "legacy field is hidden");
+        assert!(properties["id"].get("default").is_none());
+        assert!(properties["updated"].get("default").is_none());
+
+        let definitions = &schema["definitions"];
+        let item = definitions["ListItem"]["properties"].as_object().unwrap();
+        for field in [
+            "text", "status", "anchor", "due", "priority", "note", "created",
+        ] {
+            assert!(item.contains_key(field), "missing item field {}", field);
+        }
+        assert!(item["created"].get("default").is_none());
+        assert_eq!(
+            definitions["ItemStatus"]["enum"],
+            serde_json::json!(["Todo", "Done"])
+        );
+        assert_eq!(
+            definitions["Priority"]["enum"],
+            serde_json::json!(["high", "medium", "low"])
+        );
+        assert_eq!(
+            definitions["ItemStatus"]["enum"][1],
+            serde_json::to_value(ItemStatus::Done).unwrap()
+        );
+
+        let schema: serde_json::Value =
+            serde_json::from_str(&crate::models::NoteFrontmatter::generate_schema().unwrap())
+                .unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+        for field in ["title", "created", "updated", "tags"] {
+            assert!(
+                properties.contains_key(field),
+                "missing note field {}",
+                field
+            );
+        }
+    }
 }
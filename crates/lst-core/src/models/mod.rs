@@ -1,3 +1,5 @@
 pub mod list;
+pub mod note;
 
 pub use list::*;
+pub use note::*;
@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "tauri")]
+use specta::Type;
+
+/// The known fields of a note's YAML frontmatter; other keys are allowed and
+/// left alone
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+#[cfg_attr(feature = "tauri", derive(Type))]
+pub struct NoteFrontmatter {
+    /// Title of the note
+    pub title: Option<String>,
+
+    /// When the note was created
+    pub created: Option<DateTime<Utc>>,
+
+    /// When the note was last updated
+    pub updated: Option<DateTime<Utc>>,
+
+    /// Free-form tags used to group and filter notes
+    pub tags: Option<Vec<String>>,
+}
+
+impl NoteFrontmatter {
+    /// JSON Schema of the note frontmatter
+    pub fn generate_schema() -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(&schemars::schema_for!(
+            NoteFrontmatter
+        ))?)
+    }
+}
@@ -0,0 +1,256 @@
+use super::markdown::{
+    format_list_as_markdown, parse_list_from_file, parse_list_from_string, resolve_list_path_in,
+    write_list_to_file,
+};
+use super::StorageError;
+use crate::models::List;
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Where lists and notes are read from and written to.
+///
+/// Names are relative paths without the `.md` extension (e.g. `groceries` or
+/// `stores/pharmacy`), the same form used everywhere else in `storage`.
+pub trait Storage: Send + Sync {
+    /// Load the list `name`
+    fn read_list(&self, name: &str) -> Result<List>;
+
+    /// Store `list` as `name`; `summary` describes the change for backends
+    /// that keep history (the filesystem backend's undo journal)
+    fn write_list(&self, name: &str, list: &List, summary: &str) -> Result<()>;
+
+    /// Names of all stored lists, sorted
+    fn list_names(&self) -> Result<Vec<String>>;
+
+    /// Raw markdown of the note `name`
+    fn read_note(&self, name: &str) -> Result<String>;
+
+    /// Store raw markdown as the note `name`
+    fn write_note(&self, name: &str, content: &str) -> Result<()>;
+
+    /// Names of all stored notes, sorted
+    fn note_names(&self) -> Result<Vec<String>>;
+}
+
+/// The local content directory: `lists/` and `notes/` markdown files
+#[derive(Debug, Clone)]
+pub struct FsStorage {
+    lists_dir: PathBuf,
+    notes_dir: PathBuf,
+    undo_dir: Option<PathBuf>,
+}
+
+impl FsStorage {
+    /// Backend rooted at `content_dir`, without an undo journal
+    pub fn new(content_dir: &Path) -> Self {
+        Self {
+            lists_dir: content_dir.join("lists"),
+            notes_dir: content_dir.join("notes"),
+            undo_dir: None,
+        }
+    }
+
+    /// Backend for the configured content directory, recording list writes in
+    /// the undo journal
+    pub fn from_config() -> Result<Self> {
+        Ok(Self {
+            lists_dir: super::get_lists_dir()?,
+            notes_dir: super::get_notes_dir()?,
+            undo_dir: Some(super::undo::get_undo_dir()?),
+        })
+    }
+
+    /// Record list writes in the undo journal stored in `undo_dir`
+    pub fn with_undo_dir(mut self, undo_dir: PathBuf) -> Self {
+        self.undo_dir = Some(undo_dir);
+        self
+    }
+
+    fn names_in(dir: &Path) -> Result<Vec<String>> {
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut names: Vec<String> = super::files_with_info_in(dir)?
+            .into_iter()
+            .map(|entry| entry.relative_path)
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+}
+
+impl Storage for FsStorage {
+    fn read_list(&self, name: &str) -> Result<List> {
+        Ok(parse_list_from_file(&resolve_list_path_in(
+            &self.lists_dir,
+            name,
+        )?)?)
+    }
+
+    fn write_list(&self, name: &str, list: &List, summary: &str) -> Result<()> {
+        let path = self.lists_dir.join(format!("{}.md", name));
+        if let Some(undo_dir) = &self.undo_dir {
+            if path.exists() {
+                let previous = fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read list file: {}", path.display()))?;
+                super::undo::record_in(undo_dir, name, summary, &previous)?;
+            }
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+
+        write_list_to_file(list, &path)
+    }
+
+    fn list_names(&self) -> Result<Vec<String>> {
+        Self::names_in(&self.lists_dir)
+    }
+
+    fn read_note(&self, name: &str) -> Result<String> {
+        let path = self.notes_dir.join(format!("{}.md", name));
+        fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read note file: {}", path.display()))
+    }
+
+    fn write_note(&self, name: &str, content: &str) -> Result<()> {
+        let path = self.notes_dir.join(format!("{}.md", name));
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+        super::atomic_write(&path, content)
+            .with_context(|| format!("Failed to write note file: {}", path.display()))
+    }
+
+    fn note_names(&self) -> Result<Vec<String>> {
+        Self::names_in(&self.notes_dir)
+    }
+}
+
+/// Keeps lists and notes in memory as markdown, so tests exercise the same
+/// parsing and formatting as the filesystem backend without touching disk
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    lists: Mutex<BTreeMap<String, String>>,
+    notes: Mutex<BTreeMap<String, String>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Raw markdown of the list `name` as it would be written to disk
+    pub fn list_markdown(&self, name: &str) -> Option<String> {
+        self.lists.lock().unwrap().get(name).cloned()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn read_list(&self, name: &str) -> Result<List> {
+        let lists = self.lists.lock().unwrap();
+        let content = lists
+            .get(name)
+            .ok_or_else(|| StorageError::NotFound(format!("List '{}' does not exist", name)))?;
+        Ok(parse_list_from_string(content, Path::new(name))?)
+    }
+
+    fn write_list(&self, name: &str, list: &List, _summary: &str) -> Result<()> {
+        self.lists
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), format_list_as_markdown(list));
+        Ok(())
+    }
+
+    fn list_names(&self) -> Result<Vec<String>> {
+        Ok(self.lists.lock().unwrap().keys().cloned().collect())
+    }
+
+    fn read_note(&self, name: &str) -> Result<String> {
+        self.notes
+            .lock()
+            .unwrap()
+            .get(name)
+            .cloned()
+            .with_context(|| format!("Note '{}' does not exist", name))
+    }
+
+    fn write_note(&self, name: &str, content: &str) -> Result<()> {
+        self.notes
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), content.to_string());
+        Ok(())
+    }
+
+    fn note_names(&self) -> Result<Vec<String>> {
+        Ok(self.notes.lock().unwrap().keys().cloned().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ItemStatus;
+    use crate::storage::markdown::{add_item_to_category_with, mark_done_with};
+
+    #[test]
+    fn test_add_and_done_against_memory_backend() {
+        let storage = MemoryStorage::new();
+        storage
+            .write_list("groceries", &List::new("groceries".to_string()), "create")
+            .unwrap();
+
+        add_item_to_category_with(&storage, "groceries", "milk", Some("Dairy"), None).unwrap();
+        add_item_to_category_with(&storage, "groceries", "bread", None, None).unwrap();
+        let done = mark_done_with(&storage, "groceries", "milk", 80).unwrap();
+        assert_eq!(done.len(), 1);
+
+        let list = storage.read_list("groceries").unwrap();
+        assert_eq!(list.uncategorized_items[0].text, "bread");
+        assert_eq!(list.categories[0].name, "Dairy");
+        assert_eq!(list.categories[0].items[0].status, ItemStatus::Done);
+        assert!(storage
+            .list_markdown("groceries")
+            .unwrap()
+            .contains("- [x] milk"));
+
+        assert!(add_item_to_category_with(&storage, "missing", "x", None, None).is_err());
+        assert_eq!(storage.list_names().unwrap(), vec!["groceries"]);
+    }
+
+    #[test]
+    fn test_fs_backend_round_trips_and_journals() {
+        let dir = tempfile::tempdir().unwrap();
+        let undo_dir = dir.path().join("undo");
+        let storage = FsStorage::new(dir.path()).with_undo_dir(undo_dir.clone());
+
+        storage
+            .write_list(
+                "stores/hardware",
+                &List::new("hardware".to_string()),
+                "create",
+            )
+            .unwrap();
+        add_item_to_category_with(&storage, "stores/hardware", "nails", None, None).unwrap();
+        assert_eq!(
+            storage.read_list("hardware").unwrap().uncategorized_items[0].text,
+            "nails"
+        );
+        assert_eq!(storage.list_names().unwrap(), vec!["stores/hardware"]);
+
+        let journal = super::super::undo::entries_in(&undo_dir, "stores/hardware").unwrap();
+        assert_eq!(journal.len(), 1);
+        assert_eq!(journal[0].summary, "add 'nails'");
+
+        storage.write_note("ideas", "# Ideas\n").unwrap();
+        assert_eq!(storage.read_note("ideas").unwrap(), "# Ideas\n");
+        assert_eq!(storage.note_names().unwrap(), vec!["ideas"]);
+    }
+}
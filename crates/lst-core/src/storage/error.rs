@@ -0,0 +1,30 @@
+use std::path::PathBuf;
+
+/// Failures of list loading and item resolution that callers (GUI, MCP) may
+/// want to tell apart. Converts into `anyhow::Error` like any other error, so
+/// `?` keeps working in `anyhow::Result` functions.
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    /// No list, item or category matched the name or query
+    #[error("{0}")]
+    NotFound(String),
+
+    /// A fuzzy name or query matched several candidates, listed in `matches`
+    #[error("Multiple {what} match '{query}': {}", .matches.join(", "))]
+    Ambiguous {
+        what: &'static str,
+        query: String,
+        matches: Vec<String>,
+    },
+
+    /// The file was read but its contents could not be parsed
+    #[error("{0}")]
+    Parse(String),
+
+    /// Reading a file or directory failed
+    #[error("Failed to access {}: {source}", .path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
@@ -0,0 +1,350 @@
+use super::markdown::parse_list_from_file;
+use super::notes::validate_frontmatter;
+use crate::sync::uuid_from_relative_path;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+/// Name of the manifest stored at the root of an export archive
+pub const MANIFEST_NAME: &str = "manifest.json";
+
+/// One file in an export archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Path inside the archive, e.g. `lists/groceries.md` or `media/cat.png`
+    pub path: String,
+    /// Size in bytes
+    pub size: u64,
+    /// Sync document id for lists and notes; `None` for media
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc_id: Option<String>,
+}
+
+/// Metadata written to [`MANIFEST_NAME`] describing every exported file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportManifest {
+    pub created_at: DateTime<Utc>,
+    pub entries: Vec<ManifestEntry>,
+}
+
+/// Write the `lists/` and `notes/` markdown under `content_dir` (and every file
+/// in `media_dir`, stored under `media/`) to a gzip tarball at `out`, together
+/// with a manifest of what was exported
+pub fn export_archive(
+    content_dir: &Path,
+    media_dir: Option<&Path>,
+    out: &Path,
+) -> Result<ExportManifest> {
+    // (archive path, source file, doc id)
+    let mut files: Vec<(String, PathBuf, Option<String>)> = Vec::new();
+    for kind in ["lists", "notes"] {
+        let dir = content_dir.join(kind);
+        if !dir.is_dir() {
+            continue;
+        }
+        for path in super::list_files_recursive(&dir, "md")? {
+            let relative = archive_path(content_dir, &path)?;
+            let doc_id = uuid_from_relative_path(&relative);
+            files.push((relative, path, Some(doc_id)));
+        }
+    }
+    if let Some(media_dir) = media_dir.filter(|dir| dir.is_dir()) {
+        for path in media_files(media_dir)? {
+            let relative = format!("media/{}", archive_path(media_dir, &path)?);
+            files.push((relative, path, None));
+        }
+    }
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+
+    if let Some(parent) = out.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    let file = File::create(out)
+        .with_context(|| format!("Failed to create archive: {}", out.display()))?;
+    let mut archive = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+
+    let mut entries = Vec::with_capacity(files.len());
+    for (relative, path, doc_id) in files {
+        let size = fs::metadata(&path)
+            .with_context(|| format!("Failed to read metadata: {}", path.display()))?
+            .len();
+        archive
+            .append_path_with_name(&path, &relative)
+            .with_context(|| format!("Failed to add {} to archive", path.display()))?;
+        entries.push(ManifestEntry {
+            path: relative,
+            size,
+            doc_id,
+        });
+    }
+
+    let manifest = ExportManifest {
+        created_at: Utc::now(),
+        entries,
+    };
+    let json = serde_json::to_vec_pretty(&manifest)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(json.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(manifest.created_at.timestamp().max(0) as u64);
+    header.set_cksum();
+    archive.append_data(&mut header, MANIFEST_NAME, json.as_slice())?;
+
+    archive
+        .into_inner()?
+        .finish()
+        .with_context(|| format!("Failed to write archive: {}", out.display()))?;
+    Ok(manifest)
+}
+
+/// What [`import_archive`] did, or would have done when there are conflicts
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportReport {
+    /// Archive paths written to disk
+    pub imported: Vec<String>,
+    /// Archive paths whose destination already exists
+    pub conflicts: Vec<String>,
+    /// Imported documents that failed to parse, with the error
+    pub invalid: Vec<(String, String)>,
+}
+
+/// Extract an archive written by [`export_archive`] into `content_dir`, with
+/// `media/` entries going to `media_dir` (skipped when `None`).
+///
+/// `kind` limits the import to `lists` or `notes`. Unless `overwrite` is set,
+/// nothing is written when any destination already exists; the clashing paths
+/// are returned in [`ImportReport::conflicts`]. Imported lists and notes are
+/// parsed afterwards and failures reported in [`ImportReport::invalid`].
+pub fn import_archive(
+    archive: &Path,
+    content_dir: &Path,
+    media_dir: Option<&Path>,
+    kind: Option<&str>,
+    overwrite: bool,
+) -> Result<ImportReport> {
+    if let Some(kind) = kind.filter(|k| !matches!(*k, "lists" | "notes")) {
+        bail!("Unknown kind '{}': expected 'lists' or 'notes'", kind);
+    }
+    let file = File::open(archive)
+        .with_context(|| format!("Failed to open archive: {}", archive.display()))?;
+    let mut reader = tar::Archive::new(GzDecoder::new(file));
+
+    // (archive path, destination, contents)
+    let mut files: Vec<(String, PathBuf, Vec<u8>)> = Vec::new();
+    for entry in reader.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?.to_string_lossy().replace('\\', "/");
+        let Some((top, rest)) = path.split_once('/') else {
+            continue; // manifest.json and anything else at the root
+        };
+        if kind.is_some_and(|k| k != top) {
+            continue;
+        }
+        let base = match top {
+            "lists" | "notes" => content_dir.join(top),
+            "media" => match media_dir {
+                Some(dir) => dir.to_path_buf(),
+                None => continue,
+            },
+            _ => continue,
+        };
+        if !Path::new(rest)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+        {
+            bail!("Refusing to import unsafe path from archive: {}", path);
+        }
+
+        let mut data = Vec::new();
+        entry
+            .read_to_end(&mut data)
+            .with_context(|| format!("Failed to read {} from archive", path))?;
+        let dest = base.join(rest);
+        files.push((path, dest, data));
+    }
+
+    let mut report = ImportReport {
+        conflicts: files
+            .iter()
+            .filter(|(_, dest, _)| dest.exists())
+            .map(|(path, _, _)| path.clone())
+            .collect(),
+        ..Default::default()
+    };
+    if !report.conflicts.is_empty() && !overwrite {
+        return Ok(report);
+    }
+
+    for (path, dest, data) in files {
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+        super::atomic_write(&dest, &data)
+            .with_context(|| format!("Failed to write file: {}", dest.display()))?;
+
+        let checked = if path.starts_with("lists/") {
+            parse_list_from_file(&dest).map(|_| ()).map_err(Into::into)
+        } else if path.starts_with("notes/") {
+            validate_frontmatter(&String::from_utf8_lossy(&data))
+        } else {
+            Ok(())
+        };
+        if let Err(e) = checked {
+            report.invalid.push((path.clone(), format!("{:#}", e)));
+        }
+        report.imported.push(path);
+    }
+    Ok(report)
+}
+
+/// `path` relative to `base` with forward slashes
+fn archive_path(base: &Path, path: &Path) -> Result<String> {
+    let relative = path
+        .strip_prefix(base)
+        .with_context(|| format!("{} is not under {}", path.display(), base.display()))?;
+    Ok(relative.to_string_lossy().replace('\\', "/"))
+}
+
+/// All files below `dir`, skipping hidden files and directories
+fn media_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        if path
+            .file_name()
+            .is_none_or(|name| name.to_string_lossy().starts_with('.'))
+        {
+            continue;
+        }
+        if path.is_dir() {
+            files.extend(media_files(&path)?);
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn test_export_round_trips_content_and_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let content = dir.path().join("content");
+        let media = content.join("media");
+        fs::create_dir_all(content.join("lists/stores")).unwrap();
+        fs::create_dir_all(content.join("notes")).unwrap();
+        fs::create_dir_all(&media).unwrap();
+        fs::write(content.join("lists/groceries.md"), "- [ ] milk\n").unwrap();
+        fs::write(content.join("lists/stores/hardware.md"), "- [ ] nails\n").unwrap();
+        fs::write(content.join("notes/ideas.md"), "# Ideas\n").unwrap();
+        fs::write(media.join("cat.png"), [0u8, 1, 2, 3]).unwrap();
+
+        let out = dir.path().join("backup.tar.gz");
+        let manifest = export_archive(&content, None, &out).unwrap();
+        let paths: Vec<&str> = manifest.entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "lists/groceries.md",
+                "lists/stores/hardware.md",
+                "notes/ideas.md"
+            ]
+        );
+        assert_eq!(manifest.entries[0].size, 11);
+        assert_eq!(
+            manifest.entries[0].doc_id.as_deref(),
+            Some(uuid_from_relative_path("lists/groceries.md").as_str())
+        );
+
+        let manifest = export_archive(&content, Some(&media), &out).unwrap();
+        assert_eq!(manifest.entries.len(), 4);
+        let image = &manifest.entries[2];
+        assert_eq!(image.path, "media/cat.png");
+        assert_eq!((image.size, image.doc_id.as_deref()), (4, None));
+
+        // Every manifest entry is in the archive with the recorded size
+        let mut archived = Vec::new();
+        let mut stored_manifest = None;
+        let mut archive = tar::Archive::new(GzDecoder::new(File::open(&out).unwrap()));
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let path = entry.path().unwrap().to_string_lossy().to_string();
+            if path == MANIFEST_NAME {
+                let mut json = String::new();
+                entry.read_to_string(&mut json).unwrap();
+                stored_manifest = Some(serde_json::from_str::<ExportManifest>(&json).unwrap());
+            } else {
+                archived.push((path, entry.size()));
+            }
+        }
+        let expected: Vec<(String, u64)> = manifest
+            .entries
+            .iter()
+            .map(|e| (e.path.clone(), e.size))
+            .collect();
+        assert_eq!(archived, expected);
+        assert_eq!(stored_manifest.unwrap().entries, manifest.entries);
+    }
+
+    #[test]
+    fn test_import_detects_conflicts_and_overwrites() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("source");
+        fs::create_dir_all(source.join("lists")).unwrap();
+        fs::create_dir_all(source.join("notes")).unwrap();
+        fs::write(source.join("lists/groceries.md"), "- [ ] milk\n").unwrap();
+        fs::write(source.join("notes/ideas.md"), "---\ntitle: Ideas\n---\n").unwrap();
+        fs::write(source.join("notes/broken.md"), "---\ntitle: [oops\n---\n").unwrap();
+        let out = dir.path().join("backup.tar.gz");
+        export_archive(&source, None, &out).unwrap();
+
+        // Only lists into an empty content dir
+        let target = dir.path().join("target");
+        let report = import_archive(&out, &target, None, Some("lists"), false).unwrap();
+        assert_eq!(report.imported, ["lists/groceries.md"]);
+        assert!(report.conflicts.is_empty() && report.invalid.is_empty());
+        assert!(!target.join("notes").exists());
+
+        // Everything: the existing list clashes, so nothing is written
+        fs::write(target.join("lists/groceries.md"), "- [ ] local edit\n").unwrap();
+        let report = import_archive(&out, &target, None, None, false).unwrap();
+        assert_eq!(report.conflicts, ["lists/groceries.md"]);
+        assert!(report.imported.is_empty());
+        assert!(!target.join("notes/ideas.md").exists());
+        assert_eq!(
+            fs::read_to_string(target.join("lists/groceries.md")).unwrap(),
+            "- [ ] local edit\n"
+        );
+
+        // Overwrite replaces it and imports the rest, flagging the broken note
+        let report = import_archive(&out, &target, None, None, true).unwrap();
+        assert_eq!(report.conflicts, ["lists/groceries.md"]);
+        assert_eq!(report.imported.len(), 3);
+        assert_eq!(
+            fs::read_to_string(target.join("lists/groceries.md")).unwrap(),
+            "- [ ] milk\n"
+        );
+        assert_eq!(report.invalid.len(), 1);
+        assert_eq!(report.invalid[0].0, "notes/broken.md");
+
+        assert!(import_archive(&out, &target, None, Some("posts"), true).is_err());
+    }
+}
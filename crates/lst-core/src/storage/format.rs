@@ -0,0 +1,306 @@
+use crate::models::NoteFrontmatter;
+use anyhow::{Context, Result};
+use regex::Regex;
+
+/// Column `lst note fmt` wraps prose at unless told otherwise
+pub const DEFAULT_WIDTH: usize = 80;
+
+lazy_static::lazy_static! {
+    /// A list item: indentation, marker (bullet or number) and the spacing
+    /// after it, then the item text
+    static ref LIST_ITEM_RE: Regex = Regex::new(r"^(\s*)([-*+]|\d{1,9}[.)])(\s+)(.*)$").unwrap();
+    /// Thematic breaks (`---`, `* * *`, `___`)
+    static ref THEMATIC_BREAK_RE: Regex =
+        Regex::new(r"^ {0,3}(?:(?:-\s*){3,}|(?:\*\s*){3,}|(?:_\s*){3,})$").unwrap();
+    /// Link reference and footnote definitions (`[id]: https://...`)
+    static ref DEFINITION_RE: Regex = Regex::new(r"^ {0,3}\[[^\]]+\]:").unwrap();
+    /// Underlines turning the line above into a setext heading
+    static ref SETEXT_RE: Regex = Regex::new(r"^ {0,3}(?:=+|-+)\s*$").unwrap();
+    /// Words that would start a new block (list item, heading, quote, ...)
+    /// when wrapped to the start of a line, so they are never wrapped there
+    static ref BLOCK_START_RE: Regex =
+        Regex::new(r"^(?:#{1,6}|[-+*]|=+|-+|_+|\d{1,9}[.)]|[>|<].*|```.*|~~~.*)$").unwrap();
+}
+
+/// Normalize a note's markdown with [`format_markdown`], keeping its
+/// frontmatter as written. Fails on frontmatter that doesn't parse rather
+/// than risk reformatting something that isn't markdown
+pub fn format_note(content: &str, width: usize) -> Result<String> {
+    let (frontmatter, body) = match content.strip_prefix("---\n") {
+        Some(rest) => {
+            let end = if rest.starts_with("---") {
+                0
+            } else {
+                rest.find("\n---")
+                    .map(|end| end + 1)
+                    .context("Frontmatter is not closed by '---'")?
+            };
+            let yaml = &rest[..end];
+            if !yaml.trim().is_empty() {
+                serde_yaml::from_str::<NoteFrontmatter>(yaml)
+                    .context("Invalid YAML frontmatter")?;
+            }
+            let after = &rest[end..];
+            let close = after.find('\n').map_or(after.len(), |i| i + 1);
+            let head = &content[..4 + end + close];
+            (Some(head.trim_end_matches('\n')), &after[close..])
+        }
+        None => (None, content),
+    };
+
+    let body = format_markdown(body, width);
+    Ok(match frontmatter {
+        Some(head) if body.is_empty() => format!("{}\n", head),
+        Some(head) => format!("{}\n\n{}", head, body),
+        None => body,
+    })
+}
+
+/// Normalize markdown: blank lines are collapsed to one (and dropped at the
+/// start and end), the text ends in a single newline, `*` and `+` bullets
+/// become `-`, and prose paragraphs are reflowed to `width` columns (`0`
+/// disables wrapping). Long list items are wrapped with a hanging indent.
+/// Fenced code, headings, quotes, tables, HTML and indented lines are left
+/// as they are
+pub fn format_markdown(markdown: &str, width: usize) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    // Marker of the code fence we are inside of, e.g. "```"
+    let mut fence: Option<&str> = None;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            out.push(line.to_string());
+            if closes_fence(trimmed, marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(marker) = fence_marker(trimmed) {
+            flush_paragraph(&mut paragraph, &mut out, width);
+            fence = Some(marker);
+            out.push(line.to_string());
+            continue;
+        }
+        if trimmed.is_empty() {
+            flush_paragraph(&mut paragraph, &mut out, width);
+            if out.last().is_some_and(|last| !last.is_empty()) {
+                out.push(String::new());
+            }
+            continue;
+        }
+        if SETEXT_RE.is_match(line) && !paragraph.is_empty() {
+            // The paragraph is a heading, which must stay on one line
+            out.extend(paragraph.drain(..).map(str::to_string));
+            out.push(line.to_string());
+            continue;
+        }
+        if is_prose(line) {
+            paragraph.push(line);
+            if hard_break(line) {
+                flush_paragraph(&mut paragraph, &mut out, width);
+            }
+            continue;
+        }
+
+        flush_paragraph(&mut paragraph, &mut out, width);
+        match LIST_ITEM_RE.captures(line) {
+            Some(item) if !THEMATIC_BREAK_RE.is_match(line) => {
+                let marker = match &item[2] {
+                    "*" | "+" => "-",
+                    marker => marker,
+                };
+                let prefix = format!("{}{}{}", &item[1], marker, &item[3]);
+                let text = &item[4];
+                if width == 0 || line.chars().count() <= width || hard_break(line) {
+                    out.push(format!("{}{}", prefix, text));
+                } else {
+                    let indent = " ".repeat(prefix.chars().count());
+                    out.extend(wrap(text, width, &prefix, &indent));
+                }
+            }
+            _ => out.push(line.to_string()),
+        }
+    }
+    flush_paragraph(&mut paragraph, &mut out, width);
+
+    while out.last().is_some_and(|last| last.is_empty()) {
+        out.pop();
+    }
+    if out.is_empty() {
+        return String::new();
+    }
+    out.join("\n") + "\n"
+}
+
+/// Run of three or more backticks or tildes opening a code fence
+fn fence_marker(trimmed: &str) -> Option<&str> {
+    let fence_char = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = trimmed.len() - trimmed.trim_start_matches(fence_char).len();
+    (len >= 3).then(|| &trimmed[..len])
+}
+
+fn closes_fence(trimmed: &str, marker: &str) -> bool {
+    let fence_char = marker.chars().next().unwrap_or('`');
+    let candidate = trimmed.trim_end();
+    candidate.len() >= marker.len() && candidate.chars().all(|c| c == fence_char)
+}
+
+/// Whether `line` is paragraph text that may be reflowed
+fn is_prose(line: &str) -> bool {
+    let starts_plain = line
+        .chars()
+        .next()
+        .is_some_and(|c| !c.is_whitespace() && !matches!(c, '#' | '>' | '|' | '<'));
+    starts_plain
+        && !LIST_ITEM_RE.is_match(line)
+        && !THEMATIC_BREAK_RE.is_match(line)
+        && !SETEXT_RE.is_match(line)
+        && !DEFINITION_RE.is_match(line)
+}
+
+/// Whether `line` ends in a markdown hard line break (two spaces or `\`)
+fn hard_break(line: &str) -> bool {
+    line.ends_with("  ") || line.ends_with('\\')
+}
+
+/// Reflow the collected paragraph lines into `out`
+fn flush_paragraph(paragraph: &mut Vec<&str>, out: &mut Vec<String>, width: usize) {
+    if paragraph.is_empty() {
+        return;
+    }
+    let broken = paragraph.last().is_some_and(|line| line.ends_with("  "));
+    if width == 0 {
+        out.extend(paragraph.iter().map(|line| line.to_string()));
+    } else {
+        let text = paragraph.join(" ");
+        out.extend(wrap(&text, width, "", ""));
+        if broken {
+            if let Some(last) = out.last_mut() {
+                last.push_str("  ");
+            }
+        }
+    }
+    paragraph.clear();
+}
+
+/// Greedily wrap the words of `text` at `width` columns. Words longer than
+/// the width get a line of their own, `[[wikilinks]]` are never split, and
+/// words that would read as the start of a new block stay on the line before
+fn wrap(text: &str, width: usize, first_prefix: &str, prefix: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = first_prefix.to_string();
+    let mut line_len = line.chars().count();
+    let mut empty = true;
+    for word in words(text) {
+        let word_len = word.chars().count();
+        if !empty && line_len + 1 + word_len > width && !BLOCK_START_RE.is_match(&word) {
+            lines.push(std::mem::replace(&mut line, prefix.to_string()));
+            line_len = line.chars().count();
+            empty = true;
+        }
+        if !empty {
+            line.push(' ');
+            line_len += 1;
+        }
+        line.push_str(&word);
+        line_len += word_len;
+        empty = false;
+    }
+    lines.push(line);
+    lines
+}
+
+/// Whitespace-separated words of `text`, keeping a `[[wikilink]]` with
+/// spaces in it together
+fn words(text: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut open_link = false;
+    for word in text.split_whitespace() {
+        match words.last_mut() {
+            Some(last) if open_link => {
+                last.push(' ');
+                last.push_str(word);
+            }
+            _ => words.push(word.to_string()),
+        }
+        let last = words.last().map(String::as_str).unwrap_or_default();
+        open_link = match (last.rfind("[["), last.rfind("]]")) {
+            (Some(open), Some(close)) => open > close,
+            (Some(_), None) => true,
+            _ => false,
+        };
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prose_is_wrapped_and_blank_lines_collapsed() {
+        let markdown = "\n\n# A heading that is rather long but must never be wrapped at all\n\n\n\n\
+                        This paragraph was typed\non short lines and goes on for long enough to need wrapping.\n\
+                        \n  \n\
+                        * first\n+ second item which is long enough that it has to be wrapped somewhere\n\
+                        Title\n=====\n\
+                        Line with a break  \nnext - 1. kept\n[1]: https://example.com\n\n\n";
+        let formatted = format_markdown(markdown, 40);
+        assert_eq!(
+            formatted,
+            "# A heading that is rather long but must never be wrapped at all\n\
+             \n\
+             This paragraph was typed on short lines\n\
+             and goes on for long enough to need\n\
+             wrapping.\n\
+             \n\
+             - first\n\
+             - second item which is long enough that\n  \
+               it has to be wrapped somewhere\n\
+             Title\n\
+             =====\n\
+             Line with a break  \n\
+             next - 1. kept\n\
+             [1]: https://example.com\n"
+        );
+        assert_eq!(format_markdown(&formatted, 40), formatted);
+
+        // Markers that would start a block are not wrapped to a line start
+        let risky = format_markdown("aaaa bbbb - cccc # dddd 1. eeee\n", 10);
+        assert_eq!(risky, "aaaa bbbb -\ncccc #\ndddd 1.\neeee\n");
+        let link = format_markdown("see [[my long note]] now\n", 12);
+        assert_eq!(link, "see\n[[my long note]]\nnow\n");
+        assert_eq!(format_markdown("one\ntwo\n", 0), "one\ntwo\n");
+        assert_eq!(format_markdown("\n\n", 80), "");
+    }
+
+    #[test]
+    fn test_code_fences_and_frontmatter_are_preserved() {
+        let content = "---\ntitle: Snippets\ntags: [code]\nextra: kept\n---\n\n\n\
+                       Intro\n\n\
+                       ````markdown\n\n\n* not a bullet\n```\nfn main() { let x = \"a very long line inside a fence stays as it is\"; }\n\
+                       ````\n\
+                       ~~~\n+ raw\n~~~\n\
+                       Outro";
+        let formatted = format_note(content, 20).unwrap();
+        assert_eq!(
+            formatted,
+            "---\ntitle: Snippets\ntags: [code]\nextra: kept\n---\n\n\
+             Intro\n\n\
+             ````markdown\n\n\n* not a bullet\n```\nfn main() { let x = \"a very long line inside a fence stays as it is\"; }\n\
+             ````\n\
+             ~~~\n+ raw\n~~~\n\
+             Outro\n"
+        );
+        assert_eq!(format_note(&formatted, 20).unwrap(), formatted);
+
+        assert_eq!(
+            format_note("---\ntitle: Empty\n---\n\n\n", 80).unwrap(),
+            "---\ntitle: Empty\n---\n"
+        );
+        assert!(format_note("---\ntags: {\n---\nbody\n", 80).is_err());
+        assert!(format_note("---\ntitle: open\n", 80).is_err());
+    }
+}
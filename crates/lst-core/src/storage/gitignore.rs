@@ -0,0 +1,83 @@
+use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::Path;
+
+/// Files at the root of the content directory whose patterns are honored
+/// when `paths.respect_gitignore` is set. Later files take precedence
+pub const IGNORE_FILES: [&str; 2] = [".gitignore", ".lstignore"];
+
+/// Ignore rules of a content directory, read from its [`IGNORE_FILES`]
+#[derive(Debug, Clone)]
+pub struct ContentIgnore {
+    matcher: Gitignore,
+}
+
+impl ContentIgnore {
+    /// Rules from the ignore files in `content_dir`; none if neither exists
+    pub fn from_dir(content_dir: &Path) -> Result<Self> {
+        let mut builder = GitignoreBuilder::new(content_dir);
+        for name in IGNORE_FILES {
+            let path = content_dir.join(name);
+            if !path.is_file() {
+                continue;
+            }
+            if let Some(e) = builder.add(&path) {
+                return Err(e).with_context(|| format!("Failed to read {}", path.display()));
+            }
+        }
+        let matcher = builder
+            .build()
+            .context("Failed to build content ignore rules")?;
+        Ok(Self { matcher })
+    }
+
+    /// Rules for the configured content directory, or `None` unless
+    /// `paths.respect_gitignore` is set
+    pub fn from_config() -> Result<Option<Self>> {
+        if !crate::config::get_config().paths.respect_gitignore {
+            return Ok(None);
+        }
+        Self::from_dir(&super::get_content_dir()?).map(Some)
+    }
+
+    /// Whether `path` (a directory if `is_dir`), or a directory it lies in,
+    /// is ignored. Paths outside the content directory never are
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let root = self.matcher.path();
+        path.starts_with(root)
+            && path != root
+            && self
+                .matcher
+                .matched_path_or_any_parents(path, is_dir)
+                .is_ignore()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_ignore_files_at_the_content_root_are_combined() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join(".gitignore"), "scratch/\n*.tmp.md\nkeep*.md\n").unwrap();
+        fs::write(root.join(".lstignore"), "lists/private.md\n!keep-me.md\n").unwrap();
+
+        let ignore = ContentIgnore::from_dir(root).unwrap();
+        assert!(ignore.is_ignored(&root.join("notes/scratch"), true));
+        assert!(ignore.is_ignored(&root.join("notes/scratch/idea.md"), false));
+        assert!(ignore.is_ignored(&root.join("lists/draft.tmp.md"), false));
+        assert!(ignore.is_ignored(&root.join("lists/private.md"), false));
+        assert!(ignore.is_ignored(&root.join("lists/keep-out.md"), false));
+        assert!(!ignore.is_ignored(&root.join("lists/keep-me.md"), false));
+        assert!(!ignore.is_ignored(&root.join("lists/groceries.md"), false));
+        assert!(!ignore.is_ignored(&root.join("notes/private.md"), false));
+        assert!(!ignore.is_ignored(root, true));
+        assert!(!ignore.is_ignored(Path::new("/elsewhere/draft.tmp.md"), false));
+
+        let none = ContentIgnore::from_dir(&root.join("lists")).unwrap();
+        assert!(!none.is_ignored(&root.join("lists/draft.tmp.md"), false));
+    }
+}
@@ -0,0 +1,216 @@
+use super::notes::{note_body, title_of};
+use crate::theme::Theme;
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Renderer used for PDF export when `ui.pdf_renderer` is not set
+pub const DEFAULT_PDF_RENDERER: &str = "wkhtmltopdf";
+
+/// Page styles, written against the theme's CSS variables so an export
+/// follows the current theme. The fallbacks apply when no theme is embedded
+const PAGE_CSS: &str = "\
+body {
+  margin: 0;
+  background: var(--background, #ffffff);
+  color: var(--foreground, #1f2328);
+  font-family: -apple-system, BlinkMacSystemFont, \"Segoe UI\", Helvetica, Arial, sans-serif;
+  line-height: 1.6;
+}
+article {
+  max-width: 48rem;
+  margin: 0 auto;
+  padding: 2rem;
+}
+a { color: var(--primary, #0969da); }
+h1, h2, h3, h4, h5, h6 { line-height: 1.25; }
+h1, h2 { border-bottom: 1px solid var(--border, #d0d7de); padding-bottom: 0.3em; }
+code, pre { background: var(--muted, #f6f8fa); border-radius: 4px; }
+code { padding: 0.1em 0.3em; }
+pre { padding: 1em; overflow-x: auto; }
+pre code { padding: 0; }
+blockquote {
+  margin: 0;
+  padding: 0 1em;
+  border-left: 4px solid var(--border, #d0d7de);
+  color: var(--muted-foreground, #59636e);
+}
+table { border-collapse: collapse; }
+th, td { border: 1px solid var(--border, #d0d7de); padding: 0.3em 0.8em; }
+img { max-width: 100%; }
+";
+
+/// Render a note to a self-contained HTML page: its body (without the
+/// frontmatter) as HTML, titled after its frontmatter `title` or first
+/// heading, falling back to `name`. Images are inlined as data URIs,
+/// resolving relative paths against `note_dir` and then by file name in
+/// `media_dir`; images that can't be found keep their link. With `theme`,
+/// its CSS variables are embedded so the page uses the theme's colors
+pub fn note_to_html(
+    content: &str,
+    name: &str,
+    note_dir: &Path,
+    media_dir: Option<&Path>,
+    theme: Option<&Theme>,
+) -> String {
+    let title = title_of(content).unwrap_or_else(|| name.to_string());
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES;
+    let events = Parser::new_ext(note_body(content), options).map(|event| match event {
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => {
+            let dest_url = find_image(&dest_url, note_dir, media_dir)
+                .and_then(|path| data_uri(&path))
+                .map(CowStr::from)
+                .unwrap_or(dest_url);
+            Event::Start(Tag::Image {
+                link_type,
+                dest_url,
+                title,
+                id,
+            })
+        }
+        event => event,
+    });
+    let mut body = String::new();
+    html::push_html(&mut body, events);
+
+    let theme_css = theme.map(Theme::generate_css_theme).unwrap_or_default();
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>\n{}{}</style>\n</head>\n<body>\n\
+         <article>\n{}</article>\n</body>\n</html>\n",
+        escape_html(&title),
+        theme_css,
+        PAGE_CSS,
+        body
+    )
+}
+
+/// Convert an HTML page to a PDF at `out` by running `renderer`, a command
+/// (with optional arguments, e.g. `wkhtmltopdf --quiet`) that is called as
+/// `<renderer> <input.html> <output.pdf>`
+pub fn html_to_pdf(html: &str, renderer: &str, out: &Path) -> Result<()> {
+    let mut words = renderer.split_whitespace();
+    let Some(program) = words.next() else {
+        bail!("No PDF renderer configured (set ui.pdf_renderer)");
+    };
+    let input = std::env::temp_dir().join(format!("lst-export-{}.html", std::process::id()));
+    fs::write(&input, html).with_context(|| format!("Failed to write {}", input.display()))?;
+    let status = Command::new(program)
+        .args(words)
+        .arg(&input)
+        .arg(out)
+        .status();
+    let _ = fs::remove_file(&input);
+    let status = status.with_context(|| {
+        format!(
+            "Failed to run PDF renderer '{}' (install it or set ui.pdf_renderer)",
+            program
+        )
+    })?;
+    if !status.success() {
+        bail!("PDF renderer '{}' failed with {}", program, status);
+    }
+    Ok(())
+}
+
+/// Local file an image link points at, if it exists
+fn find_image(target: &str, note_dir: &Path, media_dir: Option<&Path>) -> Option<PathBuf> {
+    if target.contains("://") || target.starts_with("data:") {
+        return None;
+    }
+    let target = Path::new(target);
+    let path = note_dir.join(target);
+    if path.is_file() {
+        return Some(path);
+    }
+    let path = media_dir?.join(target.file_name()?);
+    path.is_file().then_some(path)
+}
+
+/// The file at `path` as a `data:` URI, typed by its extension
+fn data_uri(path: &Path) -> Option<String> {
+    let bytes = fs::read(path).ok()?;
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+    let mime = match extension.as_deref() {
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("svg") => "image/svg+xml",
+        Some("bmp") => "image/bmp",
+        _ => "application/octet-stream",
+    };
+    let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+    Some(format!("data:{};base64,{}", mime, encoded))
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_html_has_body_tags_theme_variables_and_inlined_images() {
+        let dir = tempfile::tempdir().unwrap();
+        let notes_dir = dir.path().join("notes");
+        let media_dir = dir.path().join("media");
+        fs::create_dir_all(&notes_dir).unwrap();
+        fs::create_dir_all(&media_dir).unwrap();
+        fs::write(notes_dir.join("local.png"), b"local").unwrap();
+        fs::write(media_dir.join("abc.png"), b"stored").unwrap();
+
+        let content = "---\ntitle: Trip <plan>\ntags: [travel]\n---\n\
+                       # Packing\n\nBring **boots** and [a map](https://example.com).\n\n\
+                       - [x] tickets\n\n\
+                       ![here](local.png) ![stored](../media/abc.png) \
+                       ![gone](missing.png) ![web](https://example.com/a.png)\n";
+        let theme = Theme::default();
+        let html = note_to_html(content, "trip", &notes_dir, Some(&media_dir), Some(&theme));
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Trip &lt;plan&gt;</title>"));
+        assert!(!html.contains("tags:"), "frontmatter leaked into {}", html);
+        assert!(html.contains("<h1>Packing</h1>"));
+        assert!(html.contains("<strong>boots</strong>"));
+        assert!(html.contains("<a href=\"https://example.com\">a map</a>"));
+        assert!(html.contains("<input disabled=\"\" type=\"checkbox\" checked=\"\"/>"));
+        assert!(html.contains(":root {"));
+        assert!(html.contains(&theme.generate_css_variables()));
+        assert!(html.contains("background: var(--background"));
+
+        let encoded = |bytes: &[u8]| base64::engine::general_purpose::STANDARD.encode(bytes);
+        assert!(html.contains(&format!(
+            "<img src=\"data:image/png;base64,{}\" alt=\"here\" />",
+            encoded(b"local")
+        )));
+        assert!(html.contains(&format!("data:image/png;base64,{}", encoded(b"stored"))));
+        assert!(html.contains("<img src=\"missing.png\" alt=\"gone\" />"));
+        assert!(html.contains("<img src=\"https://example.com/a.png\" alt=\"web\" />"));
+
+        // Without a theme only the fallback colors apply
+        let plain = note_to_html("Just text\n", "plain", &notes_dir, None, None);
+        assert!(plain.contains("<title>plain</title>"));
+        assert!(plain.contains("<p>Just text</p>"));
+        assert!(!plain.contains(":root"));
+    }
+}
@@ -1,29 +1,49 @@
-use crate::models::{generate_anchor, is_valid_anchor, Category, ItemStatus, List, ListItem};
+use super::backend::{FsStorage, Storage};
+use super::StorageError;
+use crate::models::{
+    generate_anchor, is_valid_anchor, split_created, split_due_date, split_priority, Category,
+    ItemStatus, List, ListItem,
+};
 use anyhow::{Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
 use regex::Regex;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 /// Load a list from a markdown file (supports directory paths)
-pub fn load_list(list_name: &str) -> Result<List> {
-    let lists_dir = super::get_lists_dir()?;
+pub fn load_list(list_name: &str) -> Result<List, StorageError> {
+    let lists_dir = super::get_lists_dir().map_err(|e| StorageError::Io {
+        path: PathBuf::from("lists"),
+        source: std::io::Error::other(e),
+    })?;
+    parse_list_from_file(&resolve_list_path_in(&lists_dir, list_name)?)
+}
 
+/// Resolve `list_name` to a file below `lists_dir`: the exact relative path
+/// first, then (for plain names) an exact or unique partial filename match
+pub(crate) fn resolve_list_path_in(
+    lists_dir: &Path,
+    list_name: &str,
+) -> Result<PathBuf, StorageError> {
     // Try exact path first (supports both simple names and directory paths)
     let filename = format!("{}.md", list_name);
     let path = lists_dir.join(filename);
 
     if path.exists() {
-        return parse_list_from_file(&path);
+        return Ok(path);
     }
 
     // If exact path doesn't exist and input looks like a simple filename, try fuzzy search
     if !list_name.contains('/') && !list_name.contains('\\') {
-        let entries = super::list_lists_with_info()?;
+        let entries = super::files_with_info_in(lists_dir).map_err(|e| StorageError::Io {
+            path: lists_dir.to_path_buf(),
+            source: std::io::Error::other(e),
+        })?;
 
         // First try exact filename match
         for entry in &entries {
             if entry.name == list_name {
-                return parse_list_from_file(&entry.full_path);
+                return Ok(entry.full_path.clone());
             }
         }
 
@@ -34,19 +54,23 @@ pub fn load_list(list_name: &str) -> Result<List> {
             .collect();
 
         match matches.len() {
-            0 => anyhow::bail!("List '{}' does not exist", list_name),
-            1 => parse_list_from_file(&matches[0].full_path),
-            _ => {
-                let match_names: Vec<String> =
-                    matches.iter().map(|e| e.relative_path.clone()).collect();
-                anyhow::bail!("Multiple lists match '{}': {:?}", list_name, match_names);
-            }
+            0 => Err(list_not_found(list_name)),
+            1 => Ok(matches[0].full_path.clone()),
+            _ => Err(StorageError::Ambiguous {
+                what: "lists",
+                query: list_name.to_string(),
+                matches: matches.iter().map(|e| e.relative_path.clone()).collect(),
+            }),
         }
     } else {
-        anyhow::bail!("List '{}' does not exist", list_name);
+        Err(list_not_found(list_name))
     }
 }
 
+fn list_not_found(list_name: &str) -> StorageError {
+    StorageError::NotFound(format!("List '{}' does not exist", list_name))
+}
+
 /// Save a list to a markdown file using the original list name path
 pub fn save_list_with_path(list: &List, list_name: &str) -> Result<()> {
     let lists_dir = super::get_lists_dir()?;
@@ -56,26 +80,43 @@ pub fn save_list_with_path(list: &List, list_name: &str) -> Result<()> {
     write_list_to_file(list, &path)
 }
 
+/// Save a list like [`save_list_with_path`], first recording the current file
+/// contents in the undo journal under `summary`
+pub fn save_list_with_undo(list: &List, list_name: &str, summary: &str) -> Result<()> {
+    FsStorage::from_config()?.write_list(list_name, list, summary)
+}
+
+/// Short, quoted description of the items touched by an operation
+fn describe_items(items: &[ListItem]) -> String {
+    items
+        .iter()
+        .map(|item| format!("'{}'", item.text))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 /// Parse a list from a markdown file
-fn parse_list_from_file(path: &Path) -> Result<List> {
-    let content = fs::read_to_string(path)
-        .with_context(|| format!("Failed to read list file: {}", path.display()))?;
+pub(crate) fn parse_list_from_file(path: &Path) -> Result<List, StorageError> {
+    let content = fs::read_to_string(path).map_err(|source| StorageError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
 
     parse_list_from_string(&content, path)
 }
 
 /// Write a list to a markdown file
-fn write_list_to_file(list: &List, path: &Path) -> Result<()> {
+pub(crate) fn write_list_to_file(list: &List, path: &Path) -> Result<()> {
     let content = format_list_as_markdown(list);
 
-    fs::write(path, content)
+    super::atomic_write(path, content)
         .with_context(|| format!("Failed to write list file: {}", path.display()))?;
 
     Ok(())
 }
 
 /// Parse a list from a markdown string
-fn parse_list_from_string(content: &str, path: &Path) -> Result<List> {
+pub fn parse_list_from_string(content: &str, path: &Path) -> Result<List, StorageError> {
     // Split content into frontmatter and body
     let parts: Vec<&str> = content.splitn(3, "---").collect();
 
@@ -87,14 +128,20 @@ fn parse_list_from_string(content: &str, path: &Path) -> Result<List> {
             .unwrap_or_else(|| "Untitled List".to_string());
 
         let mut list = List::new(list_name);
-        parse_items(&mut list, content);
+        let legacy_created = legacy_created(path, list.metadata.updated);
+        parse_items(&mut list, content, legacy_created);
         return Ok(list);
     }
 
     // Parse frontmatter
     let frontmatter = parts[1].trim();
-    let mut list: List = serde_yaml::from_str(frontmatter)
-        .with_context(|| format!("Failed to parse list frontmatter in {}", path.display()))?;
+    let mut list: List = serde_yaml::from_str(frontmatter).map_err(|e| {
+        StorageError::Parse(format!(
+            "Failed to parse list frontmatter in {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
 
     // Handle backward compatibility: migrate old 'items' field to 'uncategorized_items'
     if !list.items.is_empty() {
@@ -103,13 +150,26 @@ fn parse_list_from_string(content: &str, path: &Path) -> Result<List> {
     }
 
     // Parse items from the body
-    parse_items(&mut list, parts[2]);
+    let legacy_created =
+        legacy_created(path, list.metadata.created.unwrap_or(list.metadata.updated));
+    parse_items(&mut list, parts[2], legacy_created);
 
     Ok(list)
 }
 
-/// Parse list items from markdown content
-fn parse_items(list: &mut List, content: &str) {
+/// Creation time of items written without an `@since` token: the modification
+/// time of the file at `path`, or `fallback` if it can't be read (e.g. content
+/// that did not come from disk)
+fn legacy_created(path: &Path, fallback: DateTime<Utc>) -> DateTime<Utc> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map(DateTime::from)
+        .unwrap_or(fallback)
+}
+
+/// Parse list items from markdown content, dating items without an `@since`
+/// token `legacy_created`
+fn parse_items(list: &mut List, content: &str, legacy_created: DateTime<Utc>) {
     // Clear existing items and categories
     list.uncategorized_items.clear();
     list.categories.clear();
@@ -124,8 +184,26 @@ fn parse_items(list: &mut List, content: &str) {
     }
 
     let mut current_category: Option<String> = None;
+    // Indented lines directly below an item (or its note) belong to its note
+    let mut in_item = false;
 
     for line in content.lines() {
+        if in_item {
+            if let Some(note_line) = line.strip_prefix(NOTE_INDENT).or(line.strip_prefix('\t')) {
+                if let Some(item) = last_item_mut(list, current_category.as_deref()) {
+                    let note_line = note_line.trim_end();
+                    match &mut item.note {
+                        Some(note) => {
+                            note.push('\n');
+                            note.push_str(note_line);
+                        }
+                        None => item.note = Some(note_line.to_string()),
+                    }
+                }
+                continue;
+            }
+        }
+        in_item = false;
         let line = line.trim();
 
         // Check for category headline
@@ -151,7 +229,9 @@ fn parse_items(list: &mut List, content: &str) {
                 ItemStatus::Done
             };
 
-            let text = captures[2].to_string();
+            let (text, created) = split_created(&captures[2]);
+            let (text, due) = split_due_date(text);
+            let (text, priority) = split_priority(text);
             let anchor = captures
                 .get(3)
                 .map(|m| format!("^{}", m.as_str()))
@@ -161,7 +241,12 @@ fn parse_items(list: &mut List, content: &str) {
                 text,
                 status,
                 anchor,
+                due,
+                priority,
+                note: None,
+                created: created.unwrap_or(legacy_created),
             };
+            in_item = true;
 
             // Add to current category or uncategorized
             match &current_category {
@@ -179,8 +264,24 @@ fn parse_items(list: &mut List, content: &str) {
     }
 }
 
+/// Indentation of item note lines in the markdown body
+const NOTE_INDENT: &str = "  ";
+
+/// The item most recently added to `category` (or the uncategorized items)
+fn last_item_mut<'a>(list: &'a mut List, category: Option<&str>) -> Option<&'a mut ListItem> {
+    match category {
+        Some(name) => list
+            .categories
+            .iter_mut()
+            .find(|c| c.name == name)?
+            .items
+            .last_mut(),
+        None => list.uncategorized_items.last_mut(),
+    }
+}
+
 /// Format a list as markdown
-fn format_list_as_markdown(list: &List) -> String {
+pub(crate) fn format_list_as_markdown(list: &List) -> String {
     // Format frontmatter - only serialize metadata, not items
     let frontmatter = serde_yaml::to_string(&list.metadata)
         .unwrap_or_else(|_| "title: Untitled List\n".to_string());
@@ -189,11 +290,7 @@ fn format_list_as_markdown(list: &List) -> String {
 
     // Format uncategorized items first (no headline)
     for item in &list.uncategorized_items {
-        let status = match item.status {
-            ItemStatus::Todo => " ",
-            ItemStatus::Done => "x",
-        };
-        content.push_str(&format!("- [{}] {}  {}\n", status, item.text, item.anchor));
+        content.push_str(&format_item_line(item));
     }
 
     // Add blank line between uncategorized and categorized if both exist
@@ -205,11 +302,7 @@ fn format_list_as_markdown(list: &List) -> String {
     for category in &list.categories {
         content.push_str(&format!("## {}\n", category.name));
         for item in &category.items {
-            let status = match item.status {
-                ItemStatus::Todo => " ",
-                ItemStatus::Done => "x",
-            };
-            content.push_str(&format!("- [{}] {}  {}\n", status, item.text, item.anchor));
+            content.push_str(&format_item_line(item));
         }
         content.push('\n');
     }
@@ -217,6 +310,51 @@ fn format_list_as_markdown(list: &List) -> String {
     content
 }
 
+/// Normalize list markdown the way saving it would: parse `content` (read
+/// from `path`), give items without a valid anchor a fresh one and format it
+/// again
+pub fn tidy_list_markdown(content: &str, path: &Path) -> Result<String> {
+    let mut list = parse_list_from_string(content, path)?;
+    for item in list.all_items_mut() {
+        if item.anchor.is_empty() || !crate::models::is_valid_anchor(&item.anchor) {
+            item.anchor = crate::models::generate_anchor();
+        }
+    }
+    Ok(format_list_as_markdown(&list))
+}
+
+/// Format a single item as a markdown task line
+fn format_item_line(item: &ListItem) -> String {
+    let status = match item.status {
+        ItemStatus::Todo => " ",
+        ItemStatus::Done => "x",
+    };
+    let priority = item
+        .priority
+        .map(|p| format!("{} ", p.marker()))
+        .unwrap_or_default();
+    let note: String = item
+        .note
+        .iter()
+        .flat_map(|note| note.lines())
+        .map(|line| format!("{}{}\n", NOTE_INDENT, line))
+        .collect();
+    let due = item
+        .due
+        .map(|due| format!(" @due:{}", due.format("%Y-%m-%d")))
+        .unwrap_or_default();
+    format!(
+        "- [{}] {}{}{} @since:{}  {}\n{}",
+        status,
+        priority,
+        item.text,
+        due,
+        item.created.to_rfc3339_opts(SecondsFormat::Secs, true),
+        item.anchor,
+        note
+    )
+}
+
 /// Create a new list (supports directory paths)
 pub fn create_list(name: &str) -> Result<PathBuf> {
     let lists_dir = super::get_lists_dir()?;
@@ -252,6 +390,187 @@ pub fn create_list(name: &str) -> Result<PathBuf> {
     Ok(path)
 }
 
+/// Copy the list `from` below `lists_dir` to `to` (a bare name copies within
+/// its directory, see [`super::move_target_name`]). The copy is titled after
+/// its file name and gets a new id, fresh timestamps and fresh item anchors,
+/// so it shares nothing with the original. Returns the source and copy paths
+pub fn copy_list_in(
+    lists_dir: &Path,
+    from: &str,
+    to: &str,
+    force: bool,
+) -> Result<(PathBuf, PathBuf)> {
+    let source = from.trim_end_matches(".md");
+    let target = super::move_target_name(source, to);
+    let from_path = lists_dir.join(format!("{}.md", source));
+    let to_path = lists_dir.join(format!("{}.md", target));
+    super::prepare_destination(&from_path, &to_path, force)?;
+
+    let mut list = parse_list_from_file(&from_path)?;
+    let now = Utc::now();
+    list.metadata.id = uuid::Uuid::new_v4();
+    list.metadata.title = Path::new(&target)
+        .file_name()
+        .map_or_else(|| target.clone(), |name| name.to_string_lossy().to_string());
+    list.metadata.created = Some(now);
+    list.metadata.updated = now;
+    for item in list.all_items_mut() {
+        item.anchor = generate_anchor();
+    }
+    write_list_to_file(&list, &to_path)?;
+    Ok((from_path, to_path))
+}
+
+/// List whose uncategorized items are carried over into each new daily list
+pub const DAILY_TEMPLATE_LIST: &str = "daily_lists/_template_daily_list";
+
+/// Create the daily list `list_name`, seeded with the uncategorized items of
+/// [`DAILY_TEMPLATE_LIST`] under fresh anchors and all set to todo. Returns
+/// the number of items carried over (zero if the template is missing or empty)
+pub fn create_daily_list(list_name: &str) -> Result<usize> {
+    create_daily_list_with(&FsStorage::from_config()?, list_name)
+}
+
+/// [`create_daily_list`] against an arbitrary storage backend
+pub fn create_daily_list_with(storage: &dyn Storage, list_name: &str) -> Result<usize> {
+    let title = Path::new(list_name)
+        .file_name()
+        .and_then(|f| f.to_str())
+        .unwrap_or(list_name);
+    let mut list = List::new(title.to_string());
+
+    if let Ok(template) = storage.read_list(DAILY_TEMPLATE_LIST) {
+        for item in &template.uncategorized_items {
+            list.uncategorized_items.push(ListItem {
+                status: ItemStatus::Todo,
+                anchor: generate_anchor(),
+                due: None,
+                created: Utc::now(),
+                ..item.clone()
+            });
+        }
+    }
+
+    let carried = list.uncategorized_items.len();
+    storage.write_list(list_name, &list, "create")?;
+    Ok(carried)
+}
+
+/// Result of [`rollover_daily_list`]
+#[derive(Debug, Default)]
+pub struct Rollover {
+    /// The earlier daily list items were taken from, if there was one
+    pub source: Option<String>,
+    /// Items added to today's list, with their new anchors
+    pub items: Vec<ListItem>,
+}
+
+/// Carry the unfinished items of the most recent daily list before `today`
+/// (a `daily_lists/YYYYMMDD_daily_list` name) over into `today`, creating it
+/// first if needed. Items get fresh anchors and keep their category; items
+/// already open in `today` are not added twice. With `clear_source` the
+/// carried items are removed from the earlier list.
+pub fn rollover_daily_list(today: &str, clear_source: bool) -> Result<Rollover> {
+    rollover_daily_list_with(&FsStorage::from_config()?, today, clear_source)
+}
+
+/// [`rollover_daily_list`] against an arbitrary storage backend
+pub fn rollover_daily_list_with(
+    storage: &dyn Storage,
+    today: &str,
+    clear_source: bool,
+) -> Result<Rollover> {
+    let names = storage.list_names()?;
+    let source = names
+        .iter()
+        .filter(|name| {
+            name.starts_with("daily_lists/")
+                && name.ends_with("_daily_list")
+                && name.as_str() != DAILY_TEMPLATE_LIST
+                && name.as_str() < today
+        })
+        .max()
+        .cloned();
+    if !names.iter().any(|name| name == today) {
+        create_daily_list_with(storage, today)?;
+    }
+    let Some(source) = source else {
+        return Ok(Rollover::default());
+    };
+
+    let mut earlier = storage.read_list(&source)?;
+    let mut list = storage.read_list(today)?;
+    let mut open: Vec<String> = list
+        .all_items()
+        .filter(|item| item.status == ItemStatus::Todo)
+        .map(|item| normalize_item_text(&item.text))
+        .collect();
+    let mut carry = |items: &[ListItem], into: &mut Vec<ListItem>, carried: &mut Vec<ListItem>| {
+        for item in items.iter().filter(|item| item.status == ItemStatus::Todo) {
+            let key = normalize_item_text(&item.text);
+            if open.contains(&key) {
+                continue;
+            }
+            open.push(key);
+            let item = ListItem {
+                anchor: generate_anchor(),
+                ..item.clone()
+            };
+            into.push(item.clone());
+            carried.push(item);
+        }
+    };
+
+    let mut carried = Vec::new();
+    carry(
+        &earlier.uncategorized_items,
+        &mut list.uncategorized_items,
+        &mut carried,
+    );
+    for category in &earlier.categories {
+        match list.categories.iter_mut().find(|c| c.name == category.name) {
+            Some(existing) => carry(&category.items, &mut existing.items, &mut carried),
+            None => {
+                let mut items = Vec::new();
+                carry(&category.items, &mut items, &mut carried);
+                if !items.is_empty() {
+                    list.categories.push(Category {
+                        name: category.name.clone(),
+                        items,
+                    });
+                }
+            }
+        }
+    }
+
+    if !carried.is_empty() {
+        list.metadata.updated = chrono::Utc::now();
+        let summary = format!("rollover from '{}'", source);
+        storage.write_list(today, &list, &summary)?;
+    }
+    if clear_source {
+        let before = earlier.all_items().count();
+        earlier
+            .uncategorized_items
+            .retain(|item| item.status != ItemStatus::Todo);
+        for category in &mut earlier.categories {
+            category
+                .items
+                .retain(|item| item.status != ItemStatus::Todo);
+        }
+        if earlier.all_items().count() != before {
+            earlier.metadata.updated = chrono::Utc::now();
+            let summary = format!("rollover to '{}'", today);
+            storage.write_list(&source, &earlier, &summary)?;
+        }
+    }
+
+    Ok(Rollover {
+        source: Some(source),
+        items: carried,
+    })
+}
+
 /// Add an item to a list
 pub fn add_item(list_name: &str, text: &str) -> Result<ListItem> {
     let mut list = load_list(list_name)?;
@@ -259,7 +578,7 @@ pub fn add_item(list_name: &str, text: &str) -> Result<ListItem> {
     let item_clone = item.clone();
 
     // Save to the correct path by resolving the list name to its actual path
-    save_list_with_path(&list, list_name)?;
+    save_list_with_undo(&list, list_name, &format!("add '{}'", text))?;
 
     Ok(item_clone)
 }
@@ -269,25 +588,77 @@ pub fn add_item_to_category(
     list_name: &str,
     text: &str,
     category: Option<&str>,
+    due: Option<chrono::NaiveDate>,
 ) -> Result<ListItem> {
-    let mut list = load_list(list_name)?;
-    let item = list.add_item_to_category(text.to_string(), category);
+    add_item_to_category_with(&FsStorage::from_config()?, list_name, text, category, due)
+}
 
-    save_list_with_path(&list, list_name)?;
+/// [`add_item_to_category`] against an arbitrary storage backend
+pub fn add_item_to_category_with(
+    storage: &dyn Storage,
+    list_name: &str,
+    text: &str,
+    category: Option<&str>,
+    due: Option<chrono::NaiveDate>,
+) -> Result<ListItem> {
+    let mut added = add_items_with(storage, list_name, &[(text, category, due)])?;
+    Ok(added.remove(0))
+}
 
-    Ok(item)
+/// Text, category and due date of an item added with [`add_items`]
+pub type NewItem<'a> = (&'a str, Option<&'a str>, Option<chrono::NaiveDate>);
+
+/// Add several items to a list in a single write, so one undo step takes them
+/// all back out
+pub fn add_items(list_name: &str, items: &[NewItem]) -> Result<Vec<ListItem>> {
+    add_items_with(&FsStorage::from_config()?, list_name, items)
+}
+
+/// [`add_items`] against an arbitrary storage backend
+pub fn add_items_with(
+    storage: &dyn Storage,
+    list_name: &str,
+    items: &[NewItem],
+) -> Result<Vec<ListItem>> {
+    let mut list = storage.read_list(list_name)?;
+    let default_category = list.metadata.list.default_category.clone();
+    let added: Vec<ListItem> = items
+        .iter()
+        .map(|&(text, category, due)| {
+            let category = category.or(default_category.as_deref());
+            list.add_item_with_due(text.to_string(), category, due)
+        })
+        .collect();
+
+    let summary = match items {
+        [(text, _, _)] => format!("add '{}'", text),
+        _ => format!("add {} items", items.len()),
+    };
+    storage.write_list(list_name, &list, &summary)?;
+
+    Ok(added)
 }
 
 /// Mark an item as done
 pub fn mark_done(list_name: &str, target: &str, threshold: i64) -> Result<Vec<ListItem>> {
-    let mut list = load_list(list_name)?;
+    mark_done_with(&FsStorage::from_config()?, list_name, target, threshold)
+}
+
+/// [`mark_done`] against an arbitrary storage backend
+pub fn mark_done_with(
+    storage: &dyn Storage,
+    list_name: &str,
+    target: &str,
+    threshold: i64,
+) -> Result<Vec<ListItem>> {
+    let mut list = storage.read_list(list_name)?;
 
-    // If there are multiple comma-separated targets, handle each one
-    if target.contains(',') {
-        let targets: Vec<&str> = target.split(',').map(|s| s.trim()).collect();
+    // If there are multiple targets (comma-separated or a range), handle each one
+    let targets = expand_targets(&list, target)?;
+    if targets.len() > 1 {
         let mut marked_items = Vec::new();
 
-        for target in targets {
+        for target in &targets {
             if let Ok(item) = mark_item_done(&mut list, target, threshold) {
                 marked_items.push(item);
             }
@@ -297,13 +668,15 @@ pub fn mark_done(list_name: &str, target: &str, threshold: i64) -> Result<Vec<Li
             anyhow::bail!("No matching items found in list '{}'", list_name);
         }
 
-        save_list_with_path(&list, list_name)?;
+        let summary = format!("done {}", describe_items(&marked_items));
+        storage.write_list(list_name, &list, &summary)?;
         return Ok(marked_items);
     }
 
     // Handle single target
+    let target = targets.first().map_or(target, String::as_str);
     if let Ok(item) = mark_item_done(&mut list, target, threshold) {
-        save_list_with_path(&list, list_name)?;
+        storage.write_list(list_name, &list, &format!("done '{}'", item.text))?;
         return Ok(vec![item]);
     }
 
@@ -318,12 +691,12 @@ pub fn mark_done(list_name: &str, target: &str, threshold: i64) -> Result<Vec<Li
 pub fn mark_undone(list_name: &str, target: &str, threshold: i64) -> Result<Vec<ListItem>> {
     let mut list = load_list(list_name)?;
 
-    // If there are multiple comma-separated targets, handle each one
-    if target.contains(',') {
-        let targets: Vec<&str> = target.split(',').map(|s| s.trim()).collect();
+    // If there are multiple targets (comma-separated or a range), handle each one
+    let targets = expand_targets(&list, target)?;
+    if targets.len() > 1 {
         let mut marked_items = Vec::new();
 
-        for target in targets {
+        for target in &targets {
             if let Ok(item) = mark_item_undone(&mut list, target, threshold) {
                 marked_items.push(item);
             }
@@ -333,13 +706,15 @@ pub fn mark_undone(list_name: &str, target: &str, threshold: i64) -> Result<Vec<
             anyhow::bail!("No matching items found in list '{}'", list_name);
         }
 
-        save_list_with_path(&list, list_name)?;
+        let summary = format!("undone {}", describe_items(&marked_items));
+        save_list_with_undo(&list, list_name, &summary)?;
         return Ok(marked_items);
     }
 
     // Handle single target
+    let target = targets.first().map_or(target, String::as_str);
     if let Ok(item) = mark_item_undone(&mut list, target, threshold) {
-        save_list_with_path(&list, list_name)?;
+        save_list_with_undo(&list, list_name, &format!("undone '{}'", item.text))?;
         return Ok(vec![item]);
     }
 
@@ -368,20 +743,153 @@ pub fn reset_list(list_name: &str) -> Result<Vec<ListItem>> {
     }
 
     list.metadata.updated = chrono::Utc::now();
-    save_list_with_path(&list, list_name)?;
+    save_list_with_undo(&list, list_name, "reset")?;
     Ok(reset_items)
 }
 
+/// Set every item in a list to `status` in a single save, returning the
+/// items that changed
+pub fn set_all_status(list_name: &str, status: ItemStatus) -> Result<Vec<ListItem>> {
+    set_all_status_with(&FsStorage::from_config()?, list_name, status)
+}
+
+/// [`set_all_status`] against an arbitrary storage backend
+pub fn set_all_status_with(
+    storage: &dyn Storage,
+    list_name: &str,
+    status: ItemStatus,
+) -> Result<Vec<ListItem>> {
+    let mut list = storage.read_list(list_name)?;
+    let mut changed = Vec::new();
+
+    for item in list.all_items_mut() {
+        if item.status != status {
+            item.status = status.clone();
+            changed.push(item.clone());
+        }
+    }
+
+    let verb = match status {
+        ItemStatus::Done => "done",
+        ItemStatus::Todo => "undone",
+    };
+    if changed.is_empty() {
+        anyhow::bail!("No items to mark {} in list '{}'", verb, list_name);
+    }
+
+    list.metadata.updated = chrono::Utc::now();
+    storage.write_list(list_name, &list, &format!("{} all", verb))?;
+    Ok(changed)
+}
+
+/// Set every item in the category `category` (matched case-insensitively) of
+/// a list to `status` in a single save, returning the items that changed.
+/// Items in other categories are left untouched
+pub fn set_category_status(
+    list_name: &str,
+    category: &str,
+    status: ItemStatus,
+) -> Result<Vec<ListItem>> {
+    set_category_status_with(&FsStorage::from_config()?, list_name, category, status)
+}
+
+/// [`set_category_status`] against an arbitrary storage backend
+pub fn set_category_status_with(
+    storage: &dyn Storage,
+    list_name: &str,
+    category: &str,
+    status: ItemStatus,
+) -> Result<Vec<ListItem>> {
+    let mut list = storage.read_list(list_name)?;
+    let Some(cat) = list
+        .categories
+        .iter_mut()
+        .find(|c| c.name.eq_ignore_ascii_case(category))
+    else {
+        anyhow::bail!("Category '{}' not found in list '{}'", category, list_name);
+    };
+    let name = cat.name.clone();
+    let mut changed = Vec::new();
+
+    for item in cat.items.iter_mut() {
+        if item.status != status {
+            item.status = status.clone();
+            changed.push(item.clone());
+        }
+    }
+
+    let verb = match status {
+        ItemStatus::Done => "done",
+        ItemStatus::Todo => "undone",
+    };
+    if changed.is_empty() {
+        anyhow::bail!("No items to mark {} in category '{}'", verb, name);
+    }
+
+    list.metadata.updated = chrono::Utc::now();
+    storage.write_list(list_name, &list, &format!("{} category '{}'", verb, name))?;
+    Ok(changed)
+}
+
+/// Split a comma-separated `target` into single targets, expanding index
+/// ranges (`#2-5`) and replacing every index (`#3`) with its item's anchor so
+/// that removing an item doesn't shift the ones after it. Indices must lie
+/// within the list; other targets (anchors, text) are passed through
+fn expand_targets(list: &List, target: &str) -> Result<Vec<String>, StorageError> {
+    lazy_static::lazy_static! {
+        static ref INDEX_RE: Regex = Regex::new(r"^#(\d+)(?:-#?(\d+))?$").unwrap();
+    }
+    let anchors: Vec<&str> = list.all_items().map(|item| item.anchor.as_str()).collect();
+    let mut targets = Vec::new();
+    for part in target
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+    {
+        let Some(captures) = INDEX_RE.captures(part) else {
+            targets.push(part.to_string());
+            continue;
+        };
+        // Numbers too large for usize are out of range as well
+        let number = |m: regex::Match| m.as_str().parse::<usize>().unwrap_or(usize::MAX);
+        let start = number(captures.get(1).unwrap());
+        let end = captures.get(2).map_or(start, number);
+        if start > end {
+            return Err(StorageError::NotFound(format!(
+                "Invalid range '{}': it ends before it starts",
+                part
+            )));
+        }
+        if start == 0 || end > anchors.len() {
+            return Err(StorageError::NotFound(format!(
+                "'{}' is out of range: the list has {} item(s)",
+                part,
+                anchors.len()
+            )));
+        }
+        targets.extend(
+            anchors[start - 1..end]
+                .iter()
+                .map(|anchor| anchor.to_string()),
+        );
+    }
+    Ok(targets)
+}
+
 /// Helper function to mark a single item as done
 fn mark_item_done(list: &mut List, target: &str, threshold: i64) -> Result<ListItem> {
     // Find item and set status
-    find_and_set_item_status(list, target, ItemStatus::Done, threshold)
+    let item = find_and_set_item_status(list, target, ItemStatus::Done, threshold)?;
+    list.metadata.updated = chrono::Utc::now();
+    Ok(item)
 }
 
 /// Helper function to mark a single item as undone
 fn mark_item_undone(list: &mut List, target: &str, threshold: i64) -> Result<ListItem> {
     // Find item and set status
-    find_and_set_item_status(list, target, ItemStatus::Todo, threshold)
+    let item = find_and_set_item_status(list, target, ItemStatus::Todo, threshold)?;
+    list.metadata.updated = chrono::Utc::now();
+    Ok(item)
 }
 
 /// Helper function to find an item and set its status
@@ -390,7 +898,7 @@ fn find_and_set_item_status(
     target: &str,
     status: ItemStatus,
     threshold: i64,
-) -> Result<ListItem> {
+) -> Result<ListItem, StorageError> {
     // Try to find the item by anchor first
     if is_valid_anchor(target) {
         if let Some(item) = list.find_item_mut_by_anchor(target) {
@@ -422,34 +930,127 @@ fn find_and_set_item_status(
     let all_items: Vec<ListItem> = list.all_items().cloned().collect();
     let matches = crate::models::fuzzy_find(&all_items, target, threshold);
     match matches.len() {
-        0 => anyhow::bail!("No item matching '{}' found", target),
+        0 => Err(item_not_found(target)),
         1 => {
             let target_anchor = &all_items[matches[0]].anchor;
-            if let Some(item) = list.find_item_mut_by_anchor(target_anchor) {
-                item.status = status;
-                Ok(item.clone())
-            } else {
-                anyhow::bail!("Internal error: anchor not found")
-            }
+            let item = list
+                .find_item_mut_by_anchor(target_anchor)
+                .ok_or_else(|| item_not_found(target))?;
+            item.status = status;
+            Ok(item.clone())
         }
-        _ => anyhow::bail!(
-            "Multiple items match '{}', please use a more specific query",
-            target
-        ),
+        _ => Err(ambiguous_items(target, &all_items, &matches)),
     }
 }
 
+/// Flip an item between todo and done, returning the updated item
+///
+/// The target is resolved like [`find_item_for_removal`] (anchor, text, `#N`,
+/// then fuzzy), so a fuzzy query matching several items is an error instead of
+/// toggling whichever scored highest.
+pub fn toggle_item(list_name: &str, target: &str, threshold: i64) -> Result<ListItem> {
+    toggle_item_with(&FsStorage::from_config()?, list_name, target, threshold)
+}
+
+/// [`toggle_item`] against an arbitrary storage backend
+pub fn toggle_item_with(
+    storage: &dyn Storage,
+    list_name: &str,
+    target: &str,
+    threshold: i64,
+) -> Result<ListItem> {
+    let mut list = storage.read_list(list_name)?;
+    let location = find_item_for_removal(&list, target, threshold)?;
+    let item = match location {
+        ItemLocation::Uncategorized(idx) => &mut list.uncategorized_items[idx],
+        ItemLocation::Categorized {
+            category_index,
+            item_index,
+        } => &mut list.categories[category_index].items[item_index],
+    };
+    item.status = match item.status {
+        ItemStatus::Todo => ItemStatus::Done,
+        ItemStatus::Done => ItemStatus::Todo,
+    };
+    let item = item.clone();
+    list.metadata.updated = chrono::Utc::now();
+
+    let verb = match item.status {
+        ItemStatus::Done => "done",
+        ItemStatus::Todo => "undone",
+    };
+    storage.write_list(list_name, &list, &format!("{} '{}'", verb, item.text))?;
+    Ok(item)
+}
+
+/// Set the note shown below an item; an empty `note` removes it
+pub fn set_item_note(
+    list_name: &str,
+    target: &str,
+    note: &str,
+    threshold: i64,
+) -> Result<ListItem> {
+    set_item_note_with(
+        &FsStorage::from_config()?,
+        list_name,
+        target,
+        note,
+        threshold,
+    )
+}
+
+/// [`set_item_note`] against an arbitrary storage backend
+pub fn set_item_note_with(
+    storage: &dyn Storage,
+    list_name: &str,
+    target: &str,
+    note: &str,
+    threshold: i64,
+) -> Result<ListItem> {
+    let mut list = storage.read_list(list_name)?;
+    let location = find_item_for_removal(&list, target, threshold)?;
+    let item = match location {
+        ItemLocation::Uncategorized(idx) => &mut list.uncategorized_items[idx],
+        ItemLocation::Categorized {
+            category_index,
+            item_index,
+        } => &mut list.categories[category_index].items[item_index],
+    };
+    let note = note
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n");
+    let note = note.trim_matches('\n');
+    item.note = (!note.is_empty()).then(|| note.to_string());
+    let item = item.clone();
+    list.metadata.updated = chrono::Utc::now();
+
+    storage.write_list(list_name, &list, &format!("note '{}'", item.text))?;
+    Ok(item)
+}
+
 /// Delete an item from a list
 pub fn delete_item(list_name: &str, target: &str, threshold: i64) -> Result<Vec<ListItem>> {
-    let mut list = load_list(list_name)?;
+    delete_item_with(&FsStorage::from_config()?, list_name, target, threshold)
+}
 
-    // If there are multiple comma-separated targets, handle each one
-    if target.contains(',') {
-        let targets: Vec<&str> = target.split(',').map(|s| s.trim()).collect();
+/// [`delete_item`] against an arbitrary storage backend
+pub fn delete_item_with(
+    storage: &dyn Storage,
+    list_name: &str,
+    target: &str,
+    threshold: i64,
+) -> Result<Vec<ListItem>> {
+    let mut list = storage.read_list(list_name)?;
+
+    // If there are multiple targets (comma-separated or a range), handle each
+    // one. Indices were turned into anchors, so removals don't shift them
+    let targets = expand_targets(&list, target)?;
+    if targets.len() > 1 {
         let mut removed_items = Vec::new();
 
-        // Handle each target - we need to process them carefully to avoid index issues
-        for target in targets {
+        for target in &targets {
             if let Ok(location) = find_item_for_removal(&list, target, threshold) {
                 let removed = remove_item_at_location(&mut list, location);
                 removed_items.push(removed);
@@ -461,15 +1062,17 @@ pub fn delete_item(list_name: &str, target: &str, threshold: i64) -> Result<Vec<
         }
 
         list.metadata.updated = chrono::Utc::now();
-        save_list_with_path(&list, list_name)?;
+        let summary = format!("rm {}", describe_items(&removed_items));
+        storage.write_list(list_name, &list, &summary)?;
         return Ok(removed_items);
     }
 
     // Handle single target
+    let target = targets.first().map_or(target, String::as_str);
     if let Ok(location) = find_item_for_removal(&list, target, threshold) {
         let removed = remove_item_at_location(&mut list, location);
         list.metadata.updated = chrono::Utc::now();
-        save_list_with_path(&list, list_name)?;
+        storage.write_list(list_name, &list, &format!("rm '{}'", removed.text))?;
         return Ok(vec![removed]);
     }
 
@@ -504,7 +1107,7 @@ pub fn edit_item_text(list_name: &str, target: &str, new_text: &str) -> Result<(
         if let Some(item) = list.find_item_mut_by_anchor(target) {
             item.text = new_text.to_string();
             list.metadata.updated = chrono::Utc::now();
-            save_list_with_path(&list, list_name)?;
+            save_list_with_undo(&list, list_name, &format!("edit '{}'", new_text))?;
             return Ok(());
         }
     }
@@ -520,7 +1123,7 @@ pub fn edit_item_text(list_name: &str, target: &str, new_text: &str) -> Result<(
         if let Some(item) = list.find_item_mut_by_anchor(&anchor) {
             item.text = new_text.to_string();
             list.metadata.updated = chrono::Utc::now();
-            save_list_with_path(&list, list_name)?;
+            save_list_with_undo(&list, list_name, &format!("edit '{}'", new_text))?;
             Ok(())
         } else {
             anyhow::bail!("Internal error: anchor not found")
@@ -544,10 +1147,11 @@ pub fn reorder_item(list_name: &str, target: &str, new_index: usize, threshold:
         // For now, reordering puts items in uncategorized section
         // TODO: Could be enhanced to support reordering within categories
         let clamped = new_index.min(list.uncategorized_items.len());
+        let summary = format!("reorder '{}'", item.text);
         list.uncategorized_items.insert(clamped, item);
 
         list.metadata.updated = chrono::Utc::now();
-        save_list_with_path(&list, list_name)?;
+        save_list_with_undo(&list, list_name, &summary)?;
         Ok(())
     } else {
         anyhow::bail!(
@@ -568,7 +1172,11 @@ pub fn save_list(list: &List) -> Result<()> {
 }
 
 /// Helper function to find an item for removal, returning location info
-pub fn find_item_for_removal(list: &List, target: &str, threshold: i64) -> Result<ItemLocation> {
+pub fn find_item_for_removal(
+    list: &List,
+    target: &str,
+    threshold: i64,
+) -> Result<ItemLocation, StorageError> {
     // Try to find the item by anchor first
     if is_valid_anchor(target) {
         if let Some(location) = find_item_location_by_anchor(list, target) {
@@ -594,19 +1202,23 @@ pub fn find_item_for_removal(list: &List, target: &str, threshold: i64) -> Resul
     let all_items: Vec<ListItem> = list.all_items().cloned().collect();
     let matches = crate::models::fuzzy_find(&all_items, target, threshold);
     match matches.len() {
-        0 => anyhow::bail!("No item matching '{}' found", target),
-        1 => {
-            let target_anchor = &all_items[matches[0]].anchor;
-            if let Some(location) = find_item_location_by_anchor(list, target_anchor) {
-                Ok(location)
-            } else {
-                anyhow::bail!("Internal error: anchor not found")
-            }
-        }
-        _ => anyhow::bail!(
-            "Multiple items match '{}', please use a more specific query",
-            target
-        ),
+        0 => Err(item_not_found(target)),
+        1 => find_item_location_by_anchor(list, &all_items[matches[0]].anchor)
+            .ok_or_else(|| item_not_found(target)),
+        _ => Err(ambiguous_items(target, &all_items, &matches)),
+    }
+}
+
+fn item_not_found(target: &str) -> StorageError {
+    StorageError::NotFound(format!("No item matching '{}' found", target))
+}
+
+/// Error for a fuzzy `target` matching the items at `matches` in `items`
+fn ambiguous_items(target: &str, items: &[ListItem], matches: &[usize]) -> StorageError {
+    StorageError::Ambiguous {
+        what: "items",
+        query: target.to_string(),
+        matches: matches.iter().map(|&i| items[i].text.clone()).collect(),
     }
 }
 
@@ -699,6 +1311,384 @@ fn find_item_location_by_global_index(list: &List, global_index: usize) -> Optio
     None
 }
 
+/// Rename a category in a list file, returning the number of items moved
+///
+/// See [`rename_category_in_list`] for the collision and merge semantics.
+pub fn rename_category(
+    list_name: &str,
+    old_name: &str,
+    new_name: &str,
+    merge: bool,
+    dedupe: bool,
+) -> Result<usize> {
+    let mut list = load_list(list_name)?;
+    let moved = rename_category_in_list(&mut list, old_name, new_name, merge, dedupe)?;
+    let summary = format!("rename category '{}' to '{}'", old_name, new_name);
+    save_list_with_undo(&list, list_name, &summary)?;
+    Ok(moved)
+}
+
+/// Rename a category in place. If `new_name` already exists this errors unless
+/// `merge` is set, in which case the items are appended to the existing category
+/// (skipping items whose text is already present when `dedupe` is set).
+pub fn rename_category_in_list(
+    list: &mut List,
+    old_name: &str,
+    new_name: &str,
+    merge: bool,
+    dedupe: bool,
+) -> Result<usize> {
+    let source_idx = list
+        .categories
+        .iter()
+        .position(|c| c.name == old_name)
+        .ok_or_else(|| anyhow::anyhow!("Category '{}' not found", old_name))?;
+
+    if old_name == new_name {
+        return Ok(list.categories[source_idx].items.len());
+    }
+
+    let moved = match list.categories.iter().position(|c| c.name == new_name) {
+        Some(_) if !merge => {
+            anyhow::bail!(
+                "Category '{}' already exists (use --merge to combine them)",
+                new_name
+            );
+        }
+        Some(_) => {
+            let source = list.categories.remove(source_idx);
+            let target = list
+                .categories
+                .iter_mut()
+                .find(|c| c.name == new_name)
+                .expect("target category exists");
+            let mut moved = 0;
+            for item in source.items {
+                if dedupe
+                    && target
+                        .items
+                        .iter()
+                        .any(|existing| existing.text.to_lowercase() == item.text.to_lowercase())
+                {
+                    continue;
+                }
+                target.items.push(item);
+                moved += 1;
+            }
+            moved
+        }
+        None => {
+            let category = &mut list.categories[source_idx];
+            category.name = new_name.to_string();
+            category.items.len()
+        }
+    };
+
+    list.metadata.updated = chrono::Utc::now();
+    Ok(moved)
+}
+
+/// Name of the list [`split_by_category`] creates for `category` of `list_name`
+pub fn split_list_name(list_name: &str, category: &str) -> String {
+    let slug = category
+        .trim()
+        .to_lowercase()
+        .replace([' ', '/', '\\'], "-");
+    format!("{}-{}", list_name, slug)
+}
+
+/// Split each category of a list into its own list (see [`split_list_name`]).
+/// With `move_items` the categories are removed from the original, which is
+/// deleted if nothing is left. Returns the new list names and whether the
+/// original was deleted
+pub fn split_by_category(list_name: &str, move_items: bool) -> Result<(Vec<String>, bool)> {
+    let created = split_by_category_with(&FsStorage::from_config()?, list_name, move_items)?;
+    let emptied = move_items && load_list(list_name)?.uncategorized_items.is_empty();
+    if emptied {
+        delete_list(list_name)?;
+    }
+    Ok((created, emptied))
+}
+
+/// Copy every category of `list_name` into a new list holding the category's
+/// items (text, status and anchors unchanged) as uncategorized items. Nothing
+/// is written if any of the new lists already exists. With `remove` the
+/// categories are taken out of the original, leaving its uncategorized items
+pub fn split_by_category_with(
+    storage: &dyn Storage,
+    list_name: &str,
+    remove: bool,
+) -> Result<Vec<String>> {
+    let mut list = storage.read_list(list_name)?;
+    if list.categories.is_empty() {
+        anyhow::bail!("List '{}' has no categories to split", list_name);
+    }
+
+    let existing = storage.list_names()?;
+    let targets: Vec<String> = list
+        .categories
+        .iter()
+        .map(|c| split_list_name(list_name, &c.name))
+        .collect();
+    if let Some(taken) = targets.iter().find(|name| existing.contains(name)) {
+        anyhow::bail!("List '{}' already exists", taken);
+    }
+    // Categories whose names slug alike would overwrite each other's list
+    let mut seen = std::collections::HashMap::new();
+    for (category, name) in list.categories.iter().zip(&targets) {
+        if let Some(first) = seen.insert(name, &category.name) {
+            anyhow::bail!(
+                "Categories '{}' and '{}' would both be split into '{}'; rename one first",
+                first,
+                category.name,
+                name
+            );
+        }
+    }
+
+    for (category, name) in list.categories.iter().zip(&targets) {
+        let title = Path::new(name)
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_else(|| name.clone());
+        let mut split = List::new(title);
+        split.metadata.tags = list.metadata.tags.clone();
+        split.uncategorized_items = category.items.clone();
+        let summary = format!("split from '{}'", list_name);
+        storage.write_list(name, &split, &summary)?;
+    }
+
+    if remove {
+        list.categories.clear();
+        list.metadata.updated = chrono::Utc::now();
+        storage.write_list(list_name, &list, "split by category")?;
+    }
+    Ok(targets)
+}
+
+/// Load a list template by name from the templates directory
+pub fn load_template(template_name: &str) -> Result<List> {
+    let path = super::get_templates_dir()?.join(format!("{}.md", template_name));
+    if !path.exists() {
+        anyhow::bail!("Template '{}' does not exist", template_name);
+    }
+    Ok(parse_list_from_file(&path)?)
+}
+
+/// Overlay a template onto an existing list, returning the number of items added
+///
+/// See [`apply_template_to_list`] for how categories and items are merged.
+pub fn apply_template(list_name: &str, template_name: &str) -> Result<usize> {
+    let template = load_template(template_name)?;
+    let mut list = load_list(list_name)?;
+    let added = apply_template_to_list(&mut list, &template);
+    if added > 0 {
+        let summary = format!("apply template '{}'", template_name);
+        save_list_with_undo(&list, list_name, &summary)?;
+    }
+    Ok(added)
+}
+
+fn normalize_item_text(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Add the template's missing categories and every template item whose
+/// normalized text is not already present anywhere in the list. Existing items
+/// are left untouched; added items are open and get fresh anchors.
+pub fn apply_template_to_list(list: &mut List, template: &List) -> usize {
+    let mut present: std::collections::HashSet<String> = list
+        .all_items()
+        .map(|item| normalize_item_text(&item.text))
+        .collect();
+    let mut added = 0;
+
+    for item in &template.uncategorized_items {
+        if present.insert(normalize_item_text(&item.text)) {
+            list.add_item_with_due(item.text.clone(), None, item.due);
+            added += 1;
+        }
+    }
+
+    for category in &template.categories {
+        if !list.categories.iter().any(|c| c.name == category.name) {
+            list.categories.push(Category {
+                name: category.name.clone(),
+                items: Vec::new(),
+            });
+        }
+        for item in &category.items {
+            if present.insert(normalize_item_text(&item.text)) {
+                list.add_item_with_due(item.text.clone(), Some(&category.name), item.due);
+                added += 1;
+            }
+        }
+    }
+
+    added
+}
+
+/// Pin or unpin a list, returning whether that changed anything
+pub fn set_pinned(list_name: &str, pinned: bool) -> Result<bool> {
+    set_pinned_with(&FsStorage::from_config()?, list_name, pinned)
+}
+
+/// [`set_pinned`] against an arbitrary storage backend
+pub fn set_pinned_with(storage: &dyn Storage, list_name: &str, pinned: bool) -> Result<bool> {
+    let mut list = storage.read_list(list_name)?;
+    if list.metadata.pinned == pinned {
+        return Ok(false);
+    }
+    list.metadata.pinned = pinned;
+    list.metadata.updated = chrono::Utc::now();
+    let summary = if pinned { "pin" } else { "unpin" };
+    storage.write_list(list_name, &list, summary)?;
+    Ok(true)
+}
+
+/// Add tags to a list, returning the list's tags afterwards
+pub fn add_tags(list_name: &str, tags: &[String]) -> Result<Vec<String>> {
+    let mut list = load_list(list_name)?;
+    if add_tags_to_list(&mut list, tags) > 0 {
+        save_list_with_undo(&list, list_name, &format!("tag {}", tags.join(", ")))?;
+    }
+    Ok(list.metadata.tags)
+}
+
+/// Remove tags from a list, returning the list's tags afterwards
+pub fn remove_tags(list_name: &str, tags: &[String]) -> Result<Vec<String>> {
+    let mut list = load_list(list_name)?;
+    if remove_tags_from_list(&mut list, tags) > 0 {
+        save_list_with_undo(&list, list_name, &format!("untag {}", tags.join(", ")))?;
+    }
+    Ok(list.metadata.tags)
+}
+
+/// Normalize a tag as typed by the user (`#work` and ` work ` both become `work`)
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim().trim_start_matches('#').trim().to_string()
+}
+
+/// Add tags that are not yet present, returning how many were added
+pub fn add_tags_to_list(list: &mut List, tags: &[String]) -> usize {
+    let mut added = 0;
+    for tag in tags.iter().map(|t| normalize_tag(t)) {
+        if !tag.is_empty() && !list.metadata.tags.contains(&tag) {
+            list.metadata.tags.push(tag);
+            added += 1;
+        }
+    }
+    if added > 0 {
+        list.metadata.updated = chrono::Utc::now();
+    }
+    added
+}
+
+/// Remove the given tags, returning how many were removed
+pub fn remove_tags_from_list(list: &mut List, tags: &[String]) -> usize {
+    let tags: Vec<String> = tags.iter().map(|t| normalize_tag(t)).collect();
+    let before = list.metadata.tags.len();
+    list.metadata.tags.retain(|t| !tags.contains(t));
+    let removed = before - list.metadata.tags.len();
+    if removed > 0 {
+        list.metadata.updated = chrono::Utc::now();
+    }
+    removed
+}
+
+/// Name of the category that holds archived done items
+pub const ARCHIVE_CATEGORY: &str = "Archived";
+
+/// Move all done items of a list into the archive category, returning the number archived
+pub fn archive_done_items(list_name: &str) -> Result<usize> {
+    let mut list = load_list(list_name)?;
+    let archived = archive_done_items_in_list(&mut list);
+    if archived > 0 {
+        save_list_with_undo(&list, list_name, &format!("archive {} item(s)", archived))?;
+    }
+    Ok(archived)
+}
+
+/// Move archived items back into the uncategorized section, returning the number restored
+pub fn restore_archived_items(list_name: &str) -> Result<usize> {
+    let mut list = load_list(list_name)?;
+    let restored = restore_archived_items_in_list(&mut list);
+    if restored > 0 {
+        save_list_with_undo(&list, list_name, &format!("restore {} item(s)", restored))?;
+    }
+    Ok(restored)
+}
+
+/// Move done items out of the uncategorized section and all other categories into
+/// an `## Archived` category kept at the bottom of the list. Items already in the
+/// archive are left alone, so running this twice archives nothing new.
+pub fn archive_done_items_in_list(list: &mut List) -> usize {
+    let mut done = Vec::new();
+
+    let (finished, open): (Vec<_>, Vec<_>) = std::mem::take(&mut list.uncategorized_items)
+        .into_iter()
+        .partition(|item| item.status == ItemStatus::Done);
+    list.uncategorized_items = open;
+    done.extend(finished);
+
+    for category in list
+        .categories
+        .iter_mut()
+        .filter(|c| c.name != ARCHIVE_CATEGORY)
+    {
+        let (finished, open): (Vec<_>, Vec<_>) = std::mem::take(&mut category.items)
+            .into_iter()
+            .partition(|item| item.status == ItemStatus::Done);
+        category.items = open;
+        done.extend(finished);
+    }
+
+    if done.is_empty() {
+        return 0;
+    }
+
+    let archived = done.len();
+    let mut archive = match list
+        .categories
+        .iter()
+        .position(|c| c.name == ARCHIVE_CATEGORY)
+    {
+        Some(idx) => list.categories.remove(idx),
+        None => Category {
+            name: ARCHIVE_CATEGORY.to_string(),
+            items: Vec::new(),
+        },
+    };
+    archive.items.extend(done);
+    list.categories.push(archive);
+
+    list.metadata.updated = chrono::Utc::now();
+    archived
+}
+
+/// Move every item of the archive category back into the uncategorized section
+/// and drop the then-empty archive category
+pub fn restore_archived_items_in_list(list: &mut List) -> usize {
+    let Some(idx) = list
+        .categories
+        .iter()
+        .position(|c| c.name == ARCHIVE_CATEGORY)
+    else {
+        return 0;
+    };
+
+    let archive = list.categories.remove(idx);
+    let restored = archive.items.len();
+    list.uncategorized_items.extend(archive.items);
+    if restored > 0 {
+        list.metadata.updated = chrono::Utc::now();
+    }
+    restored
+}
+
 /// Remove all items from a list, returning the number of removed entries
 pub fn wipe_list(list_name: &str) -> Result<usize> {
     let mut list = load_list(list_name)?;
@@ -710,11 +1700,11 @@ pub fn wipe_list(list_name: &str) -> Result<usize> {
     list.uncategorized_items.clear();
     list.categories.clear();
     list.metadata.updated = chrono::Utc::now();
-    save_list_with_path(&list, list_name)?;
+    save_list_with_undo(&list, list_name, "wipe")?;
     Ok(removed)
 }
 
-/// Delete a list file completely
+/// Delete a list file, moving it to the trash unless `ui.use_trash` is off
 pub fn delete_list(list_name: &str) -> Result<()> {
     let lists_dir = super::get_lists_dir()?;
     let filename = format!("{}.md", list_name);
@@ -724,8 +1714,779 @@ pub fn delete_list(list_name: &str) -> Result<()> {
         anyhow::bail!("List '{}' does not exist", list_name);
     }
 
-    fs::remove_file(&path)
-        .with_context(|| format!("Failed to delete list file: {}", path.display()))?;
+    super::trash::discard(&path)?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> List {
+        let mut list = List::new("groceries".to_string());
+        list.add_item_to_category("milk".to_string(), Some("Dairy"));
+        list.add_item_to_category("cheese".to_string(), Some("Dairy"));
+        list.add_item_to_category("Milk".to_string(), Some("Fridge"));
+        list.add_item_to_category("butter".to_string(), Some("Fridge"));
+        list
+    }
+
+    #[test]
+    fn test_rename_category_collision_without_merge_errors() {
+        let mut list = fixture();
+        let err = rename_category_in_list(&mut list, "Dairy", "Fridge", false, false)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("already exists"));
+        assert_eq!(list.categories.len(), 2);
+    }
+
+    #[test]
+    fn test_rename_category_merge_appends_items() {
+        let mut list = fixture();
+        let moved = rename_category_in_list(&mut list, "Dairy", "Fridge", true, false).unwrap();
+        assert_eq!(moved, 2);
+        assert_eq!(list.categories.len(), 1);
+        let texts: Vec<_> = list.categories[0]
+            .items
+            .iter()
+            .map(|i| i.text.as_str())
+            .collect();
+        assert_eq!(texts, vec!["Milk", "butter", "milk", "cheese"]);
+    }
+
+    #[test]
+    fn test_rename_category_merge_dedupes() {
+        let mut list = fixture();
+        let moved = rename_category_in_list(&mut list, "Dairy", "Fridge", true, true).unwrap();
+        assert_eq!(moved, 1);
+        let texts: Vec<_> = list.categories[0]
+            .items
+            .iter()
+            .map(|i| i.text.as_str())
+            .collect();
+        assert_eq!(texts, vec!["Milk", "butter", "cheese"]);
+    }
+
+    #[test]
+    fn test_archive_done_items_is_idempotent() {
+        let mut list = fixture();
+        list.add_item("bread".to_string());
+        list.uncategorized_items[0].status = ItemStatus::Done;
+        list.categories[0].items[1].status = ItemStatus::Done;
+
+        assert_eq!(archive_done_items_in_list(&mut list), 2);
+        assert_eq!(list.uncategorized_items.len(), 0);
+        assert_eq!(list.categories[0].items.len(), 1);
+        assert_eq!(list.categories[1].items.len(), 2);
+        let archive = list.categories.last().unwrap();
+        assert_eq!(archive.name, ARCHIVE_CATEGORY);
+        assert_eq!(archive.items.len(), 2);
+
+        assert_eq!(archive_done_items_in_list(&mut list), 0);
+        assert_eq!(list.categories.last().unwrap().items.len(), 2);
+    }
+
+    #[test]
+    fn test_archive_round_trip_preserves_anchors() {
+        let mut list = fixture();
+        list.categories[0].items[0].status = ItemStatus::Done;
+        list.categories[1].items[1].status = ItemStatus::Done;
+        let anchors: Vec<_> = [&list.categories[0].items[0], &list.categories[1].items[1]]
+            .iter()
+            .map(|i| (i.anchor.clone(), i.text.clone()))
+            .collect();
+
+        archive_done_items_in_list(&mut list);
+        let markdown = format_list_as_markdown(&list);
+        let mut reloaded = parse_list_from_string(&markdown, Path::new("groceries.md")).unwrap();
+        assert_eq!(reloaded.categories.last().unwrap().name, ARCHIVE_CATEGORY);
+
+        assert_eq!(restore_archived_items_in_list(&mut reloaded), 2);
+        assert!(reloaded
+            .categories
+            .iter()
+            .all(|c| c.name != ARCHIVE_CATEGORY));
+        let restored: Vec<_> = reloaded
+            .uncategorized_items
+            .iter()
+            .map(|i| (i.anchor.clone(), i.text.clone()))
+            .collect();
+        assert_eq!(restored, anchors);
+        assert_eq!(restore_archived_items_in_list(&mut reloaded), 0);
+    }
+
+    #[test]
+    fn test_due_date_round_trips() {
+        let markdown = "---\ntitle: todo\ncreated: 2024-05-01T09:00:00Z\n---\n\n\
+- [ ] pay rent @due:2024-06-01  ^abcde\n\
+- [ ] call mom  ^fghij\n\
+- [ ] party @due:someday  ^klmno\n";
+        let list = parse_list_from_string(markdown, Path::new("todo.md")).unwrap();
+        let items = &list.uncategorized_items;
+
+        assert_eq!(items[0].text, "pay rent");
+        assert_eq!(items[0].due, chrono::NaiveDate::from_ymd_opt(2024, 6, 1));
+        assert_eq!(items[1].due, None);
+        assert_eq!(items[2].text, "party @due:someday");
+        assert_eq!(items[2].due, None);
+
+        let formatted = format_list_as_markdown(&list);
+        assert!(formatted
+            .contains("- [ ] pay rent @due:2024-06-01 @since:2024-05-01T09:00:00Z  ^abcde\n"));
+        assert!(formatted.contains("- [ ] call mom @since:2024-05-01T09:00:00Z  ^fghij\n"));
+        assert!(
+            formatted.contains("- [ ] party @due:someday @since:2024-05-01T09:00:00Z  ^klmno\n")
+        );
+
+        assert_eq!(
+            split_due_date("pay rent @due 2024-06-01"),
+            ("pay rent", chrono::NaiveDate::from_ymd_opt(2024, 6, 1))
+        );
+    }
+
+    #[test]
+    fn test_priority_round_trips() {
+        use crate::models::Priority;
+
+        let markdown = "---\ntitle: todo\ncreated: 2024-05-01T09:00:00Z\n---\n\n\
+- [ ] !!! pay rent @due:2024-06-01  ^abcde\n\
+- [x] ! water plants  ^fghij\n\n\
+## Errands\n\
+- [ ] buy stamps @prio:medium  ^klmno\n\
+- [ ] !important is not a marker  ^pqrst\n";
+        let list = parse_list_from_string(markdown, Path::new("todo.md")).unwrap();
+        let items = &list.uncategorized_items;
+        assert_eq!(items[0].text, "pay rent");
+        assert_eq!(items[0].priority, Some(Priority::High));
+        assert_eq!(items[0].anchor, "^abcde");
+        assert_eq!(items[1].priority, Some(Priority::Low));
+        let errands = &list.categories[0].items;
+        assert_eq!(list.categories[0].name, "Errands");
+        assert_eq!(errands[0].text, "buy stamps");
+        assert_eq!(errands[0].priority, Some(Priority::Medium));
+        assert_eq!(errands[1].text, "!important is not a marker");
+        assert_eq!(errands[1].priority, None);
+
+        let formatted = format_list_as_markdown(&list);
+        assert!(formatted
+            .contains("- [ ] !!! pay rent @due:2024-06-01 @since:2024-05-01T09:00:00Z  ^abcde\n"));
+        assert!(formatted.contains("- [ ] !! buy stamps @since:2024-05-01T09:00:00Z  ^klmno\n"));
+        let reparsed = parse_list_from_string(&formatted, Path::new("todo.md")).unwrap();
+        assert_eq!(format_list_as_markdown(&reparsed), formatted);
+    }
+
+    #[test]
+    fn test_item_note_round_trips() {
+        // Note lines are indented, so they are spelled out rather than using
+        // string continuations (which would strip the indentation)
+        let markdown = [
+            "---\ntitle: todo\ncreated: 2024-05-01T09:00:00Z\n---\n",
+            "- [ ] call plumber  ^abcde",
+            "  ask about Saturday availability",
+            "  ",
+            "  - [ ] not an item, just note text",
+            "- [ ] pay rent  ^fghij\n",
+            "## Errands",
+            "- [ ] buy stamps  ^klmno",
+            "\tfirst class\n",
+        ]
+        .join("\n");
+        let list = parse_list_from_string(&markdown, Path::new("todo.md")).unwrap();
+        let items = &list.uncategorized_items;
+        assert_eq!(items.len(), 2);
+        assert_eq!(
+            items[0].note.as_deref(),
+            Some("ask about Saturday availability\n\n- [ ] not an item, just note text")
+        );
+        assert_eq!(items[1].note, None);
+        assert_eq!(
+            list.categories[0].items[0].note.as_deref(),
+            Some("first class")
+        );
+
+        let formatted = format_list_as_markdown(&list);
+        assert!(formatted.contains(
+            "- [ ] call plumber @since:2024-05-01T09:00:00Z  ^abcde\n  ask about Saturday availability\n  \n  - [ ] not"
+        ));
+        assert!(formatted
+            .contains("- [ ] buy stamps @since:2024-05-01T09:00:00Z  ^klmno\n  first class\n"));
+        let reparsed = parse_list_from_string(&formatted, Path::new("todo.md")).unwrap();
+        assert_eq!(format_list_as_markdown(&reparsed), formatted);
+
+        let storage = super::super::backend::MemoryStorage::new();
+        storage.write_list("todo", &list, "create").unwrap();
+        let item = set_item_note_with(&storage, "todo", "pay rent", "by\nFriday \n\n", 50).unwrap();
+        assert_eq!(item.note.as_deref(), Some("by\nFriday"));
+        set_item_note_with(&storage, "todo", "^abcde", "", 50).unwrap();
+        let list = storage.read_list("todo").unwrap();
+        assert_eq!(list.uncategorized_items[0].note, None);
+        assert_eq!(
+            list.uncategorized_items[1].note.as_deref(),
+            Some("by\nFriday")
+        );
+    }
+
+    #[test]
+    fn test_priority_order_is_stable() {
+        use crate::models::{priority_order, Priority};
+
+        let mut list = List::new("todo".to_string());
+        for text in ["a", "! b", "!!! c", "d", "! e", "!!! f"] {
+            list.add_item(text.to_string());
+        }
+        assert_eq!(list.uncategorized_items[2].priority, Some(Priority::High));
+        let texts: Vec<&str> = priority_order(&list.uncategorized_items)
+            .into_iter()
+            .map(|i| list.uncategorized_items[i].text.as_str())
+            .collect();
+        assert_eq!(texts, ["c", "f", "b", "e", "a", "d"]);
+    }
+
+    #[test]
+    fn test_apply_template_adds_missing_category_and_items() {
+        let mut list = fixture();
+        let milk_anchor = list.categories[0].items[0].anchor.clone();
+        list.categories[0].items[0].status = ItemStatus::Done;
+
+        let mut template = List::new("weekly".to_string());
+        template.add_item_to_category("  MILK ".to_string(), Some("Dairy"));
+        template.add_item_to_category("yoghurt".to_string(), Some("Dairy"));
+        template.add_item_to_category("soap".to_string(), Some("Household"));
+
+        assert_eq!(apply_template_to_list(&mut list, &template), 2);
+
+        let names: Vec<_> = list.categories.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Dairy", "Fridge", "Household"]);
+        let dairy: Vec<_> = list.categories[0]
+            .items
+            .iter()
+            .map(|i| i.text.as_str())
+            .collect();
+        assert_eq!(dairy, vec!["milk", "cheese", "yoghurt"]);
+        assert_eq!(list.categories[0].items[0].anchor, milk_anchor);
+        assert_eq!(list.categories[0].items[0].status, ItemStatus::Done);
+        assert_eq!(list.categories[2].items[0].text, "soap");
+
+        // Applying the same template again adds nothing
+        assert_eq!(apply_template_to_list(&mut list, &template), 0);
+    }
+
+    #[test]
+    fn test_add_and_remove_tags() {
+        let mut list = fixture();
+        let tags = vec!["#shopping".to_string(), "weekly".to_string()];
+        assert_eq!(add_tags_to_list(&mut list, &tags), 2);
+        assert_eq!(add_tags_to_list(&mut list, &["shopping".to_string()]), 0);
+        assert_eq!(list.metadata.tags, vec!["shopping", "weekly"]);
+
+        let markdown = format_list_as_markdown(&list);
+        let mut reloaded = parse_list_from_string(&markdown, Path::new("groceries.md")).unwrap();
+        assert_eq!(reloaded.metadata.tags, vec!["shopping", "weekly"]);
+
+        assert_eq!(
+            remove_tags_from_list(&mut reloaded, &["weekly".to_string()]),
+            1
+        );
+        assert_eq!(reloaded.metadata.tags, vec!["shopping"]);
+        assert_eq!(
+            remove_tags_from_list(&mut reloaded, &["missing".to_string()]),
+            0
+        );
+    }
+
+    #[test]
+    fn test_list_without_tags_key_parses() {
+        let markdown = "---\ntitle: old\nsharing: []\n---\n\n- [ ] item  ^abcde\n";
+        let list = parse_list_from_string(markdown, Path::new("old.md")).unwrap();
+        assert!(list.metadata.tags.is_empty());
+        assert!(!format_list_as_markdown(&list).contains("tags"));
+        assert_eq!(list.metadata.list, Default::default());
+        assert!(!format_list_as_markdown(&list).contains("list:"));
+    }
+
+    #[test]
+    fn test_default_category_applies_to_items_without_one() {
+        let storage = super::super::backend::MemoryStorage::new();
+        let mut list = fixture();
+        list.metadata.list.default_category = Some("Dairy".to_string());
+        storage.write_list("groceries", &list, "create").unwrap();
+        storage.write_list("plain", &fixture(), "create").unwrap();
+
+        add_item_to_category_with(&storage, "groceries", "yogurt", None, None).unwrap();
+        add_item_to_category_with(&storage, "groceries", "eggs", Some("Fridge"), None).unwrap();
+        add_item_to_category_with(&storage, "plain", "yogurt", None, None).unwrap();
+
+        let list = storage.read_list("groceries").unwrap();
+        assert!(list.uncategorized_items.is_empty());
+        assert_eq!(list.categories[0].items.last().unwrap().text, "yogurt");
+        assert_eq!(list.categories[1].items.last().unwrap().text, "eggs");
+        assert_eq!(
+            list.metadata.list.default_category.as_deref(),
+            Some("Dairy")
+        );
+        let plain = storage.read_list("plain").unwrap();
+        assert_eq!(plain.uncategorized_items[0].text, "yogurt");
+    }
+
+    #[test]
+    fn test_toggle_item_by_anchor_and_ambiguous_query() {
+        let storage = super::super::backend::MemoryStorage::new();
+        let mut list = List::new("groceries".to_string());
+        list.add_item("oat milk".to_string());
+        list.add_item("almond milk".to_string());
+        let anchor = list.uncategorized_items[1].anchor.clone();
+        storage.write_list("groceries", &list, "create").unwrap();
+
+        let item = toggle_item_with(&storage, "groceries", &anchor, 50).unwrap();
+        assert_eq!(item.text, "almond milk");
+        assert_eq!(item.status, ItemStatus::Done);
+        let item = toggle_item_with(&storage, "groceries", &anchor, 50).unwrap();
+        assert_eq!(item.status, ItemStatus::Todo);
+
+        let err = toggle_item_with(&storage, "groceries", "milk", 50).unwrap_err();
+        assert!(err.to_string().contains("Multiple items match"));
+        let list = storage.read_list("groceries").unwrap();
+        assert!(list.all_items().all(|i| i.status == ItemStatus::Todo));
+    }
+
+    #[test]
+    fn test_default_sort_persists_across_reloads() {
+        use crate::models::ListSort;
+
+        for (name, expected) in [
+            ("alpha", ["apples", "Bread", "!! cheese"]),
+            ("created", ["Bread", "apples", "!! cheese"]),
+            ("priority", ["!! cheese", "Bread", "apples"]),
+        ] {
+            let markdown = format!(
+                "---\ntitle: shop\nlist:\n  default_sort: {}\n---\n\n\
+- [ ] Bread  ^aaaaa\n- [ ] apples  ^bbbbb\n- [ ] !! cheese  ^ccccc\n",
+                name
+            );
+            let list = parse_list_from_string(&markdown, Path::new("shop.md")).unwrap();
+            let formatted = format_list_as_markdown(&list);
+            let reparsed = parse_list_from_string(&formatted, Path::new("shop.md")).unwrap();
+            assert_eq!(reparsed.metadata.list, list.metadata.list);
+
+            let sort = reparsed.metadata.list.default_sort.unwrap();
+            assert_eq!(Some(sort), ListSort::from_name(name));
+            let items = &reparsed.uncategorized_items;
+            let texts: Vec<String> = sort
+                .order(items)
+                .into_iter()
+                .map(|i| match items[i].priority {
+                    Some(p) => format!("{} {}", p.marker(), items[i].text),
+                    None => items[i].text.clone(),
+                })
+                .collect();
+            assert_eq!(texts, expected);
+        }
+    }
+
+    #[test]
+    fn test_split_by_category_creates_one_list_per_category() {
+        let storage = super::super::backend::MemoryStorage::new();
+        let mut list = fixture();
+        list.add_item("bread".to_string());
+        list.categories[0].items[0].status = ItemStatus::Done;
+        let milk_anchor = list.categories[0].items[0].anchor.clone();
+        storage.write_list("groceries", &list, "create").unwrap();
+
+        let created = split_by_category_with(&storage, "groceries", true).unwrap();
+        assert_eq!(created, ["groceries-dairy", "groceries-fridge"]);
+
+        let dairy = storage.read_list("groceries-dairy").unwrap();
+        assert!(dairy.categories.is_empty());
+        let texts: Vec<_> = dairy
+            .uncategorized_items
+            .iter()
+            .map(|i| i.text.as_str())
+            .collect();
+        assert_eq!(texts, ["milk", "cheese"]);
+        assert_eq!(dairy.uncategorized_items[0].status, ItemStatus::Done);
+        assert_eq!(dairy.uncategorized_items[0].anchor, milk_anchor);
+        assert_eq!(
+            storage
+                .read_list("groceries-fridge")
+                .unwrap()
+                .uncategorized_items[1]
+                .text,
+            "butter"
+        );
+
+        let original = storage.read_list("groceries").unwrap();
+        assert!(original.categories.is_empty());
+        assert_eq!(original.uncategorized_items[0].text, "bread");
+
+        // Splitting again would clobber the lists created above
+        storage
+            .write_list("groceries", &fixture(), "reset")
+            .unwrap();
+        let err = split_by_category_with(&storage, "groceries", false).unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+    }
+
+    #[test]
+    fn test_split_by_category_rejects_categories_with_the_same_name() {
+        let storage = super::super::backend::MemoryStorage::new();
+        let mut list = fixture();
+        list.add_item_to_category("yogurt".to_string(), Some("dairy"));
+        storage.write_list("groceries", &list, "create").unwrap();
+
+        let err = split_by_category_with(&storage, "groceries", true).unwrap_err();
+        assert!(err.to_string().contains("'Dairy' and 'dairy'"), "{}", err);
+        assert!(!storage
+            .list_names()
+            .unwrap()
+            .contains(&"groceries-dairy".to_string()));
+        let list = storage.read_list("groceries").unwrap();
+        assert_eq!(list.categories.len(), 3);
+    }
+
+    #[test]
+    fn test_set_all_status_done_completes_every_item() {
+        let storage = super::super::backend::MemoryStorage::new();
+        let mut list = fixture();
+        list.add_item("bread".to_string());
+        list.categories[0].items[0].status = ItemStatus::Done;
+        storage.write_list("groceries", &list, "create").unwrap();
+
+        let changed = set_all_status_with(&storage, "groceries", ItemStatus::Done).unwrap();
+        assert_eq!(changed.len(), list.all_items().count() - 1);
+        assert!(changed.iter().all(|item| item.status == ItemStatus::Done));
+
+        let list = storage.read_list("groceries").unwrap();
+        assert!(list.all_items().all(|item| item.status == ItemStatus::Done));
+        assert!(set_all_status_with(&storage, "groceries", ItemStatus::Done).is_err());
+
+        let changed = set_all_status_with(&storage, "groceries", ItemStatus::Todo).unwrap();
+        assert_eq!(changed.len(), list.all_items().count());
+    }
+
+    #[test]
+    fn test_set_category_status_only_touches_that_category() {
+        let storage = super::super::backend::MemoryStorage::new();
+        let mut list = fixture();
+        list.add_item("bread".to_string());
+        storage.write_list("groceries", &list, "create").unwrap();
+
+        let changed =
+            set_category_status_with(&storage, "groceries", "dairy", ItemStatus::Done).unwrap();
+        let texts: Vec<_> = changed.iter().map(|item| item.text.as_str()).collect();
+        assert_eq!(texts, ["milk", "cheese"]);
+
+        let list = storage.read_list("groceries").unwrap();
+        assert!(list.categories[0]
+            .items
+            .iter()
+            .all(|item| item.status == ItemStatus::Done));
+        assert!(list.categories[1]
+            .items
+            .iter()
+            .all(|item| item.status == ItemStatus::Todo));
+        assert_eq!(list.uncategorized_items[0].status, ItemStatus::Todo);
+
+        assert!(
+            set_category_status_with(&storage, "groceries", "Dairy", ItemStatus::Done).is_err()
+        );
+        assert!(
+            set_category_status_with(&storage, "groceries", "Bakery", ItemStatus::Done)
+                .unwrap_err()
+                .to_string()
+                .contains("not found")
+        );
+    }
+
+    #[test]
+    fn test_daily_list_carries_over_template_items() {
+        let storage = super::super::backend::MemoryStorage::new();
+        assert_eq!(
+            create_daily_list_with(&storage, "daily_lists/a").unwrap(),
+            0
+        );
+
+        let mut template = List::new("_template_daily_list".to_string());
+        template.add_item("!! stretch".to_string());
+        template.add_item("journal".to_string());
+        template.uncategorized_items[1].status = ItemStatus::Done;
+        template.add_item_to_category("not carried".to_string(), Some("Extra"));
+        storage
+            .write_list(DAILY_TEMPLATE_LIST, &template, "create")
+            .unwrap();
+
+        let name = "daily_lists/20240601_daily_list";
+        assert_eq!(create_daily_list_with(&storage, name).unwrap(), 2);
+        let list = storage.read_list(name).unwrap();
+        assert_eq!(list.metadata.title, "20240601_daily_list");
+        assert!(list.categories.is_empty());
+        for (item, original) in list
+            .uncategorized_items
+            .iter()
+            .zip(&template.uncategorized_items)
+        {
+            assert_eq!(item.text, original.text);
+            assert_eq!(item.priority, original.priority);
+            assert_eq!(item.status, ItemStatus::Todo);
+            assert_ne!(item.anchor, original.anchor);
+            assert!(is_valid_anchor(&item.anchor));
+        }
+    }
+
+    #[test]
+    fn test_rollover_carries_only_open_items_into_today() {
+        let storage = super::super::backend::MemoryStorage::new();
+        let today = "daily_lists/20240603_daily_list";
+        let rollover = rollover_daily_list_with(&storage, today, false).unwrap();
+        assert!(rollover.source.is_none() && rollover.items.is_empty());
+        assert!(storage.read_list(today).is_ok(), "today's list is created");
+
+        let mut older = List::new("20240531_daily_list".to_string());
+        older.add_item("stale".to_string());
+        storage
+            .write_list("daily_lists/20240531_daily_list", &older, "create")
+            .unwrap();
+        let mut yesterday = List::new("20240602_daily_list".to_string());
+        yesterday.add_item("pay rent".to_string());
+        yesterday.add_item("water plants".to_string());
+        yesterday.add_item_to_category("call plumber".to_string(), Some("Home"));
+        yesterday.add_item_to_category("fix sink".to_string(), Some("Home"));
+        yesterday.uncategorized_items[1].status = ItemStatus::Done;
+        yesterday.categories[0].items[1].status = ItemStatus::Done;
+        let yesterday_name = "daily_lists/20240602_daily_list";
+        storage
+            .write_list(yesterday_name, &yesterday, "create")
+            .unwrap();
+
+        let rollover = rollover_daily_list_with(&storage, today, false).unwrap();
+        assert_eq!(rollover.source.as_deref(), Some(yesterday_name));
+        let texts: Vec<_> = rollover.items.iter().map(|i| i.text.as_str()).collect();
+        assert_eq!(texts, vec!["pay rent", "call plumber"]);
+        let list = storage.read_list(today).unwrap();
+        assert_eq!(list.uncategorized_items.len(), 1);
+        assert_eq!(list.categories[0].name, "Home");
+        assert_eq!(list.categories[0].items[0].text, "call plumber");
+        assert!(list.all_items().all(|i| i.status == ItemStatus::Todo));
+        assert_ne!(
+            list.uncategorized_items[0].anchor,
+            yesterday.uncategorized_items[0].anchor
+        );
+
+        // Running it again adds nothing; clearing empties the source of todos
+        let rollover = rollover_daily_list_with(&storage, today, true).unwrap();
+        assert!(rollover.items.is_empty());
+        assert_eq!(storage.read_list(today).unwrap().all_items().count(), 2);
+        let source = storage.read_list(yesterday_name).unwrap();
+        let left: Vec<_> = source.all_items().map(|i| i.text.as_str()).collect();
+        assert_eq!(left, vec!["water plants", "fix sink"]);
+    }
+
+    #[test]
+    fn test_index_ranges_and_sets_target_several_items() {
+        let storage = super::super::backend::MemoryStorage::new();
+        let mut list = List::new("groceries".to_string());
+        for text in [
+            "milk", "bread", "eggs", "cheese", "apples", "pears", "plums",
+        ] {
+            list.add_item(text.to_string());
+        }
+        storage.write_list("groceries", &list, "create").unwrap();
+        let texts = |items: Vec<ListItem>| -> Vec<String> {
+            items.into_iter().map(|item| item.text).collect()
+        };
+
+        let done = mark_done_with(&storage, "groceries", "#2-5", 80).unwrap();
+        assert_eq!(texts(done), ["bread", "eggs", "cheese", "apples"]);
+        let list = storage.read_list("groceries").unwrap();
+        let statuses: Vec<bool> = list
+            .all_items()
+            .map(|item| item.status == ItemStatus::Done)
+            .collect();
+        assert_eq!(statuses, [false, true, true, true, true, false, false]);
+
+        // Indices refer to the list as it was, not as it is mid-removal
+        let removed = delete_item_with(&storage, "groceries", "#1,#3, #7", 80).unwrap();
+        assert_eq!(texts(removed), ["milk", "eggs", "plums"]);
+        let list = storage.read_list("groceries").unwrap();
+        let left: Vec<&str> = list.all_items().map(|item| item.text.as_str()).collect();
+        assert_eq!(left, ["bread", "cheese", "apples", "pears"]);
+
+        for (target, message) in [
+            ("#3-9", "'#3-9' is out of range: the list has 4 item(s)"),
+            ("#0", "'#0' is out of range: the list has 4 item(s)"),
+            ("#1,#5", "'#5' is out of range: the list has 4 item(s)"),
+            ("#3-2", "Invalid range '#3-2': it ends before it starts"),
+        ] {
+            let err = mark_done_with(&storage, "groceries", target, 80).unwrap_err();
+            assert_eq!(err.to_string(), message);
+        }
+        let unchanged = storage.read_list("groceries").unwrap();
+        assert_eq!(unchanged.uncategorized_items[0].status, ItemStatus::Done);
+        assert_eq!(unchanged.uncategorized_items[3].status, ItemStatus::Todo);
+
+        // Text and anchor targets still work alongside indices
+        let done = mark_done_with(&storage, "groceries", "pears,#2", 80).unwrap();
+        assert_eq!(texts(done), ["pears", "cheese"]);
+    }
+
+    #[test]
+    fn test_mutations_bump_updated_and_keep_created() {
+        let storage = super::super::backend::MemoryStorage::new();
+        let mut list = List::new("groceries".to_string());
+        list.add_item("milk".to_string());
+        list.add_item("bread".to_string());
+        let long_ago = chrono::Utc::now() - chrono::Duration::days(30);
+        list.metadata.created = Some(long_ago);
+        list.metadata.updated = long_ago;
+        storage.write_list("groceries", &list, "create").unwrap();
+
+        let mut last = long_ago;
+        let mut bumped = |storage: &dyn Storage| {
+            let list = storage.read_list("groceries").unwrap();
+            assert_eq!(list.metadata.created, Some(long_ago));
+            assert!(list.metadata.updated > last);
+            last = list.metadata.updated;
+        };
+        mark_done_with(&storage, "groceries", "milk", 80).unwrap();
+        bumped(&storage);
+        toggle_item_with(&storage, "groceries", "milk", 80).unwrap();
+        bumped(&storage);
+        add_item_to_category_with(&storage, "groceries", "eggs", None, None).unwrap();
+        bumped(&storage);
+        delete_item_with(&storage, "groceries", "bread", 80).unwrap();
+        bumped(&storage);
+
+        // Lists from before `created` was recorded don't gain one
+        let legacy = parse_list_from_string("---\ntitle: old\n---\n", Path::new("old.md"));
+        assert_eq!(legacy.unwrap().metadata.created, None);
+    }
+
+    #[test]
+    fn test_copied_list_gets_fresh_anchors_and_title() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("templates/packing.md");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let content = "---\nid: 3f0e4a1c-5b2d-4c8e-9f7a-1d2e3f4a5b6c\ntitle: packing\n\
+                       tags:\n- travel\n---\n\n\
+                       - [ ] passport  ^abcde\n\n## Clothes\n\n- [x] socks  ^fghij\n";
+        fs::write(&path, content).unwrap();
+
+        let (from, to) =
+            copy_list_in(dir.path(), "templates/packing", "trips/rome", false).unwrap();
+        assert_eq!(from, path);
+        assert_eq!(to, dir.path().join("trips/rome.md"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+
+        let original = parse_list_from_file(&path).unwrap();
+        let copy = parse_list_from_file(&to).unwrap();
+        assert_eq!(copy.metadata.title, "rome");
+        assert_eq!(copy.metadata.tags, ["travel"]);
+        assert_ne!(copy.metadata.id, original.metadata.id);
+        let texts = |list: &List| list.all_items().map(|i| i.text.clone()).collect::<Vec<_>>();
+        assert_eq!(texts(&copy), texts(&original));
+        assert_eq!(copy.categories[0].items[0].status, ItemStatus::Done);
+        for item in copy.all_items() {
+            assert!(is_valid_anchor(&item.anchor), "{}", item.anchor);
+            assert!(original.find_by_anchor(&item.anchor).is_none());
+        }
+
+        assert!(copy_list_in(dir.path(), "templates/packing", "trips/rome", false).is_err());
+        assert!(copy_list_in(dir.path(), "missing", "other", false).is_err());
+    }
+
+    #[test]
+    fn test_items_record_creation_time_and_legacy_items_fall_back_to_mtime() {
+        use chrono::TimeZone;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("groceries.md");
+        fs::write(
+            &path,
+            "---\ntitle: groceries\n---\n\n\
+- [ ] milk  ^abcde\n\
+- [ ] bread @due:2024-06-01 @since:2024-05-01T09:30:00+02:00  ^fghij\n",
+        )
+        .unwrap();
+        let mtime = Utc.with_ymd_and_hms(2024, 3, 1, 8, 0, 0).unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(mtime.into())
+            .unwrap();
+
+        let mut list = parse_list_from_file(&path).unwrap();
+        let items = &list.uncategorized_items;
+        assert_eq!(items[0].created, mtime);
+        assert_eq!(items[1].text, "bread");
+        assert_eq!(items[1].due, chrono::NaiveDate::from_ymd_opt(2024, 6, 1));
+        assert_eq!(
+            items[1].created,
+            Utc.with_ymd_and_hms(2024, 5, 1, 7, 30, 0).unwrap()
+        );
+
+        let before = Utc::now();
+        let eggs = list.add_item_to_category("eggs".to_string(), None);
+        assert!(eggs.created >= before && eggs.created <= Utc::now());
+
+        // Saving writes every item's timestamp, so legacy ones keep theirs
+        let formatted = format_list_as_markdown(&list);
+        assert!(formatted.contains("- [ ] milk @since:2024-03-01T08:00:00Z  ^abcde\n"));
+        assert!(
+            formatted.contains("- [ ] bread @due:2024-06-01 @since:2024-05-01T07:30:00Z  ^fghij\n")
+        );
+        let reparsed = parse_list_from_string(&formatted, Path::new("groceries.md")).unwrap();
+        assert_eq!(reparsed.uncategorized_items[0].created, mtime);
+        assert_eq!(
+            reparsed.uncategorized_items[2].created.timestamp(),
+            eggs.created.timestamp()
+        );
+    }
+
+    #[test]
+    fn test_resolution_and_load_failures_map_to_storage_error_variants() {
+        let dir = tempfile::tempdir().unwrap();
+        let lists_dir = dir.path();
+        for name in ["groceries", "grocery-ideas"] {
+            let list = List::new(name.to_string());
+            write_list_to_file(&list, &lists_dir.join(format!("{}.md", name))).unwrap();
+        }
+        std::fs::write(lists_dir.join("broken.md"), "---\ntitle: [oops\n---\n").unwrap();
+
+        let err = resolve_list_path_in(lists_dir, "chores").unwrap_err();
+        assert!(matches!(err, StorageError::NotFound(_)), "{:?}", err);
+        match resolve_list_path_in(lists_dir, "grocer").unwrap_err() {
+            StorageError::Ambiguous { query, matches, .. } => {
+                assert_eq!(query, "grocer");
+                assert_eq!(matches.len(), 2, "{:?}", matches);
+            }
+            err => panic!("expected Ambiguous, got {:?}", err),
+        }
+        let err = parse_list_from_file(&lists_dir.join("broken.md")).unwrap_err();
+        assert!(matches!(err, StorageError::Parse(_)), "{:?}", err);
+        let err = parse_list_from_file(&lists_dir.join("missing.md")).unwrap_err();
+        assert!(matches!(err, StorageError::Io { .. }), "{:?}", err);
+
+        let mut list = List::new("groceries".to_string());
+        list.add_item("oat milk".to_string());
+        list.add_item("almond milk".to_string());
+        let err = find_item_for_removal(&list, "coffee", 50).unwrap_err();
+        assert!(matches!(err, StorageError::NotFound(_)), "{:?}", err);
+        match find_item_for_removal(&list, "milk", 50).unwrap_err() {
+            StorageError::Ambiguous { what, matches, .. } => {
+                assert_eq!(what, "items");
+                assert_eq!(matches, ["oat milk", "almond milk"]);
+            }
+            err => panic!("expected Ambiguous, got {:?}", err),
+        }
+        let err = find_and_set_item_status(&mut list, "coffee", ItemStatus::Done, 50).unwrap_err();
+        assert!(matches!(err, StorageError::NotFound(_)), "{:?}", err);
+
+        // Through `?` the variant stays recoverable from the anyhow error
+        let storage = super::super::backend::MemoryStorage::new();
+        let err = mark_done_with(&storage, "chores", "milk", 50).unwrap_err();
+        let err = err.downcast_ref::<StorageError>();
+        assert!(matches!(err, Some(StorageError::NotFound(_))), "{:?}", err);
+    }
+}
@@ -0,0 +1,580 @@
+use super::backend::{FsStorage, Storage};
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// A markdown link or image in a document pointing at a hashed media file
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MediaRef {
+    /// Link text: the caption of an image or the name of an attachment
+    pub label: String,
+    /// Sha256 of the referenced file, which is also its name in the media dir
+    pub hash: String,
+    /// Link target as written in the document
+    pub target: String,
+    /// Whether this is an image (`![..](..)`) rather than a plain link
+    pub image: bool,
+    /// 1-based line of the reference
+    pub line: usize,
+}
+
+/// Result of attaching a file to a document
+#[derive(Debug, Clone, Serialize)]
+pub struct Attachment {
+    /// Sha256 of the file contents
+    pub hash: String,
+    /// Location of the file in the media dir
+    pub path: PathBuf,
+    /// Markdown appended to the document
+    pub markdown: String,
+    /// Whether an identical file was already stored and got reused
+    pub reused: bool,
+}
+
+/// Result of removing a hash's references from a document
+#[derive(Debug, Clone, Serialize)]
+pub struct Detached {
+    /// Number of references removed from the document
+    pub removed: usize,
+    /// Whether the media file was deleted because nothing references it anymore
+    pub media_deleted: bool,
+}
+
+lazy_static::lazy_static! {
+    static ref LINK_RE: Regex = Regex::new(
+        r"(!?)\[([^\]]*)\]\(((?:[^)\s]*/)?([0-9a-f]{64})(?:\.[A-Za-z0-9]+)?)\)"
+    )
+    .unwrap();
+}
+
+/// All references to hashed media files in `content`, in document order
+pub fn references(content: &str) -> Vec<MediaRef> {
+    let mut refs = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        for captures in LINK_RE.captures_iter(line) {
+            refs.push(MediaRef {
+                label: captures[2].to_string(),
+                hash: captures[4].to_string(),
+                target: captures[3].to_string(),
+                image: !captures[1].is_empty(),
+                line: index + 1,
+            });
+        }
+    }
+    refs
+}
+
+/// Distinct hashes referenced in `content` that start with `prefix`, sorted
+pub fn matching_hashes(content: &str, prefix: &str) -> Vec<String> {
+    let mut hashes: Vec<String> = references(content)
+        .into_iter()
+        .map(|r| r.hash)
+        .filter(|h| h.starts_with(prefix))
+        .collect();
+    hashes.sort();
+    hashes.dedup();
+    hashes
+}
+
+/// Path of the media file `reference` points at, if it exists in `media_dir`
+pub fn stored_file_in(media_dir: &Path, reference: &MediaRef) -> Option<PathBuf> {
+    let path = media_dir.join(Path::new(&reference.target).file_name()?);
+    path.is_file().then_some(path)
+}
+
+/// Remove every reference to `hash` from `content`. Lines left empty by the
+/// removal are dropped entirely. Returns the new content and how many
+/// references were removed
+pub fn remove_references(content: &str, hash: &str) -> (String, usize) {
+    let mut removed = 0;
+    let mut lines = Vec::new();
+    for line in content.split_inclusive('\n') {
+        let mut hit = false;
+        let kept = LINK_RE.replace_all(line, |captures: &regex::Captures| {
+            if &captures[4] == hash {
+                hit = true;
+                removed += 1;
+                String::new()
+            } else {
+                captures[0].to_string()
+            }
+        });
+        if hit && kept.trim().is_empty() {
+            continue;
+        }
+        lines.push(kept.into_owned());
+    }
+    (lines.concat(), removed)
+}
+
+/// Copy `file` into `media_dir` as `<sha256>.<ext>`, reusing an existing copy
+/// with the same contents. Returns the hash, the stored path and whether it
+/// was reused
+pub fn store_file(media_dir: &Path, file: &Path) -> Result<(String, PathBuf, bool)> {
+    let bytes =
+        fs::read(file).with_context(|| format!("Failed to read file: {}", file.display()))?;
+    let hash = hex::encode(Sha256::digest(&bytes));
+    let name = match file.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => format!("{}.{}", hash, ext.to_lowercase()),
+        None => hash.clone(),
+    };
+    let path = media_dir.join(name);
+    if path.exists() {
+        return Ok((hash, path, true));
+    }
+    fs::create_dir_all(media_dir)
+        .with_context(|| format!("Failed to create directory: {}", media_dir.display()))?;
+    fs::write(&path, &bytes)
+        .with_context(|| format!("Failed to write media file: {}", path.display()))?;
+    Ok((hash, path, false))
+}
+
+/// Path of `to` relative to the directory `from`, with forward slashes
+pub fn relative_link(from: &Path, to: &Path) -> String {
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut parts: Vec<String> = vec!["..".to_string(); from.len() - common];
+    parts.extend(
+        to[common..]
+            .iter()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned()),
+    );
+    parts.join("/")
+}
+
+/// Markdown reference from the document at `doc` to the stored media file
+/// `stored` (an image when `image` is set, else a link)
+pub fn link_markdown(doc: &Path, stored: &Path, label: &str, image: bool) -> String {
+    let link = relative_link(doc.parent().unwrap_or(Path::new("")), stored);
+    let label = label.replace(['[', ']'], "");
+    format!("{}[{}]({})", if image { "!" } else { "" }, label, link)
+}
+
+/// Store `file` in `media_dir` and append a markdown reference to it at the
+/// end of the document at `doc` (an image when `image` is set, else a link)
+pub fn attach_in(
+    media_dir: &Path,
+    doc: &Path,
+    file: &Path,
+    label: &str,
+    image: bool,
+) -> Result<Attachment> {
+    let mut content = fs::read_to_string(doc)
+        .with_context(|| format!("Failed to read document: {}", doc.display()))?;
+    let (hash, path, reused) = store_file(media_dir, file)?;
+
+    let markdown = link_markdown(doc, &path, label, image);
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push('\n');
+    content.push_str(&markdown);
+    content.push('\n');
+    super::atomic_write(doc, content)
+        .with_context(|| format!("Failed to write document: {}", doc.display()))?;
+
+    Ok(Attachment {
+        hash,
+        path,
+        markdown,
+        reused,
+    })
+}
+
+/// Store `file` in `media_dir` and add a reference to it as a new item of the
+/// list `list_name` in `lists_dir`. Lists only keep their items when saved,
+/// so the reference has to live in an item rather than a free-standing line
+pub fn attach_to_list_in(
+    storage: &dyn Storage,
+    lists_dir: &Path,
+    media_dir: &Path,
+    list_name: &str,
+    file: &Path,
+    label: &str,
+    image: bool,
+) -> Result<Attachment> {
+    let mut list = storage.read_list(list_name)?;
+    let (hash, path, reused) = store_file(media_dir, file)?;
+
+    let doc = lists_dir.join(format!("{}.md", list_name));
+    let markdown = link_markdown(&doc, &path, label, image);
+    list.add_item(markdown.clone());
+    storage.write_list(list_name, &list, &format!("attach '{}'", label))?;
+
+    Ok(Attachment {
+        hash,
+        path,
+        markdown,
+        reused,
+    })
+}
+
+/// Number of references to `hash` across all markdown documents under
+/// `content_dir`
+pub fn reference_count_in(content_dir: &Path, hash: &str) -> Result<usize> {
+    let mut count = 0;
+    for path in super::list_files_recursive(content_dir, "md")? {
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read document: {}", path.display()))?;
+        count += references(&content)
+            .iter()
+            .filter(|r| r.hash == hash)
+            .count();
+    }
+    Ok(count)
+}
+
+/// Delete the media files named after `hash` if no document under
+/// `content_dir` references it anymore. Returns whether anything was deleted
+pub fn remove_if_orphaned_in(content_dir: &Path, media_dir: &Path, hash: &str) -> Result<bool> {
+    if reference_count_in(content_dir, hash)? > 0 || !media_dir.exists() {
+        return Ok(false);
+    }
+    let mut deleted = false;
+    for entry in fs::read_dir(media_dir)
+        .with_context(|| format!("Failed to read directory: {}", media_dir.display()))?
+    {
+        let path = entry?.path();
+        let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned());
+        if path.is_file() && stem.as_deref() == Some(hash) {
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove media file: {}", path.display()))?;
+            deleted = true;
+        }
+    }
+    Ok(deleted)
+}
+
+/// Remove all references to `hash` from the document at `doc`, then delete
+/// the media file if that was its last reference
+pub fn detach_in(content_dir: &Path, media_dir: &Path, doc: &Path, hash: &str) -> Result<Detached> {
+    let content = fs::read_to_string(doc)
+        .with_context(|| format!("Failed to read document: {}", doc.display()))?;
+    let (content, removed) = remove_references(&content, hash);
+    if removed > 0 {
+        super::atomic_write(doc, content)
+            .with_context(|| format!("Failed to write document: {}", doc.display()))?;
+    }
+    let media_deleted = remove_if_orphaned_in(content_dir, media_dir, hash)?;
+    Ok(Detached {
+        removed,
+        media_deleted,
+    })
+}
+
+/// Remove all references to `hash` from the items of the list `list_name`,
+/// dropping items that are left without text, then delete the media file if
+/// that was its last reference
+pub fn detach_from_list_in(
+    storage: &dyn Storage,
+    content_dir: &Path,
+    media_dir: &Path,
+    list_name: &str,
+    hash: &str,
+) -> Result<Detached> {
+    let mut list = storage.read_list(list_name)?;
+    let mut removed = 0;
+    let mut strip = |items: &mut Vec<crate::models::ListItem>| {
+        items.retain_mut(|item| {
+            let (text, count) = remove_references(&item.text, hash);
+            if count == 0 {
+                return true;
+            }
+            removed += count;
+            item.text = text.trim().to_string();
+            !item.text.is_empty()
+        });
+    };
+    strip(&mut list.uncategorized_items);
+    for category in &mut list.categories {
+        strip(&mut category.items);
+    }
+    if removed > 0 {
+        list.metadata.updated = chrono::Utc::now();
+        let short = &hash[..hash.len().min(12)];
+        storage.write_list(list_name, &list, &format!("detach '{}'", short))?;
+    }
+    let media_deleted = remove_if_orphaned_in(content_dir, media_dir, hash)?;
+    Ok(Detached {
+        removed,
+        media_deleted,
+    })
+}
+
+/// [`attach_in`] using the configured media directory
+pub fn attach(doc: &Path, file: &Path, label: &str, image: bool) -> Result<Attachment> {
+    attach_in(&super::get_media_dir()?, doc, file, label, image)
+}
+
+/// [`attach_to_list_in`] using the configured lists and media directories
+pub fn attach_to_list(
+    list_name: &str,
+    file: &Path,
+    label: &str,
+    image: bool,
+) -> Result<Attachment> {
+    attach_to_list_in(
+        &FsStorage::from_config()?,
+        &super::get_lists_dir()?,
+        &super::get_media_dir()?,
+        list_name,
+        file,
+        label,
+        image,
+    )
+}
+
+/// [`detach_in`] using the configured content and media directories
+pub fn detach(doc: &Path, hash: &str) -> Result<Detached> {
+    detach_in(
+        &super::get_content_dir()?,
+        &super::get_media_dir()?,
+        doc,
+        hash,
+    )
+}
+
+/// [`detach_from_list_in`] using the configured content and media directories
+pub fn detach_from_list(list_name: &str, hash: &str) -> Result<Detached> {
+    detach_from_list_in(
+        &FsStorage::from_config()?,
+        &super::get_content_dir()?,
+        &super::get_media_dir()?,
+        list_name,
+        hash,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attach_non_image_file_and_clean_up_on_removal() {
+        let dir = tempfile::tempdir().unwrap();
+        let content_dir = dir.path().join("content");
+        let media_dir = content_dir.join("media");
+        let notes_dir = content_dir.join("notes");
+        fs::create_dir_all(&notes_dir).unwrap();
+        let note = notes_dir.join("trip.md");
+        let other = notes_dir.join("other.md");
+        fs::write(&note, "---\ntitle: trip\n---\n\nTickets:").unwrap();
+        fs::write(&other, "nothing here\n").unwrap();
+        let pdf = dir.path().join("ticket.pdf");
+        fs::write(&pdf, b"%PDF-1.4 not really").unwrap();
+
+        let attachment = attach_in(&media_dir, &note, &pdf, "ticket.pdf", false).unwrap();
+        assert!(!attachment.reused);
+        assert_eq!(
+            attachment.path,
+            media_dir.join(format!("{}.pdf", attachment.hash))
+        );
+        assert_eq!(fs::read(&attachment.path).unwrap(), b"%PDF-1.4 not really");
+        let link = format!("[ticket.pdf](../media/{}.pdf)", attachment.hash);
+        assert_eq!(attachment.markdown, link);
+        let content = fs::read_to_string(&note).unwrap();
+        assert!(content.ends_with(&format!("Tickets:\n\n{}\n", link)));
+
+        let refs = references(&content);
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].hash, attachment.hash);
+        assert_eq!(refs[0].label, "ticket.pdf");
+        assert!(!refs[0].image);
+
+        // A second document sharing the file keeps it alive
+        assert!(
+            attach_in(&media_dir, &other, &pdf, "copy", false)
+                .unwrap()
+                .reused
+        );
+        let detached = detach_in(&content_dir, &media_dir, &note, &attachment.hash).unwrap();
+        assert_eq!(detached.removed, 1);
+        assert!(!detached.media_deleted);
+        assert!(attachment.path.exists());
+        assert!(!fs::read_to_string(&note)
+            .unwrap()
+            .contains(&attachment.hash));
+
+        let detached = detach_in(&content_dir, &media_dir, &other, &attachment.hash).unwrap();
+        assert!(detached.media_deleted);
+        assert!(!attachment.path.exists());
+        assert_eq!(fs::read_to_string(&other).unwrap(), "nothing here\n\n");
+    }
+
+    #[test]
+    fn test_adding_same_image_twice_reuses_one_media_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let content_dir = dir.path().join("content");
+        let media_dir = content_dir.join("media");
+        let notes_dir = content_dir.join("notes").join("travel");
+        fs::create_dir_all(&notes_dir).unwrap();
+        let note = notes_dir.join("trip.md");
+        fs::write(&note, "# Trip\n").unwrap();
+        let photo = dir.path().join("Photo.PNG");
+        fs::write(&photo, b"\x89PNG fake image").unwrap();
+
+        let first = attach_in(&media_dir, &note, &photo, "beach", true).unwrap();
+        let second = attach_in(&media_dir, &note, &photo, "beach again", true).unwrap();
+        assert!(!first.reused);
+        assert!(second.reused);
+        assert_eq!(first.path, second.path);
+        assert_eq!(fs::read_dir(&media_dir).unwrap().count(), 1);
+        assert_eq!(
+            first.markdown,
+            format!("![beach](../../media/{}.png)", first.hash)
+        );
+
+        let refs = references(&fs::read_to_string(&note).unwrap());
+        assert_eq!(refs.len(), 2);
+        assert!(refs.iter().all(|r| r.image && r.hash == first.hash));
+        assert_eq!(reference_count_in(&content_dir, &first.hash).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_attach_image_to_list_as_item() {
+        let dir = tempfile::tempdir().unwrap();
+        let content_dir = dir.path().join("content");
+        let lists_dir = content_dir.join("lists");
+        let media_dir = content_dir.join("media");
+        fs::create_dir_all(&lists_dir).unwrap();
+        fs::write(
+            lists_dir.join("packing.md"),
+            "---\ntitle: packing\n---\n\n- [ ] socks  ^abcde\n",
+        )
+        .unwrap();
+        let photo = dir.path().join("bag.jpg");
+        fs::write(&photo, b"jpeg bytes").unwrap();
+        let storage = FsStorage::new(&content_dir);
+
+        let attachment = attach_to_list_in(
+            &storage, &lists_dir, &media_dir, "packing", &photo, "bag", true,
+        )
+        .unwrap();
+        assert_eq!(
+            attachment.markdown,
+            format!("![bag](../media/{}.jpg)", attachment.hash)
+        );
+
+        // The reference survives a load/save round trip of the list
+        let list = storage.read_list("packing").unwrap();
+        storage.write_list("packing", &list, "resave").unwrap();
+        let content = fs::read_to_string(lists_dir.join("packing.md")).unwrap();
+        let refs = references(&content);
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].label, "bag");
+        assert_eq!(list.uncategorized_items[1].text, attachment.markdown);
+    }
+
+    #[test]
+    fn test_list_and_remove_one_of_several_images() {
+        let dir = tempfile::tempdir().unwrap();
+        let content_dir = dir.path().join("content");
+        let media_dir = content_dir.join("media");
+        let notes_dir = content_dir.join("notes");
+        fs::create_dir_all(&notes_dir).unwrap();
+        let note = notes_dir.join("album.md");
+        fs::write(&note, "# Album\n").unwrap();
+        let sea = dir.path().join("sea.png");
+        let hill = dir.path().join("hill.png");
+        fs::write(&sea, b"sea").unwrap();
+        fs::write(&hill, b"hill").unwrap();
+
+        let sea_ref = attach_in(&media_dir, &note, &sea, "sea", true).unwrap();
+        attach_in(&media_dir, &note, &sea, "sea again", true).unwrap();
+        let hill_ref = attach_in(&media_dir, &note, &hill, "hill", true).unwrap();
+        // A reference whose file has gone missing is still listed
+        let ghost = "b".repeat(64);
+        let mut content = fs::read_to_string(&note).unwrap();
+        content.push_str(&format!("![ghost](../media/{}.png)\n", ghost));
+        fs::write(&note, &content).unwrap();
+
+        let refs = references(&content);
+        let captions: Vec<&str> = refs.iter().map(|r| r.label.as_str()).collect();
+        assert_eq!(captions, ["sea", "sea again", "hill", "ghost"]);
+        assert_eq!(
+            stored_file_in(&media_dir, &refs[0]),
+            Some(sea_ref.path.clone())
+        );
+        assert_eq!(stored_file_in(&media_dir, &refs[3]), None);
+        assert_eq!(
+            matching_hashes(&content, &sea_ref.hash[..8]),
+            std::slice::from_ref(&sea_ref.hash)
+        );
+
+        let detached = detach_in(&content_dir, &media_dir, &note, &sea_ref.hash).unwrap();
+        assert_eq!(detached.removed, 2);
+        assert!(detached.media_deleted);
+        assert!(!sea_ref.path.exists());
+        assert!(hill_ref.path.exists());
+        let captions: Vec<String> = references(&fs::read_to_string(&note).unwrap())
+            .into_iter()
+            .map(|r| r.label)
+            .collect();
+        assert_eq!(captions, ["hill", "ghost"]);
+
+        let detached = detach_in(&content_dir, &media_dir, &note, &ghost).unwrap();
+        assert_eq!(detached.removed, 1);
+        assert!(!detached.media_deleted);
+    }
+
+    #[test]
+    fn test_detach_from_list_drops_image_items() {
+        let dir = tempfile::tempdir().unwrap();
+        let content_dir = dir.path().join("content");
+        let lists_dir = content_dir.join("lists");
+        let media_dir = content_dir.join("media");
+        let notes_dir = content_dir.join("notes");
+        fs::create_dir_all(&lists_dir).unwrap();
+        fs::create_dir_all(&notes_dir).unwrap();
+        fs::write(
+            lists_dir.join("packing.md"),
+            "---\ntitle: packing\n---\n\n- [ ] socks  ^abcde\n",
+        )
+        .unwrap();
+        let note = notes_dir.join("trip.md");
+        fs::write(&note, "").unwrap();
+        let photo = dir.path().join("bag.jpg");
+        fs::write(&photo, b"jpeg bytes").unwrap();
+        let storage = FsStorage::new(&content_dir);
+
+        let attachment = attach_to_list_in(
+            &storage, &lists_dir, &media_dir, "packing", &photo, "bag", true,
+        )
+        .unwrap();
+        attach_in(&media_dir, &note, &photo, "bag", true).unwrap();
+
+        // Still referenced from the note, so the file stays
+        let detached = detach_from_list_in(
+            &storage,
+            &content_dir,
+            &media_dir,
+            "packing",
+            &attachment.hash,
+        )
+        .unwrap();
+        assert_eq!(detached.removed, 1);
+        assert!(!detached.media_deleted);
+        let list = storage.read_list("packing").unwrap();
+        assert_eq!(list.uncategorized_items.len(), 1);
+        assert_eq!(list.uncategorized_items[0].text, "socks");
+
+        detach_in(&content_dir, &media_dir, &note, &attachment.hash).unwrap();
+        assert!(!attachment.path.exists());
+    }
+
+    #[test]
+    fn test_remove_references_keeps_surrounding_text() {
+        let hash = "a".repeat(64);
+        let content = format!(
+            "see ![pic](media/{h}.png) here\n[doc](../media/{h}.pdf)\n[site](https://example.com)\n",
+            h = hash
+        );
+        let (rest, removed) = remove_references(&content, &hash);
+        assert_eq!(removed, 2);
+        assert_eq!(rest, "see  here\n[site](https://example.com)\n");
+    }
+}
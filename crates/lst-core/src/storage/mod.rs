@@ -1,55 +1,148 @@
 use crate::config::get_config;
 use anyhow::{Context, Result};
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// Storage backend trait with filesystem and in-memory implementations
+pub mod backend;
+/// Distinguishable errors of list loading and item resolution
+pub mod error;
+/// Gzip tarball export and import of lists, notes and media
+pub mod export;
+/// Markdown normalizer behind `lst note fmt`
+pub mod format;
+/// `.gitignore`/`.lstignore` rules of the content directory
+pub mod gitignore;
+/// HTML (and, through an external renderer, PDF) rendering of notes
+pub mod html;
 pub mod markdown;
+/// Content-addressed media files and the markdown references to them
+pub mod media;
 /// Notes storage (creates and opens individual markdown files under notes/)
 pub mod notes;
+/// Progress manifest for resumable server pulls
+pub mod pull;
+/// Literal and regex search across list items and notes
+pub mod search;
+/// Soft-deleted lists and notes (kept under `.trash/` until restored)
+pub mod trash;
+/// Undo journal of list pre-images (stored under `.lst/undo/`)
+pub mod undo;
+
+pub use error::StorageError;
+pub use gitignore::ContentIgnore;
 
 /// Get the base content directory path
 /// Get the base content directory path, using the global cached configuration
 pub fn get_content_dir() -> Result<PathBuf> {
-    // First check the config (cached)
-    let config = get_config();
-
-    // If content_dir is specified in config, use that (supports absolute, relative, or '~' paths)
-    if let Some(dir) = config.paths.content_dir.clone() {
-        let dir_str = dir.to_string_lossy();
-        // Only expand leading '~' to home directory; otherwise use as given
-        let expanded: PathBuf = if dir_str.starts_with("~") {
-            // Tilde expansion
-            if let Some(home) = dirs::home_dir() {
-                // Remove '~' and any leading separator, then join to home
-                let without_tilde = dir_str
-                    .trim_start_matches('~')
-                    .trim_start_matches(std::path::MAIN_SEPARATOR);
-                home.join(without_tilde)
-            } else {
-                // Fallback to literal path
-                PathBuf::from(&*dir_str)
-            }
-        } else {
-            // Use the path as-is (absolute or relative)
-            dir
-        };
-        if !expanded.exists() {
-            fs::create_dir_all(&expanded).with_context(|| {
-                format!("Failed to create content directory: {}", expanded.display())
-            })?;
+    // Configured directory (supports absolute, relative, '~' and environment
+    // variable paths) or content/ in the current directory
+    let content_dir = get_config().content_dir()?;
+    if !content_dir.exists() {
+        fs::create_dir_all(&content_dir).with_context(|| {
+            format!(
+                "Failed to create content directory: {}",
+                content_dir.display()
+            )
+        })?;
+    }
+
+    Ok(content_dir)
+}
+
+/// Destination name for moving the document `source` to `to`: a bare name
+/// renames in place, anything with a separator is relative to the root
+pub fn move_target_name(source: &str, to: &str) -> String {
+    let target = to.trim_end_matches(".md");
+    if target.contains('/') || target.contains('\\') {
+        return target.to_string();
+    }
+    match Path::new(source).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => {
+            format!("{}/{}", parent.to_string_lossy(), target)
         }
-        return Ok(expanded);
+        _ => target.to_string(),
     }
+}
 
-    // Default to content/ in current directory
-    let current_dir = std::env::current_dir().context("Failed to get current directory")?;
+/// Move a document file from `from` to `to`, creating parent directories.
+/// Fails if the destination exists unless `force` is set.
+pub fn move_document_file(from: &Path, to: &Path, force: bool) -> Result<()> {
+    prepare_destination(from, to, force)?;
+    fs::rename(from, to)
+        .with_context(|| format!("Failed to move {} to {}", from.display(), to.display()))?;
+    Ok(())
+}
 
-    let content_dir = current_dir.join("content");
-    if !content_dir.exists() {
-        fs::create_dir_all(&content_dir).context("Failed to create content directory")?;
+/// Check that `from` exists and `to` is free (or `force` is set), and create
+/// the parent directories of `to`
+pub(crate) fn prepare_destination(from: &Path, to: &Path, force: bool) -> Result<()> {
+    if !from.exists() {
+        anyhow::bail!("Source does not exist: {}", from.display());
+    }
+    if from == to {
+        anyhow::bail!("Source and destination are the same: {}", from.display());
+    }
+    if to.exists() && !force {
+        anyhow::bail!(
+            "Destination already exists: {} (use --force to overwrite)",
+            to.display()
+        );
     }
 
-    Ok(content_dir)
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    Ok(())
+}
+
+/// Write `content` to `path` without ever leaving it half written: the data
+/// goes to a temporary file next to `path` (hidden and ending in `.tmp`, so
+/// neither listings nor lst-syncd pick it up), is flushed to disk, and is then
+/// renamed over `path`. Readers such as an editor or lst-syncd see either the
+/// old or the new contents.
+pub fn atomic_write(path: &Path, content: impl AsRef<[u8]>) -> Result<()> {
+    use std::io::Write;
+    atomic_write_with(path, |file| file.write_all(content.as_ref()))
+}
+
+fn atomic_write_with(
+    path: &Path,
+    write: impl FnOnce(&mut fs::File) -> std::io::Result<()>,
+) -> Result<()> {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    // Replace the file a symlink points to, not the link itself
+    let path = match fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_symlink() => fs::canonicalize(path)
+            .with_context(|| format!("Failed to resolve symlink: {}", path.display()))?,
+        _ => path.to_path_buf(),
+    };
+    let file_name = path
+        .file_name()
+        .with_context(|| format!("Not a file path: {}", path.display()))?;
+    let temp = path.with_file_name(format!(
+        ".{}.{}-{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let written = fs::File::create(&temp).and_then(|mut file| {
+        write(&mut file)?;
+        if let Ok(meta) = fs::metadata(&path) {
+            file.set_permissions(meta.permissions())?;
+        }
+        file.sync_all()
+    });
+    let result = written.and_then(|()| fs::rename(&temp, &path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result.with_context(|| format!("Failed to write {}", path.display()))
 }
 
 /// Get the lists directory path
@@ -62,6 +155,27 @@ pub fn get_lists_dir() -> Result<PathBuf> {
     Ok(lists_dir)
 }
 
+/// Get the list templates directory path (templates use the list file format)
+pub fn get_templates_dir() -> Result<PathBuf> {
+    let templates_dir = get_content_dir()?.join("templates");
+    if !templates_dir.exists() {
+        fs::create_dir_all(&templates_dir).context("Failed to create templates directory")?;
+    }
+
+    Ok(templates_dir)
+}
+
+/// Get the note templates directory path, kept apart from the list templates
+/// as those use the list file format
+pub fn get_note_templates_dir() -> Result<PathBuf> {
+    let templates_dir = get_content_dir()?.join("note_templates");
+    if !templates_dir.exists() {
+        fs::create_dir_all(&templates_dir).context("Failed to create note templates directory")?;
+    }
+
+    Ok(templates_dir)
+}
+
 /// Get the notes directory path
 pub fn get_notes_dir() -> Result<PathBuf> {
     let notes_dir = get_content_dir()?.join("notes");
@@ -72,28 +186,65 @@ pub fn get_notes_dir() -> Result<PathBuf> {
     Ok(notes_dir)
 }
 
-/// Recursively list all files in a directory tree with a specific extension
+/// Get the media directory path (`paths.media_dir`, resolved against the
+/// content directory when relative; defaults to `media/`)
+pub fn get_media_dir() -> Result<PathBuf> {
+    let content_dir = get_content_dir()?;
+    let media_dir = match get_config().paths.media_dir.as_deref() {
+        Some(dir) => content_dir.join(crate::config::expand_path(dir)),
+        None => content_dir.join("media"),
+    };
+    if !media_dir.exists() {
+        fs::create_dir_all(&media_dir).context("Failed to create media directory")?;
+    }
+
+    Ok(media_dir)
+}
+
+/// Recursively list all files in a directory tree with a specific extension,
+/// sorted by path. Files ignored by the content directory's ignore files are
+/// left out when `paths.respect_gitignore` is set
 pub fn list_files_recursive(dir: &Path, extension: &str) -> Result<Vec<PathBuf>> {
+    list_files_in(dir, extension, ContentIgnore::from_config()?.as_ref())
+}
+
+/// Recursively list all files below `dir` with a specific extension, sorted
+/// by path, skipping hidden directories and anything `ignore` matches
+pub fn list_files_in(
+    dir: &Path,
+    extension: &str,
+    ignore: Option<&ContentIgnore>,
+) -> Result<Vec<PathBuf>> {
     let mut files = Vec::new();
-    list_files_recursive_impl(dir, extension, &mut files)?;
+    list_files_recursive_impl(dir, extension, ignore, &mut files)?;
+    files.sort();
     Ok(files)
 }
 
-fn list_files_recursive_impl(dir: &Path, extension: &str, files: &mut Vec<PathBuf>) -> Result<()> {
+fn list_files_recursive_impl(
+    dir: &Path,
+    extension: &str,
+    ignore: Option<&ContentIgnore>,
+    files: &mut Vec<PathBuf>,
+) -> Result<()> {
     let entries = fs::read_dir(dir)
         .with_context(|| format!("Failed to read directory: {}", dir.display()))?;
 
     for entry in entries {
         let entry = entry?;
         let path = entry.path();
+        let is_dir = path.is_dir();
+        if ignore.is_some_and(|ignore| ignore.is_ignored(&path, is_dir)) {
+            continue;
+        }
 
         if path.is_file() && path.extension().map_or(false, |ext| ext == extension) {
             files.push(path);
-        } else if path.is_dir() {
+        } else if is_dir {
             // Skip hidden directories (starting with .)
             if let Some(dir_name) = path.file_name() {
                 if !dir_name.to_string_lossy().starts_with('.') {
-                    list_files_recursive_impl(&path, extension, files)?;
+                    list_files_recursive_impl(&path, extension, ignore, files)?;
                 }
             }
         }
@@ -112,14 +263,19 @@ pub struct FileEntry {
 
 /// List all available lists with directory structure support
 pub fn list_lists() -> Result<Vec<String>> {
-    let lists_dir = get_lists_dir()?;
-    let files = list_files_recursive(&lists_dir, "md")?;
+    list_lists_in(&get_lists_dir()?, ContentIgnore::from_config()?.as_ref())
+}
+
+/// Names of all lists below `lists_dir` (relative paths without `.md`),
+/// leaving out files matched by `ignore`
+pub fn list_lists_in(lists_dir: &Path, ignore: Option<&ContentIgnore>) -> Result<Vec<String>> {
+    let files = list_files_in(lists_dir, "md", ignore)?;
 
     let lists = files
         .iter()
         .filter_map(|path| {
             // Get relative path from lists directory
-            if let Ok(relative) = path.strip_prefix(&lists_dir) {
+            if let Ok(relative) = path.strip_prefix(lists_dir) {
                 // Remove .md extension and convert to string
                 let path_without_ext = relative.with_extension("");
                 return Some(path_without_ext.to_string_lossy().to_string());
@@ -131,16 +287,111 @@ pub fn list_lists() -> Result<Vec<String>> {
     Ok(lists)
 }
 
+/// List all lists carrying `tag` in their frontmatter
+pub fn list_lists_with_tag(tag: &str) -> Result<Vec<String>> {
+    list_lists_with_tag_in(&get_lists_dir()?, tag)
+}
+
+/// List all lists below `lists_dir` carrying `tag` in their frontmatter
+pub fn list_lists_with_tag_in(lists_dir: &Path, tag: &str) -> Result<Vec<String>> {
+    let tag = markdown::normalize_tag(tag);
+    let mut lists = Vec::new();
+    for path in list_files_recursive(lists_dir, "md")? {
+        let list = markdown::parse_list_from_file(&path)?;
+        if !list.metadata.tags.contains(&tag) {
+            continue;
+        }
+        if let Ok(relative) = path.strip_prefix(lists_dir) {
+            lists.push(relative.with_extension("").to_string_lossy().to_string());
+        }
+    }
+    lists.sort();
+    Ok(lists)
+}
+
+/// Order list names most recently updated first, going by the `updated`
+/// timestamp in each list's frontmatter or, for lists without one, the file's
+/// modification time
+pub fn sort_lists_by_updated(names: &mut [String]) -> Result<()> {
+    sort_lists_by_updated_in(&get_lists_dir()?, names)
+}
+
+/// [`sort_lists_by_updated`] for lists below `lists_dir`. Lists whose
+/// frontmatter fails to parse are sorted by modification time with a warning
+pub fn sort_lists_by_updated_in(lists_dir: &Path, names: &mut [String]) -> Result<()> {
+    let mut updated = std::collections::HashMap::new();
+    for name in names.iter() {
+        let path = lists_dir.join(format!("{}.md", name));
+        let frontmatter = list_updated_field(&path).unwrap_or_else(|e| {
+            eprintln!("Warning: {:#}", e);
+            None
+        });
+        let modified = || {
+            fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .map(chrono::DateTime::<chrono::Utc>::from)
+        };
+        updated.insert(name.clone(), frontmatter.or_else(modified));
+    }
+    names.sort_by(|a, b| updated[b].cmp(&updated[a]).then_with(|| a.cmp(b)));
+    Ok(())
+}
+
+/// The `updated` timestamp in the frontmatter of the list at `path`, if it
+/// has one
+fn list_updated_field(path: &Path) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+    #[derive(serde::Deserialize)]
+    struct Frontmatter {
+        updated: Option<chrono::DateTime<chrono::Utc>>,
+    }
+
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read list: {}", path.display()))?;
+    let parts: Vec<&str> = content.splitn(3, "---").collect();
+    if parts.len() < 3 {
+        return Ok(None);
+    }
+    let frontmatter: Frontmatter = serde_yaml::from_str(parts[1].trim())
+        .with_context(|| format!("Failed to parse list frontmatter in {}", path.display()))?;
+    Ok(frontmatter.updated)
+}
+
+/// Move pinned lists to the front of `names`, otherwise keeping their order.
+/// Returns the names of the pinned lists
+pub fn pinned_first(names: &mut [String]) -> Result<HashSet<String>> {
+    Ok(pinned_first_in(&get_lists_dir()?, names))
+}
+
+/// [`pinned_first`] for lists below `lists_dir`; lists that fail to parse
+/// count as unpinned
+pub fn pinned_first_in(lists_dir: &Path, names: &mut [String]) -> HashSet<String> {
+    let pinned: HashSet<String> = names
+        .iter()
+        .filter(|name| {
+            markdown::parse_list_from_file(&lists_dir.join(format!("{}.md", name)))
+                .is_ok_and(|list| list.metadata.pinned)
+        })
+        .cloned()
+        .collect();
+    names.sort_by_key(|name| !pinned.contains(name));
+    pinned
+}
+
 /// List all available lists with full file information
 pub fn list_lists_with_info() -> Result<Vec<FileEntry>> {
-    let lists_dir = get_lists_dir()?;
-    let files = list_files_recursive(&lists_dir, "md")?;
+    files_with_info_in(&get_lists_dir()?)
+}
 
-    let lists = files
+/// All markdown files below `dir` with their names and relative paths
+pub(crate) fn files_with_info_in(dir: &Path) -> Result<Vec<FileEntry>> {
+    let files = list_files_recursive(dir, "md")?;
+
+    let entries = files
         .iter()
         .filter_map(|path| {
-            // Get relative path from lists directory
-            if let Ok(relative) = path.strip_prefix(&lists_dir) {
+            // Get relative path from the base directory
+            if let Ok(relative) = path.strip_prefix(dir) {
                 // Get filename without extension
                 let name = relative.file_stem()?.to_string_lossy().to_string();
                 // Get relative path without extension
@@ -156,7 +407,7 @@ pub fn list_lists_with_info() -> Result<Vec<FileEntry>> {
         })
         .collect();
 
-    Ok(lists)
+    Ok(entries)
 }
 
 /// List all available notes with directory structure support
@@ -207,3 +458,207 @@ pub fn list_notes_with_info() -> Result<Vec<FileEntry>> {
 
     Ok(notes)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::backend::Storage;
+    use super::*;
+    use crate::models::List;
+
+    #[test]
+    fn test_list_lists_filtered_by_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, frontmatter: &str| {
+            let path = dir.path().join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(
+                path,
+                format!("---\n{}---\n\n- [ ] item  ^abcde\n", frontmatter),
+            )
+            .unwrap();
+        };
+        write("groceries.md", "title: groceries\ntags:\n- shopping\n");
+        write(
+            "stores/hardware.md",
+            "title: hardware\ntags: [shopping, diy]\n",
+        );
+        write("work.md", "title: work\ntags: [office]\n");
+        write("legacy.md", "title: legacy\n");
+
+        let lists = list_lists_with_tag_in(dir.path(), "#shopping").unwrap();
+        assert_eq!(lists, vec!["groceries", "stores/hardware"]);
+        assert!(list_lists_with_tag_in(dir.path(), "none")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_list_lists_leaves_out_ignored_files_when_enabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let content_dir = dir.path();
+        let lists_dir = content_dir.join("lists");
+        for name in [
+            "groceries.md",
+            "scratch.md",
+            "drafts/party.md",
+            ".hidden/x.md",
+        ] {
+            let path = lists_dir.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "- [ ] item\n").unwrap();
+        }
+        fs::write(content_dir.join(".gitignore"), "drafts/\n").unwrap();
+        fs::write(content_dir.join(".lstignore"), "lists/scratch.md\n").unwrap();
+
+        let all = list_lists_in(&lists_dir, None).unwrap();
+        assert_eq!(all, ["drafts/party", "groceries", "scratch"]);
+
+        let ignore = ContentIgnore::from_dir(content_dir).unwrap();
+        let kept = list_lists_in(&lists_dir, Some(&ignore)).unwrap();
+        assert_eq!(kept, ["groceries"]);
+    }
+
+    #[test]
+    fn test_sort_lists_by_updated_puts_recent_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, updated: &str| {
+            let path = dir.path().join(format!("{}.md", name));
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            let content = format!("---\ntitle: {}\nupdated: {}\n---\n", name, updated);
+            fs::write(path, content).unwrap();
+        };
+        write("groceries", "2024-03-01T10:00:00Z");
+        write("stores/hardware", "2024-05-01T10:00:00Z");
+        write("work", "2023-12-24T10:00:00Z");
+        // Lists without an `updated` timestamp go by their modification time,
+        // as do lists with broken frontmatter
+        let touch = |name: &str, content: &str, modified: &str| {
+            let path = dir.path().join(format!("{}.md", name));
+            fs::write(&path, content).unwrap();
+            let modified: chrono::DateTime<chrono::Utc> = modified.parse().unwrap();
+            fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(modified.into())
+                .unwrap();
+        };
+        touch("legacy", "- [ ] milk\n", "2024-04-01T10:00:00Z");
+        touch("broken", "---\ntitle: [\n---\n", "2024-01-01T10:00:00Z");
+
+        let mut lists = vec![
+            "broken".to_string(),
+            "groceries".to_string(),
+            "legacy".to_string(),
+            "stores/hardware".to_string(),
+            "work".to_string(),
+        ];
+        sort_lists_by_updated_in(dir.path(), &mut lists).unwrap();
+        assert_eq!(
+            lists,
+            ["stores/hardware", "legacy", "groceries", "broken", "work"]
+        );
+    }
+
+    #[test]
+    fn test_pinned_lists_come_first_until_unpinned() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = backend::FsStorage::new(dir.path());
+        for name in ["groceries", "packing", "work"] {
+            storage
+                .write_list(name, &List::new(name.to_string()), "create")
+                .unwrap();
+        }
+        let lists_dir = dir.path().join("lists");
+        let ordered = |names: &[&str]| {
+            let mut names: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+            pinned_first_in(&lists_dir, &mut names);
+            names
+        };
+
+        assert!(markdown::set_pinned_with(&storage, "work", true).unwrap());
+        assert!(markdown::set_pinned_with(&storage, "packing", true).unwrap());
+        assert!(!markdown::set_pinned_with(&storage, "work", true).unwrap());
+        assert_eq!(
+            ordered(&["groceries", "packing", "work"]),
+            ["packing", "work", "groceries"]
+        );
+
+        markdown::set_pinned_with(&storage, "work", false).unwrap();
+        markdown::set_pinned_with(&storage, "packing", false).unwrap();
+        assert_eq!(
+            ordered(&["groceries", "packing", "work"]),
+            ["groceries", "packing", "work"]
+        );
+        let content = fs::read_to_string(lists_dir.join("work.md")).unwrap();
+        assert!(!content.contains("pinned"));
+    }
+    #[test]
+    fn test_move_document_file_within_and_across_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = dir.path().join("groceries.md");
+        fs::write(&original, "- [ ] milk\n").unwrap();
+
+        let renamed = dir.path().join("food.md");
+        move_document_file(&original, &renamed, false).unwrap();
+        assert!(!original.exists());
+        assert_eq!(fs::read_to_string(&renamed).unwrap(), "- [ ] milk\n");
+
+        let nested = dir.path().join("groceries").join("pharmacy.md");
+        move_document_file(&renamed, &nested, false).unwrap();
+        assert!(!renamed.exists());
+        assert!(nested.exists());
+    }
+
+    #[test]
+    fn test_move_document_file_refuses_to_overwrite_without_force() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = dir.path().join("a.md");
+        let to = dir.path().join("b.md");
+        fs::write(&from, "a").unwrap();
+        fs::write(&to, "b").unwrap();
+
+        assert!(move_document_file(&from, &to, false).is_err());
+        assert_eq!(fs::read_to_string(&to).unwrap(), "b");
+
+        move_document_file(&from, &to, true).unwrap();
+        assert_eq!(fs::read_to_string(&to).unwrap(), "a");
+    }
+
+    #[test]
+    fn test_failed_atomic_write_leaves_target_intact() {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("groceries.md");
+        fs::write(&path, "- [ ] milk\n- [ ] eggs\n").unwrap();
+
+        // The process "crashes" halfway through writing the new contents
+        let err = atomic_write_with(&path, |file| {
+            file.write_all(b"- [x] mi")?;
+            Err(std::io::Error::other("simulated crash"))
+        })
+        .unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("simulated crash"),
+            "{:#}",
+            err
+        );
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "- [ ] milk\n- [ ] eggs\n"
+        );
+
+        // A temp file left behind by a real crash is invisible to listings
+        // and does not get in the way of the next write
+        fs::write(dir.path().join(".groceries.md.1-0.tmp"), "- [x] mi").unwrap();
+        atomic_write(&path, "- [x] milk\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "- [x] milk\n");
+        let names: Vec<String> = files_with_info_in(dir.path())
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.relative_path)
+            .collect();
+        assert_eq!(names, ["groceries"]);
+    }
+}
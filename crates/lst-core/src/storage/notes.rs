@@ -1,7 +1,44 @@
 use anyhow::{anyhow, Context, Result};
-use chrono::Utc;
+use chrono::{DateTime, Local, Utc};
+use regex::Regex;
+use serde::Serialize;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+lazy_static::lazy_static! {
+    static ref WIKILINK_RE: Regex = Regex::new(r"\[\[([^\[\]\n]+)\]\]").unwrap();
+}
+
+/// A `[[wikilink]]` from one note to another
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NoteLink {
+    /// Link target as written, without `#heading` or `|alias`
+    pub target: String,
+    /// Note the target resolves to, relative to the notes directory; `None`
+    /// for a broken link
+    pub note: Option<String>,
+}
+
+/// Outcome of renaming a note after its title
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TitleRename {
+    /// Previous name, relative to the notes directory
+    pub from: String,
+    /// New name, relative to the notes directory; equal to `from` when the
+    /// filename already matched the title
+    pub to: String,
+    /// Notes whose `[[wikilinks]]` to the old name were rewritten, sorted
+    pub relinked: Vec<String>,
+}
+
+/// Links of a note in both directions
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NoteLinks {
+    /// Links in the note's body, in document order
+    pub outgoing: Vec<NoteLink>,
+    /// Notes linking to this one, sorted
+    pub backlinks: Vec<String>,
+}
 
 /// Simple slugify: lowercase, replace non-alphanumeric with '-', trim hyphens
 fn slugify(title: &str) -> String {
@@ -44,12 +81,18 @@ pub fn get_note_path(title: &str) -> Result<PathBuf> {
 /// Resolve a note by title using fuzzy search (filename only)
 pub fn resolve_note_path(title: &str) -> Result<PathBuf> {
     let entries = super::list_notes_with_info()?;
+    Ok(match_note(&entries, title)?.full_path.clone())
+}
 
-    // First try exact filename match
-    for entry in &entries {
-        if entry.name == title {
-            return Ok(entry.full_path.clone());
-        }
+/// Find the note `title` refers to: an exact path or filename wins, then a
+/// single filename containing `title`
+fn match_note<'a>(entries: &'a [super::FileEntry], title: &str) -> Result<&'a super::FileEntry> {
+    // First try exact path or filename match
+    if let Some(entry) = entries
+        .iter()
+        .find(|entry| entry.relative_path == title || entry.name == title)
+    {
+        return Ok(entry);
     }
 
     // Then try fuzzy match by filename
@@ -60,7 +103,7 @@ pub fn resolve_note_path(title: &str) -> Result<PathBuf> {
 
     match matches.len() {
         0 => anyhow::bail!("Note '{}' does not exist", title),
-        1 => Ok(matches[0].full_path.clone()),
+        1 => Ok(matches[0]),
         _ => {
             let match_names: Vec<String> =
                 matches.iter().map(|e| e.relative_path.clone()).collect();
@@ -69,7 +112,8 @@ pub fn resolve_note_path(title: &str) -> Result<PathBuf> {
     }
 }
 
-/// Delete a note with the given title (`slug.md`).
+/// Delete a note with the given title (`slug.md`), moving it to the trash
+/// unless `ui.use_trash` is off.
 pub fn delete_note(title: &str) -> Result<()> {
     let path = get_note_path(title).context("building note path failed")?;
 
@@ -78,19 +122,229 @@ pub fn delete_note(title: &str) -> Result<()> {
         anyhow::bail!("note `{}` does not exist", title);
     }
 
-    fs::remove_file(&path).with_context(|| format!("could not delete {}", path.display()))?;
+    super::trash::discard(&path)?;
 
     Ok(())
 }
 
+/// Move or rename a note, keeping its content and frontmatter as they are
+///
+/// A bare `to` renames the note within its directory, a `to` with directories
+/// (e.g. `journal/2024/entry`) is relative to the notes root. Returns the old
+/// and new paths.
+pub fn move_note(from: &str, to: &str, force: bool) -> Result<(PathBuf, PathBuf)> {
+    move_note_in(&super::get_notes_dir()?, from, to, force)
+}
+
+/// Move a note below `notes_dir`, see [`move_note`]
+pub fn move_note_in(
+    notes_dir: &Path,
+    from: &str,
+    to: &str,
+    force: bool,
+) -> Result<(PathBuf, PathBuf)> {
+    let source = from.trim_end_matches(".md");
+    let target = super::move_target_name(source, to);
+    let from_path = notes_dir.join(format!("{}.md", source));
+    let to_path = notes_dir.join(format!("{}.md", target));
+    if from_path == to_path {
+        anyhow::bail!(
+            "Source and destination are the same: {}",
+            from_path.display()
+        );
+    }
+    super::move_document_file(&from_path, &to_path, force)?;
+    Ok((from_path, to_path))
+}
+
+/// Copy the note `from` below `notes_dir` to `to` (a bare name copies within
+/// its directory, see [`super::move_target_name`]), setting the `title` in the
+/// copy's frontmatter to its new name. Returns the source and copy paths
+pub fn copy_note_in(
+    notes_dir: &Path,
+    from: &str,
+    to: &str,
+    force: bool,
+) -> Result<(PathBuf, PathBuf)> {
+    let source = from.trim_end_matches(".md");
+    let target = super::move_target_name(source, to);
+    let from_path = notes_dir.join(format!("{}.md", source));
+    let to_path = notes_dir.join(format!("{}.md", target));
+    super::prepare_destination(&from_path, &to_path, force)?;
+
+    let content = fs::read_to_string(&from_path)
+        .with_context(|| format!("Failed to read note: {}", from_path.display()))?;
+    let title = note_title(&target);
+    let content = rewrite_frontmatter(&content, |mapping| {
+        mapping.insert("title".into(), title.into());
+        true
+    })?
+    .unwrap_or(content);
+    super::atomic_write(&to_path, content)?;
+    Ok((from_path, to_path))
+}
+
 /// Create a new note file with frontmatter and return its path
-pub fn create_note(title: &str) -> Result<PathBuf> {
-    let notes_dir = super::get_notes_dir()?;
-    let filename = format!("{}.md", title);
-    let path = notes_dir.join(&filename);
+///
+/// If a note with this title already exists and `overwrite` is false, the note
+/// is created as `title-2`, `title-3`, etc. instead; the returned path is the one
+/// actually used.
+pub fn create_note(title: &str, overwrite: bool) -> Result<PathBuf> {
+    create_note_in(&super::get_notes_dir()?, title, overwrite)
+}
+
+/// Create a new note below `notes_dir`, see [`create_note`]
+pub fn create_note_in(notes_dir: &Path, title: &str, overwrite: bool) -> Result<PathBuf> {
+    let path = new_note_path(notes_dir, title, overwrite)?;
+
+    // Build frontmatter
+    let now = Utc::now().to_rfc3339();
+    let note_title = note_title(title);
+    let content = format!("---\ntitle: \"{}\"\ncreated: {}\n---\n\n", note_title, now);
+    super::atomic_write(&path, content)
+        .with_context(|| format!("Failed to create note file: {}", path.display()))?;
+    Ok(path)
+}
+
+/// Create a new note from the template `note_templates/<template>.md`, see
+/// [`create_note`] and [`render_template`]
+pub fn create_note_from_template(title: &str, template: &str, overwrite: bool) -> Result<PathBuf> {
+    create_note_from_template_in(
+        &super::get_notes_dir()?,
+        &super::get_note_templates_dir()?,
+        title,
+        template,
+        overwrite,
+    )
+}
+
+/// Create a new note below `notes_dir` from a template in `templates_dir`,
+/// see [`create_note_from_template`]
+pub fn create_note_from_template_in(
+    notes_dir: &Path,
+    templates_dir: &Path,
+    title: &str,
+    template: &str,
+    overwrite: bool,
+) -> Result<PathBuf> {
+    let template_name = template.trim_end_matches(".md");
+    if template_name.is_empty()
+        || template_name.contains("..")
+        || template_name.contains(['/', '\\'])
+    {
+        anyhow::bail!(
+            "Invalid template name '{}': it must be a file name in {}",
+            template,
+            templates_dir.display()
+        );
+    }
+    let template_path = templates_dir.join(format!("{}.md", template_name));
+    if !template_path.exists() {
+        anyhow::bail!(
+            "Template '{}' does not exist in {} (see 'lst note template ls')",
+            template_name,
+            templates_dir.display()
+        );
+    }
+    let template = fs::read_to_string(&template_path)
+        .with_context(|| format!("Failed to read template: {}", template_path.display()))?;
+    let content = render_template(&template, &note_title(title), Local::now())
+        .with_context(|| format!("Invalid template: {}", template_path.display()))?;
+
+    let path = new_note_path(notes_dir, title, overwrite)?;
+    super::atomic_write(&path, content)
+        .with_context(|| format!("Failed to create note file: {}", path.display()))?;
+    Ok(path)
+}
+
+/// Fill in a note template: `{{title}}`, `{{date}}` (YYYY-MM-DD) and
+/// `{{time}}` (HH:MM) are substituted in the body and frontmatter values, and
+/// the frontmatter gets the usual `title` (unless the template sets one) and
+/// `created` fields
+pub fn render_template(template: &str, title: &str, now: DateTime<Local>) -> Result<String> {
+    let date = now.format("%Y-%m-%d").to_string();
+    let time = now.format("%H:%M").to_string();
+    let fill = |text: &str| {
+        text.replace("{{title}}", title)
+            .replace("{{date}}", &date)
+            .replace("{{time}}", &time)
+    };
+
+    validate_frontmatter(template)?;
+    let (template_fields, body) = match split_frontmatter(template) {
+        Some((mapping, body)) => (mapping, fill(body)),
+        None => (
+            serde_yaml::Mapping::new(),
+            format!("\n\n{}", fill(template)),
+        ),
+    };
+
+    let mut mapping = serde_yaml::Mapping::new();
+    mapping.insert("title".into(), title.into());
+    mapping.insert(
+        "created".into(),
+        now.with_timezone(&Utc).to_rfc3339().into(),
+    );
+    for (key, mut value) in template_fields {
+        if key.as_str() == Some("created") {
+            continue;
+        }
+        fill_yaml_strings(&mut value, &fill);
+        mapping.insert(key, value);
+    }
+
+    let yaml = serde_yaml::to_string(&mapping).context("Failed to serialize frontmatter")?;
+    Ok(format!("---\n{}---{}", yaml, body))
+}
+
+/// Apply `fill` to every string inside a frontmatter value
+fn fill_yaml_strings(value: &mut serde_yaml::Value, fill: &impl Fn(&str) -> String) {
+    match value {
+        serde_yaml::Value::String(text) => *text = fill(text),
+        serde_yaml::Value::Sequence(items) => {
+            for item in items {
+                fill_yaml_strings(item, fill);
+            }
+        }
+        serde_yaml::Value::Mapping(mapping) => {
+            for (_, item) in mapping.iter_mut() {
+                fill_yaml_strings(item, fill);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Names of the templates in `templates_dir`, sorted. Files in
+/// subdirectories are not templates, as template names are plain file names
+pub fn list_templates_in(templates_dir: &Path) -> Result<Vec<String>> {
+    if !templates_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut names: Vec<String> = super::files_with_info_in(templates_dir)?
+        .into_iter()
+        .map(|entry| entry.relative_path)
+        .filter(|name| !name.contains('/'))
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// Names of the templates in the note templates directory
+pub fn list_templates() -> Result<Vec<String>> {
+    list_templates_in(&super::get_note_templates_dir()?)
+}
 
-    if path.exists() {
-        return Err(anyhow!("Note '{}' already exists", title));
+/// Path for a new note called `title` below `notes_dir`, with a numeric suffix
+/// if it is taken and `overwrite` is false; parent directories are created
+fn new_note_path(notes_dir: &Path, title: &str, overwrite: bool) -> Result<PathBuf> {
+    let mut path = notes_dir.join(format!("{}.md", title));
+    if !overwrite {
+        let mut n = 2;
+        while path.exists() {
+            path = notes_dir.join(format!("{}-{}.md", title, n));
+            n += 1;
+        }
     }
 
     // Create parent directories if they don't exist
@@ -100,9 +354,12 @@ pub fn create_note(title: &str) -> Result<PathBuf> {
                 .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
         }
     }
+    Ok(path)
+}
 
-    // Extract just the filename for the note title (not the full path)
-    let note_title = if title.contains('/') || title.contains('\\') {
+/// The note title for `title`: just the file name, not the directories
+fn note_title(title: &str) -> String {
+    if title.contains('/') || title.contains('\\') {
         std::path::Path::new(title)
             .file_name()
             .and_then(|f| f.to_str())
@@ -110,14 +367,7 @@ pub fn create_note(title: &str) -> Result<PathBuf> {
             .to_string()
     } else {
         title.to_string()
-    };
-
-    // Build frontmatter
-    let now = Utc::now().to_rfc3339();
-    let content = format!("---\ntitle: \"{}\"\ncreated: {}\n---\n\n", note_title, now);
-    fs::write(&path, content)
-        .with_context(|| format!("Failed to create note file: {}", path.display()))?;
-    Ok(path)
+    }
 }
 
 /// Ensure note exists and return its path
@@ -136,23 +386,654 @@ pub fn load_note(title: &str) -> Result<PathBuf> {
     // Use fuzzy resolution for simple names
     resolve_note_path(title)
 }
-/// Append text to a note (with a newline between old and new text).
-/// Creates the note if it does not exist.
-pub fn append_to_note(title: &str, text: &str) -> Result<PathBuf> {
+/// Append text to a note (with a blank line between old and new text), under
+/// a `## heading` if given. Creates the note if it does not exist.
+pub fn append_to_note(title: &str, text: &str, heading: Option<&str>) -> Result<PathBuf> {
     let path = get_note_path(title)?;
     if !path.exists() {
         // Create a new note with frontmatter
-        create_note(title)?;
-    }
-    // Append text with preceding blank line
-    let mut file = std::fs::OpenOptions::new()
-        .write(true)
-        .append(true)
-        .open(&path)
-        .with_context(|| format!("Failed to open note file for append: {}", path.display()))?;
-    // Write a blank line, the text, and a newline
-    use std::io::Write;
-    writeln!(file, "\n{}", text)
+        create_note(title, false)?;
+    }
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read note file: {}", path.display()))?;
+    super::atomic_write(&path, append_block(&content, text, heading))
         .with_context(|| format!("Failed to write to note file: {}", path.display()))?;
     Ok(path)
 }
+
+/// `content` followed by a blank line, `## heading` (if given) and `text`,
+/// ending in a single newline. Frontmatter and earlier text stay as they are
+pub fn append_block(content: &str, text: &str, heading: Option<&str>) -> String {
+    let mut appended = content.trim_end_matches('\n').to_string();
+    if !appended.is_empty() {
+        appended.push_str("\n\n");
+    }
+    if let Some(heading) = heading {
+        appended.push_str(&format!("## {}\n\n", heading.trim()));
+    }
+    appended.push_str(text.trim_end_matches('\n'));
+    appended.push('\n');
+    appended
+}
+
+/// Return the path of the rolling scratch note, honouring `paths.scratch_note`
+pub fn get_scratch_note_path() -> Result<PathBuf> {
+    let notes_dir = super::get_notes_dir()?;
+    let configured = crate::config::get_config().paths.scratch_note.clone();
+
+    let path = match configured {
+        Some(path) => notes_dir.join(crate::config::expand_path(&path)),
+        None => notes_dir.join("scratch.md"),
+    };
+    Ok(path)
+}
+
+/// Append a timestamped section to the scratch note at `path`, creating the
+/// file (with frontmatter) if it does not exist yet
+pub fn append_scratch_section(path: &Path) -> Result<()> {
+    if !path.exists() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+        let content = format!(
+            "---\ntitle: \"scratch\"\ncreated: {}\n---\n",
+            Utc::now().to_rfc3339()
+        );
+        super::atomic_write(path, content)
+            .with_context(|| format!("Failed to create scratch note: {}", path.display()))?;
+    }
+
+    let mut content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read scratch note: {}", path.display()))?;
+    content.push_str(&format!(
+        "\n## {}\n\n",
+        Local::now().format("%Y-%m-%d %H:%M:%S")
+    ));
+    super::atomic_write(path, content)
+        .with_context(|| format!("Failed to write to scratch note: {}", path.display()))?;
+    Ok(())
+}
+
+/// Split a note into its frontmatter mapping and body, or `None` when it has
+/// no (parseable) YAML frontmatter
+fn split_frontmatter(content: &str) -> Option<(serde_yaml::Mapping, &str)> {
+    let rest = content.strip_prefix("---")?;
+    let end = rest.find("\n---")?;
+    let mapping = serde_yaml::from_str(&rest[..end]).ok()?;
+    Some((mapping, &rest[end + 4..]))
+}
+
+/// A note's markdown without its frontmatter block, if it has one
+pub fn note_body(content: &str) -> &str {
+    content
+        .strip_prefix("---")
+        .and_then(|rest| rest.find("\n---").map(|end| &rest[end + 4..]))
+        .unwrap_or(content)
+}
+
+/// Check that a note's frontmatter, if it has any, is closed and valid YAML
+pub fn validate_frontmatter(content: &str) -> Result<()> {
+    let Some(rest) = content.strip_prefix("---") else {
+        return Ok(());
+    };
+    let end = rest
+        .find("\n---")
+        .context("Frontmatter is not closed by '---'")?;
+    if !rest[..end].trim().is_empty() {
+        serde_yaml::from_str::<serde_yaml::Mapping>(&rest[..end])
+            .context("Invalid YAML frontmatter")?;
+    }
+    Ok(())
+}
+
+/// Apply `edit` to a note's frontmatter, keeping fields it doesn't touch
+/// (including ones lst doesn't know about) and the body unchanged. Returns the
+/// rewritten note, or `None` when `edit` reports no change or there is no
+/// frontmatter
+pub fn rewrite_frontmatter(
+    content: &str,
+    edit: impl FnOnce(&mut serde_yaml::Mapping) -> bool,
+) -> Result<Option<String>> {
+    let Some((mut mapping, body)) = split_frontmatter(content) else {
+        return Ok(None);
+    };
+    if !edit(&mut mapping) {
+        return Ok(None);
+    }
+    let yaml = serde_yaml::to_string(&mapping).context("Failed to serialize frontmatter")?;
+    Ok(Some(format!("---\n{}---{}", yaml, body)))
+}
+
+/// Replace the tag `old` with `new` in a note's `tags`, dropping duplicates if
+/// the note already had `new`. Returns the rewritten note when it changed
+pub fn rename_tag_in_note(content: &str, old: &str, new: &str) -> Result<Option<String>> {
+    rewrite_frontmatter(content, |mapping| {
+        let Some(serde_yaml::Value::Sequence(tags)) = mapping.get_mut("tags") else {
+            return false;
+        };
+        if !tags.iter().any(|tag| tag.as_str() == Some(old)) {
+            return false;
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        let renamed: Vec<serde_yaml::Value> = tags
+            .drain(..)
+            .map(|tag| match tag.as_str() {
+                Some(t) if t == old => serde_yaml::Value::String(new.to_string()),
+                _ => tag,
+            })
+            .filter(|tag| seen.insert(tag.clone()))
+            .collect();
+        *tags = renamed;
+        true
+    })
+}
+
+/// Rename the tag `old` to `new` in every note under `notes_dir`. Returns the
+/// names of the notes that carry the tag; with `dry_run` nothing is written
+pub fn rename_tag_in(notes_dir: &Path, old: &str, new: &str, dry_run: bool) -> Result<Vec<String>> {
+    if !notes_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut changed = Vec::new();
+    for entry in super::files_with_info_in(notes_dir)? {
+        let content = fs::read_to_string(&entry.full_path)
+            .with_context(|| format!("Failed to read note: {}", entry.full_path.display()))?;
+        let Some(updated) = rename_tag_in_note(&content, old, new)? else {
+            continue;
+        };
+        if !dry_run {
+            super::atomic_write(&entry.full_path, updated)
+                .with_context(|| format!("Failed to write note: {}", entry.full_path.display()))?;
+        }
+        changed.push(entry.relative_path);
+    }
+    changed.sort();
+    Ok(changed)
+}
+
+/// Rename a tag across all notes in the configured notes directory
+pub fn rename_tag(old: &str, new: &str, dry_run: bool) -> Result<Vec<String>> {
+    rename_tag_in(&super::get_notes_dir()?, old, new, dry_run)
+}
+
+/// Targets of the `[[wikilinks]]` in a note's body, in order of first
+/// appearance. `[[target#heading]]` and `[[target|alias]]` yield `target`
+pub fn extract_links(content: &str) -> Vec<String> {
+    let body = split_frontmatter(content).map_or(content, |(_, body)| body);
+    let mut targets: Vec<String> = Vec::new();
+    for captures in WIKILINK_RE.captures_iter(body) {
+        let target = captures[1].split(['|', '#']).next().unwrap_or("").trim();
+        if !target.is_empty() && !targets.iter().any(|t| t == target) {
+            targets.push(target.to_string());
+        }
+    }
+    targets
+}
+
+/// The note a link target refers to, like [`resolve_note_path`] but also
+/// trying the slug of the target (`[[Project Plan]]` finds `project-plan`).
+/// `None` when nothing or several notes match
+fn resolve_link<'a>(entries: &'a [super::FileEntry], target: &str) -> Option<&'a super::FileEntry> {
+    let target = target.trim_end_matches(".md");
+    match_note(entries, target)
+        .or_else(|_| match_note(entries, &slugify(target)))
+        .ok()
+}
+
+/// Outgoing links and backlinks of the note at `note` below `notes_dir`
+pub fn note_links_in(notes_dir: &Path, note: &Path) -> Result<NoteLinks> {
+    let entries = super::files_with_info_in(notes_dir)?;
+    let content = fs::read_to_string(note)
+        .with_context(|| format!("Failed to read note: {}", note.display()))?;
+
+    let outgoing = extract_links(&content)
+        .into_iter()
+        .map(|target| NoteLink {
+            note: resolve_link(&entries, &target).map(|entry| entry.relative_path.clone()),
+            target,
+        })
+        .collect();
+
+    let mut backlinks = Vec::new();
+    for entry in entries.iter().filter(|entry| entry.full_path != note) {
+        let content = fs::read_to_string(&entry.full_path)
+            .with_context(|| format!("Failed to read note: {}", entry.full_path.display()))?;
+        let links_here = extract_links(&content).iter().any(|target| {
+            resolve_link(&entries, target).is_some_and(|linked| linked.full_path == note)
+        });
+        if links_here {
+            backlinks.push(entry.relative_path.clone());
+        }
+    }
+    backlinks.sort();
+
+    Ok(NoteLinks {
+        outgoing,
+        backlinks,
+    })
+}
+
+/// Outgoing links and backlinks of a note in the configured notes directory
+pub fn note_links(note: &Path) -> Result<NoteLinks> {
+    note_links_in(&super::get_notes_dir()?, note)
+}
+
+/// Rewrite the targets of the `[[wikilinks]]` in a note's body, keeping any
+/// `#heading` or `|alias`. `replace` returns the new target, or `None` to
+/// leave a link alone. Returns the rewritten note when a link changed
+fn rewrite_links(content: &str, mut replace: impl FnMut(&str) -> Option<String>) -> Option<String> {
+    let body = split_frontmatter(content).map_or(content, |(_, body)| body);
+    let (head, body) = content.split_at(content.len() - body.len());
+    let mut changed = false;
+    let body = WIKILINK_RE.replace_all(body, |captures: &regex::Captures| {
+        let inner = &captures[1];
+        let end = inner.find(['|', '#']).unwrap_or(inner.len());
+        let target = inner[..end].trim();
+        match (!target.is_empty()).then(|| replace(target)).flatten() {
+            Some(new) => {
+                changed = true;
+                format!("[[{}{}]]", new, &inner[end..])
+            }
+            None => captures[0].to_string(),
+        }
+    });
+    changed.then(|| format!("{}{}", head, body))
+}
+
+/// A note's title: the `title` in its frontmatter, else its first H1
+pub(super) fn title_of(content: &str) -> Option<String> {
+    let (fields, body) = match split_frontmatter(content) {
+        Some((fields, body)) => (Some(fields), body),
+        None => (None, content),
+    };
+    let title = fields
+        .as_ref()
+        .and_then(|fields| fields.get("title"))
+        .and_then(|title| title.as_str())
+        .map(str::to_string);
+    title
+        .or_else(|| {
+            body.lines()
+                .find_map(|line| line.strip_prefix("# ").map(|h| h.trim().to_string()))
+        })
+        .filter(|title| !title.is_empty())
+}
+
+/// Rename the note `name` below `notes_dir` to the slug of its title, keeping
+/// it in its directory, and point the `[[wikilinks]]` that resolved to it at
+/// the new name. With `dry_run` nothing is moved or written
+pub fn rename_to_title_in(notes_dir: &Path, name: &str, dry_run: bool) -> Result<TitleRename> {
+    let name = name.trim_end_matches(".md");
+    let path = notes_dir.join(format!("{}.md", name));
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read note: {}", path.display()))?;
+    let title = title_of(&content)
+        .with_context(|| format!("Note '{}' has no frontmatter title or H1 heading", name))?;
+    let slug = slugify(&title);
+    if slug.is_empty() {
+        anyhow::bail!("Title '{}' has nothing usable as a filename", title);
+    }
+
+    let target = super::move_target_name(name, &slug);
+    let mut rename = TitleRename {
+        from: name.to_string(),
+        to: target.clone(),
+        relinked: Vec::new(),
+    };
+    if target == name {
+        return Ok(rename);
+    }
+    let to_path = notes_dir.join(format!("{}.md", target));
+    if to_path.exists() {
+        anyhow::bail!("A note named '{}' already exists", target);
+    }
+
+    // Links are resolved while the old name still exists. A bare new name is
+    // used unless the link had a path or the name is taken elsewhere
+    let entries = super::files_with_info_in(notes_dir)?;
+    let slug_taken = entries.iter().any(|entry| entry.name == slug);
+    let mut rewritten = Vec::new();
+    for entry in &entries {
+        let content = fs::read_to_string(&entry.full_path)
+            .with_context(|| format!("Failed to read note: {}", entry.full_path.display()))?;
+        let updated = rewrite_links(&content, |link| {
+            let linked = resolve_link(&entries, link)?;
+            (linked.full_path == path).then(|| {
+                if link.contains('/') || slug_taken {
+                    target.clone()
+                } else {
+                    slug.clone()
+                }
+            })
+        });
+        if let Some(updated) = updated {
+            rename.relinked.push(entry.relative_path.clone());
+            rewritten.push((entry.full_path.clone(), updated));
+        }
+    }
+    rename.relinked.sort();
+    if dry_run {
+        return Ok(rename);
+    }
+
+    move_note_in(notes_dir, name, &slug, false)?;
+    for (file, updated) in rewritten {
+        // A note linking to itself has just moved
+        let file = if file == path { to_path.clone() } else { file };
+        super::atomic_write(&file, updated)
+            .with_context(|| format!("Failed to write note: {}", file.display()))?;
+    }
+    Ok(rename)
+}
+
+/// Rename a note in the configured notes directory after its title, see
+/// [`rename_to_title_in`]
+pub fn rename_to_title(name: &str, dry_run: bool) -> Result<TitleRename> {
+    rename_to_title_in(&super::get_notes_dir()?, name, dry_run)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_create_note_twice_yields_distinct_files() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let first = create_note_in(dir.path(), "ideas", false).unwrap();
+        let second = create_note_in(dir.path(), "ideas", false).unwrap();
+        assert_eq!(first, dir.path().join("ideas.md"));
+        assert_eq!(second, dir.path().join("ideas-2.md"));
+        assert!(first.exists() && second.exists());
+
+        let third = create_note_in(dir.path(), "ideas", false).unwrap();
+        assert_eq!(third, dir.path().join("ideas-3.md"));
+
+        fs::write(&first, "edited").unwrap();
+        let overwritten = create_note_in(dir.path(), "ideas", true).unwrap();
+        assert_eq!(overwritten, first);
+        assert!(fs::read_to_string(&first)
+            .unwrap()
+            .contains("title: \"ideas\""));
+    }
+
+    #[test]
+    fn test_move_note_renames_in_same_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = create_note_in(dir.path(), "work/ideas", false).unwrap();
+        let content = fs::read_to_string(&original).unwrap();
+
+        let (from, to) = move_note_in(dir.path(), "work/ideas", "plans", false).unwrap();
+        assert_eq!(from, original);
+        assert_eq!(to, dir.path().join("work/plans.md"));
+        assert!(!from.exists());
+        assert_eq!(fs::read_to_string(&to).unwrap(), content);
+    }
+
+    #[test]
+    fn test_copy_note_updates_title_and_leaves_original_untouched() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = dir.path().join("recipes/pasta.md");
+        fs::create_dir_all(original.parent().unwrap()).unwrap();
+        let content = "---\ntitle: pasta\ntags: [food]\n---\n\n# Pasta\n";
+        fs::write(&original, content).unwrap();
+
+        let (from, to) =
+            copy_note_in(dir.path(), "recipes/pasta", "drafts/risotto", false).unwrap();
+        assert_eq!(from, original);
+        assert_eq!(to, dir.path().join("drafts/risotto.md"));
+        assert_eq!(fs::read_to_string(&original).unwrap(), content);
+        let copy = fs::read_to_string(&to).unwrap();
+        assert_eq!(title_of(&copy).as_deref(), Some("risotto"));
+        assert!(copy.contains("- food"), "{}", copy);
+        assert!(copy.ends_with("\n# Pasta\n"), "{}", copy);
+
+        // A bare name stays in the source's directory; existing files need force
+        let (_, sibling) = copy_note_in(dir.path(), "recipes/pasta", "pizza", false).unwrap();
+        assert_eq!(sibling, dir.path().join("recipes/pizza.md"));
+        let err = copy_note_in(dir.path(), "recipes/pasta", "pizza", false).unwrap_err();
+        assert!(err.to_string().contains("--force"));
+        copy_note_in(dir.path(), "recipes/pasta", "pizza", true).unwrap();
+        assert!(copy_note_in(dir.path(), "recipes/pasta", "pasta", true).is_err());
+
+        // Notes without frontmatter are copied as they are
+        fs::write(dir.path().join("plain.md"), "just text\n").unwrap();
+        let (_, plain) = copy_note_in(dir.path(), "plain", "plain-2", false).unwrap();
+        assert_eq!(fs::read_to_string(plain).unwrap(), "just text\n");
+    }
+
+    #[test]
+    fn test_move_note_into_nested_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        create_note_in(dir.path(), "entry", false).unwrap();
+        create_note_in(dir.path(), "other", false).unwrap();
+
+        let (_, to) = move_note_in(dir.path(), "entry", "journal/2024/entry", false).unwrap();
+        assert_eq!(to, dir.path().join("journal/2024/entry.md"));
+        assert!(fs::read_to_string(&to)
+            .unwrap()
+            .starts_with("---\ntitle: \"entry\""));
+
+        let err = move_note_in(dir.path(), "other", "journal/2024/entry", false).unwrap_err();
+        assert!(err.to_string().contains("--force"));
+        assert!(dir.path().join("other.md").exists());
+        move_note_in(dir.path(), "other", "journal/2024/entry", true).unwrap();
+        assert!(!dir.path().join("other.md").exists());
+    }
+
+    #[test]
+    fn test_scratch_sections_accumulate() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scratch.md");
+
+        append_scratch_section(&path).unwrap();
+        append_scratch_section(&path).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("---\ntitle: \"scratch\""));
+        assert_eq!(content.matches("\n## ").count(), 2);
+    }
+
+    #[test]
+    fn test_appended_block_follows_frontmatter_with_optional_heading() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = create_note_in(dir.path(), "log", false).unwrap();
+        let created = fs::read_to_string(&path).unwrap();
+
+        let once = append_block(&created, "first line\nsecond line\n\n", None);
+        assert!(once.starts_with(created.trim_end_matches('\n')));
+        assert!(
+            once.ends_with("---\n\nfirst line\nsecond line\n"),
+            "{}",
+            once
+        );
+        assert!(split_frontmatter(&once).is_some());
+
+        let twice = append_block(&once, "more", Some(" Friday "));
+        assert!(
+            twice.ends_with("second line\n\n## Friday\n\nmore\n"),
+            "{}",
+            twice
+        );
+        assert_eq!(append_block("", "text", None), "text\n");
+    }
+
+    #[test]
+    fn test_rename_tag_keeps_other_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let note = "---\ntitle: Plan\ntags:\n- project\n- q3\nreviewer: sam\n---\n\nbody\n";
+        fs::write(dir.path().join("plan.md"), note).unwrap();
+        fs::write(dir.path().join("other.md"), "---\ntags: [home]\n---\nx\n").unwrap();
+        fs::write(dir.path().join("plain.md"), "no frontmatter\n").unwrap();
+
+        let preview = rename_tag_in(dir.path(), "project", "work/project", true).unwrap();
+        assert_eq!(preview, vec!["plan"]);
+        assert_eq!(
+            fs::read_to_string(dir.path().join("plan.md")).unwrap(),
+            note
+        );
+
+        let changed = rename_tag_in(dir.path(), "project", "work/project", false).unwrap();
+        assert_eq!(changed, vec!["plan"]);
+        assert_eq!(
+            fs::read_to_string(dir.path().join("plan.md")).unwrap(),
+            "---\ntitle: Plan\ntags:\n- work/project\n- q3\nreviewer: sam\n---\n\nbody\n"
+        );
+        assert!(rename_tag_in(dir.path(), "project", "work/project", false)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_rename_tag_dedupes_existing_new_tag() {
+        let note = "---\ntags: [project, q3, work/project]\n---\nbody\n";
+        let updated = rename_tag_in_note(note, "project", "work/project")
+            .unwrap()
+            .unwrap();
+        let (mapping, body) = split_frontmatter(&updated).unwrap();
+        let tags: Vec<&str> = mapping["tags"]
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .map(|t| t.as_str().unwrap())
+            .collect();
+        assert_eq!(tags, ["work/project", "q3"]);
+        assert_eq!(body, "\nbody\n");
+    }
+
+    #[test]
+    fn test_wikilinks_resolve_both_ways_and_report_broken_targets() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("projects")).unwrap();
+        fs::write(
+            dir.path().join("projects/plan.md"),
+            "---\ntitle: Plan\n---\nSee [[ideas|my ideas]] and [[Ideas#later]], [[nowhere]].\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("ideas.md"),
+            "---\ntitle: \"[[not a link]]\"\n---\nBack to [[projects/plan]].\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("other.md"), "Unrelated [[plan]]\n").unwrap();
+
+        assert_eq!(
+            extract_links(&fs::read_to_string(dir.path().join("ideas.md")).unwrap()),
+            ["projects/plan"]
+        );
+
+        let plan = note_links_in(dir.path(), &dir.path().join("projects/plan.md")).unwrap();
+        let outgoing: Vec<(&str, Option<&str>)> = plan
+            .outgoing
+            .iter()
+            .map(|link| (link.target.as_str(), link.note.as_deref()))
+            .collect();
+        assert_eq!(
+            outgoing,
+            [
+                ("ideas", Some("ideas")),
+                ("Ideas", Some("ideas")),
+                ("nowhere", None)
+            ]
+        );
+        assert_eq!(plan.backlinks, ["ideas", "other"]);
+
+        let ideas = note_links_in(dir.path(), &dir.path().join("ideas.md")).unwrap();
+        assert_eq!(ideas.backlinks, ["projects/plan"]);
+        assert!(note_links_in(dir.path(), &dir.path().join("other.md"))
+            .unwrap()
+            .backlinks
+            .is_empty());
+    }
+
+    #[test]
+    fn test_rename_to_title_moves_note_and_rewrites_links() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("work")).unwrap();
+        let draft = "---\ntitle: Launch Plan\n---\n\nSee [[draft#goals]].\n";
+        fs::write(dir.path().join("work/draft.md"), draft).unwrap();
+        let index = "---\ntitle: Index\n---\n[[draft|the plan]], [[work/draft]], [[other]]\n";
+        fs::write(dir.path().join("index.md"), index).unwrap();
+        fs::write(dir.path().join("other.md"), "# Other\n").unwrap();
+
+        let preview = rename_to_title_in(dir.path(), "work/draft", true).unwrap();
+        assert_eq!(preview.to, "work/launch-plan");
+        assert_eq!(preview.relinked, ["index", "work/draft"]);
+        assert!(dir.path().join("work/draft.md").exists());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("index.md")).unwrap(),
+            index
+        );
+
+        let rename = rename_to_title_in(dir.path(), "work/draft", false).unwrap();
+        assert_eq!(rename, preview);
+        assert!(!dir.path().join("work/draft.md").exists());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("work/launch-plan.md")).unwrap(),
+            "---\ntitle: Launch Plan\n---\n\nSee [[launch-plan#goals]].\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("index.md")).unwrap(),
+            "---\ntitle: Index\n---\n[[launch-plan|the plan]], [[work/launch-plan]], [[other]]\n"
+        );
+
+        // Already named after its (H1) title
+        let other = rename_to_title_in(dir.path(), "other", false).unwrap();
+        assert_eq!(other.to, "other");
+        assert!(other.relinked.is_empty());
+    }
+
+    #[test]
+    fn test_render_template_fills_placeholders_and_frontmatter() {
+        let now = Local.with_ymd_and_hms(2024, 3, 5, 9, 30, 0).unwrap();
+        let template = "---\ntags: [meeting, \"{{date}}\"]\ncreated: old\n---\n\n# {{title}}\n\nDate: {{date}} {{time}}\n";
+
+        let note = render_template(template, "Standup: team", now).unwrap();
+        validate_frontmatter(&note).unwrap();
+        let (fields, body) = split_frontmatter(&note).unwrap();
+        assert_eq!(fields["title"].as_str(), Some("Standup: team"));
+        assert_eq!(
+            fields["created"].as_str(),
+            Some(now.with_timezone(&Utc).to_rfc3339().as_str())
+        );
+        assert_eq!(fields["tags"][1].as_str(), Some("2024-03-05"));
+        assert_eq!(body, "\n\n# Standup: team\n\nDate: 2024-03-05 09:30\n");
+
+        // Templates without frontmatter get the standard one
+        let note = render_template("Notes for {{title}}\n", "journal", now).unwrap();
+        let (fields, body) = split_frontmatter(&note).unwrap();
+        assert_eq!(fields["title"].as_str(), Some("journal"));
+        assert_eq!(body, "\n\nNotes for journal\n");
+    }
+
+    #[test]
+    fn test_note_from_missing_template_fails_clearly() {
+        let dir = tempfile::tempdir().unwrap();
+        let templates = dir.path().join("note_templates");
+        fs::create_dir_all(templates.join("work")).unwrap();
+        fs::write(templates.join("meeting.md"), "# {{title}}\n").unwrap();
+        fs::write(templates.join("work/standup.md"), "# {{title}}\n").unwrap();
+        fs::write(dir.path().join("secret.md"), "secret\n").unwrap();
+        assert_eq!(list_templates_in(&templates).unwrap(), ["meeting"]);
+
+        let err = create_note_from_template_in(dir.path(), &templates, "x", "journal", false)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Template 'journal' does not exist"), "{}", err);
+
+        // Template names can't reach outside the templates directory
+        for name in ["../secret", "work/standup", "..\\secret", ""] {
+            let err = create_note_from_template_in(dir.path(), &templates, "x", name, false)
+                .unwrap_err()
+                .to_string();
+            assert!(err.contains("Invalid template name"), "{}", err);
+        }
+
+        let path =
+            create_note_from_template_in(dir.path(), &templates, "x", "meeting", false).unwrap();
+        assert!(fs::read_to_string(path).unwrap().ends_with("# x\n"));
+    }
+}
@@ -0,0 +1,113 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// An entry of the server's content listing (`GET /api/content/{kind}`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteItem {
+    /// Path of the item relative to its kind
+    pub path: String,
+    /// Server-side modification timestamp
+    pub updated_at: String,
+}
+
+/// Progress of a bulk `lst server pull`, so an interrupted pull can be resumed
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PullManifest {
+    /// Server `updated_at` of every item already written locally, keyed by path
+    pub items: BTreeMap<String, String>,
+}
+
+/// Location of the pull manifest for `kind` (`<content_dir>/.lst/pull/<kind>.json`)
+pub fn get_manifest_path(kind: &str) -> Result<PathBuf> {
+    Ok(super::get_content_dir()?
+        .join(".lst")
+        .join("pull")
+        .join(format!("{}.json", kind)))
+}
+
+impl PullManifest {
+    /// Load a manifest, returning an empty one if the file does not exist
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read pull manifest: {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse pull manifest: {}", path.display()))
+    }
+
+    /// Write the manifest to `path`, creating parent directories as needed
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write pull manifest: {}", path.display()))
+    }
+
+    /// Items of `remote` that are missing from the manifest or newer on the server
+    pub fn pending<'a>(&self, remote: &'a [RemoteItem]) -> Vec<&'a RemoteItem> {
+        remote
+            .iter()
+            .filter(|item| self.items.get(&item.path) != Some(&item.updated_at))
+            .collect()
+    }
+
+    /// Record that `item` has been downloaded
+    pub fn mark_done(&mut self, item: &RemoteItem) {
+        self.items
+            .insert(item.path.clone(), item.updated_at.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(path: &str, updated_at: &str) -> RemoteItem {
+        RemoteItem {
+            path: path.to_string(),
+            updated_at: updated_at.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_resume_only_fetches_remaining_items() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest_path = dir.path().join("lists.json");
+        let remote = vec![
+            item("a.md", "2025-01-01 10:00:00"),
+            item("b.md", "2025-01-01 10:00:00"),
+            item("c.md", "2025-01-01 10:00:00"),
+        ];
+
+        // Simulate a pull that died after the first item
+        let mut manifest = PullManifest::load(&manifest_path).unwrap();
+        assert_eq!(manifest.pending(&remote).len(), 3);
+        manifest.mark_done(&remote[0]);
+        manifest.save(&manifest_path).unwrap();
+
+        let resumed = PullManifest::load(&manifest_path).unwrap();
+        let pending: Vec<_> = resumed.pending(&remote).iter().map(|i| &i.path).collect();
+        assert_eq!(pending, vec!["b.md", "c.md"]);
+    }
+
+    #[test]
+    fn test_resume_refetches_items_updated_on_server() {
+        let mut manifest = PullManifest::default();
+        manifest.mark_done(&item("a.md", "2025-01-01 10:00:00"));
+        manifest.mark_done(&item("b.md", "2025-01-01 10:00:00"));
+
+        let remote = vec![
+            item("a.md", "2025-01-01 10:00:00"),
+            item("b.md", "2025-01-02 08:30:00"),
+        ];
+        let pending: Vec<_> = manifest.pending(&remote).iter().map(|i| &i.path).collect();
+        assert_eq!(pending, vec!["b.md"]);
+    }
+}
@@ -0,0 +1,195 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::models::ItemStatus;
+
+/// A line of a note matching a search query
+#[derive(Debug, Clone, Serialize)]
+pub struct NoteMatch {
+    /// Note name relative to the notes directory, without `.md`
+    pub note: String,
+    /// 1-based line number
+    pub line: u32,
+    /// The matching line, trimmed
+    pub content: String,
+}
+
+/// A list item whose text matches a search query
+#[derive(Debug, Clone, Serialize)]
+pub struct ListMatch {
+    /// List name relative to the lists directory, without `.md`
+    pub list: String,
+    pub anchor: String,
+    pub text: String,
+    pub status: ItemStatus,
+}
+
+/// Build the matcher for `query`: a literal substring match by default, or the
+/// query as a regular expression when `regex` is set
+fn build_matcher(query: &str, regex: bool) -> Result<Regex> {
+    let pattern = if regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    Regex::new(&pattern).with_context(|| format!("Invalid search pattern: {}", query))
+}
+
+fn document_name(base: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(base).unwrap_or(path);
+    relative
+        .with_extension("")
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Search the text of every item of every list below `lists_dir`
+pub fn search_lists_in(lists_dir: &Path, query: &str, regex: bool) -> Result<Vec<ListMatch>> {
+    let matcher = build_matcher(query, regex)?;
+    let mut matches = Vec::new();
+    if !lists_dir.exists() {
+        return Ok(matches);
+    }
+
+    let mut files = super::list_files_recursive(lists_dir, "md")?;
+    files.sort();
+    for path in files {
+        let list = super::markdown::parse_list_from_file(&path)?;
+        let name = document_name(lists_dir, &path);
+        for item in list.all_items().filter(|item| matcher.is_match(&item.text)) {
+            matches.push(ListMatch {
+                list: name.clone(),
+                anchor: item.anchor.clone(),
+                text: item.text.clone(),
+                status: item.status.clone(),
+            });
+        }
+    }
+
+    Ok(matches)
+}
+
+/// Search the contents of every note below `notes_dir` with ripgrep, falling
+/// back to an in-process scan when `rg` is not installed
+pub fn search_notes_in(notes_dir: &Path, query: &str, regex: bool) -> Result<Vec<NoteMatch>> {
+    if !notes_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut cmd = Command::new("rg");
+    if !regex {
+        cmd.arg("--fixed-strings");
+    }
+    let output = cmd
+        .arg("--line-number")
+        .arg("--no-heading")
+        .arg("--with-filename")
+        .arg("--color=never")
+        .arg("--glob=*.md")
+        .arg("--")
+        .arg(query)
+        .arg(notes_dir)
+        .output();
+
+    let output = match output {
+        Ok(output) => output,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return scan_notes(notes_dir, query, regex);
+        }
+        Err(e) => return Err(e).context("Failed to execute ripgrep"),
+    };
+
+    // Exit code 1 means "no matches"; anything above that is a real error
+    if output.status.code().is_some_and(|code| code > 1) {
+        anyhow::bail!("ripgrep error: {}", String::from_utf8_lossy(&output.stderr));
+    }
+
+    let mut matches: Vec<NoteMatch> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, ':');
+            let file = parts.next()?;
+            let line_num = parts.next()?.parse().ok()?;
+            let content = parts.next()?;
+            Some(NoteMatch {
+                note: document_name(notes_dir, Path::new(file)),
+                line: line_num,
+                content: content.trim().to_string(),
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| a.note.cmp(&b.note).then(a.line.cmp(&b.line)));
+    Ok(matches)
+}
+
+fn scan_notes(notes_dir: &Path, query: &str, regex: bool) -> Result<Vec<NoteMatch>> {
+    let matcher = build_matcher(query, regex)?;
+    let mut files = super::list_files_recursive(notes_dir, "md")?;
+    files.sort();
+
+    let mut matches = Vec::new();
+    for path in files {
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read note: {}", path.display()))?;
+        let note = document_name(notes_dir, &path);
+        for (idx, line) in content.lines().enumerate() {
+            if matcher.is_match(line) {
+                matches.push(NoteMatch {
+                    note: note.clone(),
+                    line: idx as u32 + 1,
+                    content: line.trim().to_string(),
+                });
+            }
+        }
+    }
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_finds_keyword_in_lists_and_notes() {
+        let dir = tempfile::tempdir().unwrap();
+        let lists_dir = dir.path().join("lists");
+        let notes_dir = dir.path().join("notes");
+        fs::create_dir_all(&lists_dir).unwrap();
+        fs::create_dir_all(&notes_dir).unwrap();
+
+        fs::write(
+            lists_dir.join("groceries.md"),
+            "---\ntitle: groceries\n---\n\n- [ ] oat milk  ^abcde\n- [ ] bread  ^fghij\n",
+        )
+        .unwrap();
+        fs::write(
+            notes_dir.join("recipes.md"),
+            "---\ntitle: recipes\n---\n\nPancakes need oat milk.\n",
+        )
+        .unwrap();
+
+        let lists = search_lists_in(&lists_dir, "oat milk", false).unwrap();
+        assert_eq!(lists.len(), 1);
+        assert_eq!(lists[0].list, "groceries");
+        assert_eq!(lists[0].anchor, "^abcde");
+
+        let notes = search_notes_in(&notes_dir, "oat milk", false).unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].note, "recipes");
+        assert_eq!(notes[0].line, 5);
+
+        // Literal by default, regex only on request
+        assert!(search_lists_in(&lists_dir, "oat.milk", false)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            search_lists_in(&lists_dir, "oat.milk", true).unwrap().len(),
+            1
+        );
+        assert_eq!(scan_notes(&notes_dir, "o[a]t", true).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,209 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Deletion time appended to trashed file names (`groceries.20240501T120000123Z.md`)
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%3fZ";
+
+/// A deleted list or note waiting in the trash
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrashEntry {
+    /// Original path relative to the content directory, without `.md`
+    /// (e.g. `lists/groceries`)
+    pub name: String,
+    /// When the file was deleted
+    pub deleted_at: DateTime<Utc>,
+    /// Location of the file in the trash
+    pub path: PathBuf,
+}
+
+/// Directory holding deleted files (`<content_dir>/.trash`), mirroring the
+/// layout of the content directory. Being hidden, neither listings nor
+/// lst-syncd look inside it
+pub fn trash_dir_in(content_dir: &Path) -> PathBuf {
+    content_dir.join(".trash")
+}
+
+/// Delete the list or note file at `path`: move it to the trash when
+/// `ui.use_trash` is set (the default), remove it for good otherwise
+pub fn discard(path: &Path) -> Result<()> {
+    if crate::config::get_config().ui.use_trash {
+        move_to_trash_in(&super::get_content_dir()?, path, Utc::now())?;
+    } else {
+        fs::remove_file(path).with_context(|| format!("Failed to delete {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Move the file at `path` below `content_dir` into the trash, stamped with
+/// `deleted_at`. Returns its location in the trash
+pub fn move_to_trash_in(
+    content_dir: &Path,
+    path: &Path,
+    deleted_at: DateTime<Utc>,
+) -> Result<PathBuf> {
+    let relative = path
+        .strip_prefix(content_dir)
+        .with_context(|| format!("{} is outside the content directory", path.display()))?;
+    let stem = relative
+        .file_stem()
+        .with_context(|| format!("Not a file: {}", path.display()))?
+        .to_string_lossy();
+    let name = format!("{}.{}.md", stem, deleted_at.format(TIMESTAMP_FORMAT));
+    let target = trash_dir_in(content_dir).join(relative.with_file_name(name));
+    super::move_document_file(path, &target, false)?;
+    Ok(target)
+}
+
+/// Everything in the trash below `content_dir`, most recently deleted first
+pub fn entries_in(content_dir: &Path) -> Result<Vec<TrashEntry>> {
+    let trash_dir = trash_dir_in(content_dir);
+    if !trash_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut entries: Vec<TrashEntry> = super::list_files_in(&trash_dir, "md", None)?
+        .into_iter()
+        .filter_map(|path| {
+            let relative = path.strip_prefix(&trash_dir).ok()?;
+            let (stem, stamp) = relative.file_stem()?.to_str()?.rsplit_once('.')?;
+            let deleted_at = NaiveDateTime::parse_from_str(stamp, TIMESTAMP_FORMAT).ok()?;
+            let name = relative.with_file_name(stem).to_string_lossy().to_string();
+            Some(TrashEntry {
+                name,
+                deleted_at: deleted_at.and_utc(),
+                path,
+            })
+        })
+        .collect();
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.deleted_at));
+    Ok(entries)
+}
+
+/// Move the most recently deleted file called `name` (its original path
+/// without `.md`, or just its file name when that is unambiguous) back to
+/// where it was. Fails rather than overwrite a file that exists again
+pub fn restore_in(content_dir: &Path, name: &str) -> Result<TrashEntry> {
+    let name = name.trim_end_matches(".md");
+    let entries = entries_in(content_dir)?;
+    let file_name = |entry: &TrashEntry| {
+        Path::new(&entry.name)
+            .file_name()
+            .is_some_and(|file| file == name)
+    };
+    let entry = match entries.iter().find(|entry| entry.name == name) {
+        Some(entry) => entry,
+        None => {
+            let matches: Vec<&TrashEntry> = entries.iter().filter(|e| file_name(e)).collect();
+            let mut originals: Vec<&str> = matches.iter().map(|e| e.name.as_str()).collect();
+            originals.sort();
+            originals.dedup();
+            match originals.len() {
+                0 => anyhow::bail!("Nothing named '{}' in the trash", name),
+                // Entries are newest first
+                1 => matches[0],
+                _ => anyhow::bail!(
+                    "Multiple deleted files match '{}': {}",
+                    name,
+                    originals.join(", ")
+                ),
+            }
+        }
+    };
+
+    let target = content_dir.join(format!("{}.md", entry.name));
+    if target.exists() {
+        anyhow::bail!(
+            "Cannot restore '{}': {} exists again",
+            entry.name,
+            target.display()
+        );
+    }
+    super::move_document_file(&entry.path, &target, false)?;
+    Ok(entry.clone())
+}
+
+/// Permanently delete everything in the trash, returning how many files
+/// were removed
+pub fn empty_in(content_dir: &Path) -> Result<usize> {
+    let count = entries_in(content_dir)?.len();
+    let trash_dir = trash_dir_in(content_dir);
+    if trash_dir.exists() {
+        fs::remove_dir_all(&trash_dir)
+            .with_context(|| format!("Failed to empty {}", trash_dir.display()))?;
+    }
+    Ok(count)
+}
+
+/// Everything in the trash of the configured content directory
+pub fn entries() -> Result<Vec<TrashEntry>> {
+    entries_in(&super::get_content_dir()?)
+}
+
+/// Restore a deleted list or note, see [`restore_in`]
+pub fn restore(name: &str) -> Result<TrashEntry> {
+    restore_in(&super::get_content_dir()?, name)
+}
+
+/// Empty the trash of the configured content directory
+pub fn empty() -> Result<usize> {
+    empty_in(&super::get_content_dir()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_deleted_files_can_be_restored_until_the_trash_is_emptied() {
+        let dir = tempfile::tempdir().unwrap();
+        let content_dir = dir.path();
+        let list = content_dir.join("lists/groceries.md");
+        let note = content_dir.join("notes/work/groceries.md");
+        for (path, content) in [(&list, "- [ ] milk\n"), (&note, "# Shop\n")] {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+
+        let first = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let trashed = move_to_trash_in(content_dir, &list, first).unwrap();
+        assert_eq!(
+            trashed,
+            content_dir.join(".trash/lists/groceries.20240501T120000000Z.md")
+        );
+        assert!(!list.exists());
+        move_to_trash_in(content_dir, &note, first + chrono::Duration::hours(1)).unwrap();
+
+        let entries = entries_in(content_dir).unwrap();
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["notes/work/groceries", "lists/groceries"]);
+        assert_eq!(entries[1].deleted_at, first);
+
+        // A bare name matching two deleted files is ambiguous
+        let err = restore_in(content_dir, "groceries")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("lists/groceries, notes/work/groceries"),
+            "{}",
+            err
+        );
+
+        let restored = restore_in(content_dir, "lists/groceries.md").unwrap();
+        assert_eq!(restored.name, "lists/groceries");
+        assert_eq!(fs::read_to_string(&list).unwrap(), "- [ ] milk\n");
+        assert!(restore_in(content_dir, "lists/groceries").is_err());
+
+        // A restored file that was deleted again can't be restored over
+        fs::write(&note, "# New\n").unwrap();
+        assert!(restore_in(content_dir, "groceries").is_err());
+        assert_eq!(fs::read_to_string(&note).unwrap(), "# New\n");
+
+        assert_eq!(empty_in(content_dir).unwrap(), 1);
+        assert!(entries_in(content_dir).unwrap().is_empty());
+        assert!(!trash_dir_in(content_dir).exists());
+        assert_eq!(empty_in(content_dir).unwrap(), 0);
+    }
+}
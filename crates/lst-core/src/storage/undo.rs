@@ -0,0 +1,279 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Maximum number of pre-images kept per list
+pub const MAX_ENTRIES_PER_LIST: usize = 10;
+
+/// A pre-image of a list captured right before a mutating operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoEntry {
+    /// When the operation happened
+    pub timestamp: DateTime<Utc>,
+    /// One-line description of the operation (e.g. "done 'milk'")
+    pub summary: String,
+    /// Raw markdown of the list before the operation
+    pub snapshot: String,
+}
+
+/// Directory holding the undo journal (`<content_dir>/.lst/undo`)
+pub fn get_undo_dir() -> Result<PathBuf> {
+    Ok(super::get_content_dir()?.join(".lst").join("undo"))
+}
+
+fn journal_path(dir: &Path, list_name: &str) -> PathBuf {
+    dir.join(format!("{}.json", list_name))
+}
+
+/// Record a pre-image for `list_name` in the default undo directory
+pub fn record(list_name: &str, summary: &str, snapshot: &str) -> Result<()> {
+    record_in(&get_undo_dir()?, list_name, summary, snapshot)
+}
+
+/// Return the journal entries for `list_name`, newest first
+pub fn entries(list_name: &str) -> Result<Vec<UndoEntry>> {
+    entries_in(&get_undo_dir()?, list_name)
+}
+
+/// Return the journal entries of every list that has any, newest first per list
+pub fn all_entries() -> Result<Vec<(String, Vec<UndoEntry>)>> {
+    all_entries_in(&get_undo_dir()?)
+}
+
+/// Restore the most recent pre-image of `list_name`, see [`restore_in`]
+pub fn restore(list_name: &str) -> Result<Option<UndoEntry>> {
+    restore_in(&get_undo_dir()?, &super::get_lists_dir()?, list_name)
+}
+
+/// Write the newest pre-image of `list_name` back into `lists_dir` and drop it
+/// from the journal, so repeated calls step further back. Returns the undone
+/// entry, or `None` when the journal is empty
+pub fn restore_in(dir: &Path, lists_dir: &Path, list_name: &str) -> Result<Option<UndoEntry>> {
+    let path = journal_path(dir, list_name);
+    let mut journal = read_journal(&path)?;
+    let Some(entry) = journal.pop() else {
+        return Ok(None);
+    };
+
+    let list_path = lists_dir.join(format!("{}.md", list_name));
+    if let Some(parent) = list_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    super::atomic_write(&list_path, &entry.snapshot)
+        .with_context(|| format!("Failed to write list file: {}", list_path.display()))?;
+
+    write_journal(&path, &journal)?;
+    Ok(Some(entry))
+}
+
+/// Append a pre-image to the journal in `dir`, dropping the oldest entries
+/// beyond [`MAX_ENTRIES_PER_LIST`]
+pub fn record_in(dir: &Path, list_name: &str, summary: &str, snapshot: &str) -> Result<()> {
+    let path = journal_path(dir, list_name);
+    let mut journal = read_journal(&path)?;
+
+    journal.push(UndoEntry {
+        timestamp: Utc::now(),
+        summary: summary.to_string(),
+        snapshot: snapshot.to_string(),
+    });
+    if journal.len() > MAX_ENTRIES_PER_LIST {
+        let excess = journal.len() - MAX_ENTRIES_PER_LIST;
+        journal.drain(..excess);
+    }
+
+    write_journal(&path, &journal)
+}
+
+/// Return the journal entries for `list_name` stored in `dir`, newest first
+pub fn entries_in(dir: &Path, list_name: &str) -> Result<Vec<UndoEntry>> {
+    let mut journal = read_journal(&journal_path(dir, list_name))?;
+    journal.reverse();
+    Ok(journal)
+}
+
+/// Return the journal entries of every list stored in `dir`
+pub fn all_entries_in(dir: &Path) -> Result<Vec<(String, Vec<UndoEntry>)>> {
+    let mut result = Vec::new();
+    if !dir.exists() {
+        return Ok(result);
+    }
+
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(current) = stack.pop() {
+        for entry in fs::read_dir(&current)
+            .with_context(|| format!("Failed to read undo directory: {}", current.display()))?
+        {
+            let path = entry?.path();
+            if path.is_dir() {
+                stack.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "json") {
+                let Ok(relative) = path
+                    .with_extension("")
+                    .strip_prefix(dir)
+                    .map(Path::to_path_buf)
+                else {
+                    continue;
+                };
+                let list_name = relative.to_string_lossy().replace('\\', "/");
+                let entries = entries_in(dir, &list_name)?;
+                if !entries.is_empty() {
+                    result.push((list_name, entries));
+                }
+            }
+        }
+    }
+
+    result.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(result)
+}
+
+fn read_journal(path: &Path) -> Result<Vec<UndoEntry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read undo journal: {}", path.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse undo journal: {}", path.display()))
+}
+
+fn write_journal(path: &Path, journal: &[UndoEntry]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    fs::write(path, serde_json::to_string_pretty(journal)?)
+        .with_context(|| format!("Failed to write undo journal: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_journal_lists_entries_newest_first() {
+        let dir = tempfile::tempdir().unwrap();
+
+        record_in(dir.path(), "groceries", "add 'milk'", "before add").unwrap();
+        record_in(dir.path(), "groceries", "done 'milk'", "before done").unwrap();
+
+        let entries = entries_in(dir.path(), "groceries").unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].summary, "done 'milk'");
+        assert_eq!(entries[0].snapshot, "before done");
+        assert_eq!(entries[1].summary, "add 'milk'");
+        assert!(entries[0].timestamp >= entries[1].timestamp);
+
+        let all = all_entries_in(dir.path()).unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].0, "groceries");
+    }
+
+    #[test]
+    fn test_journal_is_capped_per_list() {
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..MAX_ENTRIES_PER_LIST + 3 {
+            record_in(dir.path(), "daily_lists/today", &format!("op {}", i), "").unwrap();
+        }
+
+        let entries = entries_in(dir.path(), "daily_lists/today").unwrap();
+        assert_eq!(entries.len(), MAX_ENTRIES_PER_LIST);
+        assert_eq!(
+            entries[0].summary,
+            format!("op {}", MAX_ENTRIES_PER_LIST + 2)
+        );
+        assert_eq!(
+            all_entries_in(dir.path()).unwrap()[0].0,
+            "daily_lists/today"
+        );
+    }
+
+    #[test]
+    fn test_restore_brings_back_removed_item_with_anchor() {
+        use super::super::backend::{FsStorage, Storage};
+        use super::super::markdown::{add_item_to_category_with, delete_item_with};
+
+        let dir = tempfile::tempdir().unwrap();
+        let undo_dir = dir.path().join("undo");
+        let lists_dir = dir.path().join("lists");
+        let storage = FsStorage::new(dir.path()).with_undo_dir(undo_dir.clone());
+        storage
+            .write_list(
+                "groceries",
+                &crate::models::List::new("groceries".to_string()),
+                "create",
+            )
+            .unwrap();
+        let milk = add_item_to_category_with(&storage, "groceries", "milk", None, None).unwrap();
+        add_item_to_category_with(&storage, "groceries", "bread", None, None).unwrap();
+
+        delete_item_with(&storage, "groceries", &milk.anchor, 50).unwrap();
+        assert_eq!(
+            storage.read_list("groceries").unwrap().all_items().count(),
+            1
+        );
+
+        let undone = restore_in(&undo_dir, &lists_dir, "groceries")
+            .unwrap()
+            .unwrap();
+        assert_eq!(undone.summary, "rm 'milk'");
+        let list = storage.read_list("groceries").unwrap();
+        let restored = list.all_items().find(|item| item.text == "milk").unwrap();
+        assert_eq!(restored.anchor, milk.anchor);
+
+        // Further undos step back through the earlier adds
+        restore_in(&undo_dir, &lists_dir, "groceries").unwrap();
+        assert_eq!(
+            storage.read_list("groceries").unwrap().all_items().count(),
+            1
+        );
+        restore_in(&undo_dir, &lists_dir, "groceries").unwrap();
+        assert!(restore_in(&undo_dir, &lists_dir, "groceries")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_multi_item_add_is_undone_in_one_step() {
+        use super::super::backend::{FsStorage, Storage};
+        use super::super::markdown::{add_item_to_category_with, add_items_with};
+
+        let dir = tempfile::tempdir().unwrap();
+        let undo_dir = dir.path().join("undo");
+        let lists_dir = dir.path().join("lists");
+        let storage = FsStorage::new(dir.path()).with_undo_dir(undo_dir.clone());
+        storage
+            .write_list(
+                "groceries",
+                &crate::models::List::new("groceries".to_string()),
+                "create",
+            )
+            .unwrap();
+        add_item_to_category_with(&storage, "groceries", "milk", None, None).unwrap();
+        let before = std::fs::read_to_string(lists_dir.join("groceries.md")).unwrap();
+
+        let items: Vec<String> = (0..12).map(|i| format!("item {}", i)).collect();
+        let new: Vec<_> = items
+            .iter()
+            .map(|text| (text.as_str(), None, None))
+            .collect();
+        assert_eq!(
+            add_items_with(&storage, "groceries", &new).unwrap().len(),
+            12
+        );
+        assert_eq!(entries_in(&undo_dir, "groceries").unwrap().len(), 2);
+
+        let undone = restore_in(&undo_dir, &lists_dir, "groceries")
+            .unwrap()
+            .unwrap();
+        assert_eq!(undone.summary, "add 12 items");
+        assert_eq!(
+            std::fs::read_to_string(lists_dir.join("groceries.md")).unwrap(),
+            before
+        );
+    }
+}
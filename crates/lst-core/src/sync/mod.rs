@@ -1,6 +1,9 @@
+use crate::config::State;
 use crate::storage;
 use anyhow::{anyhow, Context, Result};
 use automerge::{transaction::Transactable as _, Automerge, ObjType, ReadDoc, ScalarValue, Value};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
@@ -27,6 +30,240 @@ impl DocumentKind {
     }
 }
 
+/// Connection state of the sync daemon's server listener. The daemon writes it to
+/// `syncd-status.json` next to the state file so `lst sync status` can report it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ConnectionStatus {
+    Connecting,
+    Connected {
+        since: DateTime<Utc>,
+    },
+    Reconnecting {
+        attempt: u32,
+        retry_at: DateTime<Utc>,
+    },
+}
+
+impl ConnectionStatus {
+    /// Location of the status file (`~/.local/share/lst/syncd-status.json`)
+    pub fn status_path() -> Result<PathBuf> {
+        Ok(State::get_state_path()?.with_file_name("syncd-status.json"))
+    }
+
+    /// Read the last status written by the daemon, if any
+    pub fn load() -> Result<Option<Self>> {
+        let path = Self::status_path()?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read sync status: {}", path.display()))?;
+        Ok(serde_json::from_str(&content).ok())
+    }
+
+    /// Persist the status for other processes
+    pub fn save(&self) -> Result<()> {
+        let path = Self::status_path()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+        std::fs::write(&path, serde_json::to_string(self)?)
+            .with_context(|| format!("Failed to write sync status: {}", path.display()))
+    }
+
+    /// Human-readable description, e.g. "reconnecting in 4s (attempt 3)"
+    pub fn describe(&self, now: DateTime<Utc>) -> String {
+        match self {
+            ConnectionStatus::Connecting => "connecting".to_string(),
+            ConnectionStatus::Connected { .. } => "connected".to_string(),
+            ConnectionStatus::Reconnecting { attempt, retry_at } => {
+                let secs = (*retry_at - now).num_seconds().max(0);
+                format!("reconnecting in {}s (attempt {})", secs, attempt)
+            }
+        }
+    }
+}
+
+/// Live state of a running sync daemon, served by its control endpoint
+/// (`GET /status`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    pub pid: u32,
+    /// Server the daemon syncs with; `None` in local-only mode
+    pub server: Option<String>,
+    pub connection: Option<ConnectionStatus>,
+    /// Local changes waiting to be pushed to the server
+    pub queued_changes: usize,
+    /// When the last sync cycle finished successfully
+    pub last_sync: Option<DateTime<Utc>>,
+    /// Error of the last sync cycle, cleared by a successful one
+    pub last_error: Option<String>,
+}
+
+/// Outcome of a sync requested through the control endpoint (`POST /sync`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ManualSyncResult {
+    Synced,
+    /// No server is configured, so there is nothing to sync with
+    Skipped,
+    Failed {
+        error: String,
+    },
+}
+
+/// Directory the sync daemon keeps its PID file and logs in (`~/.config/lst`)
+fn syncd_dir() -> Result<PathBuf> {
+    let home_dir = dirs::home_dir().context("Could not determine home directory")?;
+    Ok(home_dir.join(".config").join("lst"))
+}
+
+/// PID file of the running sync daemon (`~/.config/lst/syncd.pid`)
+pub fn syncd_pid_path() -> Result<PathBuf> {
+    Ok(syncd_dir()?.join("syncd.pid"))
+}
+
+/// Log file the detached sync daemon writes to (`~/.config/lst/logs/syncd.log`)
+pub fn syncd_log_path() -> Result<PathBuf> {
+    Ok(syncd_dir()?.join("logs").join("syncd.log"))
+}
+
+/// Read the PID recorded in `path`; `None` when the file is missing or garbled
+pub fn read_pid_file(path: &Path) -> Result<Option<u32>> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(content.trim().parse().ok()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Failed to read PID file: {}", path.display())),
+    }
+}
+
+/// Whether a process with `pid` exists
+#[cfg(unix)]
+pub fn process_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: signal 0 only checks for existence and permission
+    let rc = unsafe { libc::kill(pid, 0) };
+    rc == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Whether a process with `pid` exists
+#[cfg(not(unix))]
+pub fn process_alive(pid: u32) -> bool {
+    process_name(pid).is_some()
+}
+
+/// Executable name the sync daemon runs as
+const SYNCD_PROCESS_NAME: &str = "lst-syncd";
+
+/// Whether `pid` is a running `lst-syncd`. A PID reused by another program, or
+/// one whose process can't be inspected, doesn't count.
+pub fn is_syncd_process(pid: u32) -> bool {
+    process_alive(pid) && process_name(pid).as_deref() == Some(SYNCD_PROCESS_NAME)
+}
+
+/// Executable name of process `pid`, without directory or `.exe`
+#[cfg(target_os = "linux")]
+fn process_name(pid: u32) -> Option<String> {
+    let comm = std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok()?;
+    Some(comm.trim_end().to_string())
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn process_name(pid: u32) -> Option<String> {
+    let output = std::process::Command::new("ps")
+        .args(["-p", &pid.to_string(), "-o", "comm="])
+        .output()
+        .ok()?;
+    let comm = String::from_utf8_lossy(&output.stdout);
+    let comm = comm.trim();
+    if !output.status.success() || comm.is_empty() {
+        return None;
+    }
+    Path::new(comm)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+}
+
+#[cfg(not(unix))]
+fn process_name(pid: u32) -> Option<String> {
+    // `tasklist` prints `"lst-syncd.exe","1234",...` or an INFO line when no
+    // process matches
+    let output = std::process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/FO", "CSV", "/NH"])
+        .output()
+        .ok()?;
+    let listing = String::from_utf8_lossy(&output.stdout);
+    let mut fields = listing.lines().next()?.split(',');
+    let image = fields.next()?.trim_matches('"');
+    if fields.next()?.trim_matches('"') != pid.to_string() {
+        return None;
+    }
+    let name = image
+        .strip_suffix(".exe")
+        .or_else(|| image.strip_suffix(".EXE"))
+        .unwrap_or(image);
+    Some(name.to_string())
+}
+
+/// Ask the process `pid` to shut down (SIGTERM)
+#[cfg(unix)]
+pub fn terminate_process(pid: u32) -> Result<()> {
+    send_signal(pid, libc::SIGTERM)
+}
+
+/// Ask the sync daemon `pid` to run a sync cycle right away (SIGUSR1)
+#[cfg(unix)]
+pub fn request_daemon_sync(pid: u32) -> Result<()> {
+    send_signal(pid, libc::SIGUSR1)
+}
+
+#[cfg(unix)]
+fn send_signal(pid: u32, signal: libc::c_int) -> Result<()> {
+    let raw = libc::pid_t::try_from(pid).map_err(|_| anyhow!("Invalid PID {}", pid))?;
+    // SAFETY: plain kill(2) call with a validated pid
+    if unsafe { libc::kill(raw, signal) } != 0 {
+        return Err(std::io::Error::last_os_error())
+            .with_context(|| format!("Failed to signal process {}", pid));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn terminate_process(pid: u32) -> Result<()> {
+    Err(anyhow!(
+        "Stopping the sync daemon is only supported on Unix; end process {} manually",
+        pid
+    ))
+}
+
+#[cfg(not(unix))]
+pub fn request_daemon_sync(_pid: u32) -> Result<()> {
+    Err(anyhow!(
+        "Triggering the sync daemon is only supported on Unix; restart it with 'lst sync start'"
+    ))
+}
+
+/// PID of the daemon recorded in `path` if that process is still a running
+/// `lst-syncd`. A stale or unreadable PID file (daemon crashed or was killed,
+/// possibly with its PID since reused) is removed.
+pub fn running_daemon_pid(path: &Path) -> Result<Option<u32>> {
+    match read_pid_file(path)? {
+        Some(pid) if is_syncd_process(pid) => Ok(Some(pid)),
+        _ => {
+            if path.exists() {
+                std::fs::remove_file(path).with_context(|| {
+                    format!("Failed to remove stale PID file: {}", path.display())
+                })?;
+            }
+            Ok(None)
+        }
+    }
+}
+
 /// Canonical representation of a document path.
 #[derive(Debug, Clone)]
 pub struct CanonicalDocPath {
@@ -45,8 +282,12 @@ impl CanonicalDocPath {
 /// Resolve a path (absolute or relative) against the configured content directory
 /// and derive its canonical metadata used for sync.
 pub fn canonicalize_doc_path(path: &Path) -> Result<CanonicalDocPath> {
-    let content_dir = storage::get_content_dir()?;
+    Ok(canonicalize_doc_path_in(&storage::get_content_dir()?, path))
+}
 
+/// Canonical metadata of `path` like [`canonicalize_doc_path`], resolved
+/// against `content_dir` instead of the configured content directory
+pub fn canonicalize_doc_path_in(content_dir: &Path, path: &Path) -> CanonicalDocPath {
     let full_path = if path.is_absolute() {
         path.to_path_buf()
     } else {
@@ -55,7 +296,7 @@ pub fn canonicalize_doc_path(path: &Path) -> Result<CanonicalDocPath> {
 
     // Best-effort normalization; if the path lives outside the content dir we still
     // use the display form to keep IDs stable across platforms.
-    let relative_path = match full_path.strip_prefix(&content_dir) {
+    let relative_path = match full_path.strip_prefix(content_dir) {
         Ok(rel) => rel.to_path_buf(),
         Err(_) => {
             // Accept already relative inputs like "lists/foo.md"
@@ -70,11 +311,11 @@ pub fn canonicalize_doc_path(path: &Path) -> Result<CanonicalDocPath> {
     let relative_str = normalize_relative_path(&relative_path);
     let kind = detect_kind(&relative_str);
 
-    Ok(CanonicalDocPath {
+    CanonicalDocPath {
         full_path,
         relative_path: relative_str,
         kind,
-    })
+    }
 }
 
 fn normalize_relative_path(path: &Path) -> String {
@@ -94,7 +335,9 @@ fn detect_kind(relative: &str) -> DocumentKind {
     }
 }
 
-fn uuid_from_relative_path(relative: &str) -> String {
+/// Deterministic document id for a path relative to the content directory
+/// (e.g. `lists/groceries.md`)
+pub fn uuid_from_relative_path(relative: &str) -> String {
     let normalized = relative.replace('\\', "/");
     Uuid::new_v5(&Uuid::NAMESPACE_OID, normalized.as_bytes()).to_string()
 }
@@ -115,6 +358,34 @@ pub fn extract_automerge_content(doc: &Automerge, kind: DocumentKind) -> Result<
     }
 }
 
+/// Hash of the content held by an Automerge `snapshot`, as it would be
+/// written to disk by the sync daemon
+pub fn snapshot_content_hash(snapshot: &[u8], kind: DocumentKind) -> Result<String> {
+    use sha2::{Digest, Sha256};
+
+    let content = snapshot_content(snapshot, kind)?;
+    Ok(hex::encode(Sha256::digest(content.as_bytes())))
+}
+
+/// Content held by an Automerge `snapshot`, as it would be written to disk
+pub fn snapshot_content(snapshot: &[u8], kind: DocumentKind) -> Result<String> {
+    let doc = Automerge::load(snapshot).context("Failed to load Automerge snapshot")?;
+    extract_automerge_content(&doc, kind)
+}
+
+/// Hash of a local file's content as the sync layer sees it (e.g. trailing
+/// blank lines in lists are dropped), comparable to [`snapshot_content_hash`]
+pub fn synced_content_hash(content: &str, kind: DocumentKind) -> Result<String> {
+    snapshot_content_hash(&snapshot_from_content(content, kind)?, kind)
+}
+
+/// Fresh Automerge snapshot holding `content`
+pub fn snapshot_from_content(content: &str, kind: DocumentKind) -> Result<Vec<u8>> {
+    let mut doc = Automerge::new();
+    update_automerge_doc(&mut doc, kind, content)?;
+    Ok(doc.save())
+}
+
 fn update_note_doc(doc: &mut Automerge, content: &str) -> Result<()> {
     let maybe_id = if let Some((Value::Object(_), id)) = doc.get(automerge::ROOT, "content")? {
         Some(id)
@@ -137,13 +408,12 @@ fn update_list_doc(doc: &mut Automerge, content: &str) -> Result<()> {
     tx.delete(&automerge::ROOT, "items").ok();
     let items_id = tx.put_object(&automerge::ROOT, "items", ObjType::List)?;
 
-    let mut insert_index = 0;
-    for line in content.lines() {
-        let trimmed = line.trim();
-        if !trimmed.is_empty() {
-            tx.insert(&items_id, insert_index, ScalarValue::Str(trimmed.into()))?;
-            insert_index += 1;
-        }
+    // Lines are stored verbatim: indentation carries item notes and nested
+    // frontmatter, and blank note lines are written as bare indentation. Only
+    // trailing blank lines are dropped
+    let content = content.trim_end_matches(['\r', '\n']);
+    for (index, line) in content.lines().enumerate() {
+        tx.insert(&items_id, index, ScalarValue::Str(line.into()))?;
     }
 
     tx.commit();
@@ -207,7 +477,7 @@ pub fn ensure_parent_dir(path: &CanonicalDocPath) -> Result<()> {
 /// Write content to disk for a canonical path.
 pub fn write_document(path: &CanonicalDocPath, content: &str) -> Result<()> {
     ensure_parent_dir(path)?;
-    std::fs::write(&path.full_path, content).with_context(|| {
+    crate::storage::atomic_write(&path.full_path, content).with_context(|| {
         format!(
             "Failed to write document content to {}",
             path.full_path.display()
@@ -216,6 +486,102 @@ pub fn write_document(path: &CanonicalDocPath, content: &str) -> Result<()> {
     Ok(())
 }
 
+/// Doc ids lst-syncd has to delete on the server on its next sync, because the
+/// document moved to a new id. Created on demand, as the CLI may write to the
+/// sync database before a daemon that knows the table has run
+pub const DELETED_DOCUMENTS_SCHEMA: &str =
+    "CREATE TABLE IF NOT EXISTS deleted_documents (doc_id TEXT PRIMARY KEY)";
+
+/// Re-register a moved document in the local sync database (the `documents`
+/// table maintained by lst-syncd).
+///
+/// Document ids are derived from the relative path, so a move changes the id: the
+/// old row is removed and a fresh Automerge snapshot of `content` is stored under
+/// the new id, keeping owner and sharing info. The sync daemon seeds documents the
+/// server doesn't know yet, so the new id gets pushed on the next sync, and it
+/// deletes the old id on the server (see [`DELETED_DOCUMENTS_SCHEMA`]) so the
+/// document isn't pulled back under its old path. Other devices that already
+/// track the old id are not told about the move and push it again as a new
+/// document. Returns `false` when the old path was not tracked.
+pub fn rename_tracked_document(
+    conn: &rusqlite::Connection,
+    old: &CanonicalDocPath,
+    new: &CanonicalDocPath,
+    content: &str,
+) -> Result<bool> {
+    use rusqlite::OptionalExtension;
+    use sha2::{Digest, Sha256};
+
+    let existing = conn
+        .query_row(
+            "SELECT doc_id, owner, writers, readers FROM documents
+             WHERE doc_id = ?1 OR file_path = ?2 LIMIT 1",
+            rusqlite::params![old.document_id(), old.relative_path],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                ))
+            },
+        )
+        .optional()?;
+
+    let Some((old_id, owner, writers, readers)) = existing else {
+        return Ok(false);
+    };
+
+    let mut doc = Automerge::new();
+    update_automerge_doc(&mut doc, new.kind, content)?;
+    let hash = hex::encode(Sha256::digest(content.as_bytes()));
+
+    conn.execute(
+        "DELETE FROM documents WHERE doc_id = ?1",
+        rusqlite::params![old_id],
+    )?;
+    conn.execute_batch(DELETED_DOCUMENTS_SCHEMA)?;
+    conn.execute(
+        "INSERT OR IGNORE INTO deleted_documents (doc_id) VALUES (?1)",
+        rusqlite::params![old_id],
+    )?;
+    // Moving a document back to a path it left keeps that id on the server
+    conn.execute(
+        "DELETE FROM deleted_documents WHERE doc_id = ?1",
+        rusqlite::params![new.document_id()],
+    )?;
+    conn.execute(
+        "INSERT OR REPLACE INTO documents
+            (doc_id, file_path, doc_type, last_sync_hash, automerge_state, owner, writers, readers)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        rusqlite::params![
+            new.document_id(),
+            new.relative_path,
+            new.kind.as_str(),
+            hash,
+            doc.save(),
+            owner,
+            writers,
+            readers
+        ],
+    )?;
+    Ok(true)
+}
+
+/// Drop a deleted document from the local sync database, so that lst-syncd
+/// registers it afresh should it reappear (e.g. through `lst restore`).
+/// Returns `false` when the path was not tracked.
+pub fn forget_tracked_document(
+    conn: &rusqlite::Connection,
+    doc: &CanonicalDocPath,
+) -> Result<bool> {
+    let removed = conn.execute(
+        "DELETE FROM documents WHERE doc_id = ?1 OR file_path = ?2",
+        rusqlite::params![doc.document_id(), doc.relative_path],
+    )?;
+    Ok(removed > 0)
+}
+
 /// Convenience to derive canonical metadata and document id in a single call.
 pub fn canonical_path_with_id(path: &Path) -> Result<(CanonicalDocPath, String)> {
     let canonical = canonicalize_doc_path(path)?;
@@ -225,21 +591,364 @@ pub fn canonical_path_with_id(path: &Path) -> Result<(CanonicalDocPath, String)>
 
 /// Build a canonical path from a stored relative path.
 pub fn path_from_relative(relative: &str) -> Result<CanonicalDocPath> {
-    let content_dir = storage::get_content_dir()?;
-    let full_path = content_dir.join(relative);
-    let kind = detect_kind(relative);
+    Ok(path_from_relative_in(
+        &storage::get_content_dir()?,
+        relative,
+    ))
+}
 
-    Ok(CanonicalDocPath {
-        full_path,
+/// Canonical path of `relative` below `content_dir`
+pub fn path_from_relative_in(content_dir: &Path, relative: &str) -> CanonicalDocPath {
+    CanonicalDocPath {
+        full_path: content_dir.join(relative),
         relative_path: relative.to_string(),
-        kind,
-    })
+        kind: detect_kind(relative),
+    }
+}
+
+/// Resolve a canonical path from an encrypted filename supplied by the server
+/// (see [`crate::crypto::encrypt_filename`]). Paths that would escape the
+/// content directory are rejected.
+pub fn path_from_server_filename(filename: &str, key: &[u8; 32]) -> Result<CanonicalDocPath> {
+    let relative = relative_path_from_server_filename(filename, key)?;
+    path_from_relative(&relative)
+}
+
+/// Resolve a server filename like [`path_from_server_filename`], below
+/// `content_dir` instead of the configured content directory
+pub fn path_from_server_filename_in(
+    content_dir: &Path,
+    filename: &str,
+    key: &[u8; 32],
+) -> Result<CanonicalDocPath> {
+    let relative = relative_path_from_server_filename(filename, key)?;
+    Ok(path_from_relative_in(content_dir, &relative))
 }
 
-/// Resolve a canonical path from a filename supplied by the server.
-pub fn path_from_server_filename(encoded_relative: &str) -> Result<CanonicalDocPath> {
-    if encoded_relative.is_empty() {
-        return Err(anyhow!("Empty relative path received from server"));
+/// Decrypted path (relative to the content directory) of a server filename,
+/// rejecting paths that would escape the content directory
+fn relative_path_from_server_filename(filename: &str, key: &[u8; 32]) -> Result<String> {
+    if filename.is_empty() {
+        return Err(anyhow!("Empty filename received from server"));
+    }
+    let relative = crate::crypto::decrypt_filename(filename, key)
+        .context("Failed to decrypt filename received from server")?;
+    let escapes = Path::new(&relative)
+        .components()
+        .any(|c| !matches!(c, std::path::Component::Normal(_)));
+    if relative.is_empty() || escapes {
+        return Err(anyhow!("Invalid path received from server: {}", relative));
+    }
+    Ok(relative)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc_path(relative: &str) -> CanonicalDocPath {
+        CanonicalDocPath {
+            full_path: PathBuf::from("/content").join(relative),
+            relative_path: relative.to_string(),
+            kind: detect_kind(relative),
+        }
+    }
+
+    fn sync_db() -> rusqlite::Connection {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE documents (
+                doc_id TEXT PRIMARY KEY,
+                file_path TEXT NOT NULL UNIQUE,
+                doc_type TEXT NOT NULL,
+                last_sync_hash TEXT,
+                automerge_state BLOB NOT NULL,
+                owner TEXT NOT NULL,
+                writers TEXT,
+                readers TEXT
+            );",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn track(conn: &rusqlite::Connection, path: &CanonicalDocPath) {
+        conn.execute(
+            "INSERT INTO documents VALUES (?1, ?2, 'list', 'hash', x'00', 'device', 'a@b.c', NULL)",
+            rusqlite::params![path.document_id(), path.relative_path],
+        )
+        .unwrap();
+    }
+
+    fn tracked_ids(conn: &rusqlite::Connection) -> Vec<(String, String, Option<String>)> {
+        let mut stmt = conn
+            .prepare("SELECT doc_id, file_path, writers FROM documents")
+            .unwrap();
+        stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect()
+    }
+
+    /// Content of `content` after a trip through a list document
+    fn list_round_trip(content: &str) -> String {
+        let mut doc = Automerge::new();
+        update_list_doc(&mut doc, content).unwrap();
+        let doc = Automerge::load(&doc.save()).unwrap();
+        extract_list_content(&doc).unwrap()
+    }
+
+    #[test]
+    fn test_list_doc_keeps_item_notes() {
+        let content = [
+            "---",
+            "title: todo",
+            "---",
+            "",
+            "- [ ] call plumber @since:2024-05-01T09:00:00Z  ^abcde",
+            "  ask about Saturday availability",
+            "  ",
+            "  - [ ] not an item, just note text",
+            "- [ ] pay rent @since:2024-05-01T09:00:00Z  ^fghij",
+        ]
+        .join("\n");
+        assert_eq!(list_round_trip(&content), content);
+
+        let path = Path::new("todo.md");
+        let list = storage::markdown::parse_list_from_string(&content, path).unwrap();
+        let synced =
+            storage::markdown::parse_list_from_string(&list_round_trip(&content), path).unwrap();
+        assert_eq!(synced.uncategorized_items.len(), 2);
+        assert_eq!(
+            synced.uncategorized_items[0].note,
+            list.uncategorized_items[0].note
+        );
+    }
+
+    #[test]
+    fn test_list_doc_keeps_category_colors() {
+        use crate::models::List;
+        use storage::markdown::{format_list_as_markdown, parse_list_from_string};
+
+        let mut list = List::new("groceries".to_string());
+        list.metadata
+            .category_colors
+            .insert("Dairy".to_string(), "blue".to_string());
+        list.metadata
+            .category_colors
+            .insert("Produce".to_string(), "#00ff00".to_string());
+        let content = format_list_as_markdown(&list);
+        assert!(content.contains("category_colors:\n  Dairy: blue\n"));
+
+        let synced = list_round_trip(&content);
+        assert!(synced.contains("category_colors:\n  Dairy: blue\n"));
+        let parsed = parse_list_from_string(&synced, Path::new("groceries.md")).unwrap();
+        assert_eq!(
+            parsed.metadata.category_colors,
+            list.metadata.category_colors
+        );
+    }
+
+    #[test]
+    fn test_list_doc_keeps_list_settings() {
+        use crate::models::{List, ListSort};
+        use storage::markdown::{format_list_as_markdown, parse_list_from_string};
+
+        let mut list = List::new("groceries".to_string());
+        list.metadata.list.default_sort = Some(ListSort::Priority);
+        list.metadata.list.default_category = Some("Dairy".to_string());
+        let content = format_list_as_markdown(&list);
+        assert!(content.contains("list:\n  default_sort: priority\n"));
+
+        let synced = list_round_trip(&content);
+        assert!(synced.contains("list:\n  default_sort: priority\n"));
+        let parsed = parse_list_from_string(&synced, Path::new("groceries.md")).unwrap();
+        assert_eq!(parsed.metadata.list, list.metadata.list);
+    }
+
+    #[test]
+    fn test_server_filename_must_stay_inside_content_dir() {
+        let key = [3u8; 32];
+        for path in ["../outside.md", "/etc/passwd", "lists/../../x.md", ""] {
+            let filename = crate::crypto::encrypt_filename(path, &key).unwrap();
+            assert!(path_from_server_filename(&filename, &key).is_err());
+        }
+        assert!(path_from_server_filename("not encrypted", &key).is_err());
+
+        let filename = crate::crypto::encrypt_filename("notes/work/ideas.md", &key).unwrap();
+        let path = path_from_server_filename_in(Path::new("/content"), &filename, &key).unwrap();
+        assert_eq!(path.full_path, Path::new("/content/notes/work/ideas.md"));
+        assert_eq!(path.kind, DocumentKind::Note);
+    }
+
+    #[test]
+    fn test_rename_tracked_document_within_directory() {
+        let conn = sync_db();
+        let old = doc_path("lists/groceries.md");
+        let new = doc_path("lists/food.md");
+        track(&conn, &old);
+
+        assert!(rename_tracked_document(&conn, &old, &new, "- [ ] milk").unwrap());
+
+        let rows = tracked_ids(&conn);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].0, new.document_id());
+        assert_eq!(rows[0].1, "lists/food.md");
+        assert_eq!(rows[0].2.as_deref(), Some("a@b.c"));
+    }
+
+    #[test]
+    fn test_rename_tracked_document_across_directories() {
+        let conn = sync_db();
+        let old = doc_path("lists/pharmacy.md");
+        let new = doc_path("lists/groceries/pharmacy.md");
+        track(&conn, &old);
+
+        assert!(rename_tracked_document(&conn, &old, &new, "- [ ] plasters").unwrap());
+        let rows = tracked_ids(&conn);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].0, new.document_id());
+        assert_ne!(rows[0].0, old.document_id());
+
+        let state: Vec<u8> = conn
+            .query_row("SELECT automerge_state FROM documents", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        let doc = Automerge::load(&state).unwrap();
+        assert_eq!(
+            extract_automerge_content(&doc, DocumentKind::List).unwrap(),
+            "- [ ] plasters"
+        );
+
+        // Untracked documents are left alone
+        let other = doc_path("lists/other.md");
+        assert!(!rename_tracked_document(&conn, &other, &new, "").unwrap());
+    }
+
+    #[test]
+    fn test_rename_tracked_document_queues_old_id_for_deletion() {
+        let deleted = |conn: &rusqlite::Connection| -> Vec<String> {
+            let mut stmt = conn
+                .prepare("SELECT doc_id FROM deleted_documents")
+                .unwrap();
+            stmt.query_map([], |row| row.get(0))
+                .unwrap()
+                .map(Result::unwrap)
+                .collect()
+        };
+        let conn = sync_db();
+        let old = doc_path("lists/groceries.md");
+        let new = doc_path("lists/food.md");
+        track(&conn, &old);
+
+        assert!(rename_tracked_document(&conn, &old, &new, "- [ ] milk").unwrap());
+        assert_eq!(deleted(&conn), vec![old.document_id()]);
+
+        // Moving it back keeps the original id alive on the server
+        assert!(rename_tracked_document(&conn, &new, &old, "- [ ] milk").unwrap());
+        assert_eq!(deleted(&conn), vec![new.document_id()]);
+    }
+
+    #[test]
+    fn test_note_moved_over_tracked_note_deletes_only_its_old_id() {
+        let conn = sync_db();
+        let entry = doc_path("notes/entry.md");
+        let target = doc_path("notes/journal/2024/entry.md");
+        track(&conn, &entry);
+        track(&conn, &target);
+
+        // `lst note mv --force` replaces the destination, which keeps its id
+        assert!(rename_tracked_document(&conn, &entry, &target, "# Entry").unwrap());
+        let rows = tracked_ids(&conn);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].0, target.document_id());
+        assert_eq!(rows[0].1, "notes/journal/2024/entry.md");
+        let deleted: String = conn
+            .query_row("SELECT doc_id FROM deleted_documents", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(deleted, entry.document_id());
+    }
+
+    #[test]
+    fn test_forget_tracked_document_removes_only_that_document() {
+        let conn = sync_db();
+        let groceries = doc_path("lists/groceries.md");
+        let pharmacy = doc_path("lists/pharmacy.md");
+        track(&conn, &groceries);
+        track(&conn, &pharmacy);
+
+        assert!(forget_tracked_document(&conn, &groceries).unwrap());
+        assert!(!forget_tracked_document(&conn, &groceries).unwrap());
+        let rows = tracked_ids(&conn);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].1, "lists/pharmacy.md");
+    }
+
+    /// Run `sleep 30` from a copy named `lst-syncd`, so it looks like the daemon
+    #[cfg(target_os = "linux")]
+    fn spawn_fake_syncd(dir: &Path) -> std::process::Child {
+        let sleep = ["/bin/sleep", "/usr/bin/sleep"]
+            .into_iter()
+            .find(|path| Path::new(path).exists())
+            .expect("sleep binary");
+        let fake = dir.join("lst-syncd");
+        std::fs::copy(sleep, &fake).unwrap();
+        let child = std::process::Command::new(&fake).arg("30").spawn().unwrap();
+        // spawn() may return before exec has renamed the child
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while !is_syncd_process(child.id()) && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        child
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_running_daemon_pid_reports_live_process() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("syncd.pid");
+        assert_eq!(running_daemon_pid(&path).unwrap(), None);
+
+        let mut child = spawn_fake_syncd(dir.path());
+        std::fs::write(&path, format!("{}\n", child.id())).unwrap();
+        let running = running_daemon_pid(&path).unwrap();
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert_eq!(running, Some(child.id()));
+        assert!(path.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_running_daemon_pid_ignores_pid_reused_by_other_program() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("syncd.pid");
+
+        // This test binary is alive but isn't lst-syncd
+        std::fs::write(&path, format!("{}\n", std::process::id())).unwrap();
+        assert!(process_alive(std::process::id()));
+        assert!(!is_syncd_process(std::process::id()));
+        assert_eq!(running_daemon_pid(&path).unwrap(), None);
+        assert!(!path.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_running_daemon_pid_removes_stale_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("syncd.pid");
+
+        // A PID that is guaranteed to be gone: a child we already reaped
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        child.wait().unwrap();
+        std::fs::write(&path, format!("{}\n", child.id())).unwrap();
+        assert!(!process_alive(child.id()));
+        assert_eq!(running_daemon_pid(&path).unwrap(), None);
+        assert!(!path.exists());
+
+        std::fs::write(&path, "not a pid").unwrap();
+        assert_eq!(running_daemon_pid(&path).unwrap(), None);
+        assert!(!path.exists());
     }
-    path_from_relative(encoded_relative)
 }
@@ -23,7 +23,7 @@ impl Default for ThemeSystem {
 }
 
 /// Theme variant (light or dark)
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[cfg_attr(feature = "tauri", derive(Type))]
 #[serde(rename_all = "lowercase")]
 pub enum ThemeVariant {
@@ -371,8 +371,33 @@ impl Theme {
         format!(":root {{\n{}}}\n", self.generate_css_variables())
     }
 
+    /// The theme to use when the system appearance is `variant`: the theme
+    /// named in `variants.light`/`variants.dark`, or this theme itself when
+    /// it names none for that appearance
+    pub fn resolve_for_variant(
+        &self,
+        variant: ThemeVariant,
+        loader: &ThemeLoader,
+    ) -> Result<Theme> {
+        let child = self.variants.as_ref().and_then(|variants| match variant {
+            ThemeVariant::Light => variants.light.as_deref(),
+            ThemeVariant::Dark => variants.dark.as_deref(),
+        });
+        match child {
+            Some(name) if name != self.scheme => loader
+                .load_theme(name)
+                .with_context(|| format!("Failed to load {:?} variant '{}'", variant, name)),
+            _ => Ok(self.clone()),
+        }
+    }
+
     /// Resolve a semantic color to its actual hex value
     pub fn resolve_semantic_color(&self, semantic_name: &str) -> Option<String> {
+        self.resolve_base_color(self.semantic_color_ref(semantic_name)?)
+    }
+
+    /// The base color (e.g. `base0D`) a semantic color is mapped to
+    pub fn semantic_color_ref(&self, semantic_name: &str) -> Option<&str> {
         let base_color = match semantic_name {
             "background" => &self.semantic.background,
             "foreground" => &self.semantic.foreground,
@@ -387,8 +412,7 @@ impl Theme {
             "info" => &self.semantic.info,
             _ => return None,
         };
-
-        self.resolve_base_color(base_color)
+        Some(base_color)
     }
 
     /// Resolve a base color reference to its actual hex value
@@ -421,6 +445,15 @@ impl Theme {
             _ => None,
         }
     }
+
+    /// Resolve a palette key (e.g. `base0B`) or a literal hex color to RGB
+    pub fn resolve_rgb(&self, color: &str) -> Option<(u8, u8, u8)> {
+        if color.starts_with('#') {
+            hex_to_rgb(color)
+        } else {
+            hex_to_rgb(&self.resolve_base_color(color)?)
+        }
+    }
 }
 
 impl Default for Theme {
@@ -461,7 +494,9 @@ impl ThemeLoader {
 
         // Add configured themes directory or default
         if let Some(themes_dir) = themes_dir {
-            loader.theme_dirs.push(themes_dir);
+            loader
+                .theme_dirs
+                .push(crate::config::expand_path(&themes_dir));
         } else {
             // Default to ~/.config/themes (tinty compatible)
             if let Some(home_dir) = dirs::home_dir() {
@@ -483,6 +518,30 @@ impl ThemeLoader {
         loader
     }
 
+    /// Directory user themes are written to: the configured themes
+    /// directory, else `~/.config/themes`
+    pub fn user_themes_dir(&self) -> Option<&Path> {
+        self.theme_dirs.first().map(PathBuf::as_path)
+    }
+
+    /// Validate `theme` and write it to `<dir>/<scheme>.toml`, refusing to
+    /// replace an existing file unless `overwrite` is set. The written file
+    /// is loaded back to make sure it is a usable theme
+    pub fn save_theme_in(&self, dir: &Path, theme: &Theme, overwrite: bool) -> Result<PathBuf> {
+        self.validate_theme(theme)?;
+        let path = dir.join(format!("{}.toml", theme.scheme));
+        if path.exists() && !overwrite {
+            anyhow::bail!("Theme file already exists: {}", path.display());
+        }
+        let content = toml::to_string_pretty(theme).context("Failed to serialize theme")?;
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create themes directory: {}", dir.display()))?;
+        std::fs::write(&path, content)
+            .with_context(|| format!("Failed to write theme file: {}", path.display()))?;
+        self.load_theme_from_file(&path)?;
+        Ok(path)
+    }
+
     /// Add a theme directory to search path
     pub fn add_theme_dir<P: AsRef<Path>>(&mut self, path: P) {
         self.theme_dirs.push(path.as_ref().to_path_buf());
@@ -798,7 +857,7 @@ impl ThemeLoader {
     }
 
     /// Apply theme overrides
-    fn apply_overrides(&self, mut theme: Theme, overrides: &ThemeOverrides) -> Result<Theme> {
+    pub fn apply_overrides(&self, mut theme: Theme, overrides: &ThemeOverrides) -> Result<Theme> {
         // Apply palette overrides
         for (key, value) in &overrides.palette {
             match key.as_str() {
@@ -1094,7 +1153,7 @@ pub struct ThemeInfo {
 }
 
 /// Validate color format (hex colors)
-fn is_valid_color(color: &str) -> bool {
+pub fn is_valid_color(color: &str) -> bool {
     if !color.starts_with('#') {
         return false;
     }
@@ -1107,6 +1166,26 @@ fn is_valid_color(color: &str) -> bool {
     hex.chars().all(|c| c.is_ascii_hexdigit())
 }
 
+/// Parse `#rrggbb` or `#rgb` into RGB components
+pub fn hex_to_rgb(color: &str) -> Option<(u8, u8, u8)> {
+    if !is_valid_color(color) {
+        return None;
+    }
+
+    let hex = &color[1..];
+    let channel = |s: &str| u8::from_str_radix(s, 16).ok();
+    if hex.len() == 3 {
+        let expand = |i: usize| channel(&hex[i..i + 1]).map(|v| v * 17);
+        Some((expand(0)?, expand(1)?, expand(2)?))
+    } else {
+        Some((
+            channel(&hex[0..2])?,
+            channel(&hex[2..4])?,
+            channel(&hex[4..6])?,
+        ))
+    }
+}
+
 // Default semantic color mappings
 fn default_background() -> String {
     "base00".to_string()
@@ -1156,6 +1235,37 @@ mod tests {
         assert!(!is_valid_color("#ff00"));
     }
 
+    #[test]
+    fn test_hex_to_rgb() {
+        assert_eq!(hex_to_rgb("#a6e3a1"), Some((0xa6, 0xe3, 0xa1)));
+        assert_eq!(hex_to_rgb("#f00"), Some((255, 0, 0)));
+        assert_eq!(hex_to_rgb("a6e3a1"), None);
+    }
+
+    #[test]
+    fn test_category_color_resolves_against_palette() {
+        let theme = ThemeLoader::new()
+            .load_theme("base16-catppuccin-mocha")
+            .unwrap();
+        let mut list = crate::models::List::new("groceries".to_string());
+        list.metadata
+            .category_colors
+            .insert("Produce".to_string(), "base0B".to_string());
+        list.metadata
+            .category_colors
+            .insert("Bakery".to_string(), "#ff8800".to_string());
+
+        let expected = hex_to_rgb(&theme.resolve_base_color("base0B").unwrap());
+        assert!(expected.is_some());
+        assert_eq!(list.category_color("Produce", &theme), expected);
+        assert_eq!(
+            list.category_color("Bakery", &theme),
+            Some((0xff, 0x88, 0x00))
+        );
+        // Unconfigured categories fall back to the default style
+        assert_eq!(list.category_color("Dairy", &theme), None);
+    }
+
     #[test]
     fn test_theme_loader() {
         let loader = ThemeLoader::new();
@@ -1221,4 +1331,78 @@ mod tests {
             Some("#cdd6f4".to_string())
         );
     }
+
+    #[test]
+    fn test_saved_theme_loads_and_validates() {
+        let dir = tempfile::tempdir().unwrap();
+        let loader = ThemeLoader::with_config(Some(dir.path().to_path_buf()));
+        assert_eq!(loader.user_themes_dir(), Some(dir.path()));
+
+        let mut theme = loader.load_theme("base16-nord").unwrap();
+        theme.scheme = "my-nord".to_string();
+        theme.name = Some("My Nord".to_string());
+        let overrides = ThemeOverrides {
+            palette: BTreeMap::from([("base08".to_string(), "#ff0000".to_string())]),
+            semantic: Some(BTreeMap::from([(
+                "primary".to_string(),
+                "base0E".to_string(),
+            )])),
+        };
+        let theme = loader.apply_overrides(theme, &overrides).unwrap();
+
+        let path = loader.save_theme_in(dir.path(), &theme, false).unwrap();
+        assert_eq!(path, dir.path().join("my-nord.toml"));
+        let loaded = loader.load_theme("my-nord").unwrap();
+        assert_eq!(loaded.name.as_deref(), Some("My Nord"));
+        assert_eq!(loaded.palette.base08.as_deref(), Some("#ff0000"));
+        assert_eq!(loaded.semantic.primary, "base0E");
+        assert_eq!(loaded.palette.base00, theme.palette.base00);
+        assert!(loader.list_themes().contains(&"my-nord".to_string()));
+
+        // Existing files are kept unless overwriting, invalid themes never written
+        assert!(loader.save_theme_in(dir.path(), &theme, false).is_err());
+        assert!(loader.save_theme_in(dir.path(), &theme, true).is_ok());
+        let mut broken = theme.clone();
+        broken.scheme = "broken".to_string();
+        broken.palette.base03 = Some("grey".to_string());
+        assert!(loader.save_theme_in(dir.path(), &broken, false).is_err());
+        assert!(!dir.path().join("broken.toml").exists());
+    }
+
+    #[test]
+    fn test_resolve_for_variant_picks_child_theme_or_falls_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let loader = ThemeLoader::with_config(Some(dir.path().to_path_buf()));
+        let mut theme = loader.load_theme("base16-default-dark").unwrap();
+        theme.scheme = "default".to_string();
+
+        // Without variants the base theme is used for either appearance
+        for variant in [ThemeVariant::Light, ThemeVariant::Dark] {
+            let resolved = theme.resolve_for_variant(variant, &loader).unwrap();
+            assert_eq!(resolved.scheme, "default");
+        }
+
+        theme.variants = Some(ThemeVariants {
+            light: Some("base16-default-light".to_string()),
+            dark: None,
+        });
+        let light = theme
+            .resolve_for_variant(ThemeVariant::Light, &loader)
+            .unwrap();
+        assert_eq!(light.scheme, "base16-default-light");
+        assert_eq!(light.variant, Some(ThemeVariant::Light));
+        // A missing variant falls back to the base theme
+        let dark = theme
+            .resolve_for_variant(ThemeVariant::Dark, &loader)
+            .unwrap();
+        assert_eq!(dark.scheme, "default");
+
+        theme.variants = Some(ThemeVariants {
+            light: None,
+            dark: Some("no-such-theme".to_string()),
+        });
+        assert!(theme
+            .resolve_for_variant(ThemeVariant::Dark, &loader)
+            .is_err());
+    }
 }
@@ -49,8 +49,11 @@ impl ServerHandler for MyServerHandler {
         match tool_params {
             LstTools::ListListsTool(list_list_tool) => list_list_tool.call_tool(),
             LstTools::AddToListTool(add_to_list_tool) => add_to_list_tool.call_tool().await,
+            LstTools::AddListItemTool(add_list_item_tool) => add_list_item_tool.call_tool(),
             LstTools::MarkDoneTool(mark_done_tool) => mark_done_tool.call_tool().await,
             LstTools::MarkUndoneTool(mark_undone_tool) => mark_undone_tool.call_tool().await,
+            LstTools::ToggleListItemTool(toggle_list_item_tool) => toggle_list_item_tool.call_tool(),
+            LstTools::GetListTool(get_list_tool) => get_list_tool.call_tool(),
         }
     }
 }
@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod tests {
     use super::super::tools::*;
-    use lst_core::storage;
+    use lst_core::{storage, ItemStatus};
     use std::fs;
     use std::path::PathBuf;
 
@@ -90,6 +90,87 @@ mod tests {
         let _ = fs::remove_file(lists_dir.join("test_mcp_shopping2.md"));
     }
 
+    #[test]
+    fn test_add_list_item_under_category() {
+        let tool = AddListItemTool {
+            list: "test_mcp_add_item".to_string(),
+            text: "oat milk".to_string(),
+            category: Some("Dairy".to_string()),
+        };
+
+        let result = tool.call_tool();
+        assert!(result.is_ok(), "Failed to add item: {:?}", result.err());
+
+        let list = storage::markdown::load_list("test_mcp_add_item").unwrap();
+        let dairy = list
+            .categories
+            .iter()
+            .find(|c| c.name == "Dairy")
+            .expect("category was not created");
+        assert!(dairy.items.iter().any(|item| item.text == "oat milk"));
+
+        // Clean up
+        let lists_dir = get_test_lists_dir();
+        let _ = fs::remove_file(lists_dir.join("test_mcp_add_item.md"));
+    }
+
+    #[test]
+    fn test_add_list_item_rejects_invalid_list_name() {
+        for list in ["", "  ", "../outside", "/tmp/absolute"] {
+            let tool = AddListItemTool {
+                list: list.to_string(),
+                text: "milk".to_string(),
+                category: None,
+            };
+            assert!(tool.call_tool().is_err(), "Should reject list name '{}'", list);
+        }
+    }
+
+    #[test]
+    fn test_toggle_list_item_by_anchor() {
+        let lists_dir = get_test_lists_dir();
+        let list_path = lists_dir.join("test_mcp_toggle.md");
+        fs::write(&list_path, "- [ ] oat milk  ^oatmk\n- [ ] almond milk  ^almnd\n").unwrap();
+
+        let tool = ToggleListItemTool {
+            list: "test_mcp_toggle".to_string(),
+            target: "^almnd".to_string(),
+        };
+        let result = tool.call_tool();
+        assert!(result.is_ok(), "Failed to toggle item: {:?}", result.err());
+
+        let list = storage::markdown::load_list("test_mcp_toggle").unwrap();
+        let statuses: Vec<ItemStatus> = list.all_items().map(|i| i.status.clone()).collect();
+        assert_eq!(statuses, [ItemStatus::Todo, ItemStatus::Done]);
+
+        let get = GetListTool {
+            list: "test_mcp_toggle".to_string(),
+        };
+        assert!(get.call_tool().is_ok());
+
+        // Clean up
+        let _ = fs::remove_file(&list_path);
+    }
+
+    #[test]
+    fn test_toggle_list_item_ambiguous_text() {
+        let lists_dir = get_test_lists_dir();
+        let list_path = lists_dir.join("test_mcp_toggle_ambiguous.md");
+        fs::write(&list_path, "- [ ] oat milk  ^oatmk\n- [ ] almond milk  ^almnd\n").unwrap();
+
+        let tool = ToggleListItemTool {
+            list: "test_mcp_toggle_ambiguous".to_string(),
+            target: "milk".to_string(),
+        };
+        assert!(tool.call_tool().is_err(), "Should fail for ambiguous target");
+
+        let list = storage::markdown::load_list("test_mcp_toggle_ambiguous").unwrap();
+        assert!(list.all_items().all(|i| i.status == ItemStatus::Todo));
+
+        // Clean up
+        let _ = fs::remove_file(&list_path);
+    }
+
     #[tokio::test]
     async fn test_mark_done() {
         let lists_dir = get_test_lists_dir();
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use lst_core::{commands, storage};
+use lst_core::{commands, storage, Config};
 use rust_mcp_sdk::schema::{schema_utils::CallToolError, CallToolResult, TextContent};
 use rust_mcp_sdk::{
     macros::{mcp_tool, JsonSchema},
@@ -98,6 +98,185 @@ impl AddToListTool {
     }
 }
 
+//********************//
+//  AddListItemTool  //
+//********************//
+#[mcp_tool(
+    name = "add_list_item",
+    description = "adds a single item to a list, optionally under a category (heading); creates the list if it does not yet exist and returns the list's items as JSON",
+    idempotent_hint = false,
+    destructive_hint = false,
+    open_world_hint = false,
+    read_only_hint = false
+)]
+#[derive(Debug, ::serde::Deserialize, ::serde::Serialize, JsonSchema)]
+pub struct AddListItemTool {
+    /// The name of the list to add the item to, may include directories (e.g. work/todo).
+    pub list: String,
+    /// The text of the item to add.
+    pub text: String,
+    /// The category (heading) to add the item under; uses the list's default if omitted.
+    pub category: Option<String>,
+}
+
+impl AddListItemTool {
+    pub fn call_tool(&self) -> Result<CallToolResult, CallToolError> {
+        tracing::debug!("AddListItemTool: Adding '{}' to list '{}'", self.text, self.list);
+
+        let list_name = validate_list_name(&self.list)?;
+        let text = self.text.trim();
+        if text.is_empty() {
+            return Err(tool_error("Item text must not be empty".to_string()));
+        }
+
+        match self.add(&list_name, text) {
+            Ok(json) => {
+                tracing::info!("AddListItemTool: Added '{}' to list '{}'", text, list_name);
+                Ok(CallToolResult::text_content(vec![TextContent::new(
+                    json.to_string(),
+                    None,
+                    None,
+                )]))
+            }
+            Err(e) => {
+                tracing::error!("AddListItemTool: Failed to add item: {}", e);
+                Err(tool_error(format!("Failed to add item: {}", e)))
+            }
+        }
+    }
+
+    fn add(&self, list_name: &str, text: &str) -> Result<serde_json::Value> {
+        if storage::markdown::load_list(list_name).is_err() {
+            storage::markdown::create_list(list_name)?;
+        }
+        let category = self
+            .category
+            .as_deref()
+            .map(str::trim)
+            .filter(|c| !c.is_empty());
+        let item = storage::markdown::add_item_to_category(list_name, text, category, None)?;
+        let list = storage::markdown::load_list(list_name)?;
+        Ok(serde_json::json!({
+            "list": list_name,
+            "added": item,
+            "uncategorized_items": list.uncategorized_items,
+            "categories": list.categories,
+        }))
+    }
+}
+
+//***********************//
+//  ToggleListItemTool  //
+//***********************//
+#[mcp_tool(
+    name = "toggle_list_item",
+    description = "toggles a single item in a list between todo and done and returns its new status; fails if the target matches more than one item",
+    idempotent_hint = false,
+    destructive_hint = false,
+    open_world_hint = false,
+    read_only_hint = false
+)]
+#[derive(Debug, ::serde::Deserialize, ::serde::Serialize, JsonSchema)]
+pub struct ToggleListItemTool {
+    /// The name of the list containing the item.
+    pub list: String,
+    /// The item to toggle (anchor like ^abc12, exact text, #index, or a fuzzy query).
+    pub target: String,
+}
+
+impl ToggleListItemTool {
+    pub fn call_tool(&self) -> Result<CallToolResult, CallToolError> {
+        tracing::debug!("ToggleListItemTool: Toggling '{}' in list '{}'", self.target, self.list);
+
+        let list_name = validate_list_name(&self.list)?;
+        let result = Config::load().and_then(|config| {
+            storage::markdown::toggle_item(&list_name, &self.target, config.fuzzy.threshold)
+        });
+
+        match result {
+            Ok(item) => {
+                tracing::info!("ToggleListItemTool: '{}' in list '{}' is now {:?}", item.text, list_name, item.status);
+                let json = serde_json::json!({
+                    "list": list_name,
+                    "text": item.text,
+                    "anchor": item.anchor,
+                    "status": item.status,
+                });
+                Ok(CallToolResult::text_content(vec![TextContent::new(
+                    json.to_string(),
+                    None,
+                    None,
+                )]))
+            }
+            Err(e) => {
+                tracing::error!("ToggleListItemTool: Failed to toggle item: {}", e);
+                Err(tool_error(format!("Failed to toggle item: {}", e)))
+            }
+        }
+    }
+}
+
+//****************//
+//  GetListTool  //
+//****************//
+#[mcp_tool(
+    name = "get_list",
+    description = "returns the full structure of a list (metadata, uncategorized items and categories with their items, status and anchors) as JSON",
+    idempotent_hint = true,
+    destructive_hint = false,
+    open_world_hint = false,
+    read_only_hint = true
+)]
+#[derive(Debug, ::serde::Deserialize, ::serde::Serialize, JsonSchema)]
+pub struct GetListTool {
+    /// The name of the list to read.
+    pub list: String,
+}
+
+impl GetListTool {
+    pub fn call_tool(&self) -> Result<CallToolResult, CallToolError> {
+        tracing::debug!("GetListTool: Reading list '{}'", self.list);
+
+        let list_name = validate_list_name(&self.list)?;
+        let result = storage::markdown::load_list(&list_name)
+            .and_then(|list| Ok(serde_json::to_string(&list)?));
+
+        match result {
+            Ok(json) => Ok(CallToolResult::text_content(vec![TextContent::new(
+                json,
+                None,
+                None,
+            )])),
+            Err(e) => {
+                tracing::error!("GetListTool: Failed to read list: {}", e);
+                Err(tool_error(format!("Failed to read list '{}': {}", list_name, e)))
+            }
+        }
+    }
+}
+
+/// Reject list names that are empty or would escape the lists directory
+fn validate_list_name(list: &str) -> Result<String, CallToolError> {
+    let name = list.trim().trim_end_matches(".md");
+    let path = std::path::Path::new(name);
+    let escapes = path.is_absolute()
+        || path
+            .components()
+            .any(|c| !matches!(c, std::path::Component::Normal(_)));
+    if name.is_empty() || escapes {
+        tracing::error!("Invalid list name '{}'", list);
+        return Err(tool_error(format!(
+            "Invalid list name '{}': use a relative name like 'groceries' or 'work/todo'",
+            list
+        )));
+    }
+    Ok(name.to_string())
+}
+
+fn tool_error(message: String) -> CallToolError {
+    CallToolError::new(std::io::Error::new(std::io::ErrorKind::Other, message))
+}
+
 //******************//
 //  MarkDoneTool   //
 //******************//
@@ -190,5 +369,13 @@ impl MarkUndoneTool {
 // Generates an enum names LstTools, with all tool variants
 tool_box!(
     LstTools,
-    [ListListsTool, AddToListTool, MarkDoneTool, MarkUndoneTool]
+    [
+        ListListsTool,
+        AddToListTool,
+        AddListItemTool,
+        MarkDoneTool,
+        MarkUndoneTool,
+        ToggleListItemTool,
+        GetListTool
+    ]
 );
@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
 /// Information about a document stored on the server
@@ -16,7 +17,21 @@ pub enum ClientMessage {
     Authenticate {
         jwt: String,
     },
-    RequestDocumentList,
+    /// Ask for the documents the user can access, oldest `updated_at` first.
+    /// `after` and `after_updated_at` are the `doc_id` and `updated_at` of the
+    /// last document of the previous page; a page holds at most `limit`
+    /// (default and cap [`MAX_DOCUMENT_PAGE`]) documents, so a shorter page is
+    /// the last one
+    RequestDocumentList {
+        #[serde(default)]
+        after: Option<Uuid>,
+        /// Missing from older clients; the server then looks up the current
+        /// `updated_at` of `after`
+        #[serde(default)]
+        after_updated_at: Option<DateTime<Utc>>,
+        #[serde(default)]
+        limit: Option<u32>,
+    },
     RequestSnapshot {
         doc_id: Uuid,
     },
@@ -29,9 +44,51 @@ pub enum ClientMessage {
         doc_id: Uuid,
         filename: String,
         snapshot: Vec<u8>,
+        /// [`snapshot_hash`] of `snapshot`; missing from older clients
+        #[serde(default)]
+        hash: Option<String>,
+    },
+    /// Delete a document the user owns along with its stored changes, e.g.
+    /// because it was moved and now syncs under the doc id of its new path.
+    /// Nothing is sent back unless the request is refused
+    DeleteDocument {
+        doc_id: Uuid,
+    },
+    /// Liveness probe; the server answers with a `Pong` carrying the same `id`
+    Ping {
+        id: u64,
     },
 }
 
+/// Largest `DocumentList` page the server sends, also used when a client
+/// does not ask for a `limit`
+pub const MAX_DOCUMENT_PAGE: u32 = 10_000;
+
+impl ClientMessage {
+    /// Request for the page of at most `limit` documents following `last`, the
+    /// last document of the previous page (`None` for the first page)
+    pub fn document_page_after(last: Option<&DocumentInfo>, limit: Option<u32>) -> Self {
+        ClientMessage::RequestDocumentList {
+            after: last.map(|doc| doc.doc_id),
+            after_updated_at: last.map(|doc| doc.updated_at),
+            limit,
+        }
+    }
+
+    /// Parse a client message, also accepting the bare `"RequestDocumentList"`
+    /// sent by clients from before document list paging
+    pub fn from_json(text: &str) -> serde_json::Result<Self> {
+        if text.trim() == "\"RequestDocumentList\"" {
+            return Ok(ClientMessage::RequestDocumentList {
+                after: None,
+                after_updated_at: None,
+                limit: None,
+            });
+        }
+        serde_json::from_str(text)
+    }
+}
+
 /// Messages sent from the server to the client
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ServerMessage {
@@ -45,6 +102,9 @@ pub enum ServerMessage {
         doc_id: Uuid,
         filename: String,
         snapshot: Vec<u8>,
+        /// [`snapshot_hash`] of `snapshot`; missing from older servers
+        #[serde(default)]
+        hash: Option<String>,
     },
     NewChanges {
         doc_id: Uuid,
@@ -54,4 +114,83 @@ pub enum ServerMessage {
     RequestCompaction {
         doc_id: Uuid,
     },
+    Pong {
+        id: u64,
+    },
+    /// A request was rejected; `code` is one of the [`error_code`] constants.
+    /// Clients must ignore variants they do not know, so older clients simply
+    /// skip this message
+    Error {
+        doc_id: Option<Uuid>,
+        code: String,
+        message: String,
+    },
+}
+
+/// Checksum carried along with snapshots: the hex encoded SHA-256 of the
+/// (encrypted) snapshot bytes
+pub fn snapshot_hash(snapshot: &[u8]) -> String {
+    hex::encode(Sha256::digest(snapshot))
+}
+
+/// Whether `snapshot` arrived intact. Snapshots from peers that predate
+/// checksums carry no `hash` and are accepted as they are
+pub fn verify_snapshot(snapshot: &[u8], hash: Option<&str>) -> bool {
+    hash.is_none_or(|hash| hash.eq_ignore_ascii_case(&snapshot_hash(snapshot)))
+}
+
+/// Machine readable codes carried by [`ServerMessage::Error`]
+pub mod error_code {
+    /// The JWT is missing, invalid or expired; refresh it and reconnect
+    pub const UNAUTHORIZED: &str = "unauthorized";
+    /// The user may not read or write the document
+    pub const FORBIDDEN: &str = "forbidden";
+    /// The document does not exist on the server
+    pub const NOT_FOUND: &str = "not_found";
+    /// The client message could not be parsed
+    pub const MALFORMED: &str = "malformed";
+    /// A pushed snapshot does not match the hash sent with it
+    pub const CHECKSUM_MISMATCH: &str = "checksum_mismatch";
+    /// The server failed to process an otherwise valid request
+    pub const INTERNAL: &str = "internal";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_document_list_request_still_parses() {
+        for text in [
+            "\"RequestDocumentList\"",
+            r#"{"RequestDocumentList":{}}"#,
+            r#"{"RequestDocumentList":{"after":null,"limit":null}}"#,
+        ] {
+            assert!(matches!(
+                ClientMessage::from_json(text).unwrap(),
+                ClientMessage::RequestDocumentList {
+                    after: None,
+                    after_updated_at: None,
+                    limit: None
+                }
+            ));
+        }
+    }
+
+    #[test]
+    fn test_snapshot_hash_detects_corruption_and_is_optional() {
+        let snapshot = b"automerge bytes".to_vec();
+        let hash = snapshot_hash(&snapshot);
+        assert!(verify_snapshot(&snapshot, Some(&hash)));
+        assert!(verify_snapshot(&snapshot, Some(&hash.to_uppercase())));
+        assert!(!verify_snapshot(&snapshot[..10], Some(&hash)));
+        assert!(verify_snapshot(&snapshot[..10], None));
+
+        // Messages from peers without checksums still parse
+        let legacy = r#"{"Snapshot":{"doc_id":"67e55044-10b1-426f-9247-bb680e5fe0c8","filename":"f","snapshot":[1,2]}}"#;
+        assert!(matches!(
+            serde_json::from_str::<ServerMessage>(legacy).unwrap(),
+            ServerMessage::Snapshot { hash: None, .. }
+        ));
+    }
 }
@@ -25,6 +25,20 @@ pub struct ServerSettings {
     /// Port to listen on, e.g. 3000
     #[serde(default = "default_port")]
     pub port: u16,
+    /// Secret used to sign and verify JWTs
+    #[serde(default)]
+    pub jwt_secret: Option<String>,
+    /// Lifetime of issued JWTs in minutes, e.g. 60
+    #[serde(default = "default_jwt_expiry_minutes")]
+    pub jwt_expiry_minutes: u32,
+    /// Stored changes per document after which clients are asked to push a
+    /// compacted snapshot, e.g. 200 (0 disables compaction requests)
+    #[serde(default = "default_compaction_threshold")]
+    pub compaction_threshold: u32,
+    /// URL that login links are POSTed to as JSON (e.g. a Slack or Telegram
+    /// relay), instead of only printing them to the console
+    #[serde(default)]
+    pub webhook_url: Option<String>,
 }
 
 fn default_host() -> String {
@@ -35,6 +49,14 @@ fn default_port() -> u16 {
     5673
 }
 
+fn default_jwt_expiry_minutes() -> u32 {
+    60
+}
+
+fn default_compaction_threshold() -> u32 {
+    200
+}
+
 /// Path settings shared with CLI
 #[derive(Debug, Deserialize, Clone)]
 #[allow(dead_code)]
@@ -85,6 +107,10 @@ impl Default for ServerSettings {
         Self {
             host: default_host(),
             port: default_port(),
+            jwt_secret: None,
+            jwt_expiry_minutes: default_jwt_expiry_minutes(),
+            compaction_threshold: default_compaction_threshold(),
+            webhook_url: None,
         }
     }
 }
@@ -131,15 +157,44 @@ impl Settings {
     }
 }
 
+pub use lst_core::config::JWT_SECRET_ENV;
+
+/// Well-known signing secret, only accepted with `--dev`
+const DEV_JWT_SECRET: &str = "lst-jwt-demo-secret-goes-here";
+
+impl ServerSettings {
+    /// Resolve the JWT signing secret: `env_secret` (from [`JWT_SECRET_ENV`])
+    /// wins over the config file; the dev fallback is only used when `dev` is set
+    pub fn jwt_secret(&self, env_secret: Option<String>, dev: bool) -> anyhow::Result<Vec<u8>> {
+        let configured = env_secret
+            .or_else(|| self.jwt_secret.clone())
+            .filter(|secret| !secret.trim().is_empty());
+        match configured {
+            Some(secret) => Ok(secret.into_bytes()),
+            None if dev => {
+                eprintln!("WARNING: using the built-in development JWT secret (--dev)");
+                Ok(DEV_JWT_SECRET.as_bytes().to_vec())
+            }
+            None => anyhow::bail!(
+                "No JWT secret configured. Set server.jwt_secret in the config file or {}, \
+                 or pass --dev to use an insecure development secret",
+                JWT_SECRET_ENV
+            ),
+        }
+    }
+}
+
+impl ServerSettings {
+    /// How long issued JWTs stay valid (at least one minute)
+    pub fn jwt_expiry(&self) -> chrono::Duration {
+        chrono::Duration::minutes(i64::from(self.jwt_expiry_minutes.max(1)))
+    }
+}
+
 impl DatabaseSettings {
-    /// Resolve the data directory path, expanding ~ to home directory
+    /// Resolve the data directory path, expanding `~` and environment variables
     pub fn resolve_data_dir(&self) -> anyhow::Result<PathBuf> {
-        if self.data_dir.starts_with("~/") {
-            let home_dir = dirs::home_dir().context("Could not determine home directory")?;
-            Ok(home_dir.join(&self.data_dir[2..]))
-        } else {
-            Ok(PathBuf::from(&self.data_dir))
-        }
+        Ok(lst_core::config::expand_path(Path::new(&self.data_dir)))
     }
 
     /// Get the full path to the tokens database
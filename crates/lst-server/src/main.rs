@@ -9,7 +9,7 @@ use argon2::{
 use axum::{
     extract::{
         ws::{Message as WsMessage, WebSocket, WebSocketUpgrade},
-        Path, Request, State,
+        Path, Query, Request, State,
     },
     http::{header, HeaderMap, StatusCode},
     middleware::{self, Next},
@@ -57,6 +57,25 @@ struct ContentResponse {
     path: Option<String>,
 }
 
+#[derive(Deserialize)]
+struct ContentPrefixQuery {
+    prefix: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ContentDeleteResponse {
+    message: String,
+    deleted: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ContentListEntry {
+    pub path: String,
+    pub updated_at: String,
+    /// Content length in bytes
+    pub size: i64,
+}
+
 // --- SQLite Token Store ---
 #[derive(Debug, Clone)]
 pub struct SqliteTokenStore {
@@ -178,6 +197,33 @@ impl SqliteTokenStore {
         }
     }
 
+    /// Stored email of the enabled user `email` (compared case-insensitively)
+    /// if their stored hash matches the client-side `password_hash`
+    pub async fn verify_user_password(
+        &self,
+        email: &str,
+        password_hash: &str,
+    ) -> Result<Option<String>, sqlx::Error> {
+        let Some(row) = sqlx::query(
+            "SELECT email, password_hash FROM users WHERE lower(email) = ? AND enabled != 0",
+        )
+        .bind(email.to_lowercase())
+        .fetch_optional(&self.pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let email: String = row.get(0);
+        let stored: String = row.get(1);
+        let verified = PasswordHash::new(&stored).is_ok_and(|parsed| {
+            Argon2::default()
+                .verify_password(password_hash.as_bytes(), &parsed)
+                .is_ok()
+        });
+        Ok(verified.then_some(email))
+    }
+
     pub async fn set_user(
         &self,
         email: &str,
@@ -356,7 +402,32 @@ impl SqliteTokenStore {
 
 type TokenStore = Arc<SqliteTokenStore>;
 // Auth tokens are now permanent - they're part of the encryption key derivation
-const JWT_SECRET: &[u8] = b"lst-jwt-demo-secret-goes-here";
+/// Secret used to sign and verify JWTs, resolved from the config at startup
+#[derive(Clone)]
+struct JwtSecret(Arc<Vec<u8>>);
+
+impl JwtSecret {
+    fn new(secret: Vec<u8>) -> Self {
+        Self(Arc::new(secret))
+    }
+
+    fn encoding_key(&self) -> EncodingKey {
+        EncodingKey::from_secret(&self.0)
+    }
+
+    fn decoding_key(&self) -> DecodingKey {
+        DecodingKey::from_secret(&self.0)
+    }
+
+    /// Sign a JWT for `user` that expires after `ttl`
+    fn issue(&self, user: &str, ttl: chrono::Duration) -> String {
+        let claims = Claims {
+            sub: user.to_string(),
+            exp: (chrono::Utc::now() + ttl).timestamp() as usize,
+        };
+        encode(&Header::default(), &claims, &self.encoding_key()).unwrap()
+    }
+}
 
 // --- SQLite Content Store ---
 #[derive(Debug, Clone)]
@@ -430,6 +501,17 @@ impl SqliteContentStore {
         .execute(&pool)
         .await?;
 
+        // Migrate existing content table: indexed frontmatter metadata
+        let _ = sqlx::query("ALTER TABLE content ADD COLUMN title TEXT")
+            .execute(&pool)
+            .await;
+        let _ = sqlx::query("ALTER TABLE content ADD COLUMN tags TEXT")
+            .execute(&pool)
+            .await;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_content_title ON content(title)")
+            .execute(&pool)
+            .await?;
+
         Ok(SqliteContentStore { pool })
     }
 
@@ -439,35 +521,41 @@ impl SqliteContentStore {
         item_path: &str,
         content: &str,
     ) -> Result<i64, sqlx::Error> {
+        let meta = ContentFrontmatter::parse(content);
         let result = sqlx::query(
             r#"
-            INSERT INTO content (kind, item_path, content)
-            VALUES (?, ?, ?)
+            INSERT INTO content (kind, item_path, content, title, tags)
+            VALUES (?, ?, ?, ?, ?)
             "#,
         )
         .bind(kind)
         .bind(item_path)
         .bind(content)
+        .bind(meta.title.clone())
+        .bind(meta.tags_column())
         .execute(&self.pool)
         .await?;
         Ok(result.last_insert_rowid())
     }
 
+    /// Read an item's content together with the time it was last written
     pub async fn read_content(
         &self,
         kind: &str,
         item_path: &str,
-    ) -> Result<Option<String>, sqlx::Error> {
+    ) -> Result<Option<(String, chrono::DateTime<chrono::Utc>)>, sqlx::Error> {
         let result: Option<sqlx::sqlite::SqliteRow> = sqlx::query(
             r#"
-            SELECT content FROM content WHERE kind = ? AND item_path = ?
+            SELECT content, updated_at FROM content WHERE kind = ? AND item_path = ?
             "#,
         )
         .bind(kind)
         .bind(item_path)
         .fetch_optional(&self.pool)
         .await?;
-        Ok(result.map(|row| row.get("content")))
+        result
+            .map(|row| Ok((row.try_get("content")?, row.try_get("updated_at")?)))
+            .transpose()
     }
 
     pub async fn update_content(
@@ -479,13 +567,16 @@ impl SqliteContentStore {
         // The trigger will handle updated_at if the content actually changes.
         // If only other fields were to change, we might need explicit updated_at here.
         // For this case, content is the main mutable part besides path/kind (which would be a new row).
+        let meta = ContentFrontmatter::parse(content);
         let result = sqlx::query(
             r#"
-            UPDATE content SET content = ?
+            UPDATE content SET content = ?, title = ?, tags = ?
             WHERE kind = ? AND item_path = ?
             "#,
         )
         .bind(content)
+        .bind(meta.title.clone())
+        .bind(meta.tags_column())
         .bind(kind)
         .bind(item_path)
         .execute(&self.pool)
@@ -493,6 +584,55 @@ impl SqliteContentStore {
         Ok(result.rows_affected())
     }
 
+    /// List every item of `kind` whose path starts with `prefix` (pass "" for
+    /// all of them), ordered by path
+    pub async fn list_content(
+        &self,
+        kind: &str,
+        prefix: &str,
+    ) -> Result<Vec<ContentListEntry>, sqlx::Error> {
+        let rows = sqlx::query(
+            r#"
+            SELECT item_path, CAST(updated_at AS TEXT) AS updated_at,
+                   length(CAST(content AS BLOB)) AS size
+            FROM content WHERE kind = ? AND substr(item_path, 1, length(?)) = ?
+            ORDER BY item_path
+            "#,
+        )
+        .bind(kind)
+        .bind(prefix)
+        .bind(prefix)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| ContentListEntry {
+                path: row.get("item_path"),
+                updated_at: row.get("updated_at"),
+                size: row.get("size"),
+            })
+            .collect())
+    }
+
+    /// Delete every item of `kind` whose path starts with `prefix`
+    pub async fn delete_content_prefix(
+        &self,
+        kind: &str,
+        prefix: &str,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM content WHERE kind = ? AND substr(item_path, 1, length(?)) = ?
+            "#,
+        )
+        .bind(kind)
+        .bind(prefix)
+        .bind(prefix)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
     pub async fn delete_content(&self, kind: &str, item_path: &str) -> Result<u64, sqlx::Error> {
         let result = sqlx::query(
             r#"
@@ -509,10 +649,45 @@ impl SqliteContentStore {
 
 type ContentStore = Arc<SqliteContentStore>;
 
+/// Metadata extracted from a markdown document's YAML frontmatter for indexing.
+/// The full content is always stored unchanged; these fields stay NULL when the
+/// document has no (parsable) frontmatter.
+#[derive(Debug, Default, Deserialize)]
+struct ContentFrontmatter {
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+impl ContentFrontmatter {
+    fn parse(content: &str) -> Self {
+        let Some(rest) = content.strip_prefix("---") else {
+            return Self::default();
+        };
+        let Some(end) = rest.find("\n---") else {
+            return Self::default();
+        };
+        serde_yaml::from_str(&rest[..end]).unwrap_or_default()
+    }
+
+    /// Tags are stored comma-separated so they can be matched with `LIKE`
+    fn tags_column(&self) -> Option<String> {
+        if self.tags.is_empty() {
+            None
+        } else {
+            Some(self.tags.join(","))
+        }
+    }
+}
+
 #[derive(Clone)]
 struct AppState {
     db: sync_db::SyncDb,
     tx: broadcast::Sender<(String, lst_proto::ServerMessage)>,
+    jwt_secret: JwtSecret,
+    /// Stored changes per document that trigger a `RequestCompaction`
+    compaction_threshold: u32,
 }
 
 #[derive(Deserialize)]
@@ -527,6 +702,53 @@ struct AuthResponse {
     status: String,
 }
 
+/// JSON body POSTed to `server.webhook_url` for every login request:
+///
+/// ```json
+/// {
+///   "email": "user@example.com",
+///   "token": "APPLE-RIVER-STONE-1234",
+///   "login_url": "lst-login://host/auth/verify?token=...&email=...",
+///   "text": "lst login for user@example.com: lst-login://..."
+/// }
+/// ```
+///
+/// `text` is a ready-made message, the field Slack-style incoming webhooks show
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct LoginWebhookPayload {
+    email: String,
+    token: String,
+    login_url: String,
+    text: String,
+}
+
+/// Delivers login links to the configured `server.webhook_url`
+#[derive(Clone)]
+struct LoginWebhook {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl LoginWebhook {
+    fn new(url: String) -> Self {
+        Self {
+            url,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn send(&self, payload: &LoginWebhookPayload) -> anyhow::Result<()> {
+        self.client
+            .post(&self.url)
+            .json(payload)
+            .timeout(std::time::Duration::from_secs(10))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
 #[derive(Parser)]
 #[command(name = "lst-server", about = "lst server API and admin CLI")]
 struct Args {
@@ -534,6 +756,9 @@ struct Args {
     command: Option<Commands>,
     #[arg(long, default_value = "~/.config/lst/config.toml")]
     config: String,
+    /// Fall back to a built-in, insecure JWT secret when none is configured
+    #[arg(long)]
+    dev: bool,
 }
 
 #[derive(Subcommand)]
@@ -600,7 +825,7 @@ async fn main() {
     match args.command {
         Some(Commands::Serve) | None => {
             // Start server (default behavior)
-            start_server(config_file_path_str).await;
+            start_server(config_file_path_str, args.dev).await;
         }
         Some(Commands::User { command }) => {
             // Handle user management commands
@@ -641,12 +866,32 @@ fn load_merged_settings(config_file_path: &PathBuf) -> anyhow::Result<Settings>
     if let Some(port) = cli_config.server.port {
         settings.server.port = port;
     }
+    if let Some(ref secret) = cli_config.server.jwt_secret {
+        settings.server.jwt_secret = Some(secret.clone());
+    }
+    if let Some(minutes) = cli_config.server.jwt_expiry_minutes {
+        settings.server.jwt_expiry_minutes = minutes;
+    }
+    if let Some(threshold) = cli_config.server.compaction_threshold {
+        settings.server.compaction_threshold = threshold;
+    }
 
     Ok(settings)
 }
 
-async fn start_server(config_file_path: PathBuf) {
+async fn start_server(config_file_path: PathBuf, dev: bool) {
     let settings = Arc::new(load_merged_settings(&config_file_path).unwrap());
+    let jwt_secret = match settings
+        .server
+        .jwt_secret(std::env::var(config::JWT_SECRET_ENV).ok(), dev)
+    {
+        Ok(secret) => JwtSecret::new(secret),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let jwt_expiry = settings.server.jwt_expiry();
 
     // Get database paths from configuration
     let tokens_db_path = settings
@@ -678,7 +923,12 @@ async fn start_server(config_file_path: PathBuf) {
         .await
         .expect("Failed to initialize sync db");
     let (tx, _) = broadcast::channel(100);
-    let app_state = Arc::new(AppState { db: sync_db, tx });
+    let app_state = Arc::new(AppState {
+        db: sync_db,
+        tx,
+        jwt_secret: jwt_secret.clone(),
+        compaction_threshold: settings.server.compaction_threshold,
+    });
 
     // Router for content API (protected)
     // The handlers (e.g., create_content_handler) will be updated next to accept ContentStore
@@ -691,12 +941,23 @@ async fn start_server(config_file_path: PathBuf) {
                 move |Json(payload)| create_content_handler(Json(payload), store)
             }),
         )
+        .route(
+            "/{kind}",
+            get({
+                let store = content_store.clone();
+                move |path, query| list_content_handler(path, query, store)
+            })
+            .delete({
+                let store = content_store.clone();
+                move |path, query| delete_content_prefix_handler(path, query, store)
+            }),
+        )
         .route(
             "/{kind}/{*path}",
             get({
                 let store = content_store.clone();
                 // Signature of read_content_handler will change
-                move |path| read_content_handler(path, store)
+                move |path, headers| read_content_handler(path, headers, store)
             })
             .put({
                 let store = content_store.clone();
@@ -709,7 +970,10 @@ async fn start_server(config_file_path: PathBuf) {
                 move |path| delete_content_handler(path, store)
             }),
         )
-        .layer(middleware::from_fn(jwt_auth_middleware));
+        .layer(middleware::from_fn_with_state(
+            jwt_secret.clone(),
+            jwt_auth_middleware,
+        ));
 
     let api_router =
         Router::new()
@@ -718,14 +982,24 @@ async fn start_server(config_file_path: PathBuf) {
                 "/auth/request",
                 post({
                     let ts = token_store.clone();
-                    move |j| auth_request_handler(j, ts)
+                    let webhook = settings.server.webhook_url.clone().map(LoginWebhook::new);
+                    move |j| auth_request_handler(j, ts, webhook)
                 }),
             )
             .route(
                 "/auth/verify",
                 post({
                     let ts = token_store.clone();
-                    move |j| auth_verify_handler(j, ts)
+                    let secret = jwt_secret.clone();
+                    move |j| auth_verify_handler(j, ts, secret, jwt_expiry)
+                }),
+            )
+            .route(
+                "/auth/refresh",
+                post({
+                    let ts = token_store.clone();
+                    let secret = jwt_secret.clone();
+                    move |j| auth_refresh_handler(j, ts, secret, jwt_expiry)
                 }),
             )
             .nest("/content", content_api_router)
@@ -758,9 +1032,13 @@ async fn health_handler() -> &'static str {
     "OK"
 }
 
+/// Register a new user and hand out their login token: through `webhook`
+/// when one is configured, printed with a QR code to the console otherwise
+/// (or when the webhook can't be reached)
 async fn auth_request_handler(
     Json(req): Json<AuthRequest>,
     token_store: TokenStore,
+    webhook: Option<LoginWebhook>,
 ) -> Result<Json<AuthResponse>, (StatusCode, String)> {
     // verify or create user
     let params = Params::new(128 * 1024, 3, 2, None).expect("invalid params");
@@ -823,6 +1101,23 @@ async fn auth_request_handler(
         urlencoding::encode(&token),
         urlencoding::encode(&req.email)
     );
+    if let Some(webhook) = webhook {
+        let payload = LoginWebhookPayload {
+            text: format!("lst login for {}: {}", req.email, login_url),
+            email: req.email.clone(),
+            token: token.clone(),
+            login_url: login_url.clone(),
+        };
+        match webhook.send(&payload).await {
+            Ok(()) => {
+                println!("Sent login link for {} to the login webhook", req.email);
+                return Ok(Json(AuthResponse {
+                    status: "ok".to_string(),
+                }));
+            }
+            Err(e) => eprintln!("Login webhook failed, printing the token instead: {e:#}"),
+        }
+    }
     let code = QrCode::new(login_url.as_bytes()).unwrap();
     let qr_string = code.render::<unicode::Dense1x2>().build();
     println!("Authentication token for {}: {}", req.email, token);
@@ -858,6 +1153,8 @@ struct VerifyRequest {
 struct VerifyResponse {
     jwt: String,
     user: String,
+    /// Seconds until `jwt` expires
+    expires_in: i64,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)] // Added Clone here for the middleware
@@ -869,29 +1166,60 @@ struct Claims {
 async fn auth_verify_handler(
     Json(req): Json<VerifyRequest>,
     token_store: TokenStore,
+    jwt_secret: JwtSecret,
+    jwt_expiry: chrono::Duration,
 ) -> Result<Json<VerifyResponse>, (StatusCode, String)> {
     match token_store.verify(&req.email, &req.token).await {
         Ok(true) => {
-            let exp = (chrono::Utc::now() + chrono::Duration::hours(1)).timestamp() as usize;
-            let claims = Claims {
-                sub: req.email.to_lowercase(),
-                exp,
-            };
-            let jwt = encode(
-                &Header::default(),
-                &claims,
-                &EncodingKey::from_secret(JWT_SECRET),
-            )
-            .unwrap();
+            let user = req.email.to_lowercase();
             Ok(Json(VerifyResponse {
-                jwt,
-                user: req.email.to_lowercase(),
+                jwt: jwt_secret.issue(&user, jwt_expiry),
+                user,
+                expires_in: jwt_expiry.num_seconds(),
             }))
         }
         Ok(false) | Err(_) => Err((StatusCode::UNAUTHORIZED, "Invalid or expired token".into())),
     }
 }
 
+#[derive(Deserialize)]
+struct RefreshRequest {
+    /// Account to refresh
+    email: String,
+    /// Client-side hashed password, as sent to `/auth/request`
+    password_hash: String,
+}
+
+/// Issue a fresh JWT for a client that still holds its password hash
+async fn auth_refresh_handler(
+    Json(req): Json<RefreshRequest>,
+    token_store: TokenStore,
+    jwt_secret: JwtSecret,
+    jwt_expiry: chrono::Duration,
+) -> Result<Json<VerifyResponse>, (StatusCode, String)> {
+    let user = token_store
+        .verify_user_password(&req.email, &req.password_hash)
+        .await
+        .map_err(|e| {
+            eprintln!("Failed to look up user for refresh: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to refresh token".to_string(),
+            )
+        })?
+        .ok_or((
+            StatusCode::UNAUTHORIZED,
+            "Unknown user or password hash".to_string(),
+        ))?;
+
+    let user = user.to_lowercase();
+    Ok(Json(VerifyResponse {
+        jwt: jwt_secret.issue(&user, jwt_expiry),
+        user,
+        expires_in: jwt_expiry.num_seconds(),
+    }))
+}
+
 // --- Content Management Handlers (SQLite based) ---
 
 async fn create_content_handler(
@@ -949,13 +1277,56 @@ async fn create_content_handler(
     }
 }
 
+/// Strong ETag for a content body: the quoted SHA-256 of its bytes
+fn content_etag(content: &str) -> String {
+    format!("\"{}\"", hex::encode(Sha256::digest(content.as_bytes())))
+}
+
+/// Format a timestamp as an HTTP date, e.g. "Sun, 06 Nov 1994 08:49:37 GMT"
+fn http_date(time: chrono::DateTime<chrono::Utc>) -> String {
+    time.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Whether the client's cached copy is still current. `If-None-Match` takes
+/// precedence; `If-Modified-Since` is only consulted without it and compares
+/// at the one-second precision of HTTP dates.
+fn not_modified(
+    request: &HeaderMap,
+    etag: &str,
+    updated_at: chrono::DateTime<chrono::Utc>,
+) -> bool {
+    if let Some(if_none_match) = request.get(header::IF_NONE_MATCH) {
+        let Ok(if_none_match) = if_none_match.to_str() else {
+            return false;
+        };
+        return if_none_match.split(',').map(str::trim).any(|candidate| {
+            candidate == "*" || candidate.strip_prefix("W/").unwrap_or(candidate) == etag
+        });
+    }
+    request
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| chrono::DateTime::parse_from_rfc2822(value).ok())
+        .is_some_and(|since| updated_at.timestamp() <= since.timestamp())
+}
+
 async fn read_content_handler(
     Path((kind, item_path)): Path<(String, String)>,
+    request: HeaderMap,
     store: ContentStore,
 ) -> Result<Response, (StatusCode, String)> {
     match store.read_content(&kind, &item_path).await {
-        Ok(Some(content)) => {
+        Ok(Some((content, updated_at))) => {
+            let etag = content_etag(&content);
             let mut headers = HeaderMap::new();
+            headers.insert(header::ETAG, etag.parse().unwrap());
+            headers.insert(
+                header::LAST_MODIFIED,
+                http_date(updated_at).parse().unwrap(),
+            );
+            if not_modified(&request, &etag, updated_at) {
+                return Ok((StatusCode::NOT_MODIFIED, headers).into_response());
+            }
             headers.insert(
                 header::CONTENT_TYPE,
                 "text/plain; charset=utf-8".parse().unwrap(),
@@ -973,6 +1344,45 @@ async fn read_content_handler(
     }
 }
 
+async fn list_content_handler(
+    Path(kind): Path<String>,
+    Query(query): Query<ContentPrefixQuery>,
+    store: ContentStore,
+) -> Result<Json<Vec<ContentListEntry>>, (StatusCode, String)> {
+    let prefix = query.prefix.unwrap_or_default();
+    match store.list_content(&kind, &prefix).await {
+        Ok(entries) => Ok(Json(entries)),
+        Err(e) => {
+            eprintln!("Failed to list content: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to list content.".to_string(),
+            ))
+        }
+    }
+}
+
+async fn delete_content_prefix_handler(
+    Path(kind): Path<String>,
+    Query(query): Query<ContentPrefixQuery>,
+    store: ContentStore,
+) -> Result<Json<ContentDeleteResponse>, (StatusCode, String)> {
+    let prefix = query.prefix.unwrap_or_default();
+    match store.delete_content_prefix(&kind, &prefix).await {
+        Ok(deleted) => Ok(Json(ContentDeleteResponse {
+            message: format!("Deleted {} item(s) under {}/{}", deleted, kind, prefix),
+            deleted,
+        })),
+        Err(e) => {
+            eprintln!("Failed to delete content: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to delete content.".to_string(),
+            ))
+        }
+    }
+}
+
 async fn update_content_handler(
     Path((kind, item_path)): Path<(String, String)>,
     Json(payload): Json<UpdateContentRequest>,
@@ -1037,14 +1447,241 @@ async fn ws_handler(
         .and_then(|h| h.to_str().ok())
         .and_then(|h| h.strip_prefix("Bearer "))
     {
-        let decoding_key = DecodingKey::from_secret(JWT_SECRET);
+        let decoding_key = state.jwt_secret.decoding_key();
         let validation = Validation::default();
         if let Ok(token_data) = decode::<Claims>(auth, &decoding_key, &validation) {
             let user = token_data.claims.sub.to_lowercase();
             return ws.on_upgrade(move |socket| handle_ws(socket, state, user));
         }
     }
-    (StatusCode::UNAUTHORIZED, "unauthorized").into_response()
+    ws.on_upgrade(reject_ws)
+}
+
+/// Tell a client with a missing or invalid JWT why it is disconnected, so it
+/// can refresh the token instead of treating the server as unreachable
+async fn reject_ws(mut socket: WebSocket) {
+    let messages = [
+        lst_proto::ServerMessage::Authenticated { success: false },
+        lst_proto::ServerMessage::Error {
+            doc_id: None,
+            code: lst_proto::error_code::UNAUTHORIZED.to_string(),
+            message: "Missing, invalid or expired JWT".to_string(),
+        },
+    ];
+    for msg in &messages {
+        let text = serde_json::to_string(msg).unwrap();
+        if socket.send(WsMessage::Text(text.into())).await.is_err() {
+            return;
+        }
+    }
+    let _ = socket.close().await;
+}
+
+/// Error reply for a client message that failed to parse, naming the document
+/// when the message carried a readable `doc_id`
+fn malformed_message_error(text: &str, err: &serde_json::Error) -> lst_proto::ServerMessage {
+    let doc_id = serde_json::from_str::<serde_json::Value>(text)
+        .ok()
+        .and_then(|value| {
+            let (_, body) = value.as_object()?.iter().next()?;
+            body.get("doc_id")?.as_str()?.parse().ok()
+        });
+    lst_proto::ServerMessage::Error {
+        doc_id,
+        code: lst_proto::error_code::MALFORMED.to_string(),
+        message: format!("Could not parse message: {}", err),
+    }
+}
+
+/// `Error` about `doc_id` to send back to the client
+fn doc_error(doc_id: uuid::Uuid, code: &str, message: String) -> lst_proto::ServerMessage {
+    lst_proto::ServerMessage::Error {
+        doc_id: Some(doc_id),
+        code: code.to_string(),
+        message,
+    }
+}
+
+/// Storage id of the document `user` calls `doc_id` (see
+/// [`sync_db::SyncDb::resolve`]) and the access they have to it
+async fn lookup_access(
+    state: &AppState,
+    user: &str,
+    doc_id: uuid::Uuid,
+) -> Result<(uuid::Uuid, sync_db::Access), lst_proto::ServerMessage> {
+    let lookup = async {
+        let stored = state.db.resolve(&doc_id, user).await?;
+        let access = state.db.access(&stored, user).await?;
+        anyhow::Ok((stored, access))
+    };
+    lookup.await.map_err(|e| {
+        eprintln!("Failed to look up access to doc {}: {}", doc_id, e);
+        doc_error(
+            doc_id,
+            lst_proto::error_code::INTERNAL,
+            "Failed to check document access".into(),
+        )
+    })
+}
+
+/// Check that `user` may read (or, with `write`, push to) `doc_id` and return
+/// the id the document is stored under. Returns the `Error` message to send
+/// back when the request is not allowed
+async fn authorize(
+    state: &AppState,
+    user: &str,
+    doc_id: uuid::Uuid,
+    write: bool,
+) -> Result<uuid::Uuid, lst_proto::ServerMessage> {
+    let error = |code: &str, message: String| doc_error(doc_id, code, message);
+    let (stored, access) = lookup_access(state, user, doc_id).await?;
+    if write && access.can_write() || !write && access.can_read() {
+        return Ok(stored);
+    }
+    if access == sync_db::Access::Unclaimed {
+        return Err(error(
+            lst_proto::error_code::NOT_FOUND,
+            format!("Unknown document {}", doc_id),
+        ));
+    }
+    let action = if write { "write to" } else { "read" };
+    eprintln!("Denied {} to {} doc {}", user, action, doc_id);
+    Err(error(
+        lst_proto::error_code::FORBIDDEN,
+        format!("Not allowed to {} document {}", action, doc_id),
+    ))
+}
+
+/// Serialize `msg` and queue it for the client, returning false once the
+/// connection is gone
+async fn send_to_client(
+    tx: &tokio::sync::mpsc::Sender<WsMessage>,
+    msg: &lst_proto::ServerMessage,
+) -> bool {
+    let text = serde_json::to_string(msg).unwrap();
+    tx.send(WsMessage::Text(text.into())).await.is_ok()
+}
+
+/// Store pushed changes and broadcast them to all devices of every user with
+/// access to the document, under the id each of them knows it by (devices
+/// filter out their own changes). Asks the devices of `user` to compact the
+/// document once its stored changes reach the configured threshold. Pushes
+/// from users without write access are rejected with the error to send back
+async fn store_changes(
+    state: &AppState,
+    user: &str,
+    doc_id: uuid::Uuid,
+    device_id: String,
+    changes: Vec<Vec<u8>>,
+) -> Result<(), lst_proto::ServerMessage> {
+    let stored_id = authorize(state, user, doc_id, true).await?;
+    // Ensure a document row exists so DocumentList can surface it even before a snapshot
+    if let Err(e) = state
+        .db
+        .ensure_document_exists(&stored_id, &doc_id, user)
+        .await
+    {
+        eprintln!("Failed to ensure document row: {}", e);
+    }
+    let added = changes.len();
+    let stored = match state.db.add_changes(&stored_id, &device_id, &changes).await {
+        Ok(stored) => Some(stored),
+        Err(e) => {
+            eprintln!("Failed to add changes: {}", e);
+            None
+        }
+    };
+    let members = state.db.members(&stored_id).await.unwrap_or_else(|e| {
+        eprintln!("Failed to look up members of doc {}: {}", doc_id, e);
+        vec![(user.to_string(), doc_id)]
+    });
+    for (member, known_as) in members {
+        let msg = lst_proto::ServerMessage::NewChanges {
+            doc_id: known_as,
+            from_device_id: device_id.clone(),
+            changes: changes.clone(),
+        };
+        if let Err(e) = state.tx.send((member, msg)) {
+            eprintln!("Failed to broadcast changes: {}", e);
+        }
+    }
+
+    if stored.is_some_and(|stored| {
+        sync_db::crossed_compaction_threshold(stored, added, state.compaction_threshold)
+    }) {
+        eprintln!("Requesting compaction of doc {} from {}", doc_id, user);
+        if let Err(e) = state.db.mark_compaction(&stored_id).await {
+            eprintln!("Failed to record compaction request: {}", e);
+        }
+        let msg = lst_proto::ServerMessage::RequestCompaction { doc_id };
+        if let Err(e) = state.tx.send((user.to_string(), msg)) {
+            eprintln!("Failed to broadcast compaction request: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Save a pushed snapshot; the stored changes covered by the last compaction
+/// request are dropped.
+/// Pushes from users without write access are rejected like in [`store_changes`]
+async fn store_snapshot(
+    state: &AppState,
+    user: &str,
+    doc_id: uuid::Uuid,
+    filename: &str,
+    snapshot: &[u8],
+    hash: Option<&str>,
+) -> Result<(), lst_proto::ServerMessage> {
+    let stored_id = authorize(state, user, doc_id, true).await?;
+    if !lst_proto::verify_snapshot(snapshot, hash) {
+        return Err(lst_proto::ServerMessage::Error {
+            doc_id: Some(doc_id),
+            code: lst_proto::error_code::CHECKSUM_MISMATCH.to_string(),
+            message: "Snapshot does not match its hash; it was not stored".to_string(),
+        });
+    }
+    if let Err(e) = state
+        .db
+        .save_snapshot(&stored_id, &doc_id, user, filename, snapshot)
+        .await
+    {
+        eprintln!("Failed to save snapshot: {}", e);
+        return Ok(());
+    }
+    if let Err(e) = state.db.delete_compacted_changes(&stored_id).await {
+        eprintln!("Failed to delete superseded changes: {}", e);
+    }
+    Ok(())
+}
+
+/// Delete a document on behalf of its owner. Deleting an unknown document is a
+/// no-op; anyone else is refused with the error to send back
+async fn remove_document(
+    state: &AppState,
+    user: &str,
+    doc_id: uuid::Uuid,
+) -> Result<(), lst_proto::ServerMessage> {
+    let error = |code: &str, message: String| doc_error(doc_id, code, message);
+    let (stored_id, access) = lookup_access(state, user, doc_id).await?;
+    match access {
+        sync_db::Access::Owner => {}
+        sync_db::Access::Unclaimed => return Ok(()),
+        _ => {
+            eprintln!("Denied {} to delete doc {}", user, doc_id);
+            return Err(error(
+                lst_proto::error_code::FORBIDDEN,
+                format!("Only the owner can delete document {}", doc_id),
+            ));
+        }
+    }
+    if let Err(e) = state.db.delete_document(&stored_id).await {
+        eprintln!("Failed to delete doc {}: {}", doc_id, e);
+        return Err(error(
+            lst_proto::error_code::INTERNAL,
+            "Failed to delete document".into(),
+        ));
+    }
+    Ok(())
 }
 
 async fn handle_ws(stream: WebSocket, state: Arc<AppState>, user: String) {
@@ -1075,7 +1712,7 @@ async fn handle_ws(stream: WebSocket, state: Arc<AppState>, user: String) {
             tokio::select! {
                 // Handle broadcast messages
                 Ok((target, msg)) = rx.recv() => {
-                    if target == user_clone {
+                    if target.eq_ignore_ascii_case(&user_clone) {
                         if let Ok(txt) = serde_json::to_string(&msg) {
                             if sender.send(WsMessage::Text(txt.into())).await.is_err() {
                                 eprintln!("Failed to send broadcast message to {}", user_clone);
@@ -1105,11 +1742,24 @@ async fn handle_ws(stream: WebSocket, state: Arc<AppState>, user: String) {
         match msg_result {
             Ok(WsMessage::Text(text)) => {
                 eprintln!("Received message from {}: {}", user, text);
-                if let Ok(cmsg) = serde_json::from_str::<lst_proto::ClientMessage>(&text) {
-                    match cmsg {
-                        lst_proto::ClientMessage::RequestDocumentList => {
+                match lst_proto::ClientMessage::from_json(&text) {
+                    Ok(cmsg) => match cmsg {
+                        lst_proto::ClientMessage::RequestDocumentList {
+                            after,
+                            after_updated_at,
+                            limit,
+                        } => {
                             eprintln!("Processing RequestDocumentList for {}", user);
-                            if let Ok(list) = state.db.list_documents(&user).await {
+                            let limit = limit
+                                .unwrap_or(lst_proto::MAX_DOCUMENT_PAGE)
+                                .clamp(1, lst_proto::MAX_DOCUMENT_PAGE);
+                            let cursor = after.map(|doc_id| sync_db::PageCursor {
+                                doc_id,
+                                updated_at: after_updated_at,
+                            });
+                            if let Ok(list) =
+                                state.db.list_documents(&user, cursor.as_ref(), limit).await
+                            {
                                 let resp =
                                     lst_proto::ServerMessage::DocumentList { documents: list };
                                 if let Err(e) = tx
@@ -1125,20 +1775,24 @@ async fn handle_ws(stream: WebSocket, state: Arc<AppState>, user: String) {
                         }
                         lst_proto::ClientMessage::RequestSnapshot { doc_id } => {
                             eprintln!("Processing RequestSnapshot for {} doc: {}", user, doc_id);
-                            if let Ok(Some((filename, snap))) = state.db.get_snapshot(&doc_id).await
-                            {
-                                let resp = lst_proto::ServerMessage::Snapshot {
-                                    doc_id,
-                                    filename,
-                                    snapshot: snap,
-                                };
-                                if let Err(e) = tx
-                                    .send(WsMessage::Text(
-                                        serde_json::to_string(&resp).unwrap().into(),
-                                    ))
-                                    .await
-                                {
-                                    eprintln!("Failed to send snapshot: {}", e);
+                            let resp = match authorize(&state, &user, doc_id, false).await {
+                                Ok(stored_id) => {
+                                    state.db.get_snapshot(&stored_id).await.ok().flatten().map(
+                                        |(filename, snapshot, hash)| {
+                                            lst_proto::ServerMessage::Snapshot {
+                                                doc_id,
+                                                filename,
+                                                snapshot,
+                                                hash: Some(hash),
+                                            }
+                                        },
+                                    )
+                                }
+                                Err(denied) => Some(denied),
+                            };
+                            if let Some(resp) = resp {
+                                if !send_to_client(&tx, &resp).await {
+                                    eprintln!("Failed to send snapshot");
                                     break;
                                 }
                             }
@@ -1150,29 +1804,19 @@ async fn handle_ws(stream: WebSocket, state: Arc<AppState>, user: String) {
                         } => {
                             eprintln!("Processing PushChanges for {} doc: {} from device: {} ({} changes)", 
                                      user, doc_id, device_id, changes.len());
-                            // Ensure a document row exists so DocumentList can surface it even before a snapshot
-                            if let Err(e) = state.db.ensure_document_exists(&doc_id, &user).await {
-                                eprintln!("Failed to ensure document row: {}", e);
-                            }
-                            if let Err(e) =
-                                state.db.add_changes(&doc_id, &device_id, &changes).await
+                            if let Err(denied) =
+                                store_changes(&state, &user, doc_id, device_id, changes).await
                             {
-                                eprintln!("Failed to add changes: {}", e);
-                            }
-                            let msg = lst_proto::ServerMessage::NewChanges {
-                                doc_id,
-                                from_device_id: device_id,
-                                changes,
-                            };
-                            // Broadcast to all devices of this user (they will filter out their own changes)
-                            if let Err(e) = state.tx.send((user.clone(), msg)) {
-                                eprintln!("Failed to broadcast changes: {}", e);
+                                if !send_to_client(&tx, &denied).await {
+                                    break;
+                                }
                             }
                         }
                         lst_proto::ClientMessage::PushSnapshot {
                             doc_id,
                             filename,
                             snapshot,
+                            hash,
                         } => {
                             eprintln!(
                                 "Processing PushSnapshot for {} doc: {} filename: {} ({} bytes)",
@@ -1181,20 +1825,51 @@ async fn handle_ws(stream: WebSocket, state: Arc<AppState>, user: String) {
                                 filename,
                                 snapshot.len()
                             );
-                            if let Err(e) = state
-                                .db
-                                .save_snapshot(&doc_id, &user, &filename, &snapshot)
-                                .await
+                            if let Err(denied) = store_snapshot(
+                                &state,
+                                &user,
+                                doc_id,
+                                &filename,
+                                &snapshot,
+                                hash.as_deref(),
+                            )
+                            .await
                             {
-                                eprintln!("Failed to save snapshot: {}", e);
+                                if !send_to_client(&tx, &denied).await {
+                                    break;
+                                }
+                            }
+                        }
+                        lst_proto::ClientMessage::DeleteDocument { doc_id } => {
+                            eprintln!("Processing DeleteDocument for {} doc: {}", user, doc_id);
+                            if let Err(denied) = remove_document(&state, &user, doc_id).await {
+                                if !send_to_client(&tx, &denied).await {
+                                    break;
+                                }
                             }
                         }
                         lst_proto::ClientMessage::Authenticate { .. } => {
                             eprintln!("Received duplicate authentication from {}", user);
                         }
+                        lst_proto::ClientMessage::Ping { id } => {
+                            let resp = lst_proto::ServerMessage::Pong { id };
+                            if let Err(e) = tx
+                                .send(WsMessage::Text(
+                                    serde_json::to_string(&resp).unwrap().into(),
+                                ))
+                                .await
+                            {
+                                eprintln!("Failed to send pong: {}", e);
+                                break;
+                            }
+                        }
+                    },
+                    Err(e) => {
+                        eprintln!("Failed to parse message from {}: {}", user, text);
+                        if !send_to_client(&tx, &malformed_message_error(&text, &e)).await {
+                            break;
+                        }
                     }
-                } else {
-                    eprintln!("Failed to parse message from {}: {}", user, text);
                 }
             }
             Ok(WsMessage::Close(_)) => {
@@ -1216,14 +1891,18 @@ async fn handle_ws(stream: WebSocket, state: Arc<AppState>, user: String) {
 }
 
 // --- JWT Auth Middleware ---
-async fn jwt_auth_middleware(req: Request, next: Next) -> Result<Response, StatusCode> {
+async fn jwt_auth_middleware(
+    State(jwt_secret): State<JwtSecret>,
+    req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
     let headers = req.headers();
     let auth_header = headers
         .get(header::AUTHORIZATION)
         .and_then(|header| header.to_str().ok());
     if let Some(auth_header) = auth_header {
         if let Some(token) = auth_header.strip_prefix("Bearer ") {
-            let decoding_key = DecodingKey::from_secret(JWT_SECRET);
+            let decoding_key = jwt_secret.decoding_key();
             let validation = Validation::default();
             match decode::<Claims>(token, &decoding_key, &validation) {
                 Ok(_token_data) => {
@@ -1485,3 +2164,671 @@ async fn handle_user_command(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign(secret: &[u8], sub: &str) -> String {
+        let claims = Claims {
+            sub: sub.to_string(),
+            exp: (chrono::Utc::now() + chrono::Duration::hours(1)).timestamp() as usize,
+        };
+        encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(secret),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_middleware_rejects_tokens_signed_with_another_secret() {
+        let settings = config::ServerSettings {
+            jwt_secret: Some("configured-secret".to_string()),
+            ..Default::default()
+        };
+        let secret = JwtSecret::new(settings.jwt_secret(None, false).unwrap());
+        assert!(config::ServerSettings::default()
+            .jwt_secret(None, false)
+            .is_err());
+
+        let app = Router::new()
+            .route("/protected", get(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(secret, jwt_auth_middleware));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/protected", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = reqwest::Client::new();
+        let status = |token: String| {
+            let request = client.get(&url).bearer_auth(token);
+            async move { request.send().await.unwrap().status() }
+        };
+        assert_eq!(
+            status(sign(b"configured-secret", "a@example.com")).await,
+            reqwest::StatusCode::OK
+        );
+        assert_eq!(
+            status(sign(b"some-other-secret", "a@example.com")).await,
+            reqwest::StatusCode::UNAUTHORIZED
+        );
+    }
+
+    /// Serve only `/auth/refresh` for a token store holding `email` with the
+    /// client-side hash `password_hash`; returns the endpoint URL
+    async fn serve_refresh(
+        dir: &StdPath,
+        secret: JwtSecret,
+        email: &str,
+        password_hash: &str,
+    ) -> String {
+        let store = Arc::new(SqliteTokenStore::new(dir.join("tokens.db")).await.unwrap());
+        let salt = SaltString::encode_b64(b"fixed-test-salt!").unwrap();
+        let stored = Argon2::default()
+            .hash_password(password_hash.as_bytes(), &salt)
+            .unwrap()
+            .to_string();
+        store.set_user(email, &stored, salt.as_str()).await.unwrap();
+
+        let app = Router::new().route(
+            "/auth/refresh",
+            post(move |j| auth_refresh_handler(j, store, secret, chrono::Duration::minutes(15))),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/auth/refresh", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_auth_request_posts_login_link_to_webhook() {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+        let hook = Router::new().route(
+            "/hook",
+            post(move |Json(body): Json<serde_json::Value>| async move {
+                tx.send(body).await.unwrap();
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let hook_url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, hook).await.unwrap() });
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(
+            SqliteTokenStore::new(dir.path().join("tokens.db"))
+                .await
+                .unwrap(),
+        );
+        let request = AuthRequest {
+            email: "a@example.com".to_string(),
+            host: "lst.example.com".to_string(),
+            password_hash: "client-hash".to_string(),
+        };
+        let webhook = Some(LoginWebhook::new(hook_url));
+        let response = auth_request_handler(Json(request), store.clone(), webhook)
+            .await
+            .unwrap();
+        assert_eq!(response.status, "ok");
+
+        let body: LoginWebhookPayload = serde_json::from_value(rx.recv().await.unwrap()).unwrap();
+        assert_eq!(body.email, "a@example.com");
+        assert!(store.verify("a@example.com", &body.token).await.unwrap());
+        assert_eq!(
+            body.login_url,
+            format!(
+                "lst-login://lst.example.com/auth/verify?token={}&email=a%40example.com",
+                body.token
+            )
+        );
+        assert!(body.text.contains(&body.login_url));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_issues_jwt_with_configured_expiry() {
+        let dir = tempfile::tempdir().unwrap();
+        let secret = JwtSecret::new(b"refresh-secret".to_vec());
+        let url = serve_refresh(dir.path(), secret.clone(), "a@example.com", "client-hash").await;
+
+        let client = reqwest::Client::new();
+        for payload in [
+            serde_json::json!({ "email": "a@example.com", "password_hash": "client-hash" }),
+            serde_json::json!({ "email": "A@Example.com", "password_hash": "client-hash" }),
+        ] {
+            let response = client.post(&url).json(&payload).send().await.unwrap();
+            assert_eq!(response.status(), reqwest::StatusCode::OK);
+            let body: serde_json::Value = response.json().await.unwrap();
+            assert_eq!(body["user"], "a@example.com");
+            assert_eq!(body["expires_in"], 15 * 60);
+
+            let claims = decode::<Claims>(
+                body["jwt"].as_str().unwrap(),
+                &secret.decoding_key(),
+                &Validation::default(),
+            )
+            .unwrap()
+            .claims;
+            assert_eq!(claims.sub, "a@example.com");
+            let remaining = claims.exp as i64 - chrono::Utc::now().timestamp();
+            assert!((14 * 60..=15 * 60).contains(&remaining));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refresh_rejects_unknown_password_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let secret = JwtSecret::new(b"refresh-secret".to_vec());
+        let url = serve_refresh(dir.path(), secret, "a@example.com", "client-hash").await;
+
+        let client = reqwest::Client::new();
+        for payload in [
+            serde_json::json!({ "email": "a@example.com", "password_hash": "wrong" }),
+            serde_json::json!({ "email": "b@example.com", "password_hash": "client-hash" }),
+        ] {
+            let response = client.post(&url).json(&payload).send().await.unwrap();
+            assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+            assert_eq!(
+                response.text().await.unwrap(),
+                "Unknown user or password hash"
+            );
+        }
+
+        // The account must be named; the hash is never tried against every user
+        let payload = serde_json::json!({ "password_hash": "client-hash" });
+        let response = client.post(&url).json(&payload).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_create_content_indexes_frontmatter_title() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SqliteContentStore::new(dir.path().join("content.db"))
+            .await
+            .unwrap();
+
+        let note = "---\ntitle: \"Meeting notes\"\ntags: [work, weekly]\n---\n\nbody\n";
+        store
+            .create_content("notes", "meeting.md", note)
+            .await
+            .unwrap();
+        store
+            .create_content("notes", "plain.md", "no frontmatter")
+            .await
+            .unwrap();
+
+        let row = sqlx::query("SELECT title, tags, content FROM content WHERE item_path = ?")
+            .bind("meeting.md")
+            .fetch_one(&store.pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            row.get::<Option<String>, _>("title").as_deref(),
+            Some("Meeting notes")
+        );
+        assert_eq!(
+            row.get::<Option<String>, _>("tags").as_deref(),
+            Some("work,weekly")
+        );
+        assert_eq!(row.get::<String, _>("content"), note);
+
+        let row = sqlx::query("SELECT title FROM content WHERE item_path = ?")
+            .bind("plain.md")
+            .fetch_one(&store.pool)
+            .await
+            .unwrap();
+        assert_eq!(row.get::<Option<String>, _>("title"), None);
+    }
+
+    #[tokio::test]
+    async fn test_delete_content_prefix_only_removes_matching_items() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SqliteContentStore::new(dir.path().join("content.db"))
+            .await
+            .unwrap();
+
+        for path in [
+            "daily_lists/20250101.md",
+            "daily_lists/20250102.md",
+            "daily_lists_old/20240101.md",
+            "groceries.md",
+        ] {
+            store.create_content("lists", path, "x").await.unwrap();
+        }
+        store
+            .create_content("notes", "daily_lists/keep.md", "x")
+            .await
+            .unwrap();
+
+        let deleted = store
+            .delete_content_prefix("lists", "daily_lists/")
+            .await
+            .unwrap();
+        assert_eq!(deleted, 2);
+
+        let remaining: Vec<_> = store
+            .list_content("lists", "")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|e| e.path)
+            .collect();
+        assert_eq!(
+            remaining,
+            vec!["daily_lists_old/20240101.md", "groceries.md"]
+        );
+        assert_eq!(store.list_content("notes", "").await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_list_content_returns_paths_sizes_and_filters_by_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SqliteContentStore::new(dir.path().join("content.db"))
+            .await
+            .unwrap();
+
+        store.create_content("lists", "b.md", "b").await.unwrap();
+        store.create_content("lists", "a.md", "a").await.unwrap();
+        store
+            .create_content("lists", "daily/20240102.md", "- [ ] ü")
+            .await
+            .unwrap();
+        store.create_content("notes", "n.md", "n").await.unwrap();
+
+        let entries = store.list_content("lists", "").await.unwrap();
+        let paths: Vec<_> = entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, vec!["a.md", "b.md", "daily/20240102.md"]);
+        assert!(entries.iter().all(|e| !e.updated_at.is_empty()));
+        let sizes: Vec<_> = entries.iter().map(|e| e.size).collect();
+        assert_eq!(sizes, vec![1, 1, 8], "sizes are in bytes");
+
+        let store: ContentStore = Arc::new(store);
+        let Json(entries) = list_content_handler(
+            Path("lists".to_string()),
+            Query(ContentPrefixQuery {
+                prefix: Some("daily/".to_string()),
+            }),
+            store,
+        )
+        .await
+        .unwrap();
+        let paths: Vec<_> = entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, vec!["daily/20240102.md"]);
+    }
+
+    #[tokio::test]
+    async fn test_conditional_get_returns_not_modified_until_content_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let store: ContentStore = Arc::new(
+            SqliteContentStore::new(dir.path().join("content.db"))
+                .await
+                .unwrap(),
+        );
+        store.create_content("lists", "a.md", "one").await.unwrap();
+
+        let app = Router::new().route(
+            "/{kind}/{*path}",
+            get({
+                let store = store.clone();
+                move |path, headers| read_content_handler(path, headers, store)
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/lists/a.md", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        let client = reqwest::Client::new();
+
+        let response = client.get(&url).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let etag = response.headers()["etag"].to_str().unwrap().to_string();
+        let last_modified = response.headers()["last-modified"]
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert_eq!(response.text().await.unwrap(), "one");
+
+        let response = client
+            .get(&url)
+            .header("If-None-Match", &etag)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()["etag"], etag.as_str());
+        assert!(response.text().await.unwrap().is_empty());
+
+        let response = client
+            .get(&url)
+            .header("If-Modified-Since", &last_modified)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_MODIFIED);
+
+        store.update_content("lists", "a.md", "two").await.unwrap();
+        let response = client
+            .get(&url)
+            .header("If-None-Match", &etag)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert_ne!(response.headers()["etag"], etag.as_str());
+        assert_eq!(response.text().await.unwrap(), "two");
+    }
+
+    #[tokio::test]
+    async fn test_compaction_is_requested_once_threshold_is_reached() {
+        let dir = tempfile::tempdir().unwrap();
+        let (tx, mut rx) = broadcast::channel(100);
+        let state = AppState {
+            db: sync_db::SyncDb::new(dir.path().join("sync.db"))
+                .await
+                .unwrap(),
+            tx,
+            jwt_secret: JwtSecret::new(b"secret".to_vec()),
+            compaction_threshold: 5,
+        };
+        let doc_id = uuid::Uuid::new_v4();
+        let stored_id = state.db.resolve(&doc_id, "a@b.c").await.unwrap();
+        let mut requests = || {
+            let mut count = 0;
+            while let Ok((user, msg)) = rx.try_recv() {
+                if let lst_proto::ServerMessage::RequestCompaction { doc_id: id } = msg {
+                    assert_eq!((user.as_str(), id), ("a@b.c", doc_id));
+                    count += 1;
+                }
+            }
+            count
+        };
+
+        for _ in 0..2 {
+            store_changes(
+                &state,
+                "a@b.c",
+                doc_id,
+                "dev".into(),
+                vec![vec![1], vec![2]],
+            )
+            .await
+            .unwrap();
+        }
+        assert_eq!(requests(), 0);
+        // Crossing the threshold asks for compaction exactly once
+        store_changes(
+            &state,
+            "a@b.c",
+            doc_id,
+            "dev".into(),
+            vec![vec![3], vec![4]],
+        )
+        .await
+        .unwrap();
+        assert_eq!(requests(), 1);
+        // Another device pushes while the compacted snapshot is on its way
+        store_changes(&state, "a@b.c", doc_id, "other".into(), vec![vec![5]])
+            .await
+            .unwrap();
+        assert_eq!(requests(), 0);
+        assert_eq!(state.db.change_count(&stored_id).await.unwrap(), 7);
+
+        // The snapshot supersedes only the changes stored when compaction was
+        // requested; the later change is kept
+        store_snapshot(&state, "a@b.c", doc_id, "f", b"snapshot", None)
+            .await
+            .unwrap();
+        assert_eq!(state.db.change_count(&stored_id).await.unwrap(), 1);
+        // Snapshots pushed without a compaction request drop nothing
+        store_snapshot(&state, "a@b.c", doc_id, "f", b"snapshot", None)
+            .await
+            .unwrap();
+        assert_eq!(state.db.change_count(&stored_id).await.unwrap(), 1);
+        for i in 0..4 {
+            store_changes(&state, "a@b.c", doc_id, "dev".into(), vec![vec![i]])
+                .await
+                .unwrap();
+        }
+        assert_eq!(requests(), 1);
+    }
+
+    fn refused<T>(result: Result<T, lst_proto::ServerMessage>, expected: &str) -> bool {
+        matches!(result, Err(lst_proto::ServerMessage::Error { code, .. }) if code == expected)
+    }
+
+    #[tokio::test]
+    async fn test_pushes_require_write_access() {
+        let dir = tempfile::tempdir().unwrap();
+        let (tx, _rx) = broadcast::channel(100);
+        let state = AppState {
+            db: sync_db::SyncDb::new(dir.path().join("sync.db"))
+                .await
+                .unwrap(),
+            tx,
+            jwt_secret: JwtSecret::new(b"secret".to_vec()),
+            compaction_threshold: 0,
+        };
+        let doc_id = uuid::Uuid::new_v4();
+        let forbidden = lst_proto::error_code::FORBIDDEN;
+
+        // The first push claims the document for its owner
+        store_snapshot(&state, "owner@b.c", doc_id, "f", b"v1", None)
+            .await
+            .unwrap();
+        store_changes(&state, "owner@b.c", doc_id, "dev".into(), vec![vec![1]])
+            .await
+            .unwrap();
+        let stored_id = state.db.resolve(&doc_id, "owner@b.c").await.unwrap();
+
+        // Anyone else gets nowhere near it, even knowing its storage id
+        assert!(refused(
+            authorize(&state, "eve@b.c", stored_id, false).await,
+            lst_proto::error_code::NOT_FOUND
+        ));
+        assert!(state
+            .db
+            .list_documents("eve@b.c", None, 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // A reader can fetch the document but still not push to it
+        state
+            .db
+            .grant(&stored_id, "reader@b.c", "read")
+            .await
+            .unwrap();
+        assert!(matches!(
+            authorize(&state, "reader@b.c", stored_id, false).await,
+            Ok(id) if id == stored_id
+        ));
+        let listed = state
+            .db
+            .list_documents("reader@b.c", None, 10)
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].doc_id, stored_id);
+        assert!(refused(
+            store_changes(&state, "reader@b.c", stored_id, "dev".into(), vec![vec![3]]).await,
+            forbidden
+        ));
+
+        state
+            .db
+            .grant(&stored_id, "writer@b.c", "write")
+            .await
+            .unwrap();
+        store_changes(&state, "writer@b.c", stored_id, "dev".into(), vec![vec![4]])
+            .await
+            .unwrap();
+
+        assert_eq!(state.db.change_count(&stored_id).await.unwrap(), 2);
+        let (_, snapshot, hash) = state.db.get_snapshot(&stored_id).await.unwrap().unwrap();
+        assert_eq!(snapshot, b"v1");
+        assert_eq!(hash, lst_proto::snapshot_hash(b"v1"));
+
+        // A snapshot damaged on the way is rejected instead of stored
+        let rejected = store_snapshot(
+            &state,
+            "owner@b.c",
+            doc_id,
+            "f",
+            b"v",
+            Some(&lst_proto::snapshot_hash(b"v2")),
+        )
+        .await;
+        assert!(refused(rejected, lst_proto::error_code::CHECKSUM_MISMATCH));
+        let (_, snapshot, _) = state.db.get_snapshot(&stored_id).await.unwrap().unwrap();
+        assert_eq!(snapshot, b"v1");
+    }
+
+    #[tokio::test]
+    async fn test_documents_are_scoped_per_user_and_changes_reach_every_member() {
+        let dir = tempfile::tempdir().unwrap();
+        let (tx, mut rx) = broadcast::channel(100);
+        let state = AppState {
+            db: sync_db::SyncDb::new(dir.path().join("sync.db"))
+                .await
+                .unwrap(),
+            tx,
+            jwt_secret: JwtSecret::new(b"secret".to_vec()),
+            compaction_threshold: 0,
+        };
+        // Both users sync a file at the same path, hence with the same doc id
+        let doc_id = uuid::Uuid::new_v4();
+        for (user, snapshot) in [("alice@b.c", b"alice"), ("bob@b.c", b"bob!!")] {
+            store_snapshot(&state, user, doc_id, "f", snapshot, None)
+                .await
+                .unwrap();
+            store_changes(&state, user, doc_id, "dev".into(), vec![vec![1]])
+                .await
+                .unwrap();
+        }
+        let alice_id = state.db.resolve(&doc_id, "alice@b.c").await.unwrap();
+        let bob_id = state.db.resolve(&doc_id, "Bob@b.c").await.unwrap();
+        assert_ne!(alice_id, bob_id);
+        for (stored_id, snapshot) in [(alice_id, b"alice"), (bob_id, b"bob!!")] {
+            let (_, stored, _) = state.db.get_snapshot(&stored_id).await.unwrap().unwrap();
+            assert_eq!(stored, snapshot);
+            assert_eq!(state.db.change_count(&stored_id).await.unwrap(), 1);
+        }
+        let listed = |user: &'static str| {
+            let db = state.db.clone();
+            async move {
+                let mut ids: Vec<uuid::Uuid> = db
+                    .list_documents(user, None, 10)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|doc| doc.doc_id)
+                    .collect();
+                ids.sort();
+                ids
+            }
+        };
+        assert_eq!(listed("alice@b.c").await, [doc_id]);
+
+        // Once alice shares her document, bob sees it under its storage id
+        // next to his own, and his pushes reach alice's devices too
+        state.db.grant(&alice_id, "bob@b.c", "write").await.unwrap();
+        let mut expected = vec![doc_id, alice_id];
+        expected.sort();
+        assert_eq!(listed("bob@b.c").await, expected);
+        while rx.try_recv().is_ok() {}
+        store_changes(&state, "bob@b.c", alice_id, "bob-dev".into(), vec![vec![2]])
+            .await
+            .unwrap();
+        let mut delivered = Vec::new();
+        while let Ok((user, msg)) = rx.try_recv() {
+            if let lst_proto::ServerMessage::NewChanges { doc_id, .. } = msg {
+                delivered.push((user, doc_id));
+            }
+        }
+        delivered.sort();
+        assert_eq!(
+            delivered,
+            [
+                ("alice@b.c".to_string(), doc_id),
+                ("bob@b.c".to_string(), alice_id)
+            ]
+        );
+        assert_eq!(state.db.change_count(&alice_id).await.unwrap(), 2);
+        assert_eq!(state.db.change_count(&bob_id).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_only_the_owner_deletes_a_document() {
+        let dir = tempfile::tempdir().unwrap();
+        let (tx, _rx) = broadcast::channel(100);
+        let state = AppState {
+            db: sync_db::SyncDb::new(dir.path().join("sync.db"))
+                .await
+                .unwrap(),
+            tx,
+            jwt_secret: JwtSecret::new(b"secret".to_vec()),
+            compaction_threshold: 0,
+        };
+        let doc_id = uuid::Uuid::new_v4();
+        store_snapshot(&state, "owner@b.c", doc_id, "f", b"v1", None)
+            .await
+            .unwrap();
+        store_changes(&state, "owner@b.c", doc_id, "dev".into(), vec![vec![1]])
+            .await
+            .unwrap();
+        let stored_id = state.db.resolve(&doc_id, "owner@b.c").await.unwrap();
+        state
+            .db
+            .grant(&stored_id, "writer@b.c", "write")
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            remove_document(&state, "writer@b.c", stored_id).await,
+            Err(lst_proto::ServerMessage::Error { code, .. })
+                if code == lst_proto::error_code::FORBIDDEN
+        ));
+        assert!(state.db.get_snapshot(&stored_id).await.unwrap().is_some());
+
+        remove_document(&state, "owner@b.c", doc_id).await.unwrap();
+        assert!(state.db.get_snapshot(&stored_id).await.unwrap().is_none());
+        assert_eq!(state.db.change_count(&stored_id).await.unwrap(), 0);
+        assert!(state
+            .db
+            .list_documents("writer@b.c", None, 10)
+            .await
+            .unwrap()
+            .is_empty());
+        // Deleting it again is a no-op
+        remove_document(&state, "owner@b.c", doc_id).await.unwrap();
+    }
+
+    #[test]
+    fn test_malformed_push_changes_yields_error_for_its_document() {
+        let doc_id = uuid::Uuid::new_v4();
+        let text = format!(
+            r#"{{"PushChanges":{{"doc_id":"{}","device_id":"dev","changes":"oops"}}}}"#,
+            doc_id
+        );
+        let err = serde_json::from_str::<lst_proto::ClientMessage>(&text).unwrap_err();
+
+        // Round-trip through JSON as the client would receive it
+        let wire = serde_json::to_string(&malformed_message_error(&text, &err)).unwrap();
+        match serde_json::from_str::<lst_proto::ServerMessage>(&wire).unwrap() {
+            lst_proto::ServerMessage::Error {
+                doc_id: id,
+                code,
+                message,
+            } => {
+                assert_eq!(id, Some(doc_id));
+                assert_eq!(code, lst_proto::error_code::MALFORMED);
+                assert!(message.contains("Could not parse"));
+            }
+            other => panic!("expected an error, got {:?}", other),
+        }
+
+        let err = serde_json::from_str::<lst_proto::ClientMessage>("not json").unwrap_err();
+        assert!(matches!(
+            malformed_message_error("not json", &err),
+            lst_proto::ServerMessage::Error { doc_id: None, .. }
+        ));
+    }
+}
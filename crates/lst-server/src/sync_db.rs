@@ -35,6 +35,20 @@ impl SyncDb {
         )
         .execute(&pool)
         .await?;
+        // Added after the first release; fails harmlessly once the column exists
+        let _ = sqlx::query("ALTER TABLE documents ADD COLUMN snapshot_hash TEXT")
+            .execute(&pool)
+            .await;
+        // Highest change id a requested compaction covers, see `mark_compaction`
+        let _ = sqlx::query("ALTER TABLE documents ADD COLUMN compact_through INTEGER")
+            .execute(&pool)
+            .await;
+        // Id the owner's clients use for the document, see `resolve`. Rows
+        // stored before ids were scoped per user have none and are known by
+        // `doc_id` itself
+        let _ = sqlx::query("ALTER TABLE documents ADD COLUMN client_doc_id TEXT")
+            .execute(&pool)
+            .await;
 
         sqlx::query(
             r#"CREATE TABLE IF NOT EXISTS document_permissions (
@@ -62,14 +76,41 @@ impl SyncDb {
         Ok(SyncDb { pool })
     }
 
-    pub async fn list_documents(&self, user_email: &str) -> Result<Vec<DocumentInfo>> {
+    /// Documents `user_email` owns or may read under the ids they know them
+    /// by, ordered by `updated_at` (ties by that id). Paging starts after the
+    /// `after` cursor, so updates to documents between pages (the cursor
+    /// document included) move them to the end instead of shifting the page
+    /// boundaries
+    pub async fn list_documents(
+        &self,
+        user_email: &str,
+        after: Option<&PageCursor>,
+        limit: u32,
+    ) -> Result<Vec<DocumentInfo>> {
         let rows = sqlx::query(
-            r#"SELECT DISTINCT d.doc_id, d.encrypted_filename, d.updated_at 
-               FROM documents d
-               JOIN document_permissions p ON d.doc_id = p.doc_id
-               WHERE p.user_email = ?"#,
+            r#"WITH visible AS (
+                   SELECT COALESCE(CASE WHEN d.user_id = ?1 THEN d.client_doc_id END, d.doc_id)
+                              AS doc_id,
+                          d.encrypted_filename, d.updated_at
+                   FROM documents d
+                   WHERE d.user_id = ?1 OR EXISTS (
+                       SELECT 1 FROM document_permissions p
+                       WHERE p.doc_id = d.doc_id AND p.user_email = ?1))
+               SELECT v.doc_id, v.encrypted_filename, v.updated_at
+               FROM visible v
+               WHERE ?2 IS NULL OR (datetime(v.updated_at), v.doc_id) > (datetime(COALESCE(?3,
+                     (SELECT c.updated_at FROM visible c WHERE c.doc_id = ?2))), ?2)
+               ORDER BY datetime(v.updated_at), v.doc_id
+               LIMIT ?4"#,
         )
-        .bind(&user_email.to_lowercase())
+        .bind(user_email.to_lowercase())
+        .bind(after.map(|cursor| cursor.doc_id.to_string()))
+        .bind(
+            after
+                .and_then(|cursor| cursor.updated_at)
+                .map(|t| t.to_rfc3339()),
+        )
+        .bind(i64::from(limit))
         .fetch_all(&self.pool)
         .await?;
         Ok(rows
@@ -85,19 +126,127 @@ impl SyncDb {
             .collect())
     }
 
-    pub async fn get_snapshot(&self, doc_id: &Uuid) -> Result<Option<(String, Vec<u8>)>> {
+    /// Storage id of the document `user_email` calls `doc_id`. Clients derive
+    /// doc ids from file paths, so the same id names a different document for
+    /// every user: each user's documents are stored under ids scoped to them.
+    /// Documents shared with a user are listed under their storage id, which
+    /// resolves to the shared document as long as the user still has access.
+    /// Unknown ids resolve to a new document in the user's scope
+    pub async fn resolve(&self, doc_id: &Uuid, user_email: &str) -> Result<Uuid> {
+        let user_email = user_email.to_lowercase();
+        let scoped = scoped_doc_id(&user_email, doc_id);
+        let found: Option<String> = sqlx::query_scalar(
+            r#"SELECT d.doc_id FROM documents d
+               WHERE d.doc_id = ?1 OR (d.doc_id = ?2 AND (d.user_id = ?3 OR EXISTS (
+                   SELECT 1 FROM document_permissions p
+                   WHERE p.doc_id = d.doc_id AND p.user_email = ?3)))
+               ORDER BY d.doc_id = ?1 DESC
+               LIMIT 1"#,
+        )
+        .bind(scoped.to_string())
+        .bind(doc_id.to_string())
+        .bind(&user_email)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(match found {
+            Some(found) => Uuid::parse_str(&found)?,
+            None => scoped,
+        })
+    }
+
+    /// Users with access to the stored document `doc_id`, each with the id
+    /// their clients know it by (see [`resolve`](Self::resolve))
+    pub async fn members(&self, doc_id: &Uuid) -> Result<Vec<(String, Uuid)>> {
+        let rows = sqlx::query(
+            r#"SELECT d.user_id AS user_email, COALESCE(d.client_doc_id, d.doc_id) AS known_as
+               FROM documents d WHERE d.doc_id = ?1
+               UNION
+               SELECT p.user_email, d.doc_id AS known_as
+               FROM document_permissions p JOIN documents d ON d.doc_id = p.doc_id
+               WHERE p.doc_id = ?1 AND p.user_email != d.user_id"#,
+        )
+        .bind(doc_id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter()
+            .map(|row| {
+                let known_as: String = row.get("known_as");
+                Ok((row.get("user_email"), Uuid::parse_str(&known_as)?))
+            })
+            .collect()
+    }
+
+    /// Access `user_email` has to the stored document `doc_id`; the document's
+    /// owner always has full access
+    pub async fn access(&self, doc_id: &Uuid, user_email: &str) -> Result<Access> {
+        let user_email = user_email.to_lowercase();
+        let row = sqlx::query(
+            r#"SELECT d.user_id, p.permission_type
+               FROM documents d
+               LEFT JOIN document_permissions p ON p.doc_id = d.doc_id AND p.user_email = ?
+               WHERE d.doc_id = ?"#,
+        )
+        .bind(&user_email)
+        .bind(doc_id.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(Access::Unclaimed);
+        };
+        if row.get::<String, _>("user_id") == user_email {
+            return Ok(Access::Owner);
+        }
+        let permission: Option<String> = row.get("permission_type");
+        Ok(match permission.as_deref() {
+            Some("owner") => Access::Owner,
+            Some("write") | Some("writer") => Access::Write,
+            Some("read") | Some("reader") => Access::Read,
+            _ => Access::Denied,
+        })
+    }
+
+    /// Grant `user_email` `permission` (`owner`, `write` or `read`) on `doc_id`,
+    /// replacing any earlier grant
+    #[cfg(test)]
+    pub async fn grant(&self, doc_id: &Uuid, user_email: &str, permission: &str) -> Result<()> {
+        sqlx::query(
+            r#"INSERT INTO document_permissions (doc_id, user_email, permission_type)
+               VALUES (?, ?, ?)
+               ON CONFLICT(doc_id, user_email) DO UPDATE SET
+                   permission_type = excluded.permission_type"#,
+        )
+        .bind(doc_id.to_string())
+        .bind(user_email.to_lowercase())
+        .bind(permission)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Encrypted filename, snapshot and snapshot hash of `doc_id`. The hash of
+    /// snapshots stored before hashes were recorded is computed on the fly
+    pub async fn get_snapshot(&self, doc_id: &Uuid) -> Result<Option<(String, Vec<u8>, String)>> {
         let row = sqlx::query(
-            "SELECT encrypted_filename, encrypted_snapshot FROM documents WHERE doc_id = ?",
+            "SELECT encrypted_filename, encrypted_snapshot, snapshot_hash FROM documents WHERE doc_id = ?",
         )
         .bind(doc_id.to_string())
         .fetch_optional(&self.pool)
         .await?;
-        Ok(row.map(|r| (r.get("encrypted_filename"), r.get("encrypted_snapshot"))))
+        Ok(row.map(|r| {
+            let snapshot: Vec<u8> = r.get("encrypted_snapshot");
+            let hash: Option<String> = r.get("snapshot_hash");
+            let hash = hash.unwrap_or_else(|| lst_proto::snapshot_hash(&snapshot));
+            (r.get("encrypted_filename"), snapshot, hash)
+        }))
     }
 
+    /// Store a snapshot of `doc_id`, creating the document for `user_id`
+    /// (who knows it as `client_doc_id`) if it does not exist yet
     pub async fn save_snapshot(
         &self,
         doc_id: &Uuid,
+        client_doc_id: &Uuid,
         user_id: &str,
         encrypted_filename: &str,
         snapshot: &[u8],
@@ -105,17 +254,20 @@ impl SyncDb {
         let mut tx = self.pool.begin().await?;
 
         sqlx::query(
-            r#"INSERT INTO documents (doc_id, user_id, encrypted_filename, encrypted_snapshot)
-               VALUES (?, ?, ?, ?)
+            r#"INSERT INTO documents (doc_id, user_id, encrypted_filename, encrypted_snapshot, snapshot_hash, client_doc_id)
+               VALUES (?, ?, ?, ?, ?, ?)
                ON CONFLICT(doc_id) DO UPDATE SET
                    encrypted_filename = excluded.encrypted_filename,
                    encrypted_snapshot = excluded.encrypted_snapshot,
+                   snapshot_hash = excluded.snapshot_hash,
                    updated_at = CURRENT_TIMESTAMP"#,
         )
         .bind(doc_id.to_string())
         .bind(&user_id.to_lowercase())
         .bind(encrypted_filename)
         .bind(snapshot)
+        .bind(lst_proto::snapshot_hash(snapshot))
+        .bind(client_doc_id.to_string())
         .execute(&mut *tx)
         .await?;
 
@@ -132,12 +284,14 @@ impl SyncDb {
         Ok(())
     }
 
+    /// Store `changes` for `doc_id` and return how many changes are now stored
+    /// for the document
     pub async fn add_changes(
         &self,
         doc_id: &Uuid,
         device_id: &str,
         changes: &[Vec<u8>],
-    ) -> Result<()> {
+    ) -> Result<u64> {
         for c in changes {
             sqlx::query(
                 "INSERT INTO document_changes (doc_id, device_id, encrypted_change) VALUES (?, ?, ?)",
@@ -148,19 +302,89 @@ impl SyncDb {
             .execute(&self.pool)
             .await?;
         }
+        self.change_count(doc_id).await
+    }
+
+    /// Number of change blobs stored for `doc_id`
+    pub async fn change_count(&self, doc_id: &Uuid) -> Result<u64> {
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM document_changes WHERE doc_id = ?")
+                .bind(doc_id.to_string())
+                .fetch_one(&self.pool)
+                .await?;
+        Ok(count as u64)
+    }
+
+    /// Remember the changes stored for `doc_id` when compaction is requested;
+    /// the snapshot pushed in response supersedes only those
+    pub async fn mark_compaction(&self, doc_id: &Uuid) -> Result<()> {
+        sqlx::query(
+            r#"UPDATE documents SET compact_through =
+                   (SELECT MAX(change_id) FROM document_changes WHERE doc_id = ?1)
+               WHERE doc_id = ?1"#,
+        )
+        .bind(doc_id.to_string())
+        .execute(&self.pool)
+        .await?;
         Ok(())
     }
 
+    /// Delete the stored changes of `doc_id` covered by the last
+    /// [`mark_compaction`](Self::mark_compaction) once a pushed snapshot
+    /// supersedes them. Changes that arrived after compaction was requested are
+    /// kept, as the snapshot may not contain them
+    pub async fn delete_compacted_changes(&self, doc_id: &Uuid) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let result = sqlx::query(
+            r#"DELETE FROM document_changes
+               WHERE doc_id = ?1 AND change_id <=
+                   (SELECT compact_through FROM documents WHERE doc_id = ?1)"#,
+        )
+        .bind(doc_id.to_string())
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("UPDATE documents SET compact_through = NULL WHERE doc_id = ?")
+            .bind(doc_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Delete `doc_id` with its changes and permissions; returns `false` when
+    /// there was no such document
+    pub async fn delete_document(&self, doc_id: &Uuid) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        for table in ["document_changes", "document_permissions"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE doc_id = ?", table))
+                .bind(doc_id.to_string())
+                .execute(&mut *tx)
+                .await?;
+        }
+        let result = sqlx::query("DELETE FROM documents WHERE doc_id = ?")
+            .bind(doc_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Ensure a document row exists for this user when changes arrive without prior snapshot
-    pub async fn ensure_document_exists(&self, doc_id: &Uuid, user_id: &str) -> Result<()> {
+    pub async fn ensure_document_exists(
+        &self,
+        doc_id: &Uuid,
+        client_doc_id: &Uuid,
+        user_id: &str,
+    ) -> Result<()> {
         let mut tx = self.pool.begin().await?;
         // Insert a placeholder document row if not present (empty snapshot)
         sqlx::query(
-            r#"INSERT OR IGNORE INTO documents (doc_id, user_id, encrypted_filename, encrypted_snapshot)
-               VALUES (?, ?, '', x'00')"#,
+            r#"INSERT OR IGNORE INTO documents (doc_id, user_id, encrypted_filename, encrypted_snapshot, client_doc_id)
+               VALUES (?, ?, '', x'00', ?)"#,
         )
         .bind(doc_id.to_string())
         .bind(&user_id.to_lowercase())
+        .bind(client_doc_id.to_string())
         .execute(&mut *tx)
         .await?;
 
@@ -178,3 +402,187 @@ impl SyncDb {
         Ok(())
     }
 }
+
+/// Storage id of the document `user_email` pushes as `doc_id`
+fn scoped_doc_id(user_email: &str, doc_id: &Uuid) -> Uuid {
+    let user = Uuid::new_v5(&Uuid::NAMESPACE_OID, user_email.as_bytes());
+    Uuid::new_v5(&user, doc_id.as_bytes())
+}
+
+/// Position after the last document of a [`SyncDb::list_documents`] page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageCursor {
+    pub doc_id: Uuid,
+    /// `updated_at` of `doc_id` when it was listed; older clients don't send
+    /// it, in which case the document's current `updated_at` is used
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<&DocumentInfo> for PageCursor {
+    fn from(doc: &DocumentInfo) -> Self {
+        PageCursor {
+            doc_id: doc.doc_id,
+            updated_at: Some(doc.updated_at),
+        }
+    }
+}
+
+/// What a user may do with a document
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// No such document yet; the user pushing it becomes its owner
+    Unclaimed,
+    Denied,
+    Read,
+    Write,
+    Owner,
+}
+
+impl Access {
+    pub fn can_read(self) -> bool {
+        matches!(self, Access::Read | Access::Write | Access::Owner)
+    }
+
+    pub fn can_write(self) -> bool {
+        matches!(self, Access::Unclaimed | Access::Write | Access::Owner)
+    }
+}
+
+/// Whether adding `added` changes brought a document's stored change count
+/// (`total` afterwards) up to `threshold`, so compaction is requested only
+/// once per crossing. A threshold of 0 disables compaction requests
+pub fn crossed_compaction_threshold(total: u64, added: usize, threshold: u32) -> bool {
+    let threshold = u64::from(threshold);
+    threshold > 0 && total >= threshold && total.saturating_sub(added as u64) < threshold
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn db_with_docs(dir: &tempfile::TempDir, count: usize) -> (SyncDb, Vec<Uuid>) {
+        let db = SyncDb::new(dir.path().join("sync.db")).await.unwrap();
+        let mut ids = Vec::new();
+        for i in 0..count {
+            let id = Uuid::new_v4();
+            db.save_snapshot(&id, &id, "a@b.c", "f", b"s")
+                .await
+                .unwrap();
+            // Distinct timestamps so the expected order does not depend on ids
+            sqlx::query("UPDATE documents SET updated_at = ? WHERE doc_id = ?")
+                .bind(format!("2024-01-01 00:00:{:02}", i))
+                .bind(id.to_string())
+                .execute(&db.pool)
+                .await
+                .unwrap();
+            ids.push(id);
+        }
+        (db, ids)
+    }
+
+    async fn all_pages(db: &SyncDb, limit: u32) -> Vec<Vec<Uuid>> {
+        let mut pages = Vec::new();
+        let mut after: Option<PageCursor> = None;
+        loop {
+            let docs = db
+                .list_documents("a@b.c", after.as_ref(), limit)
+                .await
+                .unwrap();
+            after = docs.last().map(PageCursor::from);
+            let page: Vec<Uuid> = docs.into_iter().map(|d| d.doc_id).collect();
+            let done = page.len() < limit as usize;
+            pages.push(page);
+            if done {
+                return pages;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_documents_pages_in_update_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let (db, ids) = db_with_docs(&dir, 5).await;
+
+        let pages = all_pages(&db, 2).await;
+        assert_eq!(pages.len(), 3);
+        assert_eq!(pages.concat(), ids);
+        assert!(db
+            .list_documents("x@y.z", None, 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_documents_cursor_survives_concurrent_updates() {
+        let dir = tempfile::tempdir().unwrap();
+        let (db, ids) = db_with_docs(&dir, 5).await;
+
+        let first = db.list_documents("a@b.c", None, 2).await.unwrap();
+        let cursor = PageCursor::from(first.last().unwrap());
+        assert_eq!(cursor.doc_id, ids[1]);
+
+        // An already listed and a not yet listed document change mid-paging
+        for id in [ids[0], ids[2]] {
+            db.save_snapshot(&id, &id, "a@b.c", "f", b"new")
+                .await
+                .unwrap();
+        }
+        let rest: Vec<Uuid> = db
+            .list_documents("a@b.c", Some(&cursor), 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|d| d.doc_id)
+            .collect();
+        // Nothing is skipped; updated documents move behind the untouched ones
+        assert_eq!(&rest[..2], &ids[3..]);
+        let mut moved = rest[2..].to_vec();
+        moved.sort();
+        let mut expected = vec![ids[0], ids[2]];
+        expected.sort();
+        assert_eq!(moved, expected);
+    }
+
+    async fn ids_after(db: &SyncDb, cursor: &PageCursor) -> Vec<Uuid> {
+        db.list_documents("a@b.c", Some(cursor), 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|d| d.doc_id)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_list_documents_cursor_survives_changes_to_cursor_document() {
+        let dir = tempfile::tempdir().unwrap();
+        let (db, ids) = db_with_docs(&dir, 5).await;
+        let first = db.list_documents("a@b.c", None, 2).await.unwrap();
+        let cursor = PageCursor::from(first.last().unwrap());
+
+        // The cursor document itself is updated: it moves to the end
+        db.save_snapshot(&ids[1], &ids[1], "a@b.c", "f", b"new")
+            .await
+            .unwrap();
+        assert_eq!(
+            ids_after(&db, &cursor).await,
+            [ids[2], ids[3], ids[4], ids[1]]
+        );
+
+        // ... or deleted: paging still continues where it left off
+        sqlx::query("DELETE FROM documents WHERE doc_id = ?")
+            .bind(ids[1].to_string())
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(ids_after(&db, &cursor).await, &ids[2..]);
+
+        // Older clients only send the doc id, which still pages correctly
+        // while the cursor document is unchanged
+        let legacy = PageCursor {
+            doc_id: ids[2],
+            updated_at: None,
+        };
+        assert_eq!(ids_after(&db, &legacy).await, &ids[3..]);
+    }
+}
@@ -0,0 +1,174 @@
+use anyhow::{Context, Result};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use lst_core::sync::{ConnectionStatus, DaemonStatus, ManualSyncResult};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, oneshot};
+
+/// A `POST /sync` waiting for the main loop to run the sync and answer
+pub type SyncRequest = oneshot::Sender<ManualSyncResult>;
+
+#[derive(Clone)]
+struct Shared {
+    status: Arc<Mutex<DaemonStatus>>,
+    requests: mpsc::Sender<SyncRequest>,
+}
+
+/// Local HTTP endpoint of the daemon, used by `lst sync status` and
+/// `lst sync now`: `GET /status` reports the live [`DaemonStatus`] and
+/// `POST /sync` runs a sync cycle, answering with its [`ManualSyncResult`]
+pub struct ControlEndpoint {
+    status: Arc<Mutex<DaemonStatus>>,
+    requests: mpsc::Receiver<SyncRequest>,
+    addr: SocketAddr,
+}
+
+impl ControlEndpoint {
+    /// Bind `addr` (meant to be on 127.0.0.1) and serve in the background
+    pub async fn bind(addr: SocketAddr, initial: DaemonStatus) -> Result<Self> {
+        let listener = tokio::net::TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("Failed to bind control endpoint to {}", addr))?;
+        let addr = listener.local_addr()?;
+
+        let status = Arc::new(Mutex::new(initial));
+        let (tx, requests) = mpsc::channel(8);
+        let app = Router::new()
+            .route("/status", get(status_handler))
+            .route("/sync", post(sync_handler))
+            .with_state(Shared {
+                status: status.clone(),
+                requests: tx,
+            });
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, app).await {
+                tracing::warn!("Control endpoint stopped: {e}");
+            }
+        });
+
+        Ok(Self {
+            status,
+            requests,
+            addr,
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Publish the daemon's current state for `GET /status`
+    pub fn update(&self, status: DaemonStatus) {
+        *self.status.lock().unwrap() = status;
+    }
+
+    /// Next sync requested through `POST /sync`
+    pub async fn next_request(&mut self) -> Option<SyncRequest> {
+        self.requests.recv().await
+    }
+}
+
+async fn status_handler(State(shared): State<Shared>) -> Json<DaemonStatus> {
+    let mut status = shared.status.lock().unwrap().clone();
+    if status.server.is_some() {
+        // The trigger listener keeps the status file current
+        status.connection = ConnectionStatus::load().ok().flatten();
+    }
+    Json(status)
+}
+
+async fn sync_handler(State(shared): State<Shared>) -> (StatusCode, Json<ManualSyncResult>) {
+    let (reply, result) = oneshot::channel();
+    let result = match shared.requests.send(reply).await {
+        Ok(()) => result.await.ok(),
+        Err(_) => None,
+    };
+    match result {
+        Some(result @ ManualSyncResult::Failed { .. }) => (StatusCode::BAD_GATEWAY, Json(result)),
+        Some(result) => (StatusCode::OK, Json(result)),
+        None => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ManualSyncResult::Failed {
+                error: "lst-syncd is shutting down".to_string(),
+            }),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn idle_status() -> DaemonStatus {
+        DaemonStatus {
+            pid: 42,
+            server: None,
+            connection: None,
+            queued_changes: 0,
+            last_sync: None,
+            last_error: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_status_and_sync_endpoints_reflect_daemon_state() {
+        let local = SocketAddr::from(([127, 0, 0, 1], 0));
+        let mut endpoint = ControlEndpoint::bind(local, idle_status()).await.unwrap();
+        let base = format!("http://{}", endpoint.addr());
+        let client = reqwest::Client::new();
+
+        let status: DaemonStatus = client
+            .get(format!("{}/status", base))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(status, idle_status());
+
+        let synced_at = chrono::Utc::now();
+        endpoint.update(DaemonStatus {
+            queued_changes: 3,
+            last_sync: Some(synced_at),
+            ..idle_status()
+        });
+        let status: DaemonStatus = client
+            .get(format!("{}/status", base))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(status.queued_changes, 3);
+        assert_eq!(status.last_sync, Some(synced_at));
+
+        // The main loop answers each request with the outcome of its sync
+        for outcome in [
+            ManualSyncResult::Synced,
+            ManualSyncResult::Failed {
+                error: "server unreachable".to_string(),
+            },
+        ] {
+            let request = client.post(format!("{}/sync", base)).send();
+            let answer = async {
+                let reply = endpoint.next_request().await.unwrap();
+                reply.send(outcome.clone()).unwrap();
+            };
+            let (response, ()) = tokio::join!(request, answer);
+            let response = response.unwrap();
+            let expected = match outcome {
+                ManualSyncResult::Failed { .. } => StatusCode::BAD_GATEWAY,
+                _ => StatusCode::OK,
+            };
+            assert_eq!(response.status().as_u16(), expected.as_u16());
+            assert_eq!(response.json::<ManualSyncResult>().await.unwrap(), outcome);
+        }
+    }
+}
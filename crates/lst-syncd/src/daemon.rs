@@ -0,0 +1,234 @@
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// PID file owned by this process; removed again when dropped
+pub struct PidFile {
+    path: PathBuf,
+    pid: u32,
+}
+
+impl PidFile {
+    /// Record `pid` in `path`, creating the parent directory if needed
+    pub fn write(path: &Path, pid: u32) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+        fs::write(path, format!("{}\n", pid))
+            .with_context(|| format!("Failed to write PID file: {}", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            pid,
+        })
+    }
+
+    /// Take over a PID file already written for this process (by the parent
+    /// during [`daemonize`])
+    pub fn adopt(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            pid: std::process::id(),
+        }
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        // Leave the file alone if another daemon has replaced it meanwhile
+        if lst_core::sync::read_pid_file(&self.path).ok().flatten() == Some(self.pid) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Fail if the PID file in `path` names a daemon that is still running; a
+/// stale file left by a crashed daemon is cleaned up
+pub fn ensure_not_running(path: &Path) -> Result<()> {
+    if lst_core::sync::read_pid_file(path)? == Some(std::process::id()) {
+        return Ok(());
+    }
+    if let Some(pid) = lst_core::sync::running_daemon_pid(path)? {
+        bail!("lst-syncd is already running (PID {})", pid);
+    }
+    Ok(())
+}
+
+/// Requests for an immediate sync cycle, delivered as SIGUSR1 by `lst sync now`
+pub struct ManualSyncSignal {
+    #[cfg(unix)]
+    signal: Option<tokio::signal::unix::Signal>,
+}
+
+impl ManualSyncSignal {
+    /// Start listening; must be called inside the tokio runtime. Before this
+    /// SIGUSR1 would terminate the daemon
+    pub fn listen() -> Self {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            let signal = signal(SignalKind::user_defined1())
+                .map_err(|e| tracing::warn!("Cannot listen for manual sync requests: {e}"))
+                .ok();
+            Self { signal }
+        }
+        #[cfg(not(unix))]
+        Self {}
+    }
+
+    /// Resolves on the next request; never, if requests can't be received
+    pub async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(signal) = self.signal.as_mut() {
+            if signal.recv().await.is_some() {
+                return;
+            }
+        }
+        std::future::pending::<()>().await
+    }
+}
+
+/// Detach from the terminal: fork, `setsid`, fork again, and send stdout and
+/// stderr to `log_path`. The intermediate process records the daemon's PID in
+/// `pid_path` before the launching process exits, so callers can read it as
+/// soon as `lst-syncd` returns.
+///
+/// Must run before the tokio runtime (or any other thread) is started. The
+/// working directory is kept so relative content paths keep resolving.
+#[cfg(unix)]
+pub fn daemonize(pid_path: &Path, log_path: &Path) -> Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    if let Some(parent) = log_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    let log = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path)
+        .with_context(|| format!("Failed to open log file: {}", log_path.display()))?;
+    let null = fs::File::open("/dev/null").context("Failed to open /dev/null")?;
+
+    // SAFETY: the process is still single-threaded, so the forked children do
+    // not inherit locks held by other threads
+    unsafe {
+        match libc::fork() {
+            -1 => bail!("fork failed: {}", std::io::Error::last_os_error()),
+            0 => {}
+            child => {
+                let mut status = 0;
+                libc::waitpid(child, &mut status, 0);
+                let ok = libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0;
+                libc::_exit(if ok { 0 } else { 1 });
+            }
+        }
+
+        if libc::setsid() == -1 {
+            bail!("setsid failed: {}", std::io::Error::last_os_error());
+        }
+
+        match libc::fork() {
+            -1 => bail!("fork failed: {}", std::io::Error::last_os_error()),
+            0 => {}
+            child => {
+                let code = match PidFile::write(pid_path, child as u32) {
+                    Ok(pid_file) => {
+                        // The daemon owns the file from here on
+                        std::mem::forget(pid_file);
+                        0
+                    }
+                    Err(e) => {
+                        eprintln!("{:#}", e);
+                        libc::kill(child, libc::SIGTERM);
+                        1
+                    }
+                };
+                libc::_exit(code);
+            }
+        }
+
+        libc::dup2(null.as_raw_fd(), libc::STDIN_FILENO);
+        libc::dup2(log.as_raw_fd(), libc::STDOUT_FILENO);
+        libc::dup2(log.as_raw_fd(), libc::STDERR_FILENO);
+    }
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use lst_core::sync::process_alive;
+
+    #[test]
+    fn test_pid_file_points_to_live_process_and_is_removed_on_drop() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run").join("syncd.pid");
+
+        let pid_file = PidFile::write(&path, std::process::id()).unwrap();
+        let pid = lst_core::sync::read_pid_file(&path).unwrap().unwrap();
+        assert_eq!(pid, std::process::id());
+        assert!(process_alive(pid));
+        assert!(ensure_not_running(&path).is_ok(), "own PID is not a rival");
+
+        drop(pid_file);
+        assert!(!path.exists());
+        assert_eq!(lst_core::sync::read_pid_file(&path).unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_sync_request_signal_is_received() {
+        let mut requests = ManualSyncSignal::listen();
+        lst_core::sync::request_daemon_sync(std::process::id()).unwrap();
+        let received = tokio::time::timeout(std::time::Duration::from_secs(5), requests.recv());
+        assert!(received.await.is_ok(), "SIGUSR1 did not arrive");
+
+        // One request triggers one cycle
+        let again = tokio::time::timeout(std::time::Duration::from_millis(200), requests.recv());
+        assert!(again.await.is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_running_daemon_blocks_a_second_start() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("syncd.pid");
+        // A copy of sleep named lst-syncd passes for a running daemon
+        let fake = dir.path().join("lst-syncd");
+        fs::copy("/bin/sleep", &fake).unwrap();
+        let mut child = std::process::Command::new(&fake).arg("30").spawn().unwrap();
+        // spawn() may return before exec has renamed the child
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while !lst_core::sync::is_syncd_process(child.id()) && std::time::Instant::now() < deadline
+        {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        fs::write(&path, format!("{}\n", child.id())).unwrap();
+
+        let err = ensure_not_running(&path).unwrap_err().to_string();
+        assert!(err.contains("already running"), "{}", err);
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert!(!process_alive(child.id()));
+        assert!(ensure_not_running(&path).is_ok());
+        assert!(!path.exists(), "stale PID file is removed");
+    }
+
+    #[test]
+    fn test_pid_reused_by_other_program_does_not_block_start() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("syncd.pid");
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        fs::write(&path, format!("{}\n", child.id())).unwrap();
+
+        let result = ensure_not_running(&path);
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert!(result.is_ok());
+        assert!(!path.exists());
+    }
+}
@@ -1,14 +1,19 @@
 use anyhow::{Context, Result};
 use automerge::{Automerge, ObjType, ReadDoc, Value};
+use chrono::{DateTime, Utc};
 use lst_core::storage;
 use lst_core::sync::{
     canonicalize_doc_path, extract_automerge_content, path_from_relative,
     path_from_server_filename, write_document, CanonicalDocPath, DocumentKind,
 };
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
 use std::collections::HashSet;
 use std::path::Path;
 
+/// Changes queued for a document as `(doc_id, device_id, changes)`
+pub type PendingBatch = (String, String, Vec<Vec<u8>>);
+
 /// Local SQLite database used by lst-syncd
 pub struct LocalDb {
     pub(crate) conn: Connection,
@@ -33,8 +38,31 @@ impl LocalDb {
                 owner TEXT NOT NULL,
                 writers TEXT,
                 readers TEXT
+            );
+            CREATE TABLE IF NOT EXISTS pending_changes (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                doc_id TEXT NOT NULL,
+                device_id TEXT NOT NULL,
+                change_hash TEXT NOT NULL,
+                change BLOB NOT NULL,
+                UNIQUE(doc_id, change_hash)
+            );
+            CREATE TABLE IF NOT EXISTS doc_authors (
+                doc_id TEXT PRIMARY KEY,
+                device_id TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS acked_changes (
+                doc_id TEXT NOT NULL,
+                change_hash TEXT NOT NULL,
+                PRIMARY KEY (doc_id, change_hash)
+            );
+            CREATE TABLE IF NOT EXISTS compactions (
+                doc_id TEXT PRIMARY KEY,
+                compacted_at TEXT NOT NULL
             );",
         )?;
+        conn.execute_batch(lst_core::sync::DELETED_DOCUMENTS_SCHEMA)?;
         Ok(Self { conn })
     }
 
@@ -255,14 +283,48 @@ impl LocalDb {
         Ok(None)
     }
 
-    /// Delete a document by id
+    /// Delete a document by id, along with any changes still queued or
+    /// acknowledged for it
     pub fn delete_document(&self, doc_id: &str) -> Result<()> {
         self.conn
             .execute("DELETE FROM documents WHERE doc_id = ?1", params![doc_id])?;
+        self.conn.execute(
+            "DELETE FROM pending_changes WHERE doc_id = ?1",
+            params![doc_id],
+        )?;
+        self.conn
+            .execute("DELETE FROM doc_authors WHERE doc_id = ?1", params![doc_id])?;
+        self.conn
+            .execute("DELETE FROM compactions WHERE doc_id = ?1", params![doc_id])?;
+        self.conn.execute(
+            "DELETE FROM acked_changes WHERE doc_id = ?1",
+            params![doc_id],
+        )?;
+        Ok(())
+    }
+
+    /// Doc ids still to be deleted on the server because their documents moved
+    /// to a new id (see [`lst_core::sync::rename_tracked_document`])
+    pub fn deleted_documents(&self) -> Result<HashSet<String>> {
+        let mut stmt = self.conn.prepare("SELECT doc_id FROM deleted_documents")?;
+        let ids = stmt.query_map([], |row| row.get(0))?;
+        Ok(ids.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Drop doc ids whose deletion the server has handled
+    pub fn forget_deleted_documents(&self, doc_ids: &HashSet<String>) -> Result<()> {
+        for doc_id in doc_ids {
+            self.conn.execute(
+                "DELETE FROM deleted_documents WHERE doc_id = ?1",
+                params![doc_id],
+            )?;
+        }
         Ok(())
     }
 
-    /// Save/overwrite snapshot bytes for a doc, inserting if absent
+    /// Save/overwrite snapshot bytes for a doc, inserting if absent. The doc's
+    /// acknowledged changes are forgotten, as the snapshot's history holds
+    /// everything they could be re-derived from
     pub fn save_document_snapshot(
         &self,
         doc_id: &str,
@@ -291,6 +353,10 @@ impl LocalDb {
                 params![doc_id, snapshot, owner, writers, readers],
             )?;
         }
+        self.conn.execute(
+            "DELETE FROM acked_changes WHERE doc_id = ?1",
+            params![doc_id],
+        )?;
         Ok(())
     }
 
@@ -322,17 +388,26 @@ impl LocalDb {
         Ok(())
     }
 
-    /// Insert a new document from snapshot with the original filename/path
+    /// Insert a new document from snapshot at the path encrypted in its server
+    /// filename, falling back to a name derived from the doc id when the
+    /// filename cannot be decrypted
     pub fn insert_new_document_from_snapshot_with_filename(
         &self,
         doc_id: &str,
-        relative_path: &str,
+        filename: &str,
+        key: &[u8; 32],
         snapshot: &[u8],
     ) -> Result<()> {
         let doc = Automerge::load(snapshot)?;
         let doc_kind = Self::detect_kind_from_doc(&doc);
         let content = extract_automerge_content(&doc, doc_kind)?;
-        let canonical = path_from_server_filename(relative_path)?;
+        let canonical = match path_from_server_filename(filename, key) {
+            Ok(canonical) => canonical,
+            Err(e) => {
+                println!("DEBUG: Using fallback path for {}: {:#}", doc_id, e);
+                path_from_relative(&format!("{}.md", &doc_id[..8.min(doc_id.len())]))?
+            }
+        };
 
         write_document(&canonical, &content)?;
         println!(
@@ -458,6 +533,132 @@ impl LocalDb {
         Ok(out)
     }
 
+    /// Queue changes that could not be pushed so they survive a restart.
+    /// Changes already queued or acknowledged by the server are skipped;
+    /// returns the number of changes added
+    pub fn enqueue_pending_changes(
+        &self,
+        doc_id: &str,
+        device_id: &str,
+        changes: &[Vec<u8>],
+    ) -> Result<usize> {
+        let mut added = 0;
+        for change in changes {
+            let hash = change_hash(change);
+            let acked: bool = self
+                .conn
+                .prepare("SELECT 1 FROM acked_changes WHERE doc_id = ?1 AND change_hash = ?2")?
+                .exists(params![doc_id, hash])?;
+            if acked {
+                continue;
+            }
+            added += self.conn.execute(
+                "INSERT OR IGNORE INTO pending_changes (doc_id, device_id, change_hash, change)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![doc_id, device_id, hash, change],
+            )?;
+        }
+        Ok(added)
+    }
+
+    /// Queued changes grouped by `(doc_id, device_id)`, each group in the order
+    /// the changes were queued
+    pub fn pending_changes(&self) -> Result<Vec<PendingBatch>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT doc_id, device_id, change FROM pending_changes ORDER BY id")?;
+        let mut rows = stmt.query([])?;
+        let mut out: Vec<PendingBatch> = Vec::new();
+        while let Some(row) = rows.next()? {
+            let doc_id: String = row.get(0)?;
+            let device_id: String = row.get(1)?;
+            let change: Vec<u8> = row.get(2)?;
+            match out
+                .iter_mut()
+                .find(|(d, dev, _)| *d == doc_id && *dev == device_id)
+            {
+                Some((_, _, changes)) => changes.push(change),
+                None => out.push((doc_id, device_id, vec![change])),
+            }
+        }
+        Ok(out)
+    }
+
+    /// Mark changes as accepted by the server and drop them from the queue
+    pub fn ack_pending_changes(&self, doc_id: &str, changes: &[Vec<u8>]) -> Result<()> {
+        for change in changes {
+            let hash = change_hash(change);
+            self.conn.execute(
+                "DELETE FROM pending_changes WHERE doc_id = ?1 AND change_hash = ?2",
+                params![doc_id, hash],
+            )?;
+            self.conn.execute(
+                "INSERT OR IGNORE INTO acked_changes (doc_id, change_hash) VALUES (?1, ?2)",
+                params![doc_id, hash],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Remember `device_id` as the last device that changed `doc_id`
+    pub fn record_last_author(&self, doc_id: &str, device_id: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO doc_authors (doc_id, device_id, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(doc_id) DO UPDATE SET
+                device_id = excluded.device_id,
+                updated_at = excluded.updated_at",
+            params![doc_id, device_id, chrono::Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Device that last changed `doc_id`, if one has been recorded
+    #[cfg(test)]
+    pub fn last_author(&self, doc_id: &str) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT device_id FROM doc_authors WHERE doc_id = ?1",
+                params![doc_id],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// When `doc_id` was last compacted at the server's request
+    pub fn last_compaction(&self, doc_id: &str) -> Result<Option<DateTime<Utc>>> {
+        let at: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT compacted_at FROM compactions WHERE doc_id = ?1",
+                params![doc_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(at
+            .and_then(|at| DateTime::parse_from_rfc3339(&at).ok())
+            .map(|at| at.with_timezone(&Utc)))
+    }
+
+    /// Record that `doc_id` was compacted at `at` and prune its local change
+    /// log (queued and acknowledged changes), which the snapshot supersedes
+    pub fn record_compaction(&self, doc_id: &str, at: DateTime<Utc>) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO compactions (doc_id, compacted_at) VALUES (?1, ?2)
+             ON CONFLICT(doc_id) DO UPDATE SET compacted_at = excluded.compacted_at",
+            params![doc_id, at.to_rfc3339()],
+        )?;
+        self.conn.execute(
+            "DELETE FROM pending_changes WHERE doc_id = ?1",
+            params![doc_id],
+        )?;
+        self.conn.execute(
+            "DELETE FROM acked_changes WHERE doc_id = ?1",
+            params![doc_id],
+        )?;
+        Ok(())
+    }
+
     /// Run pending database migrations
     pub fn run_migrations(&self) -> Result<()> {
         self.migrate_paths_to_relative()
@@ -504,3 +705,120 @@ impl LocalDb {
         Ok(())
     }
 }
+
+fn change_hash(change: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(change);
+    hex::encode(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_queue_keeps_order_and_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sync.db");
+        let first = vec![b"edit-1".to_vec(), b"edit-2".to_vec()];
+        let second = vec![b"edit-3".to_vec()];
+
+        {
+            let db = LocalDb::new(&path).unwrap();
+            assert_eq!(db.enqueue_pending_changes("doc", "dev", &first).unwrap(), 2);
+            assert_eq!(
+                db.enqueue_pending_changes("doc", "dev", &second).unwrap(),
+                1
+            );
+            // Re-queueing the same changes after another failed push is a no-op
+            assert_eq!(db.enqueue_pending_changes("doc", "dev", &first).unwrap(), 0);
+        }
+
+        let db = LocalDb::new(&path).unwrap();
+        let pending = db.pending_changes().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].0, "doc");
+        assert_eq!(pending[0].1, "dev");
+        assert_eq!(
+            pending[0].2,
+            vec![b"edit-1".to_vec(), b"edit-2".to_vec(), b"edit-3".to_vec()]
+        );
+    }
+
+    #[test]
+    fn test_acked_changes_are_not_queued_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = LocalDb::new(&dir.path().join("sync.db")).unwrap();
+        let changes = vec![b"edit-1".to_vec()];
+
+        db.enqueue_pending_changes("doc", "dev", &changes).unwrap();
+        db.ack_pending_changes("doc", &changes).unwrap();
+        assert!(db.pending_changes().unwrap().is_empty());
+
+        assert_eq!(
+            db.enqueue_pending_changes("doc", "dev", &changes).unwrap(),
+            0
+        );
+        assert!(db.pending_changes().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_acked_changes_are_pruned_with_snapshots_and_deletes() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = LocalDb::new(&dir.path().join("sync.db")).unwrap();
+        let acked = |doc_id: &str| -> i64 {
+            db.conn
+                .query_row(
+                    "SELECT COUNT(*) FROM acked_changes WHERE doc_id = ?1",
+                    params![doc_id],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+        let changes = vec![b"edit-1".to_vec(), b"edit-2".to_vec()];
+        for doc_id in ["doc", "other"] {
+            db.enqueue_pending_changes(doc_id, "dev", &changes).unwrap();
+            db.ack_pending_changes(doc_id, &changes).unwrap();
+        }
+        assert_eq!(acked("doc"), 2);
+
+        db.save_document_snapshot("doc", b"snapshot", None, None, None)
+            .unwrap();
+        assert_eq!(acked("doc"), 0);
+        assert_eq!(acked("other"), 2);
+
+        db.delete_document("other").unwrap();
+        assert_eq!(acked("other"), 0);
+    }
+
+    #[test]
+    fn test_moved_documents_are_queued_for_deletion_on_the_server() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = LocalDb::new(&dir.path().join("sync.db")).unwrap();
+        let old = lst_core::sync::path_from_relative_in(dir.path(), "lists/groceries.md");
+        let new = lst_core::sync::path_from_relative_in(dir.path(), "lists/food.md");
+        db.upsert_document(
+            &old.document_id(),
+            &old.relative_path,
+            "list",
+            "hash",
+            b"state",
+            "a@b.c",
+            None,
+            None,
+        )
+        .unwrap();
+        assert!(db.deleted_documents().unwrap().is_empty());
+
+        assert!(
+            lst_core::sync::rename_tracked_document(&db.conn, &old, &new, "- [ ] milk").unwrap()
+        );
+        let deleted = db.deleted_documents().unwrap();
+        assert_eq!(deleted, HashSet::from([old.document_id()]));
+        assert!(db.get_document(&old.document_id()).unwrap().is_none());
+        assert!(db.get_document(&new.document_id()).unwrap().is_some());
+
+        db.forget_deleted_documents(&deleted).unwrap();
+        assert!(db.deleted_documents().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,53 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::prelude::*;
+
+/// Size after which the log is rolled over to `syncd.log.1` on startup
+const MAX_LOG_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Move an oversized log at `path` aside (keeping one previous generation) so
+/// a long-running setup does not grow it without bound
+pub fn roll_over(path: &Path) -> Result<()> {
+    let Ok(metadata) = fs::metadata(path) else {
+        return Ok(());
+    };
+    if metadata.len() < MAX_LOG_BYTES {
+        return Ok(());
+    }
+    let mut previous = path.as_os_str().to_owned();
+    previous.push(".1");
+    fs::rename(path, &previous)
+        .with_context(|| format!("Failed to roll over log file: {}", path.display()))
+}
+
+/// Send `tracing` events to the log file at `path` (append-only, so `lst sync
+/// logs` can read it while the daemon writes) and, in the foreground, to
+/// stdout as well. The returned guard flushes pending lines when dropped.
+pub fn init(path: &Path, foreground: bool, verbose: bool) -> Result<WorkerGuard> {
+    let dir = path.parent().context("Log file has no parent directory")?;
+    let file_name = path.file_name().context("Log file has no file name")?;
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+
+    let (writer, guard) =
+        tracing_appender::non_blocking(tracing_appender::rolling::never(dir, file_name));
+    let level = if verbose {
+        LevelFilter::DEBUG
+    } else {
+        LevelFilter::INFO
+    };
+    let file_layer = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_ansi(false);
+    let stdout_layer = foreground.then(tracing_subscriber::fmt::layer);
+
+    tracing_subscriber::registry()
+        .with(file_layer.with_filter(level))
+        .with(stdout_layer.with_filter(level))
+        .try_init()
+        .context("Failed to initialise logging")?;
+    Ok(guard)
+}
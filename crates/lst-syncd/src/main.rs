@@ -1,5 +1,8 @@
 mod config;
+mod control;
+mod daemon;
 mod database;
+mod logging;
 mod sync;
 mod trigger;
 mod watcher;
@@ -7,9 +10,12 @@ mod watcher;
 use anyhow::Result;
 use clap::Parser;
 use lst_cli::storage;
-use std::path::PathBuf;
+use lst_core::sync::ManualSyncResult;
+use std::path::{Path, PathBuf};
 
-use crate::config::load_syncd_config;
+use crate::config::{load_syncd_config, Config};
+use crate::control::ControlEndpoint;
+use crate::daemon::{ManualSyncSignal, PidFile};
 use crate::sync::{run_migrations, SyncManager, SyncReason};
 use crate::trigger::{ServerTrigger, TriggerEvent};
 use crate::watcher::FileWatcher;
@@ -34,15 +40,14 @@ struct Args {
     migrate_only: bool,
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<()> {
     let args = Args::parse();
 
     // Expand config path
     let config_path = if args.config.starts_with("~/") {
         dirs::home_dir().unwrap().join(&args.config[2..])
     } else {
-        PathBuf::from(args.config)
+        PathBuf::from(&args.config)
     };
 
     // Load configuration
@@ -54,36 +59,97 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
-    if args.verbose {
-        println!("lst-syncd starting with config: {}", config_path.display());
-
-        // Get content directory with proper path expansion
-        eprintln!("DEBUG: About to call storage::get_content_dir()");
-        let content_dir = storage::get_content_dir()?;
-        eprintln!(
-            "DEBUG: storage::get_content_dir() returned: {}",
-            content_dir.display()
-        );
-        println!("Watching content directory: {}", content_dir.display());
-        if let Some(ref sync) = config.sync {
-            if let Some(ref server_url) = sync.server_url {
-                println!("Syncing to server: {}", server_url);
-            } else {
-                println!("No server configured - running in local-only mode");
+    // Forking has to happen before the tokio runtime spawns its threads
+    let pid_path = lst_core::sync::syncd_pid_path()?;
+    let log_path = lst_core::sync::syncd_log_path()?;
+    daemon::ensure_not_running(&pid_path)?;
+    logging::roll_over(&log_path)?;
+    let _pid_file = if args.foreground {
+        PidFile::write(&pid_path, std::process::id())?
+    } else {
+        detach(&pid_path, &log_path)?
+    };
+
+    // The log writer runs on its own thread, so it is only set up after forking
+    let _log_guard = logging::init(&log_path, args.foreground, args.verbose)?;
+    tracing::info!("lst-syncd started (PID {})", std::process::id());
+
+    tokio::runtime::Runtime::new()?.block_on(run(config_path, config))
+}
+
+/// Detach into the background, returning the PID file the daemon now owns
+#[cfg(unix)]
+fn detach(pid_path: &Path, log_path: &Path) -> Result<PidFile> {
+    daemon::daemonize(pid_path, log_path)?;
+    Ok(PidFile::adopt(pid_path))
+}
+
+#[cfg(not(unix))]
+fn detach(pid_path: &Path, _log_path: &Path) -> Result<PidFile> {
+    println!(
+        "lst-syncd cannot detach from the terminal on this platform; \
+         running in the foreground"
+    );
+    PidFile::write(pid_path, std::process::id())
+}
+
+/// Resolves on Ctrl-C, or on SIGTERM (as sent by `lst sync stop`) on Unix
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        if let Ok(mut terminate) = signal(SignalKind::terminate()) {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = terminate.recv() => {}
             }
-        } else {
-            println!("No sync configuration found - running in local-only mode");
+            return;
         }
     }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Run a sync cycle requested by the user
+async fn manual_sync_now(sync_manager: &mut SyncManager) -> ManualSyncResult {
+    if !sync_manager.has_server() {
+        tracing::info!("Manual sync requested, but no server is configured");
+        return ManualSyncResult::Skipped;
+    }
+    tracing::info!("Manual sync requested");
+    match sync_manager.sync_now(SyncReason::Manual).await {
+        Ok(()) => {
+            tracing::info!("Manual sync finished");
+            ManualSyncResult::Synced
+        }
+        Err(e) => {
+            tracing::warn!("Manual sync failed: {e:#}");
+            ManualSyncResult::Failed {
+                error: format!("{e:#}"),
+            }
+        }
+    }
+}
+
+async fn run(config_path: PathBuf, config: Config) -> Result<()> {
+    tracing::debug!("Using config: {}", config_path.display());
+    let server_url = config
+        .sync
+        .as_ref()
+        .and_then(|sync| sync.server_url.as_ref());
+    match server_url {
+        Some(server_url) => tracing::info!("Syncing to server: {}", server_url),
+        None => tracing::info!("No server configured - running in local-only mode"),
+    }
 
     // Initialize file watcher
-    eprintln!("DEBUG: About to call storage::get_content_dir() for watcher");
     let content_dir = storage::get_content_dir()?;
-    eprintln!(
-        "DEBUG: storage::get_content_dir() for watcher returned: {}",
-        content_dir.display()
-    );
-    let mut watcher = FileWatcher::new(&content_dir)?;
+    tracing::info!("Watching content directory: {}", content_dir.display());
+    let mut watcher = FileWatcher::new(
+        &content_dir,
+        &config.sync_ignore(),
+        storage::ContentIgnore::from_config()?,
+        config.watch_debounce(),
+    )?;
 
     // Initialize sync manager
     let mut sync_manager = SyncManager::new(config.clone()).await?;
@@ -92,11 +158,20 @@ async fn main() -> Result<()> {
     }
 
     let mut trigger = ServerTrigger::spawn(&config, &sync_manager.state_snapshot());
-
-    if !args.foreground {
-        println!("lst-syncd daemon started");
-        // TODO: Daemonize process (platform-specific)
-    }
+    let mut manual_sync = ManualSyncSignal::listen();
+    let mut control =
+        match ControlEndpoint::bind(config.syncd_control_addr(), sync_manager.daemon_status()?)
+            .await
+        {
+            Ok(control) => {
+                tracing::info!("Control endpoint listening on http://{}", control.addr());
+                Some(control)
+            }
+            Err(e) => {
+                tracing::warn!("Control endpoint unavailable: {e:#}");
+                None
+            }
+        };
 
     // Main event loop
     loop {
@@ -104,11 +179,13 @@ async fn main() -> Result<()> {
             // Handle file system events
             event = watcher.next_event() => {
                 if let Some(event) = event {
-                    if args.verbose {
-                        println!("File event: {:?}", event);
-                    }
+                    tracing::debug!("File event: {:?}", event);
                     sync_manager.handle_file_event(event).await?;
-                    sync_manager.sync_now(SyncReason::LocalChange).await?;
+                    // Changes stay queued when the server is unreachable and are
+                    // pushed once the trigger listener reconnects
+                    if let Err(e) = sync_manager.sync_now(SyncReason::LocalChange).await {
+                        tracing::warn!("Local-change sync failed: {e:#}");
+                    }
                 }
             }
 
@@ -120,11 +197,9 @@ async fn main() -> Result<()> {
             }, if trigger.is_some() => {
                 match trigger_event {
                     Some(TriggerEvent::RemoteChange) => {
-                        if args.verbose {
-                            println!("Remote change trigger received");
-                        }
+                        tracing::debug!("Remote change trigger received");
                         if let Err(e) = sync_manager.sync_now(SyncReason::RemoteTrigger).await {
-                            eprintln!("Remote-triggered sync failed: {e}");
+                            tracing::warn!("Remote-triggered sync failed: {e:#}");
                         }
                     }
                     None => {
@@ -134,12 +209,35 @@ async fn main() -> Result<()> {
                 }
             }
 
+            // `lst sync now`, through the control endpoint or (from older
+            // clients) as a signal
+            request = async {
+                match control.as_mut() {
+                    Some(c) => c.next_request().await,
+                    None => None,
+                }
+            }, if control.is_some() => {
+                if let Some(reply) = request {
+                    let _ = reply.send(manual_sync_now(&mut sync_manager).await);
+                }
+            }
+            _ = manual_sync.recv() => {
+                manual_sync_now(&mut sync_manager).await;
+            }
+
             // Handle shutdown signals
-            _ = tokio::signal::ctrl_c() => {
-                println!("Received shutdown signal, stopping lst-syncd");
+            _ = shutdown_signal() => {
+                tracing::info!("Received shutdown signal, stopping lst-syncd");
                 break;
             }
         }
+
+        if let Some(control) = &control {
+            match sync_manager.daemon_status() {
+                Ok(status) => control.update(status),
+                Err(e) => tracing::warn!("Failed to collect daemon status: {e:#}"),
+            }
+        }
     }
 
     Ok(())
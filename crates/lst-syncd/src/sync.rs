@@ -2,14 +2,11 @@ use crate::config::Config;
 use crate::database::LocalDb;
 use anyhow::{anyhow, Context, Result};
 use automerge::{Automerge, Change};
-use base64::engine::general_purpose;
-use base64::Engine;
 use futures_util::{SinkExt, StreamExt};
 use lst_core::config::State;
 use lst_core::crypto;
 use lst_core::sync::{
-    canonical_path_with_id, canonicalize_doc_path, extract_automerge_content, update_automerge_doc,
-    CanonicalDocPath, DocumentKind,
+    canonicalize_doc_path_in, extract_automerge_content, update_automerge_doc, DocumentKind,
 };
 use notify::Event;
 use sha2::{Digest, Sha256};
@@ -26,11 +23,16 @@ pub enum SyncReason {
     Startup,
     LocalChange,
     RemoteTrigger,
+    /// Requested by the user, e.g. with `lst sync now`
+    Manual,
 }
 
 impl SyncReason {
     fn force(self) -> bool {
-        matches!(self, SyncReason::Startup | SyncReason::RemoteTrigger)
+        matches!(
+            self,
+            SyncReason::Startup | SyncReason::RemoteTrigger | SyncReason::Manual
+        )
     }
 }
 
@@ -39,6 +41,8 @@ pub struct SyncManager {
     state: State,
     client: Option<reqwest::Client>,
     db: LocalDb,
+    /// Directory document paths are resolved against
+    content_dir: PathBuf,
     encryption_key: [u8; 32],
     pending_changes: HashMap<String, Vec<Vec<u8>>>,
     initial_sync_done: bool,
@@ -46,6 +50,10 @@ pub struct SyncManager {
     recently_synced_files: HashSet<std::path::PathBuf>,
     sync_in_progress: bool,
     force_sync_after_current: bool,
+    /// When the last sync cycle succeeded, for the control endpoint
+    last_sync: Option<chrono::DateTime<chrono::Utc>>,
+    /// Error of the last sync cycle, cleared by a successful one
+    last_error: Option<String>,
 }
 
 impl SyncManager {
@@ -81,7 +89,6 @@ impl SyncManager {
 
         let db_path = state
             .get_sync_database_path()
-            .cloned()
             .expect("sync database path must be set in state");
 
         let db = LocalDb::new(&db_path)?;
@@ -125,15 +132,38 @@ impl SyncManager {
             state,
             client,
             db,
+            content_dir: lst_core::storage::get_content_dir()?,
             encryption_key,
             pending_changes: HashMap::new(),
             initial_sync_done: false,
             recently_synced_files: HashSet::new(),
             sync_in_progress: false,
             force_sync_after_current: false,
+            last_sync: None,
+            last_error: None,
         })
     }
 
+    /// Manager syncing the documents in `db`, without a server or stored state
+    #[cfg(test)]
+    fn for_tests(db: LocalDb, content_dir: &Path, encryption_key: [u8; 32]) -> Self {
+        Self {
+            config: Config::default(),
+            state: State::default(),
+            client: None,
+            db,
+            content_dir: content_dir.to_path_buf(),
+            encryption_key,
+            pending_changes: HashMap::new(),
+            initial_sync_done: false,
+            recently_synced_files: HashSet::new(),
+            sync_in_progress: false,
+            force_sync_after_current: false,
+            last_sync: None,
+            last_error: None,
+        }
+    }
+
     pub fn state_snapshot(&self) -> State {
         self.state.clone()
     }
@@ -142,19 +172,28 @@ impl SyncManager {
         self.client.is_some()
     }
 
+    /// Live state reported by the control endpoint; the connection state is
+    /// filled in by the endpoint itself
+    pub fn daemon_status(&self) -> Result<lst_core::sync::DaemonStatus> {
+        let queued = collect_pending(&self.db, self.pending_changes.clone())?;
+        Ok(lst_core::sync::DaemonStatus {
+            pid: std::process::id(),
+            server: self
+                .config
+                .sync
+                .as_ref()
+                .and_then(|sync| sync.server_url.clone()),
+            connection: None,
+            queued_changes: queued.values().map(Vec::len).sum(),
+            last_sync: self.last_sync,
+            last_error: self.last_error.clone(),
+        })
+    }
+
     pub async fn handle_file_event(&mut self, event: Event) -> Result<()> {
         for original_path in event.paths {
-            let (canonical, derived_doc_id) = match canonical_path_with_id(&original_path) {
-                Ok(result) => result,
-                Err(e) => {
-                    eprintln!(
-                        "DEBUG: Skipping path {}: failed to canonicalize ({})",
-                        original_path.display(),
-                        e
-                    );
-                    continue;
-                }
-            };
+            let canonical = canonicalize_doc_path_in(&self.content_dir, &original_path);
+            let derived_doc_id = canonical.document_id();
 
             // Skip files we just created via sync
             if self.recently_synced_files.contains(&canonical.full_path) {
@@ -196,12 +235,22 @@ impl SyncManager {
                     if filename_str.starts_with('.')
                         || filename_str.ends_with(".tmp")
                         || filename_str.ends_with(".swp")
+                        || filename_str.ends_with(CONFLICT_SUFFIX)
                     {
                         continue;
                     }
                 }
             }
 
+            // Skip anything inside hidden directories (e.g. the `.lst/undo` journal)
+            if canonical
+                .relative_path
+                .split('/')
+                .any(|component| component.starts_with('.'))
+            {
+                continue;
+            }
+
             // Skip files we just created via sync
             let file_path_str = canonical.full_path.to_string_lossy().to_string();
             let relative_path = canonical.relative_path.clone();
@@ -279,12 +328,27 @@ impl SyncManager {
                     continue;
                 }
 
-                let mut doc = Automerge::load(&state)?;
-                let old_heads = doc.get_heads().into_iter().collect::<Vec<_>>();
-
-                update_automerge_doc(&mut doc, existing_kind, &new_content)?;
-
-                let new_state = doc.save();
+                let Some((new_state, changes)) =
+                    prepare_local_changes(&state, existing_kind, &new_content)?
+                else {
+                    // File was touched but its synced content is unchanged; remember the
+                    // new file hash so we don't reconsider it and skip the push entirely.
+                    println!(
+                        "DEBUG: Content of {} unchanged since last push, skipping",
+                        doc_id
+                    );
+                    self.db.upsert_document(
+                        &doc_id,
+                        &relative_path,
+                        &existing_doc_type,
+                        &hash,
+                        &state,
+                        &existing_owner,
+                        writers.as_deref(),
+                        readers.as_deref(),
+                    )?;
+                    continue;
+                };
 
                 println!(
                     "DEBUG: Updating existing document {} with {} bytes",
@@ -302,13 +366,6 @@ impl SyncManager {
                     readers.as_deref(),
                 )?;
 
-                let new_doc = Automerge::load(&new_state)?;
-                let changes = new_doc
-                    .get_changes(&old_heads)
-                    .into_iter()
-                    .map(|c| c.raw_bytes().to_vec())
-                    .collect::<Vec<_>>();
-
                 self.pending_changes
                     .entry(doc_id)
                     .or_insert_with(Vec::new)
@@ -358,53 +415,18 @@ impl SyncManager {
     pub async fn apply_remote_changes(
         &mut self,
         doc_id: &str,
+        from_device_id: &str,
         changes: Vec<Vec<u8>>,
     ) -> Result<()> {
-        if let Some((file_path, doc_type, _last_hash, state, owner, writers, readers)) =
+        if let Some((file_path, doc_type, last_hash, state, owner, writers, readers)) =
             self.db.get_document(doc_id)?
         {
             let doc_kind = DocumentKind::from_str(&doc_type);
-            let canonical =
-                canonicalize_doc_path(Path::new(&file_path)).unwrap_or_else(|_| CanonicalDocPath {
-                    full_path: PathBuf::from(&file_path),
-                    relative_path: file_path.clone(),
-                    kind: doc_kind,
-                });
-            let mut doc = Automerge::load(&state)?;
-
-            let mut change_objs = Vec::new();
-            for (i, raw) in changes.iter().enumerate() {
-                match crypto::decrypt(raw, &self.encryption_key) {
-                    Ok(decrypted) => match Change::from_bytes(decrypted) {
-                        Ok(change) => change_objs.push(change),
-                        Err(e) => {
-                            eprintln!(
-                                "WARNING: Failed to parse change {} for doc {}: {}",
-                                i, doc_id, e
-                            );
-                            continue;
-                        }
-                    },
-                    Err(e) => {
-                        eprintln!("WARNING: Failed to decrypt change {} for doc {} - likely different encryption key: {}", i, doc_id, e);
-                        eprintln!("  This typically happens when different devices use different encryption keys");
-                        eprintln!("  Skipping this change to prevent crash");
-                        continue;
-                    }
-                }
-            }
-
-            if change_objs.is_empty() {
-                eprintln!(
-                    "WARNING: No valid changes could be decrypted for doc {}, skipping",
-                    doc_id
-                );
+            let canonical = canonicalize_doc_path_in(&self.content_dir, Path::new(&file_path));
+            let Some(doc) = merge_remote_changes(&state, &self.encryption_key, doc_id, &changes)?
+            else {
                 return Ok(());
-            }
-
-            doc.apply_changes(change_objs)?;
-
-            let new_state = doc.save();
+            };
 
             let content = extract_automerge_content(&doc, doc_kind)?;
 
@@ -412,18 +434,22 @@ impl SyncManager {
             self.recently_synced_files
                 .insert(canonical.full_path.clone());
 
-            tokio::fs::write(&canonical.full_path, &content)
-                .await
-                .with_context(|| {
-                    format!(
-                        "Failed to write updated file: {}",
-                        canonical.full_path.display()
-                    )
-                })?;
-
-            let mut hasher = Sha256::new();
-            hasher.update(content.as_bytes());
-            let new_hash = hex::encode(hasher.finalize());
+            let (new_hash, new_state) =
+                match write_remote_content(&canonical.full_path, &content, &last_hash)? {
+                    RemoteWrite::Written => (content_hash(&content), doc.save()),
+                    RemoteWrite::Conflict(conflict_path) => {
+                        eprintln!(
+                            "WARNING: {} has unsynced local edits; remote version written to {}",
+                            canonical.full_path.display(),
+                            conflict_path.display()
+                        );
+                        self.recently_synced_files.remove(&canonical.full_path);
+                        // Keep the old hash and the pre-merge state, so the pending
+                        // local edit is picked up and pushed as a change concurrent
+                        // to the remote one instead of one that reverts it
+                        (last_hash, state)
+                    }
+                };
 
             let relative_path = canonical.relative_path.clone();
 
@@ -437,6 +463,7 @@ impl SyncManager {
                 writers.as_deref(),
                 readers.as_deref(),
             )?;
+            self.db.record_last_author(doc_id, from_device_id)?;
         }
 
         Ok(())
@@ -621,8 +648,26 @@ impl SyncManager {
         let (mut write, mut read) = ws.split();
         println!("WebSocket connection established with HTTP header auth");
 
+        // 0) Delete documents that moved to a new id, before listing, so they
+        //    don't come back under their old path
+        let deleted = self.db.deleted_documents()?;
+        for doc_id in &deleted {
+            let Ok(uuid) = Uuid::parse_str(doc_id) else {
+                continue;
+            };
+            let msg = lst_proto::ClientMessage::DeleteDocument { doc_id: uuid };
+            write
+                .send(Message::Text(serde_json::to_string(&msg)?))
+                .await?;
+            println!("DEBUG: Sent DeleteDocument for moved doc {}", doc_id);
+        }
+
         // 1) Discover server docs
-        let request_list = lst_proto::ClientMessage::RequestDocumentList;
+        let request_list = lst_proto::ClientMessage::RequestDocumentList {
+            after: None,
+            after_updated_at: None,
+            limit: Some(DOCUMENT_PAGE_SIZE),
+        };
         write
             .send(Message::Text(serde_json::to_string(&request_list)?))
             .await?;
@@ -662,6 +707,9 @@ impl SyncManager {
         let mut expected_snapshots = 0;
         let mut received_snapshots = 0;
         let mut received_document_list = false;
+        let mut server_ids = std::collections::HashSet::new();
+        let mut jwt_rejected = false;
+        let mut snapshot_retries = HashMap::new();
 
         loop {
             match timeout(Duration::from_secs(60), read.next()).await {
@@ -676,8 +724,12 @@ impl SyncManager {
                                 // Filter out our own changes to avoid infinite loops
                                 if from_device_id != device_id {
                                     println!("DEBUG: Applying {} remote changes for doc {} from device {}", changes.len(), doc_id, from_device_id);
-                                    self.apply_remote_changes(&doc_id.to_string(), changes)
-                                        .await?;
+                                    self.apply_remote_changes(
+                                        &doc_id.to_string(),
+                                        &from_device_id,
+                                        changes,
+                                    )
+                                    .await?;
                                 } else {
                                     println!(
                                         "DEBUG: Ignoring own changes for doc {} from device {}",
@@ -686,7 +738,6 @@ impl SyncManager {
                                 }
                             }
                             lst_proto::ServerMessage::DocumentList { documents } => {
-                                received_document_list = true;
                                 println!(
                                     "DEBUG: ✅ RECEIVED DocumentList with {} documents from server",
                                     documents.len()
@@ -704,7 +755,7 @@ impl SyncManager {
                                 // Request snapshots for unknown server docs
                                 for info in &documents {
                                     let id_str = info.doc_id.to_string();
-                                    if !local_ids.contains(&id_str) {
+                                    if !local_ids.contains(&id_str) && !deleted.contains(&id_str) {
                                         println!(
                                             "DEBUG: Requesting snapshot for missing doc: {}",
                                             id_str
@@ -722,12 +773,26 @@ impl SyncManager {
                                 }
                                 println!("DEBUG: Finished processing {} server documents, expecting {} snapshots", documents.len(), expected_snapshots);
 
+                                // A full page means there may be more; fetch it before
+                                // deciding which local docs the server is missing
+                                let page_len = documents.len();
+                                let next_page = lst_proto::ClientMessage::document_page_after(
+                                    documents.last(),
+                                    Some(DOCUMENT_PAGE_SIZE),
+                                );
+                                server_ids
+                                    .extend(documents.into_iter().map(|d| d.doc_id.to_string()));
+                                if page_len >= DOCUMENT_PAGE_SIZE as usize {
+                                    write
+                                        .send(Message::Text(serde_json::to_string(&next_page)?))
+                                        .await?;
+                                    continue;
+                                }
+                                received_document_list = true;
+                                // The deletions were handled before the list was sent
+                                self.db.forget_deleted_documents(&deleted)?;
+
                                 // Push snapshots for local docs missing on server
-                                use std::collections::HashSet;
-                                let server_ids: HashSet<String> = documents
-                                    .into_iter()
-                                    .map(|d| d.doc_id.to_string())
-                                    .collect();
                                 println!("DEBUG: Server has {} documents", server_ids.len());
                                 let local_docs_for_push = self.db.list_all_documents()?;
                                 let mut pushed_count = 0;
@@ -737,35 +802,18 @@ impl SyncManager {
                                     if !server_ids.contains(&doc_id) {
                                         println!("DEBUG: 📤 Pushing local doc {} to server (not on server)", doc_id);
                                         if let Ok(uuid) = Uuid::parse_str(&doc_id) {
-                                            // Extract relative path from content directory to preserve structure
                                             let content_dir = lst_core::storage::get_content_dir()
                                                 .unwrap_or_else(|_| std::path::PathBuf::from("."));
-                                            let relative_path = if Path::new(&path).is_absolute() {
-                                                Path::new(&path)
-                                                    .strip_prefix(&content_dir)
-                                                    .unwrap_or(Path::new("unknown.md"))
-                                                    .to_string_lossy()
-                                                    .to_string()
-                                            } else {
-                                                path.clone()
-                                            };
-
-                                            // Encrypt relative path before sending
-                                            let encrypted_filename = crypto::encrypt(
-                                                relative_path.as_bytes(),
+                                            let encoded_filename = encrypted_filename(
+                                                &path,
+                                                &content_dir,
                                                 &self.encryption_key,
                                             )?;
-                                            let encoded_filename = general_purpose::STANDARD
-                                                .encode(&encrypted_filename);
-
-                                            println!(
-                                                "DEBUG: 🔐 Encrypting relative path: {} for doc {}",
-                                                relative_path, doc_id
-                                            );
 
                                             let msg = lst_proto::ClientMessage::PushSnapshot {
                                                 doc_id: uuid,
                                                 filename: encoded_filename,
+                                                hash: Some(lst_proto::snapshot_hash(&state)),
                                                 snapshot: state,
                                             };
                                             if let Err(e) = write
@@ -794,6 +842,7 @@ impl SyncManager {
                                 doc_id,
                                 filename,
                                 snapshot,
+                                hash,
                             } => {
                                 received_snapshots += 1;
                                 println!(
@@ -804,58 +853,98 @@ impl SyncManager {
                                     snapshot.len()
                                 );
 
-                                // Decrypt filename
-                                let decrypted_filename = if let Ok(encrypted_bytes) =
-                                    general_purpose::STANDARD.decode(&filename)
-                                {
-                                    if let Ok(decrypted_bytes) =
-                                        crypto::decrypt(&encrypted_bytes, &self.encryption_key)
-                                    {
-                                        String::from_utf8(decrypted_bytes).unwrap_or_else(|_| {
-                                            format!("{}.md", &doc_id.to_string()[..8])
-                                        })
-                                    } else {
-                                        format!("{}.md", &doc_id.to_string()[..8])
-                                    }
-                                } else {
-                                    format!("{}.md", &doc_id.to_string()[..8])
-                                };
-
-                                println!("DEBUG: Decrypted filename: {}", decrypted_filename);
-
-                                // Persist snapshot as baseline
-                                let id_str = doc_id.to_string();
-                                match self.db.get_document(&id_str)? {
-                                    Some((_path, _typ, _hash, _state, owner, writers, readers)) => {
-                                        let _ = self.db.save_document_snapshot(
-                                            &id_str,
-                                            &snapshot,
-                                            Some(owner.as_str()),
-                                            writers.as_deref(),
-                                            readers.as_deref(),
-                                        );
-                                    }
-                                    None => {
-                                        let _ = self
-                                            .db
-                                            .insert_new_document_from_snapshot_with_filename(
+                                let check = check_snapshot(
+                                    doc_id,
+                                    &snapshot,
+                                    hash.as_deref(),
+                                    &mut snapshot_retries,
+                                    &mut write,
+                                )
+                                .await?;
+                                if check == SnapshotCheck::Requested {
+                                    expected_snapshots += 1;
+                                }
+
+                                // Persist intact snapshots as baseline
+                                if check == SnapshotCheck::Intact {
+                                    let id_str = doc_id.to_string();
+                                    match self.db.get_document(&id_str)? {
+                                        Some((
+                                            _path,
+                                            _typ,
+                                            _hash,
+                                            _state,
+                                            owner,
+                                            writers,
+                                            readers,
+                                        )) => {
+                                            let _ = self.db.save_document_snapshot(
                                                 &id_str,
-                                                &decrypted_filename,
                                                 &snapshot,
+                                                Some(owner.as_str()),
+                                                writers.as_deref(),
+                                                readers.as_deref(),
                                             );
+                                        }
+                                        None => {
+                                            let _ = self
+                                                .db
+                                                .insert_new_document_from_snapshot_with_filename(
+                                                    &id_str,
+                                                    &filename,
+                                                    &self.encryption_key,
+                                                    &snapshot,
+                                                );
+                                        }
                                     }
                                 }
 
                                 // Check if we've received all expected snapshots
-                                if expected_snapshots > 0
+                                if received_document_list
+                                    && expected_snapshots > 0
                                     && received_snapshots >= expected_snapshots
                                 {
                                     println!("DEBUG: Received all {} expected snapshots, closing connection", expected_snapshots);
                                     break;
                                 }
                             }
+                            lst_proto::ServerMessage::RequestCompaction { doc_id } => {
+                                let content_dir = lst_core::storage::get_content_dir()
+                                    .unwrap_or_else(|_| std::path::PathBuf::from("."));
+                                match respond_to_compaction(
+                                    &self.db,
+                                    &self.encryption_key,
+                                    &content_dir,
+                                    doc_id,
+                                    &mut write,
+                                )
+                                .await
+                                {
+                                    Ok(true) => {
+                                        println!("DEBUG: Sent compacted snapshot for {}", doc_id)
+                                    }
+                                    Ok(false) => println!(
+                                        "DEBUG: Skipping compaction of {} (unknown or compacted recently)",
+                                        doc_id
+                                    ),
+                                    Err(e) => {
+                                        eprintln!("WARNING: Failed to compact doc {}: {}", doc_id, e)
+                                    }
+                                }
+                            }
+                            lst_proto::ServerMessage::Error {
+                                doc_id,
+                                code,
+                                message,
+                            } if report_server_error(doc_id, &code, &message) => {
+                                jwt_rejected = true;
+                                break;
+                            }
                             _ => {}
                         }
+                    } else {
+                        // Newer servers may send variants this client does not know yet
+                        println!("DEBUG: Ignoring unrecognized server message: {}", txt);
                     }
                 }
                 Ok(Some(Ok(Message::Close(_)))) => {
@@ -882,6 +971,14 @@ impl SyncManager {
 
         // ignore errors closing
         let _ = write.close().await;
+
+        if jwt_rejected {
+            // Get a fresh token now so the next sync interval can authenticate
+            if let Err(e) = self.refresh_jwt_token().await {
+                eprintln!("Failed to refresh JWT token: {}", e);
+            }
+            return Ok(false);
+        }
         Ok(true) // Sync succeeded
     }
 
@@ -889,6 +986,7 @@ impl SyncManager {
     async fn ensure_initial_sync(&mut self) -> Result<()> {
         println!("DEBUG: Starting initial file discovery...");
         let content_dir = lst_core::storage::get_content_dir()?;
+        let ignore = lst_core::storage::ContentIgnore::from_config()?;
 
         // Recursively scan content directory for .md files
         let mut files_found = 0;
@@ -897,7 +995,12 @@ impl SyncManager {
         if let Ok(entries) = std::fs::read_dir(&content_dir) {
             for entry in entries.flatten() {
                 if let Err(e) = self
-                    .scan_directory_recursive(entry.path(), &mut files_found, &mut files_added)
+                    .scan_directory_recursive(
+                        entry.path(),
+                        ignore.as_ref(),
+                        &mut files_found,
+                        &mut files_added,
+                    )
                     .await
                 {
                     eprintln!("Error scanning directory {}: {}", entry.path().display(), e);
@@ -912,13 +1015,18 @@ impl SyncManager {
         Ok(())
     }
 
-    /// Recursively scan directory for markdown files
+    /// Recursively scan directory for markdown files, skipping what `ignore`
+    /// matches
     async fn scan_directory_recursive(
         &mut self,
         dir_path: std::path::PathBuf,
+        ignore: Option<&lst_core::storage::ContentIgnore>,
         files_found: &mut usize,
         files_added: &mut usize,
     ) -> Result<()> {
+        if ignore.is_some_and(|ignore| ignore.is_ignored(&dir_path, dir_path.is_dir())) {
+            return Ok(());
+        }
         if dir_path.is_file() {
             if let Some(ext) = dir_path.extension() {
                 if ext == "md" {
@@ -935,8 +1043,13 @@ impl SyncManager {
         } else if dir_path.is_dir() {
             if let Ok(entries) = std::fs::read_dir(&dir_path) {
                 for entry in entries.flatten() {
-                    Box::pin(self.scan_directory_recursive(entry.path(), files_found, files_added))
-                        .await?;
+                    Box::pin(self.scan_directory_recursive(
+                        entry.path(),
+                        ignore,
+                        files_found,
+                        files_added,
+                    ))
+                    .await?;
                 }
             }
         }
@@ -949,7 +1062,8 @@ impl SyncManager {
         file_path: &std::path::Path,
         files_added: &mut usize,
     ) -> Result<()> {
-        let (canonical, derived_doc_id) = canonical_path_with_id(file_path)?;
+        let canonical = canonicalize_doc_path_in(&self.content_dir, file_path);
+        let derived_doc_id = canonical.document_id();
         let file_path_str = canonical.full_path.to_string_lossy().to_string();
         let existing_doc_id = self
             .db
@@ -1042,11 +1156,23 @@ impl SyncManager {
         Ok(())
     }
 
+    /// Run a sync cycle, recording its outcome for [`Self::daemon_status`]
     pub async fn sync_now(&mut self, reason: SyncReason) -> Result<()> {
         if self.client.is_none() {
             return Ok(());
         }
+        let result = self.sync_cycle(reason).await;
+        match &result {
+            Ok(()) => {
+                self.last_sync = Some(chrono::Utc::now());
+                self.last_error = None;
+            }
+            Err(e) => self.last_error = Some(format!("{e:#}")),
+        }
+        result
+    }
 
+    async fn sync_cycle(&mut self, reason: SyncReason) -> Result<()> {
         if self.sync_in_progress {
             if reason.force() {
                 self.force_sync_after_current = true;
@@ -1066,7 +1192,8 @@ impl SyncManager {
             let mut encrypted_total = 0;
             let mut encrypted: HashMap<String, Vec<Vec<u8>>> = HashMap::new();
 
-            let pending = std::mem::take(&mut self.pending_changes);
+            // Changes queued by earlier failed pushes go out before new ones
+            let pending = collect_pending(&self.db, std::mem::take(&mut self.pending_changes))?;
             if !pending.is_empty() {
                 println!(
                     "DEBUG: Preparing {} documents with pending changes",
@@ -1099,20 +1226,27 @@ impl SyncManager {
                 );
             }
 
+            let device_id = self
+                .state
+                .device
+                .device_id
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string());
             match self.sync_with_server(encrypted).await {
                 Ok(true) => {
                     println!(
                         "DEBUG: Sync completed successfully for {:?}",
                         reason_to_process
                     );
+                    settle_pending(&self.db, &device_id, &pending, true)?;
                 }
                 Ok(false) => {
-                    println!("DEBUG: Sync connection failed, restoring pending changes");
-                    self.pending_changes = pending;
+                    println!("DEBUG: Sync connection failed, queueing pending changes");
+                    settle_pending(&self.db, &device_id, &pending, false)?;
                 }
                 Err(e) => {
-                    self.pending_changes = pending;
                     self.sync_in_progress = false;
+                    settle_pending(&self.db, &device_id, &pending, false)?;
                     return Err(e);
                 }
             }
@@ -1131,6 +1265,284 @@ impl SyncManager {
     }
 }
 
+/// Hash the extracted content of a document so edits can be compared cheaply.
+fn content_hash(content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Suffix of the file a remote version is written to when the local file has
+/// unsynced edits
+const CONFLICT_SUFFIX: &str = ".conflict.md";
+
+/// Outcome of [`write_remote_content`]
+#[derive(Debug, PartialEq)]
+enum RemoteWrite {
+    /// The document file was updated with the remote content
+    Written,
+    /// The document file had local edits; the remote content went to this path
+    Conflict(PathBuf),
+}
+
+/// Write remotely merged `content` to `path`, unless the file on disk no longer
+/// matches `last_hash` (the hash recorded at the last sync), i.e. it was edited
+/// locally in the meantime. In that case the remote version is written next to
+/// it as `<name>.conflict.md` so neither side is lost
+fn write_remote_content(path: &Path, content: &str, last_hash: &str) -> Result<RemoteWrite> {
+    if !last_hash.is_empty() {
+        if let Ok(on_disk) = std::fs::read(path) {
+            if hex::encode(Sha256::digest(&on_disk)) != last_hash {
+                let stem = path
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let conflict_path = path.with_file_name(format!("{}{}", stem, CONFLICT_SUFFIX));
+                lst_core::storage::atomic_write(&conflict_path, content).with_context(|| {
+                    format!("Failed to write conflict file: {}", conflict_path.display())
+                })?;
+                return Ok(RemoteWrite::Conflict(conflict_path));
+            }
+        }
+    }
+    lst_core::storage::atomic_write(path, content)
+        .with_context(|| format!("Failed to write updated file: {}", path.display()))?;
+    Ok(RemoteWrite::Written)
+}
+
+/// New Automerge state plus the raw changes produced by a local edit.
+type LocalChanges = (Vec<u8>, Vec<Vec<u8>>);
+
+/// Apply `new_content` on top of the stored Automerge `state` and return the new
+/// state with the changes to push, or `None` when the extracted content is unchanged
+/// (e.g. an editor touched the file without a real edit).
+fn prepare_local_changes(
+    state: &[u8],
+    kind: DocumentKind,
+    new_content: &str,
+) -> Result<Option<LocalChanges>> {
+    let mut doc = Automerge::load(state)?;
+    let previous_hash = content_hash(&extract_automerge_content(&doc, kind)?);
+    let old_heads = doc.get_heads().into_iter().collect::<Vec<_>>();
+
+    update_automerge_doc(&mut doc, kind, new_content)?;
+
+    if content_hash(&extract_automerge_content(&doc, kind)?) == previous_hash {
+        return Ok(None);
+    }
+
+    let changes = doc
+        .get_changes(&old_heads)
+        .into_iter()
+        .map(|c| c.raw_bytes().to_vec())
+        .collect::<Vec<_>>();
+    Ok(Some((doc.save(), changes)))
+}
+
+/// Decrypt remote `changes` and apply them on top of the stored Automerge `state`.
+/// Changes that fail to decrypt or parse are skipped; returns `None` when none
+/// of them could be used
+fn merge_remote_changes(
+    state: &[u8],
+    key: &[u8; 32],
+    doc_id: &str,
+    changes: &[Vec<u8>],
+) -> Result<Option<Automerge>> {
+    let mut doc = Automerge::load(state)?;
+
+    let mut change_objs = Vec::new();
+    for (i, raw) in changes.iter().enumerate() {
+        match crypto::decrypt(raw, key) {
+            Ok(decrypted) => match Change::from_bytes(decrypted) {
+                Ok(change) => change_objs.push(change),
+                Err(e) => {
+                    eprintln!(
+                        "WARNING: Failed to parse change {} for doc {}: {}",
+                        i, doc_id, e
+                    );
+                    continue;
+                }
+            },
+            Err(e) => {
+                eprintln!("WARNING: Failed to decrypt change {} for doc {} - likely different encryption key: {}", i, doc_id, e);
+                eprintln!(
+                    "  This typically happens when different devices use different encryption keys"
+                );
+                eprintln!("  Skipping this change to prevent crash");
+                continue;
+            }
+        }
+    }
+
+    if change_objs.is_empty() {
+        eprintln!(
+            "WARNING: No valid changes could be decrypted for doc {}, skipping",
+            doc_id
+        );
+        return Ok(None);
+    }
+
+    doc.apply_changes(change_objs)?;
+    Ok(Some(doc))
+}
+
+/// Minimum time between two compactions of the same document, so repeated
+/// `RequestCompaction` messages don't cause a storm of snapshot pushes
+const COMPACTION_COOLDOWN_MINUTES: i64 = 10;
+
+/// Encrypt a document path, relative to the content directory, for use as
+/// its filename on the server
+fn encrypted_filename(path: &str, content_dir: &Path, key: &[u8; 32]) -> Result<String> {
+    let relative_path = if Path::new(path).is_absolute() {
+        Path::new(path)
+            .strip_prefix(content_dir)
+            .unwrap_or(Path::new("unknown.md"))
+            .to_string_lossy()
+            .to_string()
+    } else {
+        path.to_string()
+    };
+    crypto::encrypt_filename(&relative_path, key)
+}
+
+/// Answer a server `RequestCompaction`: re-save the stored Automerge state as
+/// one compacted snapshot, send it as `PushSnapshot` and prune the doc's local
+/// change log. Returns `false` without sending anything for unknown docs or
+/// docs compacted within the last [`COMPACTION_COOLDOWN_MINUTES`]
+async fn respond_to_compaction<W>(
+    db: &LocalDb,
+    key: &[u8; 32],
+    content_dir: &Path,
+    doc_id: Uuid,
+    write: &mut W,
+) -> Result<bool>
+where
+    W: futures_util::Sink<Message> + Unpin,
+    W::Error: std::error::Error + Send + Sync + 'static,
+{
+    let id = doc_id.to_string();
+    let now = chrono::Utc::now();
+    if let Some(last) = db.last_compaction(&id)? {
+        if now - last < chrono::Duration::minutes(COMPACTION_COOLDOWN_MINUTES) {
+            return Ok(false);
+        }
+    }
+    let Some((path, _typ, _hash, state, _owner, _writers, _readers)) = db.get_document(&id)? else {
+        return Ok(false);
+    };
+
+    let snapshot = Automerge::load(&state)?.save();
+    let msg = lst_proto::ClientMessage::PushSnapshot {
+        doc_id,
+        filename: encrypted_filename(&path, content_dir, key)?,
+        hash: Some(lst_proto::snapshot_hash(&snapshot)),
+        snapshot: snapshot.clone(),
+    };
+    write
+        .send(Message::Text(serde_json::to_string(&msg)?))
+        .await?;
+
+    db.save_document_snapshot(&id, &snapshot, None, None, None)?;
+    db.record_compaction(&id, now)?;
+    Ok(true)
+}
+
+/// Times a snapshot failing its checksum is requested again within one sync
+const MAX_SNAPSHOT_RETRIES: u32 = 3;
+
+/// What to do with a `Snapshot` received from the server
+#[derive(Debug, PartialEq)]
+enum SnapshotCheck {
+    /// The snapshot matches its hash and can be applied
+    Intact,
+    /// The snapshot was corrupt and has been requested again
+    Requested,
+    /// The snapshot was corrupt too often; it is dropped until the next sync
+    Rejected,
+}
+
+/// Verify a snapshot against the hash the server sent with it before it is
+/// applied. Corrupt (e.g. truncated) snapshots are never written; they are
+/// requested again up to [`MAX_SNAPSHOT_RETRIES`] times per document, counted
+/// in `retries`
+async fn check_snapshot<W>(
+    doc_id: Uuid,
+    snapshot: &[u8],
+    hash: Option<&str>,
+    retries: &mut HashMap<Uuid, u32>,
+    write: &mut W,
+) -> Result<SnapshotCheck>
+where
+    W: futures_util::Sink<Message> + Unpin,
+    W::Error: std::error::Error + Send + Sync + 'static,
+{
+    if lst_proto::verify_snapshot(snapshot, hash) {
+        return Ok(SnapshotCheck::Intact);
+    }
+    let attempts = retries.entry(doc_id).or_default();
+    if *attempts >= MAX_SNAPSHOT_RETRIES {
+        eprintln!(
+            "WARNING: Snapshot for doc {} failed its checksum {} times, skipping it",
+            doc_id,
+            *attempts + 1
+        );
+        return Ok(SnapshotCheck::Rejected);
+    }
+    *attempts += 1;
+    eprintln!(
+        "WARNING: Snapshot for doc {} failed its checksum, requesting it again",
+        doc_id
+    );
+    let request = lst_proto::ClientMessage::RequestSnapshot { doc_id };
+    write
+        .send(Message::Text(serde_json::to_string(&request)?))
+        .await?;
+    Ok(SnapshotCheck::Requested)
+}
+
+/// Merge changes still queued in the database with freshly generated ones.
+/// Queued changes come first so each document's changes keep their order
+fn collect_pending(
+    db: &LocalDb,
+    fresh: HashMap<String, Vec<Vec<u8>>>,
+) -> Result<HashMap<String, Vec<Vec<u8>>>> {
+    let mut pending: HashMap<String, Vec<Vec<u8>>> = HashMap::new();
+    for (doc_id, _device_id, changes) in db.pending_changes()? {
+        pending.entry(doc_id).or_default().extend(changes);
+    }
+    for (doc_id, changes) in fresh {
+        let queued = pending.entry(doc_id).or_default();
+        for change in changes {
+            if !queued.contains(&change) {
+                queued.push(change);
+            }
+        }
+    }
+    Ok(pending)
+}
+
+/// Record the outcome of a push: acknowledged changes leave the queue (and make
+/// this device the docs' last author), failed ones are persisted so they are
+/// retried after a reconnect or restart
+fn settle_pending(
+    db: &LocalDb,
+    device_id: &str,
+    pending: &HashMap<String, Vec<Vec<u8>>>,
+    pushed: bool,
+) -> Result<()> {
+    for (doc_id, changes) in pending {
+        if pushed {
+            db.ack_pending_changes(doc_id, changes)?;
+            if !changes.is_empty() {
+                db.record_last_author(doc_id, device_id)?;
+            }
+        } else {
+            db.enqueue_pending_changes(doc_id, device_id, changes)?;
+        }
+    }
+    Ok(())
+}
+
 pub fn run_migrations() -> Result<()> {
     let mut state = State::load()?;
     if state.get_sync_database_path().is_none() {
@@ -1140,10 +1552,358 @@ pub fn run_migrations() -> Result<()> {
 
     let db_path = state
         .get_sync_database_path()
-        .cloned()
         .ok_or_else(|| anyhow!("sync database path must be set in state"))?;
 
     let db = LocalDb::new(&db_path)?;
     db.run_migrations()?;
     Ok(())
 }
+
+/// Documents requested per `DocumentList` page; a shorter page is the last
+const DOCUMENT_PAGE_SIZE: u32 = 500;
+
+/// Log an `Error` sent by the sync server. Returns true when the server
+/// rejected our JWT and it needs to be refreshed
+fn report_server_error(doc_id: Option<Uuid>, code: &str, message: &str) -> bool {
+    match doc_id {
+        Some(doc_id) => eprintln!(
+            "WARNING: Sync server rejected request for doc {}: {} ({})",
+            doc_id, message, code
+        ),
+        None => eprintln!("WARNING: Sync server error: {} ({})", message, code),
+    }
+    code == lst_proto::error_code::UNAUTHORIZED
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list_state(content: &str) -> Vec<u8> {
+        let mut doc = Automerge::new();
+        update_automerge_doc(&mut doc, DocumentKind::List, content).unwrap();
+        doc.save()
+    }
+
+    #[test]
+    fn test_identical_content_produces_no_push() {
+        let content = "---\ntitle: groceries\n---\n- [ ] milk ^abc12\n";
+        let state = list_state(content);
+
+        assert!(prepare_local_changes(&state, DocumentKind::List, content)
+            .unwrap()
+            .is_none());
+        // Trailing blank lines are not part of the synced list content either
+        let touched = format!("{}\n\n", content);
+        assert!(prepare_local_changes(&state, DocumentKind::List, &touched)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_changed_content_produces_push() {
+        let state = list_state("- [ ] milk ^abc12\n");
+        let (_, changes) = prepare_local_changes(&state, DocumentKind::List, "- [x] milk ^abc12\n")
+            .unwrap()
+            .expect("changed content should be pushed");
+        assert!(!changes.is_empty());
+    }
+
+    #[test]
+    fn test_offline_edits_queue_in_order_and_drain_on_reconnect() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = LocalDb::new(&dir.path().join("sync.db")).unwrap();
+
+        // Two edits of the same list while the server is unreachable
+        let state = list_state("- [ ] milk ^abc12\n");
+        let (state, first) =
+            prepare_local_changes(&state, DocumentKind::List, "- [x] milk ^abc12\n")
+                .unwrap()
+                .unwrap();
+        let pending =
+            collect_pending(&db, HashMap::from([("doc".to_string(), first.clone())])).unwrap();
+        settle_pending(&db, "dev", &pending, false).unwrap();
+
+        let (_, second) = prepare_local_changes(
+            &state,
+            DocumentKind::List,
+            "- [x] milk ^abc12\n- [ ] eggs ^def34\n",
+        )
+        .unwrap()
+        .unwrap();
+        let pending =
+            collect_pending(&db, HashMap::from([("doc".to_string(), second.clone())])).unwrap();
+        settle_pending(&db, "dev", &pending, false).unwrap();
+
+        let expected: Vec<Vec<u8>> = first.iter().chain(second.iter()).cloned().collect();
+        assert_eq!(db.pending_changes().unwrap()[0].2, expected);
+
+        // Reconnect: queued changes are pushed in order and the queue empties
+        let pending = collect_pending(&db, HashMap::new()).unwrap();
+        assert_eq!(pending["doc"], expected);
+        settle_pending(&db, "dev", &pending, true).unwrap();
+        assert!(db.pending_changes().unwrap().is_empty());
+        assert!(collect_pending(&db, HashMap::new()).unwrap().is_empty());
+        assert_eq!(db.last_author("doc").unwrap().as_deref(), Some("dev"));
+    }
+
+    #[test]
+    fn test_remote_change_after_local_edit_writes_conflict_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ideas.md");
+        let synced = "# Ideas\n- first\n";
+        std::fs::write(&path, synced).unwrap();
+        let last_hash = content_hash(synced);
+
+        // Edited in an editor before the watcher pushed it
+        let local = "# Ideas\n- first\n- local thought\n";
+        std::fs::write(&path, local).unwrap();
+        let remote = "# Ideas\n- first\n- remote thought\n";
+
+        let outcome = write_remote_content(&path, remote, &last_hash).unwrap();
+        let conflict_path = dir.path().join("ideas.conflict.md");
+        assert_eq!(outcome, RemoteWrite::Conflict(conflict_path.clone()));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), local);
+        assert_eq!(std::fs::read_to_string(&conflict_path).unwrap(), remote);
+
+        // Once the local edit is synced, remote content is applied in place
+        let outcome = write_remote_content(&path, remote, &content_hash(local)).unwrap();
+        assert_eq!(outcome, RemoteWrite::Written);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), remote);
+    }
+
+    #[tokio::test]
+    async fn test_local_edit_after_conflict_does_not_revert_remote_edit() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = LocalDb::new(&dir.path().join("sync.db")).unwrap();
+        let key = [7u8; 32];
+        let path = dir.path().join("notes").join("ideas.md");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+        let synced = "# Ideas\n- first\n";
+        std::fs::write(&path, synced).unwrap();
+        let mut base = Automerge::new();
+        update_automerge_doc(&mut base, DocumentKind::Note, synced).unwrap();
+        let base_state = base.save();
+        db.upsert_document(
+            "doc",
+            "notes/ideas.md",
+            "note",
+            &content_hash(synced),
+            &base_state,
+            "local",
+            None,
+            None,
+        )
+        .unwrap();
+
+        // Another device adds a line while this one has an unsynced edit
+        let (_, remote) = prepare_local_changes(
+            &base_state,
+            DocumentKind::Note,
+            "# Ideas\n- first\n- remote thought\n",
+        )
+        .unwrap()
+        .unwrap();
+        let encrypted: Vec<Vec<u8>> = remote
+            .iter()
+            .map(|c| crypto::encrypt(c, &key).unwrap())
+            .collect();
+        std::fs::write(&path, "# Ideas\n- first\n- local thought\n").unwrap();
+
+        let mut manager = SyncManager::for_tests(db, dir.path(), key);
+        manager
+            .apply_remote_changes("doc", "device-x", encrypted)
+            .await
+            .unwrap();
+        assert!(dir.path().join("notes").join("ideas.conflict.md").exists());
+
+        // The next scan pushes only the local edit
+        let event = Event::new(notify::EventKind::Modify(notify::event::ModifyKind::Any))
+            .add_path(path.clone());
+        manager.handle_file_event(event).await.unwrap();
+        let local = manager
+            .pending_changes
+            .remove("doc")
+            .expect("local edit is pushed");
+
+        let mut other = Automerge::load(&base_state).unwrap();
+        let changes = remote
+            .iter()
+            .chain(local.iter())
+            .map(|c| Change::from_bytes(c.clone()).unwrap());
+        other.apply_changes(changes).unwrap();
+        let merged = extract_automerge_content(&other, DocumentKind::Note).unwrap();
+        assert!(merged.contains("- remote thought\n"), "{}", merged);
+        assert!(merged.contains("- local thought\n"), "{}", merged);
+    }
+
+    #[tokio::test]
+    async fn test_applying_remote_change_records_its_device() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = LocalDb::new(&dir.path().join("sync.db")).unwrap();
+        let key = [7u8; 32];
+        let path = dir.path().join("lists").join("groceries.md");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+        let synced = "- [ ] milk ^abc12\n";
+        std::fs::write(&path, synced).unwrap();
+        let state = list_state(synced);
+        db.upsert_document(
+            "doc",
+            "lists/groceries.md",
+            "list",
+            &content_hash(synced),
+            &state,
+            "local",
+            None,
+            None,
+        )
+        .unwrap();
+        let (_, changes) = prepare_local_changes(&state, DocumentKind::List, "- [x] milk ^abc12\n")
+            .unwrap()
+            .unwrap();
+        let encrypted: Vec<Vec<u8>> = changes
+            .iter()
+            .map(|c| crypto::encrypt(c, &key).unwrap())
+            .collect();
+
+        let mut manager = SyncManager::for_tests(db, dir.path(), key);
+        manager
+            .apply_remote_changes("doc", "device-x", encrypted.clone())
+            .await
+            .unwrap();
+        assert!(std::fs::read_to_string(&path).unwrap().contains("[x] milk"));
+        assert_eq!(
+            manager.db.last_author("doc").unwrap().as_deref(),
+            Some("device-x")
+        );
+
+        // Undecryptable changes are skipped and leave nothing to apply
+        assert!(merge_remote_changes(&state, &[0u8; 32], "doc", &encrypted)
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_request_compaction_pushes_one_snapshot_and_prunes_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = LocalDb::new(&dir.path().join("sync.db")).unwrap();
+        let key = [7u8; 32];
+        let doc_id = Uuid::new_v4();
+        let id = doc_id.to_string();
+
+        let state = list_state("- [ ] milk ^abc12\n");
+        let (state, changes) =
+            prepare_local_changes(&state, DocumentKind::List, "- [x] milk ^abc12\n")
+                .unwrap()
+                .unwrap();
+        db.save_document_snapshot(&id, &state, None, None, None)
+            .unwrap();
+        db.enqueue_pending_changes(&id, "dev", &changes).unwrap();
+
+        let mut sent: Vec<Message> = Vec::new();
+        assert!(
+            respond_to_compaction(&db, &key, dir.path(), doc_id, &mut sent)
+                .await
+                .unwrap()
+        );
+        // A repeated request right away is ignored
+        assert!(
+            !respond_to_compaction(&db, &key, dir.path(), doc_id, &mut sent)
+                .await
+                .unwrap()
+        );
+
+        assert_eq!(sent.len(), 1);
+        let Message::Text(txt) = &sent[0] else {
+            panic!("expected a text message");
+        };
+        match serde_json::from_str::<lst_proto::ClientMessage>(txt).unwrap() {
+            lst_proto::ClientMessage::PushSnapshot {
+                doc_id: sent_id,
+                snapshot,
+                ..
+            } => {
+                assert_eq!(sent_id, doc_id);
+                let doc = Automerge::load(&snapshot).unwrap();
+                assert!(extract_automerge_content(&doc, DocumentKind::List)
+                    .unwrap()
+                    .contains("[x] milk"));
+            }
+            other => panic!("expected PushSnapshot, got {:?}", other),
+        }
+        assert!(db.pending_changes().unwrap().is_empty());
+        assert!(db.last_compaction(&id).unwrap().is_some());
+
+        // Unknown documents are not compacted
+        assert!(
+            !respond_to_compaction(&db, &key, dir.path(), Uuid::new_v4(), &mut sent)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_snapshot_is_requested_again_instead_of_applied() {
+        let doc_id = Uuid::new_v4();
+        let snapshot = list_state("- [ ] milk ^abc12\n");
+        let hash = lst_proto::snapshot_hash(&snapshot);
+        let truncated = &snapshot[..snapshot.len() / 2];
+        let mut retries = HashMap::new();
+        let mut sent: Vec<Message> = Vec::new();
+
+        for _ in 0..MAX_SNAPSHOT_RETRIES {
+            let check = check_snapshot(doc_id, truncated, Some(&hash), &mut retries, &mut sent)
+                .await
+                .unwrap();
+            assert_eq!(check, SnapshotCheck::Requested);
+        }
+        let check = check_snapshot(doc_id, truncated, Some(&hash), &mut retries, &mut sent)
+            .await
+            .unwrap();
+        assert_eq!(check, SnapshotCheck::Rejected);
+
+        assert_eq!(sent.len(), MAX_SNAPSHOT_RETRIES as usize);
+        for message in &sent {
+            let Message::Text(txt) = message else {
+                panic!("expected a text message");
+            };
+            assert!(matches!(
+                serde_json::from_str::<lst_proto::ClientMessage>(txt).unwrap(),
+                lst_proto::ClientMessage::RequestSnapshot { doc_id: id } if id == doc_id
+            ));
+        }
+
+        // Intact snapshots, and those from servers without hashes, are applied
+        for hash in [Some(hash.as_str()), None] {
+            let check = check_snapshot(doc_id, &snapshot, hash, &mut retries, &mut sent)
+                .await
+                .unwrap();
+            assert_eq!(check, SnapshotCheck::Intact);
+        }
+        assert_eq!(sent.len(), MAX_SNAPSHOT_RETRIES as usize);
+    }
+
+    #[test]
+    fn test_server_errors_request_jwt_refresh_only_when_unauthorized() {
+        let wire = r#"{"Error":{"doc_id":null,"code":"unauthorized","message":"expired"}}"#;
+        let lst_proto::ServerMessage::Error {
+            doc_id,
+            code,
+            message,
+        } = serde_json::from_str(wire).unwrap()
+        else {
+            panic!("expected an error message");
+        };
+        assert!(report_server_error(doc_id, &code, &message));
+        assert!(!report_server_error(
+            Some(Uuid::new_v4()),
+            lst_proto::error_code::FORBIDDEN,
+            "Not allowed"
+        ));
+
+        // Variants from newer servers fail to parse and are skipped, not fatal
+        assert!(serde_json::from_str::<lst_proto::ServerMessage>(r#"{"Future":{}}"#).is_err());
+    }
+}
@@ -1,7 +1,10 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use futures_util::{SinkExt, StreamExt};
 use lst_cli::config::Config;
 use lst_core::config::State;
+use lst_core::sync::ConnectionStatus;
+use rand::Rng;
+use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 use tokio::time::Duration;
 use tokio_tungstenite::tungstenite::client::IntoClientRequest;
@@ -20,10 +23,24 @@ pub struct ServerTrigger {
 impl ServerTrigger {
     pub fn spawn(config: &Config, state: &State) -> Option<Self> {
         let server_url = config.sync.as_ref().and_then(|s| s.server_url.clone())?;
-        let jwt = state.auth.jwt_token.clone()?;
+        let initial_jwt = state.auth.jwt_token.clone()?;
 
         let (tx, rx) = unbounded_channel();
-        tokio::spawn(run_listener(server_url, jwt, tx.clone()));
+        // Re-read the JWT before every attempt so a token refreshed by the sync
+        // manager is picked up when re-authenticating after a disconnect
+        let jwt_source = move || {
+            State::load()
+                .ok()
+                .and_then(|s| s.auth.jwt_token)
+                .or_else(|| Some(initial_jwt.clone()))
+        };
+        tokio::spawn(run_listener(
+            server_url,
+            jwt_source,
+            tx,
+            StatusHandle::persisted(),
+            Backoff::default(),
+        ));
 
         Some(Self { rx })
     }
@@ -33,6 +50,86 @@ impl ServerTrigger {
     }
 }
 
+/// Exponential reconnect backoff: base, 2x base, 4x base, ... capped at `max`,
+/// with up to 20% random jitter so many clients don't reconnect in lockstep
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    base: Duration,
+    max: Duration,
+    attempt: u32,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1), Duration::from_secs(60))
+    }
+}
+
+impl Backoff {
+    pub fn new(base: Duration, max: Duration) -> Self {
+        Self {
+            base,
+            max,
+            attempt: 0,
+        }
+    }
+
+    /// Number of consecutive failed attempts
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// Delay before the next attempt, without jitter
+    pub fn base_delay(&self) -> Duration {
+        let factor = 2u32.saturating_pow(self.attempt.min(16));
+        self.base.saturating_mul(factor).min(self.max)
+    }
+
+    /// Advance to the next attempt and return the delay to wait, with jitter
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.base_delay();
+        self.attempt = self.attempt.saturating_add(1);
+        let jitter = rand::thread_rng().gen_range(0.0..=0.2);
+        delay.mul_f64(1.0 + jitter).min(self.max)
+    }
+
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+}
+
+/// Current connection state of the listener, optionally mirrored to the status
+/// file read by `lst sync status`
+#[derive(Clone)]
+struct StatusHandle {
+    current: Arc<Mutex<ConnectionStatus>>,
+    persist: bool,
+}
+
+impl StatusHandle {
+    fn persisted() -> Self {
+        Self {
+            current: Arc::new(Mutex::new(ConnectionStatus::Connecting)),
+            persist: true,
+        }
+    }
+
+    fn set(&self, status: ConnectionStatus) {
+        if self.persist {
+            // The status file is informational only; failing to write it must not stop syncing
+            let _ = status.save();
+        }
+        *self.current.lock().unwrap() = status;
+    }
+
+    fn is_connected(&self) -> bool {
+        matches!(
+            *self.current.lock().unwrap(),
+            ConnectionStatus::Connected { .. }
+        )
+    }
+}
+
 fn normalize_ws_url(server_url: &str) -> String {
     let mut ws_url = server_url
         .replace("http://", "ws://")
@@ -48,16 +145,52 @@ fn normalize_ws_url(server_url: &str) -> String {
     ws_url
 }
 
-async fn run_listener(server_url: String, jwt: String, tx: UnboundedSender<TriggerEvent>) {
+async fn run_listener<F>(
+    server_url: String,
+    jwt_source: F,
+    tx: UnboundedSender<TriggerEvent>,
+    status: StatusHandle,
+    mut backoff: Backoff,
+) where
+    F: Fn() -> Option<String>,
+{
     loop {
-        if let Err(e) = listen_once(&server_url, &jwt, tx.clone()).await {
-            eprintln!("Server trigger listener error: {e}");
+        if tx.is_closed() {
+            return;
+        }
+
+        status.set(ConnectionStatus::Connecting);
+        match jwt_source() {
+            Some(jwt) => {
+                if let Err(e) = listen_once(&server_url, &jwt, &tx, &status).await {
+                    eprintln!("Server trigger listener error: {e}");
+                }
+            }
+            None => eprintln!("Server trigger listener: no JWT available"),
+        }
+
+        // A session that got as far as authenticating starts the backoff over
+        if status.is_connected() {
+            backoff.reset();
         }
-        tokio::time::sleep(Duration::from_secs(5)).await;
+
+        let delay = backoff.next_delay();
+        let retry_at = chrono::Utc::now()
+            + chrono::Duration::from_std(delay).unwrap_or_else(|_| chrono::Duration::seconds(60));
+        status.set(ConnectionStatus::Reconnecting {
+            attempt: backoff.attempt(),
+            retry_at,
+        });
+        tokio::time::sleep(delay).await;
     }
 }
 
-async fn listen_once(server_url: &str, jwt: &str, tx: UnboundedSender<TriggerEvent>) -> Result<()> {
+async fn listen_once(
+    server_url: &str,
+    jwt: &str,
+    tx: &UnboundedSender<TriggerEvent>,
+    status: &StatusHandle,
+) -> Result<()> {
     let ws_url = normalize_ws_url(server_url);
     let mut request = ws_url
         .as_str()
@@ -73,7 +206,11 @@ async fn listen_once(server_url: &str, jwt: &str, tx: UnboundedSender<TriggerEve
     let (mut write, mut read) = ws.split();
 
     // Always ask for the latest snapshot list before listening
-    let request_list = lst_proto::ClientMessage::RequestDocumentList;
+    let request_list = lst_proto::ClientMessage::RequestDocumentList {
+        after: None,
+        after_updated_at: None,
+        limit: None,
+    };
     write
         .send(Message::Text(
             serde_json::to_string(&request_list)
@@ -81,6 +218,7 @@ async fn listen_once(server_url: &str, jwt: &str, tx: UnboundedSender<TriggerEve
         ))
         .await
         .context("Failed to send RequestDocumentList for triggers")?;
+    // Also makes the sync manager push anything queued while disconnected
     let _ = tx.send(TriggerEvent::RemoteChange);
 
     while let Some(msg) = read.next().await {
@@ -88,6 +226,14 @@ async fn listen_once(server_url: &str, jwt: &str, tx: UnboundedSender<TriggerEve
             Ok(Message::Text(text)) => {
                 if let Ok(server_msg) = serde_json::from_str::<lst_proto::ServerMessage>(&text) {
                     match server_msg {
+                        lst_proto::ServerMessage::Authenticated { success: true } => {
+                            status.set(ConnectionStatus::Connected {
+                                since: chrono::Utc::now(),
+                            });
+                        }
+                        lst_proto::ServerMessage::Authenticated { success: false } => {
+                            bail!("Sync server rejected authentication");
+                        }
                         lst_proto::ServerMessage::NewChanges { .. }
                         | lst_proto::ServerMessage::DocumentList { .. }
                         | lst_proto::ServerMessage::Snapshot { .. } => {
@@ -108,3 +254,99 @@ async fn listen_once(server_url: &str, jwt: &str, tx: UnboundedSender<TriggerEve
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+
+    #[test]
+    fn test_backoff_doubles_and_caps() {
+        let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(60));
+        let expected = [1, 2, 4, 8, 16, 32, 60, 60];
+        for secs in expected {
+            assert_eq!(backoff.base_delay(), Duration::from_secs(secs));
+            let delay = backoff.next_delay();
+            assert!(delay >= Duration::from_secs(secs));
+            assert!(
+                delay <= Duration::from_secs(60).min(Duration::from_secs_f64(secs as f64 * 1.2))
+            );
+        }
+        backoff.reset();
+        assert_eq!(backoff.base_delay(), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    #[allow(clippy::result_large_err)] // accept_hdr_async's callback signature
+    async fn test_listener_reconnects_and_reauthenticates_after_drop() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // Mock server: authenticate each connection, wait for the document list
+        // request, then drop the socket; report the bearer token seen each time
+        let server = tokio::spawn(async move {
+            let mut seen = Vec::new();
+            for _ in 0..2 {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut auth = None;
+                let mut ws =
+                    tokio_tungstenite::accept_hdr_async(stream, |req: &Request, resp: Response| {
+                        auth = req
+                            .headers()
+                            .get(AUTHORIZATION)
+                            .map(|v| v.to_str().unwrap().to_string());
+                        Ok(resp)
+                    })
+                    .await
+                    .unwrap();
+                let authenticated =
+                    serde_json::to_string(&lst_proto::ServerMessage::Authenticated {
+                        success: true,
+                    })
+                    .unwrap();
+                ws.send(Message::Text(authenticated)).await.unwrap();
+                let msg = ws.next().await.unwrap().unwrap();
+                assert!(msg.to_text().unwrap().contains("RequestDocumentList"));
+                seen.push(auth);
+                drop(ws);
+            }
+            seen
+        });
+
+        let (tx, mut rx) = unbounded_channel();
+        let status = StatusHandle {
+            current: Arc::new(Mutex::new(ConnectionStatus::Connecting)),
+            persist: false,
+        };
+        let tokens = Arc::new(Mutex::new(vec!["second".to_string(), "first".to_string()]));
+        let jwt_source = {
+            let tokens = tokens.clone();
+            move || tokens.lock().unwrap().pop()
+        };
+        let client = tokio::spawn(run_listener(
+            format!("http://{}", addr),
+            jwt_source,
+            tx,
+            status,
+            Backoff::new(Duration::from_millis(10), Duration::from_millis(50)),
+        ));
+
+        let seen = tokio::time::timeout(Duration::from_secs(10), server)
+            .await
+            .expect("client did not reconnect")
+            .unwrap();
+        client.abort();
+
+        assert_eq!(
+            seen,
+            vec![
+                Some("Bearer first".to_string()),
+                Some("Bearer second".to_string())
+            ]
+        );
+        // One trigger per (re)connection so queued changes get pushed
+        assert!(rx.recv().await.is_some());
+        assert!(rx.recv().await.is_some());
+    }
+}
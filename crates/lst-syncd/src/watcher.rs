@@ -1,19 +1,39 @@
 use anyhow::{Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use lst_core::storage::ContentIgnore;
 use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::sync::mpsc;
+use tokio::time::Instant;
 
 pub struct FileWatcher {
     _watcher: RecommendedWatcher,
     receiver: mpsc::UnboundedReceiver<notify::Result<Event>>,
+    content_dir: PathBuf,
+    ignore: GlobSet,
+    /// `.gitignore`/`.lstignore` rules, when `paths.respect_gitignore` is set
+    content_ignore: Option<ContentIgnore>,
+    debounce: Duration,
+    /// Latest event per path, with the time it was seen, waiting for the
+    /// debounce window to pass without further events for that path
+    pending: HashMap<PathBuf, (Event, Instant)>,
 }
 
 impl FileWatcher {
-    pub fn new(content_dir: &Path) -> Result<Self> {
-        eprintln!(
-            "DEBUG: FileWatcher::new called with path: {}",
-            content_dir.display()
-        );
+    /// Watch `content_dir` recursively, dropping events for paths matching
+    /// any of the `ignore` globs or the `content_ignore` rules. Events for a
+    /// path are held back until `debounce` has passed without another one
+    /// and then reported once.
+    pub fn new(
+        content_dir: &Path,
+        ignore: &[String],
+        content_ignore: Option<ContentIgnore>,
+        debounce: Duration,
+    ) -> Result<Self> {
+        tracing::debug!("Starting file watcher on {}", content_dir.display());
+        let ignore = build_ignore_set(ignore)?;
         let (tx, receiver) = mpsc::unbounded_channel();
 
         let mut watcher = RecommendedWatcher::new(
@@ -33,25 +53,173 @@ impl FileWatcher {
         Ok(Self {
             _watcher: watcher,
             receiver,
+            content_dir: content_dir.to_path_buf(),
+            ignore,
+            content_ignore,
+            debounce,
+            pending: HashMap::new(),
         })
     }
 
+    /// Next settled change: one event per path, carrying the kind of the last
+    /// event seen for it within the debounce window
     pub async fn next_event(&mut self) -> Option<Event> {
-        match self.receiver.recv().await {
-            Some(Ok(event)) => {
-                // Filter out events we don't care about
-                match event.kind {
-                    notify::EventKind::Create(_)
-                    | notify::EventKind::Modify(_)
-                    | notify::EventKind::Remove(_) => Some(event),
-                    _ => None,
-                }
+        loop {
+            let deadline = self.pending.values().map(|(_, seen)| *seen).min();
+            let deadline = deadline.map(|seen| seen + self.debounce);
+            if deadline.is_some_and(|deadline| deadline <= Instant::now()) {
+                return self.pop_oldest();
             }
-            Some(Err(e)) => {
-                eprintln!("File watcher error: {e}");
-                None
+
+            tokio::select! {
+                received = self.receiver.recv() => match received {
+                    Some(Ok(event)) => {
+                        if let Some(event) = self.filter(event) {
+                            self.queue(event);
+                        }
+                    }
+                    Some(Err(e)) => tracing::warn!("File watcher error: {e}"),
+                    None => return self.pop_oldest(),
+                },
+                _ = tokio::time::sleep_until(deadline.unwrap_or_else(Instant::now)),
+                    if deadline.is_some() => {}
             }
-            None => None,
         }
     }
+
+    /// Hold `event` back per path, replacing what is pending for each
+    fn queue(&mut self, event: Event) {
+        let now = Instant::now();
+        for path in &event.paths {
+            let single = Event {
+                paths: vec![path.clone()],
+                ..event.clone()
+            };
+            self.pending.insert(path.clone(), (single, now));
+        }
+    }
+
+    /// Remove and return the pending event that was last updated longest ago
+    fn pop_oldest(&mut self) -> Option<Event> {
+        let path = self
+            .pending
+            .iter()
+            .min_by_key(|(_, (_, seen))| *seen)
+            .map(|(path, _)| path.clone())?;
+        self.pending.remove(&path).map(|(event, _)| event)
+    }
+
+    /// Keep create/modify/remove events, minus their ignored paths
+    fn filter(&self, mut event: Event) -> Option<Event> {
+        match event.kind {
+            notify::EventKind::Create(_)
+            | notify::EventKind::Modify(_)
+            | notify::EventKind::Remove(_) => {}
+            _ => return None,
+        }
+        event.paths.retain(|path| !self.is_ignored(path));
+        (!event.paths.is_empty()).then_some(event)
+    }
+
+    /// Whether `path` (or any directory it lies in below the content
+    /// directory) matches an ignore glob or the content directory's ignore
+    /// files
+    fn is_ignored(&self, path: &Path) -> bool {
+        let relative = path.strip_prefix(&self.content_dir).unwrap_or(path);
+        self.ignore.is_match(relative)
+            || relative
+                .components()
+                .any(|component| self.ignore.is_match(component.as_os_str()))
+            || self
+                .content_ignore
+                .as_ref()
+                .is_some_and(|rules| rules.is_ignored(path, path.is_dir()))
+    }
+}
+
+fn build_ignore_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern)
+            .with_context(|| format!("Invalid ignore pattern in sync settings: {}", pattern))?;
+        builder.add(glob);
+    }
+    builder.build().context("Failed to build ignore patterns")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Paths of all events arriving within `window`
+    async fn collect_paths(watcher: &mut FileWatcher, window: Duration) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        let deadline = tokio::time::Instant::now() + window;
+        while let Ok(Some(event)) = tokio::time::timeout_at(deadline, watcher.next_event()).await {
+            paths.extend(event.paths);
+        }
+        paths
+    }
+
+    #[tokio::test]
+    async fn test_ignored_paths_produce_no_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let content_dir = dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(content_dir.join("lists/.git")).unwrap();
+        std::fs::write(content_dir.join(".gitignore"), "scratch/\n").unwrap();
+        let ignore: Vec<String> = [".*", "*.conflict.md", "*.swp"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let content_ignore = ContentIgnore::from_dir(&content_dir).unwrap();
+        let mut watcher =
+            FileWatcher::new(&content_dir, &ignore, Some(content_ignore), Duration::ZERO).unwrap();
+
+        std::fs::write(content_dir.join("lists/.groceries.md.swp"), "x").unwrap();
+        std::fs::write(content_dir.join("lists/groceries.conflict.md"), "x").unwrap();
+        std::fs::write(content_dir.join("lists/.git/HEAD"), "x").unwrap();
+        std::fs::create_dir_all(content_dir.join("lists/scratch")).unwrap();
+        std::fs::write(content_dir.join("lists/scratch/idea.md"), "x").unwrap();
+        std::fs::write(content_dir.join("lists/groceries.md"), "- [ ] milk\n").unwrap();
+
+        let paths = collect_paths(&mut watcher, Duration::from_millis(500)).await;
+        assert!(
+            paths.contains(&content_dir.join("lists/groceries.md")),
+            "{:?}",
+            paths
+        );
+        assert!(
+            paths
+                .iter()
+                .all(|path| path == &content_dir.join("lists/groceries.md")),
+            "{:?}",
+            paths
+        );
+    }
+
+    #[tokio::test]
+    async fn test_quick_writes_to_one_file_yield_a_single_event() {
+        let dir = tempfile::tempdir().unwrap();
+        let content_dir = dir.path().canonicalize().unwrap();
+        let window = Duration::from_millis(300);
+        let mut watcher = FileWatcher::new(&content_dir, &[], None, window).unwrap();
+
+        let path = content_dir.join("groceries.md");
+        for content in ["- [ ] milk\n", "- [ ] milk\n- [ ] eggs\n", "- [x] milk\n"] {
+            std::fs::write(&path, content).unwrap();
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        let last_write = Instant::now();
+
+        let event = tokio::time::timeout(Duration::from_secs(5), watcher.next_event())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.paths, vec![path]);
+        assert!(last_write.elapsed() >= window, "emitted before the window");
+        assert!(
+            collect_paths(&mut watcher, window * 2).await.is_empty(),
+            "writes were not coalesced"
+        );
+    }
 }